@@ -0,0 +1,135 @@
+//! Serves objects from a directory through the `ObjectSource` interface.
+//!
+//! The same interface can be implemented for remote object storage services, such as S3 or GCS,
+//! by replacing the filesystem calls with requests to the storage API.
+
+use std::{
+    fs,
+    io::{self, Read as _, Seek as _},
+    path::PathBuf,
+    time::UNIX_EPOCH,
+};
+
+use actix_files::{ObjectMetadata, ObjectResponder, ObjectSource, ObjectStream};
+use actix_web::{
+    error, get, http::header::EntityTag, middleware, web, App, Error, HttpServer, Responder,
+};
+use futures_core::future::LocalBoxFuture;
+use futures_util::{stream, StreamExt as _};
+
+const EXAMPLES_DIR: &str = concat![env!("CARGO_MANIFEST_DIR"), "/examples"];
+
+/// Maximum number of bytes read per chunk.
+const CHUNK_SIZE: u64 = 16_384;
+
+/// An object stored as a file in a directory "bucket".
+struct FsObject {
+    path: PathBuf,
+}
+
+impl ObjectSource for FsObject {
+    fn metadata(&self) -> LocalBoxFuture<'_, Result<ObjectMetadata, Error>> {
+        let path = self.path.clone();
+
+        Box::pin(async move {
+            let md = web::block(move || fs::metadata(path)).await??;
+
+            if !md.is_file() {
+                return Err(error::ErrorNotFound("object not found"));
+            }
+
+            let mut metadata = ObjectMetadata::new(md.len());
+
+            if let Ok(modified) = md.modified() {
+                let mtime = modified.duration_since(UNIX_EPOCH).unwrap_or_default();
+                let etag = format!("{:x}-{:x}", md.len(), mtime.as_secs());
+
+                metadata = metadata
+                    .etag(EntityTag::new_strong(etag))
+                    .last_modified(modified.into());
+            }
+
+            if let Some(ext) = self.path.extension().and_then(|ext| ext.to_str()) {
+                metadata = metadata.content_type(actix_files::file_extension_to_mime(ext));
+            }
+
+            Ok(metadata)
+        })
+    }
+
+    fn range_stream(
+        &self,
+        offset: u64,
+        length: u64,
+    ) -> LocalBoxFuture<'static, Result<ObjectStream, Error>> {
+        let path = self.path.clone();
+
+        Box::pin(async move {
+            let file = web::block(move || fs::File::open(path)).await??;
+
+            let chunks = stream::try_unfold(
+                (file, offset, length),
+                |(mut file, offset, remaining)| async move {
+                    if remaining == 0 {
+                        return Ok(None);
+                    }
+
+                    let (file, chunk) = web::block(move || {
+                        let mut buf = Vec::new();
+                        file.seek(io::SeekFrom::Start(offset))?;
+                        file.by_ref()
+                            .take(remaining.min(CHUNK_SIZE))
+                            .read_to_end(&mut buf)?;
+
+                        if buf.is_empty() {
+                            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+                        }
+
+                        Ok::<_, io::Error>((file, buf))
+                    })
+                    .await??;
+
+                    let n_bytes = chunk.len() as u64;
+                    Ok::<_, Error>(Some((
+                        web::Bytes::from(chunk),
+                        (file, offset + n_bytes, remaining - n_bytes),
+                    )))
+                },
+            );
+
+            Ok(chunks.boxed_local())
+        })
+    }
+}
+
+#[get("/objects/{key}")]
+async fn object(key: web::Path<String>) -> Result<impl Responder, Error> {
+    let key = key.into_inner();
+
+    // keys are plain file names; reject anything that could escape the bucket directory
+    if key.contains(['/', '\\']) || key.starts_with('.') {
+        return Err(error::ErrorBadRequest("invalid object key"));
+    }
+
+    let path = PathBuf::from(EXAMPLES_DIR).join(key);
+
+    ObjectResponder::new(FsObject { path }).await
+}
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+
+    log::info!("starting HTTP server at http://localhost:8080");
+    log::info!("try: curl -r 0-99 http://localhost:8080/objects/object-source.rs");
+
+    HttpServer::new(|| {
+        App::new()
+            .service(object)
+            .wrap(middleware::Logger::default())
+    })
+    .bind(("127.0.0.1", 8080))?
+    .workers(2)
+    .run()
+    .await
+}
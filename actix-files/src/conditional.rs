@@ -0,0 +1,118 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use actix_web::{http::header, HttpMessage as _, HttpRequest};
+
+/// Outcome of evaluating a request's conditional headers against a representation.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Preconditions {
+    /// An `If-Match` or `If-Unmodified-Since` condition failed.
+    pub(crate) failed: bool,
+
+    /// An `If-None-Match` or `If-Modified-Since` condition indicates the client copy is current.
+    pub(crate) not_modified: bool,
+}
+
+impl Preconditions {
+    /// Evaluates the conditional request headers in `req` against the given validators.
+    pub(crate) fn evaluate(
+        req: &HttpRequest,
+        etag: Option<&header::EntityTag>,
+        last_modified: Option<&header::HttpDate>,
+    ) -> Self {
+        let failed = if !any_match(etag, req) {
+            true
+        } else if let (Some(m), Some(header::IfUnmodifiedSince(ref since))) =
+            (last_modified, req.get_header())
+        {
+            let t1: SystemTime = (*m).into();
+            let t2: SystemTime = (*since).into();
+
+            match (t1.duration_since(UNIX_EPOCH), t2.duration_since(UNIX_EPOCH)) {
+                (Ok(t1), Ok(t2)) => t1.as_secs() > t2.as_secs(),
+                _ => false,
+            }
+        } else {
+            false
+        };
+
+        let not_modified = if !none_match(etag, req) {
+            true
+        } else if req.headers().contains_key(header::IF_NONE_MATCH) {
+            false
+        } else if let (Some(m), Some(header::IfModifiedSince(ref since))) =
+            (last_modified, req.get_header())
+        {
+            let t1: SystemTime = (*m).into();
+            let t2: SystemTime = (*since).into();
+
+            match (t1.duration_since(UNIX_EPOCH), t2.duration_since(UNIX_EPOCH)) {
+                (Ok(t1), Ok(t2)) => t1.as_secs() <= t2.as_secs(),
+                _ => false,
+            }
+        } else {
+            false
+        };
+
+        Self {
+            failed,
+            not_modified,
+        }
+    }
+}
+
+/// Returns true if `req` has no `If-Range` header or one which matches the given validators.
+///
+/// Only strong entity tags are considered a match, as required for range requests.
+pub(crate) fn if_range_match(
+    etag: Option<&header::EntityTag>,
+    last_modified: Option<&header::HttpDate>,
+    req: &HttpRequest,
+) -> bool {
+    match req.get_header::<header::IfRange>() {
+        None => true,
+
+        Some(header::IfRange::EntityTag(ref tag)) => etag.is_some_and(|etag| etag.strong_eq(tag)),
+
+        Some(header::IfRange::Date(ref date)) => last_modified.is_some_and(|m| m == date),
+    }
+}
+
+/// Returns true if `req` has no `If-Match` header or one which matches `etag`.
+fn any_match(etag: Option<&header::EntityTag>, req: &HttpRequest) -> bool {
+    match req.get_header::<header::IfMatch>() {
+        None | Some(header::IfMatch::Any) => true,
+
+        Some(header::IfMatch::Items(ref items)) => {
+            if let Some(some_etag) = etag {
+                for item in items {
+                    if item.strong_eq(some_etag) {
+                        return true;
+                    }
+                }
+            }
+
+            false
+        }
+    }
+}
+
+/// Returns true if `req` doesn't have an `If-None-Match` header matching `req`.
+fn none_match(etag: Option<&header::EntityTag>, req: &HttpRequest) -> bool {
+    match req.get_header::<header::IfNoneMatch>() {
+        Some(header::IfNoneMatch::Any) => false,
+
+        Some(header::IfNoneMatch::Items(ref items)) => {
+            if let Some(some_etag) = etag {
+                for item in items {
+                    if item.weak_eq(some_etag) {
+                        return false;
+                    }
+                }
+            }
+
+            true
+        }
+
+        None => true,
+    }
+}
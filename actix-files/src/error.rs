@@ -21,24 +21,80 @@ impl ResponseError for FilesError {
     }
 }
 
+/// Errors which can occur when a request path is rejected by a [`PathBufConfig`].
+///
+/// Variants that relate to a single path segment report the (percent-decoded) segment that broke
+/// the rule.
+///
+/// [`PathBufConfig`]: crate::PathBufConfig
 #[derive(Debug, PartialEq, Eq, Display)]
 #[non_exhaustive]
 pub enum UriSegmentError {
-    /// Segment started with the wrapped invalid character.
-    #[display("segment started with invalid character: ('{_0}')")]
-    BadStart(char),
+    /// Segment started with an invalid character.
+    #[display("segment `{segment}` started with invalid character ('{character}')")]
+    BadStart {
+        /// The offending segment.
+        segment: String,
 
-    /// Segment contained the wrapped invalid character.
-    #[display("segment contained invalid character ('{_0}')")]
-    BadChar(char),
+        /// The invalid character.
+        character: char,
+    },
 
-    /// Segment ended with the wrapped invalid character.
-    #[display("segment ended with invalid character: ('{_0}')")]
-    BadEnd(char),
+    /// Segment contained an invalid character.
+    #[display("segment `{segment}` contained invalid character ('{character}')")]
+    BadChar {
+        /// The offending segment.
+        segment: String,
+
+        /// The invalid character.
+        character: char,
+    },
+
+    /// Segment ended with an invalid character.
+    #[display("segment `{segment}` ended with invalid character ('{character}')")]
+    BadEnd {
+        /// The offending segment.
+        segment: String,
+
+        /// The invalid character.
+        character: char,
+    },
 
     /// Path is not a valid UTF-8 string after percent-decoding.
     #[display("path is not a valid UTF-8 string after percent-decoding")]
     NotValidUtf8,
+
+    /// Segment names a hidden file or directory, which are not allowed.
+    #[display("segment `{segment}` is a hidden file or directory")]
+    HiddenSegment {
+        /// The offending segment.
+        segment: String,
+    },
+
+    /// Path contained a `..` segment, which are not allowed.
+    #[display("path contained a parent directory (`..`) segment")]
+    ParentSegment,
+
+    /// Final segment does not have one of the allowed file extensions.
+    #[display("segment `{segment}` does not have an allowed file extension")]
+    ExtensionNotAllowed {
+        /// The offending segment.
+        segment: String,
+    },
+}
+
+impl UriSegmentError {
+    /// Returns the path segment that caused this error, if it relates to a single segment.
+    pub fn segment(&self) -> Option<&str> {
+        match self {
+            Self::BadStart { segment, .. }
+            | Self::BadChar { segment, .. }
+            | Self::BadEnd { segment, .. }
+            | Self::HiddenSegment { segment }
+            | Self::ExtensionNotAllowed { segment } => Some(segment),
+            Self::NotValidUtf8 | Self::ParentSegment => None,
+        }
+    }
 }
 
 impl ResponseError for UriSegmentError {
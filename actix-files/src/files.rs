@@ -20,7 +20,7 @@ use futures_core::future::LocalBoxFuture;
 use crate::{
     directory_listing, named,
     service::{FilesService, FilesServiceInner},
-    Directory, DirectoryRenderer, HttpNewService, MimeOverride, PathFilter,
+    Directory, DirectoryRenderer, HttpNewService, MimeOverride, PathBufConfig, PathFilter,
 };
 
 /// Static files handling service.
@@ -48,7 +48,7 @@ pub struct Files {
     file_flags: named::Flags,
     use_guards: Option<Rc<dyn Guard>>,
     guards: Vec<Rc<dyn Guard>>,
-    hidden_files: bool,
+    path_config: PathBufConfig,
 }
 
 impl fmt::Debug for Files {
@@ -72,7 +72,7 @@ impl Clone for Files {
             path_filter: self.path_filter.clone(),
             use_guards: self.use_guards.clone(),
             guards: self.guards.clone(),
-            hidden_files: self.hidden_files,
+            path_config: self.path_config.clone(),
         }
     }
 }
@@ -118,7 +118,7 @@ impl Files {
             file_flags: named::Flags::default(),
             use_guards: None,
             guards: Vec::new(),
-            hidden_files: false,
+            path_config: PathBufConfig::default(),
         }
     }
 
@@ -228,6 +228,18 @@ impl Files {
         self
     }
 
+    /// Specifies whether to serve pre-compressed variants of files.
+    ///
+    /// When enabled, Brotli (`.br`) and Gzip (`.gz`) compressed siblings of requested files are
+    /// served to clients that accept them. See
+    /// [`NamedFile::open_precompressed_async()`](crate::NamedFile::open_precompressed_async()).
+    ///
+    /// Default is false.
+    pub fn use_precompressed(mut self, value: bool) -> Self {
+        self.file_flags.set(named::Flags::PRECOMPRESSED, value);
+        self
+    }
+
     /// Adds a routing guard.
     ///
     /// Use this to allow multiple chained file services that respond to strictly different
@@ -312,8 +324,19 @@ impl Files {
     }
 
     /// Enables serving hidden files and directories, allowing a leading dots in url fragments.
+    ///
+    /// This is a shortcut for enabling [`PathBufConfig::hidden_files()`].
     pub fn use_hidden_files(mut self) -> Self {
-        self.hidden_files = true;
+        self.path_config = std::mem::take(&mut self.path_config).hidden_files(true);
+        self
+    }
+
+    /// Sets the rules for which request paths may be mapped to files on disk.
+    ///
+    /// Requests for paths that break the rules receive a `400 Bad Request` response. See
+    /// [`PathBufConfig`] for the available rules and their defaults.
+    pub fn path_config(mut self, config: PathBufConfig) -> Self {
+        self.path_config = config;
         self
     }
 }
@@ -366,7 +389,7 @@ impl ServiceFactory<ServiceRequest> for Files {
             path_filter: self.path_filter.clone(),
             file_flags: self.file_flags,
             guards: self.use_guards.clone(),
-            hidden_files: self.hidden_files,
+            path_config: self.path_config.clone(),
         };
 
         if let Some(ref default) = *self.default.borrow() {
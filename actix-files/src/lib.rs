@@ -27,18 +27,31 @@ use actix_web::{
 use mime_guess::from_ext;
 
 mod chunked;
+mod conditional;
 mod directory;
 mod encoding;
 mod error;
 mod files;
+mod live_reload;
 mod named;
+mod object;
 mod path_buf;
 mod range;
+mod ranged;
 mod service;
 
 pub use self::{
-    chunked::ChunkedReadFile, directory::Directory, files::Files, named::NamedFile,
-    range::HttpRange, service::FilesService,
+    chunked::ChunkedReadFile,
+    directory::Directory,
+    error::UriSegmentError,
+    files::Files,
+    live_reload::{LiveReload, ScriptInjector},
+    named::NamedFile,
+    object::{ObjectMetadata, ObjectResponder, ObjectSource, ObjectStream},
+    path_buf::PathBufConfig,
+    range::HttpRange,
+    ranged::RangedContent,
+    service::FilesService,
 };
 use self::{
     directory::{directory_listing, DirectoryRenderer},
@@ -678,6 +691,65 @@ mod tests {
         );
     }
 
+    #[actix_rt::test]
+    async fn test_files_precompressed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(temp_dir.path().join("app.js"), "plain").unwrap();
+        fs::write(temp_dir.path().join("app.js.gz"), "gzipped").unwrap();
+        fs::write(temp_dir.path().join("app.js.br"), "brotli").unwrap();
+        fs::write(temp_dir.path().join("other.js"), "other").unwrap();
+
+        let srv = test::init_service(
+            App::new()
+                .wrap(Compress::default())
+                .service(Files::new("/", temp_dir.path()).use_precompressed(true)),
+        )
+        .await;
+
+        for (accept_encoding, encoding, body) in [
+            (Some("gzip"), Some("gzip"), "gzipped"),
+            (Some("gzip;q=0.5, br"), Some("br"), "brotli"),
+            (Some("identity"), None, "plain"),
+            (None, None, "plain"),
+        ] {
+            let mut req = TestRequest::get().uri("/app.js");
+            if let Some(accept_encoding) = accept_encoding {
+                req = req.insert_header((header::ACCEPT_ENCODING, accept_encoding));
+            }
+
+            let res = test::call_service(&srv, req.to_request()).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(
+                res.headers().get(header::CONTENT_TYPE).unwrap(),
+                "text/javascript"
+            );
+            assert_eq!(
+                res.headers().get(header::CONTENT_ENCODING),
+                encoding.map(header::HeaderValue::from_static).as_ref(),
+            );
+            assert_eq!(res.headers().get(header::VARY).unwrap(), "Accept-Encoding");
+            assert_eq!(test::read_body(res).await, body);
+        }
+
+        // range requests are served from the uncompressed file
+        let req = TestRequest::get()
+            .uri("/app.js")
+            .insert_header((header::ACCEPT_ENCODING, "gzip"))
+            .insert_header((header::RANGE, "bytes=0-2"))
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(test::read_body(res).await, "pla");
+
+        // files without variants are left to the compression middleware
+        let req = TestRequest::get()
+            .uri("/other.js")
+            .insert_header((header::ACCEPT_ENCODING, "gzip"))
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+    }
+
     #[actix_rt::test]
     async fn test_named_file_allowed_method() {
         let req = TestRequest::default().method(Method::GET).to_http_request();
@@ -1018,6 +1090,36 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::NOT_FOUND);
     }
 
+    #[actix_rt::test]
+    async fn test_path_config() {
+        let st = Files::new("/", ".")
+            .path_config(
+                PathBufConfig::new()
+                    .reject_parent_segments(true)
+                    .allowed_extensions(["toml"]),
+            )
+            .new_service(())
+            .await
+            .unwrap();
+
+        let req = TestRequest::with_uri("/Cargo.toml").to_srv_request();
+        let resp = test::call_service(&st, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/src/lib.rs").to_srv_request();
+        let resp = test::call_service(&st, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body = test::read_body(resp).await;
+        assert_eq!(
+            body,
+            "segment `lib.rs` does not have an allowed file extension"
+        );
+
+        let req = TestRequest::with_uri("/src/../Cargo.toml").to_srv_request();
+        let resp = test::call_service(&st, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[actix_rt::test]
     async fn test_default_handler_filter() {
         let st = Files::new("/", ".")
@@ -0,0 +1,456 @@
+//! Browser live-reload for development.
+
+use std::{
+    convert::Infallible,
+    error::Error as StdError,
+    fs, io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, OnceLock, Weak,
+    },
+    task::{Context, Poll},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Ready};
+use actix_web::{
+    body::{self, BoxBody, EitherBody, MessageBody},
+    dev::{self, AppService, HttpServiceFactory, ServiceRequest, ServiceResponse},
+    error::{Error, ErrorInternalServerError},
+    http::header::{self, ContentType},
+    rt::time::{interval, Interval},
+    web, HttpResponse,
+};
+use bytes::Bytes;
+use futures_core::{future::LocalBoxFuture, Stream};
+
+/// Default path of the event stream endpoint.
+const DEFAULT_PATH: &str = "/__reload";
+
+/// Watches directories for changes and tells connected browsers to reload.
+///
+/// `LiveReload` polls the watched directories (recursively) for added, removed, or modified files
+/// on a background thread. When registered as a service, it serves a [Server-Sent Events] stream
+/// at `/__reload` that emits a `reload` event whenever a change is detected. Pages opt in to
+/// reloading by including the [script](Self::script_tag), either by hand in templates or by
+/// wrapping the app in the [injector middleware](Self::script_injector).
+///
+/// The script also reloads the page when the event stream reconnects, so restarting the server
+/// (e.g., after a rebuild) refreshes open pages, too.
+///
+/// This is intended for local development only and should not be enabled in production builds.
+///
+/// # Examples
+/// ```
+/// use actix_files::{Files, LiveReload};
+/// use actix_web::App;
+///
+/// let reload = LiveReload::new().watch("./templates").watch("./static");
+///
+/// let app = App::new()
+///     .wrap(reload.script_injector())
+///     .service(reload.clone())
+///     .service(Files::new("/static", "./static"));
+/// ```
+///
+/// [Server-Sent Events]: https://developer.mozilla.org/en-US/docs/Web/API/Server-sent_events
+#[derive(Debug, Clone)]
+pub struct LiveReload {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    path: String,
+    dirs: Vec<PathBuf>,
+    poll_interval: Duration,
+    generation: AtomicU64,
+    watcher: OnceLock<()>,
+}
+
+impl LiveReload {
+    /// Constructs new live-reload service that does not watch any directories yet.
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                path: DEFAULT_PATH.to_owned(),
+                dirs: Vec::new(),
+                poll_interval: Duration::from_millis(500),
+                generation: AtomicU64::new(0),
+                watcher: OnceLock::new(),
+            }),
+        }
+    }
+
+    /// Adds a directory to watch for changes.
+    pub fn watch(self, dir: impl Into<PathBuf>) -> Self {
+        self.configure(|inner| inner.dirs.push(dir.into()))
+    }
+
+    /// Sets the path of the event stream endpoint.
+    ///
+    /// By default, the path is `/__reload`.
+    pub fn path(self, path: impl Into<String>) -> Self {
+        self.configure(|inner| inner.path = path.into())
+    }
+
+    /// Sets how often watched directories are scanned for changes.
+    ///
+    /// By default, directories are scanned every 500 milliseconds.
+    pub fn poll_interval(self, interval: Duration) -> Self {
+        self.configure(|inner| inner.poll_interval = interval)
+    }
+
+    fn configure(mut self, f: impl FnOnce(&mut Inner)) -> Self {
+        f(Arc::get_mut(&mut self.inner)
+            .expect("LiveReload can not be configured after it has been cloned"));
+        self
+    }
+
+    /// Returns a `<script>` tag that connects to the event stream and reloads the page on change.
+    pub fn script_tag(&self) -> String {
+        format!(
+            "<script>(function(){{var e=false,s=new EventSource({path:?});\
+             s.addEventListener('reload',function(){{location.reload()}});\
+             s.onerror=function(){{e=true}};\
+             s.onopen=function(){{if(e)location.reload()}}}})();</script>",
+            path = self.inner.path,
+        )
+    }
+
+    /// Returns middleware that injects the [reload script](Self::script_tag) into HTML responses.
+    ///
+    /// The script is inserted before the closing `</body>` tag, or appended if there is none.
+    /// Responses that are not `text/html` or that have a `Content-Encoding` are left untouched.
+    pub fn script_injector(&self) -> ScriptInjector {
+        ScriptInjector {
+            script: Bytes::from(self.script_tag()),
+        }
+    }
+
+    /// Returns number of changes detected so far.
+    fn generation(&self) -> u64 {
+        self.inner.generation.load(Ordering::Acquire)
+    }
+
+    /// Starts the watcher thread, if it is not running yet.
+    fn start_watcher(&self) {
+        self.inner.watcher.get_or_init(|| {
+            let inner = Arc::downgrade(&self.inner);
+            let dirs = self.inner.dirs.clone();
+            let poll_interval = self.inner.poll_interval;
+
+            thread::Builder::new()
+                .name("actix-files live-reload".to_owned())
+                .spawn(move || watch_dirs(inner, dirs, poll_interval))
+                .expect("can not spawn live-reload watcher thread");
+        });
+    }
+
+    fn events(&self) -> HttpResponse {
+        HttpResponse::Ok()
+            .content_type(ContentType(mime::TEXT_EVENT_STREAM))
+            .insert_header((header::CACHE_CONTROL, "no-cache"))
+            .streaming(ReloadEvents {
+                seen: self.generation(),
+                reload: self.clone(),
+                interval: interval(self.inner.poll_interval),
+                connected: false,
+            })
+    }
+}
+
+impl Default for LiveReload {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpServiceFactory for LiveReload {
+    fn register(self, config: &mut AppService) {
+        self.start_watcher();
+
+        let path = self.inner.path.clone();
+
+        web::resource(path)
+            .route(web::get().to(move || {
+                let events = self.events();
+                async move { events }
+            }))
+            .register(config);
+    }
+}
+
+/// Scans `dirs` every `poll_interval` until the `LiveReload` service is dropped.
+fn watch_dirs(inner: Weak<Inner>, dirs: Vec<PathBuf>, poll_interval: Duration) {
+    let mut last = snapshot(&dirs);
+
+    loop {
+        thread::sleep(poll_interval);
+
+        let Some(inner) = inner.upgrade() else {
+            return;
+        };
+
+        let current = snapshot(&dirs);
+
+        if current != last {
+            log::debug!("change detected in watched directories; reloading");
+            inner.generation.fetch_add(1, Ordering::AcqRel);
+            last = current;
+        }
+    }
+}
+
+type Snapshot = Vec<(PathBuf, Option<SystemTime>, u64)>;
+
+/// Lists all files under `dirs` along with their modification time and length.
+fn snapshot(dirs: &[PathBuf]) -> Snapshot {
+    let mut files = Vec::new();
+
+    for dir in dirs {
+        if let Err(err) = visit(dir, &mut files) {
+            log::debug!("can not scan {}: {}", dir.display(), err);
+        }
+    }
+
+    files.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    files
+}
+
+fn visit(dir: &Path, files: &mut Snapshot) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+
+        if meta.is_dir() {
+            visit(&entry.path(), files)?;
+        } else {
+            files.push((entry.path(), meta.modified().ok(), meta.len()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Event stream sent to each connected browser.
+struct ReloadEvents {
+    reload: LiveReload,
+    seen: u64,
+    interval: Interval,
+    connected: bool,
+}
+
+impl Stream for ReloadEvents {
+    type Item = Result<Bytes, Infallible>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        // send something immediately so the browser sees the stream as open
+        if !this.connected {
+            this.connected = true;
+            return Poll::Ready(Some(Ok(Bytes::from_static(b": connected\n\n"))));
+        }
+
+        loop {
+            std::task::ready!(this.interval.poll_tick(cx));
+
+            let generation = this.reload.generation();
+
+            if generation != this.seen {
+                this.seen = generation;
+                return Poll::Ready(Some(Ok(Bytes::from_static(
+                    b"event: reload\ndata: reload\n\n",
+                ))));
+            }
+        }
+    }
+}
+
+/// Middleware that injects the live-reload script into HTML responses.
+///
+/// Created by [`LiveReload::script_injector`].
+#[derive(Debug, Clone)]
+pub struct ScriptInjector {
+    script: Bytes,
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ScriptInjector
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = ScriptInjectorMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ScriptInjectorMiddleware {
+            service,
+            script: self.script.clone(),
+        }))
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ScriptInjectorMiddleware<S> {
+    service: S,
+    script: Bytes,
+}
+
+impl<S, B> Service<ServiceRequest> for ScriptInjectorMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let fut = self.service.call(req);
+        let script = self.script.clone();
+
+        Box::pin(async move {
+            let res = fut.await?;
+
+            if !is_plain_html(&res) {
+                return Ok(res.map_into_left_body());
+            }
+
+            let (req, res) = res.into_parts();
+            let (res, body) = res.into_parts();
+
+            let html = body::to_bytes(body)
+                .await
+                .map_err(|err| ErrorInternalServerError(err.into() as Box<dyn StdError>))?;
+
+            let res = res.set_body(BoxBody::new(inject_script(&html, &script)));
+            Ok(ServiceResponse::new(req, res).map_into_right_body())
+        })
+    }
+}
+
+/// Returns true if response body is uncompressed HTML.
+fn is_plain_html<B>(res: &ServiceResponse<B>) -> bool {
+    let headers = res.headers();
+
+    !headers.contains_key(header::CONTENT_ENCODING)
+        && headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|ct| ct.to_str().ok())
+            .and_then(|ct| ct.parse::<mime::Mime>().ok())
+            .is_some_and(|ct| ct.essence_str() == mime::TEXT_HTML.essence_str())
+}
+
+/// Inserts `script` before the last `</body>` tag in `html`, or appends it if there is none.
+fn inject_script(html: &[u8], script: &[u8]) -> Bytes {
+    let pos = html
+        .windows(7)
+        .rposition(|window| window.eq_ignore_ascii_case(b"</body>"))
+        .unwrap_or(html.len());
+
+    let mut buf = Vec::with_capacity(html.len() + script.len());
+    buf.extend_from_slice(&html[..pos]);
+    buf.extend_from_slice(script);
+    buf.extend_from_slice(&html[pos..]);
+    Bytes::from(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        test::{self, TestRequest},
+        App,
+    };
+
+    use super::*;
+
+    #[test]
+    fn injects_before_body_end() {
+        assert_eq!(
+            inject_script(b"<html><body>hi</BODY></html>", b"<script></script>"),
+            "<html><body>hi<script></script></BODY></html>"
+        );
+        assert_eq!(
+            inject_script(b"hi", b"<script></script>"),
+            "hi<script></script>"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn injector_only_touches_html() {
+        let reload = LiveReload::new();
+
+        let app = test::init_service(
+            App::new()
+                .wrap(reload.script_injector())
+                .route(
+                    "/html",
+                    web::to(|| async {
+                        HttpResponse::Ok()
+                            .content_type(ContentType::html())
+                            .body("<body></body>")
+                    }),
+                )
+                .route("/text", web::to(|| async { "<body></body>" })),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/html").to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, format!("<body>{}</body>", reload.script_tag()));
+
+        let req = TestRequest::with_uri("/text").to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "<body></body>");
+    }
+
+    #[actix_rt::test]
+    async fn sends_reload_event_on_change() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("index.html"), "old").unwrap();
+
+        let reload = LiveReload::new()
+            .watch(dir.path())
+            .poll_interval(Duration::from_millis(10));
+
+        let app = test::init_service(App::new().service(reload)).await;
+
+        let req = TestRequest::with_uri("/__reload").to_request();
+        let res = test::call_service(&app, req).await;
+        assert!(res.status().is_success());
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+
+        let mut body = std::pin::pin!(res.into_body());
+        let chunk = std::future::poll_fn(|cx| body.as_mut().poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunk, ": connected\n\n");
+
+        actix_rt::time::sleep(Duration::from_millis(50)).await;
+        fs::write(dir.path().join("new.css"), "body {}").unwrap();
+
+        let chunk = std::future::poll_fn(|cx| body.as_mut().poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunk, "event: reload\ndata: reload\n\n");
+    }
+}
@@ -13,19 +13,19 @@ use actix_web::{
     },
     http::{
         header::{
-            self, Charset, ContentDisposition, ContentEncoding, DispositionParam, DispositionType,
-            ExtendedValue, HeaderValue,
+            self, AcceptEncoding, Charset, ContentDisposition, ContentEncoding, DispositionParam,
+            DispositionType, Encoding, ExtendedValue, HeaderValue,
         },
         StatusCode,
     },
-    Error, HttpMessage, HttpRequest, HttpResponse, Responder,
+    Error, HttpMessage as _, HttpRequest, HttpResponse, Responder,
 };
 use bitflags::bitflags;
 use derive_more::derive::{Deref, DerefMut};
 use futures_core::future::LocalBoxFuture;
 use mime::Mime;
 
-use crate::{encoding::equiv_utf8_text, range::HttpRange};
+use crate::{conditional::Preconditions, encoding::equiv_utf8_text, range::HttpRange};
 
 bitflags! {
     #[derive(Debug, Clone, Copy)]
@@ -34,6 +34,7 @@ bitflags! {
         const LAST_MD =             0b0000_0010;
         const CONTENT_DISPOSITION = 0b0000_0100;
         const PREFER_UTF8 =         0b0000_1000;
+        const PRECOMPRESSED =       0b0001_0000;
     }
 }
 
@@ -80,8 +81,15 @@ pub struct NamedFile {
     pub(crate) content_type: Mime,
     pub(crate) content_disposition: ContentDisposition,
     pub(crate) encoding: Option<ContentEncoding>,
+    pub(crate) has_encoded_variants: bool,
 }
 
+/// Encodings of pre-compressed file variants and the extensions they are stored with.
+const PRECOMPRESSED_VARIANTS: &[(ContentEncoding, &str)] = &[
+    (ContentEncoding::Brotli, ".br"),
+    (ContentEncoding::Gzip, ".gz"),
+];
+
 #[cfg(not(feature = "experimental-io-uring"))]
 pub(crate) use std::fs::File;
 
@@ -198,6 +206,7 @@ impl NamedFile {
             md,
             modified,
             encoding,
+            has_encoded_variants: false,
             status_code: StatusCode::OK,
             flags: Flags::default(),
         })
@@ -229,19 +238,90 @@ impl NamedFile {
     /// # }
     /// ```
     pub async fn open_async<P: AsRef<Path>>(path: P) -> io::Result<NamedFile> {
-        let file = {
-            #[cfg(not(feature = "experimental-io-uring"))]
-            {
-                File::open(&path)?
+        let file = open_file(path.as_ref()).await?;
+        Self::from_file(file, path)
+    }
+
+    /// Attempts to open the pre-compressed variant of a file preferred by the client, falling back
+    /// to the file itself.
+    ///
+    /// Brotli (`.br`) and Gzip (`.gz`) compressed siblings of `path` (e.g., `app.js.br` for
+    /// `app.js`) are negotiated against the `Accept-Encoding` header of `req`. A selected variant is
+    /// served with the matching `Content-Encoding` header, which also prevents the `Compress`
+    /// middleware from compressing it again, and with the `Content-Type` and `Content-Disposition`
+    /// of the uncompressed file. Responses for files that have pre-compressed siblings include a
+    /// `Vary: Accept-Encoding` header.
+    ///
+    /// The uncompressed file is always used for range requests.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_files::NamedFile;
+    /// use actix_web::{HttpRequest, Responder};
+    ///
+    /// async fn index(req: HttpRequest) -> impl Responder {
+    ///     NamedFile::open_precompressed_async("./static/app.js", &req).await
+    /// }
+    /// ```
+    pub async fn open_precompressed_async<P: AsRef<Path>>(
+        path: P,
+        req: &HttpRequest,
+    ) -> io::Result<NamedFile> {
+        let path = path.as_ref();
+
+        let variants = PRECOMPRESSED_VARIANTS
+            .iter()
+            .filter_map(|&(enc, ext)| {
+                let mut variant = path.as_os_str().to_owned();
+                variant.push(ext);
+                let variant = PathBuf::from(variant);
+
+                variant.is_file().then_some((enc, variant))
+            })
+            .collect::<Vec<_>>();
+
+        if variants.is_empty() {
+            return Self::open_async(path).await;
+        }
+
+        let accept_encoding = if req.headers().contains_key(header::RANGE) {
+            None
+        } else {
+            req.get_header::<AcceptEncoding>()
+        };
+
+        let selected = accept_encoding.and_then(|accept_encoding| {
+            let supported = variants
+                .iter()
+                .map(|&(enc, _)| Encoding::Known(enc))
+                .chain([Encoding::identity()])
+                .collect::<Vec<_>>();
+
+            match accept_encoding.negotiate(supported.iter()) {
+                Some(Encoding::Known(enc)) => variants
+                    .into_iter()
+                    .find(|&(variant_enc, _)| variant_enc == enc),
+                _ => None,
             }
+        });
 
-            #[cfg(feature = "experimental-io-uring")]
-            {
-                File::open(&path).await?
+        let mut named_file = match selected {
+            Some((enc, variant)) => {
+                let file = open_file(&variant).await?;
+
+                // name the file after the uncompressed path for Content-Type and -Disposition
+                let mut named_file = Self::from_file(file, path)?;
+                named_file.path = variant;
+                named_file.encoding = Some(enc);
+                named_file
             }
+
+            _ => Self::open_async(path).await?,
         };
 
-        Self::from_file(file, path)
+        named_file.has_encoded_variants = true;
+
+        Ok(named_file)
     }
 
     /// Returns reference to the underlying file object.
@@ -440,6 +520,10 @@ impl NamedFile {
                 res.insert_header((header::CONTENT_ENCODING, current_encoding.as_str()));
             }
 
+            if self.has_encoded_variants {
+                res.insert_header((header::VARY, "Accept-Encoding"));
+            }
+
             let reader = chunked::new_chunked_read(self.md.len(), 0, self.file);
 
             return res.streaming(reader);
@@ -457,41 +541,7 @@ impl NamedFile {
             None
         };
 
-        // check preconditions
-        let precondition_failed = if !any_match(etag.as_ref(), req) {
-            true
-        } else if let (Some(ref m), Some(header::IfUnmodifiedSince(ref since))) =
-            (last_modified, req.get_header())
-        {
-            let t1: SystemTime = (*m).into();
-            let t2: SystemTime = (*since).into();
-
-            match (t1.duration_since(UNIX_EPOCH), t2.duration_since(UNIX_EPOCH)) {
-                (Ok(t1), Ok(t2)) => t1.as_secs() > t2.as_secs(),
-                _ => false,
-            }
-        } else {
-            false
-        };
-
-        // check last modified
-        let not_modified = if !none_match(etag.as_ref(), req) {
-            true
-        } else if req.headers().contains_key(header::IF_NONE_MATCH) {
-            false
-        } else if let (Some(ref m), Some(header::IfModifiedSince(ref since))) =
-            (last_modified, req.get_header())
-        {
-            let t1: SystemTime = (*m).into();
-            let t2: SystemTime = (*since).into();
-
-            match (t1.duration_since(UNIX_EPOCH), t2.duration_since(UNIX_EPOCH)) {
-                (Ok(t1), Ok(t2)) => t1.as_secs() <= t2.as_secs(),
-                _ => false,
-            }
-        } else {
-            false
-        };
+        let preconditions = Preconditions::evaluate(req, etag.as_ref(), last_modified.as_ref());
 
         let mut res = HttpResponse::build(self.status_code);
 
@@ -514,6 +564,10 @@ impl NamedFile {
             res.insert_header((header::CONTENT_ENCODING, current_encoding.as_str()));
         }
 
+        if self.has_encoded_variants {
+            res.insert_header((header::VARY, "Accept-Encoding"));
+        }
+
         if let Some(lm) = last_modified {
             res.insert_header((header::LAST_MODIFIED, lm.to_string()));
         }
@@ -568,9 +622,9 @@ impl NamedFile {
             };
         };
 
-        if precondition_failed {
+        if preconditions.failed {
             return res.status(StatusCode::PRECONDITION_FAILED).finish();
-        } else if not_modified {
+        } else if preconditions.not_modified {
             return res
                 .status(StatusCode::NOT_MODIFIED)
                 .body(body::None::new())
@@ -587,43 +641,16 @@ impl NamedFile {
     }
 }
 
-/// Returns true if `req` has no `If-Match` header or one which matches `etag`.
-fn any_match(etag: Option<&header::EntityTag>, req: &HttpRequest) -> bool {
-    match req.get_header::<header::IfMatch>() {
-        None | Some(header::IfMatch::Any) => true,
-
-        Some(header::IfMatch::Items(ref items)) => {
-            if let Some(some_etag) = etag {
-                for item in items {
-                    if item.strong_eq(some_etag) {
-                        return true;
-                    }
-                }
-            }
-
-            false
-        }
+/// Opens a file in read-only mode.
+async fn open_file(path: &Path) -> io::Result<File> {
+    #[cfg(not(feature = "experimental-io-uring"))]
+    {
+        File::open(path)
     }
-}
-
-/// Returns true if `req` doesn't have an `If-None-Match` header matching `req`.
-fn none_match(etag: Option<&header::EntityTag>, req: &HttpRequest) -> bool {
-    match req.get_header::<header::IfNoneMatch>() {
-        Some(header::IfNoneMatch::Any) => false,
-
-        Some(header::IfNoneMatch::Items(ref items)) => {
-            if let Some(some_etag) = etag {
-                for item in items {
-                    if item.weak_eq(some_etag) {
-                        return false;
-                    }
-                }
-            }
-
-            true
-        }
 
-        None => true,
+    #[cfg(feature = "experimental-io-uring")]
+    {
+        File::open(path).await
     }
 }
 
@@ -0,0 +1,362 @@
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use actix_web::{
+    body::{BoxBody, SizedStream},
+    http::header::{ContentDisposition, EntityTag, HttpDate},
+    Error, HttpRequest, HttpResponse, Responder,
+};
+use bytes::Bytes;
+use futures_core::{
+    future::LocalBoxFuture,
+    ready,
+    stream::{LocalBoxStream, Stream},
+};
+use mime::Mime;
+
+use crate::ranged::{Prepared, Validators};
+
+/// Stream of object content returned by [`ObjectSource::range_stream`].
+pub type ObjectStream = LocalBoxStream<'static, Result<Bytes, Error>>;
+
+/// Metadata describing an object served by an [`ObjectResponder`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ObjectMetadata {
+    /// Total size of the object in bytes.
+    pub size: u64,
+
+    /// Content type of the object. Defaults to `application/octet-stream` when `None`.
+    pub content_type: Option<Mime>,
+
+    /// Entity tag used to validate conditional and resumed requests.
+    pub etag: Option<EntityTag>,
+
+    /// Last modified date used to validate conditional and resumed requests.
+    pub last_modified: Option<HttpDate>,
+}
+
+impl ObjectMetadata {
+    /// Constructs metadata for an object of `size` bytes with no other information.
+    pub fn new(size: u64) -> Self {
+        Self {
+            size,
+            content_type: None,
+            etag: None,
+            last_modified: None,
+        }
+    }
+
+    /// Sets the content type of the object.
+    pub fn content_type(mut self, content_type: Mime) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Sets the entity tag of the object.
+    pub fn etag(mut self, etag: EntityTag) -> Self {
+        self.etag = Some(etag);
+        self
+    }
+
+    /// Sets the last modified date of the object.
+    pub fn last_modified(mut self, last_modified: HttpDate) -> Self {
+        self.last_modified = Some(last_modified);
+        self
+    }
+}
+
+/// A source of objects, such as a bucket in remote object storage.
+///
+/// Implement this trait to serve objects from storage backends (e.g., S3 or GCS) using an
+/// [`ObjectResponder`], which takes care of conditional requests, range requests, and setting the
+/// content type.
+///
+/// The future returned from [`range_stream`](Self::range_stream) must not borrow from `self`
+/// because it is only polled after the response has been sent; clone any (cheap) handles it needs.
+///
+/// See the `object-source` example in this crate's repository for an implementation backed by the
+/// local filesystem.
+pub trait ObjectSource {
+    /// Fetches the object's metadata.
+    fn metadata(&self) -> LocalBoxFuture<'_, Result<ObjectMetadata, Error>>;
+
+    /// Opens a stream of exactly `length` bytes of the object, starting at `offset`.
+    fn range_stream(
+        &self,
+        offset: u64,
+        length: u64,
+    ) -> LocalBoxFuture<'static, Result<ObjectStream, Error>>;
+}
+
+/// Responder that serves an [`ObjectSource`] with support for conditional and range requests.
+///
+/// # Examples
+/// ```
+/// use actix_files::{ObjectMetadata, ObjectResponder, ObjectSource, ObjectStream};
+/// use actix_web::{get, web::Bytes, Error, Responder};
+/// use futures_core::future::LocalBoxFuture;
+///
+/// struct Greeting;
+///
+/// impl ObjectSource for Greeting {
+///     fn metadata(&self) -> LocalBoxFuture<'_, Result<ObjectMetadata, Error>> {
+///         Box::pin(async { Ok(ObjectMetadata::new(5).content_type(mime::TEXT_PLAIN)) })
+///     }
+///
+///     fn range_stream(
+///         &self,
+///         offset: u64,
+///         length: u64,
+///     ) -> LocalBoxFuture<'static, Result<ObjectStream, Error>> {
+///         let (start, end) = (offset as usize, (offset + length) as usize);
+///         let chunk = Bytes::from_static(&b"hello"[start..end]);
+///
+///         Box::pin(async move {
+///             let stream = futures_util::stream::once(async move { Ok(chunk) });
+///             Ok(Box::pin(stream) as ObjectStream)
+///         })
+///     }
+/// }
+///
+/// #[get("/greeting")]
+/// async fn greeting() -> Result<impl Responder, Error> {
+///     ObjectResponder::new(Greeting).await
+/// }
+/// ```
+pub struct ObjectResponder<S> {
+    source: S,
+    metadata: ObjectMetadata,
+    content_disposition: Option<ContentDisposition>,
+}
+
+impl<S> fmt::Debug for ObjectResponder<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObjectResponder")
+            .field("metadata", &self.metadata)
+            .field("content_disposition", &self.content_disposition)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: ObjectSource> ObjectResponder<S> {
+    /// Fetches the source's metadata and constructs a responder for it.
+    pub async fn new(source: S) -> Result<Self, Error> {
+        let metadata = source.metadata().await?;
+        Ok(Self::with_metadata(source, metadata))
+    }
+
+    /// Constructs a responder using already known metadata.
+    pub fn with_metadata(source: S, metadata: ObjectMetadata) -> Self {
+        Self {
+            source,
+            metadata,
+            content_disposition: None,
+        }
+    }
+
+    /// Returns the object's metadata.
+    pub fn metadata(&self) -> &ObjectMetadata {
+        &self.metadata
+    }
+
+    /// Sets the `Content-Disposition` of the response.
+    pub fn set_content_disposition(mut self, content_disposition: ContentDisposition) -> Self {
+        self.content_disposition = Some(content_disposition);
+        self
+    }
+
+    /// Creates an `HttpResponse` with the requested range of the object as a streaming body.
+    pub fn into_response(self, req: &HttpRequest) -> HttpResponse<BoxBody> {
+        let content_type = self
+            .metadata
+            .content_type
+            .clone()
+            .unwrap_or(mime::APPLICATION_OCTET_STREAM);
+
+        let validators = Validators {
+            size: self.metadata.size,
+            content_type: &content_type,
+            content_disposition: self.content_disposition.as_ref(),
+            etag: self.metadata.etag.as_ref(),
+            last_modified: self.metadata.last_modified.as_ref(),
+        };
+
+        match validators.prepare(req) {
+            Prepared::Done(res) => res,
+            Prepared::Stream {
+                mut res,
+                offset,
+                length,
+            } => {
+                let stream = LazyObjectStream::Opening {
+                    fut: self.source.range_stream(offset, length),
+                };
+
+                res.body(SizedStream::new(length, stream))
+            }
+        }
+    }
+}
+
+impl<S: ObjectSource> Responder for ObjectResponder<S> {
+    type Body = BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        self.into_response(req)
+    }
+}
+
+/// Stream that opens the object stream on first poll.
+enum LazyObjectStream {
+    Opening {
+        fut: LocalBoxFuture<'static, Result<ObjectStream, Error>>,
+    },
+    Streaming {
+        stream: ObjectStream,
+    },
+    Done,
+}
+
+impl Stream for LazyObjectStream {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            match this {
+                LazyObjectStream::Opening { fut } => match ready!(fut.as_mut().poll(cx)) {
+                    Ok(stream) => *this = LazyObjectStream::Streaming { stream },
+                    Err(err) => {
+                        *this = LazyObjectStream::Done;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                },
+
+                LazyObjectStream::Streaming { stream } => {
+                    return stream.as_mut().poll_next(cx);
+                }
+
+                LazyObjectStream::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::{
+            header::{self, HeaderValue},
+            StatusCode,
+        },
+        test::{self, TestRequest},
+    };
+
+    use super::*;
+
+    const DATA: &[u8] = b"0123456789";
+
+    struct Static;
+
+    impl ObjectSource for Static {
+        fn metadata(&self) -> LocalBoxFuture<'_, Result<ObjectMetadata, Error>> {
+            Box::pin(async {
+                Ok(ObjectMetadata::new(DATA.len() as u64)
+                    .content_type(mime::TEXT_PLAIN)
+                    .etag(EntityTag::new_strong("abc".to_owned())))
+            })
+        }
+
+        fn range_stream(
+            &self,
+            offset: u64,
+            length: u64,
+        ) -> LocalBoxFuture<'static, Result<ObjectStream, Error>> {
+            let chunk = Bytes::from_static(&DATA[offset as usize..(offset + length) as usize]);
+
+            Box::pin(async move {
+                let stream = Chunks(vec![
+                    chunk.slice(..chunk.len() / 2),
+                    chunk.slice(chunk.len() / 2..),
+                ]);
+                Ok(Box::pin(stream) as ObjectStream)
+            })
+        }
+    }
+
+    struct Chunks(Vec<Bytes>);
+
+    impl Stream for Chunks {
+        type Item = Result<Bytes, Error>;
+
+        fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            if self.0.is_empty() {
+                Poll::Ready(None)
+            } else {
+                Poll::Ready(Some(Ok(self.0.remove(0))))
+            }
+        }
+    }
+
+    #[actix_rt::test]
+    async fn serves_full_object() {
+        let req = TestRequest::default().to_http_request();
+        let res = ObjectResponder::new(Static)
+            .await
+            .unwrap()
+            .into_response(&req);
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("text/plain")
+        );
+        let body = test::read_body(TestRequest::default().to_srv_response(res)).await;
+        assert_eq!(body, DATA);
+    }
+
+    #[actix_rt::test]
+    async fn serves_range() {
+        let req = TestRequest::default()
+            .insert_header((header::RANGE, "bytes=2-5"))
+            .to_http_request();
+        let res = ObjectResponder::new(Static)
+            .await
+            .unwrap()
+            .into_response(&req);
+
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        let body = test::read_body(TestRequest::default().to_srv_response(res)).await;
+        assert_eq!(body, &DATA[2..6]);
+    }
+
+    #[actix_rt::test]
+    async fn conditional_request() {
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, "\"abc\""))
+            .to_http_request();
+        let res = ObjectResponder::new(Static)
+            .await
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[actix_rt::test]
+    async fn default_content_type() {
+        let req = TestRequest::default().to_http_request();
+        let res = ObjectResponder::with_metadata(Static, ObjectMetadata::new(DATA.len() as u64))
+            .into_response(&req);
+
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("application/octet-stream")
+        );
+        assert!(res.headers().get(header::ETAG).is_none());
+    }
+}
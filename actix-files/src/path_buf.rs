@@ -8,6 +8,95 @@ use actix_web::{dev::Payload, FromRequest, HttpRequest};
 
 use crate::error::UriSegmentError;
 
+/// Rules for turning the tail of a request path into a path on disk, used by [`Files`].
+///
+/// Paths are always percent-decoded and normalized so that they cannot traverse outside of the
+/// served directory; segments that are unsafe on common filesystems (e.g., starting with `*` or
+/// ending with `:`) are always rejected. On top of that, the config controls whether:
+/// - hidden files and directories (segments starting with `.`) may be served;
+/// - parent directory (`..`) segments are resolved or rejected;
+/// - only files with certain extensions may be served.
+///
+/// Rejected paths result in a [`UriSegmentError`], which responds with `400 Bad Request`.
+///
+/// # Examples
+/// ```
+/// use actix_files::{Files, PathBufConfig};
+///
+/// let files = Files::new("/static", ".").path_config(
+///     PathBufConfig::new()
+///         .reject_parent_segments(true)
+///         .allowed_extensions(["css", "js", "png"]),
+/// );
+/// ```
+///
+/// [`Files`]: crate::Files
+#[derive(Debug, Clone, Default)]
+pub struct PathBufConfig {
+    hidden_files: bool,
+    reject_parent_segments: bool,
+    allowed_extensions: Option<Vec<String>>,
+}
+
+impl PathBufConfig {
+    /// Constructs a config that rejects hidden files and resolves `..` segments.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether hidden files and directories may be served. Disabled by default.
+    pub fn hidden_files(mut self, allow: bool) -> Self {
+        self.hidden_files = allow;
+        self
+    }
+
+    /// Sets whether paths containing `..` segments are rejected.
+    ///
+    /// By default, `..` segments remove the preceding segment, without ever leaving the served
+    /// directory. Since this happens before the path is resolved on disk, `link/..` resolves to
+    /// the served directory even if `link` is a symbolic link to somewhere else; rejecting such
+    /// segments avoids that ambiguity.
+    pub fn reject_parent_segments(mut self, reject: bool) -> Self {
+        self.reject_parent_segments = reject;
+        self
+    }
+
+    /// Restricts served files to those with one of the given extensions, compared
+    /// case-insensitively.
+    ///
+    /// Only the final segment is checked. Requests for directories, which end with a slash, are
+    /// not affected.
+    pub fn allowed_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let extensions = extensions
+            .into_iter()
+            .map(|ext| ext.as_ref().trim_start_matches('.').to_owned())
+            .collect();
+
+        self.allowed_extensions = Some(extensions);
+        self
+    }
+
+    /// Returns true if the file name in `segment` has an allowed extension.
+    fn extension_allowed(&self, segment: &str) -> bool {
+        let Some(allowed) = &self.allowed_extensions else {
+            return true;
+        };
+
+        Path::new(segment)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                allowed
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(ext))
+            })
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub(crate) struct PathBufWrap(PathBuf);
 
@@ -15,52 +104,83 @@ impl FromStr for PathBufWrap {
     type Err = UriSegmentError;
 
     fn from_str(path: &str) -> Result<Self, Self::Err> {
-        Self::parse_path(path, false)
+        Self::parse_path(path, &PathBufConfig::default())
     }
 }
 
 impl PathBufWrap {
-    /// Parse a path, giving the choice of allowing hidden files to be considered valid segments.
+    /// Parse a path according to the given config.
     ///
     /// Path traversal is guarded by this method.
-    pub fn parse_path(path: &str, hidden_files: bool) -> Result<Self, UriSegmentError> {
+    pub fn parse_path(path: &str, config: &PathBufConfig) -> Result<Self, UriSegmentError> {
         let mut buf = PathBuf::new();
 
         // equivalent to `path.split('/').count()`
         let mut segment_count = path.matches('/').count() + 1;
 
+        let is_dir = path.is_empty() || path.ends_with('/');
+
         // we can decode the whole path here (instead of per-segment decoding)
         // because we will reject `%2F` in paths using `segment_count`.
-        let path = percent_encoding::percent_decode_str(path)
+        let decoded = percent_encoding::percent_decode_str(path)
             .decode_utf8()
             .map_err(|_| UriSegmentError::NotValidUtf8)?;
 
         // disallow decoding `%2F` into `/`
-        if segment_count != path.matches('/').count() + 1 {
-            return Err(UriSegmentError::BadChar('/'));
+        if segment_count != decoded.matches('/').count() + 1 {
+            let segment = path
+                .split('/')
+                .map(|segment| percent_encoding::percent_decode_str(segment).decode_utf8_lossy())
+                .find(|segment| segment.contains('/'))
+                .unwrap_or_default()
+                .into_owned();
+
+            return Err(UriSegmentError::BadChar {
+                segment,
+                character: '/',
+            });
         }
 
-        for segment in path.split('/') {
+        for segment in decoded.split('/') {
+            let bad_start = |character| UriSegmentError::BadStart {
+                segment: segment.to_owned(),
+                character,
+            };
+            let bad_char = |character| UriSegmentError::BadChar {
+                segment: segment.to_owned(),
+                character,
+            };
+            let bad_end = |character| UriSegmentError::BadEnd {
+                segment: segment.to_owned(),
+                character,
+            };
+
             if segment == ".." {
+                if config.reject_parent_segments {
+                    return Err(UriSegmentError::ParentSegment);
+                }
+
                 segment_count -= 1;
                 buf.pop();
-            } else if !hidden_files && segment.starts_with('.') {
-                return Err(UriSegmentError::BadStart('.'));
+            } else if !config.hidden_files && segment.starts_with('.') {
+                return Err(UriSegmentError::HiddenSegment {
+                    segment: segment.to_owned(),
+                });
             } else if segment.starts_with('*') {
-                return Err(UriSegmentError::BadStart('*'));
+                return Err(bad_start('*'));
             } else if segment.ends_with(':') {
-                return Err(UriSegmentError::BadEnd(':'));
+                return Err(bad_end(':'));
             } else if segment.ends_with('>') {
-                return Err(UriSegmentError::BadEnd('>'));
+                return Err(bad_end('>'));
             } else if segment.ends_with('<') {
-                return Err(UriSegmentError::BadEnd('<'));
+                return Err(bad_end('<'));
             } else if segment.is_empty() {
                 segment_count -= 1;
                 continue;
             } else if cfg!(windows) && segment.contains('\\') {
-                return Err(UriSegmentError::BadChar('\\'));
+                return Err(bad_char('\\'));
             } else if cfg!(windows) && segment.contains(':') {
-                return Err(UriSegmentError::BadChar(':'));
+                return Err(bad_char(':'));
             } else {
                 buf.push(segment)
             }
@@ -76,6 +196,16 @@ impl PathBufWrap {
             assert!(i < segment_count);
         }
 
+        if !is_dir {
+            if let Some(file_name) = buf.file_name().and_then(|name| name.to_str()) {
+                if !config.extension_allowed(file_name) {
+                    return Err(UriSegmentError::ExtensionNotAllowed {
+                        segment: file_name.to_owned(),
+                    });
+                }
+            }
+        }
+
         Ok(PathBufWrap(buf))
     }
 }
@@ -103,23 +233,37 @@ mod tests {
     fn test_path_buf() {
         assert_eq!(
             PathBufWrap::from_str("/test/.tt").map(|t| t.0),
-            Err(UriSegmentError::BadStart('.'))
+            Err(UriSegmentError::HiddenSegment {
+                segment: ".tt".to_owned()
+            })
         );
         assert_eq!(
             PathBufWrap::from_str("/test/*tt").map(|t| t.0),
-            Err(UriSegmentError::BadStart('*'))
+            Err(UriSegmentError::BadStart {
+                segment: "*tt".to_owned(),
+                character: '*'
+            })
         );
         assert_eq!(
             PathBufWrap::from_str("/test/tt:").map(|t| t.0),
-            Err(UriSegmentError::BadEnd(':'))
+            Err(UriSegmentError::BadEnd {
+                segment: "tt:".to_owned(),
+                character: ':'
+            })
         );
         assert_eq!(
             PathBufWrap::from_str("/test/tt<").map(|t| t.0),
-            Err(UriSegmentError::BadEnd('<'))
+            Err(UriSegmentError::BadEnd {
+                segment: "tt<".to_owned(),
+                character: '<'
+            })
         );
         assert_eq!(
             PathBufWrap::from_str("/test/tt>").map(|t| t.0),
-            Err(UriSegmentError::BadEnd('>'))
+            Err(UriSegmentError::BadEnd {
+                segment: "tt>".to_owned(),
+                character: '>'
+            })
         );
         assert_eq!(
             PathBufWrap::from_str("/seg1/seg2/").unwrap().0,
@@ -134,12 +278,16 @@ mod tests {
     #[test]
     fn test_parse_path() {
         assert_eq!(
-            PathBufWrap::parse_path("/test/.tt", false).map(|t| t.0),
-            Err(UriSegmentError::BadStart('.'))
+            PathBufWrap::parse_path("/test/.tt", &PathBufConfig::new()).map(|t| t.0),
+            Err(UriSegmentError::HiddenSegment {
+                segment: ".tt".to_owned()
+            })
         );
 
         assert_eq!(
-            PathBufWrap::parse_path("/test/.tt", true).unwrap().0,
+            PathBufWrap::parse_path("/test/.tt", &PathBufConfig::new().hidden_files(true))
+                .unwrap()
+                .0,
             PathBuf::from_iter(vec!["test", ".tt"])
         );
     }
@@ -147,20 +295,92 @@ mod tests {
     #[test]
     fn path_traversal() {
         assert_eq!(
-            PathBufWrap::parse_path("/../README.md", false).unwrap().0,
+            PathBufWrap::parse_path("/../README.md", &PathBufConfig::new())
+                .unwrap()
+                .0,
             PathBuf::from_iter(vec!["README.md"])
         );
 
         assert_eq!(
-            PathBufWrap::parse_path("/../README.md", true).unwrap().0,
+            PathBufWrap::parse_path("/../README.md", &PathBufConfig::new().hidden_files(true))
+                .unwrap()
+                .0,
             PathBuf::from_iter(vec!["README.md"])
         );
 
         assert_eq!(
-            PathBufWrap::parse_path("/../../../../../../../../../../etc/passwd", false)
+            PathBufWrap::parse_path(
+                "/../../../../../../../../../../etc/passwd",
+                &PathBufConfig::new()
+            )
+            .unwrap()
+            .0,
+            PathBuf::from_iter(vec!["etc/passwd"])
+        );
+    }
+
+    #[test]
+    fn reject_parent_segments() {
+        let config = PathBufConfig::new().reject_parent_segments(true);
+
+        assert_eq!(
+            PathBufWrap::parse_path("/link/../README.md", &config).map(|t| t.0),
+            Err(UriSegmentError::ParentSegment)
+        );
+
+        assert_eq!(
+            PathBufWrap::parse_path("/seg1/seg2", &config).unwrap().0,
+            PathBuf::from_iter(vec!["seg1", "seg2"])
+        );
+    }
+
+    #[test]
+    fn allowed_extensions() {
+        let config = PathBufConfig::new().allowed_extensions([".css", "js"]);
+
+        assert_eq!(
+            PathBufWrap::parse_path("/assets/app.JS", &config)
                 .unwrap()
                 .0,
-            PathBuf::from_iter(vec!["etc/passwd"])
+            PathBuf::from_iter(vec!["assets", "app.JS"])
+        );
+
+        // directories are not checked
+        assert_eq!(
+            PathBufWrap::parse_path("/assets/", &config).unwrap().0,
+            PathBuf::from_iter(vec!["assets"])
+        );
+
+        let err = PathBufWrap::parse_path("/assets/secret.env", &config).unwrap_err();
+        assert_eq!(
+            err,
+            UriSegmentError::ExtensionNotAllowed {
+                segment: "secret.env".to_owned()
+            }
+        );
+        assert_eq!(err.segment(), Some("secret.env"));
+
+        assert_eq!(
+            PathBufWrap::parse_path("/assets/Makefile", &config).map(|t| t.0),
+            Err(UriSegmentError::ExtensionNotAllowed {
+                segment: "Makefile".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn encoded_slash() {
+        let err = PathBufWrap::from_str("/seg1/a%2Fb").unwrap_err();
+        assert_eq!(
+            err,
+            UriSegmentError::BadChar {
+                segment: "a/b".to_owned(),
+                character: '/'
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "segment `a/b` contained invalid character ('/')"
         );
     }
 
@@ -171,17 +391,23 @@ mod tests {
         // see <https://github.com/SergioBenitez/Rocket/issues/1949
 
         assert_eq!(
-            PathBufWrap::parse_path("C:test.txt", false).unwrap().0,
+            PathBufWrap::parse_path("C:test.txt", &PathBufConfig::new())
+                .unwrap()
+                .0,
             PathBuf::from_iter(vec!["C:test.txt"])
         );
 
         assert_eq!(
-            PathBufWrap::parse_path("C:../whatever", false).unwrap().0,
+            PathBufWrap::parse_path("C:../whatever", &PathBufConfig::new())
+                .unwrap()
+                .0,
             PathBuf::from_iter(vec!["C:../whatever"])
         );
 
         assert_eq!(
-            PathBufWrap::parse_path(":test.txt", false).unwrap().0,
+            PathBufWrap::parse_path(":test.txt", &PathBufConfig::new())
+                .unwrap()
+                .0,
             PathBuf::from_iter(vec![":test.txt"])
         );
     }
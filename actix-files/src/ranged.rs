@@ -0,0 +1,455 @@
+use std::{
+    cmp, fmt, io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use actix_web::{
+    body::{self, BoxBody, SizedStream},
+    http::{
+        header::{self, ContentDisposition, EntityTag, HttpDate},
+        StatusCode,
+    },
+    Error, HttpRequest, HttpResponse, HttpResponseBuilder, Responder,
+};
+use bytes::Bytes;
+use futures_core::{ready, Stream};
+use mime::Mime;
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+
+use crate::{
+    conditional::{if_range_match, Preconditions},
+    range::HttpRange,
+};
+
+/// Maximum number of bytes read from the source per chunk.
+const MAX_CHUNK_SIZE: u64 = 65_536;
+
+/// Range-capable responder for any seekable asynchronous content source.
+///
+/// `RangedContent` brings the partial content and conditional request handling of [`NamedFile`] to
+/// content that does not live on the local filesystem, such as objects fetched from remote storage
+/// or data generated on the fly, allowing clients to resume interrupted downloads.
+///
+/// The source must implement Tokio's [`AsyncRead`] and [`AsyncSeek`] traits and its total size must
+/// be known up front. When an entity tag is [set](Self::set_etag), it is used to validate
+/// `If-Match`, `If-None-Match`, and `If-Range` request headers so that a client resuming a download
+/// never receives a range of content that has changed since its first request.
+///
+/// # Examples
+/// ```
+/// use std::io::Cursor;
+///
+/// use actix_files::RangedContent;
+/// use actix_web::{get, http::header::EntityTag, Responder};
+///
+/// #[get("/report.csv")]
+/// async fn report() -> impl Responder {
+///     let data = b"id,name\n1,actix\n".to_vec();
+///     let size = data.len() as u64;
+///
+///     RangedContent::new(Cursor::new(data), size)
+///         .set_content_type(mime::TEXT_CSV)
+///         .set_etag(EntityTag::new_strong("report-v1".to_owned()))
+/// }
+/// ```
+///
+/// [`NamedFile`]: crate::NamedFile
+pub struct RangedContent<R> {
+    source: R,
+    size: u64,
+    content_type: Mime,
+    content_disposition: Option<ContentDisposition>,
+    etag: Option<EntityTag>,
+    last_modified: Option<HttpDate>,
+}
+
+impl<R> fmt::Debug for RangedContent<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RangedContent")
+            .field("size", &self.size)
+            .field("content_type", &self.content_type)
+            .field("content_disposition", &self.content_disposition)
+            .field("etag", &self.etag)
+            .field("last_modified", &self.last_modified)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R> RangedContent<R>
+where
+    R: AsyncRead + AsyncSeek + Unpin + 'static,
+{
+    /// Constructs a new responder from a seekable source and its total size in bytes.
+    ///
+    /// The content type defaults to `application/octet-stream`.
+    pub fn new(source: R, size: u64) -> Self {
+        Self {
+            source,
+            size,
+            content_type: mime::APPLICATION_OCTET_STREAM,
+            content_disposition: None,
+            etag: None,
+            last_modified: None,
+        }
+    }
+
+    /// Sets the `Content-Type` of the response.
+    pub fn set_content_type(mut self, content_type: Mime) -> Self {
+        self.content_type = content_type;
+        self
+    }
+
+    /// Sets the `Content-Disposition` of the response.
+    pub fn set_content_disposition(mut self, content_disposition: ContentDisposition) -> Self {
+        self.content_disposition = Some(content_disposition);
+        self
+    }
+
+    /// Sets the entity tag used to validate conditional and resumed requests.
+    pub fn set_etag(mut self, etag: EntityTag) -> Self {
+        self.etag = Some(etag);
+        self
+    }
+
+    /// Sets the last modified date used to validate conditional and resumed requests.
+    pub fn set_last_modified(mut self, last_modified: HttpDate) -> Self {
+        self.last_modified = Some(last_modified);
+        self
+    }
+
+    /// Creates an `HttpResponse` with the requested range of the source as a streaming body.
+    pub fn into_response(self, req: &HttpRequest) -> HttpResponse<BoxBody> {
+        let validators = Validators {
+            size: self.size,
+            content_type: &self.content_type,
+            content_disposition: self.content_disposition.as_ref(),
+            etag: self.etag.as_ref(),
+            last_modified: self.last_modified.as_ref(),
+        };
+
+        match validators.prepare(req) {
+            Prepared::Done(res) => res,
+            Prepared::Stream {
+                mut res,
+                offset,
+                length,
+            } => res.body(SizedStream::new(
+                length,
+                SeekableRead::new(self.source, offset, length),
+            )),
+        }
+    }
+}
+
+impl<R> Responder for RangedContent<R>
+where
+    R: AsyncRead + AsyncSeek + Unpin + 'static,
+{
+    type Body = BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        self.into_response(req)
+    }
+}
+
+/// Representation metadata used to answer range and conditional requests.
+pub(crate) struct Validators<'a> {
+    pub(crate) size: u64,
+    pub(crate) content_type: &'a Mime,
+    pub(crate) content_disposition: Option<&'a ContentDisposition>,
+    pub(crate) etag: Option<&'a EntityTag>,
+    pub(crate) last_modified: Option<&'a HttpDate>,
+}
+
+/// Result of [`Validators::prepare`].
+pub(crate) enum Prepared {
+    /// Response is complete and has no content to stream.
+    Done(HttpResponse<BoxBody>),
+
+    /// Response requires `length` bytes of content starting at `offset`.
+    Stream {
+        res: HttpResponseBuilder,
+        offset: u64,
+        length: u64,
+    },
+}
+
+impl Validators<'_> {
+    /// Evaluates conditional and range headers in `req`, preparing the response headers.
+    pub(crate) fn prepare(&self, req: &HttpRequest) -> Prepared {
+        let preconditions = Preconditions::evaluate(req, self.etag, self.last_modified);
+
+        let mut res = HttpResponse::Ok();
+
+        res.insert_header((header::CONTENT_TYPE, self.content_type.to_string()));
+
+        if let Some(cd) = self.content_disposition {
+            res.insert_header((header::CONTENT_DISPOSITION, cd.to_string()));
+        }
+
+        if let Some(lm) = self.last_modified {
+            res.insert_header((header::LAST_MODIFIED, lm.to_string()));
+        }
+
+        if let Some(etag) = self.etag {
+            res.insert_header((header::ETAG, etag.to_string()));
+        }
+
+        res.insert_header((header::ACCEPT_RANGES, "bytes"));
+
+        if preconditions.failed {
+            return Prepared::Done(res.status(StatusCode::PRECONDITION_FAILED).finish());
+        } else if preconditions.not_modified {
+            return Prepared::Done(
+                res.status(StatusCode::NOT_MODIFIED)
+                    .body(body::None::new())
+                    .map_into_boxed_body(),
+            );
+        }
+
+        let mut length = self.size;
+        let mut offset = 0;
+
+        // a stale `If-Range` validator means the full, current content must be sent
+        let range = req
+            .headers()
+            .get(header::RANGE)
+            .filter(|_| if_range_match(self.etag, self.last_modified, req));
+
+        if let Some(range) = range {
+            let Ok(range) = range.to_str() else {
+                return Prepared::Done(res.status(StatusCode::BAD_REQUEST).finish());
+            };
+
+            match HttpRange::parse(range, self.size) {
+                Ok(ranges) if !ranges.is_empty() => {
+                    length = ranges[0].length;
+                    offset = ranges[0].start;
+
+                    res.status(StatusCode::PARTIAL_CONTENT);
+                    res.insert_header((
+                        header::CONTENT_RANGE,
+                        format!("bytes {}-{}/{}", offset, offset + length - 1, self.size),
+                    ));
+                }
+
+                _ => {
+                    res.insert_header((header::CONTENT_RANGE, format!("bytes */{}", self.size)));
+                    return Prepared::Done(res.status(StatusCode::RANGE_NOT_SATISFIABLE).finish());
+                }
+            }
+        }
+
+        Prepared::Stream {
+            res,
+            offset,
+            length,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SeekState {
+    Start,
+    Seeking,
+    Done,
+}
+
+/// Stream that seeks a source to `offset` and then yields `remaining` bytes from it in chunks.
+struct SeekableRead<R> {
+    source: R,
+    offset: u64,
+    remaining: u64,
+    state: SeekState,
+    buf: Vec<u8>,
+}
+
+impl<R> SeekableRead<R> {
+    fn new(source: R, offset: u64, remaining: u64) -> Self {
+        Self {
+            source,
+            offset,
+            remaining,
+            state: SeekState::Start,
+            buf: Vec::new(),
+        }
+    }
+}
+
+impl<R> Stream for SeekableRead<R>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.state == SeekState::Start {
+            if let Err(err) =
+                Pin::new(&mut this.source).start_seek(io::SeekFrom::Start(this.offset))
+            {
+                this.state = SeekState::Done;
+                this.remaining = 0;
+                return Poll::Ready(Some(Err(err.into())));
+            }
+
+            this.state = SeekState::Seeking;
+        }
+
+        if this.state == SeekState::Seeking {
+            let res = ready!(Pin::new(&mut this.source).poll_complete(cx));
+            this.state = SeekState::Done;
+
+            if let Err(err) = res {
+                this.remaining = 0;
+                return Poll::Ready(Some(Err(err.into())));
+            }
+        }
+
+        if this.remaining == 0 {
+            return Poll::Ready(None);
+        }
+
+        let max_bytes = cmp::min(this.remaining, MAX_CHUNK_SIZE) as usize;
+        this.buf.resize(max_bytes, 0);
+
+        let mut buf = ReadBuf::new(&mut this.buf);
+        if let Err(err) = ready!(Pin::new(&mut this.source).poll_read(cx, &mut buf)) {
+            this.remaining = 0;
+            return Poll::Ready(Some(Err(err.into())));
+        }
+
+        let n_bytes = buf.filled().len();
+
+        if n_bytes == 0 {
+            this.remaining = 0;
+            let err = io::Error::from(io::ErrorKind::UnexpectedEof);
+            return Poll::Ready(Some(Err(err.into())));
+        }
+
+        this.remaining -= n_bytes as u64;
+        Poll::Ready(Some(Ok(Bytes::copy_from_slice(buf.filled()))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use actix_web::{
+        http::header::{self, EntityTag, HeaderValue},
+        test::{self, TestRequest},
+    };
+
+    use super::*;
+
+    const DATA: &[u8] = b"0123456789abcdefghij";
+
+    fn content() -> RangedContent<Cursor<&'static [u8]>> {
+        RangedContent::new(Cursor::new(DATA), DATA.len() as u64)
+            .set_content_type(mime::TEXT_PLAIN)
+            .set_etag(EntityTag::new_strong("v1".to_owned()))
+    }
+
+    #[actix_rt::test]
+    async fn full_content() {
+        let req = TestRequest::default().to_http_request();
+        let res = content().into_response(&req);
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::ACCEPT_RANGES).unwrap(),
+            HeaderValue::from_static("bytes")
+        );
+        assert_eq!(
+            res.headers().get(header::ETAG).unwrap(),
+            HeaderValue::from_static("\"v1\"")
+        );
+        assert_eq!(
+            test::read_body(test::TestRequest::default().to_srv_response(res)).await,
+            DATA
+        );
+    }
+
+    #[actix_rt::test]
+    async fn partial_content() {
+        let req = TestRequest::default()
+            .insert_header((header::RANGE, "bytes=10-"))
+            .to_http_request();
+        let res = content().into_response(&req);
+
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            res.headers().get(header::CONTENT_RANGE).unwrap(),
+            HeaderValue::from_static("bytes 10-19/20")
+        );
+        let body = test::read_body(TestRequest::default().to_srv_response(res)).await;
+        assert_eq!(body, &DATA[10..]);
+    }
+
+    #[actix_rt::test]
+    async fn if_range_validation() {
+        // matching validator resumes download
+        let req = TestRequest::default()
+            .insert_header((header::RANGE, "bytes=5-9"))
+            .insert_header((header::IF_RANGE, "\"v1\""))
+            .to_http_request();
+        let res = content().into_response(&req);
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        let body = test::read_body(TestRequest::default().to_srv_response(res)).await;
+        assert_eq!(body, &DATA[5..10]);
+
+        // stale validator restarts download
+        let req = TestRequest::default()
+            .insert_header((header::RANGE, "bytes=5-9"))
+            .insert_header((header::IF_RANGE, "\"v0\""))
+            .to_http_request();
+        let res = content().into_response(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().get(header::CONTENT_RANGE).is_none());
+        let body = test::read_body(TestRequest::default().to_srv_response(res)).await;
+        assert_eq!(body, DATA);
+    }
+
+    #[actix_rt::test]
+    async fn conditional_requests() {
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, "\"v1\""))
+            .to_http_request();
+        let res = content().into_response(&req);
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_MATCH, "\"v2\""))
+            .to_http_request();
+        let res = content().into_response(&req);
+        assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[actix_rt::test]
+    async fn unsatisfiable_range() {
+        let req = TestRequest::default()
+            .insert_header((header::RANGE, "bytes=30-40"))
+            .to_http_request();
+        let res = content().into_response(&req);
+
+        assert_eq!(res.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            res.headers().get(header::CONTENT_RANGE).unwrap(),
+            HeaderValue::from_static("bytes */20")
+        );
+    }
+
+    #[actix_rt::test]
+    async fn large_content_is_chunked() {
+        let data = vec![7u8; MAX_CHUNK_SIZE as usize * 2 + 10];
+        let size = data.len() as u64;
+
+        let req = TestRequest::default().to_http_request();
+        let res = RangedContent::new(Cursor::new(data.clone()), size).into_response(&req);
+        let body = test::read_body(TestRequest::default().to_srv_response(res)).await;
+        assert_eq!(body, data);
+    }
+}
@@ -12,7 +12,7 @@ use futures_core::future::LocalBoxFuture;
 
 use crate::{
     named, Directory, DirectoryRenderer, FilesError, HttpService, MimeOverride, NamedFile,
-    PathBufWrap, PathFilter,
+    PathBufConfig, PathBufWrap, PathFilter,
 };
 
 /// Assembled file serving service.
@@ -38,7 +38,7 @@ pub struct FilesServiceInner {
     pub(crate) path_filter: Option<Rc<PathFilter>>,
     pub(crate) file_flags: named::Flags,
     pub(crate) guards: Option<Rc<dyn Guard>>,
-    pub(crate) hidden_files: bool,
+    pub(crate) path_config: PathBufConfig,
 }
 
 impl fmt::Debug for FilesServiceInner {
@@ -62,6 +62,14 @@ impl FilesService {
         }
     }
 
+    async fn open_file(&self, path: PathBuf, req: &ServiceRequest) -> io::Result<NamedFile> {
+        if self.file_flags.contains(named::Flags::PRECOMPRESSED) {
+            NamedFile::open_precompressed_async(path, req.request()).await
+        } else {
+            NamedFile::open_async(path).await
+        }
+    }
+
     fn serve_named_file(&self, req: ServiceRequest, mut named_file: NamedFile) -> ServiceResponse {
         if let Some(ref mime_override) = self.mime_override {
             let new_disposition = mime_override(&named_file.content_type.type_());
@@ -117,7 +125,7 @@ impl Service<ServiceRequest> for FilesService {
             }
 
             let path_on_disk =
-                match PathBufWrap::parse_path(req.match_info().unprocessed(), this.hidden_files) {
+                match PathBufWrap::parse_path(req.match_info().unprocessed(), &this.path_config) {
                     Ok(item) => item,
                     Err(err) => return Ok(req.error_response(err)),
                 };
@@ -155,7 +163,7 @@ impl Service<ServiceRequest> for FilesService {
                 match this.index {
                     Some(ref index) => {
                         let named_path = path.join(index);
-                        match NamedFile::open_async(named_path).await {
+                        match this.open_file(named_path, &req).await {
                             Ok(named_file) => Ok(this.serve_named_file(req, named_file)),
                             Err(_) if this.show_index => Ok(this.show_index(req, path)),
                             Err(err) => this.handle_err(err, req).await,
@@ -168,7 +176,7 @@ impl Service<ServiceRequest> for FilesService {
                     )),
                 }
             } else {
-                match NamedFile::open_async(&path).await {
+                match this.open_file(path, &req).await {
                     Ok(mut named_file) => {
                         if let Some(ref mime_override) = this.mime_override {
                             let new_disposition = mime_override(&named_file.content_type.type_());
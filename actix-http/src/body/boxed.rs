@@ -1,14 +1,16 @@
 use std::{
+    collections::VecDeque,
     error::Error as StdError,
     fmt,
     pin::Pin,
     task::{Context, Poll},
 };
 
-use bytes::Bytes;
+use actix_utils::future::poll_fn;
+use bytes::{Bytes, BytesMut};
 
-use super::{BodySize, MessageBody, MessageBodyMapErr};
-use crate::body;
+use super::{private, BodySize, FileBody, MessageBody, MessageBodyMapErr, WithTrailers};
+use crate::{body, header::HeaderMap};
 
 /// A boxed message body with boxed errors.
 #[derive(Debug)]
@@ -57,6 +59,116 @@ impl BoxBody {
     pub fn as_pin_mut(&mut self) -> Pin<&mut Self> {
         Pin::new(self)
     }
+
+    /// Returns the complete body bytes if they are already held in memory.
+    ///
+    /// Returns `None` for streaming bodies. Use [`try_buffer`](Self::try_buffer) to collect small
+    /// streaming bodies.
+    #[inline]
+    pub fn as_bytes(&self) -> Option<&Bytes> {
+        match &self.0 {
+            BoxBodyInner::Bytes(bytes) => Some(bytes),
+            BoxBodyInner::None(_) | BoxBodyInner::Stream(_) => None,
+        }
+    }
+
+    /// Collects the body into memory if it yields no more than `limit` bytes.
+    ///
+    /// This allows middleware to inspect and modify small responses without knowing whether their
+    /// bodies are streamed. If the body turns out to be larger than `limit` (or has trailers), the
+    /// body is returned as `Ok(Err(body))`; it yields the same bytes as the original body, including
+    /// any that were read while trying to buffer it.
+    ///
+    /// # Errors
+    /// Errors produced by the body stream are returned immediately. In that case, the body cannot
+    /// be recovered.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_http::body::{BodyStream, BoxBody, MessageBody as _};
+    /// use bytes::Bytes;
+    /// use futures_util::stream;
+    ///
+    /// # actix_rt::System::new().block_on(async {
+    /// let chunks = ["hello", " ", "world"].map(|chunk| Ok::<_, std::io::Error>(Bytes::from(chunk)));
+    /// let body = BodyStream::new(stream::iter(chunks)).boxed();
+    ///
+    /// let bytes = body.try_buffer(1024).await.unwrap().unwrap();
+    /// assert_eq!(bytes, "hello world");
+    /// # });
+    /// ```
+    pub async fn try_buffer(self, limit: usize) -> Result<Result<Bytes, Self>, Box<dyn StdError>> {
+        let size = self.size();
+
+        let mut body = match size {
+            BodySize::Sized(len) if len > limit as u64 => return Ok(Err(self)),
+            _ => match self.try_into_bytes() {
+                Ok(bytes) if bytes.len() <= limit => return Ok(Ok(bytes)),
+                Ok(bytes) => return Ok(Err(BoxBody::new(bytes))),
+                Err(body) => body,
+            },
+        };
+
+        let mut buf = BytesMut::new();
+
+        while let Some(chunk) = poll_fn(|cx| body.as_pin_mut().poll_next(cx)).await {
+            let chunk = chunk?;
+
+            if buf.len() + chunk.len() > limit {
+                let body = Buffered {
+                    size,
+                    chunks: VecDeque::from([buf.freeze(), chunk]),
+                    body,
+                };
+
+                return Ok(Err(BoxBody::new(body)));
+            }
+
+            buf.extend_from_slice(&chunk);
+        }
+
+        match poll_fn(|cx| body.as_pin_mut().poll_trailers(cx)).await {
+            None => Ok(Ok(buf.freeze())),
+            Some(trailers) => Ok(Err(BoxBody::new(WithTrailers::new(
+                buf.freeze(),
+                move || trailers,
+            )))),
+        }
+    }
+}
+
+/// Body that yields chunks already read from `body` before continuing with the rest of it.
+struct Buffered {
+    size: BodySize,
+    chunks: VecDeque<Bytes>,
+    body: BoxBody,
+}
+
+impl MessageBody for Buffered {
+    type Error = Box<dyn StdError>;
+
+    #[inline]
+    fn size(&self) -> BodySize {
+        self.size
+    }
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        while let Some(chunk) = self.chunks.pop_front() {
+            if !chunk.is_empty() {
+                return Poll::Ready(Some(Ok(chunk)));
+            }
+        }
+
+        self.body.as_pin_mut().poll_next(cx)
+    }
+
+    #[inline]
+    fn poll_trailers(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        self.body.as_pin_mut().poll_trailers(cx)
+    }
 }
 
 impl MessageBody for BoxBody {
@@ -83,6 +195,22 @@ impl MessageBody for BoxBody {
         }
     }
 
+    #[inline]
+    fn poll_trailers(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        match &mut self.0 {
+            BoxBodyInner::Stream(body) => Pin::new(body).poll_trailers(cx),
+            _ => Poll::Ready(None),
+        }
+    }
+
+    #[inline]
+    fn as_file_body(self: Pin<&mut Self>, token: private::Token) -> Option<&mut FileBody> {
+        match &mut self.get_mut().0 {
+            BoxBodyInner::Stream(body) => body.as_mut().as_file_body(token),
+            _ => None,
+        }
+    }
+
     #[inline]
     fn try_into_bytes(self) -> Result<Bytes, Self> {
         match self.0 {
@@ -108,6 +236,53 @@ mod tests {
     assert_impl_all!(BoxBody: fmt::Debug, MessageBody, Unpin);
     assert_not_impl_any!(BoxBody: Send, Sync);
 
+    #[actix_rt::test]
+    async fn try_buffer() {
+        use futures_util::stream;
+
+        use crate::body::{BodyStream, SizedStream};
+
+        fn chunks() -> impl Iterator<Item = Result<Bytes, Box<dyn StdError>>> {
+            ["ab", "cd", "ef"]
+                .into_iter()
+                .map(|chunk| Ok(Bytes::from_static(chunk.as_bytes())))
+        }
+
+        let body = BoxBody::new(Bytes::from_static(b"abc"));
+        assert_eq!(body.as_bytes().unwrap(), "abc");
+        assert_eq!(body.try_buffer(3).await.unwrap().unwrap(), "abc");
+
+        let body = BoxBody::new(Bytes::from_static(b"abc"));
+        let body = body.try_buffer(2).await.unwrap().unwrap_err();
+        assert_eq!(to_bytes(body).await.unwrap(), "abc");
+
+        let body = BoxBody::new(BodyStream::new(stream::iter(chunks())));
+        assert!(body.as_bytes().is_none());
+        assert_eq!(body.try_buffer(6).await.unwrap().unwrap(), "abcdef");
+
+        // exceeding limit part way through stream yields all bytes from returned body
+        let body = BoxBody::new(BodyStream::new(stream::iter(chunks())));
+        let body = body.try_buffer(3).await.unwrap().unwrap_err();
+        assert_eq!(body.size(), BodySize::Stream);
+        assert_eq!(to_bytes(body).await.unwrap(), "abcdef");
+
+        // declared size over limit is not read at all
+        let body = BoxBody::new(SizedStream::new(6, stream::iter(chunks())));
+        let body = body.try_buffer(3).await.unwrap().unwrap_err();
+        assert_eq!(body.size(), BodySize::Sized(6));
+        assert_eq!(to_bytes(body).await.unwrap(), "abcdef");
+
+        // body with trailers is not buffered
+        let body = BoxBody::new(WithTrailers::new(
+            BodyStream::new(stream::iter(chunks())),
+            HeaderMap::new,
+        ));
+        let mut body = body.try_buffer(1024).await.unwrap().unwrap_err();
+        assert_eq!(to_bytes(&mut body).await.unwrap(), "abcdef");
+        let trailers = poll_fn(|cx| body.as_pin_mut().poll_trailers(cx)).await;
+        assert!(trailers.is_some());
+    }
+
     #[actix_rt::test]
     async fn nested_boxed_body() {
         let body = Bytes::from_static(&[1, 2, 3]);
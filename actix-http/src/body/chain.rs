@@ -0,0 +1,122 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures_core::ready;
+use pin_project_lite::pin_project;
+
+use super::{BodySize, MessageBody};
+use crate::{header::HeaderMap, Error};
+
+pin_project! {
+    /// Body that yields all chunks of one body followed by all chunks of another.
+    ///
+    /// Only the trailers of the second body are sent.
+    ///
+    /// Created by [`MessageBodyExt::chain`](super::MessageBodyExt::chain).
+    #[derive(Debug, Clone)]
+    pub struct Chain<A, B> {
+        #[pin]
+        first: A,
+        #[pin]
+        second: B,
+        first_done: bool,
+    }
+}
+
+impl<A, B> Chain<A, B>
+where
+    A: MessageBody,
+    B: MessageBody,
+{
+    /// Constructs new body that yields `first` and then `second`.
+    #[inline]
+    pub fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second,
+            first_done: false,
+        }
+    }
+}
+
+impl<A, B> MessageBody for Chain<A, B>
+where
+    A: MessageBody,
+    B: MessageBody,
+{
+    type Error = Error;
+
+    #[inline]
+    fn size(&self) -> BodySize {
+        let first = if self.first_done {
+            BodySize::None
+        } else {
+            self.first.size()
+        };
+
+        match (first, self.second.size()) {
+            (BodySize::None, size) | (size, BodySize::None) => size,
+            (BodySize::Sized(first), BodySize::Sized(second)) => BodySize::Sized(first + second),
+            _ => BodySize::Stream,
+        }
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.project();
+
+        if !*this.first_done {
+            match ready!(this.first.poll_next(cx)) {
+                Some(res) => {
+                    return Poll::Ready(Some(res.map_err(|err| Error::new_body().with_cause(err))))
+                }
+                None => *this.first_done = true,
+            }
+        }
+
+        this.second
+            .poll_next(cx)
+            .map_err(|err| Error::new_body().with_cause(err))
+    }
+
+    #[inline]
+    fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        self.project().second.poll_trailers(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::{to_bytes, BodyStream};
+
+    #[actix_rt::test]
+    async fn chains_bodies() {
+        let body = Chain::new("hello ", Bytes::from_static(b"world"));
+        assert_eq!(body.size(), BodySize::Sized(11));
+        assert_eq!(to_bytes(body).await.unwrap(), "hello world");
+
+        let body = Chain::new((), "world");
+        assert_eq!(body.size(), BodySize::Sized(5));
+
+        let body = Chain::new(crate::body::None::new(), crate::body::None::new());
+        assert_eq!(body.size(), BodySize::None);
+
+        let chunks = futures_util::stream::iter([Ok::<_, std::io::Error>(Bytes::from("a"))]);
+        let body = Chain::new(BodyStream::new(chunks), "b");
+        assert_eq!(body.size(), BodySize::Stream);
+        assert_eq!(to_bytes(body).await.unwrap(), "ab");
+    }
+
+    #[actix_rt::test]
+    async fn forwards_errors() {
+        let chunks = futures_util::stream::iter([Err::<Bytes, _>(std::io::Error::other("oops"))]);
+        let body = Chain::new(BodyStream::new(chunks), "b");
+        assert!(to_bytes(body).await.is_err());
+    }
+}
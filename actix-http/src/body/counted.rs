@@ -0,0 +1,145 @@
+use std::{
+    cell::Cell,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures_core::ready;
+use pin_project_lite::pin_project;
+
+use super::{BodySize, MessageBody};
+use crate::header::HeaderMap;
+
+/// Shared count of the bytes yielded by a [`Counted`] body.
+///
+/// Clones refer to the same count, which allows reading it after the body has been handed off
+/// (e.g., once the response has been sent).
+#[derive(Debug, Clone, Default)]
+pub struct ByteCounter(Rc<Cell<u64>>);
+
+impl ByteCounter {
+    /// Returns the number of bytes counted so far.
+    #[inline]
+    pub fn get(&self) -> u64 {
+        self.0.get()
+    }
+
+    fn add(&self, n: u64) {
+        self.0.set(self.0.get() + n);
+    }
+}
+
+pin_project! {
+    /// Body wrapper that counts the bytes actually yielded by the wrapped body.
+    ///
+    /// Unlike the declared [`size`](MessageBody::size), this also covers streaming bodies and
+    /// bodies that end early due to an error.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_http::body::{self, Counted};
+    ///
+    /// # actix_rt::System::new().block_on(async {
+    /// let body = Counted::new("hello world");
+    /// let counter = body.counter();
+    ///
+    /// body::to_bytes(body).await.unwrap();
+    /// assert_eq!(counter.get(), 11);
+    /// # });
+    /// ```
+    #[derive(Debug)]
+    pub struct Counted<B> {
+        #[pin]
+        body: B,
+        counter: ByteCounter,
+    }
+}
+
+impl<B> Counted<B>
+where
+    B: MessageBody,
+{
+    /// Constructs new body wrapper that counts the bytes yielded by `body`.
+    #[inline]
+    pub fn new(body: B) -> Self {
+        Self {
+            body,
+            counter: ByteCounter::default(),
+        }
+    }
+
+    /// Returns a handle to the count of bytes yielded so far.
+    #[inline]
+    pub fn counter(&self) -> ByteCounter {
+        self.counter.clone()
+    }
+}
+
+impl<B> MessageBody for Counted<B>
+where
+    B: MessageBody,
+{
+    type Error = B::Error;
+
+    #[inline]
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.project();
+
+        let item = ready!(this.body.poll_next(cx));
+
+        if let Some(Ok(ref chunk)) = item {
+            this.counter.add(chunk.len() as u64);
+        }
+
+        Poll::Ready(item)
+    }
+
+    #[inline]
+    fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        self.project().body.poll_trailers(cx)
+    }
+
+    #[inline]
+    fn try_into_bytes(self) -> Result<Bytes, Self> {
+        let Self { body, counter } = self;
+
+        match body.try_into_bytes() {
+            Ok(bytes) => {
+                counter.add(bytes.len() as u64);
+                Ok(bytes)
+            }
+            Err(body) => Err(Self { body, counter }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::{to_bytes, BodyStream};
+
+    #[actix_rt::test]
+    async fn counts_streamed_bytes() {
+        let chunks = futures_util::stream::iter([
+            Ok(Bytes::from_static(b"abc")),
+            Ok(Bytes::from_static(b"de")),
+            Err(std::io::Error::other("oops")),
+        ]);
+
+        let body = Counted::new(BodyStream::new(chunks));
+        let counter = body.counter();
+        assert_eq!(body.size(), BodySize::Stream);
+
+        assert!(to_bytes(body).await.is_err());
+        assert_eq!(counter.get(), 5);
+    }
+}
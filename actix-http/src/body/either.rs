@@ -6,8 +6,8 @@ use std::{
 use bytes::Bytes;
 use pin_project_lite::pin_project;
 
-use super::{BodySize, BoxBody, MessageBody};
-use crate::Error;
+use super::{private, BodySize, BoxBody, FileBody, MessageBody};
+use crate::{header::HeaderMap, Error};
 
 pin_project! {
     /// An "either" type specialized for body types.
@@ -87,6 +87,22 @@ where
         }
     }
 
+    #[inline]
+    fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        match self.project() {
+            EitherBodyProj::Left { body } => body.poll_trailers(cx),
+            EitherBodyProj::Right { body } => body.poll_trailers(cx),
+        }
+    }
+
+    #[inline]
+    fn as_file_body(self: Pin<&mut Self>, token: private::Token) -> Option<&mut FileBody> {
+        match self.project() {
+            EitherBodyProj::Left { body } => body.as_file_body(token),
+            EitherBodyProj::Right { body } => body.as_file_body(token),
+        }
+    }
+
     #[inline]
     fn try_into_bytes(self) -> Result<Bytes, Self> {
         match self {
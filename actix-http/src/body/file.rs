@@ -0,0 +1,189 @@
+use std::{
+    fs::File,
+    io::{self, Seek as _},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures_core::ready;
+
+use super::{private, BodySize, MessageBody, ReaderBody};
+
+/// Response body backed by a file.
+///
+/// When this body is sent unencoded over a plain TCP connection on Linux, the HTTP/1 dispatcher
+/// writes it to the socket using `sendfile(2)`, avoiding copying the file contents through the
+/// write buffer. Otherwise, the file is read in chunks on the blocking thread pool.
+///
+/// The `sendfile` path is kept when this body is wrapped in the body types of this crate, such as
+/// [`BoxBody`](super::BoxBody) and [`EitherBody`](super::EitherBody), but not when it is wrapped in
+/// body types from other crates (e.g., for logging).
+///
+/// # Examples
+/// ```no_run
+/// use std::fs::File;
+///
+/// use actix_http::{body::FileBody, Response};
+///
+/// # fn run() -> std::io::Result<()> {
+/// let body = FileBody::new(File::open("index.html")?)?;
+/// let res = Response::ok().set_body(body);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FileBody {
+    offset: u64,
+    body: ReaderBody<File>,
+}
+
+impl FileBody {
+    /// Constructs a body containing the whole of `file`, sized from its metadata.
+    pub fn new(file: File) -> io::Result<Self> {
+        let len = file.metadata()?.len();
+        Ok(Self::with_range(file, 0, len))
+    }
+
+    /// Constructs a body containing `len` bytes of `file`, starting at `offset`.
+    ///
+    /// Sending fails with an `UnexpectedEof` error if the file ends before `len` bytes are read.
+    pub fn with_range(file: File, offset: u64, len: u64) -> Self {
+        Self {
+            offset,
+            body: ReaderBody::new(file, len),
+        }
+    }
+
+    /// Returns the number of bytes of the body that have not yet been sent.
+    #[cfg(target_os = "linux")]
+    pub(crate) fn remaining(&self) -> u64 {
+        self.body.remaining()
+    }
+
+    /// Returns true if the rest of the body can be written using [`poll_sendfile`].
+    ///
+    /// [`poll_sendfile`]: Self::poll_sendfile
+    #[cfg(target_os = "linux")]
+    pub(crate) fn can_sendfile(&self) -> bool {
+        self.body.remaining() > 0 && self.body.reader().is_some()
+    }
+
+    /// Writes part of the remaining body to `stream` using `sendfile(2)`.
+    ///
+    /// Returns the number of bytes written, which are consumed from this body.
+    #[cfg(target_os = "linux")]
+    pub(crate) fn poll_sendfile(
+        &mut self,
+        stream: &actix_rt::net::TcpStream,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<usize>> {
+        use std::os::unix::io::AsRawFd as _;
+
+        use tokio::io::Interest;
+
+        // Linux transfers at most this many bytes in a single call
+        const MAX_SENDFILE_SIZE: u64 = 0x7fff_f000;
+
+        let file = self
+            .body
+            .reader()
+            .expect("FileBody::poll_sendfile called while body is being read");
+
+        let count = std::cmp::min(self.body.remaining(), MAX_SENDFILE_SIZE) as usize;
+
+        let mut offset = libc::off_t::try_from(self.offset)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "file offset too large"))?;
+
+        loop {
+            ready!(stream.poll_write_ready(cx))?;
+
+            let res = stream.try_io(Interest::WRITABLE, || {
+                // SAFETY: both file descriptors are valid for the duration of the call and
+                // `offset` is a valid pointer
+                let n = unsafe {
+                    libc::sendfile(stream.as_raw_fd(), file.as_raw_fd(), &mut offset, count)
+                };
+
+                if n < 0 {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(n as usize)
+                }
+            });
+
+            match res {
+                Ok(0) => return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into())),
+
+                Ok(n) => {
+                    self.offset += n as u64;
+                    self.body.consume(n as u64);
+                    return Poll::Ready(Ok(n));
+                }
+
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => continue,
+
+                Err(err) => return Poll::Ready(Err(err)),
+            }
+        }
+    }
+}
+
+impl MessageBody for FileBody {
+    type Error = io::Error;
+
+    #[inline]
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+
+        // sendfile does not move the file position, so seek to the offset before each read
+        let offset = this.offset;
+        let seek = move |file: &mut File| file.seek(io::SeekFrom::Start(offset)).map(drop);
+        let chunk = ready!(this.body.poll_next_chunk(cx, seek));
+
+        if let Some(Ok(ref bytes)) = chunk {
+            this.offset += bytes.len() as u64;
+        }
+
+        Poll::Ready(chunk)
+    }
+
+    #[inline]
+    fn as_file_body(self: Pin<&mut Self>, _: private::Token) -> Option<&mut FileBody> {
+        Some(self.get_mut())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use super::*;
+    use crate::body::to_bytes;
+
+    #[actix_rt::test]
+    async fn reads_range() {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(&[b'a'; 100_000]).unwrap();
+        file.write_all(b"bcd").unwrap();
+
+        let body = FileBody::new(file.try_clone().unwrap()).unwrap();
+        assert_eq!(body.size(), BodySize::Sized(100_003));
+        assert_eq!(to_bytes(body).await.unwrap().len(), 100_003);
+
+        let body = FileBody::with_range(file.try_clone().unwrap(), 100_000, 2);
+        assert_eq!(to_bytes(body).await.unwrap(), "bc");
+
+        // file ends before declared length
+        let body = FileBody::with_range(file, 100_000, 4);
+        let err = to_bytes(body).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
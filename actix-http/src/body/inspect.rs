@@ -0,0 +1,114 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures_core::ready;
+use pin_project_lite::pin_project;
+
+use super::{BodySize, MessageBody};
+use crate::header::HeaderMap;
+
+pin_project! {
+    /// Body wrapper that calls a function with a reference to each chunk of the wrapped body.
+    ///
+    /// Chunks are yielded unchanged. The function is also called if the complete body is taken
+    /// using [`MessageBody::try_into_bytes`].
+    ///
+    /// Created by [`MessageBodyExt::inspect`](super::MessageBodyExt::inspect).
+    #[derive(Debug, Clone)]
+    pub struct Inspect<B, F> {
+        #[pin]
+        body: B,
+        inspector: F,
+    }
+}
+
+impl<B, F> Inspect<B, F>
+where
+    B: MessageBody,
+    F: FnMut(&Bytes),
+{
+    /// Constructs new body wrapper that passes each chunk of `body` to `inspector`.
+    #[inline]
+    pub fn new(body: B, inspector: F) -> Self {
+        Self { body, inspector }
+    }
+}
+
+impl<B, F> MessageBody for Inspect<B, F>
+where
+    B: MessageBody,
+    F: FnMut(&Bytes),
+{
+    type Error = B::Error;
+
+    #[inline]
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.project();
+
+        let item = ready!(this.body.poll_next(cx));
+
+        if let Some(Ok(ref chunk)) = item {
+            (this.inspector)(chunk);
+        }
+
+        Poll::Ready(item)
+    }
+
+    #[inline]
+    fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        self.project().body.poll_trailers(cx)
+    }
+
+    #[inline]
+    fn try_into_bytes(self) -> Result<Bytes, Self> {
+        let Self {
+            body,
+            mut inspector,
+        } = self;
+
+        match body.try_into_bytes() {
+            Ok(bytes) => {
+                inspector(&bytes);
+                Ok(bytes)
+            }
+            Err(body) => Err(Self { body, inspector }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::{to_bytes, BodyStream};
+
+    #[actix_rt::test]
+    async fn inspects_chunks() {
+        let chunks = futures_util::stream::iter([
+            Ok::<_, std::io::Error>(Bytes::from_static(b"abc")),
+            Ok(Bytes::from_static(b"de")),
+        ]);
+
+        let mut seen = Vec::new();
+        let body = Inspect::new(BodyStream::new(chunks), |chunk: &Bytes| {
+            seen.push(chunk.len())
+        });
+        assert_eq!(to_bytes(body).await.unwrap(), "abcde");
+        assert_eq!(seen, [3, 2]);
+
+        let mut seen = 0;
+        let body = Inspect::new("test", |chunk: &Bytes| seen += chunk.len());
+        assert_eq!(body.size(), BodySize::Sized(4));
+        assert_eq!(body.try_into_bytes().ok().unwrap(), "test");
+        assert_eq!(seen, 4);
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use pin_project_lite::pin_project;
+
+use super::{BodySize, MessageBody};
+use crate::header::HeaderMap;
+
+pin_project! {
+    /// Body that is only generated once it is first polled.
+    ///
+    /// Dispatchers do not poll the bodies of responses that are sent without one, i.e., responses
+    /// to `HEAD` requests and `204 No Content` or `304 Not Modified` responses. Deferring expensive
+    /// work, like serialization, to the generator function means it is skipped for those responses.
+    ///
+    /// Unless the size of the generated body is given upfront using [`sized`](Self::sized), the
+    /// body is sent as a stream of unknown length.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_http::body::{BodySize, LazyBody, MessageBody as _};
+    ///
+    /// let body = LazyBody::new(|| {
+    ///     // ... expensive serialization
+    ///     "[1, 2, 3]"
+    /// });
+    /// assert_eq!(body.size(), BodySize::Stream);
+    /// ```
+    pub struct LazyBody<F, B> {
+        size: BodySize,
+        #[pin]
+        state: LazyState<F, B>,
+    }
+}
+
+pin_project! {
+    #[project = LazyStateProj]
+    enum LazyState<F, B> {
+        Pending { generate: Option<F> },
+        Generated { #[pin] body: B },
+    }
+}
+
+impl<F, B> LazyBody<F, B>
+where
+    F: FnOnce() -> B,
+    B: MessageBody,
+{
+    /// Constructs new body that is generated by `generate` when it is first polled.
+    #[inline]
+    pub fn new(generate: F) -> Self {
+        Self::sized(BodySize::Stream, generate)
+    }
+
+    /// Constructs new body with known size that is generated by `generate` when it is first
+    /// polled.
+    ///
+    /// The generated body must have the given size; otherwise, sending it fails.
+    #[inline]
+    pub fn sized(size: BodySize, generate: F) -> Self {
+        Self {
+            size,
+            state: LazyState::Pending {
+                generate: Some(generate),
+            },
+        }
+    }
+
+    /// Returns true if the body has been generated.
+    pub fn is_generated(&self) -> bool {
+        matches!(self.state, LazyState::Generated { .. })
+    }
+}
+
+impl<F, B> MessageBody for LazyBody<F, B>
+where
+    F: FnOnce() -> B,
+    B: MessageBody,
+{
+    type Error = B::Error;
+
+    #[inline]
+    fn size(&self) -> BodySize {
+        match &self.state {
+            LazyState::Pending { .. } => self.size,
+            LazyState::Generated { body } => body.size(),
+        }
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let mut state = self.project().state;
+
+        if let LazyStateProj::Pending { generate } = state.as_mut().project() {
+            let generate = generate.take().expect("LazyBody generator already taken");
+            state.set(LazyState::Generated { body: generate() });
+        }
+
+        match state.project() {
+            LazyStateProj::Generated { body } => body.poll_next(cx),
+            LazyStateProj::Pending { .. } => unreachable!("LazyBody was just generated"),
+        }
+    }
+
+    #[inline]
+    fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        match self.project().state.project() {
+            LazyStateProj::Generated { body } => body.poll_trailers(cx),
+            LazyStateProj::Pending { .. } => Poll::Ready(None),
+        }
+    }
+}
+
+impl<F, B: fmt::Debug> fmt::Debug for LazyBody<F, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut dbg = f.debug_struct("LazyBody");
+        dbg.field("size", &self.size);
+
+        match &self.state {
+            LazyState::Pending { .. } => dbg.field("body", &"<pending>"),
+            LazyState::Generated { body } => dbg.field("body", body),
+        };
+
+        dbg.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::body::to_bytes;
+
+    #[actix_rt::test]
+    async fn generated_on_first_poll() {
+        let calls = Cell::new(0);
+
+        let body = LazyBody::new(|| {
+            calls.set(calls.get() + 1);
+            "data"
+        });
+        assert_eq!(body.size(), BodySize::Stream);
+        assert!(!body.is_generated());
+        assert_eq!(calls.get(), 0);
+
+        assert_eq!(to_bytes(body).await.unwrap(), "data");
+        assert_eq!(calls.get(), 1);
+
+        let body = LazyBody::sized(BodySize::Sized(4), || "data");
+        assert_eq!(body.size(), BodySize::Sized(4));
+        assert!(!body.is_generated());
+    }
+}
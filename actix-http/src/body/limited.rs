@@ -0,0 +1,109 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures_core::ready;
+use pin_project_lite::pin_project;
+
+use super::{BodyLimitExceeded, BodySize, MessageBody};
+use crate::{header::HeaderMap, Error};
+
+pin_project! {
+    /// Body wrapper that fails once the wrapped body produces more than a given number of bytes.
+    ///
+    /// The chunk that would exceed the limit is not yielded; instead, an error is returned with a
+    /// [`BodyLimitExceeded`] cause.
+    ///
+    /// Created by [`MessageBodyExt::limited`](super::MessageBodyExt::limited).
+    #[derive(Debug, Clone)]
+    pub struct Limited<B> {
+        #[pin]
+        body: B,
+        remaining: u64,
+    }
+}
+
+impl<B> Limited<B>
+where
+    B: MessageBody,
+{
+    /// Constructs new body wrapper that allows `body` to produce at most `limit` bytes.
+    #[inline]
+    pub fn new(body: B, limit: u64) -> Self {
+        Self {
+            body,
+            remaining: limit,
+        }
+    }
+}
+
+impl<B> MessageBody for Limited<B>
+where
+    B: MessageBody,
+{
+    type Error = Error;
+
+    #[inline]
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.project();
+
+        match ready!(this.body.poll_next(cx)) {
+            Some(Ok(chunk)) => {
+                let len = chunk.len() as u64;
+
+                if len > *this.remaining {
+                    *this.remaining = 0;
+                    return Poll::Ready(Some(Err(Error::new_body().with_cause(BodyLimitExceeded))));
+                }
+
+                *this.remaining -= len;
+                Poll::Ready(Some(Ok(chunk)))
+            }
+
+            Some(Err(err)) => Poll::Ready(Some(Err(Error::new_body().with_cause(err)))),
+
+            None => Poll::Ready(None),
+        }
+    }
+
+    #[inline]
+    fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        self.project().body.poll_trailers(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use super::*;
+    use crate::body::{to_bytes, BodyStream};
+
+    #[actix_rt::test]
+    async fn within_limit() {
+        let body = Limited::new("test", 4);
+        assert_eq!(body.size(), BodySize::Sized(4));
+        assert_eq!(to_bytes(body).await.unwrap(), "test");
+    }
+
+    #[actix_rt::test]
+    async fn exceeds_limit() {
+        let chunks = futures_util::stream::iter([
+            Ok::<_, std::io::Error>(Bytes::from_static(b"abc")),
+            Ok(Bytes::from_static(b"def")),
+        ]);
+        let body = Limited::new(BodyStream::new(chunks), 5);
+
+        let err = to_bytes(body).await.unwrap_err();
+        assert!(err.source().unwrap().is::<BodyLimitExceeded>());
+    }
+}
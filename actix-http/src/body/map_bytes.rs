@@ -0,0 +1,123 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures_core::ready;
+use pin_project_lite::pin_project;
+
+use super::{BodySize, MessageBody};
+use crate::header::HeaderMap;
+
+pin_project! {
+    /// Body wrapper that transforms each chunk of the wrapped body.
+    ///
+    /// Since the transformation may change the length of chunks, this wrapper reports its size as
+    /// [`BodySize::Stream`] unless the wrapped body is [`BodySize::None`]. Empty chunks returned by
+    /// the transformation are skipped.
+    ///
+    /// Created by [`MessageBodyExt::map_bytes`](super::MessageBodyExt::map_bytes).
+    #[derive(Debug, Clone)]
+    pub struct MapBytes<B, F> {
+        #[pin]
+        body: B,
+        mapper: F,
+    }
+}
+
+impl<B, F> MapBytes<B, F>
+where
+    B: MessageBody,
+    F: FnMut(Bytes) -> Bytes,
+{
+    /// Constructs new body wrapper that passes each chunk of `body` through `mapper`.
+    #[inline]
+    pub fn new(body: B, mapper: F) -> Self {
+        Self { body, mapper }
+    }
+}
+
+impl<B, F> MessageBody for MapBytes<B, F>
+where
+    B: MessageBody,
+    F: FnMut(Bytes) -> Bytes,
+{
+    type Error = B::Error;
+
+    #[inline]
+    fn size(&self) -> BodySize {
+        match self.body.size() {
+            BodySize::None => BodySize::None,
+            BodySize::Sized(_) | BodySize::Stream => BodySize::Stream,
+        }
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let mut this = self.project();
+
+        loop {
+            match ready!(this.body.as_mut().poll_next(cx)) {
+                Some(Ok(chunk)) => {
+                    let chunk = (this.mapper)(chunk);
+
+                    if !chunk.is_empty() {
+                        return Poll::Ready(Some(Ok(chunk)));
+                    }
+                }
+
+                val => return Poll::Ready(val),
+            }
+        }
+    }
+
+    #[inline]
+    fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        self.project().body.poll_trailers(cx)
+    }
+
+    #[inline]
+    fn try_into_bytes(self) -> Result<Bytes, Self> {
+        let Self { body, mut mapper } = self;
+
+        match body.try_into_bytes() {
+            Ok(bytes) => Ok(mapper(bytes)),
+            Err(body) => Err(Self { body, mapper }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::{to_bytes, BodyStream};
+
+    #[actix_rt::test]
+    async fn maps_chunks() {
+        let body = MapBytes::new("test", |chunk: Bytes| {
+            Bytes::from(chunk.to_ascii_uppercase())
+        });
+        assert_eq!(body.size(), BodySize::Stream);
+        assert_eq!(to_bytes(body).await.unwrap(), "TEST");
+
+        let body = MapBytes::new(crate::body::None::new(), |_| Bytes::from_static(b"ignored"));
+        assert_eq!(body.size(), BodySize::None);
+
+        let chunks = futures_util::stream::iter([
+            Ok::<_, std::io::Error>(Bytes::from_static(b"a")),
+            Ok(Bytes::from_static(b"skip")),
+            Ok(Bytes::from_static(b"b")),
+        ]);
+        let body = MapBytes::new(BodyStream::new(chunks), |chunk: Bytes| {
+            if chunk == "skip" {
+                Bytes::new()
+            } else {
+                chunk
+            }
+        });
+        assert_eq!(to_bytes(body).await.unwrap(), "ab");
+    }
+}
@@ -12,7 +12,14 @@ use bytes::{Bytes, BytesMut};
 use futures_core::ready;
 use pin_project_lite::pin_project;
 
-use super::{BodySize, BoxBody};
+use super::{BodySize, BoxBody, Chain, FileBody, Inspect, Limited, MapBytes};
+use crate::header::HeaderMap;
+
+pub(crate) mod private {
+    /// Token that keeps `MessageBody::as_file_body` internal to this crate.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Token;
+}
 
 /// An interface for types that can be used as a response body.
 ///
@@ -86,6 +93,30 @@ pub trait MessageBody {
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<Bytes, Self::Error>>>;
 
+    /// Attempt to pull out the trailer fields to send after the body.
+    ///
+    /// This is only called once `poll_next` has returned `Ready(None)`. Returning `Ready(None)`
+    /// means there are no trailers, which is what the default implementation does.
+    ///
+    /// Trailers are sent on HTTP/2 responses and on HTTP/1.1 responses using chunked transfer
+    /// encoding; they are dropped otherwise. Senders should list the trailer fields they intend to
+    /// send in the `Trailer` header of the response (e.g., `Trailer: Server-Timing`).
+    #[inline]
+    fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        let _ = cx;
+        Poll::Ready(None)
+    }
+
+    /// Returns the [`FileBody`] backing this body, if any.
+    ///
+    /// Used by the HTTP/1 dispatcher to write file bodies to the socket directly. Not part of the
+    /// public API; it can only be called and overridden within this crate.
+    #[doc(hidden)]
+    #[inline]
+    fn as_file_body(self: Pin<&mut Self>, _: private::Token) -> Option<&mut FileBody> {
+        None
+    }
+
     /// Try to convert into the complete chunk of body bytes.
     ///
     /// Override this method if the complete body can be trivially extracted. This is useful for
@@ -119,6 +150,102 @@ pub trait MessageBody {
     }
 }
 
+/// Combinators for [`MessageBody`] types.
+///
+/// Implemented for all message bodies.
+pub trait MessageBodyExt: MessageBody {
+    /// Transforms each chunk of this body using `f`.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_http::body::{self, MessageBodyExt as _};
+    /// use bytes::Bytes;
+    ///
+    /// # actix_rt::System::new().block_on(async {
+    /// let body = "hello".map_bytes(|chunk| Bytes::from(chunk.to_ascii_uppercase()));
+    /// assert_eq!(body::to_bytes(body).await.unwrap(), "HELLO");
+    /// # });
+    /// ```
+    #[inline]
+    fn map_bytes<F>(self, f: F) -> MapBytes<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(Bytes) -> Bytes,
+    {
+        MapBytes::new(self, f)
+    }
+
+    /// Calls `f` with a reference to each chunk of this body, passing the chunks on unchanged.
+    ///
+    /// Useful in middleware for observing a response body, e.g., to record its first bytes,
+    /// without replacing its type by hand. See [`Counted`](super::Counted) for counting the bytes
+    /// sent.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_http::body::{self, MessageBodyExt as _};
+    ///
+    /// # actix_rt::System::new().block_on(async {
+    /// let mut len = 0;
+    /// let body = "hello".inspect(|chunk| len += chunk.len());
+    /// body::to_bytes(body).await.unwrap();
+    /// assert_eq!(len, 5);
+    /// # });
+    /// ```
+    #[inline]
+    fn inspect<F>(self, f: F) -> Inspect<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&Bytes),
+    {
+        Inspect::new(self, f)
+    }
+
+    /// Appends `other` to this body, yielding its chunks once this body is complete.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_http::body::{self, MessageBodyExt as _};
+    ///
+    /// # actix_rt::System::new().block_on(async {
+    /// let body = "<p>hello</p>".chain("<footer>bye</footer>");
+    /// assert_eq!(
+    ///     body::to_bytes(body).await.unwrap(),
+    ///     "<p>hello</p><footer>bye</footer>",
+    /// );
+    /// # });
+    /// ```
+    #[inline]
+    fn chain<B>(self, other: B) -> Chain<Self, B>
+    where
+        Self: Sized,
+        B: MessageBody,
+    {
+        Chain::new(self, other)
+    }
+
+    /// Fails this body once it has produced more than `limit` bytes.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_http::body::{self, MessageBodyExt as _};
+    ///
+    /// # actix_rt::System::new().block_on(async {
+    /// let body = "hello world".limited(5);
+    /// assert!(body::to_bytes(body).await.is_err());
+    /// # });
+    /// ```
+    #[inline]
+    fn limited(self, limit: u64) -> Limited<Self>
+    where
+        Self: Sized,
+    {
+        Limited::new(self, limit)
+    }
+}
+
+impl<B: MessageBody + ?Sized> MessageBodyExt for B {}
+
 mod foreign_impls {
     use std::{borrow::Cow, ops::DerefMut};
 
@@ -140,6 +267,17 @@ mod foreign_impls {
         ) -> Poll<Option<Result<Bytes, Self::Error>>> {
             Pin::new(&mut **self).poll_next(cx)
         }
+
+        fn poll_trailers(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Option<HeaderMap>> {
+            Pin::new(&mut **self).poll_trailers(cx)
+        }
+
+        fn as_file_body(self: Pin<&mut Self>, token: private::Token) -> Option<&mut FileBody> {
+            Pin::new(&mut **self.get_mut()).as_file_body(token)
+        }
     }
 
     impl MessageBody for Infallible {
@@ -197,6 +335,16 @@ mod foreign_impls {
         ) -> Poll<Option<Result<Bytes, Self::Error>>> {
             Pin::new(self.get_mut().as_mut()).poll_next(cx)
         }
+
+        #[inline]
+        fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+            Pin::new(self.get_mut().as_mut()).poll_trailers(cx)
+        }
+
+        #[inline]
+        fn as_file_body(self: Pin<&mut Self>, token: private::Token) -> Option<&mut FileBody> {
+            Pin::new(self.get_mut().as_mut()).as_file_body(token)
+        }
     }
 
     impl<T, B> MessageBody for Pin<T>
@@ -218,6 +366,11 @@ mod foreign_impls {
         ) -> Poll<Option<Result<Bytes, Self::Error>>> {
             self.get_mut().as_mut().poll_next(cx)
         }
+
+        #[inline]
+        fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+            self.get_mut().as_mut().poll_trailers(cx)
+        }
     }
 
     impl MessageBody for &'static [u8] {
@@ -520,6 +673,16 @@ where
         }
     }
 
+    #[inline]
+    fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        self.project().body.poll_trailers(cx)
+    }
+
+    #[inline]
+    fn as_file_body(self: Pin<&mut Self>, token: private::Token) -> Option<&mut FileBody> {
+        self.project().body.as_file_body(token)
+    }
+
     #[inline]
     fn try_into_bytes(self) -> Result<Bytes, Self> {
         let Self { body, mapper } = self;
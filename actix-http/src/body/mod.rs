@@ -7,21 +7,39 @@
 
 mod body_stream;
 mod boxed;
+mod chain;
+mod counted;
 mod either;
+mod file;
+mod inspect;
+mod lazy;
+mod limited;
+mod map_bytes;
 mod message_body;
 mod none;
+mod reader;
 mod size;
 mod sized_stream;
+mod trailers;
 mod utils;
 
-pub(crate) use self::message_body::MessageBodyMapErr;
+pub(crate) use self::message_body::{private, MessageBodyMapErr};
 pub use self::{
     body_stream::BodyStream,
     boxed::BoxBody,
+    chain::Chain,
+    counted::{ByteCounter, Counted},
     either::EitherBody,
-    message_body::MessageBody,
+    file::FileBody,
+    inspect::Inspect,
+    lazy::LazyBody,
+    limited::Limited,
+    map_bytes::MapBytes,
+    message_body::{MessageBody, MessageBodyExt},
     none::None,
+    reader::ReaderBody,
     size::BodySize,
     sized_stream::SizedStream,
+    trailers::WithTrailers,
     utils::{to_bytes, to_bytes_limited, BodyLimitExceeded},
 };
@@ -0,0 +1,168 @@
+use std::{
+    cmp,
+    future::Future as _,
+    io::{self, Read},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use actix_rt::task::{spawn_blocking, JoinHandle};
+use bytes::Bytes;
+use futures_core::ready;
+
+use super::{BodySize, MessageBody};
+
+/// Maximum number of bytes read from the reader per chunk.
+const MAX_CHUNK_SIZE: u64 = 65_536;
+
+/// Response body backed by a blocking reader.
+///
+/// Reads are performed in chunks on the blocking thread pool so that slow or blocking readers
+/// (e.g., pipes or decompressors over files) do not stall the async runtime.
+///
+/// # Examples
+/// ```
+/// use std::io;
+///
+/// use actix_http::{body::ReaderBody, Response};
+///
+/// let data = io::Cursor::new(b"hello world".to_vec());
+/// let res = Response::ok().set_body(ReaderBody::new(data, 11));
+/// ```
+#[derive(Debug)]
+pub struct ReaderBody<R> {
+    size: u64,
+    remaining: u64,
+    state: ReaderBodyState<R>,
+}
+
+#[derive(Debug)]
+enum ReaderBodyState<R> {
+    Reader(Option<R>),
+    Read(JoinHandle<io::Result<(R, Bytes)>>),
+}
+
+// the reader is never pinned; it is moved to the blocking thread pool for each read
+impl<R> Unpin for ReaderBody<R> {}
+
+impl<R> ReaderBody<R>
+where
+    R: Read + Send + 'static,
+{
+    /// Constructs a body containing the first `len` bytes read from `reader`.
+    ///
+    /// Sending fails with an `UnexpectedEof` error if the reader ends before `len` bytes are read.
+    pub fn new(reader: R, len: u64) -> Self {
+        Self {
+            size: len,
+            remaining: len,
+            state: ReaderBodyState::Reader(Some(reader)),
+        }
+    }
+
+    /// Returns the number of bytes of the body that have not yet been read.
+    #[cfg(target_os = "linux")]
+    pub(super) fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Returns the reader, unless it is being read from on the blocking thread pool.
+    #[cfg(target_os = "linux")]
+    pub(super) fn reader(&self) -> Option<&R> {
+        match self.state {
+            ReaderBodyState::Reader(ref reader) => reader.as_ref(),
+            ReaderBodyState::Read(_) => None,
+        }
+    }
+
+    /// Marks `n` bytes of the body as sent without reading them.
+    #[cfg(target_os = "linux")]
+    pub(super) fn consume(&mut self, n: u64) {
+        self.remaining -= n;
+    }
+
+    /// Reads the next chunk of the body on the blocking thread pool, first calling `prepare` on
+    /// the reader there.
+    pub(super) fn poll_next_chunk<F>(
+        &mut self,
+        cx: &mut Context<'_>,
+        prepare: F,
+    ) -> Poll<Option<io::Result<Bytes>>>
+    where
+        F: FnOnce(&mut R) -> io::Result<()> + Send + 'static,
+    {
+        if let ReaderBodyState::Reader(ref mut reader) = self.state {
+            if self.remaining == 0 {
+                return Poll::Ready(None);
+            }
+
+            let mut reader = reader.take().expect("ReaderBody polled after error");
+            let max_bytes = cmp::min(self.remaining, MAX_CHUNK_SIZE);
+
+            let handle = spawn_blocking(move || {
+                let mut buf = Vec::with_capacity(max_bytes as usize);
+
+                prepare(&mut reader)?;
+                let n_bytes = reader.by_ref().take(max_bytes).read_to_end(&mut buf)?;
+
+                if n_bytes == 0 {
+                    Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                } else {
+                    Ok((reader, Bytes::from(buf)))
+                }
+            });
+
+            self.state = ReaderBodyState::Read(handle);
+        }
+
+        let handle = match self.state {
+            ReaderBodyState::Read(ref mut handle) => handle,
+            ReaderBodyState::Reader(_) => unreachable!(),
+        };
+
+        let (reader, bytes) = ready!(Pin::new(handle).poll(cx)).map_err(io::Error::other)??;
+
+        self.state = ReaderBodyState::Reader(Some(reader));
+        self.remaining -= bytes.len() as u64;
+
+        Poll::Ready(Some(Ok(bytes)))
+    }
+}
+
+impl<R> MessageBody for ReaderBody<R>
+where
+    R: Read + Send + 'static,
+{
+    type Error = io::Error;
+
+    #[inline]
+    fn size(&self) -> BodySize {
+        BodySize::Sized(self.size)
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        self.get_mut().poll_next_chunk(cx, |_| Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::to_bytes;
+
+    #[actix_rt::test]
+    async fn reads_up_to_len() {
+        let data = io::Cursor::new(vec![b'a'; 100_000]);
+        let body = ReaderBody::new(data, 99_999);
+        assert_eq!(body.size(), BodySize::Sized(99_999));
+        assert_eq!(to_bytes(body).await.unwrap().len(), 99_999);
+
+        // reader ends before declared length
+        let body = ReaderBody::new(io::Cursor::new(b"abc"), 4);
+        let err = to_bytes(body).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -15,6 +15,12 @@ pin_project! {
     ///
     /// This body implementation should be used if total size of stream is known. Data is sent as-is
     /// without using chunked transfer encoding.
+    ///
+    /// The stream must yield exactly `size` bytes. If it yields more or fewer, the HTTP/1 dispatcher
+    /// aborts the connection with [`DispatchError::BodyLengthMismatch`] rather than sending a
+    /// response with broken framing.
+    ///
+    /// [`DispatchError::BodyLengthMismatch`]: crate::error::DispatchError::BodyLengthMismatch
     pub struct SizedStream<S> {
         size: u64,
         #[pin]
@@ -0,0 +1,120 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use pin_project_lite::pin_project;
+
+use super::{BodySize, MessageBody};
+use crate::header::HeaderMap;
+
+pin_project! {
+    /// Body wrapper that emits a trailer section after the wrapped body has finished.
+    ///
+    /// On HTTP/1.1, trailers can only be sent using chunked transfer encoding so, unless the
+    /// wrapped body is [`BodySize::None`], this wrapper always reports its size as
+    /// [`BodySize::Stream`]. The trailer fields are produced lazily, which allows them to include
+    /// values only known once the body has been fully sent (e.g., a checksum or `Server-Timing`
+    /// metrics).
+    ///
+    /// Responses using trailers should announce them using the `Trailer` header.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_http::{body::WithTrailers, header::HeaderMap, Response};
+    ///
+    /// let body = WithTrailers::new("hello world", || {
+    ///     let mut trailers = HeaderMap::new();
+    ///     trailers.insert(
+    ///         "server-timing".parse().unwrap(),
+    ///         "total;dur=12".parse().unwrap(),
+    ///     );
+    ///     trailers
+    /// });
+    ///
+    /// let res = Response::ok().set_body(body);
+    /// ```
+    pub struct WithTrailers<B, F> {
+        #[pin]
+        body: B,
+        trailers: Option<F>,
+    }
+}
+
+impl<B, F> WithTrailers<B, F>
+where
+    B: MessageBody,
+    F: FnOnce() -> HeaderMap,
+{
+    /// Constructs new body wrapper that calls `trailers` once `body` has been fully sent.
+    #[inline]
+    pub fn new(body: B, trailers: F) -> Self {
+        Self {
+            body,
+            trailers: Some(trailers),
+        }
+    }
+}
+
+impl<B, F> MessageBody for WithTrailers<B, F>
+where
+    B: MessageBody,
+    F: FnOnce() -> HeaderMap,
+{
+    type Error = B::Error;
+
+    #[inline]
+    fn size(&self) -> BodySize {
+        match self.body.size() {
+            BodySize::None => BodySize::None,
+            BodySize::Sized(_) | BodySize::Stream => BodySize::Stream,
+        }
+    }
+
+    #[inline]
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        self.project().body.poll_next(cx)
+    }
+
+    #[inline]
+    fn poll_trailers(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        Poll::Ready(self.project().trailers.take().map(|trailers| trailers()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_rt::pin;
+    use actix_utils::future::poll_fn;
+
+    use super::*;
+    use crate::header::{HeaderValue, SERVER};
+
+    #[actix_rt::test]
+    async fn trailers_after_body() {
+        let body = WithTrailers::new("test", || {
+            let mut trailers = HeaderMap::new();
+            trailers.insert(SERVER, HeaderValue::from_static("actix"));
+            trailers
+        });
+        assert_eq!(body.size(), BodySize::Stream);
+
+        pin!(body);
+
+        let chunk = poll_fn(|cx| body.as_mut().poll_next(cx)).await;
+        assert_eq!(chunk.unwrap().unwrap(), "test");
+        assert!(poll_fn(|cx| body.as_mut().poll_next(cx)).await.is_none());
+
+        let trailers = poll_fn(|cx| body.as_mut().poll_trailers(cx)).await.unwrap();
+        assert_eq!(trailers.get(SERVER).unwrap(), "actix");
+
+        // trailers are only yielded once
+        assert!(poll_fn(|cx| body.as_mut().poll_trailers(cx))
+            .await
+            .is_none());
+    }
+}
@@ -1,13 +1,24 @@
-use std::{fmt, marker::PhantomData, net, rc::Rc, time::Duration};
+use std::{
+    any::Any,
+    fmt,
+    marker::PhantomData,
+    net,
+    rc::Rc,
+    sync::{atomic::AtomicUsize, Arc},
+    time::Duration,
+};
 
 use actix_codec::Framed;
 use actix_service::{IntoServiceFactory, Service, ServiceFactory};
 
 use crate::{
     body::{BoxBody, MessageBody},
-    h1::{self, ExpectHandler, H1Service, UpgradeHandler},
+    callbacks::SharedCallbacks,
+    config::{BuilderOptions, PanicHandler, ResponseHeadObserver},
+    h1::{self, ExpectHandler, ExpectResponse, H1Service, UpgradeHandler},
     service::HttpService,
-    ConnectCallback, Extensions, KeepAlive, Request, Response, ServiceConfig,
+    ConnectCallback, ConnectionCallbacks, Extensions, HeaderLimits, KeepAlive, Request, Response,
+    ResponseHead, ServiceConfig, ShutdownSignal,
 };
 
 /// An HTTP service builder.
@@ -19,6 +30,7 @@ pub struct HttpServiceBuilder<T, S, X = ExpectHandler, U = UpgradeHandler> {
     client_disconnect_timeout: Duration,
     secure: bool,
     local_addr: Option<net::SocketAddr>,
+    options: BuilderOptions,
     expect: X,
     upgrade: Option<U>,
     on_connect_ext: Option<Rc<ConnectCallback<T>>>,
@@ -40,6 +52,7 @@ where
             client_disconnect_timeout: Duration::ZERO,
             secure: false,
             local_addr: None,
+            options: BuilderOptions::default(),
 
             // dispatcher parts
             expect: ExpectHandler,
@@ -56,7 +69,8 @@ where
     S::Error: Into<Response<BoxBody>> + 'static,
     S::InitError: fmt::Debug,
     <S::Service as Service<Request>>::Future: 'static,
-    X: ServiceFactory<Request, Config = (), Response = Request>,
+    X: ServiceFactory<Request, Config = ()>,
+    X::Response: Into<ExpectResponse>,
     X::Error: Into<Response<BoxBody>>,
     X::InitError: fmt::Debug,
     U: ServiceFactory<(Request, Framed<T, h1::Codec>), Config = (), Response = ()>,
@@ -124,15 +138,172 @@ where
         self.client_disconnect_timeout(dur)
     }
 
+    /// Set limits that request headers are checked against before calling the service.
+    ///
+    /// Requests violating these limits are rejected without reaching the service. See
+    /// [`HeaderLimits`] for details.
+    ///
+    /// By default, no limits are enforced.
+    pub fn header_limits(mut self, limits: HeaderLimits) -> Self {
+        self.options.header_limits = limits;
+        self
+    }
+
+    /// Set signal that starts graceful draining of HTTP/1 connections when triggered.
+    ///
+    /// See [`ShutdownSignal`] for details.
+    pub fn shutdown_signal(mut self, signal: ShutdownSignal) -> Self {
+        self.options.shutdown_signal = Some(signal);
+        self
+    }
+
+    /// Set maximum number of requests served on a single HTTP/1 connection.
+    ///
+    /// The response to the last allowed request is sent with a `Connection: close` header, after
+    /// which the connection is closed. Useful for letting load balancers re-distribute long-lived
+    /// connections.
+    ///
+    /// By default, the number of requests is not limited.
+    pub fn max_requests_per_connection(mut self, max: usize) -> Self {
+        self.options.max_requests_per_connection = Some(max);
+        self
+    }
+
+    /// Set maximum lifetime of an HTTP/1 connection.
+    ///
+    /// Once the lifetime has elapsed, the connection stops reading new requests and is closed
+    /// after the response to the in-flight request (if any) has been sent with a
+    /// `Connection: close` header.
+    ///
+    /// By default, the connection lifetime is not limited.
+    pub fn max_connection_lifetime(mut self, dur: Duration) -> Self {
+        self.options.max_connection_lifetime = Some(dur);
+        self
+    }
+
+    /// Set whether ambiguous HTTP/1 request heads are rejected.
+    ///
+    /// When enabled, requests with multiple `Host` headers and `GET` requests with a non-zero
+    /// `Content-Length` are rejected with a `400 Bad Request` response. Requests with conflicting
+    /// `Content-Length` headers are always rejected.
+    ///
+    /// By default, strict parsing is disabled.
+    pub fn strict_parsing(mut self, enabled: bool) -> Self {
+        self.options.strict_parsing = enabled;
+        self
+    }
+
+    /// Set maximum number of pipelined requests queued on an HTTP/1 connection.
+    ///
+    /// Requests that a client sends while another request is being handled are queued until all
+    /// preceding responses have been sent. A request arriving while the queue is full is answered
+    /// with a `503 Service Unavailable` response after the queued requests, and the connection is
+    /// closed. A limit of zero disables pipelining.
+    ///
+    /// By default, up to 16 requests are queued.
+    pub fn max_pipelined_requests(mut self, max: usize) -> Self {
+        self.options.max_pipelined_requests = Some(max);
+        self
+    }
+
+    /// Set maximum size, in bytes, of a serialized HTTP/1 response head (status line and headers).
+    ///
+    /// When a service produces a response whose head exceeds this size, e.g., due to oversized
+    /// cookies, the response is discarded and a `500 Internal Server Error` response is sent
+    /// instead, after which the connection is closed. Use
+    /// [`on_response_head_overflow`](Self::on_response_head_overflow) to be notified when this
+    /// happens.
+    ///
+    /// By default, the response head size is not limited.
+    pub fn max_response_head_size(mut self, size: usize) -> Self {
+        self.options.max_response_head_size = Some(size);
+        self
+    }
+
+    /// Set callback that is invoked when a response head exceeds the
+    /// [maximum response head size](Self::max_response_head_size).
+    ///
+    /// The callback receives the discarded response head and its serialized size.
+    pub fn on_response_head_overflow<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&ResponseHead, usize) + 'static,
+    {
+        self.options.response_head_overflow = Some(ResponseHeadObserver(Rc::new(f)));
+        self
+    }
+
+    /// Set callbacks that are notified of connection and request events.
+    ///
+    /// The HTTP/1 and HTTP/2 dispatchers call them when a connection is accepted, a request head
+    /// is parsed, a response head is written, a response body is complete, and the connection is
+    /// closed. See [`ConnectionCallbacks`] for details.
+    pub fn connection_callbacks<C>(mut self, callbacks: C) -> Self
+    where
+        C: ConnectionCallbacks + 'static,
+    {
+        self.options.connection_callbacks = Some(SharedCallbacks(Rc::new(callbacks)));
+        self
+    }
+
+    /// Set a counter of the requests in flight.
+    ///
+    /// A request is in flight from the moment a dispatcher passes it to the service until its
+    /// response has been completely written or abandoned. Sharing a counter between the services
+    /// of a server worker makes it track the load of that worker, which can be read from other
+    /// threads to distribute connections, shed load, or report it to external load balancers.
+    pub fn in_flight_counter(mut self, counter: Arc<AtomicUsize>) -> Self {
+        self.options.in_flight_counter = Some(counter);
+        self
+    }
+
+    /// Catch panics of the service and respond with `500 Internal Server Error` instead.
+    ///
+    /// By default, a panic while calling the service or polling its response future tears down the
+    /// connection (for HTTP/1) or the stream (for HTTP/2) it occurred on. When panics are caught,
+    /// the panic message is logged and an error response is sent in place of the response. Use
+    /// [`on_panic`](Self::on_panic) to customize this response.
+    ///
+    /// After the error response is sent, the connection is kept alive if `keep_alive` is true and
+    /// otherwise closed; for HTTP/2, closing starts a graceful shutdown of the connection. Closing
+    /// is the safer choice, since a panic may leave state shared with other requests inconsistent.
+    ///
+    /// Panics while streaming a response body are not caught.
+    pub fn catch_panics(mut self, keep_alive: bool) -> Self {
+        let handler = self.options.panic_handler.get_or_insert(PanicHandler {
+            keep_alive,
+            response: None,
+        });
+        handler.keep_alive = keep_alive;
+        self
+    }
+
+    /// Set callback that creates the response sent when a panic of the service is caught.
+    ///
+    /// The callback receives the panic payload. This enables [catching
+    /// panics](Self::catch_panics), closing the connection afterwards unless configured otherwise.
+    pub fn on_panic<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&(dyn Any + Send)) -> Response<BoxBody> + 'static,
+    {
+        let handler = self.options.panic_handler.get_or_insert(PanicHandler {
+            keep_alive: false,
+            response: None,
+        });
+        handler.response = Some(Rc::new(f));
+        self
+    }
+
     /// Provide service for `EXPECT: 100-Continue` support.
     ///
     /// Service get called with request that contains `EXPECT` header.
-    /// Service must return request in case of success, in that case
-    /// request will be forwarded to main service.
+    /// Service must return request (or [`ExpectResponse::Continue`]) in case of success, in that
+    /// case request will be forwarded to main service. Returning [`ExpectResponse::Reject`] sends
+    /// the given response instead, reading or skipping the request body as instructed.
     pub fn expect<F, X1>(self, expect: F) -> HttpServiceBuilder<T, S, X1, U>
     where
         F: IntoServiceFactory<X1, Request>,
-        X1: ServiceFactory<Request, Config = (), Response = Request>,
+        X1: ServiceFactory<Request, Config = ()>,
+        X1::Response: Into<ExpectResponse>,
         X1::Error: Into<Response<BoxBody>>,
         X1::InitError: fmt::Debug,
     {
@@ -142,6 +313,7 @@ where
             client_disconnect_timeout: self.client_disconnect_timeout,
             secure: self.secure,
             local_addr: self.local_addr,
+            options: self.options,
             expect: expect.into_factory(),
             upgrade: self.upgrade,
             on_connect_ext: self.on_connect_ext,
@@ -166,6 +338,7 @@ where
             client_disconnect_timeout: self.client_disconnect_timeout,
             secure: self.secure,
             local_addr: self.local_addr,
+            options: self.options,
             expect: self.expect,
             upgrade: Some(upgrade.into_factory()),
             on_connect_ext: self.on_connect_ext,
@@ -195,12 +368,13 @@ where
         S::InitError: fmt::Debug,
         S::Response: Into<Response<B>>,
     {
-        let cfg = ServiceConfig::new(
+        let cfg = ServiceConfig::with_builder_options(
             self.keep_alive,
             self.client_request_timeout,
             self.client_disconnect_timeout,
             self.secure,
             self.local_addr,
+            self.options,
         );
 
         H1Service::with_config(cfg, service.into_factory())
@@ -220,12 +394,13 @@ where
 
         B: MessageBody + 'static,
     {
-        let cfg = ServiceConfig::new(
+        let cfg = ServiceConfig::with_builder_options(
             self.keep_alive,
             self.client_request_timeout,
             self.client_disconnect_timeout,
             self.secure,
             self.local_addr,
+            self.options,
         );
 
         crate::h2::H2Service::with_config(cfg, service.into_factory())
@@ -242,12 +417,13 @@ where
 
         B: MessageBody + 'static,
     {
-        let cfg = ServiceConfig::new(
+        let cfg = ServiceConfig::with_builder_options(
             self.keep_alive,
             self.client_request_timeout,
             self.client_disconnect_timeout,
             self.secure,
             self.local_addr,
+            self.options,
         );
 
         HttpService::with_config(cfg, service.into_factory())
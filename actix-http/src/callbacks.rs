@@ -0,0 +1,352 @@
+use std::{
+    cell::Cell,
+    fmt, net,
+    rc::Rc,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{OnConnectData, Protocol, RequestHead, ResponseHead};
+
+/// Hooks called by the HTTP/1 and HTTP/2 dispatchers at points in the lifetime of a connection.
+///
+/// Callbacks are set with [`HttpServiceBuilder::connection_callbacks()`] and receive timings and
+/// byte counts of connections and the requests served on them, which makes them a basis for access
+/// logs and metrics that do not need to wrap every service. All methods do nothing by default.
+///
+/// Callbacks are invoked synchronously from the dispatcher and should return quickly.
+///
+/// For HTTP/2 connections, head sizes are not known and byte counts only include response body
+/// data.
+///
+/// # Examples
+/// ```
+/// # use std::convert::Infallible;
+/// use actix_http::{
+///     ConnectionCallbacks, ConnectionMeta, HttpService, Request, RequestStats, Response,
+/// };
+///
+/// struct AccessLog;
+///
+/// impl ConnectionCallbacks for AccessLog {
+///     fn on_body_complete(&self, conn: &ConnectionMeta, req: &RequestStats) {
+///         println!(
+///             "conn {}: {} body bytes in {:?}",
+///             conn.id,
+///             req.body_bytes,
+///             req.received_at.elapsed(),
+///         );
+///     }
+/// }
+///
+/// # actix_rt::System::new().block_on(async {
+/// HttpService::build()
+///     .connection_callbacks(AccessLog)
+///     .finish(|_req: Request| async { Ok::<_, Infallible>(Response::ok()) })
+///     .tcp();
+/// # });
+/// ```
+///
+/// [`HttpServiceBuilder::connection_callbacks()`]: crate::HttpServiceBuilder::connection_callbacks()
+pub trait ConnectionCallbacks {
+    /// Called when a connection is accepted and its dispatcher starts.
+    fn on_connect(&self, _conn: &ConnectionMeta) {}
+
+    /// Called when a request head has been parsed, before the request is passed to the service.
+    fn on_request(&self, _conn: &ConnectionMeta, _head: &RequestHead, _req: &RequestStats) {}
+
+    /// Called when a response head has been written to the connection.
+    ///
+    /// This also happens for error responses sent by the dispatcher itself, in which case there
+    /// might not have been a matching call to [`on_request`](Self::on_request).
+    fn on_response(&self, _conn: &ConnectionMeta, _head: &ResponseHead, _req: &RequestStats) {}
+
+    /// Called when the last chunk of a response body has been written to the connection.
+    fn on_body_complete(&self, _conn: &ConnectionMeta, _req: &RequestStats) {}
+
+    /// Called once the connection is closed or handed over to an upgrade handler.
+    ///
+    /// For HTTP/2 connections, requests can still be handled after the connection is closed, e.g.
+    /// when the client went away. This is called when the connection closes regardless, and no
+    /// further callbacks are made for those requests.
+    fn on_disconnect(&self, _conn: &ConnectionMeta, _stats: &ConnectionStats) {}
+}
+
+/// Details of a connection passed to [`ConnectionCallbacks`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ConnectionMeta {
+    /// Identifier of the connection, unique within the process.
+    pub id: u64,
+
+    /// Protocol spoken on the connection.
+    pub protocol: Protocol,
+
+    /// Peer address of the connection, if known.
+    pub peer_addr: Option<net::SocketAddr>,
+
+    /// Time at which the dispatcher started.
+    pub connected_at: Instant,
+
+    /// Details of the TLS session, if the connection was accepted by a TLS service that reports
+    /// them.
+    pub tls: Option<TlsInfo>,
+}
+
+/// Details of the TLS session of a connection.
+///
+/// These are reported by the OpenSSL and Rustls services of [`HttpService`] (such as
+/// `HttpService::openssl()` and `HttpService::rustls_0_23()`, and their `_with_config` variants),
+/// or by custom acceptors through [`HttpService::with_tls_info()`]. Besides being part of
+/// [`ConnectionMeta`], they are available to handlers through [`Request::conn_data()`].
+///
+/// The TLS services of the protocol-specific `H1Service` and `H2Service` do not report them.
+///
+/// [`HttpService`]: crate::HttpService
+/// [`HttpService::with_tls_info()`]: crate::HttpService::with_tls_info()
+/// [`Request::conn_data()`]: crate::Request::conn_data()
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct TlsInfo {
+    /// Time taken to complete the TLS handshake.
+    pub handshake_duration: Duration,
+
+    /// Negotiated protocol version, e.g. `TLSv1.3`.
+    pub protocol_version: Option<&'static str>,
+
+    /// Negotiated cipher suite, named as reported by the TLS implementation.
+    pub cipher: Option<&'static str>,
+
+    /// True if the session was resumed from an earlier one instead of doing a full handshake.
+    ///
+    /// Rustls only reports resumption since v0.23; this is always false for earlier versions.
+    pub session_resumed: bool,
+
+    /// Server name (SNI) requested by the client.
+    ///
+    /// With Encrypted Client Hello, this is the name from the inner, encrypted ClientHello.
+    pub server_name: Option<String>,
+
+    /// Server name (SNI) of the outer ClientHello, if Encrypted Client Hello was accepted.
+    ///
+    /// The built-in OpenSSL and Rustls acceptors do not support Encrypted Client Hello and never
+    /// set this; it is meant for custom acceptors that report through
+    /// [`HttpService::with_tls_info()`](crate::HttpService::with_tls_info()).
+    pub outer_server_name: Option<String>,
+}
+
+impl TlsInfo {
+    /// Constructs details of a TLS session whose handshake took `handshake_duration`.
+    ///
+    /// All other details are left unknown and can be filled in through the public fields.
+    pub fn new(handshake_duration: Duration) -> Self {
+        Self {
+            handshake_duration,
+            protocol_version: None,
+            cipher: None,
+            session_resumed: false,
+            server_name: None,
+            outer_server_name: None,
+        }
+    }
+}
+
+/// Totals of a connection passed to [`ConnectionCallbacks::on_disconnect()`].
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ConnectionStats {
+    /// Number of request heads received.
+    pub requests: u64,
+
+    /// Number of bytes read from the connection.
+    pub bytes_read: u64,
+
+    /// Number of bytes written to the connection.
+    pub bytes_written: u64,
+}
+
+/// Timings and sizes of a single request passed to [`ConnectionCallbacks`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RequestStats {
+    /// Time at which the request head was parsed.
+    pub received_at: Instant,
+
+    /// Size of the request head, in bytes.
+    pub head_size: Option<u64>,
+
+    /// Time at which the response head was written.
+    pub responded_at: Option<Instant>,
+
+    /// Size of the response head, in bytes.
+    pub response_head_size: Option<u64>,
+
+    /// Number of response body bytes written so far, excluding any transfer framing.
+    pub body_bytes: u64,
+}
+
+impl RequestStats {
+    fn new(head_size: Option<u64>) -> Self {
+        Self {
+            received_at: Instant::now(),
+            head_size,
+            responded_at: None,
+            response_head_size: None,
+            body_bytes: 0,
+        }
+    }
+}
+
+/// Callbacks set on the service builder.
+#[derive(Clone)]
+pub(crate) struct SharedCallbacks(pub(crate) Rc<dyn ConnectionCallbacks>);
+
+impl fmt::Debug for SharedCallbacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ConnectionCallbacks")
+    }
+}
+
+/// Connection state reported to [`ConnectionCallbacks`], shared by a dispatcher and its requests.
+///
+/// The disconnect callback is called by [`disconnect()`](Self::disconnect) or, at the latest, when
+/// the tracker is dropped. Requests do not report anything after the disconnect.
+pub(crate) struct ConnectionTracker {
+    callbacks: Rc<dyn ConnectionCallbacks>,
+    meta: ConnectionMeta,
+    requests: Cell<u64>,
+    bytes_read: Cell<u64>,
+    bytes_written: Cell<u64>,
+    disconnected: Cell<bool>,
+}
+
+impl ConnectionTracker {
+    /// Starts tracking a connection, if callbacks are configured.
+    pub(crate) fn start(
+        callbacks: Option<&SharedCallbacks>,
+        protocol: Protocol,
+        peer_addr: Option<net::SocketAddr>,
+        conn_data: &OnConnectData,
+    ) -> Option<Rc<Self>> {
+        static NEXT_CONN_ID: AtomicU64 = AtomicU64::new(0);
+
+        let callbacks = Rc::clone(&callbacks?.0);
+
+        let meta = ConnectionMeta {
+            id: NEXT_CONN_ID.fetch_add(1, Ordering::Relaxed),
+            protocol,
+            peer_addr,
+            connected_at: Instant::now(),
+            tls: conn_data.get::<TlsInfo>().cloned(),
+        };
+
+        callbacks.on_connect(&meta);
+
+        Some(Rc::new(Self {
+            callbacks,
+            meta,
+            requests: Cell::new(0),
+            bytes_read: Cell::new(0),
+            bytes_written: Cell::new(0),
+            disconnected: Cell::new(false),
+        }))
+    }
+
+    /// Starts tracking a request whose head has been parsed.
+    pub(crate) fn request(
+        self: &Rc<Self>,
+        head: &RequestHead,
+        head_size: Option<u64>,
+    ) -> TrackedRequest {
+        self.requests.set(self.requests.get() + 1);
+
+        let stats = RequestStats::new(head_size);
+        self.callbacks.on_request(&self.meta, head, &stats);
+
+        TrackedRequest {
+            tracker: Rc::clone(self),
+            stats,
+        }
+    }
+
+    /// Starts tracking a response that is not preceded by a parsed request.
+    pub(crate) fn untracked_request(self: &Rc<Self>) -> TrackedRequest {
+        TrackedRequest {
+            tracker: Rc::clone(self),
+            stats: RequestStats::new(None),
+        }
+    }
+
+    pub(crate) fn add_read(&self, n: usize) {
+        self.bytes_read.set(self.bytes_read.get() + n as u64);
+    }
+
+    pub(crate) fn add_written(&self, n: usize) {
+        self.bytes_written.set(self.bytes_written.get() + n as u64);
+    }
+
+    /// Reports the disconnect, unless it was already reported.
+    pub(crate) fn disconnect(&self) {
+        if self.disconnected.replace(true) {
+            return;
+        }
+
+        let stats = ConnectionStats {
+            requests: self.requests.get(),
+            bytes_read: self.bytes_read.get(),
+            bytes_written: self.bytes_written.get(),
+        };
+
+        self.callbacks.on_disconnect(&self.meta, &stats);
+    }
+}
+
+impl Drop for ConnectionTracker {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+/// A request reported to [`ConnectionCallbacks`].
+pub(crate) struct TrackedRequest {
+    tracker: Rc<ConnectionTracker>,
+    stats: RequestStats,
+}
+
+impl TrackedRequest {
+    /// Reports a written response head.
+    pub(crate) fn response(&mut self, head: &ResponseHead, head_size: Option<u64>) {
+        self.stats.responded_at = Some(Instant::now());
+        self.stats.response_head_size = head_size;
+
+        if self.tracker.disconnected.get() {
+            return;
+        }
+
+        self.tracker
+            .callbacks
+            .on_response(&self.tracker.meta, head, &self.stats);
+    }
+
+    /// Counts response body bytes.
+    pub(crate) fn add_body(&mut self, n: usize) {
+        self.stats.body_bytes += n as u64;
+    }
+
+    /// Reports a completely written response body.
+    pub(crate) fn complete(self) {
+        if self.tracker.disconnected.get() {
+            return;
+        }
+
+        self.tracker
+            .callbacks
+            .on_body_complete(&self.tracker.meta, &self.stats);
+    }
+
+    /// Returns the tracker of the connection the request was received on.
+    #[cfg(feature = "http2")]
+    pub(crate) fn tracker(&self) -> &ConnectionTracker {
+        &self.tracker
+    }
+}
@@ -1,12 +1,21 @@
 use std::{
-    net,
+    any::Any,
+    fmt, net,
+    panic::{self, AssertUnwindSafe},
     rc::Rc,
+    sync::{atomic::AtomicUsize, Arc},
     time::{Duration, Instant},
 };
 
 use bytes::BytesMut;
 
-use crate::{date::DateService, KeepAlive};
+use crate::{
+    body::BoxBody, callbacks::SharedCallbacks, date::DateService, HeaderLimits, KeepAlive,
+    Response, ResponseHead, ShutdownSignal,
+};
+
+/// Default maximum number of pipelined requests queued on an HTTP/1 connection.
+const DEFAULT_MAX_PIPELINED_REQUESTS: usize = 16;
 
 /// HTTP service configuration.
 #[derive(Debug, Clone)]
@@ -19,9 +28,78 @@ struct Inner {
     client_disconnect_timeout: Duration,
     secure: bool,
     local_addr: Option<std::net::SocketAddr>,
+    options: BuilderOptions,
     date_service: DateService,
 }
 
+/// Service configuration options that are only set through the service builder.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BuilderOptions {
+    pub(crate) header_limits: HeaderLimits,
+    pub(crate) shutdown_signal: Option<ShutdownSignal>,
+    pub(crate) max_requests_per_connection: Option<usize>,
+    pub(crate) max_connection_lifetime: Option<Duration>,
+    pub(crate) strict_parsing: bool,
+    pub(crate) max_response_head_size: Option<usize>,
+    pub(crate) max_pipelined_requests: Option<usize>,
+    pub(crate) response_head_overflow: Option<ResponseHeadObserver>,
+    pub(crate) connection_callbacks: Option<SharedCallbacks>,
+    pub(crate) in_flight_counter: Option<Arc<AtomicUsize>>,
+    pub(crate) panic_handler: Option<PanicHandler>,
+}
+
+/// Callback invoked with response heads that exceed the configured size limit and their size.
+#[derive(Clone)]
+pub(crate) struct ResponseHeadObserver(pub(crate) Rc<dyn Fn(&ResponseHead, usize)>);
+
+impl fmt::Debug for ResponseHeadObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ResponseHeadObserver")
+    }
+}
+
+/// Callback creating the response sent in place of a response whose service call panicked.
+pub(crate) type PanicResponse = Rc<dyn Fn(&(dyn Any + Send)) -> Response<BoxBody>>;
+
+/// Handling of panics caught in service calls.
+#[derive(Clone)]
+pub(crate) struct PanicHandler {
+    pub(crate) keep_alive: bool,
+    pub(crate) response: Option<PanicResponse>,
+}
+
+impl PanicHandler {
+    /// Logs a caught panic and creates the response sent in its place.
+    fn response(&self, payload: Box<dyn Any + Send>) -> Response<BoxBody> {
+        let msg = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("Box<dyn Any>");
+
+        tracing::error!("service panicked: {msg}");
+
+        let mut res = match &self.response {
+            Some(response) => response(&*payload),
+            None => Response::internal_server_error(),
+        };
+
+        if !self.keep_alive {
+            res.head_mut().set_keep_alive_policy(KeepAlive::Disabled);
+        }
+
+        res
+    }
+}
+
+impl fmt::Debug for PanicHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PanicHandler")
+            .field("keep_alive", &self.keep_alive)
+            .finish_non_exhaustive()
+    }
+}
+
 impl Default for ServiceConfig {
     fn default() -> Self {
         Self::new(
@@ -42,6 +120,25 @@ impl ServiceConfig {
         client_disconnect_timeout: Duration,
         secure: bool,
         local_addr: Option<net::SocketAddr>,
+    ) -> ServiceConfig {
+        Self::with_builder_options(
+            keep_alive,
+            client_request_timeout,
+            client_disconnect_timeout,
+            secure,
+            local_addr,
+            BuilderOptions::default(),
+        )
+    }
+
+    /// Create instance of `ServiceConfig`, including options only set through the service builder.
+    pub(crate) fn with_builder_options(
+        keep_alive: KeepAlive,
+        client_request_timeout: Duration,
+        client_disconnect_timeout: Duration,
+        secure: bool,
+        local_addr: Option<net::SocketAddr>,
+        options: BuilderOptions,
     ) -> ServiceConfig {
         ServiceConfig(Rc::new(Inner {
             keep_alive: keep_alive.normalize(),
@@ -49,6 +146,7 @@ impl ServiceConfig {
             client_disconnect_timeout,
             secure,
             local_addr,
+            options,
             date_service: DateService::new(),
         }))
     }
@@ -67,6 +165,78 @@ impl ServiceConfig {
         self.0.local_addr
     }
 
+    /// Limits checked against request headers before requests are passed to the service.
+    #[inline]
+    pub fn header_limits(&self) -> &HeaderLimits {
+        &self.0.options.header_limits
+    }
+
+    /// Signal that starts graceful draining of connections, if configured.
+    #[inline]
+    pub fn shutdown_signal(&self) -> Option<&ShutdownSignal> {
+        self.0.options.shutdown_signal.as_ref()
+    }
+
+    /// Maximum number of requests served on a single HTTP/1 connection, if limited.
+    #[inline]
+    pub fn max_requests_per_connection(&self) -> Option<usize> {
+        self.0.options.max_requests_per_connection
+    }
+
+    /// Maximum lifetime of an HTTP/1 connection, if limited.
+    #[inline]
+    pub fn max_connection_lifetime(&self) -> Option<Duration> {
+        self.0.options.max_connection_lifetime
+    }
+
+    /// Returns true if ambiguous HTTP/1 request heads are rejected.
+    #[inline]
+    pub fn strict_parsing(&self) -> bool {
+        self.0.options.strict_parsing
+    }
+
+    /// Maximum size, in bytes, of a serialized HTTP/1 response head, if limited.
+    #[inline]
+    pub fn max_response_head_size(&self) -> Option<usize> {
+        self.0.options.max_response_head_size
+    }
+
+    /// Notifies the response head overflow observer, if any.
+    pub(crate) fn response_head_overflow(&self, head: &ResponseHead, size: usize) {
+        if let Some(observer) = &self.0.options.response_head_overflow {
+            (observer.0)(head, size);
+        }
+    }
+
+    /// Runs `f`, which calls or polls the service, and converts a panic into the response to send
+    /// instead if catching panics is enabled.
+    pub(crate) fn catch_panic<T>(&self, f: impl FnOnce() -> T) -> Result<T, Response<BoxBody>> {
+        match &self.0.options.panic_handler {
+            Some(handler) => panic::catch_unwind(AssertUnwindSafe(f))
+                .map_err(|payload| handler.response(payload)),
+            None => Ok(f()),
+        }
+    }
+
+    /// Callbacks notified of connection and request events, if any.
+    pub(crate) fn connection_callbacks(&self) -> Option<&SharedCallbacks> {
+        self.0.options.connection_callbacks.as_ref()
+    }
+
+    /// Counter of requests in flight, if any.
+    pub(crate) fn in_flight_counter(&self) -> Option<&Arc<AtomicUsize>> {
+        self.0.options.in_flight_counter.as_ref()
+    }
+
+    /// Maximum number of pipelined requests queued on an HTTP/1 connection.
+    #[inline]
+    pub fn max_pipelined_requests(&self) -> usize {
+        self.0
+            .options
+            .max_pipelined_requests
+            .unwrap_or(DEFAULT_MAX_PIPELINED_REQUESTS)
+    }
+
     /// Connection keep-alive setting.
     #[inline]
     pub fn keep_alive(&self) -> KeepAlive {
@@ -78,7 +248,13 @@ impl ServiceConfig {
     ///
     /// When [`KeepAlive::Os`] or [`KeepAlive::Disabled`] is set, this will return `None`.
     pub fn keep_alive_deadline(&self) -> Option<Instant> {
-        match self.keep_alive() {
+        self.keep_alive_deadline_for(self.keep_alive())
+    }
+
+    /// Creates a time object representing the deadline for a keep-alive period using the given
+    /// policy instead of the configured one.
+    pub(crate) fn keep_alive_deadline_for(&self, keep_alive: KeepAlive) -> Option<Instant> {
+        match keep_alive {
             KeepAlive::Timeout(dur) => Some(self.now() + dur),
             KeepAlive::Os => None,
             KeepAlive::Disabled => None,
@@ -100,6 +276,12 @@ impl ServiceConfig {
         (timeout != Duration::ZERO).then(|| self.now() + timeout)
     }
 
+    /// Creates a time object representing the deadline after which a new connection is closed.
+    pub(crate) fn connection_lifetime_deadline(&self) -> Option<Instant> {
+        self.max_connection_lifetime()
+            .map(|lifetime| self.now() + lifetime)
+    }
+
     pub(crate) fn now(&self) -> Instant {
         self.0.date_service.now()
     }
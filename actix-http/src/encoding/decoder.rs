@@ -16,7 +16,7 @@ use futures_core::{ready, Stream};
 use zstd::stream::write::Decoder as ZstdDecoder;
 
 use crate::{
-    encoding::Writer,
+    encoding::{LimitExceeded, Writer},
     error::PayloadError,
     header::{ContentEncoding, HeaderMap, CONTENT_ENCODING},
 };
@@ -74,6 +74,19 @@ where
         }
     }
 
+    /// Sets the maximum number of bytes the decoded payload may contain.
+    ///
+    /// Exceeding the limit while decompressing fails the stream with [`PayloadError::Overflow`],
+    /// protecting against small payloads that decompress to huge sizes. Payloads that are not
+    /// compressed are passed through without checking the limit.
+    pub fn limit(mut self, limit: usize) -> Self {
+        if let Some(ref mut decoder) = self.decoder {
+            decoder.writer_mut().set_limit(limit);
+        }
+
+        self
+    }
+
     /// Construct decoder based on headers.
     #[inline]
     pub fn from_headers(stream: S, headers: &HeaderMap) -> Decoder<S> {
@@ -99,12 +112,14 @@ where
 
         loop {
             if let Some(ref mut fut) = this.fut {
-                let (chunk, decoder) = ready!(Pin::new(fut).poll(cx)).map_err(|_| {
-                    PayloadError::Io(io::Error::new(
-                        io::ErrorKind::Other,
-                        "Blocking task was cancelled unexpectedly",
-                    ))
-                })??;
+                let (chunk, decoder) = ready!(Pin::new(fut).poll(cx))
+                    .map_err(|_| {
+                        PayloadError::Io(io::Error::new(
+                            io::ErrorKind::Other,
+                            "Blocking task was cancelled unexpectedly",
+                        ))
+                    })?
+                    .map_err(decode_error)?;
 
                 *this.decoder = Some(decoder);
                 this.fut.take();
@@ -124,7 +139,7 @@ where
                 Some(Ok(chunk)) => {
                     if let Some(mut decoder) = this.decoder.take() {
                         if chunk.len() < MAX_CHUNK_SIZE_DECODE_IN_PLACE {
-                            let chunk = decoder.feed_data(chunk)?;
+                            let chunk = decoder.feed_data(chunk).map_err(decode_error)?;
                             *this.decoder = Some(decoder);
 
                             if let Some(chunk) = chunk {
@@ -150,7 +165,7 @@ where
                         match decoder.feed_eof() {
                             Ok(Some(res)) => Poll::Ready(Some(Ok(res))),
                             Ok(None) => Poll::Ready(None),
-                            Err(err) => Poll::Ready(Some(Err(err.into()))),
+                            Err(err) => Poll::Ready(Some(Err(decode_error(err)))),
                         }
                     } else {
                         Poll::Ready(None)
@@ -177,7 +192,33 @@ enum ContentDecoder {
     Zstd(Box<ZstdDecoder<'static, Writer>>),
 }
 
+/// Maps an error from a content decoder to a payload error.
+///
+/// Other than exceeding the limit, decoders only fail when given invalid data.
+fn decode_error(err: io::Error) -> PayloadError {
+    match err.get_ref() {
+        Some(err) if err.is::<LimitExceeded>() => PayloadError::Overflow,
+        _ => PayloadError::EncodingCorrupted,
+    }
+}
+
 impl ContentDecoder {
+    fn writer_mut(&mut self) -> &mut Writer {
+        match self {
+            #[cfg(feature = "compress-brotli")]
+            ContentDecoder::Brotli(ref mut decoder) => decoder.get_mut(),
+
+            #[cfg(feature = "compress-gzip")]
+            ContentDecoder::Gzip(ref mut decoder) => decoder.get_mut(),
+
+            #[cfg(feature = "compress-gzip")]
+            ContentDecoder::Deflate(ref mut decoder) => decoder.get_mut(),
+
+            #[cfg(feature = "compress-zstd")]
+            ContentDecoder::Zstd(ref mut decoder) => decoder.get_mut(),
+        }
+    }
+
     fn feed_eof(&mut self) -> io::Result<Option<Bytes>> {
         match self {
             #[cfg(feature = "compress-brotli")]
@@ -21,8 +21,8 @@ use zstd::stream::write::Encoder as ZstdEncoder;
 
 use super::Writer;
 use crate::{
-    body::{self, BodySize, MessageBody},
-    header::{self, ContentEncoding, HeaderValue, CONTENT_ENCODING},
+    body::{self, private, BodySize, FileBody, MessageBody},
+    header::{self, ContentEncoding, HeaderMap, HeaderValue, CONTENT_ENCODING},
     ResponseHead, StatusCode,
 };
 
@@ -141,6 +141,22 @@ where
         }
     }
 
+    #[inline]
+    fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        match self.project() {
+            EncoderBodyProj::Stream { body } => body.poll_trailers(cx),
+            _ => Poll::Ready(None),
+        }
+    }
+
+    #[inline]
+    fn as_file_body(self: Pin<&mut Self>, token: private::Token) -> Option<&mut FileBody> {
+        match self.project() {
+            EncoderBodyProj::Stream { body } => body.as_file_body(token),
+            _ => None,
+        }
+    }
+
     #[inline]
     fn try_into_bytes(self) -> Result<Bytes, Self>
     where
@@ -243,6 +259,21 @@ where
         }
     }
 
+    #[inline]
+    fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        self.project().body.poll_trailers(cx)
+    }
+
+    #[inline]
+    fn as_file_body(self: Pin<&mut Self>, token: private::Token) -> Option<&mut FileBody> {
+        // encoded bodies must go through the encoder
+        if self.encoder.is_some() || self.fut.is_some() {
+            return None;
+        }
+
+        self.project().body.as_file_body(token)
+    }
+
     #[inline]
     fn try_into_bytes(mut self) -> Result<Bytes, Self>
     where
@@ -268,7 +299,11 @@ fn update_head(encoding: ContentEncoding, head: &mut ResponseHead) {
     head.headers_mut()
         .append(header::VARY, HeaderValue::from_static("accept-encoding"));
 
-    head.no_chunking(false);
+    // encoding changes the length of the body, so bodies of a known length are chunked instead;
+    // close-delimited bodies have no length to begin with and stay close-delimited
+    if head.headers().contains_key(header::CONTENT_LENGTH) {
+        head.no_chunking(false);
+    }
 }
 
 enum ContentEncoder {
@@ -3,6 +3,7 @@
 use std::io;
 
 use bytes::{Bytes, BytesMut};
+use derive_more::derive::{Display, Error};
 
 mod decoder;
 mod encoder;
@@ -14,15 +15,25 @@ pub use self::{decoder::Decoder, encoder::Encoder};
 /// Pre-allocates 8KiB of capacity.
 struct Writer {
     buf: BytesMut,
+
+    /// Number of bytes that may still be written, if limited.
+    remaining: Option<usize>,
 }
 
 impl Writer {
     fn new() -> Writer {
         Writer {
             buf: BytesMut::with_capacity(8192),
+            remaining: None,
         }
     }
 
+    /// Limits the total number of bytes that can be written, after which writes fail with a
+    /// [`LimitExceeded`] error.
+    fn set_limit(&mut self, limit: usize) {
+        self.remaining = Some(limit);
+    }
+
     fn take(&mut self) -> Bytes {
         self.buf.split().freeze()
     }
@@ -30,6 +41,14 @@ impl Writer {
 
 impl io::Write for Writer {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(remaining) = self.remaining.as_mut() {
+            if buf.len() > *remaining {
+                return Err(io::Error::other(LimitExceeded));
+            }
+
+            *remaining -= buf.len();
+        }
+
         self.buf.extend_from_slice(buf);
         Ok(buf.len())
     }
@@ -38,3 +57,8 @@ impl io::Write for Writer {
         Ok(())
     }
 }
+
+/// Error returned by a [`Writer`] when its limit is exceeded.
+#[derive(Debug, Display, Error)]
+#[display("writer limit exceeded")]
+struct LimitExceeded;
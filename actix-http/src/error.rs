@@ -6,7 +6,7 @@ use derive_more::derive::{Display, Error, From};
 pub use http::{status::InvalidStatusCode, Error as HttpError};
 use http::{uri::InvalidUri, StatusCode};
 
-use crate::{body::BoxBody, Response};
+use crate::{body::BoxBody, header::HeaderName, Response};
 
 pub struct Error {
     inner: Box<ErrorInner>,
@@ -155,6 +155,38 @@ impl From<crate::ws::ProtocolError> for Error {
     }
 }
 
+/// Converts an `anyhow` error into an error that results in a `500 Internal Server Error`.
+///
+/// The error keeps the full chain of causes: it is displayed like `{:#}` formatting of the `anyhow`
+/// error, with causes separated by colons, and its debug output is that of the `anyhow` error.
+#[cfg(feature = "anyhow")]
+impl From<anyhow::Error> for Error {
+    fn from(err: anyhow::Error) -> Self {
+        Self::new_http().with_cause(AnyhowReport(err))
+    }
+}
+
+/// An `anyhow` error that is displayed with its chain of causes.
+#[cfg(feature = "anyhow")]
+struct AnyhowReport(anyhow::Error);
+
+#[cfg(feature = "anyhow")]
+impl fmt::Debug for AnyhowReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+#[cfg(feature = "anyhow")]
+impl fmt::Display for AnyhowReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#}", self.0)
+    }
+}
+
+#[cfg(feature = "anyhow")]
+impl StdError for AnyhowReport {}
+
 /// A set of errors that can occur during parsing HTTP streams.
 #[derive(Debug, Display, Error)]
 #[non_exhaustive]
@@ -179,10 +211,39 @@ pub enum ParseError {
     #[display("message head is too large")]
     TooLarge,
 
+    /// A request URI is longer than allowed.
+    #[display("request URI is too long")]
+    UriTooLong,
+
+    /// A message has multiple `Content-Length` headers with differing values.
+    #[display("conflicting Content-Length headers")]
+    ConflictingContentLength,
+
+    /// A request has multiple `Host` headers and strict parsing is enabled.
+    #[display("multiple Host headers")]
+    DuplicateHost,
+
+    /// A `GET` request has a non-zero `Content-Length` and strict parsing is enabled.
+    #[display("unexpected Content-Length for request method")]
+    UnexpectedContentLength,
+
+    /// A request has both `Content-Length` and chunked `Transfer-Encoding` headers and strict
+    /// parsing is enabled.
+    #[display("Content-Length and chunked Transfer-Encoding headers are both present")]
+    ContentLengthWithTransferEncoding,
+
+    /// A request header value is continued on the next line using obsolete line folding.
+    #[display("obsolete line folding in header")]
+    ObsoleteLineFolding,
+
     /// A message reached EOF, but is not complete.
     #[display("message is incomplete")]
     Incomplete,
 
+    /// A message head was still incomplete after the maximum number of parse attempts.
+    #[display("message head is incomplete after too many parse attempts")]
+    TooManyAttempts,
+
     /// An invalid `Status`, such as `1337 ELITE`.
     #[display("invalid status provided")]
     Status,
@@ -271,6 +332,10 @@ pub enum PayloadError {
     #[display("payload length is unknown")]
     UnknownLength,
 
+    /// Payload was not received fast enough.
+    #[display("payload was not received fast enough")]
+    Timeout,
+
     /// HTTP/2 payload error.
     #[cfg(feature = "http2")]
     #[display("{}", _0)]
@@ -289,6 +354,7 @@ impl std::error::Error for PayloadError {
             PayloadError::EncodingCorrupted => None,
             PayloadError::Overflow => None,
             PayloadError::UnknownLength => None,
+            PayloadError::Timeout => None,
             #[cfg(feature = "http2")]
             PayloadError::Http2Payload(err) => Some(err),
             PayloadError::Io(err) => Some(err),
@@ -362,6 +428,28 @@ pub enum DispatchError {
     #[display("handler dropped payload before reading EOF")]
     HandlerDroppedPayload,
 
+    /// Response body yielded a different number of bytes than its declared size.
+    ///
+    /// When a body yields too many bytes, `actual` is the number of bytes yielded by the time the
+    /// mismatch was detected.
+    #[display(
+        "response body length mismatch: expected {} bytes but body yielded {}",
+        expected,
+        actual
+    )]
+    #[from(ignore)]
+    BodyLengthMismatch {
+        /// Declared size of the body.
+        expected: u64,
+
+        /// Number of bytes the body yielded.
+        actual: u64,
+    },
+
+    /// Request headers were rejected by the configured [`HeaderLimits`](crate::HeaderLimits).
+    #[display("request headers rejected: {}", _0)]
+    HeaderLimit(HeaderLimitError),
+
     /// Internal error.
     #[display("internal error")]
     InternalError,
@@ -374,6 +462,7 @@ impl StdError for DispatchError {
             DispatchError::Body(err) => Some(&**err),
             DispatchError::Io(err) => Some(err),
             DispatchError::Parse(err) => Some(err),
+            DispatchError::HeaderLimit(err) => Some(err),
 
             #[cfg(feature = "http2")]
             DispatchError::H2(err) => Some(err),
@@ -383,6 +472,51 @@ impl StdError for DispatchError {
     }
 }
 
+/// A request header limit violation.
+///
+/// See [`HeaderLimits`](crate::HeaderLimits).
+#[derive(Debug, Clone, PartialEq, Eq, Display, Error)]
+#[non_exhaustive]
+pub enum HeaderLimitError {
+    /// Request sent more cookies than allowed.
+    #[display("too many cookies")]
+    TooManyCookies,
+
+    /// Request's `Cookie` headers were larger than allowed.
+    #[display("cookie headers are too large")]
+    CookiesTooLarge,
+
+    /// A header value was larger than allowed.
+    #[display("value of header {} is too large", _0)]
+    HeaderValueTooLarge(#[error(not(source))] HeaderName),
+
+    /// Request contained a forbidden header.
+    #[display("forbidden header {}", _0)]
+    ForbiddenHeader(#[error(not(source))] HeaderName),
+
+    /// Request sent more headers than allowed.
+    #[display("too many headers")]
+    TooManyHeaders,
+
+    /// Request URI was longer than allowed.
+    #[display("request URI is too long")]
+    UriTooLong,
+}
+
+impl HeaderLimitError {
+    /// Returns the response status code used when rejecting a request for this reason.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            HeaderLimitError::TooManyCookies
+            | HeaderLimitError::CookiesTooLarge
+            | HeaderLimitError::HeaderValueTooLarge(_)
+            | HeaderLimitError::TooManyHeaders => StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
+            HeaderLimitError::ForbiddenHeader(_) => StatusCode::BAD_REQUEST,
+            HeaderLimitError::UriTooLong => StatusCode::URI_TOO_LONG,
+        }
+    }
+}
+
 /// A set of error that can occur during parsing content type.
 #[derive(Debug, Display, Error)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
@@ -423,6 +557,26 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "anyhow")]
+    #[test]
+    fn test_from_anyhow() {
+        let err = anyhow::anyhow!("connection refused").context("failed to load user");
+        let err = Error::from(err);
+
+        assert_eq!(
+            err.to_string(),
+            "error processing HTTP: failed to load user: connection refused"
+        );
+
+        let debug = format!("{:?}", err);
+        assert!(debug.contains("failed to load user"));
+        assert!(debug.contains("Caused by"));
+        assert!(debug.contains("connection refused"));
+
+        let resp: Response<BoxBody> = err.into();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
     #[test]
     fn test_error_display() {
         let orig = io::Error::new(io::ErrorKind::Other, "other");
@@ -0,0 +1,334 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    io::{self, IoSlice},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::{Buf as _, Bytes, BytesMut};
+use tokio::io::AsyncWrite;
+
+/// Maximum number of buffers kept in each thread's pool.
+const MAX_POOLED_BUFFERS: usize = 128;
+
+/// Capacity range of buffers that are returned to the pool.
+///
+/// Smaller buffers are not worth keeping and larger ones would pin memory after traffic spikes.
+const MIN_POOLED_CAPACITY: usize = 1024 * 8;
+const MAX_POOLED_CAPACITY: usize = 1024 * 64;
+
+/// Payload chunks of at least this size are written from their own allocation instead of being
+/// copied into the write buffer, if the I/O supports vectored writes.
+const MIN_ZERO_COPY_LEN: usize = 1024;
+
+/// Maximum number of buffer segments passed to a single vectored write.
+const MAX_IO_SLICES: usize = 64;
+
+thread_local! {
+    static POOL: RefCell<Vec<BytesMut>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Takes an empty buffer with at least `capacity` bytes of capacity from this thread's pool,
+/// allocating a new one if the pool is empty.
+pub(crate) fn take(capacity: usize) -> BytesMut {
+    match POOL.with(|pool| pool.borrow_mut().pop()) {
+        Some(mut buf) => {
+            buf.reserve(capacity);
+            buf
+        }
+        None => BytesMut::with_capacity(capacity),
+    }
+}
+
+/// Returns a buffer to this thread's pool so that it can be reused by other connections.
+pub(crate) fn release(mut buf: BytesMut) {
+    if !(MIN_POOLED_CAPACITY..=MAX_POOLED_CAPACITY).contains(&buf.capacity()) {
+        return;
+    }
+
+    buf.clear();
+
+    POOL.with(|pool| {
+        let mut pool = pool.borrow_mut();
+
+        if pool.len() < MAX_POOLED_BUFFERS {
+            pool.push(buf);
+        }
+    });
+}
+
+/// Outgoing data of a connection.
+///
+/// Response heads and framing are encoded into a contiguous buffer. Large payload chunks are queued
+/// as-is behind the data buffered before them so that they can be written to the I/O using
+/// vectored writes, without copying.
+#[derive(Debug)]
+pub(crate) struct WriteBuf {
+    /// Segments that are written before `buf`.
+    queue: VecDeque<Bytes>,
+    buf: BytesMut,
+    len: usize,
+    vectored: bool,
+}
+
+impl WriteBuf {
+    /// Constructs new write buffer, taking its backing buffer from the pool.
+    ///
+    /// Payload chunks are only queued without copying if `vectored` is true.
+    pub(crate) fn new(capacity: usize, vectored: bool) -> Self {
+        Self {
+            queue: VecDeque::new(),
+            buf: take(capacity),
+            len: 0,
+            vectored,
+        }
+    }
+
+    /// Returns number of buffered bytes.
+    pub(crate) fn len(&self) -> usize {
+        self.len + self.buf.len()
+    }
+
+    /// Returns true if there is no buffered data.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the contiguous buffer that encoded data is appended to.
+    pub(crate) fn buf_mut(&mut self) -> &mut BytesMut {
+        &mut self.buf
+    }
+
+    /// Appends a copy of `data`.
+    pub(crate) fn extend_from_slice(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Appends `data`, avoiding a copy if it is large and vectored writes are supported.
+    pub(crate) fn put_bytes(&mut self, data: Bytes) {
+        if !self.vectored || data.len() < MIN_ZERO_COPY_LEN {
+            self.buf.extend_from_slice(&data);
+            return;
+        }
+
+        if !self.buf.is_empty() {
+            let head = self.buf.split().freeze();
+            self.len += head.len();
+            self.queue.push_back(head);
+        }
+
+        self.len += data.len();
+        self.queue.push_back(data);
+    }
+
+    /// Writes as much buffered data to `io` as possible without blocking.
+    ///
+    /// Returns `Ok(0)` only if `io` stopped accepting data.
+    pub(crate) fn poll_write_to<T: AsyncWrite>(
+        &mut self,
+        mut io: Pin<&mut T>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<usize>> {
+        let mut written = 0;
+
+        while !self.is_empty() {
+            let n = if self.queue.is_empty() {
+                io.as_mut().poll_write(cx, &self.buf)
+            } else {
+                let mut slices = [IoSlice::new(&[]); MAX_IO_SLICES];
+                let mut count = 0;
+
+                for seg in self.queue.iter().take(MAX_IO_SLICES - 1) {
+                    slices[count] = IoSlice::new(seg);
+                    count += 1;
+                }
+
+                if !self.buf.is_empty() {
+                    slices[count] = IoSlice::new(&self.buf);
+                    count += 1;
+                }
+
+                io.as_mut().poll_write_vectored(cx, &slices[..count])
+            };
+
+            match n {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Ok(0)),
+                Poll::Ready(Ok(n)) => {
+                    self.advance(n);
+                    written += n;
+                }
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending if written > 0 => break,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        Poll::Ready(Ok(written))
+    }
+
+    /// Removes `cnt` bytes from the front of the buffered data.
+    fn advance(&mut self, mut cnt: usize) {
+        while cnt > 0 {
+            let Some(seg) = self.queue.front_mut() else {
+                self.buf.advance(cnt);
+                return;
+            };
+
+            if cnt < seg.len() {
+                seg.advance(cnt);
+                self.len -= cnt;
+                return;
+            }
+
+            cnt -= seg.len();
+            self.len -= seg.len();
+            self.queue.pop_front();
+        }
+    }
+
+    /// Removes all buffered data, returning it as a contiguous buffer.
+    pub(crate) fn take(&mut self) -> BytesMut {
+        if self.queue.is_empty() {
+            return self.buf.split();
+        }
+
+        let mut out = BytesMut::with_capacity(self.len());
+
+        for seg in self.queue.drain(..) {
+            out.extend_from_slice(&seg);
+        }
+
+        out.extend_from_slice(&self.buf);
+        self.buf.clear();
+        self.len = 0;
+
+        out
+    }
+}
+
+impl Drop for WriteBuf {
+    fn drop(&mut self) {
+        release(std::mem::take(&mut self.buf));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::task::noop_waker;
+
+    use super::*;
+
+    /// Writer that accepts at most `limit` bytes per call.
+    struct Limited {
+        data: Vec<u8>,
+        limit: usize,
+        calls: usize,
+    }
+
+    impl AsyncWrite for Limited {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.poll_write_vectored(cx, &[IoSlice::new(buf)])
+        }
+
+        fn poll_write_vectored(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            bufs: &[IoSlice<'_>],
+        ) -> Poll<io::Result<usize>> {
+            self.calls += 1;
+
+            let mut n = 0;
+            for buf in bufs {
+                let len = buf.len().min(self.limit - n);
+                self.data.extend_from_slice(&buf[..len]);
+                n += len;
+            }
+
+            Poll::Ready(Ok(n))
+        }
+
+        fn is_write_vectored(&self) -> bool {
+            true
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn pool_reuses_buffers() {
+        let buf = take(MIN_POOLED_CAPACITY);
+        let ptr = buf.as_ptr();
+        release(buf);
+
+        let buf = take(MIN_POOLED_CAPACITY);
+        assert_eq!(buf.as_ptr(), ptr);
+        assert!(buf.is_empty());
+
+        // too small to be pooled
+        release(BytesMut::with_capacity(16));
+        assert!(POOL.with(|pool| pool.borrow().is_empty()));
+    }
+
+    #[test]
+    fn vectored_writes() {
+        let chunk = Bytes::from(vec![b'x'; MIN_ZERO_COPY_LEN]);
+
+        let mut buf = WriteBuf::new(MIN_POOLED_CAPACITY, true);
+        buf.extend_from_slice(b"head\r\n");
+        buf.put_bytes(chunk.clone());
+        buf.put_bytes(Bytes::from_static(b"small"));
+        buf.put_bytes(chunk.clone());
+        assert_eq!(buf.queue.len(), 4);
+        assert_eq!(buf.len(), 6 + 5 + MIN_ZERO_COPY_LEN * 2);
+
+        let mut expected = b"head\r\n".to_vec();
+        expected.extend_from_slice(&chunk);
+        expected.extend_from_slice(b"small");
+        expected.extend_from_slice(&chunk);
+
+        let mut io = Limited {
+            data: Vec::new(),
+            limit: 700,
+            calls: 0,
+        };
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let res = buf.poll_write_to(Pin::new(&mut io), &mut cx);
+        assert!(matches!(res, Poll::Ready(Ok(n)) if n == expected.len()));
+        assert!(buf.is_empty());
+        assert_eq!(io.data, expected);
+        assert_eq!(io.calls, expected.len().div_ceil(700));
+    }
+
+    #[test]
+    fn copies_without_vectored_io() {
+        let mut buf = WriteBuf::new(MIN_POOLED_CAPACITY, false);
+        buf.extend_from_slice(b"head\r\n");
+        buf.put_bytes(Bytes::from(vec![b'x'; MIN_ZERO_COPY_LEN]));
+        assert!(buf.queue.is_empty());
+
+        let mut vectored = WriteBuf::new(MIN_POOLED_CAPACITY, true);
+        vectored.extend_from_slice(b"head\r\n");
+        vectored.put_bytes(Bytes::from(vec![b'x'; MIN_ZERO_COPY_LEN]));
+        vectored.extend_from_slice(b"tail");
+
+        assert_eq!(buf.take().len() + 4, vectored.len());
+        let mut expected = b"head\r\n".to_vec();
+        expected.extend_from_slice(&[b'x'; MIN_ZERO_COPY_LEN]);
+        expected.extend_from_slice(b"tail");
+        assert_eq!(&vectored.take()[..], expected);
+        assert!(vectored.is_empty());
+    }
+}
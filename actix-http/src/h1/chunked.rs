@@ -3,6 +3,9 @@ use std::{io, task::Poll};
 use bytes::{Buf as _, Bytes, BytesMut};
 use tracing::{debug, trace};
 
+use super::decoder::{MAX_BUFFER_SIZE, MAX_HEADERS};
+use crate::header::{HeaderMap, HeaderName, HeaderValue};
+
 macro_rules! byte (
     ($rdr:ident) => ({
         if $rdr.len() > 0 {
@@ -24,8 +27,7 @@ pub(super) enum ChunkedState {
     Body,
     BodyCr,
     BodyLf,
-    EndCr,
-    EndLf,
+    Trailers,
     End,
 }
 
@@ -35,6 +37,7 @@ impl ChunkedState {
         body: &mut BytesMut,
         size: &mut u64,
         buf: &mut Option<Bytes>,
+        trailers: &mut Option<HeaderMap>,
     ) -> Poll<Result<ChunkedState, io::Error>> {
         use self::ChunkedState::*;
         match *self {
@@ -45,8 +48,7 @@ impl ChunkedState {
             Body => ChunkedState::read_body(body, size, buf),
             BodyCr => ChunkedState::read_body_cr(body),
             BodyLf => ChunkedState::read_body_lf(body),
-            EndCr => ChunkedState::read_end_cr(body),
-            EndLf => ChunkedState::read_end_lf(body),
+            Trailers => ChunkedState::read_trailers(body, trailers),
             End => Poll::Ready(Ok(ChunkedState::End)),
         }
     }
@@ -112,7 +114,7 @@ impl ChunkedState {
     fn read_size_lf(rdr: &mut BytesMut, size: u64) -> Poll<Result<ChunkedState, io::Error>> {
         match byte!(rdr) {
             b'\n' if size > 0 => Poll::Ready(Ok(ChunkedState::Body)),
-            b'\n' if size == 0 => Poll::Ready(Ok(ChunkedState::EndCr)),
+            b'\n' if size == 0 => Poll::Ready(Ok(ChunkedState::Trailers)),
             _ => Poll::Ready(Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
                 "Invalid chunk size LF",
@@ -166,23 +168,50 @@ impl ChunkedState {
             ))),
         }
     }
-    fn read_end_cr(rdr: &mut BytesMut) -> Poll<Result<ChunkedState, io::Error>> {
-        match byte!(rdr) {
-            b'\r' => Poll::Ready(Ok(ChunkedState::EndLf)),
-            _ => Poll::Ready(Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Invalid chunk end CR",
-            ))),
-        }
-    }
-    fn read_end_lf(rdr: &mut BytesMut) -> Poll<Result<ChunkedState, io::Error>> {
-        match byte!(rdr) {
-            b'\n' => Poll::Ready(Ok(ChunkedState::End)),
-            _ => Poll::Ready(Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Invalid chunk end LF",
-            ))),
-        }
+
+    /// Reads the trailer section following the last chunk, up to and including the empty line
+    /// that ends the payload.
+    fn read_trailers(
+        rdr: &mut BytesMut,
+        trailers: &mut Option<HeaderMap>,
+    ) -> Poll<Result<ChunkedState, io::Error>> {
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidInput, msg);
+
+        let mut parsed = [httparse::EMPTY_HEADER; MAX_HEADERS];
+
+        let len = match httparse::parse_headers(rdr, &mut parsed) {
+            Ok(httparse::Status::Complete((len, parsed))) => {
+                if !parsed.is_empty() {
+                    let mut map = HeaderMap::with_capacity(parsed.len());
+
+                    for header in parsed {
+                        let name = HeaderName::from_bytes(header.name.as_bytes())
+                            .map_err(|_| invalid("Invalid trailer field name"))?;
+                        let value = HeaderValue::from_bytes(header.value)
+                            .map_err(|_| invalid("Invalid trailer field value"))?;
+                        map.append(name, value);
+                    }
+
+                    *trailers = Some(map);
+                }
+
+                len
+            }
+
+            Ok(httparse::Status::Partial) if rdr.len() >= MAX_BUFFER_SIZE => {
+                return Poll::Ready(Err(invalid("Trailer section is too large")));
+            }
+
+            Ok(httparse::Status::Partial) => return Poll::Pending,
+
+            Err(err) => {
+                debug!("invalid trailer section: {err}");
+                return Poll::Ready(Err(invalid("Invalid trailer section")));
+            }
+        };
+
+        rdr.advance(len);
+        Poll::Ready(Ok(ChunkedState::End))
     }
 }
 
@@ -349,10 +378,6 @@ mod tests {
         let msg = pl.decode(&mut buf).unwrap().unwrap();
         assert_eq!(msg.chunk().as_ref(), b"li");
 
-        //trailers
-        //buf.feed_data("test: test\r\n");
-        //not_ready!(reader.parse(&mut buf, &mut readbuf));
-
         buf.extend(b"ne\r\n0\r\n");
         let msg = pl.decode(&mut buf).unwrap().unwrap();
         assert_eq!(msg.chunk().as_ref(), b"ne");
@@ -362,6 +387,55 @@ mod tests {
         assert!(pl.decode(&mut buf).unwrap().unwrap().eof());
     }
 
+    #[test]
+    fn test_http_request_chunked_payload_trailers() {
+        let mut buf = BytesMut::from(
+            "POST /test HTTP/1.1\r\n\
+             transfer-encoding: chunked\r\n\r\n\
+             4\r\ndata\r\n0\r\n\
+             server-timing: total;dur=12\r\n",
+        );
+
+        let mut reader = MessageDecoder::<Request>::default();
+        let (_req, pl) = reader.decode(&mut buf).unwrap().unwrap();
+        let mut pl = pl.unwrap();
+
+        let msg = pl.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(msg.chunk().as_ref(), b"data");
+
+        // trailer section is incomplete
+        assert!(pl.decode(&mut buf).unwrap().is_none());
+
+        buf.extend(b"x-checksum: abc\r\n\r\nGET /next HTTP/1.1\r\n\r\n");
+        let trailers = match pl.decode(&mut buf).unwrap().unwrap() {
+            PayloadItem::Trailers(trailers) => trailers,
+            item => panic!("expected trailers, got {item:?}"),
+        };
+        assert_eq!(trailers.len(), 2);
+        assert_eq!(trailers.get("server-timing").unwrap(), "total;dur=12");
+        assert_eq!(trailers.get("x-checksum").unwrap(), "abc");
+
+        assert!(pl.decode(&mut buf).unwrap().unwrap().eof());
+        assert_eq!(&buf[..], b"GET /next HTTP/1.1\r\n\r\n");
+    }
+
+    #[test]
+    fn test_http_request_chunked_payload_invalid_trailers() {
+        let mut buf = BytesMut::from(
+            "POST /test HTTP/1.1\r\n\
+             transfer-encoding: chunked\r\n\r\n\
+             0\r\n\
+             bad trailer\r\n\r\n",
+        );
+
+        let mut reader = MessageDecoder::<Request>::default();
+        let (_req, pl) = reader.decode(&mut buf).unwrap().unwrap();
+        let mut pl = pl.unwrap();
+
+        let err = pl.decode(&mut buf).unwrap_err();
+        assert!(err.to_string().contains("Invalid trailer section"));
+    }
+
     #[test]
     fn chunk_extension_quoted() {
         let mut buf = BytesMut::from(
@@ -378,7 +452,7 @@ mod tests {
         let mut pl = pl.unwrap();
 
         let chunk = pl.decode(&mut buf).unwrap().unwrap();
-        assert_eq!(chunk, PayloadItem::Chunk(Bytes::from_static(b"xx")));
+        assert_eq!(chunk.chunk(), Bytes::from_static(b"xx"));
     }
 
     #[test]
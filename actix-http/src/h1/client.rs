@@ -174,17 +174,24 @@ impl Decoder for ClientPayloadCodec {
             "Payload decoder is not specified"
         );
 
-        Ok(match self.inner.payload.as_mut().unwrap().decode(src)? {
-            Some(PayloadItem::Chunk(chunk)) => {
-                reserve_readbuf(src);
-                Some(Some(chunk))
-            }
-            Some(PayloadItem::Eof) => {
-                self.inner.payload.take();
-                Some(None)
+        loop {
+            match self.inner.payload.as_mut().unwrap().decode(src)? {
+                Some(PayloadItem::Chunk(chunk)) => {
+                    reserve_readbuf(src);
+                    return Ok(Some(Some(chunk)));
+                }
+
+                // response trailers are not exposed by the client
+                Some(PayloadItem::Trailers(_)) => {}
+
+                Some(PayloadItem::Eof) => {
+                    self.inner.payload.take();
+                    return Ok(Some(None));
+                }
+
+                None => return Ok(None),
             }
-            None => None,
-        })
+        }
     }
 }
 
@@ -1,15 +1,22 @@
 use std::{fmt, io};
 
 use bitflags::bitflags;
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use http::{Method, Version};
 use tokio_util::codec::{Decoder, Encoder};
 
 use super::{
+    buffer::WriteBuf,
     decoder::{self, PayloadDecoder, PayloadItem, PayloadType},
-    encoder, Message, MessageType,
+    encoder::{self, MessageType as _},
+    Message, MessageType,
+};
+use crate::{
+    body::BodySize,
+    error::ParseError,
+    header::{HeaderMap, HeaderValue, CONTENT_LENGTH, KEEP_ALIVE},
+    ConnectionType, KeepAlive, Request, Response, ResponseHead, ServiceConfig,
 };
-use crate::{body::BodySize, error::ParseError, ConnectionType, Request, Response, ServiceConfig};
 
 bitflags! {
     #[derive(Debug, Clone, Copy)]
@@ -20,13 +27,43 @@ bitflags! {
     }
 }
 
+/// Counters describing the requests decoded by a [`Codec`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CodecStats {
+    /// Number of request heads decoded.
+    pub requests: u64,
+
+    /// Number of decode attempts that found an incomplete request head in the buffer.
+    pub partial_parses: u64,
+
+    /// Number of request payload chunks decoded.
+    pub payload_chunks: u64,
+}
+
+/// The part of a request that a [`Codec`] will decode next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeState {
+    /// Waiting for a request head.
+    Head,
+
+    /// Decoding the payload of the last request.
+    Payload,
+}
+
 /// HTTP/1 Codec
 pub struct Codec {
     config: ServiceConfig,
     decoder: decoder::MessageDecoder<Request>,
     payload: Option<PayloadDecoder>,
+    trailers: Option<HeaderMap>,
     version: Version,
     conn_type: ConnectionType,
+    keep_alive: KeepAlive,
+    stats: CodecStats,
+    head_parse_attempts: usize,
+    max_head_parse_attempts: Option<usize>,
 
     // encoder part
     flags: Flags,
@@ -43,6 +80,7 @@ impl fmt::Debug for Codec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("h1::Codec")
             .field("flags", &self.flags)
+            .field("stats", &self.stats)
             .finish_non_exhaustive()
     }
 }
@@ -59,13 +97,53 @@ impl Codec {
         };
 
         Codec {
-            config,
             flags,
-            decoder: decoder::MessageDecoder::default(),
+            decoder: decoder::MessageDecoder::with_limits(config.header_limits().head_limits())
+                .strict(config.strict_parsing()),
             payload: None,
+            trailers: None,
             version: Version::HTTP_11,
             conn_type: ConnectionType::Close,
+            keep_alive: config.keep_alive(),
+            stats: CodecStats::default(),
+            head_parse_attempts: 0,
+            max_head_parse_attempts: None,
             encoder: encoder::MessageEncoder::default(),
+            config,
+        }
+    }
+
+    /// Sets the maximum number of attempts made to parse a single request head.
+    ///
+    /// Each call to `decode` that finds only part of a request head in the buffer counts as an
+    /// attempt. Once the limit is exceeded, decoding fails with [`ParseError::TooManyAttempts`].
+    /// This bounds the work done for clients that send request heads in many small fragments.
+    ///
+    /// By default, there is no limit.
+    pub fn max_head_parse_attempts(mut self, attempts: usize) -> Self {
+        self.max_head_parse_attempts = Some(attempts);
+        self
+    }
+
+    /// Returns the number of attempts made so far to parse the current request head.
+    #[inline]
+    pub fn head_parse_attempts(&self) -> usize {
+        self.head_parse_attempts
+    }
+
+    /// Returns counters describing the requests decoded by this codec.
+    #[inline]
+    pub fn stats(&self) -> CodecStats {
+        self.stats
+    }
+
+    /// Returns the part of a request that will be decoded next.
+    #[inline]
+    pub fn decode_state(&self) -> DecodeState {
+        if self.payload.is_some() {
+            DecodeState::Payload
+        } else {
+            DecodeState::Head
         }
     }
 
@@ -81,6 +159,14 @@ impl Codec {
         self.conn_type == ConnectionType::KeepAlive
     }
 
+    /// Returns the keep-alive policy that applies to the connection after the last response.
+    ///
+    /// This is the server's policy unless overridden by the last response.
+    #[inline]
+    pub fn keep_alive_policy(&self) -> KeepAlive {
+        self.keep_alive
+    }
+
     /// Check if keep-alive enabled on server level.
     #[inline]
     pub fn keep_alive_enabled(&self) -> bool {
@@ -103,6 +189,130 @@ impl Codec {
     pub fn config(&self) -> &ServiceConfig {
         &self.config
     }
+
+    /// Takes the trailer fields of the last decoded request payload, if any.
+    ///
+    /// Trailers are available once the end of a chunked payload (`Message::Chunk(None)`) has
+    /// been decoded.
+    pub fn take_trailers(&mut self) -> Option<HeaderMap> {
+        self.trailers.take()
+    }
+
+    /// Encodes the end of the response payload, followed by the given trailer fields.
+    ///
+    /// Trailers are only sent when the response uses chunked transfer encoding; otherwise this is
+    /// equivalent to encoding `Message::Chunk(None)`.
+    pub fn encode_trailers(&mut self, trailers: &HeaderMap, dst: &mut BytesMut) -> io::Result<()> {
+        self.encoder.encode_trailers(trailers, dst)
+    }
+
+    /// Returns the number of response payload bytes that can be written to the I/O as-is.
+    ///
+    /// Returns `None` if the payload needs framing, e.g., when using chunked transfer encoding.
+    pub(crate) fn unframed_remaining(&self) -> Option<u64> {
+        self.encoder.unframed_remaining()
+    }
+
+    /// Encodes a response payload chunk, avoiding a copy of the chunk where possible.
+    pub(crate) fn encode_chunk(&mut self, chunk: Bytes, dst: &mut WriteBuf) -> io::Result<()> {
+        self.encoder.encode_chunk_bytes(chunk, dst)?;
+        Ok(())
+    }
+
+    /// Returns true if a response is sent without a body, either because of its status or because
+    /// the last decoded request is a `HEAD` request.
+    pub(crate) fn omits_body(&self, head: &ResponseHead) -> bool {
+        self.flags.contains(Flags::HEAD) || !head.allows_body()
+    }
+
+    /// Encodes the head of an informational (1xx) response without affecting the codec's state.
+    pub(crate) fn encode_informational(
+        &self,
+        mut res: Response<()>,
+        dst: &mut BytesMut,
+    ) -> io::Result<()> {
+        res.head_mut().version = Version::HTTP_11;
+        res.encode_status(dst)?;
+        res.encode_headers(
+            dst,
+            Version::HTTP_11,
+            BodySize::None,
+            ConnectionType::KeepAlive,
+            &self.config,
+        )
+    }
+
+    /// Encodes the head of a response, updating the codec's connection state and preparing the
+    /// payload encoder for a body of the given size.
+    pub(crate) fn encode_response(
+        &mut self,
+        res: &mut Response<()>,
+        length: BodySize,
+        dst: &mut BytesMut,
+    ) -> io::Result<()> {
+        // set response version
+        res.head_mut().version = self.version;
+
+        let omits_body = self.omits_body(res.head());
+
+        // connection status
+        self.conn_type = if let Some(ct) = res.head().conn_type() {
+            if ct == ConnectionType::KeepAlive {
+                self.conn_type
+            } else {
+                ct
+            }
+        } else {
+            self.conn_type
+        };
+
+        // HTTP/1.0 clients do not understand chunked transfer encoding
+        if self.version < Version::HTTP_11 {
+            res.head_mut().no_chunking(true);
+        }
+
+        // streaming bodies that are not chunked and have no explicit length are delimited by
+        // closing the connection instead
+        if length == BodySize::Stream
+            && !omits_body
+            && !res.head().chunked()
+            && !res.headers().contains_key(CONTENT_LENGTH)
+            && self.conn_type == ConnectionType::KeepAlive
+        {
+            self.conn_type = ConnectionType::Close;
+        }
+
+        // keep-alive policy
+        let keep_alive = res.head().keep_alive_policy();
+        self.keep_alive = keep_alive.unwrap_or_else(|| self.config.keep_alive());
+
+        if let (ConnectionType::KeepAlive, Some(KeepAlive::Timeout(dur))) =
+            (self.conn_type, keep_alive)
+        {
+            // round up so that sub-second timeouts are not advertised as zero
+            let secs = dur.as_secs() + u64::from(dur.subsec_nanos() > 0);
+            let value = format!("timeout={secs}");
+            res.headers_mut()
+                .insert(KEEP_ALIVE, HeaderValue::from_str(&value).unwrap());
+        }
+
+        // encode message
+        self.encoder.encode(
+            dst,
+            res,
+            omits_body,
+            self.flags.contains(Flags::STREAM),
+            self.version,
+            length,
+            self.conn_type,
+            &self.config,
+        )
+    }
+
+    /// Records response payload bytes that were written to the I/O directly.
+    pub(crate) fn consume_unframed(&mut self, len: u64) {
+        self.encoder.consume_unframed(len)
+    }
 }
 
 impl Decoder for Codec {
@@ -111,15 +321,28 @@ impl Decoder for Codec {
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
         if let Some(ref mut payload) = self.payload {
-            Ok(match payload.decode(src)? {
-                Some(PayloadItem::Chunk(chunk)) => Some(Message::Chunk(Some(chunk))),
-                Some(PayloadItem::Eof) => {
-                    self.payload.take();
-                    Some(Message::Chunk(None))
+            loop {
+                match payload.decode(src)? {
+                    Some(PayloadItem::Chunk(chunk)) => {
+                        self.stats.payload_chunks += 1;
+                        return Ok(Some(Message::Chunk(Some(chunk))));
+                    }
+
+                    // kept until the end of payload is decoded
+                    Some(PayloadItem::Trailers(trailers)) => self.trailers = Some(trailers),
+
+                    Some(PayloadItem::Eof) => {
+                        self.payload.take();
+                        return Ok(Some(Message::Chunk(None)));
+                    }
+
+                    None => return Ok(None),
                 }
-                None => None,
-            })
+            }
         } else if let Some((req, payload)) = self.decoder.decode(src)? {
+            self.stats.requests += 1;
+            self.head_parse_attempts = 0;
+
             let head = req.head();
             self.flags.set(Flags::HEAD, head.method == Method::HEAD);
             self.version = head.version;
@@ -141,6 +364,17 @@ impl Decoder for Codec {
             }
             Ok(Some(Message::Item(req)))
         } else {
+            // an empty buffer is not an attempt at parsing a head
+            if !src.is_empty() {
+                self.stats.partial_parses += 1;
+                self.head_parse_attempts += 1;
+
+                if matches!(self.max_head_parse_attempts, Some(max) if self.head_parse_attempts > max)
+                {
+                    return Err(ParseError::TooManyAttempts);
+                }
+            }
+
             Ok(None)
         }
     }
@@ -156,31 +390,7 @@ impl Encoder<Message<(Response<()>, BodySize)>> for Codec {
     ) -> Result<(), Self::Error> {
         match item {
             Message::Item((mut res, length)) => {
-                // set response version
-                res.head_mut().version = self.version;
-
-                // connection status
-                self.conn_type = if let Some(ct) = res.head().conn_type() {
-                    if ct == ConnectionType::KeepAlive {
-                        self.conn_type
-                    } else {
-                        ct
-                    }
-                } else {
-                    self.conn_type
-                };
-
-                // encode message
-                self.encoder.encode(
-                    dst,
-                    &mut res,
-                    self.flags.contains(Flags::HEAD),
-                    self.flags.contains(Flags::STREAM),
-                    self.version,
-                    length,
-                    self.conn_type,
-                    &self.config,
-                )?;
+                self.encode_response(&mut res, length, dst)?;
             }
 
             Message::Chunk(Some(bytes)) => {
@@ -201,6 +411,44 @@ mod tests {
     use super::*;
     use crate::HttpMessage as _;
 
+    #[actix_rt::test]
+    async fn stats_and_parse_attempts() {
+        let mut codec = Codec::default().max_head_parse_attempts(2);
+        assert_eq!(codec.decode_state(), DecodeState::Head);
+
+        let mut buf = BytesMut::from("POST /test HTTP/1.1\r\n");
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        assert_eq!(codec.head_parse_attempts(), 1);
+
+        buf.extend_from_slice(b"content-length: 4\r\n\r\ndata");
+        codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(codec.head_parse_attempts(), 0);
+        assert_eq!(codec.decode_state(), DecodeState::Payload);
+
+        codec.decode(&mut buf).unwrap().unwrap();
+        codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(codec.decode_state(), DecodeState::Head);
+
+        // empty buffer does not count as an attempt
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+
+        let stats = codec.stats();
+        assert_eq!(stats.requests, 1);
+        assert_eq!(stats.partial_parses, 1);
+        assert_eq!(stats.payload_chunks, 1);
+
+        for _ in 0..2 {
+            buf.extend_from_slice(b"G");
+            assert!(codec.decode(&mut buf).unwrap().is_none());
+        }
+
+        buf.extend_from_slice(b"E");
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ParseError::TooManyAttempts)
+        ));
+    }
+
     #[actix_rt::test]
     async fn test_http_request_chunked_payload_and_next_message() {
         let mut codec = Codec::default();
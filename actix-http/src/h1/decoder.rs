@@ -12,10 +12,54 @@ use super::chunked::ChunkedState;
 use crate::{error::ParseError, header::HeaderMap, ConnectionType, Request, ResponseHead};
 
 pub(crate) const MAX_BUFFER_SIZE: usize = 131_072;
-const MAX_HEADERS: usize = 96;
+pub(crate) const MAX_HEADERS: usize = 96;
 
 /// Incoming message decoder
-pub(crate) struct MessageDecoder<T: MessageType>(PhantomData<T>);
+pub(crate) struct MessageDecoder<T: MessageType> {
+    limits: HeadLimits,
+    strict: bool,
+    _phantom: PhantomData<T>,
+}
+
+/// Limits enforced while parsing a message head.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct HeadLimits {
+    /// Maximum number of headers.
+    pub(crate) max_headers: usize,
+
+    /// Maximum size of the message head, in bytes.
+    pub(crate) max_head_size: usize,
+
+    /// Maximum length of the request target (URI), in bytes.
+    pub(crate) max_uri_length: Option<usize>,
+}
+
+impl Default for HeadLimits {
+    fn default() -> Self {
+        Self {
+            max_headers: MAX_HEADERS,
+            max_head_size: MAX_BUFFER_SIZE,
+            max_uri_length: None,
+        }
+    }
+}
+
+impl<T: MessageType> MessageDecoder<T> {
+    /// Constructs new message decoder that enforces the given head limits.
+    pub(crate) fn with_limits(limits: HeadLimits) -> Self {
+        Self {
+            limits,
+            strict: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Enables rejection of ambiguous message heads that are otherwise tolerated.
+    pub(crate) fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+}
 
 #[derive(Debug)]
 /// Incoming request type
@@ -27,7 +71,7 @@ pub(crate) enum PayloadType {
 
 impl<T: MessageType> Default for MessageDecoder<T> {
     fn default() -> Self {
-        MessageDecoder(PhantomData)
+        Self::with_limits(HeadLimits::default())
     }
 }
 
@@ -36,7 +80,7 @@ impl<T: MessageType> Decoder for MessageDecoder<T> {
     type Error = ParseError;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        T::decode(src)
+        T::decode(src, &self.limits, self.strict)
     }
 }
 
@@ -52,6 +96,16 @@ impl PayloadLength {
         matches!(self, Self::None)
     }
 
+    /// Returns payload length if it is set by a `Content-Length` header.
+    fn content_length(&self) -> Option<u64> {
+        match self {
+            PayloadLength::Payload(PayloadType::Payload(PayloadDecoder {
+                kind: Kind::Length(len),
+            })) => Some(*len),
+            _ => None,
+        }
+    }
+
     /// Returns true if variant is represents zero-length (not none) payload.
     fn is_zero(&self) -> bool {
         matches!(
@@ -70,19 +124,25 @@ pub(crate) trait MessageType: Sized {
 
     fn headers_mut(&mut self) -> &mut HeaderMap;
 
-    fn decode(src: &mut BytesMut) -> Result<Option<(Self, PayloadType)>, ParseError>;
+    fn decode(
+        src: &mut BytesMut,
+        limits: &HeadLimits,
+        strict: bool,
+    ) -> Result<Option<(Self, PayloadType)>, ParseError>;
 
     fn set_headers(
         &mut self,
         slice: &Bytes,
         raw_headers: &[HeaderIndex],
         version: Version,
+        strict: bool,
     ) -> Result<PayloadLength, ParseError> {
         let mut ka = None;
         let mut has_upgrade_websocket = false;
         let mut expect = false;
         let mut chunked = false;
         let mut seen_te = false;
+        let mut seen_host = false;
         let mut content_length = None;
 
         {
@@ -98,33 +158,40 @@ pub(crate) trait MessageType: Sized {
                 };
 
                 match name {
-                    header::CONTENT_LENGTH if content_length.is_some() => {
-                        debug!("multiple Content-Length");
-                        return Err(ParseError::Header);
-                    }
+                    header::CONTENT_LENGTH => {
+                        let len = match value.to_str().map(str::trim) {
+                            Ok(val) if !val.starts_with('+') => val.parse::<u64>().ok(),
+                            _ => None,
+                        };
 
-                    header::CONTENT_LENGTH => match value.to_str().map(str::trim) {
-                        Ok(val) if val.starts_with('+') => {
-                            debug!("illegal Content-Length: {:?}", val);
+                        let Some(len) = len else {
+                            debug!("illegal Content-Length: {:?}", value);
                             return Err(ParseError::Header);
-                        }
+                        };
 
-                        Ok(val) => {
-                            if let Ok(len) = val.parse::<u64>() {
-                                // accept 0 lengths here and remove them in `decode` after all
-                                // headers have been processed to prevent request smuggling issues
-                                content_length = Some(len);
-                            } else {
-                                debug!("illegal Content-Length: {:?}", val);
+                        match content_length {
+                            Some(prev) if prev != len => {
+                                debug!("conflicting Content-Length values: {prev} and {len}");
+                                return Err(ParseError::ConflictingContentLength);
+                            }
+
+                            Some(_) => {
+                                debug!("multiple Content-Length");
                                 return Err(ParseError::Header);
                             }
-                        }
 
-                        Err(_) => {
-                            debug!("illegal Content-Length: {:?}", value);
-                            return Err(ParseError::Header);
+                            // accept 0 lengths here and remove them in `decode` after all
+                            // headers have been processed to prevent request smuggling issues
+                            None => content_length = Some(len),
                         }
-                    },
+                    }
+
+                    header::HOST if strict && seen_host => {
+                        debug!("multiple Host headers");
+                        return Err(ParseError::DuplicateHost);
+                    }
+
+                    header::HOST => seen_host = true,
 
                     // transfer-encoding
                     header::TRANSFER_ENCODING if seen_te => {
@@ -188,6 +255,12 @@ pub(crate) trait MessageType: Sized {
             }
         }
 
+        // a message with both could be framed differently by an intermediary
+        if strict && chunked && content_length.is_some() {
+            debug!("Content-Length specified alongside chunked Transfer-Encoding");
+            return Err(ParseError::ContentLengthWithTransferEncoding);
+        }
+
         self.set_connection_type(ka);
 
         if expect {
@@ -228,39 +301,86 @@ impl MessageType for Request {
         &mut self.head_mut().headers
     }
 
-    fn decode(src: &mut BytesMut) -> Result<Option<(Self, PayloadType)>, ParseError> {
+    fn decode(
+        src: &mut BytesMut,
+        limits: &HeadLimits,
+        strict: bool,
+    ) -> Result<Option<(Self, PayloadType)>, ParseError> {
         let mut headers: [HeaderIndex; MAX_HEADERS] = EMPTY_HEADER_INDEX_ARRAY;
+        let mut headers_heap = Vec::new();
+
+        // SAFETY:
+        // Create an uninitialized array of `MaybeUninit`. The `assume_init` is safe because the
+        // type we are claiming to have initialized here is a bunch of `MaybeUninit`s, which
+        // do not require initialization.
+        let mut parsed = unsafe {
+            MaybeUninit::<[MaybeUninit<httparse::Header<'_>>; MAX_HEADERS]>::uninit().assume_init()
+        };
+        let mut parsed_heap = Vec::new();
+
+        // only allocate when more headers are allowed than fit on the stack
+        let (headers, parsed) = if limits.max_headers <= MAX_HEADERS {
+            (
+                &mut headers[..limits.max_headers],
+                &mut parsed[..limits.max_headers],
+            )
+        } else {
+            headers_heap.resize(limits.max_headers, EMPTY_HEADER_INDEX);
+            parsed_heap.resize_with(limits.max_headers, MaybeUninit::uninit);
+            (&mut headers_heap[..], &mut parsed_heap[..])
+        };
 
         let (len, method, uri, ver, h_len) = {
-            // SAFETY:
-            // Create an uninitialized array of `MaybeUninit`. The `assume_init` is safe because the
-            // type we are claiming to have initialized here is a bunch of `MaybeUninit`s, which
-            // do not require initialization.
-            let mut parsed = unsafe {
-                MaybeUninit::<[MaybeUninit<httparse::Header<'_>>; MAX_HEADERS]>::uninit()
-                    .assume_init()
-            };
-
             let mut req = httparse::Request::new(&mut []);
 
-            match req.parse_with_uninit_headers(src, &mut parsed)? {
+            let status = req.parse_with_uninit_headers(src, parsed).map_err(|err| {
+                if has_obs_fold(src) {
+                    debug!("obsolete line folding in request head");
+                    ParseError::ObsoleteLineFolding
+                } else {
+                    err.into()
+                }
+            })?;
+
+            match status {
                 httparse::Status::Complete(len) => {
+                    if len > limits.max_head_size {
+                        trace!("request head larger than {} bytes", limits.max_head_size);
+                        return Err(ParseError::TooLarge);
+                    }
+
+                    let path = req.path.unwrap();
+
+                    if let Some(max) = limits.max_uri_length {
+                        if path.len() > max {
+                            trace!("request URI longer than {max} bytes: {path}");
+                            return Err(ParseError::UriTooLong);
+                        }
+                    }
+
                     let method = Method::from_bytes(req.method.unwrap().as_bytes())
                         .map_err(|_| ParseError::Method)?;
-                    let uri = Uri::try_from(req.path.unwrap())?;
+                    let uri = Uri::try_from(path)?;
                     let version = if req.version.unwrap() == 1 {
                         Version::HTTP_11
                     } else {
                         Version::HTTP_10
                     };
-                    HeaderIndex::record(src, req.headers, &mut headers);
+                    HeaderIndex::record(src, req.headers, headers);
 
                     (len, method, uri, version, req.headers.len())
                 }
 
                 httparse::Status::Partial => {
-                    return if src.len() >= MAX_BUFFER_SIZE {
-                        trace!("MAX_BUFFER_SIZE unprocessed data reached, closing");
+                    if let Some(max) = limits.max_uri_length {
+                        if partial_uri_too_long(src, max) {
+                            trace!("request URI longer than {max} bytes");
+                            return Err(ParseError::UriTooLong);
+                        }
+                    }
+
+                    return if src.len() >= limits.max_head_size {
+                        trace!("maximum request head size reached, closing");
                         Err(ParseError::TooLarge)
                     } else {
                         // Return None to notify more read are needed for parsing request
@@ -273,7 +393,17 @@ impl MessageType for Request {
         let mut msg = Request::new();
 
         // convert headers
-        let mut length = msg.set_headers(&src.split_to(len).freeze(), &headers[..h_len], ver)?;
+        let mut length =
+            msg.set_headers(&src.split_to(len).freeze(), &headers[..h_len], ver, strict)?;
+
+        // GET requests have no defined body semantics; a body could be used for request smuggling
+        if strict
+            && method == Method::GET
+            && matches!(length.content_length(), Some(len) if len > 0)
+        {
+            debug!("Content-Length specified for GET request");
+            return Err(ParseError::UnexpectedContentLength);
+        }
 
         // disallow HTTP/1.0 POST requests that do not contain a Content-Length headers
         // see https://datatracker.ietf.org/doc/html/rfc1945#section-7.2.2
@@ -327,7 +457,11 @@ impl MessageType for ResponseHead {
         &mut self.headers
     }
 
-    fn decode(src: &mut BytesMut) -> Result<Option<(Self, PayloadType)>, ParseError> {
+    fn decode(
+        src: &mut BytesMut,
+        _limits: &HeadLimits,
+        _strict: bool,
+    ) -> Result<Option<(Self, PayloadType)>, ParseError> {
         let mut headers: [HeaderIndex; MAX_HEADERS] = EMPTY_HEADER_INDEX_ARRAY;
 
         let (len, ver, status, h_len) = {
@@ -375,7 +509,8 @@ impl MessageType for ResponseHead {
         msg.version = ver;
 
         // convert headers
-        let mut length = msg.set_headers(&src.split_to(len).freeze(), &headers[..h_len], ver)?;
+        let mut length =
+            msg.set_headers(&src.split_to(len).freeze(), &headers[..h_len], ver, false)?;
 
         // Remove CL value if 0 now that all headers and HTTP/1.0 special cases are processed.
         // Protects against some request smuggling attacks.
@@ -385,7 +520,11 @@ impl MessageType for ResponseHead {
         }
 
         // message payload
-        let decoder = if let PayloadLength::Payload(pl) = length {
+        let decoder = if matches!(status, StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED) {
+            // never have a body, regardless of any Content-Length header
+            // see https://datatracker.ietf.org/doc/html/rfc9112#section-6.3
+            PayloadType::None
+        } else if let PayloadLength::Payload(pl) = length {
             pl
         } else if status == StatusCode::SWITCHING_PROTOCOLS {
             // switching protocol or connect
@@ -436,10 +575,43 @@ impl HeaderIndex {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Returns true if the request target in a partially received request line is already longer
+/// than `max` bytes.
+fn partial_uri_too_long(src: &[u8], max: usize) -> bool {
+    let Some(method_end) = src.iter().position(|&b| b == b' ') else {
+        return false;
+    };
+
+    let target = &src[method_end + 1..];
+
+    target.len() > max
+        && !target[..=max]
+            .iter()
+            .any(|&b| b == b' ' || b == b'\r' || b == b'\n')
+}
+
+/// Returns true if a header line in the (possibly partial) message head in `src` is continued on
+/// the next line using obsolete line folding.
+fn has_obs_fold(src: &[u8]) -> bool {
+    let head = src
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map_or(src, |end| &src[..end]);
+
+    head.windows(2)
+        .any(|window| window[0] == b'\n' && matches!(window[1], b' ' | b'\t'))
+}
+
 /// Chunk type yielded while decoding a payload.
+#[derive(Debug, Clone)]
 pub enum PayloadItem {
+    /// Payload data, with any transfer encoding framing removed.
     Chunk(Bytes),
+
+    /// Trailer fields of a chunked payload; always followed by `Eof`.
+    Trailers(HeaderMap),
+
+    /// End of payload.
     Eof,
 }
 
@@ -527,14 +699,19 @@ impl Decoder for PayloadDecoder {
             Kind::Chunked(ref mut state, ref mut size) => {
                 loop {
                     let mut buf = None;
+                    let mut trailers = None;
 
                     // advances the chunked state
-                    *state = match state.step(src, size, &mut buf) {
+                    *state = match state.step(src, size, &mut buf, &mut trailers) {
                         Poll::Pending => return Ok(None),
                         Poll::Ready(Ok(state)) => state,
                         Poll::Ready(Err(err)) => return Err(err),
                     };
 
+                    if let Some(trailers) = trailers {
+                        return Ok(Some(PayloadItem::Trailers(trailers)));
+                    }
+
                     if *state == ChunkedState::End {
                         trace!("End of chunked stream");
                         return Ok(Some(PayloadItem::Eof));
@@ -643,6 +820,82 @@ mod tests {
         assert_eq!(req.path(), "/test");
     }
 
+    #[test]
+    fn parse_header_count_limit() {
+        let limits = HeadLimits {
+            max_headers: 2,
+            ..HeadLimits::default()
+        };
+        let mut reader = MessageDecoder::<Request>::with_limits(limits);
+
+        let mut buf = BytesMut::from("GET /test HTTP/1.1\r\na: 1\r\nb: 2\r\n\r\n");
+        assert!(reader.decode(&mut buf).unwrap().is_some());
+
+        let mut buf = BytesMut::from("GET /test HTTP/1.1\r\na: 1\r\nb: 2\r\nc: 3\r\n\r\n");
+        assert!(matches!(reader.decode(&mut buf), Err(ParseError::TooLarge)));
+
+        // more headers than fit in the stack allocated buffers
+        let limits = HeadLimits {
+            max_headers: MAX_HEADERS + 4,
+            ..HeadLimits::default()
+        };
+        let mut reader = MessageDecoder::<Request>::with_limits(limits);
+
+        let mut buf = BytesMut::from("GET /test HTTP/1.1\r\n");
+        for idx in 0..MAX_HEADERS + 4 {
+            buf.extend(format!("x-{idx}: {idx}\r\n").as_bytes());
+        }
+        buf.extend(b"\r\n");
+
+        let (req, _) = reader.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(req.headers().len(), MAX_HEADERS + 4);
+    }
+
+    #[test]
+    fn parse_head_size_limit() {
+        let limits = HeadLimits {
+            max_head_size: 32,
+            ..HeadLimits::default()
+        };
+        let mut reader = MessageDecoder::<Request>::with_limits(limits);
+
+        let mut buf = BytesMut::from("GET /test HTTP/1.1\r\na: 1\r\n\r\n");
+        assert!(reader.decode(&mut buf).unwrap().is_some());
+
+        let mut buf = BytesMut::from("GET /test HTTP/1.1\r\nabc: 123456\r\n\r\n");
+        assert!(matches!(reader.decode(&mut buf), Err(ParseError::TooLarge)));
+
+        let mut buf = BytesMut::from("GET /test HTTP/1.1\r\nabc: 1234567890123");
+        assert!(matches!(reader.decode(&mut buf), Err(ParseError::TooLarge)));
+    }
+
+    #[test]
+    fn parse_uri_length_limit() {
+        let limits = HeadLimits {
+            max_uri_length: Some(8),
+            ..HeadLimits::default()
+        };
+        let mut reader = MessageDecoder::<Request>::with_limits(limits);
+
+        let mut buf = BytesMut::from("GET /a?b=123 HTTP/1.1\r\n\r\n");
+        assert!(reader.decode(&mut buf).unwrap().is_some());
+
+        let mut buf = BytesMut::from("GET /a?b=1234 HTTP/1.1\r\n\r\n");
+        assert!(matches!(
+            reader.decode(&mut buf),
+            Err(ParseError::UriTooLong)
+        ));
+
+        // rejected before the request line is complete
+        let mut buf = BytesMut::from("GET /a?b=12");
+        assert!(reader.decode(&mut buf).unwrap().is_none());
+        buf.extend(b"34");
+        assert!(matches!(
+            reader.decode(&mut buf),
+            Err(ParseError::UriTooLong)
+        ));
+    }
+
     #[test]
     fn parse_h09_reject() {
         let mut buf = BytesMut::from(
@@ -1063,7 +1316,22 @@ mod tests {
         let mut pl = pl.unwrap();
 
         let chunk = pl.decode(&mut buf).unwrap().unwrap();
-        assert_eq!(chunk, PayloadItem::Chunk(Bytes::from_static(b"test data")));
+        assert_eq!(chunk.chunk(), Bytes::from_static(b"test data"));
+    }
+
+    #[test]
+    fn test_response_no_body_statuses() {
+        for status in ["204 No Content", "304 Not Modified"] {
+            let mut buf = BytesMut::from(
+                format!("HTTP/1.1 {status}\r\nContent-Length: 4\r\n\r\nHTTP/1.1 200 OK\r\n\r\n")
+                    .as_str(),
+            );
+
+            let mut reader = MessageDecoder::<ResponseHead>::default();
+            let (_msg, pl) = reader.decode(&mut buf).unwrap().unwrap();
+            assert!(matches!(pl, PayloadType::None));
+            assert_eq!(&buf[..], b"HTTP/1.1 200 OK\r\n\r\n");
+        }
     }
 
     #[test]
@@ -1087,6 +1355,117 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn conflicting_headers() {
+        let mut buf = BytesMut::from(
+            "POST / HTTP/1.1\r\n\
+            Content-Length: 4\r\n\
+            Content-Length: 2\r\n\
+            \r\n",
+        );
+        let res = MessageDecoder::<Request>::default().decode(&mut buf);
+        assert!(matches!(res, Err(ParseError::ConflictingContentLength)));
+
+        // identical values are rejected without the specific error
+        let mut buf = BytesMut::from(
+            "POST / HTTP/1.1\r\n\
+            Content-Length: 4\r\n\
+            Content-Length: 4\r\n\
+            \r\n",
+        );
+        let res = MessageDecoder::<Request>::default().decode(&mut buf);
+        assert!(matches!(res, Err(ParseError::Header)));
+
+        let head = "GET / HTTP/1.1\r\n\
+            Host: example.com\r\n\
+            Host: example.org\r\n\
+            \r\n";
+        let mut reader = MessageDecoder::<Request>::default();
+        let (req, _) = reader.decode(&mut BytesMut::from(head)).unwrap().unwrap();
+        assert_eq!(req.headers().get_all(header::HOST).count(), 2);
+
+        let mut reader = MessageDecoder::<Request>::default().strict(true);
+        let res = reader.decode(&mut BytesMut::from(head));
+        assert!(matches!(res, Err(ParseError::DuplicateHost)));
+    }
+
+    #[test]
+    fn strict_content_length_with_transfer_encoding() {
+        let head = "POST / HTTP/1.1\r\n\
+            Content-Length: 5\r\n\
+            Transfer-Encoding: chunked\r\n\
+            \r\n\
+            0\r\n\r\n";
+
+        // chunked encoding takes precedence by default
+        let mut buf = BytesMut::from(head);
+        let (_req, pl) = MessageDecoder::<Request>::default()
+            .decode(&mut buf)
+            .unwrap()
+            .unwrap();
+        assert!(matches!(pl, PayloadType::Payload(_)));
+
+        let mut reader = MessageDecoder::<Request>::default().strict(true);
+        let res = reader.decode(&mut BytesMut::from(head));
+        assert!(matches!(
+            res,
+            Err(ParseError::ContentLengthWithTransferEncoding)
+        ));
+
+        // transfer encoding is ignored for HTTP/1.0
+        let mut buf = BytesMut::from(
+            "POST / HTTP/1.0\r\n\
+            Content-Length: 5\r\n\
+            Transfer-Encoding: chunked\r\n\
+            \r\n",
+        );
+        assert!(reader.decode(&mut buf).unwrap().is_some());
+    }
+
+    #[test]
+    fn obsolete_line_folding() {
+        for mut reader in [
+            MessageDecoder::<Request>::default(),
+            MessageDecoder::<Request>::default().strict(true),
+        ] {
+            let mut buf = BytesMut::from(
+                "GET / HTTP/1.1\r\n\
+                X-Folded: first\r\n \
+                second\r\n\
+                \r\n",
+            );
+            let res = reader.decode(&mut buf);
+            assert!(matches!(res, Err(ParseError::ObsoleteLineFolding)));
+
+            let mut buf = BytesMut::from("GET / HTTP/1.1\r\nX-Folded: first\r\n\tsecond\r\n");
+            let res = reader.decode(&mut buf);
+            assert!(matches!(res, Err(ParseError::ObsoleteLineFolding)));
+        }
+
+        let mut buf = BytesMut::from("GET / HTTP/1.1\r\nX-Bad Name: value\r\n\r\n");
+        let res = MessageDecoder::<Request>::default().decode(&mut buf);
+        assert!(matches!(res, Err(ParseError::Header)));
+    }
+
+    #[test]
+    fn strict_get_content_length() {
+        let head = "GET / HTTP/1.1\r\nContent-Length: 4\r\n\r\nabcd";
+        assert!(MessageDecoder::<Request>::default()
+            .decode(&mut BytesMut::from(head))
+            .unwrap()
+            .is_some());
+
+        let mut reader = MessageDecoder::<Request>::default().strict(true);
+        let res = reader.decode(&mut BytesMut::from(head));
+        assert!(matches!(res, Err(ParseError::UnexpectedContentLength)));
+
+        let mut buf = BytesMut::from("GET / HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+        assert!(reader.decode(&mut buf).unwrap().is_some());
+
+        let mut buf = BytesMut::from("POST / HTTP/1.1\r\nContent-Length: 4\r\n\r\nabcd");
+        assert!(reader.decode(&mut buf).unwrap().is_some());
+    }
+
     #[test]
     fn hrs_content_length_plus() {
         expect_parse_err!(&mut BytesMut::from(
@@ -1180,6 +1559,6 @@ mod tests {
         let mut pl = pl.unwrap();
 
         let chunk = pl.decode(&mut buf).unwrap().unwrap();
-        assert_eq!(chunk, PayloadItem::Chunk(Bytes::from_static(b"0\r\n")));
+        assert_eq!(chunk.chunk(), Bytes::from_static(b"0\r\n"));
     }
 }
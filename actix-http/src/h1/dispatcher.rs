@@ -12,7 +12,7 @@ use actix_codec::{Framed, FramedParts};
 use actix_rt::time::sleep_until;
 use actix_service::Service;
 use bitflags::bitflags;
-use bytes::{Buf, BytesMut};
+use bytes::BytesMut;
 use futures_core::ready;
 use pin_project_lite::pin_project;
 use tokio::io::{AsyncRead, AsyncWrite};
@@ -20,47 +20,86 @@ use tokio_util::codec::{Decoder as _, Encoder as _};
 use tracing::{error, trace};
 
 use super::{
+    buffer::{self, WriteBuf},
     codec::Codec,
     decoder::MAX_BUFFER_SIZE,
+    expect::{DrainPolicy, ExpectResponse},
     payload::{Payload, PayloadSender, PayloadStatus},
     timer::TimerState,
     Message, MessageType,
 };
 use crate::{
-    body::{BodySize, BoxBody, MessageBody},
+    body::{private, BodySize, BoxBody, MessageBody},
+    callbacks::{ConnectionTracker, TrackedRequest},
     config::ServiceConfig,
     error::{DispatchError, ParseError, PayloadError},
+    header,
+    requests::{attach_finish, FinishGuard},
+    responses::{attach_informational, InformationalReceiver},
     service::HttpFlow,
-    Error, Extensions, OnConnectData, Request, Response, StatusCode,
+    shutdown::{ShutdownListener, ShutdownSignal},
+    ConnectionType, Error, Extensions, HttpMessage as _, OnConnectData, Protocol, Request,
+    Response, StatusCode,
 };
 
 const LW_BUFFER_SIZE: usize = 1024;
 const HW_BUFFER_SIZE: usize = 1024 * 8;
-const MAX_PIPELINED_MESSAGES: usize = 16;
+
+/// Maximum number of pipelined requests started in a single poll before yielding to other
+/// connections.
+const MAX_REQUESTS_PER_POLL: usize = 16;
 
 bitflags! {
-    #[derive(Debug, Clone, Copy)]
-    pub struct Flags: u8 {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Flags: u16 {
         /// Set when stream is read for first time.
-        const STARTED          = 0b0000_0001;
+        const STARTED          = 0b00_0000_0001;
 
         /// Set when full request-response cycle has occurred.
-        const FINISHED         = 0b0000_0010;
+        const FINISHED         = 0b00_0000_0010;
 
         /// Set if connection is in keep-alive (inactive) state.
-        const KEEP_ALIVE       = 0b0000_0100;
+        const KEEP_ALIVE       = 0b00_0000_0100;
 
         /// Set if in shutdown procedure.
-        const SHUTDOWN         = 0b0000_1000;
+        const SHUTDOWN         = 0b00_0000_1000;
 
         /// Set if read-half is disconnected.
-        const READ_DISCONNECT  = 0b0001_0000;
+        const READ_DISCONNECT  = 0b00_0001_0000;
 
         /// Set if write-half is disconnected.
-        const WRITE_DISCONNECT = 0b0010_0000;
+        const WRITE_DISCONNECT = 0b00_0010_0000;
+
+        /// Set while polling the trailers of a completed response body.
+        const BODY_EOF         = 0b00_0100_0000;
+
+        /// Set if no further requests are read and connection is closed after in-flight responses.
+        const DRAINING         = 0b00_1000_0000;
+
+        /// Set if client closed its write-half but data already read may still be decoded.
+        const READ_EOF         = 0b01_0000_0000;
+
+        /// Set while the payload of a request rejected by the expect service is being discarded.
+        const DISCARD_PAYLOAD  = 0b10_0000_0000;
     }
 }
 
+/// Pipelining statistics of an HTTP/1 connection, stored in the extensions of each request.
+///
+/// A growing depth indicates that the client sends requests faster than they are handled. See
+/// [`HttpServiceBuilder::max_pipelined_requests()`](crate::HttpServiceBuilder::max_pipelined_requests)
+/// for how the depth is limited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PipelineStats {
+    /// Number of requests awaiting a response on the connection when this request was received,
+    /// including this request.
+    pub depth: usize,
+
+    /// Highest depth seen on the connection up to and including this request.
+    pub max_depth: usize,
+}
+
 // there's 2 versions of Dispatcher state because of:
 // https://github.com/taiki-e/pin-project-lite/issues/3
 //
@@ -76,7 +115,8 @@ pin_project! {
 
         B: MessageBody,
 
-        X: Service<Request, Response = Request>,
+        X: Service<Request>,
+        X::Response: Into<ExpectResponse>,
         X::Error: Into<Response<BoxBody>>,
 
         U: Service<(Request, Framed<T, Codec>), Response = ()>,
@@ -97,7 +137,8 @@ pin_project! {
 
         B: MessageBody,
 
-        X: Service<Request, Response = Request>,
+        X: Service<Request>,
+        X::Response: Into<ExpectResponse>,
         X::Error: Into<Response<BoxBody>>,
 
         U: Service<(Request, Framed<T, Codec>), Response = ()>,
@@ -120,7 +161,8 @@ pin_project! {
 
         B: MessageBody,
 
-        X: Service<Request, Response = Request>,
+        X: Service<Request>,
+        X::Response: Into<ExpectResponse>,
         X::Error: Into<Response<BoxBody>>,
 
         U: Service<(Request, Framed<T, Codec>), Response = ()>,
@@ -140,7 +182,8 @@ pin_project! {
 
         B: MessageBody,
 
-        X: Service<Request, Response = Request>,
+        X: Service<Request>,
+        X::Response: Into<ExpectResponse>,
         X::Error: Into<Response<BoxBody>>,
 
         U: Service<(Request, Framed<T, Codec>), Response = ()>,
@@ -152,24 +195,92 @@ pin_project! {
         conn_data: Option<Rc<Extensions>>,
         config: ServiceConfig,
         error: Option<DispatchError>,
+        shutdown: Option<ShutdownListener>,
+        // number of requests received on this connection
+        requests: usize,
+        // highest number of pipelined requests awaiting a response on this connection
+        max_pipeline_depth: usize,
 
         #[pin]
         pub(super) state: State<S, B, X>,
         // when Some(_) dispatcher is in state of receiving request payload
         payload: Option<PayloadSender>,
         messages: VecDeque<DispatcherMessage>,
+        // informational responses sent by the service call in progress
+        informational: Option<InformationalReceiver>,
+        // completion callbacks of the request whose response is in progress
+        finish: Option<FinishGuard>,
+        // connection callbacks and the requests reported to them that await a complete response
+        tracker: Option<Rc<ConnectionTracker>>,
+        tracked: VecDeque<TrackedRequest>,
+        // socket used for writing file bodies with `sendfile(2)`
+        sendfile: Option<SendfileSocket<T>>,
 
         head_timer: TimerState,
         ka_timer: TimerState,
         shutdown_timer: TimerState,
+        lifetime_timer: TimerState,
 
         pub(super) io: Option<T>,
         read_buf: BytesMut,
-        write_buf: BytesMut,
+        write_buf: WriteBuf,
         codec: Codec,
+
+        trace: DispatchTrace,
+    }
+
+    impl<T, S, B, X, U> PinnedDrop for InnerDispatcher<T, S, B, X, U>
+    where
+        S: Service<Request>,
+        S::Error: Into<Response<BoxBody>>,
+
+        B: MessageBody,
+
+        X: Service<Request>,
+        X::Response: Into<ExpectResponse>,
+        X::Error: Into<Response<BoxBody>>,
+
+        U: Service<(Request, Framed<T, Codec>), Response = ()>,
+        U::Error: fmt::Display,
+    {
+        fn drop(this: Pin<&mut Self>) {
+            // the write buffer returns itself to the pool when dropped
+            buffer::release(mem::take(this.project().read_buf));
+        }
+    }
+}
+
+/// Reports the response in progress as complete.
+fn response_complete(finish: &mut Option<FinishGuard>, tracked: &mut VecDeque<TrackedRequest>) {
+    if let Some(finish) = finish.take() {
+        finish.complete();
+    }
+
+    if let Some(req) = tracked.pop_front() {
+        req.complete();
     }
 }
 
+/// Returns the TCP socket underlying an IO stream, for writing file bodies with `sendfile(2)`.
+///
+/// Set by the services of plain TCP streams; other IO types never use the `sendfile` path.
+pub(crate) type SendfileSocket<T> = fn(&T) -> &actix_rt::net::TcpStream;
+
+/// Returns true if the request asks to switch to the WebSocket protocol.
+fn is_websocket_upgrade(req: &Request) -> bool {
+    req.head().upgrade()
+        && req
+            .headers()
+            .get_all(header::UPGRADE)
+            .filter_map(|val| val.to_str().ok())
+            .flat_map(|val| val.split(','))
+            .any(|proto| {
+                // protocol names may be followed by a version, e.g. `websocket/13`
+                let name = proto.split('/').next().unwrap_or_default();
+                name.trim().eq_ignore_ascii_case("websocket")
+            })
+}
+
 enum DispatcherMessage {
     Item(Request),
     Upgrade(Request),
@@ -181,32 +292,88 @@ pin_project! {
     pub(super) enum State<S, B, X>
     where
         S: Service<Request>,
-        X: Service<Request, Response = Request>,
+        X: Service<Request>,
+        X::Response: Into<ExpectResponse>,
         B: MessageBody,
     {
         None,
         ExpectCall { #[pin] fut: X::Future },
         ServiceCall { #[pin] fut: S::Future },
-        SendPayload { #[pin] body: B },
-        SendErrorPayload { #[pin] body: BoxBody },
+        SendPayload { #[pin] body: B, len: BodyLength },
+        SendErrorPayload { #[pin] body: BoxBody, len: BodyLength },
+    }
+}
+
+/// Tracks the number of bytes yielded by a response body against its declared size.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct BodyLength {
+    expected: Option<u64>,
+    sent: u64,
+}
+
+impl BodyLength {
+    fn new(size: BodySize) -> Self {
+        let expected = match size {
+            BodySize::Sized(len) => Some(len),
+            BodySize::None | BodySize::Stream => None,
+        };
+
+        Self { expected, sent: 0 }
+    }
+
+    /// Records a body chunk, failing if the body has yielded more bytes than declared.
+    fn chunk(&mut self, len: usize) -> Result<(), DispatchError> {
+        self.sent += len as u64;
+
+        match self.expected {
+            Some(expected) if self.sent > expected => Err(DispatchError::BodyLengthMismatch {
+                expected,
+                actual: self.sent,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that a finished body yielded exactly as many bytes as declared.
+    fn eof(&self) -> Result<(), DispatchError> {
+        match self.expected {
+            Some(expected) if self.sent != expected => Err(DispatchError::BodyLengthMismatch {
+                expected,
+                actual: self.sent,
+            }),
+            _ => Ok(()),
+        }
     }
 }
 
 impl<S, B, X> State<S, B, X>
 where
     S: Service<Request>,
-    X: Service<Request, Response = Request>,
+    X: Service<Request>,
+    X::Response: Into<ExpectResponse>,
     B: MessageBody,
 {
     pub(super) fn is_none(&self) -> bool {
         matches!(self, State::None)
     }
+
+    #[cfg(feature = "dispatcher-trace")]
+    fn name(&self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::ExpectCall { .. } => "ExpectCall",
+            Self::ServiceCall { .. } => "ServiceCall",
+            Self::SendPayload { .. } => "SendPayload",
+            Self::SendErrorPayload { .. } => "SendErrorPayload",
+        }
+    }
 }
 
 impl<S, B, X> fmt::Debug for State<S, B, X>
 where
     S: Service<Request>,
-    X: Service<Request, Response = Request>,
+    X: Service<Request>,
+    X::Response: Into<ExpectResponse>,
     B: MessageBody,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -243,7 +410,8 @@ where
 
     B: MessageBody,
 
-    X: Service<Request, Response = Request>,
+    X: Service<Request>,
+    X::Response: Into<ExpectResponse>,
     X::Error: Into<Response<BoxBody>>,
 
     U: Service<(Request, Framed<T, Codec>), Response = ()>,
@@ -257,6 +425,20 @@ where
         peer_addr: Option<net::SocketAddr>,
         conn_data: OnConnectData,
     ) -> Self {
+        let lifetime_deadline = config.connection_lifetime_deadline();
+        let mut lifetime_timer = TimerState::new(lifetime_deadline.is_some());
+
+        if let Some(deadline) = lifetime_deadline {
+            lifetime_timer.set(sleep_until(deadline.into()), line!());
+        }
+
+        let tracker = ConnectionTracker::start(
+            config.connection_callbacks(),
+            Protocol::Http1,
+            peer_addr,
+            &conn_data,
+        );
+
         Dispatcher {
             inner: DispatcherState::Normal {
                 inner: InnerDispatcher {
@@ -266,19 +448,30 @@ where
                     conn_data: conn_data.0.map(Rc::new),
                     config: config.clone(),
                     error: None,
+                    shutdown: config.shutdown_signal().map(ShutdownSignal::listener),
+                    requests: 0,
+                    max_pipeline_depth: 0,
 
                     state: State::None,
                     payload: None,
                     messages: VecDeque::new(),
+                    informational: None,
+                    finish: None,
+                    tracker,
+                    tracked: VecDeque::new(),
+                    sendfile: None,
 
                     head_timer: TimerState::new(config.client_request_deadline().is_some()),
                     ka_timer: TimerState::new(config.keep_alive().enabled()),
                     shutdown_timer: TimerState::new(config.client_disconnect_deadline().is_some()),
+                    lifetime_timer,
 
+                    write_buf: WriteBuf::new(HW_BUFFER_SIZE, io.is_write_vectored()),
+                    read_buf: buffer::take(HW_BUFFER_SIZE),
                     io: Some(io),
-                    read_buf: BytesMut::with_capacity(HW_BUFFER_SIZE),
-                    write_buf: BytesMut::with_capacity(HW_BUFFER_SIZE),
                     codec: Codec::new(config),
+
+                    trace: DispatchTrace::new(),
                 },
             },
 
@@ -286,6 +479,15 @@ where
             poll_count: 0,
         }
     }
+
+    /// Enables writing file bodies to the socket returned by `socket` using `sendfile(2)`.
+    pub(crate) fn sendfile(mut self, socket: Option<SendfileSocket<T>>) -> Self {
+        if let DispatcherState::Normal { inner } = &mut self.inner {
+            inner.sendfile = socket;
+        }
+
+        self
+    }
 }
 
 impl<T, S, B, X, U> InnerDispatcher<T, S, B, X, U>
@@ -298,15 +500,53 @@ where
 
     B: MessageBody,
 
-    X: Service<Request, Response = Request>,
+    X: Service<Request>,
+    X::Response: Into<ExpectResponse>,
     X::Error: Into<Response<BoxBody>>,
 
     U: Service<(Request, Framed<T, Codec>), Response = ()>,
     U::Error: fmt::Display,
 {
+    /// Logs changes to the state, flags, and timers since the last call.
+    #[cfg(feature = "dispatcher-trace")]
+    fn trace_transition(self: Pin<&mut Self>, label: &str) {
+        let this = self.project();
+
+        let snapshot = TraceSnapshot {
+            state: this.state.name(),
+            flags: *this.flags,
+            head_timer: this.head_timer.deadline(),
+            ka_timer: this.ka_timer.deadline(),
+            shutdown_timer: this.shutdown_timer.deadline(),
+        };
+
+        if this.trace.last.as_ref() == Some(&snapshot) {
+            return;
+        }
+
+        let prev_state = this.trace.last.map_or("(new)", |prev| prev.state);
+
+        tracing::debug!(
+            target: "actix_http::h1::dispatcher",
+            "connection {}: {} -> {} after {}; flags: {:?}; head {}; keep-alive {}; shutdown {}",
+            this.trace.conn_id,
+            prev_state,
+            snapshot.state,
+            label,
+            snapshot.flags,
+            this.head_timer,
+            this.ka_timer,
+            this.shutdown_timer,
+        );
+
+        this.trace.last = Some(snapshot);
+    }
+
     fn can_read(&self, cx: &mut Context<'_>) -> bool {
         if self.flags.contains(Flags::READ_DISCONNECT) {
             false
+        } else if self.flags.contains(Flags::DISCARD_PAYLOAD) {
+            true
         } else if let Some(ref info) = self.payload {
             info.need_read(cx) == PayloadStatus::Read
         } else {
@@ -314,6 +554,8 @@ where
         }
     }
 
+    /// Marks both halves of the connection as disconnected after a hard reset or I/O error,
+    /// aborting the request payload being received.
     fn client_disconnected(self: Pin<&mut Self>) {
         let this = self.project();
 
@@ -325,58 +567,164 @@ where
         }
     }
 
+    /// Disconnects the read-half after a read EOF once all buffered data that can be decoded has
+    /// been decoded.
+    fn poll_read_eof(self: Pin<&mut Self>, cx: &mut Context<'_>) {
+        let this = self.project();
+
+        if this.flags.contains(Flags::READ_DISCONNECT) {
+            return;
+        }
+
+        match this.payload {
+            // payload is paused; remaining buffered data is decoded once it is read by the service
+            Some(ref payload)
+                if !this.flags.contains(Flags::DISCARD_PAYLOAD)
+                    && payload.need_read(cx) != PayloadStatus::Read =>
+            {
+                return
+            }
+
+            _ => {}
+        }
+
+        this.flags.insert(Flags::READ_DISCONNECT);
+
+        if let Some(mut payload) = this.payload.take() {
+            if this.codec.message_type() == MessageType::Stream {
+                // stream payloads are delimited by the end of the connection
+                payload.feed_eof();
+            } else {
+                trace!("read EOF before end of request payload");
+                payload.set_error(PayloadError::Incomplete(None));
+            }
+        }
+    }
+
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
-        let InnerDispatcherProj { io, write_buf, .. } = self.project();
+        let InnerDispatcherProj {
+            io,
+            write_buf,
+            tracker,
+            ..
+        } = self.project();
         let mut io = Pin::new(io.as_mut().unwrap());
 
-        let len = write_buf.len();
-        let mut written = 0;
-
-        while written < len {
-            match io.as_mut().poll_write(cx, &write_buf[written..])? {
+        while !write_buf.is_empty() {
+            match write_buf.poll_write_to(io.as_mut(), cx)? {
                 Poll::Ready(0) => {
                     error!("write zero; closing");
                     return Poll::Ready(Err(io::Error::new(io::ErrorKind::WriteZero, "")));
                 }
 
-                Poll::Ready(n) => written += n,
-
-                Poll::Pending => {
-                    write_buf.advance(written);
-                    return Poll::Pending;
+                Poll::Ready(n) => {
+                    if let Some(tracker) = tracker {
+                        tracker.add_written(n);
+                    }
                 }
+
+                Poll::Pending => return Poll::Pending,
             }
         }
 
-        // everything has written to I/O; clear buffer
-        write_buf.clear();
-
         // flush the I/O and check if get blocked
         io.poll_flush(cx)
     }
 
     fn send_response_inner(
-        self: Pin<&mut Self>,
-        res: Response<()>,
+        mut self: Pin<&mut Self>,
+        mut res: Response<()>,
         body: &impl MessageBody,
     ) -> Result<BodySize, DispatchError> {
-        let this = self.project();
+        let this = self.as_mut().project();
 
-        let size = body.size();
+        // no more informational responses can be sent once the final response head is written
+        *this.informational = None;
 
-        this.codec
-            .encode(Message::Item((res, size)), this.write_buf)
-            .map_err(|err| {
-                if let Some(mut payload) = this.payload.take() {
-                    payload.set_error(PayloadError::Incomplete(None));
-                }
+        // last response before the connection is drained
+        if this.flags.contains(Flags::DRAINING) && this.messages.is_empty() {
+            res.head_mut().set_connection_type(ConnectionType::Close);
+        }
+
+        let mut size = body.size();
+        let mut overflowed = false;
+
+        let dst = this.write_buf.buf_mut();
+        let start = dst.len();
+        let mut encoded = this.codec.encode_response(&mut res, size, dst);
+
+        if let Some(max) = this.config.max_response_head_size() {
+            let head_size = dst.len() - start;
+
+            if encoded.is_ok() && head_size > max {
+                overflowed = true;
+                error!("response head of {head_size} bytes exceeds limit of {max} bytes");
+                this.config.response_head_overflow(res.head(), head_size);
+
+                // replace response with an error response and close the connection
+                dst.truncate(start);
+                res = Response::with_body(StatusCode::INTERNAL_SERVER_ERROR, ());
+                res.head_mut().set_connection_type(ConnectionType::Close);
+                size = BodySize::Sized(0);
+                encoded = this.codec.encode_response(&mut res, size, dst);
+            }
+        }
+
+        let head_size = (dst.len() - start) as u64;
+
+        encoded.map_err(|err| {
+            if let Some(mut payload) = this.payload.take() {
+                payload.set_error(PayloadError::Incomplete(None));
+            }
+
+            DispatchError::Io(err)
+        })?;
 
-                DispatchError::Io(err)
-            })?;
+        if let Some(tracker) = this.tracker {
+            // responses to requests that failed to parse are not preceded by a tracked request
+            if this.tracked.is_empty() {
+                this.tracked.push_back(tracker.untracked_request());
+            }
+
+            this.tracked
+                .front_mut()
+                .unwrap()
+                .response(res.head(), Some(head_size));
+        }
+
+        if overflowed {
+            self.as_mut().start_draining();
+        }
+
+        // body is dropped without being polled if it would not be sent
+        if self.codec.omits_body(res.head()) {
+            return Ok(BodySize::None);
+        }
 
         Ok(size)
     }
 
+    /// Calls the service with `req` and sets the dispatcher state to poll its response.
+    ///
+    /// If catching panics is enabled and the call panics, the response for the panic is sent.
+    fn call_service(mut self: Pin<&mut Self>, req: Request) -> Result<(), DispatchError> {
+        let mut this = self.as_mut().project();
+        *this.informational = attach_informational(&req);
+        *this.finish = Some(attach_finish(&req, this.config.in_flight_counter()));
+
+        match this.config.catch_panic(|| this.flow.service.call(req)) {
+            Ok(fut) => {
+                this.state.set(State::ServiceCall { fut });
+                Ok(())
+            }
+
+            Err(res) => {
+                let (res, body) = res.replace_body(());
+                self.send_error_response(res, body)
+            }
+        }
+    }
+
     fn send_response(
         mut self: Pin<&mut Self>,
         res: Response<()>,
@@ -387,9 +735,13 @@ where
         this.state.set(match size {
             BodySize::None | BodySize::Sized(0) => {
                 this.flags.insert(Flags::FINISHED);
+                response_complete(this.finish, this.tracked);
                 State::None
             }
-            _ => State::SendPayload { body },
+            _ => State::SendPayload {
+                body,
+                len: BodyLength::new(size),
+            },
         });
 
         Ok(())
@@ -405,31 +757,94 @@ where
         this.state.set(match size {
             BodySize::None | BodySize::Sized(0) => {
                 this.flags.insert(Flags::FINISHED);
+                response_complete(this.finish, this.tracked);
                 State::None
             }
-            _ => State::SendErrorPayload { body },
+            _ => State::SendErrorPayload {
+                body,
+                len: BodyLength::new(size),
+            },
         });
 
         Ok(())
     }
 
+    /// Writes informational responses sent by the service call in progress.
+    fn send_informational(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Result<(), DispatchError> {
+        let this = self.project();
+
+        if let Some(rx) = this.informational {
+            while let Poll::Ready(res) = rx.poll_recv(cx) {
+                this.codec
+                    .encode_informational(res, this.write_buf.buf_mut())
+                    .map_err(DispatchError::Io)?;
+            }
+        }
+
+        Ok(())
+    }
+
     fn send_continue(self: Pin<&mut Self>) {
         self.project()
             .write_buf
             .extend_from_slice(b"HTTP/1.1 100 Continue\r\n\r\n");
     }
 
+    /// Sends the rejection response of the expect service and applies its drain policy to the
+    /// payload of the rejected request.
+    fn reject_expect(
+        mut self: Pin<&mut Self>,
+        res: Response<BoxBody>,
+        policy: DrainPolicy,
+    ) -> Result<(), DispatchError> {
+        match policy {
+            DrainPolicy::Drain => {
+                let this = self.as_mut().project();
+
+                // payload is only discarded until the end of the rejected request's body
+                if this.payload.is_some() {
+                    this.flags.insert(Flags::DISCARD_PAYLOAD);
+                }
+            }
+
+            DrainPolicy::Close => {
+                let this = self.as_mut().project();
+                this.flags.insert(Flags::READ_DISCONNECT);
+                *this.payload = None;
+
+                self.as_mut().start_draining();
+            }
+        }
+
+        let (res, body) = res.replace_body(());
+        self.send_error_response(res, body)
+    }
+
     fn poll_response(
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Result<PollResponse, DispatchError> {
+        // number of pipelined requests started by this call
+        let mut started = 0;
+
         'res: loop {
             let mut this = self.as_mut().project();
             match this.state.as_mut().project() {
+                // yield to other connections after starting a number of pipelined requests; since
+                // responses are sent in order, other requests on this connection can not go first
+                StateProj::None
+                    if started >= MAX_REQUESTS_PER_POLL && !this.messages.is_empty() =>
+                {
+                    cx.waker().wake_by_ref();
+                    return Ok(PollResponse::DoNothing);
+                }
+
                 // no future is in InnerDispatcher state; pop next message
                 StateProj::None => match this.messages.pop_front() {
                     // handle request message
                     Some(DispatcherMessage::Item(req)) => {
+                        started += 1;
+
                         // Handle `EXPECT: 100-Continue` header
                         if req.head().expect() {
                             // set InnerDispatcher state and continue loop to poll it
@@ -437,8 +852,7 @@ where
                             this.state.set(State::ExpectCall { fut });
                         } else {
                             // set InnerDispatcher state and continue loop to poll it
-                            let fut = this.flow.service.call(req);
-                            this.state.set(State::ServiceCall { fut });
+                            self.as_mut().call_service(req)?;
                         };
                     }
 
@@ -463,7 +877,19 @@ where
                 },
 
                 StateProj::ServiceCall { fut } => {
-                    match fut.poll(cx) {
+                    let res = match this.config.catch_panic(|| fut.poll(cx)) {
+                        Ok(res) => res,
+
+                        // send response of caught panic
+                        Err(res) => {
+                            let (res, body) = res.replace_body(());
+                            self.as_mut().send_error_response(res, body)?;
+                            continue;
+                        }
+                    };
+                    self.as_mut().send_informational(cx)?;
+
+                    match res {
                         // service call resolved. send response.
                         Poll::Ready(Ok(res)) => {
                             let (res, body) = res.into().replace_body(());
@@ -490,24 +916,104 @@ where
                     }
                 }
 
-                StateProj::SendPayload { mut body } => {
+                StateProj::SendPayload { mut body, len } => {
+                    // write file bodies to plain TCP sockets directly from the file
+                    #[cfg(target_os = "linux")]
+                    if let Some(file) = body.as_mut().as_file_body(private::Token) {
+                        if let Some(socket) = *this.sendfile {
+                            let stream = socket(this.io.as_ref().unwrap());
+                            let unframed = this.codec.unframed_remaining();
+
+                            if file.can_sendfile() && unframed == Some(file.remaining()) {
+                                // response head must reach the socket before the body does
+                                if !this.write_buf.is_empty() {
+                                    return Ok(PollResponse::DrainWriteBuf);
+                                }
+
+                                while file.can_sendfile() {
+                                    match file.poll_sendfile(stream, cx) {
+                                        Poll::Ready(Ok(n)) => {
+                                            len.chunk(n)?;
+                                            this.codec.consume_unframed(n as u64);
+
+                                            if let Some(tracker) = this.tracker {
+                                                tracker.add_written(n);
+                                            }
+                                            if let Some(req) = this.tracked.front_mut() {
+                                                req.add_body(n);
+                                            }
+                                        }
+
+                                        Poll::Ready(Err(err)) => {
+                                            tracing::error!("Response payload stream error: {err}");
+                                            this.flags.insert(Flags::FINISHED);
+                                            return Err(DispatchError::Io(err));
+                                        }
+
+                                        Poll::Pending => return Ok(PollResponse::DoNothing),
+                                    }
+                                }
+                            }
+                        }
+                    }
+
                     // keep populate writer buffer until buffer size limit hit,
                     // get blocked or finished.
                     while this.write_buf.len() < super::payload::MAX_BUFFER_SIZE {
-                        match body.as_mut().poll_next(cx) {
+                        // a completed body must not be polled again while awaiting trailers
+                        let item = if this.flags.contains(Flags::BODY_EOF) {
+                            Poll::Ready(None)
+                        } else {
+                            body.as_mut().poll_next(cx)
+                        };
+
+                        match item {
                             Poll::Ready(Some(Ok(item))) => {
-                                this.codec
-                                    .encode(Message::Chunk(Some(item)), this.write_buf)?;
+                                if let Err(err) = len.chunk(item.len()) {
+                                    tracing::error!("Response payload stream error: {err}");
+                                    this.flags.insert(Flags::FINISHED);
+                                    return Err(err);
+                                }
+
+                                if let Some(req) = this.tracked.front_mut() {
+                                    req.add_body(item.len());
+                                }
+
+                                this.codec.encode_chunk(item, this.write_buf)?;
                             }
 
                             Poll::Ready(None) => {
-                                this.codec.encode(Message::Chunk(None), this.write_buf)?;
+                                if let Err(err) = len.eof() {
+                                    tracing::error!("Response payload stream error: {err}");
+                                    this.flags.insert(Flags::FINISHED);
+                                    return Err(err);
+                                }
+
+                                this.flags.insert(Flags::BODY_EOF);
+
+                                let trailers = match body.as_mut().poll_trailers(cx) {
+                                    Poll::Ready(trailers) => trailers,
+                                    Poll::Pending => return Ok(PollResponse::DoNothing),
+                                };
+
+                                this.flags.remove(Flags::BODY_EOF);
+
+                                match trailers {
+                                    Some(trailers) => this
+                                        .codec
+                                        .encode_trailers(&trailers, this.write_buf.buf_mut())?,
+                                    None => this
+                                        .codec
+                                        .encode(Message::Chunk(None), this.write_buf.buf_mut())?,
+                                }
 
                                 // payload stream finished.
                                 // set state to None and handle next message
                                 this.state.set(State::None);
                                 this.flags.insert(Flags::FINISHED);
 
+                                response_complete(this.finish, this.tracked);
+
                                 continue 'res;
                             }
 
@@ -527,26 +1033,66 @@ where
                     return Ok(PollResponse::DrainWriteBuf);
                 }
 
-                StateProj::SendErrorPayload { mut body } => {
+                StateProj::SendErrorPayload { mut body, len } => {
                     // TODO: de-dupe impl with SendPayload
 
                     // keep populate writer buffer until buffer size limit hit,
                     // get blocked or finished.
                     while this.write_buf.len() < super::payload::MAX_BUFFER_SIZE {
-                        match body.as_mut().poll_next(cx) {
+                        // a completed body must not be polled again while awaiting trailers
+                        let item = if this.flags.contains(Flags::BODY_EOF) {
+                            Poll::Ready(None)
+                        } else {
+                            body.as_mut().poll_next(cx)
+                        };
+
+                        match item {
                             Poll::Ready(Some(Ok(item))) => {
-                                this.codec
-                                    .encode(Message::Chunk(Some(item)), this.write_buf)?;
+                                if let Err(err) = len.chunk(item.len()) {
+                                    tracing::error!("Response payload stream error: {err}");
+                                    this.flags.insert(Flags::FINISHED);
+                                    return Err(err);
+                                }
+
+                                if let Some(req) = this.tracked.front_mut() {
+                                    req.add_body(item.len());
+                                }
+
+                                this.codec.encode_chunk(item, this.write_buf)?;
                             }
 
                             Poll::Ready(None) => {
-                                this.codec.encode(Message::Chunk(None), this.write_buf)?;
+                                if let Err(err) = len.eof() {
+                                    tracing::error!("Response payload stream error: {err}");
+                                    this.flags.insert(Flags::FINISHED);
+                                    return Err(err);
+                                }
+
+                                this.flags.insert(Flags::BODY_EOF);
+
+                                let trailers = match body.as_mut().poll_trailers(cx) {
+                                    Poll::Ready(trailers) => trailers,
+                                    Poll::Pending => return Ok(PollResponse::DoNothing),
+                                };
+
+                                this.flags.remove(Flags::BODY_EOF);
+
+                                match trailers {
+                                    Some(trailers) => this
+                                        .codec
+                                        .encode_trailers(&trailers, this.write_buf.buf_mut())?,
+                                    None => this
+                                        .codec
+                                        .encode(Message::Chunk(None), this.write_buf.buf_mut())?,
+                                }
 
                                 // payload stream finished
                                 // set state to None and handle next message
                                 this.state.set(State::None);
                                 this.flags.insert(Flags::FINISHED);
 
+                                response_complete(this.finish, this.tracked);
+
                                 continue 'res;
                             }
 
@@ -573,12 +1119,17 @@ where
                     match fut.poll(cx) {
                         // expect resolved. write continue to buffer and set InnerDispatcher state
                         // to service call.
-                        Poll::Ready(Ok(req)) => {
-                            this.write_buf
-                                .extend_from_slice(b"HTTP/1.1 100 Continue\r\n\r\n");
-                            let fut = this.flow.service.call(req);
-                            this.state.set(State::ServiceCall { fut });
-                        }
+                        Poll::Ready(Ok(res)) => match res.into() {
+                            ExpectResponse::Continue(req) => {
+                                this.write_buf
+                                    .extend_from_slice(b"HTTP/1.1 100 Continue\r\n\r\n");
+                                self.as_mut().call_service(req)?;
+                            }
+
+                            ExpectResponse::Reject(res, policy) => {
+                                self.as_mut().reject_expect(res, policy)?;
+                            }
+                        },
 
                         // send expect error as response
                         Poll::Ready(Err(err)) => {
@@ -611,26 +1162,31 @@ where
                 this.state.set(State::ExpectCall { fut });
             } else {
                 // set dispatcher state to call service handler
-                let fut = this.flow.service.call(req);
-                this.state.set(State::ServiceCall { fut });
+                self.as_mut().call_service(req)?;
             };
         };
 
         // eagerly poll the future once (or twice if expect is resolved immediately).
         loop {
-            match self.as_mut().project().state.project() {
+            let this = self.as_mut().project();
+
+            match this.state.project() {
                 StateProj::ExpectCall { fut } => {
                     match fut.poll(cx) {
                         // expect is resolved; continue loop and poll the service call branch.
-                        Poll::Ready(Ok(req)) => {
-                            self.as_mut().send_continue();
+                        Poll::Ready(Ok(res)) => match res.into() {
+                            ExpectResponse::Continue(req) => {
+                                self.as_mut().send_continue();
+                                self.as_mut().call_service(req)?;
 
-                            let mut this = self.as_mut().project();
-                            let fut = this.flow.service.call(req);
-                            this.state.set(State::ServiceCall { fut });
+                                continue;
+                            }
 
-                            continue;
-                        }
+                            // response is sent instead of calling the service
+                            ExpectResponse::Reject(res, policy) => {
+                                return self.reject_expect(res, policy);
+                            }
+                        },
 
                         // future is error; send response and return a result
                         // on success to notify the dispatcher a new state is set and the outer loop
@@ -648,8 +1204,19 @@ where
                 }
 
                 StateProj::ServiceCall { fut } => {
+                    let res = match this.config.catch_panic(|| fut.poll(cx)) {
+                        Ok(res) => res,
+
+                        // see the comment on ExpectCall state branch's Ready(Err(_))
+                        Err(res) => {
+                            let (res, body) = res.replace_body(());
+                            return self.send_error_response(res, body);
+                        }
+                    };
+                    self.as_mut().send_informational(cx)?;
+
                     // return no matter the service call future's result.
-                    return match fut.poll(cx) {
+                    return match res {
                         // Future is resolved. Send response and return a result. On success
                         // to notify the dispatcher a new state is set and the outer loop
                         // should be continue.
@@ -681,11 +1248,7 @@ where
     ///
     /// Returns true if any meaningful work was done.
     fn poll_request(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Result<bool, DispatchError> {
-        let pipeline_queue_full = self.messages.len() >= MAX_PIPELINED_MESSAGES;
-        let can_not_read = !self.can_read(cx);
-
-        // limit amount of non-processed requests
-        if pipeline_queue_full || can_not_read {
+        if !self.can_read(cx) {
             return Ok(false);
         }
 
@@ -695,6 +1258,13 @@ where
 
         // decode from read buf as many full requests as possible
         loop {
+            // when draining, only the payload of the current request is read
+            if this.flags.contains(Flags::DRAINING) && this.payload.is_none() {
+                break;
+            }
+
+            let buffered = this.read_buf.len();
+
             match this.codec.decode(this.read_buf) {
                 Ok(Some(msg)) => {
                     updated = true;
@@ -704,11 +1274,82 @@ where
                             // head timer only applies to first request on connection
                             this.head_timer.clear(line!());
 
+                            if let Some(tracker) = this.tracker {
+                                let head_size = (buffered - this.read_buf.len()) as u64;
+                                this.tracked
+                                    .push_back(tracker.request(req.head(), Some(head_size)));
+                            }
+
+                            if let Err(err) = this.config.header_limits().check(req.head()) {
+                                trace!("request headers rejected: {err}; returning error response");
+
+                                this.messages.push_back(DispatcherMessage::Error(
+                                    Response::with_body(err.status_code(), ()),
+                                ));
+
+                                this.flags.insert(Flags::READ_DISCONNECT);
+                                *this.error = Some(err.into());
+                                break;
+                            }
+
+                            // requests are not buffered beyond the pipeline limit; the client
+                            // is asked to retry on a new connection instead
+                            if !this.state.is_none()
+                                && this.messages.len() >= this.config.max_pipelined_requests()
+                            {
+                                trace!("pipeline queue full; returning error response");
+
+                                this.messages.push_back(DispatcherMessage::Error(
+                                    Response::with_body(StatusCode::SERVICE_UNAVAILABLE, ()),
+                                ));
+
+                                this.flags.insert(Flags::DRAINING);
+                                break;
+                            }
+
+                            *this.requests += 1;
+
+                            // requests received but not yet responded to, including this one
+                            let depth =
+                                this.messages.len() + usize::from(!this.state.is_none()) + 1;
+                            *this.max_pipeline_depth = (*this.max_pipeline_depth).max(depth);
+
+                            req.extensions_mut().insert(PipelineStats {
+                                depth,
+                                max_depth: *this.max_pipeline_depth,
+                            });
+
+                            if matches!(
+                                this.config.max_requests_per_connection(),
+                                Some(max) if *this.requests >= max
+                            ) {
+                                trace!("maximum requests per connection reached; draining");
+                                this.flags.insert(Flags::DRAINING);
+                            }
+
+                            // requests following one that asks to close the connection are not
+                            // processed; see https://www.rfc-editor.org/rfc/rfc9112#section-9.6
+                            if req.head().connection_type() == ConnectionType::Close {
+                                trace!("request asked to close connection; draining");
+                                this.flags.insert(Flags::DRAINING);
+                            }
+
                             req.head_mut().peer_addr = *this.peer_addr;
 
                             req.conn_data.clone_from(this.conn_data);
 
                             match this.codec.message_type() {
+                                // Request asks to switch to WebSocket among other protocols, which
+                                // the decoder does not recognize. Add upgrade message and break.
+                                // Other upgrades, such as h2c, are handled as regular requests.
+                                MessageType::None
+                                    if this.flow.upgrade.is_some()
+                                        && is_websocket_upgrade(&req) =>
+                                {
+                                    this.messages.push_back(DispatcherMessage::Upgrade(req));
+                                    break;
+                                }
+
                                 // request has no payload
                                 MessageType::None => {}
 
@@ -757,7 +1398,13 @@ where
                         }
 
                         Message::Chunk(None) => {
+                            this.flags.remove(Flags::DISCARD_PAYLOAD);
+
                             if let Some(mut payload) = this.payload.take() {
+                                if let Some(trailers) = this.codec.take_trailers() {
+                                    payload.feed_trailers(trailers);
+                                }
+
                                 payload.feed_eof();
                             } else {
                                 error!("Internal server error: unexpected eof");
@@ -804,6 +1451,21 @@ where
                     break;
                 }
 
+                Err(ParseError::UriTooLong) => {
+                    trace!("request URI was too long; returning 414 response");
+
+                    this.messages
+                        .push_back(DispatcherMessage::Error(Response::with_body(
+                            StatusCode::URI_TOO_LONG,
+                            (),
+                        )));
+
+                    this.flags.insert(Flags::READ_DISCONNECT);
+                    *this.error = Some(ParseError::UriTooLong.into());
+
+                    break;
+                }
+
                 Err(err) => {
                     trace!("parse error {}", &err);
 
@@ -914,25 +1576,85 @@ where
         Ok(())
     }
 
-    /// Poll head, keep-alive, and disconnect timer.
+    /// Stops reading new requests and closes connection once in-flight requests are done.
+    ///
+    /// Requests that have been received but not started are dropped; the request currently being
+    /// handled is allowed to finish.
+    fn start_draining(self: Pin<&mut Self>) {
+        let this = self.project();
+
+        if this.flags.contains(Flags::DRAINING) {
+            return;
+        }
+
+        this.flags.insert(Flags::DRAINING);
+
+        let queued = this.messages.len();
+        this.messages
+            .retain(|msg| matches!(msg, DispatcherMessage::Error(_)));
+
+        // payload being received belongs to one of the dropped requests
+        if this.messages.len() != queued {
+            if let Some(mut payload) = this.payload.take() {
+                payload.set_error(PayloadError::Incomplete(None));
+            }
+        }
+    }
+
+    /// Starts draining connection once the server's shutdown signal is triggered.
+    fn poll_shutdown_signal(mut self: Pin<&mut Self>, cx: &mut Context<'_>) {
+        let this = self.as_mut().project();
+
+        let Some(listener) = this.shutdown else {
+            return;
+        };
+
+        if this.flags.contains(Flags::DRAINING) || listener.poll_shutdown(cx).is_pending() {
+            return;
+        }
+
+        trace!("server is shutting down; draining connection");
+        self.start_draining();
+    }
+
+    /// Starts draining connection once its maximum lifetime has elapsed.
+    fn poll_lifetime_timer(mut self: Pin<&mut Self>, cx: &mut Context<'_>) {
+        let this = self.as_mut().project();
+
+        if let TimerState::Active { timer } = this.lifetime_timer {
+            if timer.as_mut().poll(cx).is_ready() {
+                trace!("connection lifetime elapsed; draining connection");
+                this.lifetime_timer.clear(line!());
+                self.start_draining();
+            }
+        }
+    }
+
+    /// Poll head, keep-alive, disconnect, and connection lifetime timer.
     fn poll_timers(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Result<(), DispatchError> {
         self.as_mut().poll_head_timer(cx)?;
         self.as_mut().poll_ka_timer(cx)?;
         self.as_mut().poll_shutdown_timer(cx)?;
+        self.as_mut().poll_lifetime_timer(cx);
 
         Ok(())
     }
 
-    /// Returns true when I/O stream can be disconnected after write to it.
+    /// Returns true when the client has closed its write-half (read EOF).
     ///
-    /// It covers these conditions:
-    /// - `std::io::ErrorKind::ConnectionReset` after partial read;
-    /// - all data read done.
+    /// A hard reset of the connection is not treated as EOF; the connection is marked as
+    /// disconnected and the error is returned.
     #[inline(always)] // TODO: bench this inline
-    fn read_available(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Result<bool, DispatchError> {
-        let this = self.project();
+    fn read_available(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Result<bool, DispatchError> {
+        let this = self.as_mut().project();
 
-        if this.flags.contains(Flags::READ_DISCONNECT) {
+        if this
+            .flags
+            .intersects(Flags::READ_DISCONNECT | Flags::READ_EOF)
+        {
             return Ok(false);
         };
 
@@ -976,7 +1698,9 @@ where
                     // read anymore. At this case read_buf could always remain beyond
                     // MAX_BUFFER_SIZE and self wake up would be busy poll dispatcher and
                     // waste resources.
-                    Some(ref p) if p.need_read(cx) != PayloadStatus::Read => {}
+                    Some(ref p)
+                        if !this.flags.contains(Flags::DISCARD_PAYLOAD)
+                            && p.need_read(cx) != PayloadStatus::Read => {}
                     _ => cx.waker().wake_by_ref(),
                 }
 
@@ -993,6 +1717,10 @@ where
                 Poll::Ready(Ok(n)) => {
                     this.flags.remove(Flags::FINISHED);
 
+                    if let Some(tracker) = this.tracker {
+                        tracker.add_read(n);
+                    }
+
                     if n == 0 {
                         return Ok(true);
                     }
@@ -1009,8 +1737,12 @@ where
                         // convert WouldBlock error to the same as Pending return
                         io::ErrorKind::WouldBlock => Ok(false),
 
-                        // connection reset after partial read
-                        io::ErrorKind::ConnectionReset if read_some => Ok(true),
+                        // responses can not be delivered after a hard reset
+                        io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted => {
+                            trace!("connection reset by client (read some: {read_some})");
+                            self.client_disconnected();
+                            Err(DispatchError::Io(err))
+                        }
 
                         _ => Err(DispatchError::Io(err)),
                     };
@@ -1027,7 +1759,7 @@ where
             mem::take(this.codec),
             mem::take(this.read_buf),
         );
-        parts.write_buf = mem::take(this.write_buf);
+        parts.write_buf = this.write_buf.take();
         let framed = Framed::from_parts(parts);
         this.flow.upgrade.as_ref().unwrap().call((req, framed))
     }
@@ -1043,7 +1775,8 @@ where
 
     B: MessageBody,
 
-    X: Service<Request, Response = Request>,
+    X: Service<Request>,
+    X::Response: Into<ExpectResponse>,
     X::Error: Into<Response<BoxBody>>,
 
     U: Service<(Request, Framed<T, Codec>), Response = ()>,
@@ -1077,6 +1810,10 @@ where
                 );
 
                 inner.as_mut().poll_timers(cx)?;
+                inner.as_mut().poll_shutdown_signal(cx);
+
+                #[cfg(feature = "dispatcher-trace")]
+                inner.as_mut().trace_transition("timers");
 
                 let poll = if inner.flags.contains(Flags::SHUTDOWN) {
                     if inner.flags.contains(Flags::WRITE_DISCONNECT) {
@@ -1113,26 +1850,40 @@ where
 
                     inner.as_mut().poll_request(cx)?;
 
+                    #[cfg(feature = "dispatcher-trace")]
+                    inner.as_mut().trace_transition("request");
+
                     if should_disconnect {
-                        // I/O stream should to be closed
-                        let inner = inner.as_mut().project();
-                        inner.flags.insert(Flags::READ_DISCONNECT);
-                        if let Some(mut payload) = inner.payload.take() {
-                            payload.feed_eof();
-                        }
-                    };
+                        // client half-closed the connection; data that was already read is still
+                        // decoded and in-flight responses are still written
+                        trace!("read EOF");
+                        inner.as_mut().project().flags.insert(Flags::READ_EOF);
+                    }
+
+                    if inner.flags.contains(Flags::READ_EOF) {
+                        inner.as_mut().poll_read_eof(cx);
+                    }
 
                     loop {
                         // poll response to populate write buffer
                         // drain indicates whether write buffer should be emptied before next run
-                        let drain = match inner.as_mut().poll_response(cx)? {
+                        let res = inner.as_mut().poll_response(cx)?;
+
+                        #[cfg(feature = "dispatcher-trace")]
+                        inner.as_mut().trace_transition("response");
+
+                        let drain = match res {
                             PollResponse::DrainWriteBuf => true,
 
                             PollResponse::DoNothing => {
                                 // KEEP_ALIVE is set in send_response_inner if client allows it
                                 // FINISHED is set after writing last chunk of response
                                 if inner.flags.contains(Flags::KEEP_ALIVE | Flags::FINISHED) {
-                                    if let Some(timer) = inner.config.keep_alive_deadline() {
+                                    let keep_alive = inner.codec.keep_alive_policy();
+
+                                    if let Some(timer) =
+                                        inner.config.keep_alive_deadline_for(keep_alive)
+                                    {
                                         inner.as_mut().project().ka_timer.set_and_init(
                                             cx,
                                             sleep_until(timer.into()),
@@ -1146,6 +1897,13 @@ where
 
                             // upgrade request and goes Upgrade variant of DispatcherState.
                             PollResponse::Upgrade(req) => {
+                                #[cfg(feature = "dispatcher-trace")]
+                                tracing::debug!(
+                                    target: "actix_http::h1::dispatcher",
+                                    "connection {}: upgrading",
+                                    inner.trace.conn_id,
+                                );
+
                                 let upgrade = inner.upgrade(req);
                                 self.as_mut()
                                     .project()
@@ -1187,6 +1945,24 @@ where
                         inner_p.flags.insert(Flags::SHUTDOWN);
                     }
 
+                    // connection is being drained and in-flight requests are done
+                    if inner_p.flags.contains(Flags::DRAINING)
+                        && !inner_p.flags.contains(Flags::SHUTDOWN)
+                        && state_is_none
+                        && inner_p.messages.is_empty()
+                    {
+                        trace!("connection drained; start shutdown");
+                        inner_p.flags.insert(Flags::SHUTDOWN);
+
+                        if let Some(deadline) = inner_p.config.client_disconnect_deadline() {
+                            inner_p.shutdown_timer.set_and_init(
+                                cx,
+                                sleep_until(deadline.into()),
+                                line!(),
+                            );
+                        }
+                    }
+
                     // keep-alive and stream errors
                     if state_is_none && inner_p.write_buf.is_empty() {
                         if let Some(err) = inner_p.error.take() {
@@ -1227,6 +2003,51 @@ where
     }
 }
 
+/// Dispatcher state recorded to detect transitions between polls.
+#[cfg(feature = "dispatcher-trace")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TraceSnapshot {
+    state: &'static str,
+    flags: Flags,
+    head_timer: Option<actix_rt::time::Instant>,
+    ka_timer: Option<actix_rt::time::Instant>,
+    shutdown_timer: Option<actix_rt::time::Instant>,
+}
+
+/// Connection ID and last traced state of a dispatcher.
+#[cfg(feature = "dispatcher-trace")]
+#[derive(Debug)]
+struct DispatchTrace {
+    conn_id: u64,
+    last: Option<TraceSnapshot>,
+}
+
+#[cfg(feature = "dispatcher-trace")]
+impl DispatchTrace {
+    fn new() -> Self {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        static NEXT_CONN_ID: AtomicU64 = AtomicU64::new(0);
+
+        Self {
+            conn_id: NEXT_CONN_ID.fetch_add(1, Ordering::Relaxed),
+            last: None,
+        }
+    }
+}
+
+/// Placeholder for trace data when the `dispatcher-trace` feature is disabled.
+#[cfg(not(feature = "dispatcher-trace"))]
+#[derive(Debug)]
+struct DispatchTrace;
+
+#[cfg(not(feature = "dispatcher-trace"))]
+impl DispatchTrace {
+    fn new() -> Self {
+        Self
+    }
+}
+
 #[allow(dead_code)]
 fn trace_timer_states(
     label: &str,
@@ -1,20 +1,35 @@
-use std::{future::Future, str, task::Poll, time::Duration};
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    rc::Rc,
+    str,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::Poll,
+    time::Duration,
+};
 
 use actix_codec::Framed;
 use actix_rt::{pin, time::sleep};
-use actix_service::{fn_service, Service};
+use actix_service::{fn_service, Service, ServiceExt as _};
 use actix_utils::future::{ready, Ready};
 use bytes::{Buf, Bytes, BytesMut};
 use futures_util::future::lazy;
 
 use super::dispatcher::{Dispatcher, DispatcherState, DispatcherStateProj, Flags};
 use crate::{
-    body::MessageBody,
-    config::ServiceConfig,
-    h1::{Codec, ExpectHandler, UpgradeHandler},
+    body::{BodyStream, LazyBody, MessageBody},
+    callbacks::SharedCallbacks,
+    config::{BuilderOptions, ResponseHeadObserver, ServiceConfig},
+    h1::{Codec, DrainPolicy, ExpectHandler, ExpectResponse, PipelineStats, UpgradeHandler},
+    header::{HeaderMap, HeaderName, HeaderValue, LINK},
     service::HttpFlow,
     test::{TestBuffer, TestSeqBuffer},
-    Error, HttpMessage, KeepAlive, Method, OnConnectData, Request, Response, StatusCode,
+    ConnectionCallbacks, ConnectionMeta, ConnectionStats, Error, FinishStatus, HttpMessage,
+    InformationalSender, KeepAlive, Method, OnConnectData, OnFinish, Protocol, Request,
+    RequestHead, RequestStats, Response, ResponseHead, ShutdownSignal, StatusCode,
 };
 
 fn find_slice(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
@@ -281,6 +296,265 @@ async fn keep_alive_timeout() {
     .await;
 }
 
+#[actix_rt::test]
+async fn keep_alive_policy_override() {
+    let buf = TestBuffer::new("GET /abcd HTTP/1.1\r\n\r\n");
+
+    let cfg = ServiceConfig::new(
+        KeepAlive::Timeout(Duration::from_secs(5)),
+        Duration::from_millis(100),
+        Duration::ZERO,
+        false,
+        None,
+    );
+    let services = HttpFlow::new(
+        fn_service(|_req: Request| {
+            ready(Ok::<_, Error>(
+                Response::build(StatusCode::OK)
+                    .keep_alive_policy(Duration::from_millis(200))
+                    .finish(),
+            ))
+        }),
+        ExpectHandler,
+        None,
+    );
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        cfg,
+        None,
+        OnConnectData::default(),
+    );
+    pin!(h1);
+
+    lazy(|cx| {
+        assert!(h1.as_mut().poll(cx).is_pending());
+
+        let res = buf.take_write_buf().to_vec();
+        assert!(find_slice(&res, b"keep-alive: timeout=1\r\n", 0).is_some());
+    })
+    .await;
+
+    // sleep slightly longer than the overridden keep-alive timeout
+    sleep(Duration::from_millis(250)).await;
+
+    lazy(|cx| {
+        assert!(
+            h1.as_mut().poll(cx).is_ready(),
+            "overridden keep-alive should have resolved",
+        );
+    })
+    .await;
+}
+
+#[actix_rt::test]
+async fn keep_alive_policy_disabled() {
+    let buf = TestBuffer::new("GET /abcd HTTP/1.1\r\n\r\n");
+
+    let cfg = ServiceConfig::new(
+        KeepAlive::Timeout(Duration::from_secs(5)),
+        Duration::from_millis(100),
+        Duration::ZERO,
+        false,
+        None,
+    );
+    let services = HttpFlow::new(
+        fn_service(|_req: Request| {
+            ready(Ok::<_, Error>(
+                Response::build(StatusCode::OK)
+                    .keep_alive_policy(KeepAlive::Disabled)
+                    .finish(),
+            ))
+        }),
+        ExpectHandler,
+        None,
+    );
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        cfg,
+        None,
+        OnConnectData::default(),
+    );
+    pin!(h1);
+
+    lazy(|cx| {
+        assert!(h1.as_mut().poll(cx).is_ready());
+
+        let res = buf.take_write_buf().to_vec();
+        assert!(find_slice(&res, b"connection: close\r\n", 0).is_some());
+        assert!(find_slice(&res, b"keep-alive", 0).is_none());
+    })
+    .await;
+}
+
+#[actix_rt::test]
+async fn http10_closes_by_default() {
+    let buf = TestBuffer::new("GET /abcd HTTP/1.0\r\n\r\n");
+
+    let cfg = ServiceConfig::new(
+        KeepAlive::Timeout(Duration::from_secs(5)),
+        Duration::from_millis(100),
+        Duration::ZERO,
+        false,
+        None,
+    );
+    let services = HttpFlow::new(echo_path_service(), ExpectHandler, None);
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        cfg,
+        None,
+        OnConnectData::default(),
+    );
+    pin!(h1);
+
+    lazy(|cx| {
+        assert!(
+            h1.as_mut().poll(cx).is_ready(),
+            "HTTP/1.0 connection should be closed after response",
+        );
+
+        let mut res = buf.take_write_buf().to_vec();
+        stabilize_date_header(&mut res);
+        let res = &res[..];
+
+        let exp = http_msg(
+            r"
+            HTTP/1.0 200 OK
+            content-length: 5
+            date: Thu, 01 Jan 1970 12:34:56 UTC
+
+            /abcd
+            ",
+        );
+
+        assert_eq!(
+            res,
+            exp,
+            "\nexpected response not in write buffer:\n\
+               response: {:?}\n\
+               expected: {:?}",
+            String::from_utf8_lossy(res),
+            String::from_utf8_lossy(&exp)
+        );
+    })
+    .await;
+}
+
+#[actix_rt::test]
+async fn http10_keep_alive() {
+    let mut buf = TestBuffer::new("GET /abcd HTTP/1.0\r\nConnection: keep-alive\r\n\r\n");
+
+    let cfg = ServiceConfig::new(
+        KeepAlive::Timeout(Duration::from_secs(5)),
+        Duration::from_millis(100),
+        Duration::ZERO,
+        false,
+        None,
+    );
+    let services = HttpFlow::new(echo_path_service(), ExpectHandler, None);
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        cfg,
+        None,
+        OnConnectData::default(),
+    );
+    pin!(h1);
+
+    lazy(|cx| {
+        assert!(
+            h1.as_mut().poll(cx).is_pending(),
+            "HTTP/1.0 keep-alive connection should be kept open",
+        );
+
+        let res = buf.take_write_buf().to_vec();
+        assert!(res.starts_with(b"HTTP/1.0 200 OK\r\n"));
+        assert!(find_slice(&res, b"content-length: 5\r\n", 0).is_some());
+        assert!(find_slice(&res, b"connection: keep-alive\r\n", 0).is_some());
+    })
+    .await;
+
+    buf.extend_read_buf("GET /efgh HTTP/1.0\r\nConnection: keep-alive\r\n\r\n");
+
+    lazy(|cx| {
+        assert!(h1.as_mut().poll(cx).is_pending());
+
+        let res = buf.take_write_buf().to_vec();
+        assert!(res.ends_with(b"/efgh"));
+    })
+    .await;
+}
+
+#[actix_rt::test]
+async fn http10_streaming_response_not_chunked() {
+    let buf = TestBuffer::new("GET /abcd HTTP/1.0\r\nConnection: keep-alive\r\n\r\n");
+
+    let cfg = ServiceConfig::new(
+        KeepAlive::Timeout(Duration::from_secs(5)),
+        Duration::from_millis(100),
+        Duration::ZERO,
+        false,
+        None,
+    );
+    let services = HttpFlow::new(
+        fn_service(|_req: Request| {
+            let body = BodyStream::new(futures_util::stream::iter([
+                Ok::<_, Error>(Bytes::from_static(b"hello ")),
+                Ok(Bytes::from_static(b"world")),
+            ]));
+            ready(Ok::<_, Error>(Response::ok().set_body(body)))
+        }),
+        ExpectHandler,
+        None,
+    );
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        cfg,
+        None,
+        OnConnectData::default(),
+    );
+    pin!(h1);
+
+    lazy(|cx| {
+        assert!(
+            h1.as_mut().poll(cx).is_ready(),
+            "connection should be closed to delimit streaming body",
+        );
+
+        let mut res = buf.take_write_buf().to_vec();
+        stabilize_date_header(&mut res);
+        let res = &res[..];
+
+        let exp = http_msg(
+            r"
+            HTTP/1.0 200 OK
+            date: Thu, 01 Jan 1970 12:34:56 UTC
+
+            hello world
+            ",
+        );
+
+        assert_eq!(
+            res,
+            exp,
+            "\nexpected response not in write buffer:\n\
+               response: {:?}\n\
+               expected: {:?}",
+            String::from_utf8_lossy(res),
+            String::from_utf8_lossy(&exp)
+        );
+    })
+    .await;
+}
+
 #[actix_rt::test]
 async fn keep_alive_follow_up_req() {
     let mut buf = TestBuffer::new("GET /abcd HTTP/1.1\r\n\r\n");
@@ -510,59 +784,932 @@ async fn pipelining_ok_then_ok() {
 }
 
 #[actix_rt::test]
-async fn pipelining_ok_then_bad() {
-    lazy(|cx| {
-        let buf = TestBuffer::new(
-            "\
-                GET /abcd HTTP/1.1\r\n\r\n\
-                GET /def HTTP/1\r\n\r\n\
-                ",
-        );
-
-        let cfg = ServiceConfig::new(
-            KeepAlive::Disabled,
-            Duration::from_millis(1),
-            Duration::from_millis(1),
-            false,
-            None,
-        );
-
-        let services = HttpFlow::new(echo_path_service(), ExpectHandler, None);
+async fn connection_callbacks() {
+    #[derive(Clone, Default)]
+    struct Recorder(Rc<RefCell<Vec<String>>>);
+
+    impl ConnectionCallbacks for Recorder {
+        fn on_connect(&self, conn: &ConnectionMeta) {
+            assert_eq!(conn.protocol, Protocol::Http1);
+            self.0.borrow_mut().push("connect".to_owned());
+        }
 
-        let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
-            buf.clone(),
-            services,
-            cfg,
-            None,
-            OnConnectData::default(),
-        );
+        fn on_request(&self, _conn: &ConnectionMeta, head: &RequestHead, req: &RequestStats) {
+            let size = req.head_size.unwrap();
+            self.0
+                .borrow_mut()
+                .push(format!("request {} {size}", head.uri));
+        }
 
-        pin!(h1);
+        fn on_response(&self, _conn: &ConnectionMeta, head: &ResponseHead, req: &RequestStats) {
+            assert!(req.responded_at.unwrap() >= req.received_at);
+            let size = req.response_head_size.unwrap();
+            self.0
+                .borrow_mut()
+                .push(format!("response {} {size}", head.status.as_u16()));
+        }
 
-        assert!(matches!(&h1.inner, DispatcherState::Normal { .. }));
+        fn on_body_complete(&self, _conn: &ConnectionMeta, req: &RequestStats) {
+            self.0.borrow_mut().push(format!("body {}", req.body_bytes));
+        }
 
-        match h1.as_mut().poll(cx) {
-            Poll::Pending => panic!("first poll should not be pending"),
-            Poll::Ready(res) => assert!(res.is_err()),
+        fn on_disconnect(&self, _conn: &ConnectionMeta, stats: &ConnectionStats) {
+            self.0.borrow_mut().push(format!(
+                "disconnect {} {} {}",
+                stats.requests, stats.bytes_read, stats.bytes_written
+            ));
         }
+    }
 
-        // polls: initial => shutdown
-        assert_eq!(h1.poll_count, 1);
+    let recorder = Recorder::default();
 
-        let mut res = buf.write_buf_slice_mut();
-        stabilize_date_header(&mut res);
-        let res = &res[..];
+    let req_a = "GET /abcd HTTP/1.1\r\n\r\n";
+    let req_b = "GET /def HTTP/1.1\r\n\r\n";
+    let buf = TestBuffer::new(format!("{req_a}{req_b}").as_str());
 
-        let exp = b"\
-                HTTP/1.1 200 OK\r\n\
-                content-length: 5\r\n\
-                connection: close\r\n\
-                date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
-                /abcd\
-                HTTP/1.1 400 Bad Request\r\n\
-                content-length: 0\r\n\
-                connection: close\r\n\
-                date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+    let cfg = ServiceConfig::with_builder_options(
+        KeepAlive::Disabled,
+        Duration::ZERO,
+        Duration::ZERO,
+        false,
+        None,
+        BuilderOptions {
+            connection_callbacks: Some(SharedCallbacks(Rc::new(recorder.clone()))),
+            ..BuilderOptions::default()
+        },
+    );
+    let services = HttpFlow::new(echo_path_service(), ExpectHandler, None);
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        cfg,
+        None,
+        OnConnectData::default(),
+    );
+    h1.await.unwrap();
+
+    // both response heads have the same size and are followed by 5 and 4 body bytes
+    let written = buf.write_buf_slice().len();
+    let head = (written - 9) / 2;
+
+    assert_eq!(
+        *recorder.0.borrow(),
+        [
+            "connect".to_owned(),
+            format!("request /abcd {}", req_a.len()),
+            // first request is handled eagerly, before the next one is parsed
+            format!("response 200 {head}"),
+            format!("request /def {}", req_b.len()),
+            "body 5".to_owned(),
+            format!("response 200 {head}"),
+            "body 4".to_owned(),
+            format!("disconnect 2 {} {written}", req_a.len() + req_b.len()),
+        ]
+    );
+}
+
+fn keep_alive_config(options: BuilderOptions) -> ServiceConfig {
+    ServiceConfig::with_builder_options(
+        KeepAlive::Timeout(Duration::from_secs(5)),
+        Duration::ZERO,
+        Duration::ZERO,
+        false,
+        None,
+        options,
+    )
+}
+
+fn shutdown_config(signal: &ShutdownSignal) -> ServiceConfig {
+    keep_alive_config(BuilderOptions {
+        shutdown_signal: Some(signal.clone()),
+        ..BuilderOptions::default()
+    })
+}
+
+#[actix_rt::test]
+async fn shutdown_signal_closes_idle_connection() {
+    let buf = TestBuffer::new("GET /abcd HTTP/1.1\r\n\r\n");
+    let signal = ShutdownSignal::new();
+
+    let services = HttpFlow::new(echo_path_service(), ExpectHandler, None);
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        shutdown_config(&signal),
+        None,
+        OnConnectData::default(),
+    );
+
+    pin!(h1);
+
+    lazy(|cx| {
+        assert!(h1.as_mut().poll(cx).is_pending());
+
+        if let DispatcherStateProj::Normal { inner } = h1.as_mut().project().inner.project() {
+            assert!(inner.flags.contains(Flags::KEEP_ALIVE));
+        }
+    })
+    .await;
+
+    let res = buf.take_write_buf();
+    assert!(res.starts_with(b"HTTP/1.1 200 OK\r\n"));
+    assert!(find_slice(&res, b"connection: close", 0).is_none());
+
+    // idle connection is closed as soon as the signal is triggered
+    signal.shutdown();
+    h1.await.unwrap();
+
+    assert!(buf.write_buf_slice().is_empty());
+}
+
+#[actix_rt::test]
+async fn shutdown_signal_drains_in_flight_request() {
+    let buf = TestBuffer::new(
+        "\
+            GET /abcd HTTP/1.1\r\n\r\n\
+            GET /def HTTP/1.1\r\n\r\n\
+            ",
+    );
+    let signal = ShutdownSignal::new();
+
+    let slow_service = fn_service(|req: Request| async move {
+        sleep(Duration::from_millis(50)).await;
+        let path = Bytes::copy_from_slice(req.path().as_bytes());
+        Ok::<_, Error>(Response::ok().set_body(path))
+    });
+
+    let services = HttpFlow::new(slow_service, ExpectHandler, None);
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        shutdown_config(&signal),
+        None,
+        OnConnectData::default(),
+    );
+
+    pin!(h1);
+
+    // first request is in-flight and the second one is queued
+    lazy(|cx| assert!(h1.as_mut().poll(cx).is_pending())).await;
+    assert!(buf.write_buf_slice().is_empty());
+
+    signal.shutdown();
+    h1.await.unwrap();
+
+    let mut res = buf.write_buf_slice_mut();
+    stabilize_date_header(&mut res);
+    let res = &res[..];
+
+    let exp = b"\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 5\r\n\
+            connection: close\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            /abcd\
+            ";
+
+    assert_eq!(
+        res,
+        exp,
+        "\nexpected response not in write buffer:\n\
+           response: {:?}\n\
+           expected: {:?}",
+        String::from_utf8_lossy(res),
+        String::from_utf8_lossy(exp)
+    );
+}
+
+#[actix_rt::test]
+async fn max_requests_per_connection() {
+    let buf = TestBuffer::new(
+        "\
+            GET /abcd HTTP/1.1\r\n\r\n\
+            GET /def HTTP/1.1\r\n\r\n\
+            GET /ghi HTTP/1.1\r\n\r\n\
+            ",
+    );
+
+    let cfg = keep_alive_config(BuilderOptions {
+        max_requests_per_connection: Some(2),
+        ..BuilderOptions::default()
+    });
+
+    let services = HttpFlow::new(echo_path_service(), ExpectHandler, None);
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        cfg,
+        None,
+        OnConnectData::default(),
+    );
+
+    h1.await.unwrap();
+
+    let mut res = buf.write_buf_slice_mut();
+    stabilize_date_header(&mut res);
+    let res = &res[..];
+
+    let exp = b"\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 5\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            /abcd\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 4\r\n\
+            connection: close\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            /def\
+            ";
+
+    assert_eq!(
+        res,
+        exp,
+        "\nexpected response not in write buffer:\n\
+           response: {:?}\n\
+           expected: {:?}",
+        String::from_utf8_lossy(res),
+        String::from_utf8_lossy(exp)
+    );
+}
+
+#[actix_rt::test]
+async fn max_response_head_size() {
+    let buf = TestBuffer::new(
+        "\
+            GET /abcd HTTP/1.1\r\n\r\n\
+            GET /big HTTP/1.1\r\n\r\n\
+            GET /ghi HTTP/1.1\r\n\r\n\
+            ",
+    );
+
+    let overflows = Rc::new(Cell::new(0));
+
+    let cfg = keep_alive_config(BuilderOptions {
+        max_response_head_size: Some(128),
+        response_head_overflow: Some(ResponseHeadObserver(Rc::new({
+            let overflows = Rc::clone(&overflows);
+            move |head: &ResponseHead, size| {
+                assert_eq!(head.status, StatusCode::OK);
+                assert!(size > 256);
+                overflows.set(overflows.get() + 1);
+            }
+        }))),
+        ..BuilderOptions::default()
+    });
+
+    let services = HttpFlow::new(
+        fn_service(|req: Request| {
+            let mut res = Response::with_body(StatusCode::OK, Bytes::from_static(b"ok"));
+
+            if req.path() == "/big" {
+                let val = HeaderValue::from_str(&"x".repeat(256)).unwrap();
+                res.headers_mut()
+                    .insert(HeaderName::from_static("x-debug"), val);
+            }
+
+            ready(Ok::<_, Error>(res))
+        }),
+        ExpectHandler,
+        None,
+    );
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        cfg,
+        None,
+        OnConnectData::default(),
+    );
+
+    h1.await.unwrap();
+    assert_eq!(overflows.get(), 1);
+
+    let mut res = buf.write_buf_slice_mut();
+    stabilize_date_header(&mut res);
+    let res = &res[..];
+
+    let exp = b"\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 2\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            ok\
+            HTTP/1.1 500 Internal Server Error\r\n\
+            content-length: 0\r\n\
+            connection: close\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            ";
+
+    assert_eq!(
+        res,
+        exp,
+        "\nexpected response not in write buffer:\n\
+           response: {:?}\n\
+           expected: {:?}",
+        String::from_utf8_lossy(res),
+        String::from_utf8_lossy(exp)
+    );
+}
+
+#[actix_rt::test]
+async fn max_requests_per_connection_pipelined() {
+    let buf = TestBuffer::new(
+        "\
+            GET /abcd HTTP/1.1\r\n\r\n\
+            GET /def HTTP/1.1\r\n\r\n\
+            GET /ghi HTTP/1.1\r\n\r\n\
+            ",
+    );
+
+    let cfg = keep_alive_config(BuilderOptions {
+        max_requests_per_connection: Some(3),
+        ..BuilderOptions::default()
+    });
+
+    // first request is still in-flight when the others are read and queued
+    let slow_echo_service = fn_service(|req: Request| async move {
+        if req.path() == "/abcd" {
+            sleep(Duration::from_millis(20)).await;
+        }
+
+        Ok::<_, Error>(Response::ok().set_body(Bytes::copy_from_slice(req.path().as_bytes())))
+    });
+
+    let services = HttpFlow::new(slow_echo_service, ExpectHandler, None);
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        cfg,
+        None,
+        OnConnectData::default(),
+    );
+
+    h1.await.unwrap();
+
+    let mut res = buf.write_buf_slice_mut();
+    stabilize_date_header(&mut res);
+    let res = &res[..];
+
+    // queued requests are answered; only the last response closes the connection
+    let exp = b"\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 5\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            /abcd\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 4\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            /def\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 4\r\n\
+            connection: close\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            /ghi\
+            ";
+
+    assert_eq!(
+        res,
+        exp,
+        "\nexpected response not in write buffer:\n\
+           response: {:?}\n\
+           expected: {:?}",
+        String::from_utf8_lossy(res),
+        String::from_utf8_lossy(exp)
+    );
+}
+
+#[actix_rt::test]
+async fn max_connection_lifetime() {
+    let buf = TestBuffer::new("GET /abcd HTTP/1.1\r\n\r\n");
+
+    let cfg = keep_alive_config(BuilderOptions {
+        max_connection_lifetime: Some(Duration::from_millis(20)),
+        ..BuilderOptions::default()
+    });
+
+    let slow_service = fn_service(|_req: Request| async move {
+        sleep(Duration::from_millis(50)).await;
+        Ok::<_, Error>(Response::ok())
+    });
+
+    let services = HttpFlow::new(slow_service, ExpectHandler, None);
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        cfg,
+        None,
+        OnConnectData::default(),
+    );
+
+    // lifetime elapses while request is in-flight; it is finished before closing
+    h1.await.unwrap();
+
+    let res = buf.take_write_buf();
+    assert!(res.starts_with(b"HTTP/1.1 200 OK\r\n"));
+    assert!(find_slice(&res, b"connection: close", 0).is_some());
+}
+
+/// Echoes the request payload after a short delay, or reports why reading it failed.
+fn delayed_echo_service() -> impl Service<Request, Response = Response<Bytes>, Error = Error> {
+    fn_service(|mut req: Request| async move {
+        use futures_util::StreamExt as _;
+
+        sleep(Duration::from_millis(20)).await;
+
+        let mut pl = req.take_payload();
+        let mut body = BytesMut::new();
+        while let Some(chunk) = pl.next().await {
+            match chunk {
+                Ok(chunk) => body.extend_from_slice(&chunk),
+                Err(_) => return Ok(Response::bad_request().set_body(Bytes::from("incomplete"))),
+            }
+        }
+
+        Ok::<_, Error>(Response::ok().set_body(body.freeze()))
+    })
+}
+
+#[actix_rt::test]
+async fn connection_close_stops_pipelining() {
+    let buf = TestBuffer::new(
+        "\
+            GET /abcd HTTP/1.1\r\nConnection: close\r\n\r\n\
+            GET /def HTTP/1.1\r\n\r\n\
+            ",
+    );
+
+    let services = HttpFlow::new(echo_path_service(), ExpectHandler, None);
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        keep_alive_config(BuilderOptions::default()),
+        None,
+        OnConnectData::default(),
+    );
+
+    h1.await.unwrap();
+
+    let mut res = buf.write_buf_slice_mut();
+    stabilize_date_header(&mut res);
+    let res = &res[..];
+
+    let exp = b"\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 5\r\n\
+            connection: close\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            /abcd\
+            ";
+
+    assert_eq!(
+        res,
+        exp,
+        "\nexpected response not in write buffer:\n\
+           response: {:?}\n\
+           expected: {:?}",
+        String::from_utf8_lossy(res),
+        String::from_utf8_lossy(exp)
+    );
+}
+
+#[actix_rt::test]
+async fn pipeline_limit() {
+    let buf = TestBuffer::new(
+        "\
+            GET /a HTTP/1.1\r\n\r\n\
+            GET /b HTTP/1.1\r\n\r\n\
+            GET /c HTTP/1.1\r\n\r\n\
+            GET /d HTTP/1.1\r\n\r\n\
+            GET /e HTTP/1.1\r\n\r\n\
+            ",
+    );
+
+    let cfg = keep_alive_config(BuilderOptions {
+        max_pipelined_requests: Some(2),
+        ..BuilderOptions::default()
+    });
+
+    let services = HttpFlow::new(
+        fn_service(|req: Request| async move {
+            sleep(Duration::from_millis(5)).await;
+
+            let stats = *req.extensions().get::<PipelineStats>().unwrap();
+            let body = format!("{}:{}:{}", req.path(), stats.depth, stats.max_depth);
+            Ok::<_, Error>(Response::ok().set_body(body))
+        }),
+        ExpectHandler,
+        None,
+    );
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        cfg,
+        None,
+        OnConnectData::default(),
+    );
+
+    h1.await.unwrap();
+
+    let mut res = buf.write_buf_slice_mut();
+    stabilize_date_header(&mut res);
+    let res = &res[..];
+
+    // third request is queued behind two others; fourth one exceeds the limit and the connection
+    // is closed after responding to it
+    let exp = b"\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 6\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            /a:1:1\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 6\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            /b:2:2\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 6\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            /c:3:3\
+            HTTP/1.1 503 Service Unavailable\r\n\
+            content-length: 0\r\n\
+            connection: close\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            ";
+
+    assert_eq!(
+        res,
+        exp,
+        "\nexpected response not in write buffer:\n\
+           response: {:?}\n\
+           expected: {:?}",
+        String::from_utf8_lossy(res),
+        String::from_utf8_lossy(exp)
+    );
+}
+
+#[actix_rt::test]
+async fn half_close_keeps_in_flight_response() {
+    let mut buf = TestSeqBuffer::new("POST /test HTTP/1.1\r\nContent-Length: 4\r\n\r\ndata");
+    buf.close_read();
+
+    let services = HttpFlow::new(delayed_echo_service(), ExpectHandler, None);
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        keep_alive_config(BuilderOptions::default()),
+        None,
+        OnConnectData::default(),
+    );
+    pin!(h1);
+
+    lazy(|cx| {
+        assert!(h1.as_mut().poll(cx).is_pending());
+
+        if let DispatcherStateProj::Normal { inner } = h1.as_mut().project().inner.project() {
+            assert!(inner
+                .flags
+                .contains(Flags::READ_EOF | Flags::READ_DISCONNECT));
+            assert!(!inner.flags.contains(Flags::WRITE_DISCONNECT));
+        }
+    })
+    .await;
+
+    h1.await.unwrap();
+
+    let res = buf.write_buf();
+    let res = str::from_utf8(&res).unwrap();
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{res}");
+    assert!(res.ends_with("\r\n\r\ndata"), "{res}");
+}
+
+#[actix_rt::test]
+async fn half_close_with_incomplete_payload() {
+    let mut buf = TestSeqBuffer::new("POST /test HTTP/1.1\r\nContent-Length: 10\r\n\r\ndata");
+    buf.close_read();
+
+    let services = HttpFlow::new(delayed_echo_service(), ExpectHandler, None);
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        keep_alive_config(BuilderOptions::default()),
+        None,
+        OnConnectData::default(),
+    );
+
+    h1.await.unwrap();
+
+    let res = buf.write_buf();
+    let res = str::from_utf8(&res).unwrap();
+    assert!(res.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{res}");
+    assert!(res.ends_with("\r\n\r\nincomplete"), "{res}");
+}
+
+#[actix_rt::test]
+async fn reset_aborts_in_flight_response() {
+    use crate::error::DispatchError;
+
+    let mut buf = TestSeqBuffer::new("POST /test HTTP/1.1\r\nContent-Length: 4\r\n\r\ndata");
+    buf.set_read_err(std::io::ErrorKind::ConnectionReset.into());
+
+    let services = HttpFlow::new(delayed_echo_service(), ExpectHandler, None);
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        keep_alive_config(BuilderOptions::default()),
+        None,
+        OnConnectData::default(),
+    );
+
+    match h1.await {
+        Err(DispatchError::Io(err)) => {
+            assert_eq!(err.kind(), std::io::ErrorKind::ConnectionReset)
+        }
+        res => panic!("unexpected dispatcher result: {res:?}"),
+    }
+
+    assert!(buf.write_buf().is_empty());
+}
+
+#[actix_rt::test]
+async fn informational_responses() {
+    let buf = TestBuffer::new("GET / HTTP/1.1\r\n\r\n");
+
+    let cfg = ServiceConfig::new(
+        KeepAlive::Disabled,
+        Duration::ZERO,
+        Duration::ZERO,
+        false,
+        None,
+    );
+    let services = HttpFlow::new(
+        fn_service(|req: Request| async move {
+            let tx = req
+                .extensions()
+                .get::<InformationalSender>()
+                .cloned()
+                .unwrap();
+
+            let mut headers = HeaderMap::new();
+            headers.insert(LINK, HeaderValue::from_static("</style.css>; rel=preload"));
+            tx.send(Response::early_hints(headers)).unwrap();
+
+            sleep(Duration::from_millis(20)).await;
+
+            Ok::<_, Error>((Response::with_body(StatusCode::OK, "done"), tx))
+        })
+        .map(|(res, tx)| {
+            // final response is about to be written
+            assert!(!tx.is_closed());
+            res
+        }),
+        ExpectHandler,
+        None,
+    );
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        cfg,
+        None,
+        OnConnectData::default(),
+    );
+    pin!(h1);
+
+    // interim response is written while the service call is in progress
+    lazy(|cx| assert!(h1.as_mut().poll(cx).is_pending())).await;
+
+    let mut res = buf.take_write_buf().to_vec();
+    stabilize_date_header(&mut res);
+    assert_eq!(
+        str::from_utf8(&res).unwrap(),
+        "\
+            HTTP/1.1 103 Early Hints\r\n\
+            link: </style.css>; rel=preload\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\
+            \r\n\
+            "
+    );
+
+    assert!(h1.as_mut().await.is_ok());
+
+    let mut res = buf.take_write_buf().to_vec();
+    stabilize_date_header(&mut res);
+    assert_eq!(
+        str::from_utf8(&res).unwrap(),
+        "\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 4\r\n\
+            connection: close\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\
+            \r\n\
+            done\
+            "
+    );
+}
+
+#[actix_rt::test]
+async fn finish_callbacks() {
+    async fn run(path: &'static str) -> Option<FinishStatus> {
+        let status = Rc::new(Cell::new(None));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+
+        let status2 = Rc::clone(&status);
+        let in_flight2 = Arc::clone(&in_flight);
+        let services = HttpFlow::new(
+            fn_service(move |req: Request| {
+                assert_eq!(in_flight2.load(Ordering::Relaxed), 1);
+                let status = Rc::clone(&status2);
+
+                req.extensions()
+                    .get::<OnFinish>()
+                    .unwrap()
+                    .register(move |finish| async move { status.set(Some(finish)) });
+
+                let body = futures_util::stream::iter([
+                    Ok::<_, Error>(Bytes::from_static(b"data")),
+                    if req.path() == "/err" {
+                        Err(Error::new_body())
+                    } else {
+                        Ok(Bytes::from_static(b"data"))
+                    },
+                ]);
+
+                ready(Ok::<_, Error>(
+                    Response::ok().set_body(BodyStream::new(body)),
+                ))
+            }),
+            ExpectHandler,
+            None,
+        );
+
+        let buf = TestBuffer::new(format!("GET {path} HTTP/1.1\r\n\r\n").as_str());
+        let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+            buf,
+            services,
+            ServiceConfig::with_builder_options(
+                KeepAlive::Disabled,
+                Duration::ZERO,
+                Duration::ZERO,
+                false,
+                None,
+                BuilderOptions {
+                    in_flight_counter: Some(Arc::clone(&in_flight)),
+                    ..BuilderOptions::default()
+                },
+            ),
+            None,
+            OnConnectData::default(),
+        );
+        let _ = h1.await;
+        assert_eq!(in_flight.load(Ordering::Relaxed), 0);
+
+        actix_rt::task::yield_now().await;
+        status.get()
+    }
+
+    assert_eq!(run("/").await, Some(FinishStatus::Completed));
+    assert_eq!(run("/err").await, Some(FinishStatus::Aborted));
+}
+
+#[actix_rt::test]
+async fn omitted_bodies_not_generated() {
+    let buf = TestBuffer::new(
+        "\
+            HEAD /a HTTP/1.1\r\n\r\n\
+            GET /a HTTP/1.1\r\nIf-None-Match: \"x\"\r\n\r\n\
+            GET /a HTTP/1.1\r\nConnection: close\r\n\r\n\
+            ",
+    );
+
+    let generated = Rc::new(Cell::new(0));
+
+    let services = HttpFlow::new(
+        fn_service({
+            let generated = Rc::clone(&generated);
+
+            move |req: Request| {
+                let generated = Rc::clone(&generated);
+
+                let status = if req.headers().contains_key("if-none-match") {
+                    StatusCode::NOT_MODIFIED
+                } else {
+                    StatusCode::OK
+                };
+
+                let body = LazyBody::new(move || {
+                    generated.set(generated.get() + 1);
+                    "body"
+                });
+
+                ready(Ok::<_, Error>(Response::with_body(status, body)))
+            }
+        }),
+        ExpectHandler,
+        None,
+    );
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        keep_alive_config(BuilderOptions::default()),
+        None,
+        OnConnectData::default(),
+    );
+
+    h1.await.unwrap();
+    assert_eq!(generated.get(), 1);
+
+    let mut res = buf.write_buf_slice_mut();
+    stabilize_date_header(&mut res);
+    let res = &res[..];
+
+    let exp = b"\
+            HTTP/1.1 200 OK\r\n\
+            transfer-encoding: chunked\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            HTTP/1.1 304 Not Modified\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            HTTP/1.1 200 OK\r\n\
+            transfer-encoding: chunked\r\n\
+            connection: close\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            4\r\nbody\r\n0\r\n\r\n\
+            ";
+
+    assert_eq!(
+        res,
+        exp,
+        "\nexpected response not in write buffer:\n\
+           response: {:?}\n\
+           expected: {:?}",
+        String::from_utf8_lossy(res),
+        String::from_utf8_lossy(exp)
+    );
+}
+
+#[actix_rt::test]
+async fn pipelining_ok_then_bad() {
+    lazy(|cx| {
+        let buf = TestBuffer::new(
+            "\
+                GET /abcd HTTP/1.1\r\n\r\n\
+                GET /def HTTP/1\r\n\r\n\
+                ",
+        );
+
+        let cfg = ServiceConfig::new(
+            KeepAlive::Disabled,
+            Duration::from_millis(1),
+            Duration::from_millis(1),
+            false,
+            None,
+        );
+
+        let services = HttpFlow::new(echo_path_service(), ExpectHandler, None);
+
+        let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+            buf.clone(),
+            services,
+            cfg,
+            None,
+            OnConnectData::default(),
+        );
+
+        pin!(h1);
+
+        assert!(matches!(&h1.inner, DispatcherState::Normal { .. }));
+
+        match h1.as_mut().poll(cx) {
+            Poll::Pending => panic!("first poll should not be pending"),
+            Poll::Ready(res) => assert!(res.is_err()),
+        }
+
+        // polls: initial => shutdown
+        assert_eq!(h1.poll_count, 1);
+
+        let mut res = buf.write_buf_slice_mut();
+        stabilize_date_header(&mut res);
+        let res = &res[..];
+
+        let exp = b"\
+                HTTP/1.1 200 OK\r\n\
+                content-length: 5\r\n\
+                connection: close\r\n\
+                date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+                /abcd\
+                HTTP/1.1 400 Bad Request\r\n\
+                content-length: 0\r\n\
+                connection: close\r\n\
+                date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
                 ";
 
         assert_eq!(
@@ -721,6 +1868,115 @@ async fn expect_eager() {
     .await;
 }
 
+fn rejecting_expect_service() -> impl Service<Request, Response = ExpectResponse, Error = Error> {
+    fn_service(|req: Request| async move {
+        let res = match req.path() {
+            "/drain" => ExpectResponse::Reject(
+                Response::new(StatusCode::EXPECTATION_FAILED),
+                DrainPolicy::Drain,
+            ),
+            "/close" => ExpectResponse::Reject(
+                Response::new(StatusCode::PAYLOAD_TOO_LARGE),
+                DrainPolicy::Close,
+            ),
+            _ => ExpectResponse::Continue(req),
+        };
+
+        Ok::<_, Error>(res)
+    })
+}
+
+#[actix_rt::test]
+async fn expect_reject_drain() {
+    let mut buf = TestBuffer::new(
+        "\
+            POST /drain HTTP/1.1\r\n\
+            Content-Length: 5\r\n\
+            Expect: 100-continue\r\n\
+            \r\n\
+            ",
+    );
+
+    let services = HttpFlow::new(echo_path_service(), rejecting_expect_service(), None);
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        keep_alive_config(BuilderOptions::default()),
+        None,
+        OnConnectData::default(),
+    );
+    pin!(h1);
+
+    lazy(|cx| assert!(h1.as_mut().poll(cx).is_pending())).await;
+
+    // client sends the body anyway after receiving the rejection
+    buf.extend_read_buf("helloGET /next HTTP/1.1\r\n\r\n");
+
+    lazy(|cx| {
+        for _ in 0..3 {
+            assert!(h1.as_mut().poll(cx).is_pending());
+        }
+    })
+    .await;
+
+    let mut res = buf.write_buf_slice_mut();
+    stabilize_date_header(&mut res);
+
+    // body of the rejected request is discarded instead of being parsed as a request
+    assert_eq!(
+        str::from_utf8(&res).unwrap(),
+        "\
+            HTTP/1.1 417 Expectation Failed\r\n\
+            content-length: 0\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 5\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            /next\
+            "
+    );
+}
+
+#[actix_rt::test]
+async fn expect_reject_close() {
+    let buf = TestBuffer::new(
+        "\
+            POST /close HTTP/1.1\r\n\
+            Content-Length: 5\r\n\
+            Expect: 100-continue\r\n\
+            \r\n\
+            hello\
+            GET /next HTTP/1.1\r\n\r\n\
+            ",
+    );
+
+    let services = HttpFlow::new(echo_path_service(), rejecting_expect_service(), None);
+
+    let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+        buf.clone(),
+        services,
+        keep_alive_config(BuilderOptions::default()),
+        None,
+        OnConnectData::default(),
+    );
+
+    h1.await.unwrap();
+
+    let mut res = buf.write_buf_slice_mut();
+    stabilize_date_header(&mut res);
+
+    assert_eq!(
+        str::from_utf8(&res).unwrap(),
+        "\
+            HTTP/1.1 413 Payload Too Large\r\n\
+            content-length: 0\r\n\
+            connection: close\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            "
+    );
+}
+
 #[actix_rt::test]
 async fn upgrade_handling() {
     struct TestUpgrade;
@@ -780,6 +2036,122 @@ async fn upgrade_handling() {
     .await;
 }
 
+#[actix_rt::test]
+async fn upgrade_handover_read_buf() {
+    use std::{cell::RefCell, rc::Rc};
+
+    #[derive(Clone, Default)]
+    struct TestUpgrade {
+        read_buf: Rc<RefCell<Option<BytesMut>>>,
+    }
+
+    impl<T> Service<(Request, Framed<T, Codec>)> for TestUpgrade {
+        type Response = ();
+        type Error = Error;
+        type Future = Ready<Result<Self::Response, Self::Error>>;
+
+        actix_service::always_ready!();
+
+        fn call(&self, (req, framed): (Request, Framed<T, Codec>)) -> Self::Future {
+            assert!(req.upgrade());
+            *self.read_buf.borrow_mut() = Some(framed.into_parts().read_buf);
+            ready(Ok(()))
+        }
+    }
+
+    let upgrade = TestUpgrade::default();
+
+    lazy(|cx| {
+        let mut buf = TestSeqBuffer::empty();
+        let cfg = ServiceConfig::new(
+            KeepAlive::Disabled,
+            Duration::ZERO,
+            Duration::ZERO,
+            false,
+            None,
+        );
+
+        let services = HttpFlow::new(ok_service(), ExpectHandler, Some(upgrade.clone()));
+
+        let h1 = Dispatcher::<_, _, _, _, TestUpgrade>::new(
+            buf.clone(),
+            services,
+            cfg,
+            None,
+            OnConnectData::default(),
+        );
+
+        // bytes of the upgraded protocol are sent along with the request head
+        buf.extend_read_buf(
+            "\
+                GET /ws HTTP/1.1\r\n\
+                Connection: Upgrade\r\n\
+                Upgrade: websocket, custom\r\n\
+                \r\n\
+                HELLO\r\n\
+                ",
+        );
+
+        pin!(h1);
+
+        assert!(h1.as_mut().poll(cx).is_ready());
+        assert!(matches!(&h1.inner, DispatcherState::Upgrade { .. }));
+    })
+    .await;
+
+    let read_buf = upgrade.read_buf.borrow_mut().take().unwrap();
+    assert_eq!(&read_buf[..], b"HELLO\r\n");
+}
+
+#[actix_rt::test]
+async fn upgrade_h2c_not_handed_off() {
+    struct TestUpgrade;
+
+    impl<T> Service<(Request, Framed<T, Codec>)> for TestUpgrade {
+        type Response = ();
+        type Error = Error;
+        type Future = Ready<Result<Self::Response, Self::Error>>;
+
+        actix_service::always_ready!();
+
+        fn call(&self, _: (Request, Framed<T, Codec>)) -> Self::Future {
+            panic!("h2c upgrade should not be handed to the upgrade service");
+        }
+    }
+
+    let buf = TestBuffer::new(
+        "\
+            GET /abcd HTTP/1.1\r\n\
+            Connection: Upgrade, HTTP2-Settings\r\n\
+            Upgrade: h2c\r\n\
+            HTTP2-Settings: AAMAAABkAAQAAP__\r\n\
+            \r\n\
+            ",
+    );
+
+    let services = HttpFlow::new(echo_path_service(), ExpectHandler, Some(TestUpgrade));
+
+    let h1 = Dispatcher::<_, _, _, _, TestUpgrade>::new(
+        buf.clone(),
+        services,
+        ServiceConfig::default(),
+        None,
+        OnConnectData::default(),
+    );
+
+    pin!(h1);
+
+    lazy(|cx| {
+        assert!(h1.as_mut().poll(cx).is_pending());
+        assert!(matches!(&h1.inner, DispatcherState::Normal { .. }));
+
+        let res = buf.take_write_buf();
+        assert!(res.starts_with(b"HTTP/1.1 200 OK\r\n"));
+        assert!(res.ends_with(b"/abcd"));
+    })
+    .await;
+}
+
 // fix in #2624 reverted temporarily
 // complete fix tracked in #2745
 #[ignore]
@@ -970,3 +2342,58 @@ fn http_msg_creates_msg() {
         "GET / HTTP/1.1\r\nContent-Length: 3\r\n\r\n"
     );
 }
+
+#[actix_rt::test]
+async fn body_length_mismatch() {
+    use futures_util::stream;
+
+    use crate::{body::SizedStream, error::DispatchError};
+
+    async fn run(declared: u64, chunks: &'static [&'static [u8]]) -> Result<(), DispatchError> {
+        let buf = TestBuffer::new("GET /test HTTP/1.1\r\nConnection: close\r\n\r\n");
+
+        let services = HttpFlow::new(
+            fn_service(move |_req: Request| {
+                let body = SizedStream::new(
+                    declared,
+                    stream::iter(
+                        chunks
+                            .iter()
+                            .map(|chunk| Ok::<_, Error>(Bytes::from_static(chunk))),
+                    ),
+                );
+
+                ready(Ok::<_, Error>(Response::ok().set_body(body)))
+            }),
+            ExpectHandler,
+            None,
+        );
+
+        Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+            buf,
+            services,
+            ServiceConfig::default(),
+            None,
+            OnConnectData::default(),
+        )
+        .await
+    }
+
+    assert!(run(8, &[b"data", b"data"]).await.is_ok());
+
+    match run(8, &[b"data"]).await {
+        Err(DispatchError::BodyLengthMismatch { expected, actual }) => {
+            assert_eq!(expected, 8);
+            assert_eq!(actual, 4);
+        }
+        res => panic!("unexpected dispatcher result: {res:?}"),
+    }
+
+    match run(8, &[b"data", b"data", b"!"]).await {
+        Err(DispatchError::BodyLengthMismatch { expected, actual }) => {
+            assert_eq!(expected, 8);
+            assert_eq!(actual, 9);
+        }
+        res => panic!("unexpected dispatcher result: {res:?}"),
+    }
+}
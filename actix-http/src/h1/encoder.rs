@@ -6,12 +6,15 @@ use std::{
     slice::from_raw_parts_mut,
 };
 
-use bytes::{BufMut, BytesMut};
+use actix_codec::Encoder;
+use bytes::{BufMut, Bytes, BytesMut};
 
 use crate::{
     body::BodySize,
+    h1::{buffer::WriteBuf, PayloadItem},
     header::{
-        map::Value, HeaderMap, HeaderName, CONNECTION, CONTENT_LENGTH, DATE, TRANSFER_ENCODING,
+        map::Value, HeaderMap, HeaderName, CONNECTION, CONTENT_LENGTH, DATE, TRAILER,
+        TRANSFER_ENCODING,
     },
     helpers, ConnectionType, RequestHeadType, Response, ServiceConfig, StatusCode, Version,
 };
@@ -66,17 +69,6 @@ pub(crate) trait MessageType: Sized {
         // Content length
         if let Some(status) = self.status() {
             match status {
-                StatusCode::CONTINUE
-                | StatusCode::SWITCHING_PROTOCOLS
-                | StatusCode::PROCESSING
-                | StatusCode::NO_CONTENT => {
-                    // skip content-length and transfer-encoding headers
-                    // see https://datatracker.ietf.org/doc/html/rfc7230#section-3.3.1
-                    // and https://datatracker.ietf.org/doc/html/rfc7230#section-3.3.2
-                    skip_len = true;
-                    length = BodySize::None
-                }
-
                 StatusCode::NOT_MODIFIED => {
                     // 304 responses should never have a body but should retain a manually set
                     // content-length header
@@ -85,6 +77,22 @@ pub(crate) trait MessageType: Sized {
                     length = BodySize::None;
                 }
 
+                StatusCode::RESET_CONTENT => {
+                    // 205 responses must not have a body, which is best indicated by an explicit
+                    // zero content-length
+                    // see https://datatracker.ietf.org/doc/html/rfc9110#section-15.3.6
+                    skip_len = true;
+                    length = BodySize::Sized(0);
+                }
+
+                status if status.is_informational() || status == StatusCode::NO_CONTENT => {
+                    // skip content-length and transfer-encoding headers
+                    // see https://datatracker.ietf.org/doc/html/rfc7230#section-3.3.1
+                    // and https://datatracker.ietf.org/doc/html/rfc7230#section-3.3.2
+                    skip_len = true;
+                    length = BodySize::None
+                }
+
                 _ => {}
             }
         }
@@ -323,11 +331,35 @@ impl<T: MessageType> MessageEncoder<T> {
         self.te.encode(msg, buf)
     }
 
+    /// Encode chunk into a write buffer, avoiding a copy of the payload where possible.
+    pub(crate) fn encode_chunk_bytes(
+        &mut self,
+        msg: Bytes,
+        buf: &mut WriteBuf,
+    ) -> io::Result<bool> {
+        self.te.encode_bytes(msg, buf)
+    }
+
     /// Encode EOF.
     pub fn encode_eof(&mut self, buf: &mut BytesMut) -> io::Result<()> {
         self.te.encode_eof(buf)
     }
 
+    /// Encode EOF, followed by trailer fields if the transfer encoding supports them.
+    pub fn encode_trailers(&mut self, trailers: &HeaderMap, buf: &mut BytesMut) -> io::Result<()> {
+        self.te.encode_trailers(trailers, buf)
+    }
+
+    /// Returns the number of payload bytes that can be written to the I/O without framing.
+    pub(crate) fn unframed_remaining(&self) -> Option<u64> {
+        self.te.unframed_remaining()
+    }
+
+    /// Records payload bytes that were written to the I/O directly, bypassing this encoder.
+    pub(crate) fn consume_unframed(&mut self, len: u64) {
+        self.te.consume_unframed(len)
+    }
+
     /// Encode message.
     pub fn encode(
         &mut self,
@@ -414,6 +446,23 @@ impl TransferEncoding {
         }
     }
 
+    /// Returns the number of payload bytes remaining when no framing is applied to them.
+    #[inline]
+    pub(crate) fn unframed_remaining(&self) -> Option<u64> {
+        match self.kind {
+            TransferEncodingKind::Length(remaining) => Some(remaining),
+            TransferEncodingKind::Chunked(_) | TransferEncodingKind::Eof => None,
+        }
+    }
+
+    /// Records payload bytes that were written without going through this encoder.
+    #[inline]
+    pub(crate) fn consume_unframed(&mut self, len: u64) {
+        if let TransferEncodingKind::Length(ref mut remaining) = self.kind {
+            *remaining = remaining.saturating_sub(len);
+        }
+    }
+
     /// Encode message. Return `EOF` state of encoder
     #[inline]
     pub fn encode(&mut self, msg: &[u8], buf: &mut BytesMut) -> io::Result<bool> {
@@ -459,6 +508,37 @@ impl TransferEncoding {
         }
     }
 
+    /// Encode message into a write buffer, avoiding a copy of the payload where possible. Return
+    /// `EOF` state of encoder
+    pub(crate) fn encode_bytes(&mut self, mut msg: Bytes, buf: &mut WriteBuf) -> io::Result<bool> {
+        match self.kind {
+            TransferEncodingKind::Eof if !msg.is_empty() => {
+                buf.put_bytes(msg);
+                Ok(false)
+            }
+            TransferEncodingKind::Chunked(false) if !msg.is_empty() => {
+                writeln!(helpers::MutWriter(buf.buf_mut()), "{:X}\r", msg.len())
+                    .map_err(io::Error::other)?;
+
+                buf.put_bytes(msg);
+                buf.extend_from_slice(b"\r\n");
+                Ok(false)
+            }
+            TransferEncodingKind::Length(ref mut remaining)
+                if *remaining > 0 && !msg.is_empty() =>
+            {
+                let len = cmp::min(*remaining, msg.len() as u64);
+                msg.truncate(len as usize);
+                buf.put_bytes(msg);
+
+                *remaining -= len;
+                Ok(*remaining == 0)
+            }
+            // empty messages and finished encoders do not write payload data
+            _ => self.encode(&msg, buf.buf_mut()),
+        }
+    }
+
     /// Encode eof. Return `EOF` state of encoder
     #[inline]
     pub fn encode_eof(&mut self, buf: &mut BytesMut) -> io::Result<()> {
@@ -480,6 +560,36 @@ impl TransferEncoding {
             }
         }
     }
+
+    /// Encode EOF followed by a trailer section.
+    ///
+    /// Trailers are only written when using chunked encoding, otherwise this is the same as
+    /// [`encode_eof`](Self::encode_eof). Fields that affect message framing are never written.
+    pub fn encode_trailers(&mut self, trailers: &HeaderMap, buf: &mut BytesMut) -> io::Result<()> {
+        match self.kind {
+            TransferEncodingKind::Chunked(ref mut eof) if !*eof => {
+                *eof = true;
+                buf.extend_from_slice(b"0\r\n");
+
+                for (name, value) in trailers {
+                    if name == CONTENT_LENGTH || name == TRANSFER_ENCODING || name == TRAILER {
+                        continue;
+                    }
+
+                    buf.reserve(name.as_str().len() + value.len() + 4);
+                    buf.extend_from_slice(name.as_str().as_bytes());
+                    buf.extend_from_slice(b": ");
+                    buf.extend_from_slice(value.as_bytes());
+                    buf.extend_from_slice(b"\r\n");
+                }
+
+                buf.extend_from_slice(b"\r\n");
+                Ok(())
+            }
+
+            _ => self.encode_eof(buf),
+        }
+    }
 }
 
 /// # Safety
@@ -524,6 +634,79 @@ unsafe fn write_camel_case(value: &[u8], buf: *mut u8, len: usize) {
     }
 }
 
+/// Encoder that frames payload chunks for a given transfer encoding.
+///
+/// This is the counterpart of [`PayloadDecoder`](super::PayloadDecoder) and can be used outside
+/// the dispatcher, for example by proxies that need to re-frame a body after decoding it:
+///
+/// ```
+/// use actix_codec::{Decoder as _, Encoder as _};
+/// use actix_http::h1::{PayloadDecoder, PayloadEncoder, PayloadItem};
+/// use bytes::BytesMut;
+///
+/// let mut src = BytesMut::from("4\r\ndata\r\n0\r\n\r\n");
+/// let mut dst = BytesMut::new();
+///
+/// let mut decoder = PayloadDecoder::chunked();
+/// let mut encoder = PayloadEncoder::length(4);
+///
+/// // the decoder keeps yielding `Eof` once the payload is complete
+/// while let Some(item) = decoder.decode(&mut src).unwrap() {
+///     let eof = matches!(item, PayloadItem::Eof);
+///     encoder.encode(item, &mut dst).unwrap();
+///
+///     if eof {
+///         break;
+///     }
+/// }
+///
+/// assert_eq!(dst, "data");
+/// ```
+#[derive(Debug)]
+pub struct PayloadEncoder {
+    te: TransferEncoding,
+}
+
+impl PayloadEncoder {
+    /// Constructs a fixed-length payload encoder.
+    ///
+    /// Bytes beyond `len` are discarded and finishing the payload early is an error.
+    pub fn length(len: u64) -> Self {
+        Self {
+            te: TransferEncoding::length(len),
+        }
+    }
+
+    /// Constructs a chunked transfer encoding encoder.
+    pub fn chunked() -> Self {
+        Self {
+            te: TransferEncoding::chunked(),
+        }
+    }
+
+    /// Constructs an encoder that writes chunks as-is, leaving the payload to be delimited by
+    /// closing the connection.
+    pub fn eof() -> Self {
+        Self {
+            te: TransferEncoding::eof(),
+        }
+    }
+}
+
+impl Encoder<PayloadItem> for PayloadEncoder {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: PayloadItem, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        match item {
+            // empty chunks would be mistaken for the end of a chunked payload
+            PayloadItem::Chunk(chunk) if chunk.is_empty() => Ok(()),
+            PayloadItem::Chunk(chunk) => self.te.encode(&chunk, dst).map(|_| ()),
+            PayloadItem::Trailers(trailers) => self.te.encode_trailers(&trailers, dst),
+            PayloadItem::Eof => self.te.encode_eof(dst),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::rc::Rc;
@@ -551,6 +734,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_encode_bytes() {
+        let chunk = Bytes::from(vec![b'x'; 2048]);
+
+        let mut buf = WriteBuf::new(0, true);
+        let mut enc = TransferEncoding::chunked();
+        assert!(!enc.encode_bytes(chunk.clone(), &mut buf).unwrap());
+        assert!(enc.encode_bytes(Bytes::new(), &mut buf).unwrap());
+
+        let mut expected = b"800\r\n".to_vec();
+        expected.extend_from_slice(&chunk);
+        expected.extend_from_slice(b"\r\n0\r\n\r\n");
+        assert_eq!(&buf.take()[..], expected);
+
+        let mut enc = TransferEncoding::length(2050);
+        assert!(!enc.encode_bytes(chunk.clone(), &mut buf).unwrap());
+        assert!(enc.encode_bytes(chunk.clone(), &mut buf).unwrap());
+        assert!(enc.encode_bytes(chunk, &mut buf).unwrap());
+        assert_eq!(buf.len(), 2050);
+    }
+
+    #[test]
+    fn payload_encoder() {
+        use crate::header::{HeaderValue, SERVER};
+
+        let mut dst = BytesMut::new();
+        let mut enc = PayloadEncoder::chunked();
+        enc.encode(PayloadItem::Chunk(Bytes::from_static(b"data")), &mut dst)
+            .unwrap();
+        enc.encode(PayloadItem::Chunk(Bytes::new()), &mut dst)
+            .unwrap();
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert(SERVER, HeaderValue::from_static("actix"));
+        enc.encode(PayloadItem::Trailers(trailers), &mut dst)
+            .unwrap();
+        enc.encode(PayloadItem::Eof, &mut dst).unwrap();
+        assert_eq!(dst, "4\r\ndata\r\n0\r\nserver: actix\r\n\r\n");
+
+        let mut dst = BytesMut::new();
+        let mut enc = PayloadEncoder::length(8);
+        enc.encode(PayloadItem::Chunk(Bytes::from_static(b"data")), &mut dst)
+            .unwrap();
+        assert!(enc.encode(PayloadItem::Eof, &mut dst).is_err());
+        assert_eq!(dst, "data");
+    }
+
+    #[test]
+    fn test_chunked_te_trailers() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert(
+            HeaderName::from_static("server-timing"),
+            HeaderValue::from_static("total;dur=12"),
+        );
+        trailers.insert(CONTENT_LENGTH, HeaderValue::from_static("4"));
+
+        let mut bytes = BytesMut::new();
+        let mut enc = TransferEncoding::chunked();
+        enc.encode(b"test", &mut bytes).unwrap();
+        enc.encode_trailers(&trailers, &mut bytes).unwrap();
+        assert_eq!(
+            bytes.split().freeze(),
+            Bytes::from_static(b"4\r\ntest\r\n0\r\nserver-timing: total;dur=12\r\n\r\n")
+        );
+
+        // trailers are not written after EOF
+        enc.encode_trailers(&trailers, &mut bytes).unwrap();
+        assert!(bytes.is_empty());
+
+        // trailers are dropped without chunked encoding
+        let mut enc = TransferEncoding::length(4);
+        enc.encode(b"test", &mut bytes).unwrap();
+        enc.encode_trailers(&trailers, &mut bytes).unwrap();
+        assert_eq!(bytes.split().freeze(), Bytes::from_static(b"test"));
+    }
+
     #[actix_rt::test]
     async fn test_camel_case() {
         let mut bytes = BytesMut::with_capacity(2048);
@@ -668,4 +927,31 @@ mod tests {
         assert!(!data.contains("content-length: 0\r\n"));
         assert!(!data.contains("transfer-encoding: chunked\r\n"));
     }
+
+    #[actix_rt::test]
+    async fn test_no_body_statuses() {
+        let encode = |status| {
+            let mut bytes = BytesMut::with_capacity(2048);
+            let mut res = Response::with_body(status, ());
+            res.headers_mut().insert(DATE, HeaderValue::from_static(""));
+
+            let _ = res.encode_headers(
+                &mut bytes,
+                Version::HTTP_11,
+                BodySize::Sized(4),
+                ConnectionType::KeepAlive,
+                &ServiceConfig::default(),
+            );
+            String::from_utf8(bytes.to_vec()).unwrap()
+        };
+
+        for status in [StatusCode::from_u16(103).unwrap(), StatusCode::NO_CONTENT] {
+            let data = encode(status);
+            assert!(!data.contains("content-length"), "{data}");
+            assert!(!data.contains("transfer-encoding"), "{data}");
+        }
+
+        let data = encode(StatusCode::RESET_CONTENT);
+        assert!(data.contains("content-length: 0\r\n"), "{data}");
+    }
 }
@@ -1,8 +1,9 @@
 use actix_service::{Service, ServiceFactory};
 use actix_utils::future::{ready, Ready};
 
-use crate::{Error, Request};
+use crate::{body::BoxBody, Error, Request, Response};
 
+/// Default expect service; accepts all requests.
 pub struct ExpectHandler;
 
 impl ServiceFactory<Request> for ExpectHandler {
@@ -27,7 +28,36 @@ impl Service<Request> for ExpectHandler {
 
     fn call(&self, req: Request) -> Self::Future {
         ready(Ok(req))
-        // TODO: add some way to trigger error
-        // Err(error::ErrorExpectationFailed("test"))
     }
 }
+
+/// Outcome of an expect service call for a request with an `Expect: 100-continue` header.
+///
+/// Expect services that only ever accept requests can return the [`Request`] itself, which
+/// converts into [`ExpectResponse::Continue`].
+#[derive(Debug)]
+pub enum ExpectResponse {
+    /// Sends `100 Continue` to the client and forwards the request to the main service.
+    Continue(Request),
+
+    /// Sends the given final response instead of calling the main service, handling the request
+    /// body according to the drain policy.
+    Reject(Response<BoxBody>, DrainPolicy),
+}
+
+impl From<Request> for ExpectResponse {
+    fn from(req: Request) -> Self {
+        ExpectResponse::Continue(req)
+    }
+}
+
+/// How the dispatcher handles the body of a request rejected by the expect service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrainPolicy {
+    /// Reads and discards the request body, if the client sends it, so that the connection can be
+    /// kept alive for further requests.
+    Drain,
+
+    /// Skips reading the request body and closes the connection after the rejection response.
+    Close,
+}
@@ -2,10 +2,11 @@
 
 use bytes::{Bytes, BytesMut};
 
+mod buffer;
 mod chunked;
 mod client;
 mod codec;
-mod decoder;
+pub(crate) mod decoder;
 mod dispatcher;
 #[cfg(test)]
 mod dispatcher_tests;
@@ -17,14 +18,17 @@ mod timer;
 mod upgrade;
 mod utils;
 
+pub(crate) use self::dispatcher::SendfileSocket;
 pub use self::{
     client::{ClientCodec, ClientPayloadCodec},
-    codec::Codec,
-    dispatcher::Dispatcher,
-    expect::ExpectHandler,
+    codec::{Codec, CodecStats, DecodeState},
+    decoder::{PayloadDecoder, PayloadItem},
+    dispatcher::{Dispatcher, PipelineStats},
+    encoder::PayloadEncoder,
+    expect::{DrainPolicy, ExpectHandler, ExpectResponse},
     payload::Payload,
     service::{H1Service, H1ServiceHandler},
-    upgrade::UpgradeHandler,
+    upgrade::{reframe, BoundedCodec, Rewind, UpgradeHandler},
     utils::SendResponse,
 };
 
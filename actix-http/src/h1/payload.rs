@@ -11,7 +11,7 @@ use std::{
 use bytes::Bytes;
 use futures_core::Stream;
 
-use crate::error::PayloadError;
+use crate::{error::PayloadError, header::HeaderMap};
 
 /// max buffer size 32k
 pub(crate) const MAX_BUFFER_SIZE: usize = 32_768;
@@ -73,6 +73,27 @@ impl Payload {
     pub fn unread_data(&mut self, data: Bytes) {
         self.inner.borrow_mut().unread_data(data);
     }
+
+    /// Returns the trailer fields sent after a chunked payload, if any.
+    ///
+    /// Trailers are only available once the payload stream has been read to its end.
+    pub fn trailers(&self) -> Option<HeaderMap> {
+        self.inner.borrow().trailers.clone()
+    }
+
+    /// Sets the amounts of buffered data at which reading from the connection is paused and
+    /// resumed.
+    ///
+    /// Once `high` bytes are buffered, [`PayloadSender::need_read`] reports that the sender should
+    /// pause. Reading resumes once the buffered data has been consumed to below `low` bytes. Both
+    /// watermarks default to 32KiB.
+    ///
+    /// # Panics
+    /// Panics if `low` is greater than `high`.
+    pub fn set_read_watermarks(&mut self, low: usize, high: usize) {
+        assert!(low <= high, "low watermark must not exceed high watermark");
+        self.inner.borrow_mut().set_read_watermarks(low, high);
+    }
 }
 
 impl Stream for Payload {
@@ -117,6 +138,13 @@ impl PayloadSender {
         }
     }
 
+    #[inline]
+    pub fn feed_trailers(&mut self, trailers: HeaderMap) {
+        if let Some(shared) = self.inner.upgrade() {
+            shared.borrow_mut().trailers = Some(trailers)
+        }
+    }
+
     #[allow(clippy::needless_pass_by_ref_mut)]
     #[inline]
     pub fn need_read(&self, cx: &mut Context<'_>) -> PayloadStatus {
@@ -141,7 +169,10 @@ struct Inner {
     eof: bool,
     err: Option<PayloadError>,
     need_read: bool,
+    low_watermark: usize,
+    high_watermark: usize,
     items: VecDeque<Bytes>,
+    trailers: Option<HeaderMap>,
     task: Option<Waker>,
     io_task: Option<Waker>,
 }
@@ -153,7 +184,10 @@ impl Inner {
             len: 0,
             err: None,
             items: VecDeque::new(),
+            trailers: None,
             need_read: true,
+            low_watermark: MAX_BUFFER_SIZE,
+            high_watermark: MAX_BUFFER_SIZE,
             task: None,
             io_task: None,
         }
@@ -197,6 +231,27 @@ impl Inner {
         }
     }
 
+    fn set_read_watermarks(&mut self, low: usize, high: usize) {
+        self.low_watermark = low;
+        self.high_watermark = high;
+        self.update_need_read();
+
+        if self.need_read {
+            self.wake_io();
+        }
+    }
+
+    /// Pauses reading once the high watermark is reached and resumes it once the buffer has been
+    /// drained below the low watermark.
+    #[inline]
+    fn update_need_read(&mut self) {
+        self.need_read = if self.need_read {
+            self.len < self.high_watermark
+        } else {
+            self.len < self.low_watermark
+        };
+    }
+
     #[inline]
     fn set_error(&mut self, err: PayloadError) {
         self.err = Some(err);
@@ -211,7 +266,7 @@ impl Inner {
     fn feed_data(&mut self, data: Bytes) {
         self.len += data.len();
         self.items.push_back(data);
-        self.need_read = self.len < MAX_BUFFER_SIZE;
+        self.update_need_read();
         self.wake();
     }
 
@@ -226,7 +281,7 @@ impl Inner {
     ) -> Poll<Option<Result<Bytes, PayloadError>>> {
         if let Some(data) = self.items.pop_front() {
             self.len -= data.len();
-            self.need_read = self.len < MAX_BUFFER_SIZE;
+            self.update_need_read();
 
             if self.need_read && !self.eof {
                 self.register(cx);
@@ -279,4 +334,36 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[actix_rt::test]
+    async fn test_read_watermarks() {
+        let (mut sender, mut payload) = Payload::create(false);
+        payload.set_read_watermarks(4, 8);
+
+        poll_fn(|cx| {
+            sender.feed_data(Bytes::from_static(b"1234"));
+            assert_eq!(sender.need_read(cx), PayloadStatus::Read);
+
+            sender.feed_data(Bytes::from_static(b"5678"));
+            assert_eq!(sender.need_read(cx), PayloadStatus::Pause);
+
+            // still at the low watermark
+            let _ = Pin::new(&mut payload).poll_next(cx);
+            assert_eq!(sender.need_read(cx), PayloadStatus::Pause);
+
+            let _ = Pin::new(&mut payload).poll_next(cx);
+            assert_eq!(sender.need_read(cx), PayloadStatus::Read);
+
+            // raising the high watermark resumes reading
+            sender.feed_data(Bytes::from_static(b"12345678"));
+            assert_eq!(sender.need_read(cx), PayloadStatus::Pause);
+            payload.set_read_watermarks(4, 16);
+            assert_eq!(sender.need_read(cx), PayloadStatus::Pause);
+            payload.set_read_watermarks(12, 16);
+            assert_eq!(sender.need_read(cx), PayloadStatus::Read);
+
+            Poll::Ready(())
+        })
+        .await;
+    }
 }
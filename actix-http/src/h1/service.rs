@@ -15,7 +15,10 @@ use actix_utils::future::ready;
 use futures_core::future::LocalBoxFuture;
 use tracing::error;
 
-use super::{codec::Codec, dispatcher::Dispatcher, ExpectHandler, UpgradeHandler};
+use super::{
+    codec::Codec, dispatcher::Dispatcher, ExpectHandler, ExpectResponse, SendfileSocket,
+    UpgradeHandler,
+};
 use crate::{
     body::{BoxBody, MessageBody},
     config::ServiceConfig,
@@ -31,6 +34,7 @@ pub struct H1Service<T, S, B, X = ExpectHandler, U = UpgradeHandler> {
     expect: X,
     upgrade: Option<U>,
     on_connect_ext: Option<Rc<ConnectCallback<T>>>,
+    sendfile: Option<SendfileSocket<T>>,
     _phantom: PhantomData<B>,
 }
 
@@ -53,6 +57,7 @@ where
             expect: ExpectHandler,
             upgrade: None,
             on_connect_ext: None,
+            sendfile: None,
             _phantom: PhantomData,
         }
     }
@@ -68,7 +73,8 @@ where
 
     B: MessageBody,
 
-    X: ServiceFactory<Request, Config = (), Response = Request>,
+    X: ServiceFactory<Request, Config = ()>,
+    X::Response: Into<ExpectResponse>,
     X::Future: 'static,
     X::Error: Into<Response<BoxBody>>,
     X::InitError: fmt::Debug,
@@ -87,7 +93,10 @@ where
             let peer_addr = io.peer_addr().ok();
             ready(Ok((io, peer_addr)))
         })
-        .and_then(self)
+        .and_then(H1Service {
+            sendfile: Some(|io| io),
+            ..self
+        })
     }
 }
 
@@ -113,7 +122,8 @@ mod openssl {
 
         B: MessageBody,
 
-        X: ServiceFactory<Request, Config = (), Response = Request>,
+        X: ServiceFactory<Request, Config = ()>,
+        X::Response: Into<ExpectResponse>,
         X::Future: 'static,
         X::Error: Into<Response<BoxBody>>,
         X::InitError: fmt::Debug,
@@ -174,7 +184,8 @@ mod rustls_0_20 {
 
         B: MessageBody,
 
-        X: ServiceFactory<Request, Config = (), Response = Request>,
+        X: ServiceFactory<Request, Config = ()>,
+        X::Response: Into<ExpectResponse>,
         X::Future: 'static,
         X::Error: Into<Response<BoxBody>>,
         X::InitError: fmt::Debug,
@@ -235,7 +246,8 @@ mod rustls_0_21 {
 
         B: MessageBody,
 
-        X: ServiceFactory<Request, Config = (), Response = Request>,
+        X: ServiceFactory<Request, Config = ()>,
+        X::Response: Into<ExpectResponse>,
         X::Future: 'static,
         X::Error: Into<Response<BoxBody>>,
         X::InitError: fmt::Debug,
@@ -296,7 +308,8 @@ mod rustls_0_22 {
 
         B: MessageBody,
 
-        X: ServiceFactory<Request, Config = (), Response = Request>,
+        X: ServiceFactory<Request, Config = ()>,
+        X::Response: Into<ExpectResponse>,
         X::Future: 'static,
         X::Error: Into<Response<BoxBody>>,
         X::InitError: fmt::Debug,
@@ -357,7 +370,8 @@ mod rustls_0_23 {
 
         B: MessageBody,
 
-        X: ServiceFactory<Request, Config = (), Response = Request>,
+        X: ServiceFactory<Request, Config = ()>,
+        X::Response: Into<ExpectResponse>,
         X::Future: 'static,
         X::Error: Into<Response<BoxBody>>,
         X::InitError: fmt::Debug,
@@ -406,7 +420,8 @@ where
 {
     pub fn expect<X1>(self, expect: X1) -> H1Service<T, S, B, X1, U>
     where
-        X1: ServiceFactory<Request, Response = Request>,
+        X1: ServiceFactory<Request>,
+        X1::Response: Into<ExpectResponse>,
         X1::Error: Into<Response<BoxBody>>,
         X1::InitError: fmt::Debug,
     {
@@ -416,6 +431,7 @@ where
             srv: self.srv,
             upgrade: self.upgrade,
             on_connect_ext: self.on_connect_ext,
+            sendfile: self.sendfile,
             _phantom: PhantomData,
         }
     }
@@ -432,6 +448,7 @@ where
             srv: self.srv,
             expect: self.expect,
             on_connect_ext: self.on_connect_ext,
+            sendfile: self.sendfile,
             _phantom: PhantomData,
         }
     }
@@ -455,7 +472,8 @@ where
 
     B: MessageBody,
 
-    X: ServiceFactory<Request, Config = (), Response = Request>,
+    X: ServiceFactory<Request, Config = ()>,
+    X::Response: Into<ExpectResponse>,
     X::Future: 'static,
     X::Error: Into<Response<BoxBody>>,
     X::InitError: fmt::Debug,
@@ -477,6 +495,7 @@ where
         let expect = self.expect.new_service(());
         let upgrade = self.upgrade.as_ref().map(|s| s.new_service(()));
         let on_connect_ext = self.on_connect_ext.clone();
+        let sendfile = self.sendfile;
         let cfg = self.cfg.clone();
 
         Box::pin(async move {
@@ -504,6 +523,7 @@ where
                 expect,
                 upgrade,
                 on_connect_ext,
+                sendfile,
             ))
         })
     }
@@ -522,7 +542,8 @@ where
 
     B: MessageBody,
 
-    X: Service<Request, Response = Request>,
+    X: Service<Request>,
+    X::Response: Into<ExpectResponse>,
     X::Error: Into<Response<BoxBody>>,
 
     U: Service<(Request, Framed<T, Codec>), Response = ()>,
@@ -542,5 +563,6 @@ where
     fn call(&self, (io, addr): (T, Option<net::SocketAddr>)) -> Self::Future {
         let conn_data = OnConnectData::from_io(&io, self.on_connect_ext.as_deref());
         Dispatcher::new(io, Rc::clone(&self.flow), self.cfg.clone(), addr, conn_data)
+            .sendfile(self.sendfile)
     }
 }
@@ -23,6 +23,15 @@ impl TimerState {
         matches!(self, Self::Active { .. } | Self::Inactive)
     }
 
+    /// Returns the deadline of an active timer.
+    #[cfg(feature = "dispatcher-trace")]
+    pub(super) fn deadline(&self) -> Option<Instant> {
+        match self {
+            Self::Active { timer } => Some(timer.deadline()),
+            Self::Disabled | Self::Inactive => None,
+        }
+    }
+
     pub(super) fn set(&mut self, timer: Sleep, line: u32) {
         if matches!(self, Self::Disabled) {
             trace!("setting disabled timer from line {}", line);
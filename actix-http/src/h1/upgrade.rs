@@ -1,9 +1,206 @@
-use actix_codec::Framed;
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use actix_codec::{AsyncRead, AsyncWrite, Decoder, Encoder, Framed, FramedParts, ReadBuf};
 use actix_service::{Service, ServiceFactory};
+use bytes::{Buf as _, Bytes, BytesMut};
 use futures_core::future::LocalBoxFuture;
+use pin_project_lite::pin_project;
 
 use crate::{h1::Codec, Error, Request};
 
+/// Switches an upgraded HTTP/1 connection over to a custom protocol codec.
+///
+/// Intended for use in upgrade services (see
+/// [`HttpServiceBuilder::upgrade()`](crate::HttpServiceBuilder::upgrade)) that take over a
+/// connection after a `CONNECT` or `Upgrade` request. Bytes the client sent after the request head
+/// that have already been read from the connection, as well as response data not yet flushed, are
+/// kept so no data is lost in the hand-over. Buffered input is replayed through the [`Rewind`]
+/// I/O wrapper so it is decoded without waiting for the client to send more data.
+///
+/// To bound memory use, decoding fails with an [`io::ErrorKind::InvalidData`] error when the
+/// codec has not produced a frame from more than `max_read_buf` bytes of buffered input.
+///
+/// # Examples
+/// ```
+/// use std::io;
+///
+/// use actix_codec::{AsyncRead, AsyncWrite, Framed, LinesCodec};
+/// use actix_http::{body::BodySize, h1, Request, Response, StatusCode};
+/// use futures_util::{SinkExt as _, StreamExt as _};
+///
+/// async fn echo_lines<T>((_req, mut framed): (Request, Framed<T, h1::Codec>)) -> io::Result<()>
+/// where
+///     T: AsyncRead + AsyncWrite + Unpin,
+/// {
+///     let res = Response::with_body(StatusCode::SWITCHING_PROTOCOLS, ());
+///     framed.send((res, BodySize::None).into()).await?;
+///
+///     let mut framed = h1::reframe(framed, LinesCodec::default(), 4096);
+///
+///     while let Some(line) = framed.next().await {
+///         framed.send(line?).await?;
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub fn reframe<T, C>(
+    framed: Framed<T, Codec>,
+    codec: C,
+    max_read_buf: usize,
+) -> Framed<Rewind<T>, BoundedCodec<C>> {
+    let parts = framed.into_parts();
+
+    // buffered input is replayed through the I/O type so that it is decoded on the first read
+    let io = Rewind::new(parts.io, parts.read_buf.freeze());
+
+    let mut new_parts = FramedParts::new(io, BoundedCodec::new(codec, max_read_buf));
+    new_parts.write_buf = parts.write_buf;
+
+    Framed::from_parts(new_parts)
+}
+
+pin_project! {
+    /// I/O wrapper that yields bytes read ahead of an upgrade before reading from the inner stream.
+    ///
+    /// Returned as part of [`reframe()`].
+    #[derive(Debug)]
+    pub struct Rewind<T> {
+        pre: Bytes,
+        #[pin]
+        io: T,
+    }
+}
+
+impl<T> Rewind<T> {
+    /// Wraps `io`, yielding `pre` before any data read from it.
+    pub fn new(io: T, pre: Bytes) -> Self {
+        Self { pre, io }
+    }
+
+    /// Returns reference to the wrapped I/O stream.
+    pub fn get_ref(&self) -> &T {
+        &self.io
+    }
+
+    /// Returns the wrapped I/O stream and any bytes that have not been replayed yet.
+    pub fn into_inner(self) -> (T, Bytes) {
+        (self.io, self.pre)
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for Rewind<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+
+        if this.pre.is_empty() {
+            return this.io.poll_read(cx, buf);
+        }
+
+        let n = this.pre.len().min(buf.remaining());
+        buf.put_slice(&this.pre[..n]);
+        this.pre.advance(n);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for Rewind<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.project().io.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().io.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().io.poll_shutdown(cx)
+    }
+}
+
+/// Codec wrapper that limits how much input may be buffered while waiting for a frame.
+///
+/// Returned as part of [`reframe()`].
+#[derive(Debug, Clone)]
+pub struct BoundedCodec<C> {
+    codec: C,
+    max_read_buf: usize,
+}
+
+impl<C> BoundedCodec<C> {
+    /// Wraps `codec`, limiting buffered input to `max_read_buf` bytes.
+    pub fn new(codec: C, max_read_buf: usize) -> Self {
+        Self {
+            codec,
+            max_read_buf,
+        }
+    }
+
+    /// Returns reference to the wrapped codec.
+    pub fn get_ref(&self) -> &C {
+        &self.codec
+    }
+
+    /// Returns mutable reference to the wrapped codec.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.codec
+    }
+
+    /// Returns the wrapped codec.
+    pub fn into_inner(self) -> C {
+        self.codec
+    }
+
+    fn check_budget<E: From<io::Error>>(&self, src: &BytesMut) -> Result<(), E> {
+        if src.len() > self.max_read_buf {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "frame exceeds read buffer limit",
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+impl<C: Decoder> Decoder for BoundedCodec<C> {
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.codec.decode(src)? {
+            Some(item) => Ok(Some(item)),
+            None => self.check_budget(src).map(|_| None),
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        self.codec.decode_eof(src)
+    }
+}
+
+impl<C: Encoder<I>, I> Encoder<I> for BoundedCodec<C> {
+    type Error = C::Error;
+
+    fn encode(&mut self, item: I, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.codec.encode(item, dst)
+    }
+}
+
 pub struct UpgradeHandler;
 
 impl<T> ServiceFactory<(Request, Framed<T, Codec>)> for UpgradeHandler {
@@ -30,3 +227,39 @@ impl<T> Service<(Request, Framed<T, Codec>)> for UpgradeHandler {
         unimplemented!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use actix_codec::LinesCodec;
+    use futures_util::{SinkExt as _, StreamExt as _};
+
+    use super::*;
+    use crate::{test::TestBuffer, ServiceConfig};
+
+    #[actix_rt::test]
+    async fn reframe_keeps_buffered_input() {
+        let buf = TestBuffer::new("");
+        let mut parts = FramedParts::new(buf.clone(), Codec::new(ServiceConfig::default()));
+        parts.read_buf.extend_from_slice(b"hello\nwor");
+        parts
+            .write_buf
+            .extend_from_slice(b"HTTP/1.1 101 Switching Protocols\r\n\r\n");
+        let framed = Framed::from_parts(parts);
+
+        let mut framed = reframe(framed, LinesCodec::default(), 8);
+        assert_eq!(framed.next().await.unwrap().unwrap(), "hello");
+
+        framed.send("hi").await.unwrap();
+        assert_eq!(
+            &buf.write_buf_slice()[..],
+            b"HTTP/1.1 101 Switching Protocols\r\n\r\nhi\n"
+        );
+
+        buf.clone().extend_read_buf("ld\n");
+        assert_eq!(framed.next().await.unwrap().unwrap(), "world");
+
+        buf.clone().extend_read_buf("too long for the budget");
+        let err = framed.next().await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
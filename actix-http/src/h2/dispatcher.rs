@@ -1,4 +1,5 @@
 use std::{
+    cell::{Cell, RefCell},
     cmp,
     error::Error as StdError,
     future::Future,
@@ -6,7 +7,8 @@ use std::{
     net,
     pin::{pin, Pin},
     rc::Rc,
-    task::{Context, Poll},
+    task::{Context, Poll, Waker},
+    time::Instant,
 };
 
 use actix_codec::{AsyncRead, AsyncWrite};
@@ -17,18 +19,24 @@ use bytes::{Bytes, BytesMut};
 use futures_core::ready;
 use h2::{
     server::{Connection, SendResponse},
-    Ping, PingPong,
+    Ping, PingPong, Reason,
 };
 use pin_project_lite::pin_project;
 
 use crate::{
     body::{BodySize, BoxBody, MessageBody},
+    callbacks::{ConnectionTracker, TrackedRequest},
     config::ServiceConfig,
     header::{
-        HeaderName, HeaderValue, CONNECTION, CONTENT_LENGTH, DATE, TRANSFER_ENCODING, UPGRADE,
+        HeaderName, HeaderValue, CONNECTION, CONTENT_LENGTH, DATE, KEEP_ALIVE, TRANSFER_ENCODING,
+        UPGRADE,
     },
+    requests::attach_finish,
+    responses::attach_informational,
     service::HttpFlow,
-    Extensions, Method, OnConnectData, Payload, Request, Response, ResponseHead,
+    shutdown::{ShutdownListener, ShutdownSignal},
+    Extensions, KeepAlive, Method, OnConnectData, Payload, Protocol, Request, Response,
+    ResponseHead,
 };
 
 const CHUNK_SIZE: usize = 16_384;
@@ -42,8 +50,21 @@ pin_project! {
         config: ServiceConfig,
         peer_addr: Option<net::SocketAddr>,
         ping_pong: Option<H2PingPong>,
+        keep_alive: Rc<KeepAliveOverride>,
+        shutting_down: bool,
+        shutdown: Option<ShutdownListener>,
+        tracker: Option<Rc<ConnectionTracker>>,
         _phantom: PhantomData<B>
     }
+
+    impl<T, S, B, X, U> PinnedDrop for Dispatcher<T, S, B, X, U> {
+        fn drop(this: Pin<&mut Self>) {
+            // streams still being handled keep the tracker alive; the connection is closed now
+            if let Some(tracker) = this.project().tracker {
+                tracker.disconnect();
+            }
+        }
+    }
 }
 
 impl<T, S, B, X, U> Dispatcher<T, S, B, X, U>
@@ -70,6 +91,15 @@ where
             ping_pong: conn.ping_pong().unwrap(),
         });
 
+        let tracker = ConnectionTracker::start(
+            config.connection_callbacks(),
+            Protocol::Http2,
+            peer_addr,
+            &conn_data,
+        );
+
+        let shutdown = config.shutdown_signal().map(ShutdownSignal::listener);
+
         Self {
             flow,
             config,
@@ -77,9 +107,51 @@ where
             connection: conn,
             conn_data: conn_data.0.map(Rc::new),
             ping_pong,
+            keep_alive: Rc::default(),
+            shutting_down: false,
+            shutdown,
+            tracker,
             _phantom: PhantomData,
         }
     }
+
+    /// Stops accepting new streams, sending GOAWAY, and closes the connection once in-flight
+    /// streams are done.
+    fn start_shutdown(&mut self) {
+        self.connection.graceful_shutdown();
+        self.shutting_down = true;
+        self.ping_pong = None;
+    }
+}
+
+/// Keep-alive policy set by the most recent response that overrides it.
+///
+/// Shared between the dispatcher and the tasks sending responses.
+#[derive(Default)]
+struct KeepAliveOverride {
+    policy: Cell<Option<KeepAlive>>,
+    waker: RefCell<Option<Waker>>,
+}
+
+impl KeepAliveOverride {
+    fn set(&self, keep_alive: KeepAlive) {
+        self.policy.set(Some(keep_alive));
+
+        // wake dispatcher so that it can start shutting down the connection
+        if keep_alive == KeepAlive::Disabled {
+            if let Some(waker) = self.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    fn register(&self, waker: &Waker) {
+        let mut slot = self.waker.borrow_mut();
+
+        if !matches!(*slot, Some(ref prev) if prev.will_wake(waker)) {
+            *slot = Some(waker.clone());
+        }
+    }
 }
 
 struct H2PingPong {
@@ -110,6 +182,29 @@ where
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
 
+        this.keep_alive.register(cx.waker());
+
+        let server_shutdown = !this.shutting_down
+            && this
+                .shutdown
+                .as_mut()
+                .is_some_and(|listener| listener.poll_shutdown(cx).is_ready());
+
+        match this.keep_alive.policy.get() {
+            _ if this.shutting_down => {}
+
+            Some(KeepAlive::Disabled) => this.start_shutdown(),
+
+            _ if server_shutdown => {
+                tracing::trace!("server is shutting down; sending GOAWAY");
+                this.start_shutdown();
+            }
+
+            Some(KeepAlive::Os) => this.ping_pong = None,
+
+            _ => {}
+        }
+
         loop {
             match Pin::new(&mut this.connection).poll_accept(cx)? {
                 Poll::Ready(Some((req, tx))) => {
@@ -128,23 +223,93 @@ where
 
                     req.conn_data.clone_from(&this.conn_data);
 
-                    let fut = this.flow.service.call(req);
+                    let mut tracked = this.tracker.as_ref().map(|t| t.request(req.head(), None));
+
+                    if let Err(err) = this.config.header_limits().check(req.head()) {
+                        tracing::trace!("request headers rejected: {err}");
+
+                        let res = Response::new(err.status_code());
+                        let config = this.config.clone();
+                        let keep_alive = Rc::clone(&this.keep_alive);
+
+                        actix_rt::spawn(async move {
+                            let res = handle_response(
+                                res,
+                                tx,
+                                config,
+                                keep_alive,
+                                head_req,
+                                &mut tracked,
+                            )
+                            .await;
+
+                            if let (Ok(()), Some(req)) = (res, tracked) {
+                                req.complete();
+                            }
+                        });
+
+                        continue;
+                    }
+
+                    // h2 can not send informational responses; the sender is closed right away
+                    drop(attach_informational(&req));
+                    let finish = attach_finish(&req, this.config.in_flight_counter());
+
+                    let fut = this.config.catch_panic(|| this.flow.service.call(req));
                     let config = this.config.clone();
+                    let keep_alive = Rc::clone(&this.keep_alive);
 
                     // multiplex request handling with spawn task
                     actix_rt::spawn(async move {
-                        // resolve service call and send response.
-                        let res = match fut.await {
-                            Ok(res) => handle_response(res.into(), tx, config, head_req).await,
-                            Err(err) => {
+                        // resolve service call, catching panics if enabled, and send response.
+                        let res = match fut {
+                            Ok(fut) => {
+                                let mut fut = pin!(fut);
+
+                                poll_fn(|cx| match config.catch_panic(|| fut.as_mut().poll(cx)) {
+                                    Ok(poll) => poll.map(Ok),
+                                    Err(res) => Poll::Ready(Err(res)),
+                                })
+                                .await
+                            }
+                            Err(res) => Err(res),
+                        };
+
+                        let res = match res {
+                            Ok(Ok(res)) => {
+                                handle_response(
+                                    res.into(),
+                                    tx,
+                                    config,
+                                    keep_alive,
+                                    head_req,
+                                    &mut tracked,
+                                )
+                                .await
+                            }
+                            Ok(Err(err)) => {
                                 let res: Response<BoxBody> = err.into();
-                                handle_response(res, tx, config, head_req).await
+                                handle_response(res, tx, config, keep_alive, head_req, &mut tracked)
+                                    .await
+                            }
+
+                            // response of caught panic
+                            Err(res) => {
+                                handle_response(res, tx, config, keep_alive, head_req, &mut tracked)
+                                    .await
                             }
                         };
 
-                        // log error.
-                        if let Err(err) = res {
-                            match err {
+                        // run completion callbacks or log error.
+                        match res {
+                            Ok(()) => {
+                                finish.complete();
+
+                                if let Some(req) = tracked {
+                                    req.complete();
+                                }
+                            }
+                            Err(err) => match err {
                                 DispatchError::SendResponse(err) => {
                                     tracing::trace!("Error sending response: {err:?}");
                                 }
@@ -154,7 +319,7 @@ where
                                 DispatchError::ResponseBody(err) => {
                                     tracing::error!("Response payload stream error: {err:?}");
                                 }
-                            }
+                            },
                         }
                     });
                 }
@@ -170,7 +335,8 @@ where
                                 Poll::Ready(_) => {
                                     ping_pong.in_flight = false;
 
-                                    let dead_line = this.config.keep_alive_deadline().unwrap();
+                                    let dead_line =
+                                        keep_alive_deadline(&this.config, &this.keep_alive);
                                     ping_pong.timer.as_mut().reset(dead_line.into());
                                 }
                                 Poll::Pending => {
@@ -185,7 +351,7 @@ where
 
                             ping_pong.ping_pong.send_ping(Ping::opaque())?;
 
-                            let dead_line = this.config.keep_alive_deadline().unwrap();
+                            let dead_line = keep_alive_deadline(&this.config, &this.keep_alive);
                             ping_pong.timer.as_mut().reset(dead_line.into());
 
                             ping_pong.in_flight = true;
@@ -198,6 +364,17 @@ where
     }
 }
 
+/// Returns the deadline for the next ping-pong, taking response overrides into account.
+///
+/// Only called while pinging is enabled, which implies that a timeout is set.
+fn keep_alive_deadline(config: &ServiceConfig, keep_alive: &KeepAliveOverride) -> Instant {
+    let keep_alive = keep_alive
+        .policy
+        .get()
+        .unwrap_or_else(|| config.keep_alive());
+    config.keep_alive_deadline_for(keep_alive).unwrap()
+}
+
 enum DispatchError {
     SendResponse(h2::Error),
     SendData(h2::Error),
@@ -208,23 +385,34 @@ async fn handle_response<B>(
     res: Response<B>,
     mut tx: SendResponse<Bytes>,
     config: ServiceConfig,
+    keep_alive: Rc<KeepAliveOverride>,
     head_req: bool,
+    tracked: &mut Option<TrackedRequest>,
 ) -> Result<(), DispatchError>
 where
     B: MessageBody,
 {
     let (res, body) = res.replace_body(());
 
-    // prepare response.
+    if let Some(policy) = res.head().keep_alive_policy() {
+        keep_alive.set(policy);
+    }
+
+    // prepare response; bodies of responses to HEAD requests and of responses whose status does
+    // not allow a body are not polled
     let mut size = body.size();
-    let res = prepare_response(config, res.head(), &mut size);
-    let eof_or_head = size.is_eof() || head_req;
+    let h2_res = prepare_response(config, res.head(), &mut size);
+    let eof_or_head = size.is_eof() || head_req || !res.head().allows_body();
 
     // send response head and return on eof.
     let mut stream = tx
-        .send_response(res, eof_or_head)
+        .send_response(h2_res, eof_or_head)
         .map_err(DispatchError::SendResponse)?;
 
+    if let Some(req) = tracked {
+        req.response(res.head(), None);
+    }
+
     if eof_or_head {
         return Ok(());
     }
@@ -233,7 +421,14 @@ where
 
     // poll response body and send chunks to client
     while let Some(res) = poll_fn(|cx| body.as_mut().poll_next(cx)).await {
-        let mut chunk = res.map_err(|err| DispatchError::ResponseBody(err.into()))?;
+        let mut chunk = match res {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                // abort the stream so the client does not mistake a truncated body as complete
+                stream.send_reset(Reason::INTERNAL_ERROR);
+                return Err(DispatchError::ResponseBody(err.into()));
+            }
+        };
 
         'send: loop {
             let chunk_size = cmp::min(chunk.len(), CHUNK_SIZE);
@@ -252,6 +447,11 @@ where
                     let len = chunk.len();
                     let bytes = chunk.split_to(cmp::min(len, cap));
 
+                    if let Some(req) = tracked {
+                        req.add_body(bytes.len());
+                        req.tracker().add_written(bytes.len());
+                    }
+
                     stream
                         .send_data(bytes, false)
                         .map_err(DispatchError::SendData)?;
@@ -265,7 +465,16 @@ where
         }
     }
 
-    // response body streaming finished. send end of stream and return.
+    // response body streaming finished. send trailers, which also end the stream, if any.
+    if let Some(trailers) = poll_fn(|cx| body.as_mut().poll_trailers(cx)).await {
+        stream
+            .send_trailers(trailers.into())
+            .map_err(DispatchError::SendData)?;
+
+        return Ok(());
+    }
+
+    // otherwise, send end of stream and return.
     stream
         .send_data(Bytes::new(), true)
         .map_err(DispatchError::SendData)?;
@@ -287,13 +496,27 @@ fn prepare_response(
 
     // Content length
     match head.status {
-        http::StatusCode::NO_CONTENT
-        | http::StatusCode::CONTINUE
-        | http::StatusCode::PROCESSING => *size = BodySize::None,
         http::StatusCode::SWITCHING_PROTOCOLS => {
             skip_len = true;
             *size = BodySize::Stream;
         }
+
+        // retain a manually set content-length header, like HTTP/1
+        http::StatusCode::NOT_MODIFIED => {
+            skip_len = false;
+            *size = BodySize::None;
+        }
+
+        http::StatusCode::RESET_CONTENT => {
+            skip_len = true;
+            *size = BodySize::Sized(0);
+        }
+
+        _ if !head.allows_body() => {
+            skip_len = true;
+            *size = BodySize::None;
+        }
+
         _ => {}
     }
 
@@ -322,6 +545,7 @@ fn prepare_response(
             // omit HTTP/1.x only headers according to:
             // https://datatracker.ietf.org/doc/html/rfc7540#section-8.1.2.2
             &CONNECTION | &TRANSFER_ENCODING | &UPGRADE => continue,
+            key if key == KEEP_ALIVE => continue,
 
             &CONTENT_LENGTH if skip_len => continue,
             &DATE => has_date = true,
@@ -36,6 +36,12 @@ pub const CROSS_ORIGIN_OPENER_POLICY: HeaderName =
 pub const CROSS_ORIGIN_RESOURCE_POLICY: HeaderName =
     HeaderName::from_static("cross-origin-resource-policy");
 
+/// General header that allows the sender of a persistent HTTP/1.x connection to hint at how long
+/// the connection may be left idle.
+///
+/// See [RFC 2068 §19.7.1.1](https://www.rfc-editor.org/rfc/rfc2068#section-19.7.1.1).
+pub const KEEP_ALIVE: HeaderName = HeaderName::from_static("keep-alive");
+
 /// Response header that provides a mechanism to allow and deny the use of browser features in a
 /// document or within any `<iframe>` elements in the document.
 pub const PERMISSIONS_POLICY: HeaderName = HeaderName::from_static("permissions-policy");
@@ -43,15 +43,16 @@ pub use self::{
     // re-export list is explicit so that any updates to `http` do not conflict with this set
     common::{
         CACHE_STATUS, CDN_CACHE_CONTROL, CROSS_ORIGIN_EMBEDDER_POLICY, CROSS_ORIGIN_OPENER_POLICY,
-        CROSS_ORIGIN_RESOURCE_POLICY, PERMISSIONS_POLICY, X_FORWARDED_FOR, X_FORWARDED_HOST,
-        X_FORWARDED_PROTO,
+        CROSS_ORIGIN_RESOURCE_POLICY, KEEP_ALIVE, PERMISSIONS_POLICY, X_FORWARDED_FOR,
+        X_FORWARDED_HOST, X_FORWARDED_PROTO,
     },
     into_pair::TryIntoHeaderPair,
     into_value::TryIntoHeaderValue,
     map::HeaderMap,
     shared::{
-        parse_extended_value, q, Charset, ContentEncoding, ExtendedValue, HttpDate, LanguageTag,
-        Quality, QualityItem,
+        parse_extended_value, q, Charset, ContentEncoding, ExtendedValue, Forwarded,
+        ForwardedElement, ForwardedNode, HttpDate, LanguageTag, NodeName, NodePort, Quality,
+        QualityItem,
     },
     utils::{fmt_comma_delimited, from_comma_delimited, from_one_raw_str, http_percent_encode},
 };
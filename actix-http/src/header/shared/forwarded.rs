@@ -0,0 +1,642 @@
+use std::{
+    fmt::{self, Write as _},
+    net::{IpAddr, Ipv6Addr, SocketAddr},
+    ops::{Deref, DerefMut},
+    str::FromStr,
+};
+
+use http::header::InvalidHeaderValue;
+
+use crate::{
+    error::ParseError,
+    header::{self, Header, HeaderName, HeaderValue, TryIntoHeaderValue},
+    HttpMessage,
+};
+
+/// `Forwarded` header, defined in [RFC 7239](https://datatracker.ietf.org/doc/html/rfc7239).
+///
+/// Each proxy handling a request appends an element describing the hop: the client it received
+/// the request from (`for`), its own interface (`by`), and the `Host` header and protocol of the
+/// request it received (`host`, `proto`). The first element therefore describes the original
+/// client.
+///
+/// When parsing, elements from all `Forwarded` header lines in a message are collected in order.
+/// Whitespace around `=` and unquoted node identifiers containing `:` or `[` are tolerated, since
+/// they are commonly sent by proxies, but an element repeating a parameter is rejected.
+///
+/// # ABNF
+/// ```plain
+/// Forwarded         = 1#forwarded-element
+/// forwarded-element = [ forwarded-pair ] *( ";" [ forwarded-pair ] )
+/// forwarded-pair    = token "=" value
+/// value             = token / quoted-string
+/// ```
+///
+/// # Example Values
+/// * `for=192.0.2.43`
+/// * `for="[2001:db8:cafe::17]:4711"; proto=https, for=198.51.100.17;by=_proxy1`
+///
+/// # Examples
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+///
+/// use actix_http::header::{Forwarded, ForwardedElement, ForwardedNode, NodeName};
+///
+/// let client = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 43));
+///
+/// let forwarded = Forwarded(vec![ForwardedElement::new()
+///     .with_for(client)
+///     .with_by(ForwardedNode::new(NodeName::Obfuscated("_proxy1".to_owned())))
+///     .with_proto("https")]);
+///
+/// assert_eq!(forwarded.to_string(), "for=192.0.2.43;by=_proxy1;proto=https");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Forwarded(pub Vec<ForwardedElement>);
+
+impl Forwarded {
+    /// Parses elements from a sequence of `Forwarded` header values.
+    ///
+    /// Returns an empty list if there are no values.
+    pub fn from_raw<'a, I>(values: I) -> Result<Self, ParseError>
+    where
+        I: IntoIterator<Item = &'a HeaderValue>,
+    {
+        let mut elements = Vec::new();
+
+        for value in values {
+            let value = value.to_str().map_err(|_| ParseError::Header)?;
+            parse_elements(value, &mut elements)?;
+        }
+
+        Ok(Forwarded(elements))
+    }
+}
+
+impl Deref for Forwarded {
+    type Target = Vec<ForwardedElement>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Forwarded {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl fmt::Display for Forwarded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, element) in self.0.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+
+            fmt::Display::fmt(element, f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TryIntoHeaderValue for Forwarded {
+    type Error = InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        HeaderValue::try_from(self.to_string())
+    }
+}
+
+impl Header for Forwarded {
+    fn name() -> HeaderName {
+        header::FORWARDED
+    }
+
+    fn parse<T: HttpMessage>(msg: &T) -> Result<Self, ParseError> {
+        let headers = msg.headers();
+
+        if !headers.contains_key(Self::name()) {
+            return Err(ParseError::Header);
+        }
+
+        Forwarded::from_raw(headers.get_all(Self::name()))
+    }
+}
+
+/// A single hop in a [`Forwarded`] header.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForwardedElement {
+    for_node: Option<ForwardedNode>,
+    by_node: Option<ForwardedNode>,
+    host: Option<String>,
+    proto: Option<String>,
+    extensions: Vec<(String, String)>,
+}
+
+impl ForwardedElement {
+    /// Constructs an element with no parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `for` parameter, identifying the client the request was received from.
+    pub fn with_for(mut self, node: impl Into<ForwardedNode>) -> Self {
+        self.for_node = Some(node.into());
+        self
+    }
+
+    /// Sets the `by` parameter, identifying the interface the request was received on.
+    pub fn with_by(mut self, node: impl Into<ForwardedNode>) -> Self {
+        self.by_node = Some(node.into());
+        self
+    }
+
+    /// Sets the `host` parameter to the `Host` header of the received request.
+    pub fn with_host(mut self, host: impl Into<String>) -> Self {
+        self.host = Some(host.into());
+        self
+    }
+
+    /// Sets the `proto` parameter to the scheme of the received request (e.g., `https`).
+    pub fn with_proto(mut self, proto: impl Into<String>) -> Self {
+        self.proto = Some(proto.into());
+        self
+    }
+
+    /// Adds an extension parameter.
+    pub fn with_extension(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extensions.push((name.into(), value.into()));
+        self
+    }
+
+    /// Returns the `for` parameter, if present.
+    pub fn for_node(&self) -> Option<&ForwardedNode> {
+        self.for_node.as_ref()
+    }
+
+    /// Returns the `by` parameter, if present.
+    pub fn by_node(&self) -> Option<&ForwardedNode> {
+        self.by_node.as_ref()
+    }
+
+    /// Returns the `host` parameter, if present.
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    /// Returns the `proto` parameter, if present.
+    pub fn proto(&self) -> Option<&str> {
+        self.proto.as_deref()
+    }
+
+    /// Returns the value of the extension parameter with the given name, if present.
+    pub fn extension(&self, name: &str) -> Option<&str> {
+        self.extensions
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn set_param(&mut self, name: &str, value: String) -> Result<(), ParseError> {
+        fn set<T>(slot: &mut Option<T>, value: T) -> Result<(), ParseError> {
+            match slot {
+                Some(_) => Err(ParseError::Header),
+                None => {
+                    *slot = Some(value);
+                    Ok(())
+                }
+            }
+        }
+
+        match name.to_ascii_lowercase().as_str() {
+            "for" => set(&mut self.for_node, value.parse()?),
+            "by" => set(&mut self.by_node, value.parse()?),
+            "host" => set(&mut self.host, value),
+            "proto" => set(&mut self.proto, value),
+            _ if self.extension(name).is_some() => Err(ParseError::Header),
+            _ => {
+                self.extensions.push((name.to_owned(), value));
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for ForwardedElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let for_node = self.for_node.as_ref().map(ToString::to_string);
+        let by_node = self.by_node.as_ref().map(ToString::to_string);
+
+        let params = [
+            ("for", for_node.as_deref()),
+            ("by", by_node.as_deref()),
+            ("host", self.host.as_deref()),
+            ("proto", self.proto.as_deref()),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value?)))
+        .chain(
+            self.extensions
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str())),
+        );
+
+        for (idx, (name, value)) in params.enumerate() {
+            if idx > 0 {
+                f.write_char(';')?;
+            }
+
+            write!(f, "{name}=")?;
+
+            if !value.is_empty() && value.chars().all(is_tchar) {
+                f.write_str(value)?;
+            } else {
+                f.write_char('"')?;
+
+                for c in value.chars() {
+                    if c == '"' || c == '\\' {
+                        f.write_char('\\')?;
+                    }
+                    f.write_char(c)?;
+                }
+
+                f.write_char('"')?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A node identifier used in the `for` and `by` parameters of a [`ForwardedElement`].
+///
+/// See [RFC 7239 §6](https://datatracker.ietf.org/doc/html/rfc7239#section-6).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForwardedNode {
+    name: NodeName,
+    port: Option<NodePort>,
+}
+
+impl ForwardedNode {
+    /// Constructs a node identifier without a port.
+    pub fn new(name: NodeName) -> Self {
+        Self { name, port: None }
+    }
+
+    /// Sets the port of the node.
+    pub fn with_port(mut self, port: NodePort) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Returns the name of the node.
+    pub fn name(&self) -> &NodeName {
+        &self.name
+    }
+
+    /// Returns the port of the node, if present.
+    pub fn port(&self) -> Option<&NodePort> {
+        self.port.as_ref()
+    }
+
+    /// Returns the IP address of the node, if it is not unknown or obfuscated.
+    pub fn ip(&self) -> Option<IpAddr> {
+        match self.name {
+            NodeName::Ip(ip) => Some(ip),
+            _ => None,
+        }
+    }
+}
+
+impl From<IpAddr> for ForwardedNode {
+    fn from(ip: IpAddr) -> Self {
+        Self::new(NodeName::Ip(ip))
+    }
+}
+
+impl From<SocketAddr> for ForwardedNode {
+    fn from(addr: SocketAddr) -> Self {
+        Self::new(NodeName::Ip(addr.ip())).with_port(NodePort::Port(addr.port()))
+    }
+}
+
+impl FromStr for ForwardedNode {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // tolerate IPv6 addresses without brackets, which some proxies send
+        if let Ok(ip) = s.parse::<Ipv6Addr>() {
+            return Ok(IpAddr::V6(ip).into());
+        }
+
+        let (name, port) = match s.strip_prefix('[') {
+            Some(rest) => {
+                let (ip, rest) = rest.split_once(']').ok_or(ParseError::Header)?;
+                let ip = ip.parse::<Ipv6Addr>().map_err(|_| ParseError::Header)?;
+
+                let port = match rest {
+                    "" => None,
+                    _ => Some(rest.strip_prefix(':').ok_or(ParseError::Header)?),
+                };
+
+                (NodeName::Ip(IpAddr::V6(ip)), port)
+            }
+
+            None => {
+                let (name, port) = match s.split_once(':') {
+                    Some((name, port)) => (name, Some(port)),
+                    None => (s, None),
+                };
+
+                (name.parse()?, port)
+            }
+        };
+
+        Ok(ForwardedNode {
+            name,
+            port: port.map(str::parse).transpose()?,
+        })
+    }
+}
+
+impl fmt::Display for ForwardedNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name {
+            NodeName::Ip(IpAddr::V6(ip)) => write!(f, "[{ip}]")?,
+            ref name => write!(f, "{name}")?,
+        }
+
+        if let Some(port) = &self.port {
+            write!(f, ":{port}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The name part of a [`ForwardedNode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeName {
+    /// An IPv4 or IPv6 address.
+    Ip(IpAddr),
+
+    /// The `unknown` identifier, used when the proxy does not know or wishes to hide the node.
+    Unknown,
+
+    /// An obfuscated identifier, such as `_hidden`.
+    ///
+    /// Obfuscated identifiers start with an underscore followed by letters, digits, `.`, `_`
+    /// and `-`.
+    Obfuscated(String),
+}
+
+impl FromStr for NodeName {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("unknown") {
+            Ok(NodeName::Unknown)
+        } else if is_obfuscated(s) {
+            Ok(NodeName::Obfuscated(s.to_owned()))
+        } else {
+            s.parse().map(NodeName::Ip).map_err(|_| ParseError::Header)
+        }
+    }
+}
+
+/// Formats IPv6 addresses without brackets.
+impl fmt::Display for NodeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeName::Ip(ip) => write!(f, "{ip}"),
+            NodeName::Unknown => f.write_str("unknown"),
+            NodeName::Obfuscated(name) => f.write_str(name),
+        }
+    }
+}
+
+/// The port part of a [`ForwardedNode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodePort {
+    /// A port number.
+    Port(u16),
+
+    /// An obfuscated port, such as `_8080`.
+    Obfuscated(String),
+}
+
+impl FromStr for NodePort {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if is_obfuscated(s) {
+            Ok(NodePort::Obfuscated(s.to_owned()))
+        } else if s.bytes().all(|b| b.is_ascii_digit()) {
+            s.parse()
+                .map(NodePort::Port)
+                .map_err(|_| ParseError::Header)
+        } else {
+            Err(ParseError::Header)
+        }
+    }
+}
+
+impl fmt::Display for NodePort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodePort::Port(port) => write!(f, "{port}"),
+            NodePort::Obfuscated(port) => f.write_str(port),
+        }
+    }
+}
+
+fn is_obfuscated(s: &str) -> bool {
+    s.strip_prefix('_').is_some_and(|rest| {
+        !rest.is_empty()
+            && rest
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'.' || b == b'_' || b == b'-')
+    })
+}
+
+/// Token characters, as defined in [RFC 9110 §5.6.2](https://datatracker.ietf.org/doc/html/rfc9110#section-5.6.2).
+fn is_tchar(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+/// Parses the comma-separated elements of one header value, appending them to `elements`.
+fn parse_elements(mut s: &str, elements: &mut Vec<ForwardedElement>) -> Result<(), ParseError> {
+    let mut element = ForwardedElement::new();
+
+    loop {
+        s = s.trim_start();
+
+        if s.is_empty() || s.starts_with(',') {
+            if !element.is_empty() {
+                elements.push(std::mem::take(&mut element));
+            }
+
+            match s.strip_prefix(',') {
+                Some(rest) => s = rest,
+                None => return Ok(()),
+            }
+
+            continue;
+        }
+
+        if let Some(rest) = s.strip_prefix(';') {
+            s = rest;
+            continue;
+        }
+
+        let name_len = s.find(|c: char| !is_tchar(c)).unwrap_or(s.len());
+
+        if name_len == 0 {
+            return Err(ParseError::Header);
+        }
+
+        let (name, rest) = s.split_at(name_len);
+        let rest = rest
+            .trim_start()
+            .strip_prefix('=')
+            .ok_or(ParseError::Header)?;
+        let (value, rest) = parse_param_value(rest.trim_start())?;
+
+        element.set_param(name, value)?;
+        s = rest;
+    }
+}
+
+/// Parses a quoted-string or unquoted value from the start of `s`, returning it and the remainder.
+fn parse_param_value(s: &str) -> Result<(String, &str), ParseError> {
+    let Some(quoted) = s.strip_prefix('"') else {
+        let len = s
+            .find(|c: char| c == ';' || c == ',' || c == '"' || c.is_whitespace())
+            .unwrap_or(s.len());
+
+        if len == 0 {
+            return Err(ParseError::Header);
+        }
+
+        return Ok((s[..len].to_owned(), &s[len..]));
+    };
+
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &quoted[idx + 1..])),
+            '\\' => value.push(chars.next().ok_or(ParseError::Header)?.1),
+            c => value.push(c),
+        }
+    }
+
+    // unterminated quoted-string
+    Err(ParseError::Header)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use crate::test::TestRequest;
+
+    fn parse(values: &[&'static str]) -> Result<Forwarded, ParseError> {
+        let mut req = TestRequest::default();
+        for value in values {
+            req.append_header((header::FORWARDED, *value));
+        }
+        Forwarded::parse(&req.finish())
+    }
+
+    #[test]
+    fn parse_elements() {
+        let forwarded = parse(&[
+            "for=\"[2001:db8:cafe::17]:4711\"; proto=https;host=\"example.com:8443\", for=unknown",
+            "For=_hidden;by=192.0.2.60:_proxy;secret=\"a \\\"b\\\"\"",
+        ])
+        .unwrap();
+        assert_eq!(forwarded.len(), 3);
+
+        let client = &forwarded[0];
+        let node = client.for_node().unwrap();
+        assert_eq!(node.ip(), Some("2001:db8:cafe::17".parse().unwrap()));
+        assert_eq!(node.port(), Some(&NodePort::Port(4711)));
+        assert_eq!(client.proto(), Some("https"));
+        assert_eq!(client.host(), Some("example.com:8443"));
+        assert_eq!(client.by_node(), None);
+
+        assert_eq!(forwarded[1].for_node().unwrap().name(), &NodeName::Unknown);
+        assert_eq!(forwarded[1].for_node().unwrap().ip(), None);
+
+        let proxy = &forwarded[2];
+        assert_eq!(
+            proxy.for_node().unwrap().name(),
+            &NodeName::Obfuscated("_hidden".to_owned())
+        );
+        let by = proxy.by_node().unwrap();
+        assert_eq!(by.ip(), Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 60))));
+        assert_eq!(by.port(), Some(&NodePort::Obfuscated("_proxy".to_owned())));
+        assert_eq!(proxy.extension("SECRET"), Some("a \"b\""));
+    }
+
+    #[test]
+    fn parse_lenient() {
+        let forwarded =
+            parse(&["  for = 1.2.3.4:80 ; ; proto= http,,for=[::1]:8080, for=::1"]).unwrap();
+        assert_eq!(forwarded.len(), 3);
+        assert_eq!(forwarded[0].for_node().unwrap().to_string(), "1.2.3.4:80");
+        assert_eq!(forwarded[0].proto(), Some("http"));
+        assert_eq!(forwarded[1].for_node().unwrap().to_string(), "[::1]:8080");
+        assert_eq!(forwarded[2].for_node().unwrap().to_string(), "[::1]");
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["for"]).is_err());
+        assert!(parse(&["for="]).is_err());
+        assert!(parse(&["=1.2.3.4"]).is_err());
+        assert!(parse(&["for=example.com"]).is_err());
+        assert!(parse(&["for=_"]).is_err());
+        assert!(parse(&["for=1.2.3.4:http"]).is_err());
+        assert!(parse(&["for=\"[::1\""]).is_err());
+        assert!(parse(&["for=\"1.2.3.4"]).is_err());
+        assert!(parse(&["for=1.2.3.4;for=5.6.7.8"]).is_err());
+        assert!(parse(&["ext=1;EXT=2"]).is_err());
+
+        assert_eq!(parse(&[""]).unwrap(), Forwarded::default());
+    }
+
+    #[test]
+    fn format() {
+        let forwarded = Forwarded(vec![
+            ForwardedElement::new()
+                .with_for("[2001:db8:cafe::17]:4711".parse::<SocketAddr>().unwrap())
+                .with_host("example.com:8443")
+                .with_proto("https"),
+            ForwardedElement::new()
+                .with_for(ForwardedNode::new(NodeName::Unknown))
+                .with_by(IpAddr::V4(Ipv4Addr::LOCALHOST))
+                .with_extension("note", "a \"b\""),
+        ]);
+
+        let value = forwarded.clone().try_into_value().unwrap();
+        assert_eq!(
+            value,
+            "for=\"[2001:db8:cafe::17]:4711\";host=\"example.com:8443\";proto=https, \
+             for=unknown;by=127.0.0.1;note=\"a \\\"b\\\"\""
+        );
+
+        // round trip
+        assert_eq!(Forwarded::from_raw([&value]).unwrap(), forwarded);
+    }
+}
@@ -5,6 +5,7 @@ pub use language_tags::LanguageTag;
 mod charset;
 mod content_encoding;
 mod extended;
+mod forwarded;
 mod http_date;
 mod quality;
 mod quality_item;
@@ -13,6 +14,7 @@ pub use self::{
     charset::Charset,
     content_encoding::ContentEncoding,
     extended::{parse_extended_value, ExtendedValue},
+    forwarded::{Forwarded, ForwardedElement, ForwardedNode, NodeName, NodePort},
     http_date::HttpDate,
     quality::{q, Quality},
     quality_item::QualityItem,
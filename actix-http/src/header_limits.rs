@@ -0,0 +1,314 @@
+use crate::{
+    error::HeaderLimitError,
+    h1::decoder::{HeadLimits, MAX_BUFFER_SIZE},
+    header::{HeaderName, COOKIE},
+    RequestHead,
+};
+
+/// Limits checked against the headers of each request before it is passed to the service.
+///
+/// Requests violating any of these limits are rejected by the dispatcher with a `431 Request Header
+/// Fields Too Large` (for size and count limits), `414 URI Too Long` (for the URI length limit) or
+/// `400 Bad Request` (for forbidden headers) response and the connection is closed.
+///
+/// The header count, head size, and URI length limits are enforced by the HTTP/1 decoder while the
+/// request head is being parsed, before any header storage is allocated.
+///
+/// Limits are set using [`HttpServiceBuilder::header_limits()`](crate::HttpServiceBuilder::header_limits).
+/// By default, no limits are enforced.
+///
+/// # Examples
+/// ```
+/// use actix_http::{header::HeaderName, HeaderLimits};
+///
+/// let limits = HeaderLimits::new()
+///     .max_header_count(64)
+///     .max_head_size(16 * 1024)
+///     .max_uri_length(4096)
+///     .max_cookie_count(50)
+///     .max_cookie_size(4096)
+///     .max_header_value_size(8192)
+///     .forbid_header(HeaderName::from_static("x-internal-auth"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HeaderLimits {
+    max_header_count: Option<usize>,
+    max_head_size: Option<usize>,
+    max_uri_length: Option<usize>,
+    max_cookie_count: Option<usize>,
+    max_cookie_size: Option<usize>,
+    max_header_value_size: Option<usize>,
+    forbidden: Vec<HeaderName>,
+}
+
+impl HeaderLimits {
+    /// Constructs new header limits that do not restrict any requests.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of headers a request may send.
+    ///
+    /// When not set, HTTP/1 requests are limited to 96 headers.
+    pub fn max_header_count(mut self, count: usize) -> Self {
+        self.max_header_count = Some(count);
+        self
+    }
+
+    /// Sets the maximum size, in bytes, of an HTTP/1 request head (request line and headers).
+    ///
+    /// Values larger than the default of 128KiB have no effect.
+    pub fn max_head_size(mut self, size: usize) -> Self {
+        self.max_head_size = Some(size);
+        self
+    }
+
+    /// Sets the maximum length, in bytes, of a request's URI.
+    pub fn max_uri_length(mut self, len: usize) -> Self {
+        self.max_uri_length = Some(len);
+        self
+    }
+
+    /// Sets the maximum number of cookies a request may send across all of its `Cookie` headers.
+    pub fn max_cookie_count(mut self, count: usize) -> Self {
+        self.max_cookie_count = Some(count);
+        self
+    }
+
+    /// Sets the maximum total size, in bytes, of a request's `Cookie` header values.
+    pub fn max_cookie_size(mut self, size: usize) -> Self {
+        self.max_cookie_size = Some(size);
+        self
+    }
+
+    /// Sets the maximum size, in bytes, of any individual header value.
+    pub fn max_header_value_size(mut self, size: usize) -> Self {
+        self.max_header_value_size = Some(size);
+        self
+    }
+
+    /// Adds a header that requests must not contain.
+    ///
+    /// Useful for rejecting headers that should only ever be set by a trusted upstream proxy.
+    pub fn forbid_header(mut self, name: HeaderName) -> Self {
+        if !self.forbidden.contains(&name) {
+            self.forbidden.push(name);
+        }
+
+        self
+    }
+
+    /// Returns the limits enforced by the HTTP/1 decoder while parsing request heads.
+    pub(crate) fn head_limits(&self) -> HeadLimits {
+        let defaults = HeadLimits::default();
+
+        HeadLimits {
+            max_headers: self.max_header_count.unwrap_or(defaults.max_headers),
+            max_head_size: self
+                .max_head_size
+                .map_or(defaults.max_head_size, |size| size.min(MAX_BUFFER_SIZE)),
+            max_uri_length: self.max_uri_length,
+        }
+    }
+
+    fn is_unrestricted(&self) -> bool {
+        self.max_header_count.is_none()
+            && self.max_uri_length.is_none()
+            && self.max_cookie_count.is_none()
+            && self.max_cookie_size.is_none()
+            && self.max_header_value_size.is_none()
+            && self.forbidden.is_empty()
+    }
+
+    /// Checks request head against these limits.
+    ///
+    /// # Errors
+    /// Returns the first limit that the request violates.
+    pub fn check(&self, head: &RequestHead) -> Result<(), HeaderLimitError> {
+        if self.is_unrestricted() {
+            return Ok(());
+        }
+
+        if let Some(name) = self
+            .forbidden
+            .iter()
+            .find(|name| head.headers.contains_key(*name))
+        {
+            return Err(HeaderLimitError::ForbiddenHeader(name.clone()));
+        }
+
+        if matches!(self.max_uri_length, Some(max) if uri_length(head) > max) {
+            return Err(HeaderLimitError::UriTooLong);
+        }
+
+        if matches!(self.max_header_count, Some(max) if head.headers.len() > max) {
+            return Err(HeaderLimitError::TooManyHeaders);
+        }
+
+        if let Some(max) = self.max_header_value_size {
+            if let Some((name, _)) = head.headers.iter().find(|(_, val)| val.len() > max) {
+                return Err(HeaderLimitError::HeaderValueTooLarge(name.clone()));
+            }
+        }
+
+        if self.max_cookie_count.is_some() || self.max_cookie_size.is_some() {
+            let mut count = 0;
+            let mut size = 0;
+
+            for val in head.headers.get_all(COOKIE) {
+                size += val.len();
+                count += val
+                    .as_bytes()
+                    .split(|&b| b == b';')
+                    .filter(|pair| pair.iter().any(|b| !b.is_ascii_whitespace()))
+                    .count();
+            }
+
+            if matches!(self.max_cookie_size, Some(max) if size > max) {
+                return Err(HeaderLimitError::CookiesTooLarge);
+            }
+
+            if matches!(self.max_cookie_count, Some(max) if count > max) {
+                return Err(HeaderLimitError::TooManyCookies);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns length of the request target, as it would appear in an HTTP/1 request line.
+fn uri_length(head: &RequestHead) -> usize {
+    head.uri
+        .path_and_query()
+        .map_or(head.uri.path().len(), |pq| pq.as_str().len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{header::HeaderValue, StatusCode};
+
+    fn head(headers: &[(&'static str, &'static str)]) -> RequestHead {
+        let mut head = RequestHead::default();
+
+        for (name, val) in headers {
+            head.headers
+                .append(HeaderName::from_static(name), HeaderValue::from_static(val));
+        }
+
+        head
+    }
+
+    #[test]
+    fn unrestricted_by_default() {
+        let limits = HeaderLimits::default();
+        let long = "a".repeat(16 * 1024).leak();
+        assert!(limits.check(&head(&[("cookie", long)])).is_ok());
+    }
+
+    #[test]
+    fn cookie_count() {
+        let limits = HeaderLimits::new().max_cookie_count(2);
+
+        assert!(limits.check(&head(&[("cookie", "a=1; b=2")])).is_ok());
+        assert!(limits.check(&head(&[("cookie", "a=1; b=2;")])).is_ok());
+
+        let err = limits
+            .check(&head(&[("cookie", "a=1; b=2"), ("cookie", "c=3")]))
+            .unwrap_err();
+        assert_eq!(err, HeaderLimitError::TooManyCookies);
+        assert_eq!(
+            err.status_code(),
+            StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+        );
+    }
+
+    #[test]
+    fn cookie_size() {
+        let limits = HeaderLimits::new().max_cookie_size(8);
+
+        assert!(limits.check(&head(&[("cookie", "a=1; b=2")])).is_ok());
+
+        let err = limits
+            .check(&head(&[("cookie", "a=1"), ("cookie", "bb=222")]))
+            .unwrap_err();
+        assert_eq!(err, HeaderLimitError::CookiesTooLarge);
+    }
+
+    #[test]
+    fn header_value_size() {
+        let limits = HeaderLimits::new().max_header_value_size(4);
+
+        assert!(limits.check(&head(&[("x-a", "1234")])).is_ok());
+
+        let err = limits.check(&head(&[("x-a", "12345")])).unwrap_err();
+        assert_eq!(
+            err,
+            HeaderLimitError::HeaderValueTooLarge(HeaderName::from_static("x-a"))
+        );
+        assert_eq!(
+            err.status_code(),
+            StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+        );
+    }
+
+    #[test]
+    fn forbidden_header() {
+        let name = HeaderName::from_static("x-internal");
+        let limits = HeaderLimits::new().forbid_header(name.clone());
+
+        assert!(limits.check(&head(&[("x-other", "1")])).is_ok());
+
+        let err = limits.check(&head(&[("x-internal", "1")])).unwrap_err();
+        assert_eq!(err, HeaderLimitError::ForbiddenHeader(name));
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn header_count() {
+        let limits = HeaderLimits::new().max_header_count(2);
+
+        assert!(limits.check(&head(&[("x-a", "1"), ("x-b", "2")])).is_ok());
+
+        let err = limits
+            .check(&head(&[("x-a", "1"), ("x-b", "2"), ("x-a", "3")]))
+            .unwrap_err();
+        assert_eq!(err, HeaderLimitError::TooManyHeaders);
+        assert_eq!(
+            err.status_code(),
+            StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+        );
+    }
+
+    #[test]
+    fn uri_length() {
+        let limits = HeaderLimits::new().max_uri_length(8);
+
+        let mut req = head(&[]);
+        req.uri = "/a?b=123".parse().unwrap();
+        assert!(limits.check(&req).is_ok());
+
+        req.uri = "/a?b=1234".parse().unwrap();
+        let err = limits.check(&req).unwrap_err();
+        assert_eq!(err, HeaderLimitError::UriTooLong);
+        assert_eq!(err.status_code(), StatusCode::URI_TOO_LONG);
+    }
+
+    #[test]
+    fn head_limits() {
+        let limits = HeaderLimits::new().head_limits();
+        assert_eq!(limits.max_headers, 96);
+        assert_eq!(limits.max_head_size, MAX_BUFFER_SIZE);
+        assert_eq!(limits.max_uri_length, None);
+
+        let limits = HeaderLimits::new()
+            .max_header_count(200)
+            .max_head_size(1024 * 1024)
+            .max_uri_length(10)
+            .head_limits();
+        assert_eq!(limits.max_headers, 200);
+        assert_eq!(limits.max_head_size, MAX_BUFFER_SIZE);
+        assert_eq!(limits.max_uri_length, Some(10));
+    }
+}
@@ -13,11 +13,13 @@
 //! | `compress-brotli`   | Payload compression support: Brotli.        |
 //! | `compress-gzip`     | Payload compression support: Deflate, Gzip. |
 //! | `compress-zstd`     | Payload compression support: Zstd.          |
+//! | `anyhow`            | Conversion of [anyhow] errors into `Error`. |
 //! | `trust-dns`         | Use [trust-dns] as the client DNS resolver. |
 //!
 //! [h2]: https://crates.io/crates/h2
 //! [OpenSSL]: https://crates.io/crates/openssl
 //! [rustls]: https://crates.io/crates/rustls
+//! [anyhow]: https://crates.io/crates/anyhow
 //! [trust-dns]: https://crates.io/crates/trust-dns
 
 #![allow(
@@ -33,6 +35,7 @@ pub use http::{uri, uri::Uri, Method, StatusCode, Version};
 
 pub mod body;
 mod builder;
+mod callbacks;
 mod config;
 mod date;
 #[cfg(feature = "__compress")]
@@ -43,37 +46,48 @@ pub mod h1;
 #[cfg(feature = "http2")]
 pub mod h2;
 pub mod header;
+mod header_limits;
 mod helpers;
 mod http_message;
 mod keep_alive;
 mod message;
+mod min_data_rate;
 #[cfg(test)]
 mod notify_on_drop;
+#[cfg(any(feature = "openssl", feature = "rustls-0_23"))]
+mod ocsp;
 mod payload;
 mod requests;
 mod responses;
 mod service;
+mod shutdown;
 pub mod test;
 #[cfg(feature = "ws")]
 pub mod ws;
 
+#[cfg(any(feature = "openssl", feature = "rustls-0_23"))]
+pub use self::ocsp::OcspStapling;
 #[allow(deprecated)]
 pub use self::payload::PayloadStream;
 #[cfg(feature = "__tls")]
 pub use self::service::TlsAcceptorConfig;
 pub use self::{
     builder::HttpServiceBuilder,
+    callbacks::{ConnectionCallbacks, ConnectionMeta, ConnectionStats, RequestStats, TlsInfo},
     config::ServiceConfig,
     error::Error,
     extensions::Extensions,
     header::ContentEncoding,
+    header_limits::HeaderLimits,
     http_message::HttpMessage,
     keep_alive::KeepAlive,
     message::{ConnectionType, Message},
+    min_data_rate::MinDataRate,
     payload::{BoxedPayloadStream, Payload},
-    requests::{Request, RequestHead, RequestHeadType},
-    responses::{Response, ResponseBuilder, ResponseHead},
+    requests::{FinishStatus, OnFinish, Request, RequestHead, RequestHeadType},
+    responses::{InformationalSender, Response, ResponseBuilder, ResponseHead},
     service::HttpService,
+    shutdown::ShutdownSignal,
 };
 
 /// A major HTTP protocol version.
@@ -105,4 +119,14 @@ impl OnConnectData {
 
         Self(ext)
     }
+
+    /// Returns a reference to the connection data of type `T`, if present.
+    pub(crate) fn get<T: 'static>(&self) -> Option<&T> {
+        self.0.as_ref()?.get::<T>()
+    }
+
+    /// Inserts connection data, regardless of whether an on-connect callback is set.
+    pub(crate) fn insert<T: 'static>(&mut self, val: T) {
+        self.0.get_or_insert_with(Extensions::default).insert(val);
+    }
 }
@@ -0,0 +1,190 @@
+use std::{
+    future::Future as _,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use actix_rt::time::{sleep, Instant, Sleep};
+use bytes::Bytes;
+use futures_core::{ready, Stream};
+use pin_project_lite::pin_project;
+
+use crate::error::PayloadError;
+
+pin_project! {
+    /// Payload stream adapter that fails when the client sends data too slowly.
+    ///
+    /// The data rate is measured over consecutive windows of time. If, at the end of a window,
+    /// fewer than `min_rate` bytes per second were received during it while the stream was waiting
+    /// for more data, the stream yields [`PayloadError::Timeout`] and ends. This protects against
+    /// clients that tie up a connection by trickling a request body, much like the client request
+    /// timeout does for request heads.
+    ///
+    /// Windows that end while the stream is not being polled (e.g., because the handler is busy
+    /// processing a previous chunk) are not counted against the client.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use actix_http::{MinDataRate, Payload};
+    ///
+    /// fn guard_payload(payload: Payload) -> MinDataRate<Payload> {
+    ///     // require at least 1KiB/s, averaged over 10 seconds
+    ///     MinDataRate::new(payload, 1024, Duration::from_secs(10))
+    /// }
+    /// ```
+    #[derive(Debug)]
+    pub struct MinDataRate<S> {
+        #[pin]
+        stream: S,
+        #[pin]
+        timer: Sleep,
+        min_rate: u64,
+        window: Duration,
+        received: u64,
+        waiting: bool,
+        timed_out: bool,
+    }
+}
+
+impl<S> MinDataRate<S> {
+    /// Constructs new payload adapter requiring `stream` to yield at least `min_rate` bytes per
+    /// second, averaged over `window`.
+    ///
+    /// # Panics
+    /// Panics if `window` is zero.
+    pub fn new(stream: S, min_rate: u64, window: Duration) -> Self {
+        assert!(!window.is_zero(), "data rate window must not be zero");
+
+        Self {
+            stream,
+            timer: sleep(window),
+            min_rate,
+            window,
+            received: 0,
+            waiting: false,
+            timed_out: false,
+        }
+    }
+
+    /// Returns the wrapped payload stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S> Stream for MinDataRate<S>
+where
+    S: Stream<Item = Result<Bytes, PayloadError>>,
+{
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        if *this.timed_out {
+            return Poll::Ready(None);
+        }
+
+        // window ended while the stream was not being polled; start afresh
+        if !*this.waiting && this.timer.is_elapsed() {
+            *this.received = 0;
+            this.timer.as_mut().reset(Instant::now() + *this.window);
+        }
+
+        match this.stream.poll_next(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                *this.waiting = false;
+                *this.received += chunk.len() as u64;
+                return Poll::Ready(Some(Ok(chunk)));
+            }
+
+            Poll::Ready(item) => {
+                *this.waiting = false;
+                return Poll::Ready(item);
+            }
+
+            Poll::Pending => *this.waiting = true,
+        }
+
+        loop {
+            ready!(this.timer.as_mut().poll(cx));
+
+            let min_bytes = (*this.min_rate as f64 * this.window.as_secs_f64()) as u64;
+
+            if *this.received < min_bytes {
+                tracing::trace!(
+                    "payload data rate too low: received {} bytes in {:?}",
+                    this.received,
+                    this.window
+                );
+
+                *this.timed_out = true;
+                return Poll::Ready(Some(Err(PayloadError::Timeout)));
+            }
+
+            *this.received = 0;
+            this.timer.as_mut().reset(Instant::now() + *this.window);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{stream, StreamExt as _};
+
+    use super::*;
+
+    fn trickle(
+        interval: Duration,
+        chunk: &'static [u8],
+    ) -> impl Stream<Item = Result<Bytes, PayloadError>> {
+        stream::unfold((), move |_| async move {
+            sleep(interval).await;
+            Some((Ok(Bytes::from_static(chunk)), ()))
+        })
+    }
+
+    #[actix_rt::test]
+    async fn passes_fast_payload() {
+        let payload = stream::iter([Ok(Bytes::from_static(b"abc")), Ok(Bytes::from_static(b"d"))]);
+        let mut payload = Box::pin(MinDataRate::new(payload, 1024, Duration::from_millis(50)));
+
+        assert_eq!(payload.next().await.unwrap().unwrap(), "abc");
+        assert_eq!(payload.next().await.unwrap().unwrap(), "d");
+        assert!(payload.next().await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn fails_slow_payload() {
+        let payload = trickle(Duration::from_millis(20), b"a").take(100);
+        let mut payload = Box::pin(MinDataRate::new(payload, 1024, Duration::from_millis(100)));
+
+        let mut received = 0;
+
+        loop {
+            match payload.next().await.unwrap() {
+                Ok(chunk) => received += chunk.len(),
+                Err(PayloadError::Timeout) => break,
+                Err(err) => panic!("unexpected error: {err}"),
+            }
+        }
+
+        // timed out at end of first window
+        assert!(received < 10);
+        assert!(payload.next().await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn steady_payload_within_rate() {
+        // 10 bytes every 10ms is 1000 bytes per second
+        let payload = trickle(Duration::from_millis(10), b"0123456789").take(30);
+        let payload = MinDataRate::new(payload, 500, Duration::from_millis(50));
+
+        let chunks = payload.collect::<Vec<_>>().await;
+        assert_eq!(chunks.len(), 30);
+        assert!(chunks.iter().all(Result::is_ok));
+    }
+}
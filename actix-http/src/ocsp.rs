@@ -0,0 +1,209 @@
+//! OCSP stapling for TLS acceptors.
+
+use std::{
+    fmt,
+    sync::{Arc, RwLock},
+    thread,
+    time::Duration,
+};
+
+/// An OCSP response that TLS acceptors staple to their handshakes.
+///
+/// Stapling saves clients that check the revocation status of the server certificate from
+/// querying the OCSP responder of its issuer themselves. The response is a DER-encoded
+/// `OCSPResponse`, as returned by the responder; it is sent as-is and is not validated.
+///
+/// Clones share the same response, so it can be replaced with [`set_response()`] while the server
+/// is running or periodically by a callback registered with [`refresh_every()`].
+///
+/// For Rustls v0.23 services, set it with [`TlsAcceptorConfig::ocsp_stapling()`]. For OpenSSL
+/// services, apply it to the acceptor builder with [`configure_openssl()`].
+///
+/// # Examples
+/// ```no_run
+/// # fn fetch_ocsp_response() -> Option<Vec<u8>> { None }
+/// use std::time::Duration;
+///
+/// use actix_http::OcspStapling;
+///
+/// let stapling = OcspStapling::new(std::fs::read("cert.ocsp").unwrap())
+///     .refresh_every(Duration::from_secs(60 * 60), fetch_ocsp_response);
+/// ```
+///
+/// [`set_response()`]: Self::set_response()
+/// [`refresh_every()`]: Self::refresh_every()
+/// [`configure_openssl()`]: Self::configure_openssl()
+/// [`TlsAcceptorConfig::ocsp_stapling()`]: crate::TlsAcceptorConfig::ocsp_stapling()
+#[derive(Clone)]
+pub struct OcspStapling {
+    response: Arc<RwLock<Option<Vec<u8>>>>,
+}
+
+impl OcspStapling {
+    /// Constructs stapling of the given DER-encoded OCSP response.
+    pub fn new(response: Vec<u8>) -> Self {
+        Self {
+            response: Arc::new(RwLock::new(Some(response))),
+        }
+    }
+
+    /// Constructs stapling without an initial response.
+    ///
+    /// Handshakes are completed without stapling until a response is set.
+    pub fn empty() -> Self {
+        Self {
+            response: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Replaces the stapled response.
+    ///
+    /// Only handshakes that start after the call use the new response.
+    pub fn set_response(&self, response: Vec<u8>) {
+        *self.response.write().unwrap() = Some(response);
+    }
+
+    /// Returns the currently stapled response.
+    pub fn response(&self) -> Option<Vec<u8>> {
+        self.response.read().unwrap().clone()
+    }
+
+    /// Refreshes the stapled response with `refresh` after every `interval`.
+    ///
+    /// The callback runs on a dedicated thread, so it may block while fetching a new response from
+    /// the OCSP responder. Returning `None` keeps the current response. The thread exits at the
+    /// first refresh after the stapling and all its clones have been dropped.
+    pub fn refresh_every<F>(self, interval: Duration, refresh: F) -> Self
+    where
+        F: Fn() -> Option<Vec<u8>> + Send + 'static,
+    {
+        let response = Arc::downgrade(&self.response);
+
+        thread::Builder::new()
+            .name("actix-http-ocsp".to_owned())
+            .spawn(move || loop {
+                thread::sleep(interval);
+
+                if response.strong_count() == 0 {
+                    break;
+                }
+
+                let Some(new_response) = refresh() else {
+                    continue;
+                };
+
+                match response.upgrade() {
+                    Some(response) => *response.write().unwrap() = Some(new_response),
+                    None => break,
+                }
+            })
+            .expect("failed to spawn OCSP refresh thread");
+
+        self
+    }
+
+    /// Staples the response to handshakes of acceptors created from `builder`.
+    ///
+    /// This sets the status callback of `builder`, replacing any set before.
+    #[cfg(feature = "openssl")]
+    pub fn configure_openssl(
+        &self,
+        builder: &mut actix_tls::accept::openssl::reexports::SslAcceptorBuilder,
+    ) -> Result<(), actix_tls::accept::openssl::reexports::Error> {
+        let stapling = self.clone();
+
+        builder.set_status_callback(move |ssl| {
+            let response = stapling.response.read().unwrap();
+
+            match response.as_deref() {
+                Some(response) => {
+                    ssl.set_ocsp_status(response)?;
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        })?;
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for OcspStapling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let response = self.response.read().unwrap();
+
+        f.debug_struct("OcspStapling")
+            .field("response_len", &response.as_ref().map(Vec::len))
+            .finish()
+    }
+}
+
+/// Certificate resolver that adds the stapled OCSP response to the keys of another resolver.
+#[cfg(feature = "rustls-0_23")]
+#[derive(Debug)]
+pub(crate) struct StaplingResolver {
+    inner: Arc<dyn tls_rustls_023::server::ResolvesServerCert>,
+    stapling: OcspStapling,
+}
+
+#[cfg(feature = "rustls-0_23")]
+impl StaplingResolver {
+    pub(crate) fn new(
+        inner: Arc<dyn tls_rustls_023::server::ResolvesServerCert>,
+        stapling: OcspStapling,
+    ) -> Self {
+        Self { inner, stapling }
+    }
+}
+
+#[cfg(feature = "rustls-0_23")]
+impl tls_rustls_023::server::ResolvesServerCert for StaplingResolver {
+    fn resolve(
+        &self,
+        client_hello: tls_rustls_023::server::ClientHello<'_>,
+    ) -> Option<Arc<tls_rustls_023::sign::CertifiedKey>> {
+        let key = self.inner.resolve(client_hello)?;
+
+        match self.stapling.response() {
+            Some(ocsp) => Some(Arc::new(tls_rustls_023::sign::CertifiedKey {
+                ocsp: Some(ocsp),
+                ..(*key).clone()
+            })),
+            None => Some(key),
+        }
+    }
+
+    fn only_raw_public_keys(&self) -> bool {
+        self.inner.only_raw_public_keys()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    #[test]
+    fn shared_response() {
+        let stapling = OcspStapling::empty();
+        let stapling2 = stapling.clone();
+        assert_eq!(stapling.response(), None);
+
+        stapling2.set_response(b"ocsp".to_vec());
+        assert_eq!(stapling.response().as_deref(), Some(&b"ocsp"[..]));
+    }
+
+    #[test]
+    fn refresh() {
+        let stapling = OcspStapling::new(b"old".to_vec())
+            .refresh_every(Duration::from_millis(10), || Some(b"new".to_vec()));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+
+        while stapling.response().as_deref() != Some(&b"new"[..]) {
+            assert!(Instant::now() < deadline, "response was not refreshed");
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
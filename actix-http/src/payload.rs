@@ -8,7 +8,7 @@ use bytes::Bytes;
 use futures_core::Stream;
 use pin_project_lite::pin_project;
 
-use crate::error::PayloadError;
+use crate::{error::PayloadError, header::HeaderMap};
 
 /// A boxed payload stream.
 pub type BoxedPayloadStream = Pin<Box<dyn Stream<Item = Result<Bytes, PayloadError>>>>;
@@ -73,6 +73,32 @@ impl<S> Payload<S> {
     pub fn take(&mut self) -> Payload<S> {
         mem::replace(self, Payload::None)
     }
+
+    /// Returns the trailer fields sent after the payload, if any.
+    ///
+    /// Only HTTP/1.x chunked payloads can carry trailers, and they are only available once the
+    /// payload stream has been read to its end.
+    pub fn trailers(&self) -> Option<HeaderMap> {
+        match self {
+            Payload::H1 { payload } => payload.trailers(),
+            _ => None,
+        }
+    }
+
+    /// Sets the buffered sizes at which reading from the connection is paused and resumed.
+    ///
+    /// Only affects HTTP/1.x payloads; HTTP/2 payloads are governed by stream flow control and
+    /// other payload types are left untouched. See [`h1::Payload::set_read_watermarks`].
+    ///
+    /// # Panics
+    /// Panics if `low` is greater than `high`.
+    pub fn set_read_watermarks(&mut self, low: usize, high: usize) {
+        assert!(low <= high, "low watermark must not exceed high watermark");
+
+        if let Payload::H1 { payload } = self {
+            payload.set_read_watermarks(low, high);
+        }
+    }
 }
 
 impl<S> Stream for Payload<S>
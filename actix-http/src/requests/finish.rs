@@ -0,0 +1,272 @@
+//! Request completion callbacks.
+
+use std::{
+    cell::RefCell,
+    fmt,
+    future::Future,
+    mem,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use futures_core::future::LocalBoxFuture;
+
+use crate::{HttpMessage as _, Request};
+
+type Callback = Box<dyn FnOnce(FinishStatus) -> LocalBoxFuture<'static, ()>>;
+
+/// Describes how the handling of a request ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FinishStatus {
+    /// The whole response was produced and handed to the connection.
+    Completed,
+
+    /// The request was dropped before its response was complete, e.g. because the connection was
+    /// closed by the client mid-stream or the response body failed.
+    Aborted,
+}
+
+impl FinishStatus {
+    /// Returns true if the response was completed.
+    pub fn is_completed(&self) -> bool {
+        matches!(self, FinishStatus::Completed)
+    }
+}
+
+/// Registers async callbacks that run once the handling of a request has ended.
+///
+/// The dispatchers store a handle in the extensions of each request before calling the service.
+/// Callbacks are spawned on the current arbiter, in reverse order of registration, once the last
+/// chunk of the response has been written or, with [`FinishStatus::Aborted`], when the dispatcher
+/// gives up on the response (e.g. because the client disconnected mid-stream). This makes them
+/// suitable for releasing resources acquired while handling a request, such as database
+/// transactions or temporary files.
+///
+/// Callbacks registered after the handling of the request has ended are spawned right away.
+///
+/// # Examples
+/// ```
+/// use actix_http::{FinishStatus, HttpMessage as _, OnFinish, Request, Response, StatusCode};
+///
+/// async fn handler(req: Request) -> Response<&'static str> {
+///     if let Some(on_finish) = req.extensions().get::<OnFinish>() {
+///         on_finish.register(|status: FinishStatus| async move {
+///             if !status.is_completed() {
+///                 // ... roll back
+///             }
+///         });
+///     }
+///
+///     Response::with_body(StatusCode::OK, "done")
+/// }
+/// ```
+#[derive(Clone)]
+pub struct OnFinish {
+    inner: Rc<RefCell<Inner>>,
+}
+
+struct Inner {
+    callbacks: Vec<Callback>,
+    status: Option<FinishStatus>,
+}
+
+impl OnFinish {
+    /// Registers a callback to run when the handling of the request ends.
+    pub fn register<F, Fut>(&self, f: F)
+    where
+        F: FnOnce(FinishStatus) -> Fut + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        let mut inner = self.inner.borrow_mut();
+        inner
+            .callbacks
+            .push(Box::new(move |status| Box::pin(f(status))));
+
+        if let Some(status) = inner.status {
+            inner.run(status);
+        }
+    }
+
+    /// Returns the outcome of the request, if its handling has ended.
+    pub fn status(&self) -> Option<FinishStatus> {
+        self.inner.borrow().status
+    }
+}
+
+impl fmt::Debug for OnFinish {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.borrow();
+
+        f.debug_struct("OnFinish")
+            .field("callbacks", &inner.callbacks.len())
+            .field("status", &inner.status)
+            .finish()
+    }
+}
+
+impl Inner {
+    fn run(&mut self, status: FinishStatus) {
+        self.status = Some(status);
+
+        let callbacks = mem::take(&mut self.callbacks);
+
+        // callbacks can not be run outside of a runtime and are dropped instead
+        if callbacks.is_empty() || actix_rt::Arbiter::try_current().is_none() {
+            return;
+        }
+
+        actix_rt::spawn(async move {
+            for callback in callbacks.into_iter().rev() {
+                callback(status).await;
+            }
+        });
+    }
+}
+
+/// Dispatcher-owned half of the completion callbacks of a request.
+///
+/// Dropping the guard without completing it runs the callbacks as aborted.
+pub(crate) struct FinishGuard {
+    inner: Rc<RefCell<Inner>>,
+    in_flight: Option<Arc<AtomicUsize>>,
+}
+
+impl FinishGuard {
+    /// Runs the registered callbacks as completed.
+    pub(crate) fn complete(self) {
+        self.inner.borrow_mut().run(FinishStatus::Completed);
+    }
+}
+
+impl Drop for FinishGuard {
+    fn drop(&mut self) {
+        if let Some(in_flight) = &self.in_flight {
+            in_flight.fetch_sub(1, Ordering::Relaxed);
+        }
+
+        let mut inner = self.inner.borrow_mut();
+
+        if inner.status.is_none() {
+            inner.run(FinishStatus::Aborted);
+        }
+    }
+}
+
+/// Stores a completion callback handle in the extensions of `req`.
+///
+/// The request is counted by `in_flight`, if given, until the returned guard is dropped.
+pub(crate) fn attach(req: &Request, in_flight: Option<&Arc<AtomicUsize>>) -> FinishGuard {
+    if let Some(in_flight) = in_flight {
+        in_flight.fetch_add(1, Ordering::Relaxed);
+    }
+
+    let inner = Rc::new(RefCell::new(Inner {
+        callbacks: Vec::new(),
+        status: None,
+    }));
+
+    req.extensions_mut().insert(OnFinish {
+        inner: Rc::clone(&inner),
+    });
+
+    FinishGuard {
+        inner,
+        in_flight: in_flight.cloned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Log = Rc<RefCell<Vec<(u8, FinishStatus)>>>;
+
+    fn register(req: &Request, log: &Log, id: u8) {
+        let log = Rc::clone(log);
+
+        req.extensions()
+            .get::<OnFinish>()
+            .unwrap()
+            .register(move |status| async move { log.borrow_mut().push((id, status)) });
+    }
+
+    #[actix_rt::test]
+    async fn completed_in_reverse_order() {
+        let log = Log::default();
+
+        let req = Request::new();
+        let guard = attach(&req, None);
+        register(&req, &log, 1);
+        register(&req, &log, 2);
+
+        guard.complete();
+        actix_rt::task::yield_now().await;
+        assert_eq!(
+            *log.borrow(),
+            [(2, FinishStatus::Completed), (1, FinishStatus::Completed)]
+        );
+
+        // late registrations run right away
+        register(&req, &log, 3);
+        actix_rt::task::yield_now().await;
+        assert_eq!(log.borrow()[2], (3, FinishStatus::Completed));
+    }
+
+    #[actix_rt::test]
+    async fn aborted_on_drop() {
+        let log = Log::default();
+
+        let req = Request::new();
+        let guard = attach(&req, None);
+        register(&req, &log, 1);
+
+        drop(req);
+        actix_rt::task::yield_now().await;
+        assert!(log.borrow().is_empty());
+
+        drop(guard);
+        actix_rt::task::yield_now().await;
+        assert_eq!(*log.borrow(), [(1, FinishStatus::Aborted)]);
+    }
+
+    #[test]
+    fn in_flight() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let count = || in_flight.load(Ordering::Relaxed);
+
+        let req = Request::new();
+        let guard = attach(&req, Some(&in_flight));
+        let guard2 = attach(&Request::new(), Some(&in_flight));
+        assert_eq!(count(), 2);
+
+        guard.complete();
+        assert_eq!(count(), 1);
+
+        // requests are in flight until their response is done, even if the request is dropped
+        drop(req);
+        assert_eq!(count(), 1);
+
+        drop(guard2);
+        assert_eq!(count(), 0);
+    }
+
+    #[test]
+    fn outside_runtime() {
+        let log = Log::default();
+
+        let req = Request::new();
+        let guard = attach(&req, None);
+        register(&req, &log, 1);
+        drop(guard);
+
+        assert!(log.borrow().is_empty());
+        assert_eq!(
+            req.extensions().get::<OnFinish>().unwrap().status(),
+            Some(FinishStatus::Aborted)
+        );
+    }
+}
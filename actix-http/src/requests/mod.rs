@@ -1,9 +1,12 @@
 //! HTTP requests.
 
+mod finish;
 mod head;
 mod request;
 
+pub(crate) use self::finish::{attach as attach_finish, FinishGuard};
 pub use self::{
+    finish::{FinishStatus, OnFinish},
     head::{RequestHead, RequestHeadType},
     request::Request,
 };
@@ -7,7 +7,7 @@ use crate::{
     error::{Error, HttpError},
     header::{self, TryIntoHeaderPair, TryIntoHeaderValue},
     responses::{BoxedResponseHead, ResponseHead},
-    ConnectionType, Extensions, Response, StatusCode,
+    ConnectionType, Extensions, KeepAlive, Response, StatusCode,
 };
 
 /// An HTTP response builder.
@@ -144,6 +144,17 @@ impl ResponseBuilder {
         self
     }
 
+    /// Overrides the server's keep-alive policy for the connection this response is sent on.
+    ///
+    /// See [`ResponseHead::set_keep_alive_policy`] for details.
+    #[inline]
+    pub fn keep_alive_policy(&mut self, keep_alive: impl Into<KeepAlive>) -> &mut Self {
+        if let Some(parts) = self.inner() {
+            parts.set_keep_alive_policy(keep_alive.into());
+        }
+        self
+    }
+
     /// Set connection type to `Upgrade`.
     #[inline]
     pub fn upgrade<V>(&mut self, value: V) -> &mut Self
@@ -182,6 +193,22 @@ impl ResponseBuilder {
         self
     }
 
+    /// Delimit an HTTP/1.x streaming response body by closing the connection, without using
+    /// chunked transfer encoding.
+    ///
+    /// This is useful for clients that are known to mishandle chunked responses. Any previously
+    /// set `Content-Length` header is removed and the connection is closed once the body has been
+    /// written. Has no effect on HTTP/2 responses, which are framed by the protocol itself.
+    #[inline]
+    pub fn close_delimited(&mut self) -> &mut Self {
+        if let Some(parts) = self.inner() {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts.no_chunking(true);
+            parts.set_connection_type(ConnectionType::Close);
+        }
+        self
+    }
+
     /// Set response content type.
     #[inline]
     pub fn content_type<V>(&mut self, value: V) -> &mut Self
@@ -201,11 +228,24 @@ impl ResponseBuilder {
 
     /// Generate response with a wrapped body.
     ///
+    /// A warning is logged if the body is not empty but the response status does not allow a body
+    /// to be sent; see [`ResponseHead::allows_body()`].
+    ///
     /// This `ResponseBuilder` will be left in a useless state.
     pub fn body<B>(&mut self, body: B) -> Response<EitherBody<B>>
     where
         B: MessageBody + 'static,
     {
+        if let Some(head) = self.head.as_deref() {
+            if !head.allows_body() && !body.size().is_eof() {
+                tracing::warn!(
+                    "body of {} response will not be sent; responses with this status must not \
+                    have a body",
+                    head.status
+                );
+            }
+        }
+
         match self.message_body(body) {
             Ok(res) => res.map_body(|_, body| EitherBody::left(body)),
             Err(err) => Response::from(err).map_body(|_, body| EitherBody::right(body)),
@@ -343,6 +383,17 @@ mod tests {
         assert!(!resp.keep_alive());
     }
 
+    #[test]
+    fn test_close_delimited() {
+        let resp = Response::build(StatusCode::OK)
+            .no_chunking(10)
+            .close_delimited()
+            .finish();
+        assert!(!resp.keep_alive());
+        assert!(!resp.head().chunked());
+        assert!(!resp.headers().contains_key(header::CONTENT_LENGTH));
+    }
+
     #[test]
     fn test_content_type() {
         let resp = Response::build(StatusCode::OK)
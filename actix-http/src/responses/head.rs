@@ -2,7 +2,7 @@
 
 use std::{cell::RefCell, ops};
 
-use crate::{header::HeaderMap, message::Flags, ConnectionType, StatusCode, Version};
+use crate::{header::HeaderMap, message::Flags, ConnectionType, KeepAlive, StatusCode, Version};
 
 thread_local! {
     static RESPONSE_POOL: BoxedResponsePool = BoxedResponsePool::create();
@@ -15,6 +15,7 @@ pub struct ResponseHead {
     pub headers: HeaderMap,
     pub reason: Option<&'static str>,
     pub(crate) flags: Flags,
+    pub(crate) keep_alive: Option<KeepAlive>,
 }
 
 impl ResponseHead {
@@ -27,6 +28,7 @@ impl ResponseHead {
             headers: HeaderMap::with_capacity(12),
             reason: None,
             flags: Flags::empty(),
+            keep_alive: None,
         }
     }
 
@@ -85,6 +87,33 @@ impl ResponseHead {
         self.connection_type() == ConnectionType::KeepAlive
     }
 
+    /// Overrides the server's keep-alive policy for the connection this response is sent on.
+    ///
+    /// [`KeepAlive::Disabled`] closes the connection once this response has been sent. Otherwise,
+    /// if the connection is kept alive, the given policy determines how long it may stay idle
+    /// before the next request; it cannot keep alive a connection that the client or server
+    /// configuration would close.
+    ///
+    /// On HTTP/1.x connections, a `Keep-Alive: timeout=<secs>` header is sent along with
+    /// [`KeepAlive::Timeout`] overrides. On HTTP/2 connections, [`KeepAlive::Disabled`] starts a
+    /// graceful shutdown and other policies apply to the ping-based idle timeout.
+    #[inline]
+    pub fn set_keep_alive_policy(&mut self, keep_alive: KeepAlive) {
+        let keep_alive = keep_alive.normalize();
+
+        if keep_alive == KeepAlive::Disabled {
+            self.set_connection_type(ConnectionType::Close);
+        }
+
+        self.keep_alive = Some(keep_alive);
+    }
+
+    /// Returns the keep-alive policy override set on this response, if any.
+    #[inline]
+    pub fn keep_alive_policy(&self) -> Option<KeepAlive> {
+        self.keep_alive
+    }
+
     /// Check upgrade status of this message
     #[inline]
     pub fn upgrade(&self) -> bool {
@@ -101,6 +130,25 @@ impl ResponseHead {
         })
     }
 
+    /// Returns false if responses with this status must not carry a body.
+    ///
+    /// This is the case for informational (1xx), `204 No Content`, `205 Reset Content` and
+    /// `304 Not Modified` responses; bodies set on them are dropped without being sent. The
+    /// exception is `101 Switching Protocols`, whose body is sent as the upgraded protocol's data.
+    ///
+    /// Note that responses to `HEAD` requests never have their bodies sent either, regardless of
+    /// status.
+    ///
+    /// See <https://datatracker.ietf.org/doc/html/rfc9110#section-6.4.1>.
+    #[inline]
+    pub fn allows_body(&self) -> bool {
+        match self.status {
+            StatusCode::SWITCHING_PROTOCOLS => true,
+            StatusCode::NO_CONTENT | StatusCode::RESET_CONTENT | StatusCode::NOT_MODIFIED => false,
+            status => !status.is_informational(),
+        }
+    }
+
     #[inline]
     pub(crate) fn conn_type(&self) -> Option<ConnectionType> {
         if self.flags.contains(Flags::CLOSE) {
@@ -181,6 +229,7 @@ impl BoxedResponsePool {
             head.status = status;
             head.headers.clear();
             head.flags = Flags::empty();
+            head.keep_alive = None;
             BoxedResponseHead { head: Some(head) }
         } else {
             BoxedResponseHead {
@@ -209,12 +258,34 @@ mod tests {
 
     use memchr::memmem;
 
+    use super::*;
     use crate::{
         h1::H1Service,
         header::{HeaderName, HeaderValue},
         Error, Request, Response, ServiceConfig,
     };
 
+    #[test]
+    fn allows_body() {
+        for status in [
+            StatusCode::CONTINUE,
+            StatusCode::from_u16(103).unwrap(),
+            StatusCode::NO_CONTENT,
+            StatusCode::RESET_CONTENT,
+            StatusCode::NOT_MODIFIED,
+        ] {
+            assert!(!ResponseHead::new(status).allows_body(), "{status}");
+        }
+
+        for status in [
+            StatusCode::SWITCHING_PROTOCOLS,
+            StatusCode::OK,
+            StatusCode::NOT_FOUND,
+        ] {
+            assert!(ResponseHead::new(status).allows_body(), "{status}");
+        }
+    }
+
     #[actix_rt::test]
     async fn camel_case_headers() {
         let mut srv = actix_http_test::test_server(|| {
@@ -0,0 +1,230 @@
+//! Informational (1xx) responses.
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    fmt,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use http::{StatusCode, Version};
+
+use crate::{header::HeaderMap, HttpMessage as _, Request, Response};
+
+/// Sends informational (1xx) responses, such as 103 Early Hints, ahead of the final response.
+///
+/// The HTTP/1 dispatcher stores a sender in the extensions of each request before calling the
+/// service. Interim responses sent through it are written to the connection immediately, in the
+/// order they are sent, and always before the head of the final response.
+///
+/// Interim responses can not be delivered to HTTP/1.0 clients nor, currently, over HTTP/2; sending
+/// fails in those cases just like it does after the final response has been written.
+///
+/// # Examples
+/// ```
+/// use actix_http::{
+///     header::{HeaderMap, HeaderValue, LINK},
+///     HttpMessage as _, InformationalSender, Request, Response, StatusCode,
+/// };
+///
+/// async fn handler(req: Request) -> Response<&'static str> {
+///     if let Some(tx) = req.extensions().get::<InformationalSender>() {
+///         let mut headers = HeaderMap::new();
+///         headers.insert(LINK, HeaderValue::from_static("</style.css>; rel=preload; as=style"));
+///         let _ = tx.send(Response::early_hints(headers));
+///     }
+///
+///     // ... prepare the final response
+///
+///     Response::with_body(StatusCode::OK, "<!doctype html>")
+/// }
+/// ```
+#[derive(Clone)]
+pub struct InformationalSender {
+    inner: Rc<RefCell<Inner>>,
+}
+
+struct Inner {
+    queue: VecDeque<Response<()>>,
+    waker: Option<Waker>,
+    closed: bool,
+}
+
+impl InformationalSender {
+    /// Queues an informational response to be written ahead of the final response.
+    ///
+    /// # Errors
+    /// Returns the response back if its status is not informational, if it is
+    /// 101 Switching Protocols (which is sent by the connection upgrade mechanism instead), or if
+    /// the response can not be delivered.
+    pub fn send(&self, res: Response<()>) -> Result<(), Response<()>> {
+        let status = res.status();
+
+        if !status.is_informational() || status == StatusCode::SWITCHING_PROTOCOLS {
+            return Err(res);
+        }
+
+        let mut inner = self.inner.borrow_mut();
+
+        if inner.closed {
+            return Err(res);
+        }
+
+        inner.queue.push_back(res);
+
+        if let Some(waker) = inner.waker.take() {
+            waker.wake();
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if informational responses can no longer be sent.
+    pub fn is_closed(&self) -> bool {
+        self.inner.borrow().closed
+    }
+}
+
+impl fmt::Debug for InformationalSender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.borrow();
+
+        f.debug_struct("InformationalSender")
+            .field("queued", &inner.queue.len())
+            .field("closed", &inner.closed)
+            .finish()
+    }
+}
+
+/// Receiving half of the informational response channel of a request, owned by the dispatcher.
+///
+/// The channel closes when the receiver is dropped.
+pub(crate) struct InformationalReceiver {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl InformationalReceiver {
+    /// Returns the next queued informational response, registering the task to be woken up when
+    /// one is sent if the queue is empty.
+    pub(crate) fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Response<()>> {
+        let mut inner = self.inner.borrow_mut();
+
+        match inner.queue.pop_front() {
+            Some(res) => Poll::Ready(res),
+            None => {
+                inner.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for InformationalReceiver {
+    fn drop(&mut self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.closed = true;
+        inner.queue.clear();
+        inner.waker = None;
+    }
+}
+
+/// Stores an informational response sender in the extensions of `req`.
+///
+/// Returns the receiving half, unless the client does not support informational responses, in
+/// which case the sender is closed from the start.
+pub(crate) fn attach(req: &Request) -> Option<InformationalReceiver> {
+    let supported = req.head().version >= Version::HTTP_11;
+
+    let inner = Rc::new(RefCell::new(Inner {
+        queue: VecDeque::new(),
+        waker: None,
+        closed: !supported,
+    }));
+
+    req.extensions_mut().insert(InformationalSender {
+        inner: Rc::clone(&inner),
+    });
+
+    supported.then_some(InformationalReceiver { inner })
+}
+
+impl Response<()> {
+    /// Constructs an informational (1xx) response with the given headers.
+    ///
+    /// Informational responses are sent ahead of the final response using the request's
+    /// [`InformationalSender`].
+    pub fn informational(status: StatusCode, headers: HeaderMap) -> Response<()> {
+        let mut res = Response::with_body(status, ());
+        *res.headers_mut() = headers;
+        res
+    }
+
+    /// Constructs a 103 Early Hints response with the given headers.
+    ///
+    /// Early hints usually carry `Link` headers that let clients preload resources while the final
+    /// response is being prepared.
+    pub fn early_hints(headers: HeaderMap) -> Response<()> {
+        let status = StatusCode::from_u16(103).unwrap();
+        let mut res = Response::informational(status, headers);
+        res.head_mut().reason = Some("Early Hints");
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::task::noop_waker;
+
+    use super::*;
+
+    #[test]
+    fn send_and_close() {
+        let req = Request::new();
+        let mut rx = attach(&req).unwrap();
+        let tx = req
+            .extensions()
+            .get::<InformationalSender>()
+            .unwrap()
+            .clone();
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert!(rx.poll_recv(&mut cx).is_pending());
+
+        tx.send(Response::early_hints(HeaderMap::new())).unwrap();
+        tx.send(Response::informational(StatusCode::OK, HeaderMap::new()))
+            .unwrap_err();
+        tx.send(Response::informational(
+            StatusCode::SWITCHING_PROTOCOLS,
+            HeaderMap::new(),
+        ))
+        .unwrap_err();
+
+        let res = match rx.poll_recv(&mut cx) {
+            Poll::Ready(res) => res,
+            Poll::Pending => panic!("expected queued response"),
+        };
+        assert_eq!(res.status().as_u16(), 103);
+        assert!(rx.poll_recv(&mut cx).is_pending());
+
+        drop(rx);
+        assert!(tx.is_closed());
+        tx.send(Response::early_hints(HeaderMap::new()))
+            .unwrap_err();
+    }
+
+    #[test]
+    fn http10_closed() {
+        let mut req = Request::new();
+        req.head_mut().version = Version::HTTP_10;
+
+        assert!(attach(&req).is_none());
+        let tx = req
+            .extensions()
+            .get::<InformationalSender>()
+            .unwrap()
+            .clone();
+        assert!(tx.is_closed());
+    }
+}
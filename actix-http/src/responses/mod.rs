@@ -2,8 +2,15 @@
 
 mod builder;
 mod head;
+mod informational;
 #[allow(clippy::module_inception)]
 mod response;
 
-pub(crate) use self::head::BoxedResponseHead;
-pub use self::{builder::ResponseBuilder, head::ResponseHead, response::Response};
+pub use self::{
+    builder::ResponseBuilder, head::ResponseHead, informational::InformationalSender,
+    response::Response,
+};
+pub(crate) use self::{
+    head::BoxedResponseHead,
+    informational::{attach as attach_informational, InformationalReceiver},
+};
@@ -21,7 +21,8 @@ use crate::{
     body::{BoxBody, MessageBody},
     builder::HttpServiceBuilder,
     error::DispatchError,
-    h1, ConnectCallback, OnConnectData, Protocol, Request, Response, ServiceConfig,
+    h1::{self, ExpectResponse},
+    ConnectCallback, OnConnectData, Protocol, Request, Response, ServiceConfig,
 };
 
 /// A [`ServiceFactory`] for HTTP/1.1 and HTTP/2 connections.
@@ -63,6 +64,7 @@ pub struct HttpService<T, S, B, X = h1::ExpectHandler, U = h1::UpgradeHandler> {
     expect: X,
     upgrade: Option<U>,
     on_connect_ext: Option<Rc<ConnectCallback<T>>>,
+    sendfile: Option<h1::SendfileSocket<T>>,
     _phantom: PhantomData<B>,
 }
 
@@ -98,6 +100,7 @@ where
             expect: h1::ExpectHandler,
             upgrade: None,
             on_connect_ext: None,
+            sendfile: None,
             _phantom: PhantomData,
         }
     }
@@ -113,6 +116,7 @@ where
             expect: h1::ExpectHandler,
             upgrade: None,
             on_connect_ext: None,
+            sendfile: None,
             _phantom: PhantomData,
         }
     }
@@ -129,11 +133,14 @@ where
 {
     /// Sets service for `Expect: 100-Continue` handling.
     ///
-    /// An expect service is called with requests that contain an `Expect` header. A successful
-    /// response type is also a request which will be forwarded to the main service.
+    /// An expect service is called with requests that contain an `Expect` header. Its response
+    /// converts into an [`ExpectResponse`](h1::ExpectResponse); either the request, which is then
+    /// forwarded to the main service, or a rejection response along with a policy for handling the
+    /// request body.
     pub fn expect<X1>(self, expect: X1) -> HttpService<T, S, B, X1, U>
     where
-        X1: ServiceFactory<Request, Config = (), Response = Request>,
+        X1: ServiceFactory<Request, Config = ()>,
+        X1::Response: Into<ExpectResponse>,
         X1::Error: Into<Response<BoxBody>>,
         X1::InitError: fmt::Debug,
     {
@@ -143,6 +150,7 @@ where
             srv: self.srv,
             upgrade: self.upgrade,
             on_connect_ext: self.on_connect_ext,
+            sendfile: self.sendfile,
             _phantom: PhantomData,
         }
     }
@@ -163,6 +171,7 @@ where
             srv: self.srv,
             expect: self.expect,
             on_connect_ext: self.on_connect_ext,
+            sendfile: self.sendfile,
             _phantom: PhantomData,
         }
     }
@@ -185,7 +194,8 @@ where
 
     B: MessageBody + 'static,
 
-    X: ServiceFactory<Request, Config = (), Response = Request>,
+    X: ServiceFactory<Request, Config = ()>,
+    X::Response: Into<ExpectResponse>,
     X::Future: 'static,
     X::Error: Into<Response<BoxBody>>,
     X::InitError: fmt::Debug,
@@ -206,7 +216,10 @@ where
             let peer_addr = io.peer_addr().ok();
             Ok((io, Protocol::Http1, peer_addr))
         })
-        .and_then(self)
+        .and_then(HttpService {
+            sendfile: Some(|io| io),
+            ..self
+        })
     }
 
     /// Creates TCP stream service from HTTP service that automatically selects HTTP/1.x or HTTP/2
@@ -236,7 +249,10 @@ where
             let peer_addr = io.peer_addr().ok();
             Ok((io, proto, peer_addr))
         })
-        .and_then(self)
+        .and_then(HttpService {
+            sendfile: Some(|io| io),
+            ..self
+        })
     }
 }
 
@@ -245,6 +261,10 @@ where
 #[derive(Debug, Default)]
 pub struct TlsAcceptorConfig {
     pub(crate) handshake_timeout: Option<std::time::Duration>,
+    #[cfg(feature = "rustls-0_23")]
+    pub(crate) session_cache_size: Option<usize>,
+    #[cfg(feature = "rustls-0_23")]
+    pub(crate) ocsp_stapling: Option<crate::OcspStapling>,
 }
 
 #[cfg(feature = "__tls")]
@@ -253,9 +273,143 @@ impl TlsAcceptorConfig {
     pub fn handshake_timeout(self, dur: std::time::Duration) -> Self {
         Self {
             handshake_timeout: Some(dur),
-            // ..self
+            ..self
+        }
+    }
+
+    /// Set the number of TLS sessions kept in memory for resumption.
+    ///
+    /// Clients that reconnect within the lifetime of a cached session can resume it by session ID
+    /// or, with TLS 1.3, by a stateful session ticket, skipping the more expensive full handshake.
+    /// A size of zero disables stateful resumption. Whether a connection resumed a session is
+    /// reported in its [`TlsInfo`](crate::TlsInfo).
+    ///
+    /// Only applies to Rustls v0.23 services; by default, Rustls caches 256 sessions. Stateless
+    /// session tickets are enabled by setting a ticketer on the Rustls `ServerConfig`. For OpenSSL,
+    /// the session cache and tickets are configured on the `SslAcceptorBuilder`.
+    #[cfg(feature = "rustls-0_23")]
+    pub fn session_cache_size(self, size: usize) -> Self {
+        Self {
+            session_cache_size: Some(size),
+            ..self
         }
     }
+
+    /// Set the OCSP response stapled to handshakes.
+    ///
+    /// Only applies to Rustls v0.23 services. The response is added to the certificates returned by
+    /// the `cert_resolver` of the `ServerConfig`. For OpenSSL, see
+    /// [`OcspStapling::configure_openssl()`](crate::OcspStapling::configure_openssl()).
+    #[cfg(feature = "rustls-0_23")]
+    pub fn ocsp_stapling(self, stapling: crate::OcspStapling) -> Self {
+        Self {
+            ocsp_stapling: Some(stapling),
+            ..self
+        }
+    }
+}
+
+/// Wraps a TLS acceptor so that it also yields the duration of each handshake.
+#[cfg(feature = "__tls")]
+fn timed_handshake<A>(
+    acceptor: A,
+) -> impl ServiceFactory<
+    TcpStream,
+    Config = (),
+    Response = (A::Response, std::time::Duration),
+    Error = A::Error,
+    InitError = A::InitError,
+>
+where
+    A: ServiceFactory<TcpStream, Config = ()>,
+{
+    actix_service::apply_fn_factory(acceptor, |io: TcpStream, acceptor: &A::Service| {
+        let started = std::time::Instant::now();
+        let handshake = acceptor.call(io);
+        async move { Ok((handshake.await?, started.elapsed())) }
+    })
+}
+
+impl<T, S, B, X, U> HttpService<T, S, B, X, U>
+where
+    T: AsyncRead + AsyncWrite + Unpin + 'static,
+
+    S: ServiceFactory<Request, Config = ()>,
+    S::Future: 'static,
+    S::Error: Into<Response<BoxBody>> + 'static,
+    S::InitError: fmt::Debug,
+    S::Response: Into<Response<B>> + 'static,
+    <S::Service as Service<Request>>::Future: 'static,
+
+    B: MessageBody + 'static,
+
+    X: ServiceFactory<Request, Config = ()>,
+    X::Response: Into<ExpectResponse>,
+    X::Future: 'static,
+    X::Error: Into<Response<BoxBody>>,
+    X::InitError: fmt::Debug,
+
+    U: ServiceFactory<(Request, Framed<T, h1::Codec>), Config = (), Response = ()>,
+    U::Future: 'static,
+    U::Error: fmt::Display + Into<Response<BoxBody>>,
+    U::InitError: fmt::Debug,
+{
+    /// Creates a service for TLS connections that adds the details of their handshake to the
+    /// connection data.
+    ///
+    /// The built-in TLS services use this to report [`TlsInfo`](crate::TlsInfo). It allows custom
+    /// TLS acceptors, such as ones implementing Encrypted Client Hello, to report the same details
+    /// to handlers and [`ConnectionCallbacks`](crate::ConnectionCallbacks).
+    ///
+    /// # Examples
+    /// ```
+    /// use std::{convert::Infallible, time::Duration};
+    ///
+    /// use actix_http::{error::DispatchError, HttpService, Protocol, Request, Response, TlsInfo};
+    /// use actix_rt::net::TcpStream;
+    /// use actix_service::{fn_service, ServiceFactoryExt as _};
+    ///
+    /// # actix_rt::System::new().block_on(async {
+    /// // stands in for an acceptor that performs the TLS handshake
+    /// let acceptor = fn_service(|io: TcpStream| async move {
+    ///     let mut tls = TlsInfo::new(Duration::from_millis(5));
+    ///     tls.server_name = Some("example.com".to_owned());
+    ///
+    ///     let peer_addr = io.peer_addr().ok();
+    ///     Ok::<_, DispatchError>((io, Protocol::Http1, peer_addr, tls))
+    /// });
+    ///
+    /// let service = HttpService::build()
+    ///     .finish(|_: Request| async { Ok::<_, Infallible>(Response::ok()) })
+    ///     .with_tls_info();
+    ///
+    /// acceptor.and_then(service);
+    /// # });
+    /// ```
+    pub fn with_tls_info(
+        self,
+    ) -> impl ServiceFactory<
+        (T, Protocol, Option<net::SocketAddr>, crate::TlsInfo),
+        Config = (),
+        Response = (),
+        Error = DispatchError,
+        InitError = (),
+    > {
+        actix_service::apply_fn_factory(
+            self,
+            |(io, proto, peer_addr, tls): (
+                T,
+                Protocol,
+                Option<net::SocketAddr>,
+                crate::TlsInfo,
+            ),
+             handler: &HttpServiceHandler<T, S::Service, B, X::Service, U::Service>| {
+                let mut conn_data = OnConnectData::from_io(&io, handler.on_connect_ext.as_deref());
+                conn_data.insert(tls);
+                handler.dispatch(io, proto, peer_addr, conn_data)
+            },
+        )
+    }
 }
 
 #[cfg(feature = "openssl")]
@@ -268,8 +422,10 @@ mod openssl {
         },
         TlsError,
     };
+    use tls_openssl::ssl::NameType;
 
     use super::*;
+    use crate::TlsInfo;
 
     impl<S, B, X, U> HttpService<TlsStream<TcpStream>, S, B, X, U>
     where
@@ -282,7 +438,8 @@ mod openssl {
 
         B: MessageBody + 'static,
 
-        X: ServiceFactory<Request, Config = (), Response = Request>,
+        X: ServiceFactory<Request, Config = ()>,
+        X::Response: Into<ExpectResponse>,
         X::Future: 'static,
         X::Error: Into<Response<BoxBody>>,
         X::InitError: fmt::Debug,
@@ -328,13 +485,21 @@ mod openssl {
                 acceptor.set_handshake_timeout(handshake_timeout);
             }
 
-            acceptor
+            timed_handshake(acceptor)
                 .map_init_err(|_| {
                     unreachable!("TLS acceptor service factory does not error on init")
                 })
                 .map_err(TlsError::into_service_error)
-                .map(|io: TlsStream<TcpStream>| {
-                    let proto = if let Some(protos) = io.ssl().selected_alpn_protocol() {
+                .map(|(io, handshake_duration): (TlsStream<TcpStream>, _)| {
+                    let ssl = io.ssl();
+
+                    let mut tls = TlsInfo::new(handshake_duration);
+                    tls.protocol_version = Some(ssl.version_str());
+                    tls.cipher = ssl.current_cipher().map(|cipher| cipher.name());
+                    tls.session_resumed = ssl.session_reused();
+                    tls.server_name = ssl.servername(NameType::HOST_NAME).map(str::to_owned);
+
+                    let proto = if let Some(protos) = ssl.selected_alpn_protocol() {
                         if protos.windows(2).any(|window| window == b"h2") {
                             Protocol::Http2
                         } else {
@@ -345,9 +510,9 @@ mod openssl {
                     };
 
                     let peer_addr = io.get_ref().peer_addr().ok();
-                    (io, proto, peer_addr)
+                    (io, proto, peer_addr, tls)
                 })
-                .and_then(self.map_err(TlsError::Service))
+                .and_then(self.with_tls_info().map_err(TlsError::Service))
         }
     }
 }
@@ -363,6 +528,7 @@ mod rustls_0_20 {
     };
 
     use super::*;
+    use crate::TlsInfo;
 
     impl<S, B, X, U> HttpService<TlsStream<TcpStream>, S, B, X, U>
     where
@@ -375,7 +541,8 @@ mod rustls_0_20 {
 
         B: MessageBody + 'static,
 
-        X: ServiceFactory<Request, Config = (), Response = Request>,
+        X: ServiceFactory<Request, Config = ()>,
+        X::Response: Into<ExpectResponse>,
         X::Future: 'static,
         X::Error: Into<Response<BoxBody>>,
         X::InitError: fmt::Debug,
@@ -425,25 +592,43 @@ mod rustls_0_20 {
                 acceptor.set_handshake_timeout(handshake_timeout);
             }
 
-            acceptor
+            timed_handshake(acceptor)
                 .map_init_err(|_| {
                     unreachable!("TLS acceptor service factory does not error on init")
                 })
                 .map_err(TlsError::into_service_error)
-                .and_then(|io: TlsStream<TcpStream>| async {
-                    let proto = if let Some(protos) = io.get_ref().1.alpn_protocol() {
-                        if protos.windows(2).any(|window| window == b"h2") {
-                            Protocol::Http2
+                .and_then(
+                    |(io, handshake_duration): (TlsStream<TcpStream>, _)| async move {
+                        let conn = io.get_ref().1;
+
+                        // session resumption is not reported before Rustls v0.23
+                        let mut tls = TlsInfo::new(handshake_duration);
+                        tls.protocol_version =
+                            conn.protocol_version()
+                                .and_then(|version| match version.get_u16() {
+                                    0x0303 => Some("TLSv1.2"),
+                                    0x0304 => Some("TLSv1.3"),
+                                    _ => version.as_str(),
+                                });
+                        tls.cipher = conn
+                            .negotiated_cipher_suite()
+                            .and_then(|suite| suite.suite().as_str());
+                        tls.server_name = conn.sni_hostname().map(str::to_owned);
+
+                        let proto = if let Some(protos) = conn.alpn_protocol() {
+                            if protos.windows(2).any(|window| window == b"h2") {
+                                Protocol::Http2
+                            } else {
+                                Protocol::Http1
+                            }
                         } else {
                             Protocol::Http1
-                        }
-                    } else {
-                        Protocol::Http1
-                    };
-                    let peer_addr = io.get_ref().0.peer_addr().ok();
-                    Ok((io, proto, peer_addr))
-                })
-                .and_then(self.map_err(TlsError::Service))
+                        };
+                        let peer_addr = io.get_ref().0.peer_addr().ok();
+                        Ok((io, proto, peer_addr, tls))
+                    },
+                )
+                .and_then(self.with_tls_info().map_err(TlsError::Service))
         }
     }
 }
@@ -459,6 +644,7 @@ mod rustls_0_21 {
     };
 
     use super::*;
+    use crate::TlsInfo;
 
     impl<S, B, X, U> HttpService<TlsStream<TcpStream>, S, B, X, U>
     where
@@ -471,7 +657,8 @@ mod rustls_0_21 {
 
         B: MessageBody + 'static,
 
-        X: ServiceFactory<Request, Config = (), Response = Request>,
+        X: ServiceFactory<Request, Config = ()>,
+        X::Response: Into<ExpectResponse>,
         X::Future: 'static,
         X::Error: Into<Response<BoxBody>>,
         X::InitError: fmt::Debug,
@@ -521,25 +708,43 @@ mod rustls_0_21 {
                 acceptor.set_handshake_timeout(handshake_timeout);
             }
 
-            acceptor
+            timed_handshake(acceptor)
                 .map_init_err(|_| {
                     unreachable!("TLS acceptor service factory does not error on init")
                 })
                 .map_err(TlsError::into_service_error)
-                .and_then(|io: TlsStream<TcpStream>| async {
-                    let proto = if let Some(protos) = io.get_ref().1.alpn_protocol() {
-                        if protos.windows(2).any(|window| window == b"h2") {
-                            Protocol::Http2
+                .and_then(
+                    |(io, handshake_duration): (TlsStream<TcpStream>, _)| async move {
+                        let conn = io.get_ref().1;
+
+                        // session resumption is not reported before Rustls v0.23
+                        let mut tls = TlsInfo::new(handshake_duration);
+                        tls.protocol_version =
+                            conn.protocol_version()
+                                .and_then(|version| match version.get_u16() {
+                                    0x0303 => Some("TLSv1.2"),
+                                    0x0304 => Some("TLSv1.3"),
+                                    _ => version.as_str(),
+                                });
+                        tls.cipher = conn
+                            .negotiated_cipher_suite()
+                            .and_then(|suite| suite.suite().as_str());
+                        tls.server_name = conn.server_name().map(str::to_owned);
+
+                        let proto = if let Some(protos) = conn.alpn_protocol() {
+                            if protos.windows(2).any(|window| window == b"h2") {
+                                Protocol::Http2
+                            } else {
+                                Protocol::Http1
+                            }
                         } else {
                             Protocol::Http1
-                        }
-                    } else {
-                        Protocol::Http1
-                    };
-                    let peer_addr = io.get_ref().0.peer_addr().ok();
-                    Ok((io, proto, peer_addr))
-                })
-                .and_then(self.map_err(TlsError::Service))
+                        };
+                        let peer_addr = io.get_ref().0.peer_addr().ok();
+                        Ok((io, proto, peer_addr, tls))
+                    },
+                )
+                .and_then(self.with_tls_info().map_err(TlsError::Service))
         }
     }
 }
@@ -555,6 +760,7 @@ mod rustls_0_22 {
     };
 
     use super::*;
+    use crate::TlsInfo;
 
     impl<S, B, X, U> HttpService<TlsStream<TcpStream>, S, B, X, U>
     where
@@ -567,7 +773,8 @@ mod rustls_0_22 {
 
         B: MessageBody + 'static,
 
-        X: ServiceFactory<Request, Config = (), Response = Request>,
+        X: ServiceFactory<Request, Config = ()>,
+        X::Response: Into<ExpectResponse>,
         X::Future: 'static,
         X::Error: Into<Response<BoxBody>>,
         X::InitError: fmt::Debug,
@@ -617,40 +824,63 @@ mod rustls_0_22 {
                 acceptor.set_handshake_timeout(handshake_timeout);
             }
 
-            acceptor
+            timed_handshake(acceptor)
                 .map_init_err(|_| {
                     unreachable!("TLS acceptor service factory does not error on init")
                 })
                 .map_err(TlsError::into_service_error)
-                .and_then(|io: TlsStream<TcpStream>| async {
-                    let proto = if let Some(protos) = io.get_ref().1.alpn_protocol() {
-                        if protos.windows(2).any(|window| window == b"h2") {
-                            Protocol::Http2
+                .and_then(
+                    |(io, handshake_duration): (TlsStream<TcpStream>, _)| async move {
+                        let conn = io.get_ref().1;
+
+                        // session resumption is not reported before Rustls v0.23
+                        let mut tls = TlsInfo::new(handshake_duration);
+                        tls.protocol_version =
+                            conn.protocol_version()
+                                .and_then(|version| match version.get_u16() {
+                                    0x0303 => Some("TLSv1.2"),
+                                    0x0304 => Some("TLSv1.3"),
+                                    _ => version.as_str(),
+                                });
+                        tls.cipher = conn
+                            .negotiated_cipher_suite()
+                            .and_then(|suite| suite.suite().as_str());
+                        tls.server_name = conn.server_name().map(str::to_owned);
+
+                        let proto = if let Some(protos) = conn.alpn_protocol() {
+                            if protos.windows(2).any(|window| window == b"h2") {
+                                Protocol::Http2
+                            } else {
+                                Protocol::Http1
+                            }
                         } else {
                             Protocol::Http1
-                        }
-                    } else {
-                        Protocol::Http1
-                    };
-                    let peer_addr = io.get_ref().0.peer_addr().ok();
-                    Ok((io, proto, peer_addr))
-                })
-                .and_then(self.map_err(TlsError::Service))
+                        };
+                        let peer_addr = io.get_ref().0.peer_addr().ok();
+                        Ok((io, proto, peer_addr, tls))
+                    },
+                )
+                .and_then(self.with_tls_info().map_err(TlsError::Service))
         }
     }
 }
 
 #[cfg(feature = "rustls-0_23")]
 mod rustls_0_23 {
-    use std::io;
+    use std::{io, sync::Arc};
 
     use actix_service::ServiceFactoryExt as _;
     use actix_tls::accept::{
         rustls_0_23::{reexports::ServerConfig, Acceptor, TlsStream},
         TlsError,
     };
+    use tls_rustls_023::{
+        server::{NoServerSessionStorage, ServerSessionMemoryCache},
+        HandshakeKind, ProtocolVersion,
+    };
 
     use super::*;
+    use crate::{ocsp::StaplingResolver, TlsInfo};
 
     impl<S, B, X, U> HttpService<TlsStream<TcpStream>, S, B, X, U>
     where
@@ -663,7 +893,8 @@ mod rustls_0_23 {
 
         B: MessageBody + 'static,
 
-        X: ServiceFactory<Request, Config = (), Response = Request>,
+        X: ServiceFactory<Request, Config = ()>,
+        X::Response: Into<ExpectResponse>,
         X::Future: 'static,
         X::Error: Into<Response<BoxBody>>,
         X::InitError: fmt::Debug,
@@ -707,31 +938,61 @@ mod rustls_0_23 {
             protos.extend_from_slice(&config.alpn_protocols);
             config.alpn_protocols = protos;
 
+            if let Some(size) = tls_acceptor_config.session_cache_size {
+                config.session_storage = if size == 0 {
+                    Arc::new(NoServerSessionStorage {})
+                } else {
+                    ServerSessionMemoryCache::new(size)
+                };
+            }
+
+            if let Some(stapling) = tls_acceptor_config.ocsp_stapling {
+                let resolver = Arc::clone(&config.cert_resolver);
+                config.cert_resolver = Arc::new(StaplingResolver::new(resolver, stapling));
+            }
+
             let mut acceptor = Acceptor::new(config);
 
             if let Some(handshake_timeout) = tls_acceptor_config.handshake_timeout {
                 acceptor.set_handshake_timeout(handshake_timeout);
             }
 
-            acceptor
+            timed_handshake(acceptor)
                 .map_init_err(|_| {
                     unreachable!("TLS acceptor service factory does not error on init")
                 })
                 .map_err(TlsError::into_service_error)
-                .and_then(|io: TlsStream<TcpStream>| async {
-                    let proto = if let Some(protos) = io.get_ref().1.alpn_protocol() {
-                        if protos.windows(2).any(|window| window == b"h2") {
-                            Protocol::Http2
+                .and_then(
+                    |(io, handshake_duration): (TlsStream<TcpStream>, _)| async move {
+                        let conn = io.get_ref().1;
+
+                        let mut tls = TlsInfo::new(handshake_duration);
+                        tls.protocol_version =
+                            conn.protocol_version().and_then(|version| match version {
+                                ProtocolVersion::TLSv1_2 => Some("TLSv1.2"),
+                                ProtocolVersion::TLSv1_3 => Some("TLSv1.3"),
+                                version => version.as_str(),
+                            });
+                        tls.cipher = conn
+                            .negotiated_cipher_suite()
+                            .and_then(|suite| suite.suite().as_str());
+                        tls.session_resumed = conn.handshake_kind() == Some(HandshakeKind::Resumed);
+                        tls.server_name = conn.server_name().map(str::to_owned);
+
+                        let proto = if let Some(protos) = conn.alpn_protocol() {
+                            if protos.windows(2).any(|window| window == b"h2") {
+                                Protocol::Http2
+                            } else {
+                                Protocol::Http1
+                            }
                         } else {
                             Protocol::Http1
-                        }
-                    } else {
-                        Protocol::Http1
-                    };
-                    let peer_addr = io.get_ref().0.peer_addr().ok();
-                    Ok((io, proto, peer_addr))
-                })
-                .and_then(self.map_err(TlsError::Service))
+                        };
+                        let peer_addr = io.get_ref().0.peer_addr().ok();
+                        Ok((io, proto, peer_addr, tls))
+                    },
+                )
+                .and_then(self.with_tls_info().map_err(TlsError::Service))
         }
     }
 }
@@ -750,7 +1011,8 @@ where
 
     B: MessageBody + 'static,
 
-    X: ServiceFactory<Request, Config = (), Response = Request>,
+    X: ServiceFactory<Request, Config = ()>,
+    X::Response: Into<ExpectResponse>,
     X::Future: 'static,
     X::Error: Into<Response<BoxBody>>,
     X::InitError: fmt::Debug,
@@ -772,6 +1034,7 @@ where
         let expect = self.expect.new_service(());
         let upgrade = self.upgrade.as_ref().map(|s| s.new_service(()));
         let on_connect_ext = self.on_connect_ext.clone();
+        let sendfile = self.sendfile;
         let cfg = self.cfg.clone();
 
         Box::pin(async move {
@@ -799,6 +1062,7 @@ where
                 expect,
                 upgrade,
                 on_connect_ext,
+                sendfile,
             ))
         })
     }
@@ -814,6 +1078,7 @@ where
     pub(super) flow: Rc<HttpFlow<S, X, U>>,
     pub(super) cfg: ServiceConfig,
     pub(super) on_connect_ext: Option<Rc<ConnectCallback<T>>>,
+    pub(super) sendfile: Option<h1::SendfileSocket<T>>,
     _phantom: PhantomData<B>,
 }
 
@@ -832,10 +1097,12 @@ where
         expect: X,
         upgrade: Option<U>,
         on_connect_ext: Option<Rc<ConnectCallback<T>>>,
+        sendfile: Option<h1::SendfileSocket<T>>,
     ) -> HttpServiceHandler<T, S, B, X, U> {
         HttpServiceHandler {
             cfg,
             on_connect_ext,
+            sendfile,
             flow: HttpFlow::new(service, expect, upgrade),
             _phantom: PhantomData,
         }
@@ -883,7 +1150,8 @@ where
 
     B: MessageBody + 'static,
 
-    X: Service<Request, Response = Request>,
+    X: Service<Request>,
+    X::Response: Into<ExpectResponse>,
     X::Error: Into<Response<BoxBody>>,
 
     U: Service<(Request, Framed<T, h1::Codec>), Response = ()>,
@@ -902,7 +1170,36 @@ where
 
     fn call(&self, (io, proto, peer_addr): (T, Protocol, Option<net::SocketAddr>)) -> Self::Future {
         let conn_data = OnConnectData::from_io(&io, self.on_connect_ext.as_deref());
+        self.dispatch(io, proto, peer_addr, conn_data)
+    }
+}
 
+impl<T, S, B, X, U> HttpServiceHandler<T, S, B, X, U>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+
+    S: Service<Request>,
+    S::Error: Into<Response<BoxBody>> + 'static,
+    S::Future: 'static,
+    S::Response: Into<Response<B>> + 'static,
+
+    B: MessageBody + 'static,
+
+    X: Service<Request>,
+    X::Response: Into<ExpectResponse>,
+    X::Error: Into<Response<BoxBody>>,
+
+    U: Service<(Request, Framed<T, h1::Codec>), Response = ()>,
+    U::Error: fmt::Display + Into<Response<BoxBody>>,
+{
+    /// Starts the dispatcher of a connection.
+    pub(crate) fn dispatch(
+        &self,
+        io: T,
+        proto: Protocol,
+        peer_addr: Option<net::SocketAddr>,
+        conn_data: OnConnectData,
+    ) -> HttpServiceHandlerResponse<T, S, B, X, U> {
         match proto {
             #[cfg(feature = "http2")]
             Protocol::Http2 => HttpServiceHandlerResponse {
@@ -930,7 +1227,8 @@ where
                         self.cfg.clone(),
                         peer_addr,
                         conn_data,
-                    ),
+                    )
+                    .sendfile(self.sendfile),
                 },
             },
 
@@ -954,7 +1252,8 @@ pin_project! {
 
         B: MessageBody,
 
-        X: Service<Request, Response = Request>,
+        X: Service<Request>,
+        X::Response: Into<ExpectResponse>,
         X::Error: Into<Response<BoxBody>>,
 
         U: Service<(Request, Framed<T, h1::Codec>), Response = ()>,
@@ -979,7 +1278,8 @@ pin_project! {
 
         B: MessageBody,
 
-        X: Service<Request, Response = Request>,
+        X: Service<Request>,
+        X::Response: Into<ExpectResponse>,
         X::Error: Into<Response<BoxBody>>,
 
         U: Service<(Request, Framed<T, h1::Codec>), Response = ()>,
@@ -1017,7 +1317,8 @@ pin_project! {
 
         B: MessageBody,
 
-        X: Service<Request, Response = Request>,
+        X: Service<Request>,
+        X::Response: Into<ExpectResponse>,
         X::Error: Into<Response<BoxBody>>,
 
         U: Service<(Request, Framed<T, h1::Codec>), Response = ()>,
@@ -1039,7 +1340,8 @@ where
 
     B: MessageBody + 'static,
 
-    X: Service<Request, Response = Request>,
+    X: Service<Request>,
+    X::Response: Into<ExpectResponse>,
     X::Error: Into<Response<BoxBody>>,
 
     U: Service<(Request, Framed<T, h1::Codec>), Response = ()>,
@@ -0,0 +1,187 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+};
+
+/// Signal used to gracefully drain connections when a server is stopping.
+///
+/// Once [`shutdown()`](Self::shutdown) is called, HTTP/1 connections of services configured with
+/// this signal (see
+/// [`HttpServiceBuilder::shutdown_signal()`](crate::HttpServiceBuilder::shutdown_signal)) stop
+/// reading new requests, finish the request that is currently being handled, send its response
+/// with a `Connection: close` header, and then close. HTTP/2 connections send a `GOAWAY` frame so
+/// that clients stop opening new streams, finish the streams in flight, and then close. Idle
+/// connections are closed immediately.
+///
+/// Connections that do not finish draining are closed forcefully when the server's shutdown
+/// timeout elapses.
+///
+/// The signal is cheap to clone and can be triggered from any thread.
+///
+/// # Examples
+/// ```
+/// # use std::convert::Infallible;
+/// use actix_http::{HttpService, Request, Response, ShutdownSignal};
+///
+/// let signal = ShutdownSignal::new();
+///
+/// # actix_rt::System::new().block_on(async {
+/// HttpService::build()
+///     .shutdown_signal(signal.clone())
+///     .finish(|_req: Request| async { Ok::<_, Infallible>(Response::ok()) })
+///     .tcp();
+/// # });
+///
+/// // when the server is stopping
+/// signal.shutdown();
+/// assert!(signal.is_shutdown());
+/// ```
+#[derive(Clone, Default)]
+pub struct ShutdownSignal {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    triggered: AtomicBool,
+    listeners: Mutex<Listeners>,
+}
+
+#[derive(Default)]
+struct Listeners {
+    next_key: usize,
+    wakers: HashMap<usize, Waker>,
+}
+
+impl ShutdownSignal {
+    /// Constructs new, un-triggered shutdown signal.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Triggers the signal, starting graceful shutdown of connections.
+    ///
+    /// Calling this method more than once has no further effect.
+    pub fn shutdown(&self) {
+        if self.inner.triggered.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let wakers = {
+            let mut listeners = self.inner.listeners.lock().unwrap();
+            std::mem::take(&mut listeners.wakers)
+        };
+
+        for (_, waker) in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Returns true if the signal has been triggered.
+    pub fn is_shutdown(&self) -> bool {
+        self.inner.triggered.load(Ordering::Acquire)
+    }
+
+    /// Returns a listener that can be polled for the signal.
+    pub(crate) fn listener(&self) -> ShutdownListener {
+        ShutdownListener {
+            signal: self.clone(),
+            key: None,
+        }
+    }
+}
+
+impl fmt::Debug for ShutdownSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShutdownSignal")
+            .field("triggered", &self.is_shutdown())
+            .finish()
+    }
+}
+
+/// Per-connection registration for a [`ShutdownSignal`].
+pub(crate) struct ShutdownListener {
+    signal: ShutdownSignal,
+    key: Option<usize>,
+}
+
+impl ShutdownListener {
+    /// Resolves once the signal has been triggered, registering the task for wake up otherwise.
+    pub(crate) fn poll_shutdown(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.signal.is_shutdown() {
+            return Poll::Ready(());
+        }
+
+        {
+            let mut listeners = self.signal.inner.listeners.lock().unwrap();
+
+            let key = *self.key.get_or_insert_with(|| {
+                listeners.next_key += 1;
+                listeners.next_key
+            });
+
+            match listeners.wakers.get_mut(&key) {
+                Some(waker) if waker.will_wake(cx.waker()) => {}
+                Some(waker) => waker.clone_from(cx.waker()),
+                None => {
+                    listeners.wakers.insert(key, cx.waker().clone());
+                }
+            }
+        }
+
+        // check again in case signal was triggered while registering
+        if self.signal.is_shutdown() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for ShutdownListener {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            if let Ok(mut listeners) = self.signal.inner.listeners.lock() {
+                listeners.wakers.remove(&key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::Poll;
+
+    use futures_util::task::noop_waker;
+
+    use super::*;
+
+    #[test]
+    fn listeners_are_woken_and_removed() {
+        let signal = ShutdownSignal::new();
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let mut listener = signal.listener();
+        assert!(listener.poll_shutdown(&mut cx).is_pending());
+        assert!(listener.poll_shutdown(&mut cx).is_pending());
+
+        let mut dropped = signal.listener();
+        assert!(dropped.poll_shutdown(&mut cx).is_pending());
+        assert_eq!(signal.inner.listeners.lock().unwrap().wakers.len(), 2);
+
+        drop(dropped);
+        assert_eq!(signal.inner.listeners.lock().unwrap().wakers.len(), 1);
+
+        signal.clone().shutdown();
+        assert!(signal.is_shutdown());
+        assert!(signal.inner.listeners.lock().unwrap().wakers.is_empty());
+        assert_eq!(listener.poll_shutdown(&mut cx), Poll::Ready(()));
+        assert_eq!(signal.listener().poll_shutdown(&mut cx), Poll::Ready(()));
+    }
+}
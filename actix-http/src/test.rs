@@ -273,6 +273,7 @@ impl TestSeqBuffer {
             read_buf: data.into(),
             write_buf: BytesMut::new(),
             err: None,
+            read_eof: false,
         })))
     }
 
@@ -300,12 +301,23 @@ impl TestSeqBuffer {
             .read_buf
             .extend_from_slice(data.as_ref())
     }
+
+    /// Simulates the peer closing its write-half; reads return EOF once the read buffer is empty.
+    pub fn close_read(&mut self) {
+        self.0.borrow_mut().read_eof = true;
+    }
+
+    /// Sets error returned by the next read once the read buffer is empty.
+    pub fn set_read_err(&mut self, err: io::Error) {
+        self.0.borrow_mut().err = Some(err);
+    }
 }
 
 pub struct TestSeqInner {
     read_buf: BytesMut,
     write_buf: BytesMut,
     err: Option<io::Error>,
+    read_eof: bool,
 }
 
 impl io::Read for TestSeqBuffer {
@@ -313,6 +325,8 @@ impl io::Read for TestSeqBuffer {
         if self.0.borrow().read_buf.is_empty() {
             if self.0.borrow().err.is_some() {
                 Err(self.0.borrow_mut().err.take().unwrap())
+            } else if self.0.borrow().read_eof {
+                Ok(0)
             } else {
                 Err(io::Error::new(io::ErrorKind::WouldBlock, ""))
             }
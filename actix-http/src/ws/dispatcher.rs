@@ -8,7 +8,7 @@ use actix_codec::{AsyncRead, AsyncWrite, Framed};
 use actix_service::{IntoService, Service};
 use pin_project_lite::pin_project;
 
-use super::{Codec, Frame, Message};
+use super::{CloseCode, CloseReason, Codec, Frame, Message, ProtocolError};
 
 pin_project! {
     pub struct Dispatcher<S, T>
@@ -31,18 +31,27 @@ where
     S::Error: 'static,
 {
     pub fn new<F: IntoService<S, Frame>>(io: T, service: F) -> Self {
-        Dispatcher {
-            inner: inner::Dispatcher::new(Framed::new(io, Codec::new()), service),
-        }
+        Self::with(Framed::new(io, Codec::new()), service)
     }
 
     pub fn with<F: IntoService<S, Frame>>(framed: Framed<T, Codec>, service: F) -> Self {
         Dispatcher {
-            inner: inner::Dispatcher::new(framed, service),
+            inner: inner::Dispatcher::new(framed, service).on_decode_error(close_message),
         }
     }
 }
 
+/// Builds the close frame that tells the peer why the connection is closed after `err`.
+fn close_message(err: &ProtocolError) -> Option<Message> {
+    match err.close_code() {
+        CloseCode::Abnormal => None,
+        code => Some(Message::Close(Some(CloseReason {
+            code,
+            description: Some(err.to_string()),
+        }))),
+    }
+}
+
 impl<S, T> Future for Dispatcher<S, T>
 where
     T: AsyncRead + AsyncWrite,
@@ -184,6 +193,7 @@ mod inner {
             framed: Framed<T, U>,
             rx: mpsc::Receiver<Result<Message<I>, S::Error>>,
             tx: mpsc::Sender<Result<Message<I>, S::Error>>,
+            on_decode_error: Option<fn(&<U as Decoder>::Error) -> Option<I>>,
         }
     }
 
@@ -242,6 +252,7 @@ mod inner {
                 tx,
                 service: service.into_service(),
                 state: State::Processing,
+                on_decode_error: None,
             }
         }
 
@@ -261,9 +272,17 @@ mod inner {
                 tx,
                 service: service.into_service(),
                 state: State::Processing,
+                on_decode_error: None,
             }
         }
 
+        /// Sets a function that builds an item to send to the peer before stopping because of a
+        /// decoder error.
+        pub fn on_decode_error(mut self, f: fn(&<U as Decoder>::Error) -> Option<I>) -> Self {
+            self.on_decode_error = Some(f);
+            self
+        }
+
         /// Get sender handle.
         pub fn tx(&self) -> mpsc::Sender<Result<Message<I>, S::Error>> {
             self.tx.clone()
@@ -301,12 +320,17 @@ mod inner {
             <U as Encoder<I>>::Error: fmt::Debug,
         {
             loop {
-                let this = self.as_mut().project();
+                let mut this = self.as_mut().project();
                 match this.service.poll_ready(cx) {
                     Poll::Ready(Ok(_)) => {
-                        let item = match this.framed.next_item(cx) {
+                        let item = match this.framed.as_mut().next_item(cx) {
                             Poll::Ready(Some(Ok(el))) => el,
                             Poll::Ready(Some(Err(err))) => {
+                                if let Some(item) = this.on_decode_error.and_then(|f| f(&err)) {
+                                    // the connection is closed anyway, so errors are not reported
+                                    let _ = this.framed.as_mut().write(item);
+                                }
+
                                 *this.state = State::FramedError(DispatcherError::Decoder(err));
                                 return true;
                             }
@@ -430,6 +454,13 @@ mod inner {
                             Poll::Ready(Ok(()))
                         }
                     }
+                    State::FramedError(DispatcherError::Decoder(_)) => {
+                        // flush item sent because of the decoder error
+                        if !this.framed.is_write_buf_empty() && this.framed.flush(cx).is_pending() {
+                            return Poll::Pending;
+                        }
+                        Poll::Ready(Err(this.state.take_framed_error()))
+                    }
                     State::FramedError(_) => Poll::Ready(Err(this.state.take_framed_error())),
                     State::Stopping => Poll::Ready(Ok(())),
                 };
@@ -67,6 +67,23 @@ pub enum ProtocolError {
     Io(io::Error),
 }
 
+impl ProtocolError {
+    /// Returns the close code that describes this error to the peer.
+    ///
+    /// Oversized payloads map to [`CloseCode::Size`] and I/O errors, after which no close frame
+    /// can be sent, to [`CloseCode::Abnormal`]. All other errors are violations of the protocol
+    /// and map to [`CloseCode::Protocol`].
+    ///
+    /// [`Dispatcher`] sends a close frame with this code before stopping because of an error.
+    pub fn close_code(&self) -> CloseCode {
+        match self {
+            ProtocolError::Overflow => CloseCode::Size,
+            ProtocolError::Io(_) => CloseCode::Abnormal,
+            _ => CloseCode::Protocol,
+        }
+    }
+}
+
 /// WebSocket handshake errors
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Error)]
 pub enum HandshakeError {
@@ -95,6 +112,24 @@ pub enum HandshakeError {
     BadWebsocketKey,
 }
 
+impl HandshakeError {
+    /// Returns the status code of the response to a handshake that failed with this error.
+    ///
+    /// Requests with a method other than `GET` get `405 Method Not Allowed`, requests for an
+    /// unsupported protocol version get `426 Upgrade Required` and all other malformed handshakes
+    /// get `400 Bad Request`.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            HandshakeError::GetMethodRequired => StatusCode::METHOD_NOT_ALLOWED,
+            HandshakeError::UnsupportedVersion => StatusCode::UPGRADE_REQUIRED,
+            HandshakeError::NoWebsocketUpgrade
+            | HandshakeError::NoConnectionUpgrade
+            | HandshakeError::NoVersionHeader
+            | HandshakeError::BadWebsocketKey => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
 impl From<HandshakeError> for Response<BoxBody> {
     fn from(err: HandshakeError) -> Self {
         match err {
@@ -125,8 +160,12 @@ impl From<HandshakeError> for Response<BoxBody> {
             }
 
             HandshakeError::UnsupportedVersion => {
-                let mut res = Response::bad_request();
+                let mut res = Response::new(StatusCode::UPGRADE_REQUIRED);
                 res.head_mut().reason = Some("Unsupported WebSocket version");
+                #[allow(clippy::declare_interior_mutable_const)]
+                const HV_VERSION: HeaderValue = HeaderValue::from_static("13");
+                res.headers_mut()
+                    .insert(header::SEC_WEBSOCKET_VERSION, HV_VERSION);
                 res
             }
 
@@ -145,6 +184,25 @@ impl From<&HandshakeError> for Response<BoxBody> {
     }
 }
 
+/// Error returned by [`verify_origin()`] when the origin of a handshake request is not allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Error)]
+#[display("WebSocket origin is not allowed")]
+pub struct OriginError;
+
+impl OriginError {
+    /// Returns the status code of the response to a handshake that failed with this error,
+    /// `403 Forbidden`.
+    pub fn status_code(&self) -> StatusCode {
+        StatusCode::FORBIDDEN
+    }
+}
+
+impl From<OriginError> for Response<BoxBody> {
+    fn from(err: OriginError) -> Self {
+        Response::new(err.status_code())
+    }
+}
+
 /// Verify WebSocket handshake request and create handshake response.
 pub fn handshake(req: &RequestHead) -> Result<ResponseBuilder, HandshakeError> {
     verify_handshake(req)?;
@@ -199,6 +257,33 @@ pub fn verify_handshake(req: &RequestHead) -> Result<(), HandshakeError> {
     Ok(())
 }
 
+/// Verify the origin of a WebSocket handshake request.
+///
+/// Browsers allow any page to open WebSocket connections to any host, sending along cookies of
+/// that host, so endpoints that rely on cookies must check the `Origin` header to prevent
+/// cross-site WebSocket hijacking. Fails with [`OriginError`] if `is_allowed` returns false for the `Origin` header. Requests without an `Origin` header, which are not made
+/// by browsers, are accepted.
+///
+/// # Examples
+/// ```
+/// use actix_http::{body::BoxBody, ws, Request, Response};
+///
+/// fn verify(req: &Request) -> Result<(), Response<BoxBody>> {
+///     ws::verify_handshake(req.head())?;
+///     ws::verify_origin(req.head(), |origin| origin == "https://example.com")?;
+///     Ok(())
+/// }
+/// ```
+pub fn verify_origin<F>(req: &RequestHead, is_allowed: F) -> Result<(), OriginError>
+where
+    F: FnOnce(&HeaderValue) -> bool,
+{
+    match req.headers().get(header::ORIGIN) {
+        Some(origin) if !is_allowed(origin) => Err(OriginError),
+        _ => Ok(()),
+    }
+}
+
 /// Create WebSocket handshake response.
 ///
 /// This function returns handshake `Response`, ready to send to peer.
@@ -344,8 +429,56 @@ mod tests {
         let resp: Response<BoxBody> = HandshakeError::NoVersionHeader.into();
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
         let resp: Response<BoxBody> = HandshakeError::UnsupportedVersion.into();
-        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(resp.status(), StatusCode::UPGRADE_REQUIRED);
+        assert_eq!(
+            resp.headers().get(header::SEC_WEBSOCKET_VERSION).unwrap(),
+            "13"
+        );
         let resp: Response<BoxBody> = HandshakeError::BadWebsocketKey.into();
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
+
+    #[test]
+    fn test_ws_error_status_code() {
+        for err in [
+            HandshakeError::GetMethodRequired,
+            HandshakeError::NoWebsocketUpgrade,
+            HandshakeError::NoConnectionUpgrade,
+            HandshakeError::NoVersionHeader,
+            HandshakeError::UnsupportedVersion,
+            HandshakeError::BadWebsocketKey,
+        ] {
+            assert_eq!(err.status_code(), Response::from(err).status());
+        }
+    }
+
+    #[test]
+    fn test_verify_origin() {
+        let is_allowed = |origin: &HeaderValue| origin == "https://example.com";
+
+        let req = TestRequest::default().finish();
+        assert!(verify_origin(req.head(), is_allowed).is_ok());
+
+        let req = TestRequest::default()
+            .insert_header((header::ORIGIN, "https://example.com"))
+            .finish();
+        assert!(verify_origin(req.head(), is_allowed).is_ok());
+
+        let req = TestRequest::default()
+            .insert_header((header::ORIGIN, "https://evil.example"))
+            .finish();
+        let err = verify_origin(req.head(), is_allowed).unwrap_err();
+        assert_eq!(err, OriginError);
+        assert_eq!(Response::from(err).status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_protocol_error_close_code() {
+        assert_eq!(ProtocolError::Overflow.close_code(), CloseCode::Size);
+        assert_eq!(ProtocolError::BadOpCode.close_code(), CloseCode::Protocol);
+        assert_eq!(
+            ProtocolError::Io(io::ErrorKind::UnexpectedEof.into()).close_code(),
+            CloseCode::Abnormal
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use std::{io, time::Duration};
 
-use actix_http::{error::Error, HttpService, Response};
+use actix_http::{error::Error, HttpService, KeepAlive, Response, ShutdownSignal, StatusCode};
 use actix_server::Server;
 use tokio::io::AsyncWriteExt;
 
@@ -151,3 +151,183 @@ async fn h2_handshake_timeout() -> io::Result<()> {
 
     Ok(())
 }
+
+#[actix_rt::test]
+async fn h2_keep_alive_disabled_by_response() -> io::Result<()> {
+    let (tx, rx) = std::sync::mpsc::sync_channel(1);
+
+    let lst = std::net::TcpListener::bind("127.0.0.1:0")?;
+
+    let addr = lst.local_addr().unwrap();
+
+    let join = std::thread::spawn(move || {
+        actix_rt::System::new().block_on(async move {
+            let srv = Server::build()
+                .disable_signals()
+                .workers(1)
+                .listen("h2_keep_alive_disabled", lst, || {
+                    HttpService::build()
+                        .keep_alive(Duration::from_secs(30))
+                        .h2(|_| async {
+                            Ok::<_, Error>(
+                                Response::build(StatusCode::OK)
+                                    .keep_alive_policy(KeepAlive::Disabled)
+                                    .finish(),
+                            )
+                        })
+                        .tcp()
+                })?
+                .run();
+
+            tx.send(srv.handle()).unwrap();
+
+            srv.await
+        })
+    });
+
+    let handle = rx.recv().unwrap();
+
+    let stream = tokio::net::TcpStream::connect(addr).await?;
+    let (mut tx, conn) = h2::client::handshake(stream).await.unwrap();
+    let conn = actix_rt::spawn(conn);
+
+    let (res, _) = tx.send_request(::http::Request::new(()), true).unwrap();
+    assert_eq!(res.await.unwrap().status().as_u16(), 200);
+
+    // server closes the connection once the response has been sent
+    actix_rt::time::timeout(Duration::from_secs(5), conn)
+        .await
+        .expect("connection should be closed by server")
+        .unwrap()
+        .unwrap();
+
+    handle.stop(true).await;
+    join.join().unwrap()?;
+
+    Ok(())
+}
+
+#[actix_rt::test]
+async fn h2_shutdown_signal() -> io::Result<()> {
+    let (tx, rx) = std::sync::mpsc::sync_channel(1);
+
+    let lst = std::net::TcpListener::bind("127.0.0.1:0")?;
+
+    let addr = lst.local_addr().unwrap();
+
+    let signal = ShutdownSignal::new();
+
+    let join = std::thread::spawn({
+        let signal = signal.clone();
+
+        move || {
+            actix_rt::System::new().block_on(async move {
+                let srv = Server::build()
+                    .disable_signals()
+                    .workers(1)
+                    .listen("h2_shutdown_signal", lst, move || {
+                        HttpService::build()
+                            .keep_alive(Duration::from_secs(30))
+                            .shutdown_signal(signal.clone())
+                            .h2(|_| async { Ok::<_, Error>(Response::ok()) })
+                            .tcp()
+                    })?
+                    .run();
+
+                tx.send(srv.handle()).unwrap();
+
+                srv.await
+            })
+        }
+    });
+
+    let handle = rx.recv().unwrap();
+
+    let stream = tokio::net::TcpStream::connect(addr).await?;
+    let (mut tx, conn) = h2::client::handshake(stream).await.unwrap();
+    let conn = actix_rt::spawn(conn);
+
+    let (res, _) = tx.send_request(::http::Request::new(()), true).unwrap();
+    assert_eq!(res.await.unwrap().status().as_u16(), 200);
+
+    signal.shutdown();
+
+    // server sends GOAWAY and closes the idle connection
+    actix_rt::time::timeout(Duration::from_secs(5), conn)
+        .await
+        .expect("connection should be closed by server")
+        .unwrap()
+        .unwrap();
+
+    handle.stop(true).await;
+    join.join().unwrap()?;
+
+    Ok(())
+}
+
+#[actix_rt::test]
+async fn h2_disconnect_with_stream_in_flight() -> io::Result<()> {
+    use actix_http::{ConnectionCallbacks, ConnectionMeta, ConnectionStats};
+
+    struct Recorder(std::sync::mpsc::Sender<u64>);
+
+    impl ConnectionCallbacks for Recorder {
+        fn on_disconnect(&self, _conn: &ConnectionMeta, stats: &ConnectionStats) {
+            self.0.send(stats.requests).unwrap();
+        }
+    }
+
+    let (tx, rx) = std::sync::mpsc::sync_channel(1);
+    let (disconnect_tx, disconnect_rx) = std::sync::mpsc::channel();
+
+    let lst = std::net::TcpListener::bind("127.0.0.1:0")?;
+
+    let addr = lst.local_addr().unwrap();
+
+    let join = std::thread::spawn(move || {
+        actix_rt::System::new().block_on(async move {
+            let srv = Server::build()
+                .disable_signals()
+                .workers(1)
+                .listen("h2_disconnect_with_stream_in_flight", lst, move || {
+                    HttpService::build()
+                        .connection_callbacks(Recorder(disconnect_tx.clone()))
+                        .h2(|_| async {
+                            actix_rt::time::sleep(Duration::from_secs(30)).await;
+                            Ok::<_, Error>(Response::ok())
+                        })
+                        .tcp()
+                })?
+                .run();
+
+            tx.send(srv.handle()).unwrap();
+
+            srv.await
+        })
+    });
+
+    let handle = rx.recv().unwrap();
+
+    let stream = tokio::net::TcpStream::connect(addr).await?;
+    let (mut tx, conn) = h2::client::handshake(stream).await.unwrap();
+    let conn = actix_rt::spawn(conn);
+
+    let (_res, _) = tx.send_request(::http::Request::new(()), true).unwrap();
+    actix_rt::time::sleep(Duration::from_millis(100)).await;
+
+    // client goes away while the response is still being produced
+    conn.abort();
+    drop(tx);
+
+    let requests =
+        actix_rt::task::spawn_blocking(move || disconnect_rx.recv_timeout(Duration::from_secs(5)))
+            .await
+            .unwrap()
+            .expect("disconnect should be reported when the connection closes");
+    assert_eq!(requests, 1);
+
+    handle.stop(false).await;
+    join.join().unwrap()?;
+
+    Ok(())
+}
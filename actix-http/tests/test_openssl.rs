@@ -8,7 +8,8 @@ use actix_http::{
     body::{BodyStream, BoxBody, SizedStream},
     error::PayloadError,
     header::{self, HeaderValue},
-    Error, HttpService, Method, Request, Response, StatusCode, TlsAcceptorConfig, Version,
+    Error, HttpService, Method, OcspStapling, Request, Response, StatusCode, TlsAcceptorConfig,
+    Version,
 };
 use actix_http_test::test_server;
 use actix_service::{fn_service, ServiceFactoryExt};
@@ -19,7 +20,7 @@ use futures_core::Stream;
 use futures_util::{stream::once, StreamExt as _};
 use openssl::{
     pkey::PKey,
-    ssl::{SslAcceptor, SslMethod},
+    ssl::{SslAcceptor, SslAcceptorBuilder, SslConnector, SslMethod, SslVerifyMode, StatusType},
     x509::X509,
 };
 
@@ -42,6 +43,10 @@ where
 }
 
 fn tls_config() -> SslAcceptor {
+    tls_config_builder().build()
+}
+
+fn tls_config_builder() -> SslAcceptorBuilder {
     let rcgen::CertifiedKey { cert, key_pair } =
         rcgen::generate_simple_self_signed(["localhost".to_owned()]).unwrap();
     let cert_file = cert.pem();
@@ -64,7 +69,7 @@ fn tls_config() -> SslAcceptor {
     });
     builder.set_alpn_protos(b"\x02h2").unwrap();
 
-    builder.build()
+    builder
 }
 
 #[actix_rt::test]
@@ -446,3 +451,105 @@ async fn h2_on_connect() {
     let response = srv.sget("/").send().await.unwrap();
     assert!(response.status().is_success());
 }
+
+#[actix_rt::test]
+async fn h2_connection_callbacks() {
+    use std::sync::{Arc, Mutex};
+
+    use actix_http::{ConnectionCallbacks, ConnectionMeta, Protocol, RequestStats, ResponseHead};
+
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl ConnectionCallbacks for Recorder {
+        fn on_connect(&self, conn: &ConnectionMeta) {
+            assert_eq!(conn.protocol, Protocol::Http2);
+
+            let tls = conn.tls.as_ref().unwrap();
+            let negotiated = tls.protocol_version.is_some() && tls.cipher.is_some();
+            self.0.lock().unwrap().push(format!("connect {negotiated}"));
+        }
+
+        fn on_response(&self, _conn: &ConnectionMeta, head: &ResponseHead, _req: &RequestStats) {
+            let status = head.status.as_u16();
+            self.0.lock().unwrap().push(format!("response {status}"));
+        }
+
+        fn on_body_complete(&self, _conn: &ConnectionMeta, req: &RequestStats) {
+            let bytes = req.body_bytes;
+            self.0.lock().unwrap().push(format!("body {bytes}"));
+        }
+    }
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let events2 = Arc::clone(&events);
+
+    let mut srv = test_server(move || {
+        HttpService::build()
+            .connection_callbacks(Recorder(Arc::clone(&events2)))
+            .finish(|_| ok::<_, Infallible>(Response::ok().set_body(STR)))
+            .openssl(tls_config())
+            .map_err(|_| ())
+    })
+    .await;
+
+    let response = srv.sget("/").send().await.unwrap();
+    assert!(response.status().is_success());
+    let body = srv.load_body(response).await.unwrap();
+    assert_eq!(body.len(), STR.len());
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "connect true".to_owned(),
+            "response 200".to_owned(),
+            format!("body {}", STR.len())
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn h2_ocsp_stapling() {
+    use std::sync::{Arc, Mutex};
+
+    let stapling = OcspStapling::new(b"ocsp response".to_vec());
+
+    let srv = test_server(move || {
+        let mut builder = tls_config_builder();
+        stapling.configure_openssl(&mut builder).unwrap();
+
+        HttpService::build()
+            .h2(|_| ok::<_, Infallible>(Response::ok()))
+            .openssl(builder.build())
+            .map_err(|_| ())
+    })
+    .await;
+
+    let addr = srv.addr();
+
+    let stapled = actix_rt::task::spawn_blocking(move || {
+        let stapled = Arc::new(Mutex::new(None));
+        let stapled2 = Arc::clone(&stapled);
+
+        let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        connector
+            .set_status_callback(move |ssl| {
+                *stapled2.lock().unwrap() = ssl.ocsp_status().map(<[u8]>::to_vec);
+                Ok(true)
+            })
+            .unwrap();
+
+        let mut ssl = connector.build().configure().unwrap();
+        ssl.set_status_type(StatusType::OCSP).unwrap();
+
+        let stream = std::net::TcpStream::connect(addr).unwrap();
+        ssl.connect("localhost", stream).unwrap();
+
+        let stapled = stapled.lock().unwrap().take();
+        stapled
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(stapled.as_deref(), Some(&b"ocsp response"[..]));
+}
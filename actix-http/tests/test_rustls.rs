@@ -1,5 +1,6 @@
 #![cfg(feature = "rustls-0_23")]
 
+extern crate tls_openssl as openssl;
 extern crate tls_rustls_023 as rustls;
 
 use std::{
@@ -15,7 +16,8 @@ use actix_http::{
     body::{BodyStream, BoxBody, SizedStream},
     error::PayloadError,
     header::{self, HeaderName, HeaderValue},
-    Error, HttpService, Method, Request, Response, StatusCode, TlsAcceptorConfig, Version,
+    Error, HttpService, Method, OcspStapling, Request, Response, StatusCode, TlsAcceptorConfig,
+    TlsInfo, Version,
 };
 use actix_http_test::test_server;
 use actix_rt::pin;
@@ -171,6 +173,80 @@ async fn h2_1() -> io::Result<()> {
     Ok(())
 }
 
+#[actix_rt::test]
+async fn h2_tls_info() -> io::Result<()> {
+    let mut srv = test_server(move || {
+        HttpService::build()
+            .finish(|req: Request| {
+                let tls = req.conn_data::<TlsInfo>().unwrap();
+                assert!(tls.cipher.is_some());
+                assert!(!tls.session_resumed);
+                assert_eq!(tls.server_name.as_deref(), Some("localhost"));
+                ok::<_, Error>(Response::ok().set_body(tls.protocol_version.unwrap()))
+            })
+            .rustls_0_23_with_config(
+                tls_config(),
+                TlsAcceptorConfig::default().session_cache_size(0),
+            )
+    })
+    .await;
+
+    let response = srv.sget("/").send().await.unwrap();
+    assert!(response.status().is_success());
+
+    let body = srv.load_body(response).await.unwrap();
+    assert_eq!(body, "TLSv1.3");
+    Ok(())
+}
+
+#[actix_rt::test]
+async fn h2_ocsp_stapling() {
+    use std::sync::Mutex;
+
+    use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode, StatusType};
+
+    let stapling = OcspStapling::new(b"ocsp response".to_vec());
+
+    let srv = test_server(move || {
+        HttpService::build()
+            .finish(|_| ok::<_, Error>(Response::ok()))
+            .rustls_0_23_with_config(
+                tls_config(),
+                TlsAcceptorConfig::default().ocsp_stapling(stapling.clone()),
+            )
+    })
+    .await;
+
+    let addr = srv.addr();
+
+    let stapled = actix_rt::task::spawn_blocking(move || {
+        let stapled = Arc::new(Mutex::new(None));
+        let stapled2 = Arc::clone(&stapled);
+
+        let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        connector
+            .set_status_callback(move |ssl| {
+                *stapled2.lock().unwrap() = ssl.ocsp_status().map(<[u8]>::to_vec);
+                Ok(true)
+            })
+            .unwrap();
+
+        let mut ssl = connector.build().configure().unwrap();
+        ssl.set_status_type(StatusType::OCSP).unwrap();
+
+        let stream = StdTcpStream::connect(addr).unwrap();
+        ssl.connect("localhost", stream).unwrap();
+
+        let stapled = stapled.lock().unwrap().take();
+        stapled
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(stapled.as_deref(), Some(&b"ocsp response"[..]));
+}
+
 #[actix_rt::test]
 async fn h2_body1() -> io::Result<()> {
     let data = "HELLOWORLD".to_owned().repeat(64 * 1024);
@@ -7,12 +7,12 @@ use std::{
 
 use actix_http::{
     body::{self, BodyStream, BoxBody, SizedStream},
-    header, Error, HttpService, KeepAlive, Request, Response, StatusCode, Version,
+    header, Error, HeaderLimits, HttpService, KeepAlive, Request, Response, StatusCode, Version,
 };
 use actix_http_test::test_server;
 use actix_rt::{net::TcpStream, time::sleep};
 use actix_service::fn_service;
-use actix_utils::future::{err, ok, ready};
+use actix_utils::future::{err, ok, ready, Ready};
 use bytes::Bytes;
 use derive_more::derive::{Display, Error};
 use futures_util::{stream::once, FutureExt as _, StreamExt as _};
@@ -192,6 +192,298 @@ async fn chunked_payload() {
     srv.stop().await;
 }
 
+#[actix_rt::test]
+async fn chunked_payload_trailers() {
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .h1(fn_service(|mut req: Request| async move {
+                let mut pl = req.take_payload();
+                let mut size = 0;
+                while let Some(chunk) = pl.next().await {
+                    size += chunk?.len();
+                }
+
+                let trailers = pl.trailers().unwrap_or_default();
+                let checksum = trailers
+                    .get("x-checksum")
+                    .and_then(|val| val.to_str().ok())
+                    .unwrap_or("missing")
+                    .to_owned();
+
+                Ok::<_, Error>(Response::ok().set_body(format!("size={size};checksum={checksum}")))
+            }))
+            .tcp()
+    })
+    .await;
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(
+        b"POST /test HTTP/1.1\r\nTransfer-Encoding: chunked\r\nTrailer: x-checksum\r\n\r\n\
+        4\r\ndata\r\n0\r\nx-checksum: abc123\r\n\r\n",
+    );
+    stream.shutdown(net::Shutdown::Write).unwrap();
+
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.ends_with("size=4;checksum=abc123"), "{data}");
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn h1_response_trailers() {
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .h1(|_| {
+                let body = body::WithTrailers::new(
+                    BodyStream::new(once(ok::<_, Infallible>(Bytes::from_static(b"data")))),
+                    || {
+                        let mut trailers = header::HeaderMap::new();
+                        trailers.insert(
+                            header::HeaderName::from_static("server-timing"),
+                            header::HeaderValue::from_static("total;dur=1"),
+                        );
+                        trailers
+                    },
+                );
+
+                ok::<_, Infallible>(
+                    Response::build(StatusCode::OK)
+                        .insert_header((header::TRAILER, "server-timing"))
+                        .body(body),
+                )
+            })
+            .tcp()
+    })
+    .await;
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\nconnection: close\r\n\r\n");
+
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.contains("transfer-encoding: chunked\r\n"), "{data}");
+    assert!(
+        data.ends_with("\r\n\r\n4\r\ndata\r\n0\r\nserver-timing: total;dur=1\r\n\r\n"),
+        "{data}"
+    );
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn h1_close_delimited_body() {
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .h1(|_| {
+                let body = BodyStream::new(futures_util::stream::iter([
+                    Ok::<_, Infallible>(Bytes::from_static(b"hello ")),
+                    Ok(Bytes::from_static(b"world")),
+                ]));
+
+                ok::<_, Infallible>(Response::build(StatusCode::OK).close_delimited().body(body))
+            })
+            .tcp()
+    })
+    .await;
+
+    // keep-alive request; server must still close to delimit the body
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\n\r\n");
+
+    let mut data = String::new();
+    stream.read_to_string(&mut data).unwrap();
+    assert!(data.starts_with("HTTP/1.1 200 OK\r\n"), "{data}");
+    assert!(!data.contains("transfer-encoding"), "{data}");
+    assert!(!data.contains("content-length"), "{data}");
+    assert!(data.contains("connection: close\r\n"), "{data}");
+    assert!(data.ends_with("\r\n\r\nhello world"), "{data}");
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn h1_file_body() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    let data = (0..300_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    file.write_all(&data).unwrap();
+
+    let path = file.path().to_owned();
+
+    let mut srv = test_server(move || {
+        let path = path.clone();
+
+        HttpService::build()
+            .h1(move |req: Request| {
+                let file = std::fs::File::open(&path).unwrap();
+
+                let body = match req.path() {
+                    "/range" => body::FileBody::with_range(file, 1_000, 100),
+                    _ => body::FileBody::new(file).unwrap(),
+                };
+
+                ok::<_, Infallible>(Response::ok().set_body(body))
+            })
+            .tcp()
+    })
+    .await;
+
+    // second request checks that framing is intact after the first body
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ =
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\nGET /range HTTP/1.1\r\nconnection: close\r\n\r\n");
+
+    let mut res = Vec::new();
+    let _ = stream.read_to_end(&mut res);
+
+    let head_end = res.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+    assert!(res.starts_with(b"HTTP/1.1 200 OK\r\ncontent-length: 300000\r\n"));
+    assert_eq!(&res[head_end..head_end + data.len()], &data[..]);
+
+    let res = &res[head_end + data.len()..];
+    let head_end = res.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+    assert!(res.starts_with(b"HTTP/1.1 200 OK\r\ncontent-length: 100\r\n"));
+    assert_eq!(&res[head_end..], &data[1_000..1_100]);
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn h1_header_limits() {
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .header_limits(
+                HeaderLimits::new()
+                    .max_cookie_count(2)
+                    .forbid_header(header::HeaderName::from_static("x-internal")),
+            )
+            .h1(|_| ok::<_, Infallible>(Response::ok()))
+            .tcp()
+    })
+    .await;
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ =
+        stream.write_all(b"GET /test HTTP/1.1\r\ncookie: a=1; b=2\r\nconnection: close\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 200 OK\r\n"), "{data}");
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\ncookie: a=1; b=2; c=3\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(
+        data.starts_with("HTTP/1.1 431 Request Header Fields Too Large\r\n"),
+        "{data}"
+    );
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\nx-internal: 1\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{data}");
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn h1_head_limits() {
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .header_limits(
+                HeaderLimits::new()
+                    .max_header_count(2)
+                    .max_head_size(256)
+                    .max_uri_length(16),
+            )
+            .h1(|_| ok::<_, Infallible>(Response::ok()))
+            .tcp()
+    })
+    .await;
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\nconnection: close\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 200 OK\r\n"), "{data}");
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\nx-a: 1\r\nx-b: 2\r\nx-c: 3\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(
+        data.starts_with("HTTP/1.1 431 Request Header Fields Too Large\r\n"),
+        "{data}"
+    );
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let req = format!("GET /test HTTP/1.1\r\nx-a: {}\r\n\r\n", "a".repeat(256));
+    let _ = stream.write_all(req.as_bytes());
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(
+        data.starts_with("HTTP/1.1 431 Request Header Fields Too Large\r\n"),
+        "{data}"
+    );
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test/very/long/path HTTP/1.1\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 414 URI Too Long\r\n"), "{data}");
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn h1_strict_parsing() {
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .strict_parsing(true)
+            .h1(|_| ok::<_, Infallible>(Response::ok()))
+            .tcp()
+    })
+    .await;
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\nhost: a\r\nconnection: close\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 200 OK\r\n"), "{data}");
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\nhost: a\r\nhost: b\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{data}");
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\ncontent-length: 4\r\n\r\nabcd");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{data}");
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(
+        b"POST /test HTTP/1.1\r\ncontent-length: 5\r\ntransfer-encoding: chunked\r\n\r\n0\r\n\r\n",
+    );
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{data}");
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\nx-folded: a\r\n b\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{data}");
+
+    srv.stop().await;
+}
+
 #[actix_rt::test]
 async fn slow_request_408() {
     let mut srv = test_server(|| {
@@ -845,16 +1137,189 @@ async fn not_modified_spec_h1() {
         res.headers().get(&CL),
         Some(&header::HeaderValue::from_static("4")),
     );
-    // server does not prevent payload from being sent but clients may choose not to read it
-    // TODO: this is probably a bug in the client, especially since CL header can differ in length
-    // from the body
-    assert!(!srv.load_body(res).await.unwrap().is_empty());
+    assert!(srv.load_body(res).await.unwrap().is_empty());
 
     // TODO: add stream response tests
 
     srv.stop().await;
 }
 
+fn no_body_status_response(req: Request) -> Ready<Result<Response<&'static str>, Infallible>> {
+    let status = match req.path() {
+        "/204" => StatusCode::NO_CONTENT,
+        "/205" => StatusCode::RESET_CONTENT,
+        "/304" => StatusCode::NOT_MODIFIED,
+        _ => StatusCode::OK,
+    };
+
+    ok(Response::with_body(status, "1234"))
+}
+
+#[actix_rt::test]
+async fn no_body_statuses_h1() {
+    let mut srv = test_server(|| HttpService::build().h1(no_body_status_response).tcp()).await;
+
+    // bodies must not be sent, otherwise they would be read as the start of the next response
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(
+        b"GET /204 HTTP/1.1\r\n\r\n\
+        GET /205 HTTP/1.1\r\n\r\n\
+        GET /304 HTTP/1.1\r\n\r\n\
+        GET /200 HTTP/1.1\r\nconnection: close\r\n\r\n",
+    );
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+
+    let responses = data.split("HTTP/1.1 ").skip(1).collect::<Vec<_>>();
+    assert_eq!(responses.len(), 4, "{data}");
+
+    assert!(responses[0].starts_with("204 No Content\r\n"));
+    assert!(!responses[0].contains("content-length"));
+    assert!(!responses[0].contains("transfer-encoding"));
+    assert!(responses[0].ends_with("\r\n\r\n"));
+
+    assert!(responses[1].starts_with("205 Reset Content\r\n"));
+    assert!(responses[1].contains("content-length: 0\r\n"));
+    assert!(responses[1].ends_with("\r\n\r\n"));
+
+    assert!(responses[2].starts_with("304 Not Modified\r\n"));
+    assert!(!responses[2].contains("content-length"));
+    assert!(responses[2].ends_with("\r\n\r\n"));
+
+    assert!(responses[3].starts_with("200 OK\r\n"));
+    assert!(responses[3].ends_with("\r\n\r\n1234"));
+
+    // responses to HEAD requests keep their declared length
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"HEAD /200 HTTP/1.1\r\nconnection: close\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(data.contains("content-length: 4\r\n"));
+    assert!(data.ends_with("\r\n\r\n"));
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn no_body_statuses_h2() {
+    let mut srv = test_server(|| HttpService::build().h2(no_body_status_response).tcp()).await;
+
+    let tcp = TcpStream::connect(srv.addr()).await.unwrap();
+    let (h2, connection) = h2::client::handshake(tcp).await.unwrap();
+    tokio::spawn(async move { connection.await.unwrap() });
+    let mut h2 = h2.ready().await.unwrap();
+
+    for (method, path, content_length) in [
+        (::http::Method::GET, "/204", None),
+        (::http::Method::GET, "/205", Some("0")),
+        (::http::Method::GET, "/304", None),
+        (::http::Method::HEAD, "/200", Some("4")),
+    ] {
+        let req = ::http::Request::builder()
+            .method(method)
+            .uri(path)
+            .body(())
+            .unwrap();
+
+        let (response, _) = h2.send_request(req, true).unwrap();
+        let (head, mut body) = response.await.unwrap().into_parts();
+
+        assert_eq!(
+            head.headers
+                .get(header::CONTENT_LENGTH)
+                .map(|cl| cl.to_str().unwrap()),
+            content_length,
+            "{path}"
+        );
+        assert!(body.data().await.is_none(), "{path}");
+    }
+
+    srv.stop().await;
+}
+
+async fn panicking_service(req: Request) -> Result<Response<BoxBody>, Infallible> {
+    if req.path() == "/panic" {
+        panic!("service panicked");
+    }
+
+    Ok(Response::ok())
+}
+
+#[actix_rt::test]
+async fn h1_catch_panics() {
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .catch_panics(true)
+            .h1(panicking_service)
+            .tcp()
+    })
+    .await;
+
+    // connection is kept alive after the panic
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(
+        b"GET /panic HTTP/1.1\r\n\r\n\
+        GET / HTTP/1.1\r\nconnection: close\r\n\r\n",
+    );
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 500 Internal Server Error\r\n"));
+    assert!(data.contains("HTTP/1.1 200 OK\r\n"));
+
+    srv.stop().await;
+
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .on_panic(|payload| {
+                let msg = payload.downcast_ref::<&str>().unwrap();
+                Response::with_body(StatusCode::SERVICE_UNAVAILABLE, msg.to_string())
+                    .map_into_boxed_body()
+            })
+            .h1(panicking_service)
+            .tcp()
+    })
+    .await;
+
+    // connection is closed after the panic
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /panic HTTP/1.1\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+    assert!(data.contains("connection: close\r\n"));
+    assert!(data.ends_with("\r\n\r\nservice panicked"));
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn h2_catch_panics() {
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .catch_panics(true)
+            .h2(panicking_service)
+            .tcp()
+    })
+    .await;
+
+    let tcp = TcpStream::connect(srv.addr()).await.unwrap();
+    let (h2, connection) = h2::client::handshake(tcp).await.unwrap();
+    tokio::spawn(async move { connection.await.unwrap() });
+    let mut h2 = h2.ready().await.unwrap();
+
+    for (path, status) in [
+        ("/panic", StatusCode::INTERNAL_SERVER_ERROR),
+        ("/", StatusCode::OK),
+    ] {
+        let req = ::http::Request::get(path).body(()).unwrap();
+        let (response, _) = h2.send_request(req, true).unwrap();
+        assert_eq!(response.await.unwrap().status(), status);
+    }
+
+    srv.stop().await;
+}
+
 #[actix_rt::test]
 async fn h2c_auto() {
     let mut srv = test_server(|| {
@@ -895,3 +1360,123 @@ async fn h2c_auto() {
 
     srv.stop().await;
 }
+
+#[actix_rt::test]
+async fn h2_response_trailers() {
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .h2(|_| {
+                let body = body::WithTrailers::new("data", || {
+                    let mut trailers = header::HeaderMap::new();
+                    trailers.insert(
+                        header::HeaderName::from_static("server-timing"),
+                        header::HeaderValue::from_static("total;dur=1"),
+                    );
+                    trailers
+                });
+
+                ok::<_, Infallible>(Response::ok().set_body(body))
+            })
+            .tcp()
+    })
+    .await;
+
+    let tcp = TcpStream::connect(srv.addr()).await.unwrap();
+    let (h2, connection) = h2::client::handshake(tcp).await.unwrap();
+    tokio::spawn(async move { connection.await.unwrap() });
+    let mut h2 = h2.ready().await.unwrap();
+
+    let (response, _) = h2.send_request(::http::Request::new(()), true).unwrap();
+    let (head, mut body) = response.await.unwrap().into_parts();
+    assert!(head.status.is_success());
+    assert_eq!(body.data().await.unwrap().unwrap(), &b"data"[..]);
+    assert!(body.data().await.is_none());
+
+    let trailers = body.trailers().await.unwrap().unwrap();
+    assert_eq!(trailers.get("server-timing").unwrap(), "total;dur=1");
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn h2_body_error_resets_stream() {
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .h2(|_| {
+                let body = BodyStream::new(futures_util::stream::iter([
+                    Ok(Bytes::from_static(b"partial")),
+                    Err(std::io::Error::other("oops")),
+                ]));
+
+                ok::<_, Infallible>(Response::ok().set_body(body))
+            })
+            .tcp()
+    })
+    .await;
+
+    let tcp = TcpStream::connect(srv.addr()).await.unwrap();
+    let (h2, connection) = h2::client::handshake(tcp).await.unwrap();
+    tokio::spawn(async move { connection.await.unwrap() });
+    let mut h2 = h2.ready().await.unwrap();
+
+    let (response, _) = h2.send_request(::http::Request::new(()), true).unwrap();
+
+    // buffered response parts may be discarded by the client once the reset is received
+    let err = match response.await {
+        Ok(res) => {
+            let mut body = res.into_body();
+
+            loop {
+                match body.data().await.unwrap() {
+                    Ok(_) => continue,
+                    Err(err) => break err,
+                }
+            }
+        }
+        Err(err) => err,
+    };
+    assert_eq!(err.reason(), Some(h2::Reason::INTERNAL_ERROR));
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn custom_tls_info() {
+    use actix_http::{error::DispatchError, Protocol, TlsInfo};
+    use actix_service::ServiceFactoryExt as _;
+
+    let mut srv = test_server(|| {
+        fn_service(|io: TcpStream| async move {
+            // stands in for an acceptor that reports Encrypted Client Hello
+            let mut tls = TlsInfo::new(Duration::from_millis(5));
+            tls.server_name = Some("secret.example".to_owned());
+            tls.outer_server_name = Some("public.example".to_owned());
+
+            let peer_addr = io.peer_addr().ok();
+            Ok::<_, DispatchError>((io, Protocol::Http1, peer_addr, tls))
+        })
+        .and_then(
+            HttpService::build()
+                .finish(|req: Request| {
+                    let tls = req.conn_data::<TlsInfo>().unwrap();
+                    let names = format!(
+                        "{} via {}",
+                        tls.server_name.as_deref().unwrap(),
+                        tls.outer_server_name.as_deref().unwrap(),
+                    );
+
+                    ok::<_, Infallible>(Response::ok().set_body(names))
+                })
+                .with_tls_info(),
+        )
+    })
+    .await;
+
+    let response = srv.get("/").send().await.unwrap();
+    assert!(response.status().is_success());
+
+    let body = srv.load_body(response).await.unwrap();
+    assert_eq!(body, "secret.example via public.example");
+
+    srv.stop().await;
+}
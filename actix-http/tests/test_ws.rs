@@ -17,6 +17,7 @@ use bytes::Bytes;
 use derive_more::derive::{Display, Error, From};
 use futures_core::future::LocalBoxFuture;
 use futures_util::{SinkExt as _, StreamExt as _};
+use tokio::io::AsyncWriteExt as _;
 
 #[derive(Clone)]
 struct WsService(Cell<bool>);
@@ -193,3 +194,34 @@ async fn simple() {
     let item = framed.next().await.unwrap().unwrap();
     assert_eq!(item, Frame::Close(Some(CloseCode::Normal.into())));
 }
+
+#[actix_rt::test]
+async fn protocol_error_closes_with_code() {
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .upgrade(fn_factory(|| async {
+                Ok::<_, Infallible>(WsService::new())
+            }))
+            .finish(|_| async { Ok::<_, Infallible>(Response::not_found()) })
+            .tcp()
+    })
+    .await;
+
+    let mut framed = srv.ws().await.unwrap();
+
+    // masked, empty frame with reserved opcode 0x3
+    framed
+        .io_mut()
+        .write_all(&[0x83, 0x80, 0, 0, 0, 0])
+        .await
+        .unwrap();
+
+    let item = framed.next().await.unwrap().unwrap();
+    assert_eq!(
+        item,
+        Frame::Close(Some(ws::CloseReason {
+            code: CloseCode::Protocol,
+            description: Some("invalid opcode (3)".to_owned()),
+        }))
+    );
+}
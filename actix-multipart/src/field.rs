@@ -11,7 +11,7 @@ use std::{
 use actix_web::{
     error::PayloadError,
     http::header::{self, ContentDisposition, HeaderMap},
-    web::{Bytes, BytesMut},
+    web::{Bytes, BytesMut, UploadProgress},
 };
 use derive_more::derive::{Display, Error};
 use futures_core::Stream;
@@ -218,6 +218,9 @@ pub(crate) struct InnerField {
 
     /// Field data's stated size according to it's Content-Length header.
     length: Option<u64>,
+
+    /// Upload progress to which the field's progress is reported.
+    progress: Option<UploadProgress>,
 }
 
 impl InnerField {
@@ -225,14 +228,16 @@ impl InnerField {
         payload: PayloadRef,
         boundary: String,
         headers: &HeaderMap,
+        progress: Option<UploadProgress>,
     ) -> Result<Rc<RefCell<InnerField>>, PayloadError> {
-        Self::new(payload, boundary, headers).map(|this| Rc::new(RefCell::new(this)))
+        Self::new(payload, boundary, headers, progress).map(|this| Rc::new(RefCell::new(this)))
     }
 
     pub(crate) fn new(
         payload: PayloadRef,
         boundary: String,
         headers: &HeaderMap,
+        progress: Option<UploadProgress>,
     ) -> Result<InnerField, PayloadError> {
         let len = if let Some(len) = headers.get(&header::CONTENT_LENGTH) {
             match len.to_str().ok().and_then(|len| len.parse::<u64>().ok()) {
@@ -248,6 +253,7 @@ impl InnerField {
             payload: Some(payload),
             eof: false,
             length: len,
+            progress,
         })
     }
 
@@ -381,7 +387,12 @@ impl InnerField {
             };
 
             match ready!(res) {
-                Some(Ok(bytes)) => return Poll::Ready(Some(Ok(bytes))),
+                Some(Ok(bytes)) => {
+                    if let Some(progress) = &self.progress {
+                        progress.record_part(bytes.len());
+                    }
+                    return Poll::Ready(Some(Ok(bytes)));
+                }
                 Some(Err(err)) => return Poll::Ready(Some(Err(err))),
                 None => self.eof = true,
             }
@@ -403,6 +414,10 @@ impl InnerField {
         if let Poll::Ready(None) = result {
             // drop payload buffer and make future un-poll-able
             let _ = self.payload.take();
+
+            if let Some(progress) = &self.progress {
+                progress.finish_part();
+            }
         }
 
         result
@@ -11,8 +11,8 @@ use actix_web::{
     dev,
     error::{ParseError, PayloadError},
     http::header::{self, ContentDisposition, HeaderMap, HeaderName, HeaderValue},
-    web::Bytes,
-    HttpRequest,
+    web::{Bytes, UploadProgress},
+    HttpMessage as _, HttpRequest,
 };
 use futures_core::stream::Stream;
 use mime::Mime;
@@ -57,11 +57,19 @@ impl Multipart {
     }
 
     /// Creates multipart instance from parts.
+    ///
+    /// Reports the progress of each field to the request's [`UploadProgress`], if it is tracked.
     pub(crate) fn from_req(req: &HttpRequest, payload: &mut dev::Payload) -> Self {
-        match Self::find_ct_and_boundary(req.headers()) {
+        let mut multipart = match Self::find_ct_and_boundary(req.headers()) {
             Ok((ct, boundary)) => Self::from_ct_and_boundary(ct, boundary, payload.take()),
             Err(err) => Self::from_error(err),
+        };
+
+        if let Flow::InFlight(ref mut inner) = multipart.flow {
+            inner.progress = req.extensions().get::<UploadProgress>().cloned();
         }
+
+        multipart
     }
 
     /// Extract Content-Type and boundary info from headers.
@@ -100,6 +108,7 @@ impl Multipart {
                 boundary,
                 state: State::FirstBoundary,
                 item: Item::None,
+                progress: None,
             }),
         }
     }
@@ -185,6 +194,9 @@ struct Inner {
 
     state: State,
     item: Item,
+
+    /// Upload progress to which field progress is reported.
+    progress: Option<UploadProgress>,
 }
 
 impl Inner {
@@ -445,8 +457,20 @@ impl Inner {
                 }
             }
 
-            let field_inner =
-                InnerField::new_in_rc(self.payload.clone(), self.boundary.clone(), &field_headers)?;
+            let field_inner = InnerField::new_in_rc(
+                self.payload.clone(),
+                self.boundary.clone(),
+                &field_headers,
+                self.progress.clone(),
+            )?;
+
+            if let Some(progress) = &self.progress {
+                let cd = field_content_disposition.as_ref();
+                progress.start_part(
+                    cd.and_then(ContentDisposition::get_name),
+                    cd.and_then(ContentDisposition::get_filename),
+                );
+            }
 
             self.item = Item::Field(Rc::clone(&field_inner));
 
@@ -751,6 +775,62 @@ mod tests {
         }
     }
 
+    #[actix_rt::test]
+    async fn reports_field_progress() {
+        use std::cell::RefCell;
+
+        use actix_web::{
+            middleware::UploadTracker,
+            test::{self, TestRequest},
+            web, App,
+        };
+
+        use crate::test::create_form_data_payload_and_headers;
+
+        let parts = Rc::new(RefCell::new(Vec::new()));
+
+        let app = test::init_service(
+            App::new()
+                .wrap(UploadTracker::new().on_progress({
+                    let parts = Rc::clone(&parts);
+                    move |progress| parts.borrow_mut().extend(progress.part())
+                }))
+                .default_service(web::to(|mut mp: Multipart| async move {
+                    while let Some(field) = mp.next().await {
+                        let mut field = field.unwrap();
+                        while field.next().await.is_some() {}
+                    }
+                    "done"
+                })),
+        )
+        .await;
+
+        let (body, headers) = create_form_data_payload_and_headers(
+            "avatar",
+            Some("me.png".to_owned()),
+            None,
+            Bytes::from_static(b"0123456789"),
+        );
+
+        let mut req = TestRequest::post();
+        for header in headers {
+            req = req.insert_header(header);
+        }
+        let req = req.set_payload(body).to_request();
+        assert_eq!(test::call_and_read_body(&app, req).await, "done");
+
+        let parts = parts.borrow();
+        let first = parts.first().unwrap();
+        assert_eq!(first.name(), Some("avatar"));
+        assert_eq!(first.filename(), Some("me.png"));
+        assert_eq!(first.received(), 0);
+
+        let last = parts.last().unwrap();
+        assert_eq!(last.index(), 0);
+        assert_eq!(last.received(), 10);
+        assert!(last.is_complete());
+    }
+
     #[actix_rt::test]
     async fn no_content_disposition_form_data() {
         let bytes = Bytes::from(
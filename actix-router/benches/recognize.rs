@@ -0,0 +1,104 @@
+//! Measures `Router::recognize` latency across route tables of varying shape/size.
+//!
+//! Table sizes can be overridden with a comma-separated list in the `ROUTER_BENCH_SIZES`
+//! environment variable, e.g., `ROUTER_BENCH_SIZES=10,5000 cargo bench --bench recognize`.
+
+use std::env;
+
+use actix_router::{Path, ResourceDef, Router};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const DEFAULT_SIZES: &[usize] = &[10, 100, 1000];
+
+/// Shape of the routes in a generated table.
+#[derive(Debug, Clone, Copy)]
+enum Shape {
+    /// Fully static paths, e.g., `/static/42/items`.
+    Static,
+
+    /// Paths with dynamic segments, e.g., `/param/42/{id}/{name}`.
+    Param,
+
+    /// Paths with a tail match, e.g., `/tail/42/{tail}*`.
+    Tail,
+}
+
+impl Shape {
+    const ALL: [Shape; 3] = [Shape::Static, Shape::Param, Shape::Tail];
+
+    fn name(self) -> &'static str {
+        match self {
+            Shape::Static => "static",
+            Shape::Param => "param",
+            Shape::Tail => "tail",
+        }
+    }
+
+    fn pattern(self, idx: usize) -> String {
+        match self {
+            Shape::Static => format!("/static/{idx}/items"),
+            Shape::Param => format!("/param/{idx}/{{id}}/{{name}}"),
+            Shape::Tail => format!("/tail/{idx}/{{tail}}*"),
+        }
+    }
+
+    fn request_path(self, idx: usize) -> String {
+        match self {
+            Shape::Static => format!("/static/{idx}/items"),
+            Shape::Param => format!("/param/{idx}/1234/actix"),
+            Shape::Tail => format!("/tail/{idx}/some/nested/file.txt"),
+        }
+    }
+}
+
+fn table_sizes() -> Vec<usize> {
+    env::var("ROUTER_BENCH_SIZES")
+        .ok()
+        .map(|sizes| {
+            sizes
+                .split(',')
+                .map(|size| size.trim().parse().expect("invalid ROUTER_BENCH_SIZES"))
+                .collect()
+        })
+        .unwrap_or_else(|| DEFAULT_SIZES.to_vec())
+}
+
+fn build_router(shape: Shape, size: usize) -> Router<usize> {
+    let mut router = Router::build();
+
+    for idx in 0..size {
+        router.push(ResourceDef::new(shape.pattern(idx)), idx, ());
+    }
+
+    router.finish()
+}
+
+fn recognize(c: &mut Criterion) {
+    for shape in Shape::ALL {
+        let mut group = c.benchmark_group(format!("recognize/{}", shape.name()));
+
+        for size in table_sizes() {
+            let router = build_router(shape, size);
+
+            let lookups = [
+                ("first", shape.request_path(0)),
+                ("last", shape.request_path(size - 1)),
+                ("miss", "/not/a/registered/route".to_owned()),
+            ];
+
+            for (position, path) in lookups {
+                group.bench_with_input(BenchmarkId::new(position, size), &path, |b, path| {
+                    b.iter(|| {
+                        let mut path = Path::new(path.as_str());
+                        black_box(router.recognize(&mut path));
+                    });
+                });
+            }
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, recognize);
+criterion_main!(benches);
@@ -21,7 +21,7 @@ pub use self::url::Url;
 pub use self::{
     de::PathDeserializer,
     path::Path,
-    pattern::{IntoPatterns, Patterns},
+    pattern::{IntoPatterns, PatternError, PatternErrorKind, Patterns},
     quoter::Quoter,
     resource::ResourceDef,
     resource_path::{Resource, ResourcePath},
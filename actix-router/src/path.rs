@@ -3,7 +3,7 @@ use std::{
     ops::{DerefMut, Index},
 };
 
-use serde::{de, Deserialize};
+use serde::{de, ser::SerializeMap as _, Deserialize, Serialize, Serializer};
 
 use crate::{de::PathDeserializer, Resource, ResourcePath};
 
@@ -161,7 +161,19 @@ impl<T: ResourcePath> Path<T> {
         self.get(key).unwrap_or_default()
     }
 
-    /// Return iterator to items in parameter container.
+    /// Returns an iterator over the matched parameters as `(name, value)` pairs, in match order.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_router::{Path, ResourceDef};
+    ///
+    /// let resource = ResourceDef::new("/user/{id}/post/{post_id}");
+    /// let mut path = Path::new("/user/james/post/42");
+    /// assert!(resource.capture_match_info(&mut path));
+    ///
+    /// let params = path.iter().collect::<Vec<_>>();
+    /// assert_eq!(params, [("id", "james"), ("post_id", "42")]);
+    /// ```
     pub fn iter(&self) -> PathIter<'_, T> {
         PathIter {
             idx: 0,
@@ -203,6 +215,36 @@ impl<'a, T: ResourcePath> Iterator for PathIter<'a, T> {
         }
         None
     }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.params.segment_count() - self.idx;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, T: ResourcePath> ExactSizeIterator for PathIter<'a, T> {}
+
+impl<'a, T: ResourcePath> IntoIterator for &'a Path<T> {
+    type Item = (&'a str, &'a str);
+    type IntoIter = PathIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Serializes matched parameters as a map of names to values, in match order.
+impl<T: ResourcePath> Serialize for Path<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.segment_count()))?;
+
+        for (name, value) in self {
+            map.serialize_entry(name, value)?;
+        }
+
+        map.end()
+    }
 }
 
 impl<'a, T: ResourcePath> Index<&'a str> for Path<T> {
@@ -260,4 +302,28 @@ mod tests {
         let foo = RefCell::new(foo);
         let _ = foo.borrow_mut().resource_path();
     }
+
+    #[test]
+    fn iter_and_serialize() {
+        let mut path = Path::new("/user/james/post/42");
+        path.add("id", PathItem::Segment(6, 11));
+        path.add("post_id", PathItem::Segment(17, 19));
+        path.add_static("lang", "en");
+
+        let mut iter = path.iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next(), Some(("id", "james")));
+        assert_eq!(iter.len(), 2);
+
+        let params = (&path).into_iter().collect::<Vec<_>>();
+        assert_eq!(params, [("id", "james"), ("post_id", "42"), ("lang", "en")]);
+
+        assert_eq!(
+            serde_json::to_string(&path).unwrap(),
+            r#"{"id":"james","post_id":"42","lang":"en"}"#
+        );
+
+        let empty = Path::new("/");
+        assert_eq!(serde_json::to_string(&empty).unwrap(), "{}");
+    }
 }
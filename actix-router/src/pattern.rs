@@ -1,3 +1,5 @@
+use std::{error::Error as StdError, fmt};
+
 /// One or many patterns.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Patterns {
@@ -90,3 +92,101 @@ array_patterns_multiple!(&str, |&v| v.to_owned(), 2 3 4 5 6 7 8 9 10 11 12 13 14
 
 array_patterns_single!(String);
 array_patterns_multiple!(String, |v| v.clone(), 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16);
+
+/// Error returned when a path pattern is malformed.
+///
+/// See [`ResourceDef::try_new`](crate::ResourceDef::try_new).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternError {
+    pattern: String,
+    position: usize,
+    kind: PatternErrorKind,
+}
+
+impl PatternError {
+    pub(crate) fn new(pattern: &str, position: usize, kind: PatternErrorKind) -> Self {
+        Self {
+            pattern: pattern.to_owned(),
+            position,
+            kind,
+        }
+    }
+
+    /// Returns the malformed pattern.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns the byte offset within the pattern at which the error was found.
+    ///
+    /// For errors relating to a dynamic segment, this is the offset of its opening `{`.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the kind of error.
+    pub fn kind(&self) -> &PatternErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid path pattern \"{}\" at position {}: {}",
+            self.pattern, self.position, self.kind
+        )
+    }
+}
+
+impl StdError for PatternError {}
+
+/// Kinds of path pattern errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PatternErrorKind {
+    /// Dynamic segment is missing its closing `}`.
+    UnclosedSegment,
+
+    /// Dynamic segment has an empty name.
+    EmptySegmentName,
+
+    /// Dynamic segment name is used more than once in the pattern.
+    DuplicateSegmentName(String),
+
+    /// Tail segment specifies a custom regex.
+    TailWithCustomRegex,
+
+    /// Pattern has more dynamic segments than are supported.
+    TooManySegments {
+        /// Maximum number of dynamic segments.
+        max: usize,
+
+        /// Number of dynamic segments in the pattern.
+        found: usize,
+    },
+
+    /// Dynamic segment name or custom regex is not valid regex syntax.
+    InvalidRegex(String),
+}
+
+impl fmt::Display for PatternErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnclosedSegment => f.write_str("dynamic segment is not closed"),
+            Self::EmptySegmentName => f.write_str("dynamic segment name is empty"),
+            Self::DuplicateSegmentName(name) => {
+                write!(f, "dynamic segment name \"{name}\" is used more than once")
+            }
+            Self::TailWithCustomRegex => {
+                f.write_str("custom regex is not supported for tail match")
+            }
+            Self::TooManySegments { max, found } => write!(
+                f,
+                "only {max} dynamic segments are allowed, provided: {found}"
+            ),
+            Self::InvalidRegex(err) => write!(f, "invalid regex: {err}"),
+        }
+    }
+}
@@ -10,7 +10,7 @@ use tracing::error;
 use crate::{
     path::PathItem,
     regex_set::{escape, Regex, RegexSet},
-    IntoPatterns, Patterns, Resource, ResourcePath,
+    IntoPatterns, PatternError, PatternErrorKind, Patterns, Resource, ResourcePath,
 };
 
 const MAX_DYNAMIC_SEGMENTS: usize = 16;
@@ -277,6 +277,26 @@ impl ResourceDef {
         Self::construct(paths, false)
     }
 
+    /// Constructs a new resource definition from patterns, returning an error if any are malformed.
+    ///
+    /// This is useful for validating patterns that are not known at compile time, such as those
+    /// loaded from configuration. Otherwise, it behaves the same as [`new`](Self::new).
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_router::{PatternErrorKind, ResourceDef};
+    ///
+    /// let resource = ResourceDef::try_new("/user/{id}").unwrap();
+    /// assert!(resource.is_match("/user/123"));
+    ///
+    /// let err = ResourceDef::try_new("/user/{id}/post/{id").unwrap_err();
+    /// assert_eq!(err.position(), 16);
+    /// assert_eq!(err.kind(), &PatternErrorKind::UnclosedSegment);
+    /// ```
+    pub fn try_new<T: IntoPatterns>(paths: T) -> Result<Self, PatternError> {
+        Self::try_construct(paths, false)
+    }
+
     /// Constructs a new resource definition using a pattern that performs prefix matching.
     ///
     /// More specifically, the regular expressions generated for matching are different when using
@@ -304,6 +324,14 @@ impl ResourceDef {
         ResourceDef::construct(paths, true)
     }
 
+    /// Constructs a new prefix resource definition, returning an error if any patterns are
+    /// malformed.
+    ///
+    /// See [`prefix`](Self::prefix) and [`try_new`](Self::try_new).
+    pub fn try_prefix<T: IntoPatterns>(paths: T) -> Result<Self, PatternError> {
+        ResourceDef::try_construct(paths, true)
+    }
+
     /// Constructs a new resource definition using a string pattern that performs prefix matching,
     /// ensuring a leading `/` if pattern is not empty.
     ///
@@ -845,10 +873,17 @@ impl ResourceDef {
     }
 
     fn construct<T: IntoPatterns>(paths: T, is_prefix: bool) -> Self {
+        match Self::try_construct(paths, is_prefix) {
+            Ok(rdef) => rdef,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    fn try_construct<T: IntoPatterns>(paths: T, is_prefix: bool) -> Result<Self, PatternError> {
         let patterns = paths.patterns();
 
         let (pat_type, segments) = match &patterns {
-            Patterns::Single(pattern) => ResourceDef::parse(pattern, is_prefix, false),
+            Patterns::Single(pattern) => ResourceDef::parse(pattern, is_prefix, false)?,
 
             // since zero length pattern sets are possible
             // just return a useless `ResourceDef`
@@ -863,7 +898,7 @@ impl ResourceDef {
                 let mut segments = None;
 
                 for pattern in patterns {
-                    match ResourceDef::parse(pattern, is_prefix, true) {
+                    match ResourceDef::parse(pattern, is_prefix, true)? {
                         (PatternType::Dynamic(re, names), segs) => {
                             re_set.push(re.as_str().to_owned());
                             pattern_data.push((re, names));
@@ -883,14 +918,14 @@ impl ResourceDef {
             }
         };
 
-        ResourceDef {
+        Ok(ResourceDef {
             id: 0,
             name: None,
             patterns,
             is_prefix,
             pat_type,
             segments,
-        }
+        })
     }
 
     /// Parses a dynamic segment definition from a pattern.
@@ -903,7 +938,9 @@ impl ResourceDef {
     ///
     /// # Panics
     /// Panics if given patterns does not contain a dynamic segment.
-    fn parse_param(pattern: &str) -> (PatternSegment, String, &str, bool) {
+    fn parse_param(
+        pattern: &str,
+    ) -> Result<(PatternSegment, String, &str, bool), PatternErrorKind> {
         const DEFAULT_PATTERN: &str = "[^/]+";
         const DEFAULT_PATTERN_TAIL: &str = ".*";
 
@@ -920,12 +957,7 @@ impl ResourceDef {
                 }
                 _ => false,
             })
-            .unwrap_or_else(|| {
-                panic!(
-                    r#"pattern "{}" contains malformed dynamic segment"#,
-                    pattern
-                )
-            });
+            .ok_or(PatternErrorKind::UnclosedSegment)?;
 
         let (mut param, mut unprocessed) = pattern.split_at(close_idx + 1);
 
@@ -935,9 +967,8 @@ impl ResourceDef {
         let tail = unprocessed == "*";
 
         let (name, pattern) = match param.find(':') {
+            Some(_) if tail => return Err(PatternErrorKind::TailWithCustomRegex),
             Some(idx) => {
-                assert!(!tail, "custom regex is not supported for tail match");
-
                 let (name, pattern) = param.split_at(idx);
                 (name, &pattern[1..])
             }
@@ -952,10 +983,14 @@ impl ResourceDef {
             ),
         };
 
+        if name.is_empty() {
+            return Err(PatternErrorKind::EmptySegmentName);
+        }
+
         let segment = PatternSegment::Var(name.to_string());
         let regex = format!(r"(?P<{}>{})", &name, &pattern);
 
-        (segment, regex, unprocessed, tail)
+        Ok((segment, regex, unprocessed, tail))
     }
 
     /// Parse `pattern` using `is_prefix` and `force_dynamic` flags.
@@ -972,38 +1007,54 @@ impl ResourceDef {
         pattern: &str,
         is_prefix: bool,
         force_dynamic: bool,
-    ) -> (PatternType, Vec<PatternSegment>) {
+    ) -> Result<(PatternType, Vec<PatternSegment>), PatternError> {
         if !force_dynamic && pattern.find('{').is_none() && !pattern.ends_with('*') {
             // pattern is static
-            return (
+            return Ok((
                 PatternType::Static(pattern.to_owned()),
                 vec![PatternSegment::Const(pattern.to_owned())],
-            );
+            ));
         }
 
         let mut unprocessed = pattern;
         let mut segments = Vec::new();
         let mut re = format!("{}^", REGEX_FLAGS);
-        let mut dyn_segment_count = 0;
         let mut has_tail_segment = false;
 
+        // start position and regex of each dynamic segment, used to locate errors
+        let mut dyn_segments = Vec::<(usize, String)>::new();
+
         while let Some(idx) = unprocessed.find('{') {
             let (prefix, rem) = unprocessed.split_at(idx);
+            let position = pattern.len() - rem.len();
 
             segments.push(PatternSegment::Const(prefix.to_owned()));
             re.push_str(&escape(prefix));
 
-            let (param_pattern, re_part, rem, tail) = Self::parse_param(rem);
+            let (param_pattern, re_part, rem, tail) = Self::parse_param(rem)
+                .map_err(|kind| PatternError::new(pattern, position, kind))?;
 
             if tail {
                 has_tail_segment = true;
             }
 
+            if segments.contains(&param_pattern) {
+                let PatternSegment::Var(name) = param_pattern else {
+                    unreachable!()
+                };
+
+                return Err(PatternError::new(
+                    pattern,
+                    position,
+                    PatternErrorKind::DuplicateSegmentName(name),
+                ));
+            }
+
             segments.push(param_pattern);
             re.push_str(&re_part);
+            dyn_segments.push((position, re_part));
 
             unprocessed = rem;
-            dyn_segment_count += 1;
         }
 
         if is_prefix && has_tail_segment {
@@ -1041,12 +1092,16 @@ impl ResourceDef {
             re.push_str(&escape(unprocessed));
         }
 
-        assert!(
-            dyn_segment_count <= MAX_DYNAMIC_SEGMENTS,
-            "Only {} dynamic segments are allowed, provided: {}",
-            MAX_DYNAMIC_SEGMENTS,
-            dyn_segment_count
-        );
+        if dyn_segments.len() > MAX_DYNAMIC_SEGMENTS {
+            return Err(PatternError::new(
+                pattern,
+                dyn_segments[MAX_DYNAMIC_SEGMENTS].0,
+                PatternErrorKind::TooManySegments {
+                    max: MAX_DYNAMIC_SEGMENTS,
+                    found: dyn_segments.len(),
+                },
+            ));
+        }
 
         // Store the pattern in capture group #1 to have context info outside it
         let mut re = format!("({})", re);
@@ -1060,10 +1115,19 @@ impl ResourceDef {
             }
         }
 
-        let re = match Regex::new(&re) {
-            Ok(re) => re,
-            Err(err) => panic!("Wrong path pattern: \"{}\" {}", pattern, err),
-        };
+        let re = Regex::new(&re).map_err(|err| {
+            // find the dynamic segment responsible for the error, if possible
+            let position = dyn_segments
+                .iter()
+                .find(|(_, re_part)| Regex::new(re_part).is_err())
+                .map_or(0, |(position, _)| *position);
+
+            PatternError::new(
+                pattern,
+                position,
+                PatternErrorKind::InvalidRegex(err.to_string()),
+            )
+        })?;
 
         // `Bok::leak(Box::new(name))` is an intentional memory leak. In typical applications the
         // routing table is only constructed once (per worker) so leak is bounded. If you are
@@ -1074,7 +1138,7 @@ impl ResourceDef {
             .filter_map(|name| name.map(|name| Box::leak(Box::new(name.to_owned())).as_str()))
             .collect();
 
-        (PatternType::Dynamic(re, names), segments)
+        Ok((PatternType::Dynamic(re, names), segments))
     }
 }
 
@@ -1770,6 +1834,54 @@ mod tests {
         ResourceDef::new("/*");
     }
 
+    #[test]
+    fn try_new_errors() {
+        let err = ResourceDef::try_new("/user/{username").unwrap_err();
+        assert_eq!(err.pattern(), "/user/{username");
+        assert_eq!(err.position(), 6);
+        assert_eq!(err.kind(), &PatternErrorKind::UnclosedSegment);
+
+        let err = ResourceDef::try_new("/user/{}").unwrap_err();
+        assert_eq!(err.kind(), &PatternErrorKind::EmptySegmentName);
+
+        let err = ResourceDef::try_new("/user/{id}/post/{id}").unwrap_err();
+        assert_eq!(err.position(), 16);
+        assert_eq!(
+            err.kind(),
+            &PatternErrorKind::DuplicateSegmentName("id".to_owned())
+        );
+
+        let err = ResourceDef::try_new(r"/files/{tail:\d+}*").unwrap_err();
+        assert_eq!(err.position(), 7);
+        assert_eq!(err.kind(), &PatternErrorKind::TailWithCustomRegex);
+
+        let err = ResourceDef::try_new(
+            "/{a}/{b}/{c}/{d}/{e}/{f}/{g}/{h}/{i}/{j}/{k}/{l}/{m}/{n}/{o}/{p}/{q}/{r}",
+        )
+        .unwrap_err();
+        assert_eq!(err.position(), 65);
+        assert_eq!(
+            err.kind(),
+            &PatternErrorKind::TooManySegments { max: 16, found: 18 }
+        );
+
+        let err = ResourceDef::try_new(r"/user/{id:\d+}/{name:[a-z}").unwrap_err();
+        assert_eq!(err.position(), 15);
+        assert!(matches!(err.kind(), PatternErrorKind::InvalidRegex(_)));
+
+        let err = ResourceDef::try_new(["/user/{id}", "/user/{id"]).unwrap_err();
+        assert_eq!(err.pattern(), "/user/{id");
+
+        assert!(ResourceDef::try_new(["/user/{id}", "/user"]).is_ok());
+        assert!(ResourceDef::try_prefix("/user/{id}").unwrap().is_prefix());
+    }
+
+    #[test]
+    #[should_panic = "invalid path pattern \"/user/{username\" at position 6"]
+    fn invalid_pattern_panic_message() {
+        ResourceDef::new("/user/{username");
+    }
+
     #[test]
     #[should_panic]
     fn prefix_plus_tail_match_disallowed() {
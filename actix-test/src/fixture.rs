@@ -0,0 +1,367 @@
+use std::{
+    any::Any,
+    env, fmt, fs, io,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::TestServer;
+
+/// Time to wait for the thread of a dropped [`TestServer`] to exit.
+const SERVER_THREAD_EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Owner of the resources used by an integration test.
+///
+/// A fixture owns [`TestServer`]s, temporary directories, and cleanup closures, and tears them
+/// down in reverse order of registration, so that a server is stopped before the directories it
+/// serves files from are removed.
+///
+/// Tearing down happens when calling [`teardown()`](Self::teardown), which shuts servers down
+/// gracefully, or when the fixture is dropped, including when the test panics. When dropped,
+/// servers are stopped forcefully and the fixture waits for their threads to exit, so that test
+/// suites do not leak server threads and ports. Panics in cleanup closures do not prevent the
+/// remaining resources from being torn down.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App};
+///
+/// #[actix_rt::test]
+/// async fn test_example() {
+///     let mut fixture = actix_test::TestFixture::new();
+///
+///     let dir = fixture.temp_dir();
+///     std::fs::write(dir.join("index.html"), "hello").unwrap();
+///
+///     fixture.on_cleanup(|| log::info!("test finished"));
+///
+///     let srv = fixture.add_server(actix_test::start(move || {
+///         let dir = dir.clone();
+///         App::new().route(
+///             "/",
+///             web::get().to(move || {
+///                 let dir = dir.clone();
+///                 async move { std::fs::read_to_string(dir.join("index.html")).unwrap() }
+///             }),
+///         )
+///     }));
+///
+///     let res = srv.get("/").send().await.unwrap();
+///     assert!(res.status().is_success());
+///
+///     fixture.teardown().await;
+/// }
+/// ```
+#[derive(Default)]
+pub struct TestFixture {
+    resources: Vec<Resource>,
+}
+
+enum Resource {
+    Server(TestServer),
+    TempDir(PathBuf),
+    Cleanup(Box<dyn FnOnce()>),
+}
+
+impl TestFixture {
+    /// Constructs a fixture that owns no resources.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes ownership of a test server and returns a mutable reference to it.
+    pub fn add_server(&mut self, srv: TestServer) -> &mut TestServer {
+        self.resources.push(Resource::Server(srv));
+
+        match self.resources.last_mut() {
+            Some(Resource::Server(srv)) => srv,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns the most recently added test server.
+    ///
+    /// # Panics
+    /// Panics if no server has been added.
+    pub fn server(&self) -> &TestServer {
+        self.resources
+            .iter()
+            .rev()
+            .find_map(|res| match res {
+                Resource::Server(srv) => Some(srv),
+                _ => None,
+            })
+            .expect("no test server has been added to the fixture")
+    }
+
+    /// Returns the most recently added test server mutably.
+    ///
+    /// # Panics
+    /// Panics if no server has been added.
+    pub fn server_mut(&mut self) -> &mut TestServer {
+        self.resources
+            .iter_mut()
+            .rev()
+            .find_map(|res| match res {
+                Resource::Server(srv) => Some(srv),
+                _ => None,
+            })
+            .expect("no test server has been added to the fixture")
+    }
+
+    /// Creates an empty temporary directory that is removed, with its contents, on teardown.
+    ///
+    /// # Panics
+    /// Panics if the directory could not be created.
+    pub fn temp_dir(&mut self) -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let dir = loop {
+            let name = format!(
+                "actix-test-{}-{}",
+                process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            );
+            let dir = env::temp_dir().join(name);
+
+            match fs::create_dir(&dir) {
+                Ok(()) => break dir,
+                // left behind by an earlier process with the same ID
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => panic!("failed to create temporary directory: {err}"),
+            }
+        };
+
+        self.resources.push(Resource::TempDir(dir.clone()));
+        dir
+    }
+
+    /// Registers a closure to be called on teardown.
+    pub fn on_cleanup(&mut self, cleanup: impl FnOnce() + 'static) {
+        self.resources.push(Resource::Cleanup(Box::new(cleanup)));
+    }
+
+    /// Tears down all resources in reverse order of registration, stopping servers gracefully.
+    ///
+    /// # Panics
+    /// Panics with the first panic of a cleanup closure, after all resources have been torn down.
+    pub async fn teardown(mut self) {
+        let mut first_panic = None;
+
+        while let Some(res) = self.resources.pop() {
+            let panic = match res {
+                Resource::Server(srv) => {
+                    srv.stop().await;
+                    None
+                }
+                res => res.release(),
+            };
+
+            first_panic = first_panic.or(panic);
+        }
+
+        if let Some(panic) = first_panic {
+            panic::resume_unwind(panic);
+        }
+    }
+}
+
+impl Resource {
+    /// Releases the resource, returning the panic of a cleanup closure, if any.
+    fn release(self) -> Option<Box<dyn Any + Send>> {
+        match self {
+            Resource::Server(mut srv) => {
+                srv.stop_blocking(SERVER_THREAD_EXIT_TIMEOUT);
+                None
+            }
+
+            Resource::TempDir(dir) => {
+                if let Err(err) = fs::remove_dir_all(&dir) {
+                    log::warn!(
+                        "failed to remove temporary directory {}: {err}",
+                        dir.display()
+                    );
+                }
+
+                None
+            }
+
+            Resource::Cleanup(cleanup) => panic::catch_unwind(AssertUnwindSafe(cleanup)).err(),
+        }
+    }
+}
+
+impl Drop for TestFixture {
+    fn drop(&mut self) {
+        let mut first_panic = None;
+
+        while let Some(res) = self.resources.pop() {
+            first_panic = first_panic.or(res.release());
+        }
+
+        // re-raising while already unwinding would abort the process
+        if let Some(panic) = first_panic {
+            if !thread::panicking() {
+                panic::resume_unwind(panic);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for TestFixture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+
+        for res in &self.resources {
+            match res {
+                Resource::Server(srv) => list.entry(&format_args!("Server({})", srv.addr())),
+                Resource::TempDir(dir) => list.entry(&format_args!("TempDir({})", dir.display())),
+                Resource::Cleanup(_) => list.entry(&format_args!("Cleanup")),
+            };
+        }
+
+        list.finish()
+    }
+}
+
+impl TestServer {
+    /// Stops the server forcefully and waits up to `timeout` for its thread to exit, without
+    /// needing to be awaited.
+    fn stop_blocking(&mut self, timeout: Duration) {
+        #[allow(clippy::let_underscore_future)]
+        let _ = self.server.stop(false);
+        self.system.stop();
+
+        let deadline = Instant::now() + timeout;
+
+        while Instant::now() < deadline {
+            match self.thread_stop_rx.try_recv() {
+                Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {
+                    thread::sleep(Duration::from_millis(10));
+                }
+                _ => return,
+            }
+        }
+
+        log::warn!("test server thread did not exit within {timeout:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, net::TcpListener, rc::Rc};
+
+    use actix_web::{web, App, HttpResponse};
+
+    use super::*;
+
+    type Log = Rc<RefCell<Vec<&'static str>>>;
+
+    fn record(log: &Log, entry: &'static str) -> impl FnOnce() + 'static {
+        let log = Rc::clone(log);
+        move || log.borrow_mut().push(entry)
+    }
+
+    #[actix_rt::test]
+    async fn teardown_in_reverse_order() {
+        let log = Log::default();
+
+        let mut fixture = TestFixture::new();
+        fixture.on_cleanup(record(&log, "first"));
+
+        let dir = fixture.temp_dir();
+        assert!(dir.is_dir());
+
+        fixture.on_cleanup({
+            let log = Rc::clone(&log);
+            let dir = dir.clone();
+            move || {
+                assert!(dir.is_dir(), "directory removed before later cleanup");
+                log.borrow_mut().push("second");
+            }
+        });
+
+        fixture.teardown().await;
+
+        assert_eq!(*log.borrow(), ["second", "first"]);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn drop_in_reverse_order() {
+        let log = Log::default();
+
+        let mut fixture = TestFixture::new();
+        fixture.on_cleanup(record(&log, "first"));
+        fixture.on_cleanup(record(&log, "second"));
+        fixture.on_cleanup(record(&log, "third"));
+        drop(fixture);
+
+        assert_eq!(*log.borrow(), ["third", "second", "first"]);
+    }
+
+    #[test]
+    fn panicking_cleanup() {
+        let log = Log::default();
+        let mut dir = PathBuf::new();
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut fixture = TestFixture::new();
+            fixture.on_cleanup(record(&log, "first"));
+            dir = fixture.temp_dir();
+            fixture.on_cleanup(|| panic!("cleanup failed"));
+        }));
+
+        let panic = res.unwrap_err();
+        assert_eq!(panic.downcast_ref::<&str>(), Some(&"cleanup failed"));
+
+        // remaining resources are still torn down
+        assert_eq!(*log.borrow(), ["first"]);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn panicking_test_body() {
+        let log = Log::default();
+        let mut dir = PathBuf::new();
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut fixture = TestFixture::new();
+            fixture.on_cleanup(record(&log, "cleanup"));
+            dir = fixture.temp_dir();
+            fs::write(dir.join("file.txt"), "contents").unwrap();
+
+            panic!("test failed");
+        }));
+
+        let panic = res.unwrap_err();
+        assert_eq!(panic.downcast_ref::<&str>(), Some(&"test failed"));
+
+        assert_eq!(*log.borrow(), ["cleanup"]);
+        assert!(!dir.exists());
+    }
+
+    #[actix_rt::test]
+    async fn server_port_released() {
+        let app = || App::new().route("/", web::get().to(HttpResponse::Ok));
+
+        let mut fixture = TestFixture::new();
+        let addr = fixture.add_server(crate::start(app)).addr();
+
+        let res = fixture.server().get("/").send().await.unwrap();
+        assert!(res.status().is_success());
+
+        drop(fixture);
+        TcpListener::bind(addr).expect("port of dropped test server is still in use");
+
+        let mut fixture = TestFixture::new();
+        let addr = fixture.add_server(crate::start(app)).addr();
+
+        fixture.teardown().await;
+        TcpListener::bind(addr).expect("port of torn down test server is still in use");
+    }
+}
@@ -34,7 +34,7 @@
 #[cfg(feature = "openssl")]
 extern crate tls_openssl as openssl;
 
-use std::{fmt, net, thread, time::Duration};
+use std::{fmt, future::Future, net, panic, thread, time::Duration};
 
 use actix_codec::{AsyncRead, AsyncWrite, Framed};
 pub use actix_http::{body::to_bytes, test::TestBuffer};
@@ -55,6 +55,10 @@ pub use awc::{error::PayloadError, Client, ClientRequest, ClientResponse, Connec
 use futures_core::Stream;
 use tokio::sync::mpsc;
 
+mod fixture;
+
+pub use self::fixture::TestFixture;
+
 /// Start default [`TestServer`].
 ///
 /// # Examples
@@ -739,6 +743,65 @@ impl TestServer {
         self.ws_at("/").await
     }
 
+    /// Runs `n` client tasks concurrently against the server and returns their results in order.
+    ///
+    /// Each task is spawned on the current runtime and receives a clone of the test server's
+    /// client. All tasks are spawned before any of them are polled, so requests made at the start
+    /// of each task overlap. This is useful for testing race conditions, such as concurrent
+    /// requests using the same idempotency key.
+    ///
+    /// # Panics
+    /// Panics if any of the tasks panic.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// use actix_web::{web, App};
+    ///
+    /// static HITS: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// #[actix_rt::test]
+    /// async fn test_concurrent() {
+    ///     let srv = actix_test::start(|| {
+    ///         App::new().route("/", web::to(|| async { HITS.fetch_add(1, Ordering::SeqCst); "" }))
+    ///     });
+    ///
+    ///     let url = srv.url("/");
+    ///     let statuses = srv
+    ///         .run_concurrent(8, move |client| {
+    ///             let req = client.get(&url);
+    ///             async move { req.send().await.unwrap().status() }
+    ///         })
+    ///         .await;
+    ///
+    ///     assert!(statuses.iter().all(|status| status.is_success()));
+    ///     assert_eq!(HITS.load(Ordering::SeqCst), 8);
+    /// }
+    /// ```
+    pub async fn run_concurrent<F, Fut, T>(&self, n: usize, f: F) -> Vec<T>
+    where
+        F: Fn(Client) -> Fut,
+        Fut: Future<Output = T> + 'static,
+        T: 'static,
+    {
+        let handles = (0..n)
+            .map(|_| rt::spawn(f(self.client.clone())))
+            .collect::<Vec<_>>();
+
+        let mut results = Vec::with_capacity(n);
+
+        for handle in handles {
+            match handle.await {
+                Ok(res) => results.push(res),
+                Err(err) if err.is_panic() => panic::resume_unwind(err.into_panic()),
+                Err(err) => panic!("client task did not complete: {err}"),
+            }
+        }
+
+        results
+    }
+
     /// Get default HeaderMap of Client.
     ///
     /// Returns Some(&mut HeaderMap) when Client object is unique
@@ -776,3 +839,64 @@ impl Drop for TestServer {
         self.system.stop();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use actix_web::App;
+
+    use super::*;
+
+    #[actix_rt::test]
+    async fn run_concurrent_propagates_panic() {
+        let hits = Arc::new(AtomicUsize::new(0));
+
+        let srv = start({
+            let hits = Arc::clone(&hits);
+            move || {
+                let hits = Arc::clone(&hits);
+                App::new().route(
+                    "/",
+                    web::to(move || {
+                        hits.fetch_add(1, Ordering::SeqCst);
+                        async { "" }
+                    }),
+                )
+            }
+        });
+
+        let url = srv.url("/");
+        let next_client = Arc::new(AtomicUsize::new(0));
+
+        let res = rt::spawn(async move {
+            srv.run_concurrent(4, move |client| {
+                let idx = next_client.fetch_add(1, Ordering::SeqCst);
+                let req = client.get(&url);
+
+                async move {
+                    let res = req.send().await.unwrap();
+
+                    if idx == 2 {
+                        panic!("client {idx} failed");
+                    }
+
+                    res.status()
+                }
+            })
+            .await
+        })
+        .await;
+
+        let err = res.unwrap_err();
+        assert!(err.is_panic());
+        assert_eq!(
+            err.into_panic().downcast_ref::<String>().unwrap(),
+            "client 2 failed"
+        );
+        assert!(hits.load(Ordering::SeqCst) >= 3);
+    }
+}
@@ -9,10 +9,10 @@ use futures_util::FutureExt as _;
 
 use crate::{
     app_service::{AppEntry, AppInit, AppRoutingFactory},
-    config::ServiceConfig,
+    config::{AppModule, ServiceConfig},
     data::{Data, DataFactory, FnDataFactory},
     dev::ResourceDef,
-    error::Error,
+    error::{Error, ErrorRenderer},
     resource::Resource,
     route::Route,
     service::{
@@ -26,6 +26,7 @@ pub struct App<T> {
     endpoint: T,
     services: Vec<Box<dyn AppServiceFactory>>,
     default: Option<Rc<BoxedHttpServiceFactory>>,
+    error_renderer: Option<Rc<dyn ErrorRenderer>>,
     factory_ref: Rc<RefCell<Option<AppRoutingFactory>>>,
     data_factories: Vec<FnDataFactory>,
     external: Vec<ResourceDef>,
@@ -43,6 +44,7 @@ impl App<AppEntry> {
             data_factories: Vec::new(),
             services: Vec::new(),
             default: None,
+            error_renderer: None,
             factory_ref,
             external: Vec::new(),
             extensions: Extensions::new(),
@@ -201,6 +203,29 @@ where
         self
     }
 
+    /// Registers a [module](AppModule) as part of the application building process.
+    ///
+    /// This has the same effect as calling [`configure()`](Self::configure()) with the module's
+    /// [`register()`](AppModule::register()) method.
+    ///
+    /// ```
+    /// use actix_web::{web, App, HttpResponse};
+    ///
+    /// // this module could be located in a different crate
+    /// struct Users;
+    ///
+    /// impl web::AppModule for Users {
+    ///     fn register(&self, cfg: &mut web::ServiceConfig) {
+    ///         cfg.route("/users", web::get().to(HttpResponse::Ok));
+    ///     }
+    /// }
+    ///
+    /// App::new().module(Users);
+    /// ```
+    pub fn module<M: AppModule>(self, module: M) -> Self {
+        self.configure(|cfg| module.register(cfg))
+    }
+
     /// Configure route for a specific path.
     ///
     /// This is a simplified version of the `App::service()` method.
@@ -278,6 +303,24 @@ where
         self
     }
 
+    /// Sets the renderer of the error responses of the application's services.
+    ///
+    /// Scopes can override it with [`Scope::error_renderer()`](crate::Scope::error_renderer()).
+    /// See [`ErrorRenderer`] for which responses are rendered.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{error::ProblemDetails, web, App, HttpResponse};
+    ///
+    /// let app = App::new()
+    ///     .error_renderer(ProblemDetails)
+    ///     .route("/", web::get().to(HttpResponse::Ok));
+    /// ```
+    pub fn error_renderer<R: ErrorRenderer>(mut self, renderer: R) -> Self {
+        self.error_renderer = Some(Rc::new(renderer));
+        self
+    }
+
     /// Register an external resource.
     ///
     /// External resources are useful for URL generation purposes only
@@ -363,6 +406,7 @@ where
             data_factories: self.data_factories,
             services: self.services,
             default: self.default,
+            error_renderer: self.error_renderer,
             factory_ref: self.factory_ref,
             external: self.external,
             extensions: self.extensions,
@@ -426,6 +470,7 @@ where
             data_factories: self.data_factories,
             services: self.services,
             default: self.default,
+            error_renderer: self.error_renderer,
             factory_ref: self.factory_ref,
             external: self.external,
             extensions: self.extensions,
@@ -451,6 +496,7 @@ where
             services: Rc::new(RefCell::new(self.services)),
             external: RefCell::new(self.external),
             default: self.default,
+            error_renderer: self.error_renderer,
             factory_ref: self.factory_ref,
             extensions: RefCell::new(Some(self.extensions)),
         }
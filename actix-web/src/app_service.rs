@@ -11,6 +11,7 @@ use crate::{
     config::{AppConfig, AppService},
     data::FnDataFactory,
     dev::Extensions,
+    error::{render_errors, ErrorRenderer},
     guard::Guard,
     request::{HttpRequest, HttpRequestPool},
     rmap::ResourceMap,
@@ -39,6 +40,7 @@ where
     pub(crate) async_data_factories: Rc<[FnDataFactory]>,
     pub(crate) services: Rc<RefCell<Vec<Box<dyn AppServiceFactory>>>>,
     pub(crate) default: Option<Rc<BoxedHttpServiceFactory>>,
+    pub(crate) error_renderer: Option<Rc<dyn ErrorRenderer>>,
     pub(crate) factory_ref: Rc<RefCell<Option<AppRoutingFactory>>>,
     pub(crate) external: RefCell<Vec<ResourceDef>>,
 }
@@ -85,6 +87,7 @@ where
         // complete pipeline creation.
         *self.factory_ref.borrow_mut() = Some(AppRoutingFactory {
             default,
+            error_renderer: self.error_renderer.clone(),
             services: services
                 .into_iter()
                 .map(|(mut rdef, srv, guards, nested)| {
@@ -246,6 +249,7 @@ pub struct AppRoutingFactory {
         )],
     >,
     default: Rc<BoxedHttpServiceFactory>,
+    error_renderer: Option<Rc<dyn ErrorRenderer>>,
 }
 
 impl ServiceFactory<ServiceRequest> for AppRoutingFactory {
@@ -272,6 +276,8 @@ impl ServiceFactory<ServiceRequest> for AppRoutingFactory {
         // construct default service factory future
         let default_fut = self.default.new_service(());
 
+        let error_renderer = self.error_renderer.clone();
+
         Box::pin(async move {
             let default = default_fut.await?;
 
@@ -287,7 +293,11 @@ impl ServiceFactory<ServiceRequest> for AppRoutingFactory {
                 })
                 .finish();
 
-            Ok(AppRouting { router, default })
+            Ok(AppRouting {
+                router,
+                default,
+                error_renderer,
+            })
         })
     }
 }
@@ -296,6 +306,7 @@ impl ServiceFactory<ServiceRequest> for AppRoutingFactory {
 pub struct AppRouting {
     router: Router<BoxedHttpService, Vec<Box<dyn Guard>>>,
     default: BoxedHttpService,
+    error_renderer: Option<Rc<dyn ErrorRenderer>>,
 }
 
 impl Service<ServiceRequest> for AppRouting {
@@ -311,10 +322,15 @@ impl Service<ServiceRequest> for AppRouting {
             guards.iter().all(|guard| guard.check(&guard_ctx))
         });
 
-        if let Some((srv, _info)) = res {
+        let fut = if let Some((srv, _info)) = res {
             srv.call(req)
         } else {
             self.default.call(req)
+        };
+
+        match self.error_renderer {
+            Some(ref renderer) => render_errors(Rc::clone(renderer), fut),
+            None => fut,
         }
     }
 }
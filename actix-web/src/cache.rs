@@ -0,0 +1,388 @@
+use std::{
+    any::type_name,
+    borrow::Borrow,
+    collections::HashMap,
+    fmt,
+    future::Future,
+    hash::Hash,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, SystemTime},
+};
+
+use actix_utils::future::{ready, Ready};
+
+use crate::{clock, dev::Payload, error, web::Clock, FromRequest, HttpRequest};
+
+const SHARDS: usize = 16;
+
+/// Sharded in-memory cache with expiring entries, usable as application data and extractor.
+///
+/// Entries expire a fixed time to live (TTL) after they were inserted. Each cache holds up to a
+/// maximum number of entries; when it is full, inserting evicts expired entries and then the
+/// entries closest to expiry. Entries are spread over 16 independently locked shards so that
+/// concurrent requests rarely contend.
+///
+/// Clones share the same entries, so a cache created outside of the `HttpServer` factory closure
+/// and registered with [`App::app_data()`] is shared by all workers. Values are cloned out of the
+/// cache, so wrap large values in an `Arc`.
+///
+/// Time is read from a [`Clock`], which can be frozen to test expiry deterministically.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{get, web, App, Error};
+///
+/// # async fn fetch_rate(_currency: &str) -> Result<f64, Error> { Ok(1.0) }
+/// #[get("/rates/{currency}")]
+/// async fn rate(
+///     currency: web::Path<String>,
+///     cache: web::Cache<String, f64>,
+/// ) -> Result<String, Error> {
+///     let currency = currency.into_inner();
+///
+///     let rate = cache
+///         .try_get_or_insert_with(currency.clone(), || async move { fetch_rate(&currency).await })
+///         .await?;
+///
+///     Ok(rate.to_string())
+/// }
+///
+/// let cache = web::Cache::<String, f64>::new(Duration::from_secs(60));
+///
+/// let app = App::new().app_data(cache.clone()).service(rate);
+/// ```
+///
+/// [`App::app_data()`]: crate::App::app_data()
+pub struct Cache<K, V> {
+    shards: Arc<[Mutex<Shard<K, V>>]>,
+    hasher: ahash::RandomState,
+    ttl: Duration,
+    max_entries_per_shard: usize,
+    clock: Clock,
+}
+
+struct Shard<K, V> {
+    entries: HashMap<K, Entry<V>, ahash::RandomState>,
+}
+
+struct Entry<V> {
+    value: V,
+    expires_at: SystemTime,
+}
+
+impl<K, V> Cache<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    /// Constructs a cache whose entries expire `ttl` after they were inserted.
+    ///
+    /// The cache holds up to 10,000 entries by default.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            shards: (0..SHARDS)
+                .map(|_| {
+                    Mutex::new(Shard {
+                        entries: HashMap::default(),
+                    })
+                })
+                .collect(),
+            hasher: ahash::RandomState::new(),
+            ttl,
+            max_entries_per_shard: 10_000 / SHARDS,
+            clock: Clock::system(),
+        }
+    }
+
+    /// Sets the maximum number of entries held by the cache.
+    ///
+    /// The limit is enforced per shard, so the cache may start evicting entries somewhat before
+    /// it holds `max_entries` of them.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries_per_shard = max_entries.div_ceil(SHARDS);
+        self
+    }
+
+    /// Sets the clock that entry expiry is checked against.
+    pub fn clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the value for `key`, if it is cached and has not expired.
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let now = self.clock.now();
+
+        self.shard(key)
+            .entries
+            .get(key)
+            .filter(|entry| entry.expires_at > now)
+            .map(|entry| entry.value.clone())
+    }
+
+    /// Caches `value` for `key`, replacing any previous value.
+    pub fn insert(&self, key: K, value: V) {
+        let now = self.clock.now();
+        let mut shard = self.shard(&key);
+
+        if shard.entries.len() >= self.max_entries_per_shard && !shard.entries.contains_key(&key) {
+            shard.evict(now, self.max_entries_per_shard);
+        }
+
+        shard.entries.insert(
+            key,
+            Entry {
+                value,
+                expires_at: clock::expires_at(now, self.ttl),
+            },
+        );
+    }
+
+    /// Removes the value for `key` and returns it, if it had not expired.
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let now = self.clock.now();
+
+        self.shard(key)
+            .entries
+            .remove(key)
+            .filter(|entry| entry.expires_at > now)
+            .map(|entry| entry.value)
+    }
+
+    /// Removes all entries.
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            shard.lock().unwrap().entries.clear();
+        }
+    }
+
+    /// Returns the value for `key`, computing and caching it with `f` if it is not cached.
+    ///
+    /// No lock is held while `f` runs, so concurrent requests that miss the same key may each
+    /// compute the value; the last one to finish is cached.
+    pub async fn get_or_insert_with<F, Fut>(&self, key: K, f: F) -> V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+
+        let value = f().await;
+        self.insert(key, value.clone());
+        value
+    }
+
+    /// Returns the value for `key`, computing and caching it with `f` if it is not cached.
+    ///
+    /// Errors returned by `f` are passed on and not cached. See
+    /// [`get_or_insert_with()`](Self::get_or_insert_with()) for how concurrent misses are
+    /// handled.
+    pub async fn try_get_or_insert_with<F, Fut, E>(&self, key: K, f: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        if let Some(value) = self.get(&key) {
+            return Ok(value);
+        }
+
+        let value = f().await?;
+        self.insert(key, value.clone());
+        Ok(value)
+    }
+
+    fn shard<Q>(&self, key: &Q) -> MutexGuard<'_, Shard<K, V>>
+    where
+        Q: Hash + ?Sized,
+    {
+        let idx = self.hasher.hash_one(key) as usize % SHARDS;
+        self.shards[idx].lock().unwrap()
+    }
+}
+
+impl<K, V> Shard<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Makes room for a new entry by removing expired entries, then the ones closest to expiry.
+    fn evict(&mut self, now: SystemTime, max_entries: usize) {
+        self.entries.retain(|_, entry| entry.expires_at > now);
+
+        while self.entries.len() >= max_entries {
+            let Some(expires_at) = self.entries.values().map(|entry| entry.expires_at).min() else {
+                break;
+            };
+
+            self.entries
+                .retain(|_, entry| entry.expires_at > expires_at);
+        }
+    }
+}
+
+impl<K, V> Clone for Cache<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            shards: Arc::clone(&self.shards),
+            hasher: self.hasher.clone(),
+            ttl: self.ttl,
+            max_entries_per_shard: self.max_entries_per_shard,
+            clock: self.clock.clone(),
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for Cache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cache")
+            .field("ttl", &self.ttl)
+            .field("max_entries", &(self.max_entries_per_shard * SHARDS))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<K: 'static, V: 'static> FromRequest for Cache<K, V> {
+    type Error = crate::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(req.app_data::<Self>().cloned().ok_or_else(|| {
+            log::debug!(
+                "Failed to extract `Cache<{}, {}>` for `{}` handler. For the Cache extractor to \
+                work correctly, pass the cache to `App::app_data()`. Ensure that types align in \
+                both the set and retrieve calls.",
+                type_name::<K>(),
+                type_name::<V>(),
+                req.match_name().unwrap_or(req.path())
+            );
+
+            error::ErrorInternalServerError(
+                "Requested application data is not configured correctly. \
+                View/enable debug logs for more details.",
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{call_service, init_service, TestRequest},
+        web, App,
+    };
+
+    #[test]
+    fn expiry() {
+        let clock = Clock::frozen(SystemTime::UNIX_EPOCH);
+        let cache = Cache::new(Duration::from_secs(10)).clock(clock.clone());
+
+        cache.insert("a", 1);
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.clone().get("a"), Some(1));
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(cache.get("a"), None);
+
+        cache.insert("a", 2);
+        cache.insert("b", 3);
+        assert_eq!(cache.remove("a"), Some(2));
+        assert_eq!(cache.get("a"), None);
+
+        cache.clear();
+        assert_eq!(cache.get("b"), None);
+    }
+
+    #[test]
+    fn eviction() {
+        let clock = Clock::frozen(SystemTime::UNIX_EPOCH);
+        let cache = Cache::new(Duration::from_secs(10))
+            .max_entries(SHARDS)
+            .clock(clock.clone());
+
+        for key in 0..100 {
+            cache.insert(key, key);
+            clock.advance(Duration::from_millis(1));
+        }
+
+        // each shard holds a single entry, the one inserted last
+        let cached = (0..100).filter(|key| cache.get(key).is_some()).count();
+        assert!(cached <= SHARDS);
+        assert_eq!(cache.get(&99), Some(99));
+    }
+
+    #[test]
+    fn unbounded_ttl() {
+        let clock = Clock::frozen(SystemTime::now());
+        let cache = Cache::new(Duration::MAX).clock(clock.clone());
+
+        cache.insert("a", 1);
+
+        clock.advance(Duration::from_secs(365 * 24 * 60 * 60));
+        assert_eq!(cache.get("a"), Some(1));
+    }
+
+    #[actix_rt::test]
+    async fn get_or_insert() {
+        let cache = Cache::new(Duration::from_secs(10));
+
+        assert_eq!(cache.get_or_insert_with("a", || async { 1 }).await, 1);
+        assert_eq!(cache.get_or_insert_with("a", || async { 2 }).await, 1);
+
+        let res = cache
+            .try_get_or_insert_with("b", || async { Err::<u32, _>("failed") })
+            .await;
+        assert_eq!(res, Err("failed"));
+        assert_eq!(cache.get("b"), None);
+
+        let res = cache
+            .try_get_or_insert_with("b", || async { Ok::<_, Infallible>(3) })
+            .await;
+        assert_eq!(res, Ok(3));
+        assert_eq!(cache.get("b"), Some(3));
+    }
+
+    #[actix_rt::test]
+    async fn extract() {
+        let cache = Cache::<&'static str, u32>::new(Duration::from_secs(10));
+        cache.insert("hits", 41);
+
+        let srv = init_service(App::new().app_data(cache.clone()).route(
+            "/",
+            web::get().to(|cache: Cache<&'static str, u32>| async move {
+                let hits = cache.get("hits").unwrap_or_default() + 1;
+                cache.insert("hits", hits);
+                hits.to_string()
+            }),
+        ))
+        .await;
+
+        let res = call_service(&srv, TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(cache.get("hits"), Some(42));
+
+        let srv = init_service(App::new().route(
+            "/",
+            web::get().to(|_: Cache<&'static str, u32>| async { "" }),
+        ))
+        .await;
+
+        let res = call_service(&srv, TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
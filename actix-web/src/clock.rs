@@ -0,0 +1,204 @@
+use std::{
+    convert::Infallible,
+    fmt,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use actix_utils::future::{ok, Ready};
+
+use crate::{dev::Payload, FromRequest, HttpRequest};
+
+/// Application clock extractor.
+///
+/// Handlers that compare expirations or generate timestamps should read the time from a `Clock`
+/// rather than calling [`SystemTime::now()`] directly so that they can be tested deterministically.
+///
+/// If no `Clock` has been registered as app data, the extractor falls back to the system clock.
+/// Registering a [frozen](Self::frozen) clock, either in the [`App`](crate::App) passed to
+/// [`init_service`](crate::test::init_service) or using [`TestRequest::app_data`], causes
+/// [`now()`](Self::now) to return a fixed time that only moves when [advanced](Self::advance) or
+/// when [`sleep()`](Self::sleep) is awaited.
+///
+/// Clones of a frozen clock share the same time.
+///
+/// # Examples
+/// ```
+/// use std::time::{Duration, SystemTime};
+///
+/// use actix_web::{get, test, web, App};
+///
+/// #[get("/expired")]
+/// async fn expired(clock: web::Clock) -> String {
+///     let expiry = SystemTime::UNIX_EPOCH + Duration::from_secs(60);
+///     (clock.now() > expiry).to_string()
+/// }
+///
+/// # actix_web::rt::System::new().block_on(async {
+/// let clock = web::Clock::frozen(SystemTime::UNIX_EPOCH);
+/// let app = test::init_service(App::new().app_data(clock.clone()).service(expired)).await;
+///
+/// let req = test::TestRequest::with_uri("/expired").to_request();
+/// assert_eq!(test::call_and_read_body(&app, req).await, "false");
+///
+/// clock.advance(Duration::from_secs(120));
+///
+/// let req = test::TestRequest::with_uri("/expired").to_request();
+/// assert_eq!(test::call_and_read_body(&app, req).await, "true");
+/// # });
+/// ```
+///
+/// [`TestRequest::app_data`]: crate::test::TestRequest::app_data
+#[derive(Clone, Default)]
+pub struct Clock {
+    frozen: Option<Arc<Mutex<SystemTime>>>,
+}
+
+impl Clock {
+    /// Constructs a clock that reads the system time.
+    pub fn system() -> Self {
+        Self { frozen: None }
+    }
+
+    /// Constructs a clock frozen at the given time.
+    pub fn frozen(at: SystemTime) -> Self {
+        Self {
+            frozen: Some(Arc::new(Mutex::new(at))),
+        }
+    }
+
+    /// Returns true if this clock is frozen.
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.is_some()
+    }
+
+    /// Returns the current time according to this clock.
+    pub fn now(&self) -> SystemTime {
+        match &self.frozen {
+            Some(time) => *time.lock().unwrap(),
+            None => SystemTime::now(),
+        }
+    }
+
+    /// Waits until `dur` has elapsed according to this clock.
+    ///
+    /// A frozen clock is advanced by `dur` and the returned future completes immediately.
+    pub fn sleep(&self, dur: Duration) -> impl Future<Output = ()> {
+        let frozen = self.frozen.clone();
+
+        async move {
+            match frozen {
+                Some(time) => *time.lock().unwrap() += dur,
+                None => actix_rt::time::sleep(dur).await,
+            }
+        }
+    }
+
+    /// Advances a frozen clock by `dur`.
+    ///
+    /// Has no effect on the system clock.
+    pub fn advance(&self, dur: Duration) {
+        if let Some(time) = &self.frozen {
+            *time.lock().unwrap() += dur;
+        }
+    }
+
+    /// Sets the time of a frozen clock.
+    ///
+    /// Has no effect on the system clock.
+    pub fn set(&self, at: SystemTime) {
+        if let Some(time) = &self.frozen {
+            *time.lock().unwrap() = at;
+        }
+    }
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.frozen {
+            Some(time) => f
+                .debug_tuple("Clock::Frozen")
+                .field(&*time.lock().unwrap())
+                .finish(),
+            None => f.write_str("Clock::System"),
+        }
+    }
+}
+
+impl FromRequest for Clock {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ok(req.app_data::<Self>().cloned().unwrap_or_default())
+    }
+}
+
+/// Returns the time `ttl` after `now`, saturating at the latest time the platform can represent.
+pub(crate) fn expires_at(now: SystemTime, mut ttl: Duration) -> SystemTime {
+    loop {
+        match now.checked_add(ttl) {
+            Some(time) => return time,
+            None => ttl /= 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestRequest;
+
+    #[test]
+    fn frozen_clock_shares_time() {
+        let clock = Clock::frozen(SystemTime::UNIX_EPOCH);
+        let clone = clock.clone();
+
+        assert!(clock.is_frozen());
+        assert_eq!(clone.now(), SystemTime::UNIX_EPOCH);
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(clone.now(), SystemTime::UNIX_EPOCH + Duration::from_secs(5));
+
+        clone.set(SystemTime::UNIX_EPOCH);
+        assert_eq!(clock.now(), SystemTime::UNIX_EPOCH);
+    }
+
+    #[actix_rt::test]
+    async fn frozen_clock_sleep_advances() {
+        let clock = Clock::frozen(SystemTime::UNIX_EPOCH);
+        clock.sleep(Duration::from_secs(3600)).await;
+        assert_eq!(
+            clock.now(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(3600)
+        );
+    }
+
+    #[actix_rt::test]
+    async fn system_clock_ignores_manual_changes() {
+        let clock = Clock::system();
+        assert!(!clock.is_frozen());
+
+        clock.set(SystemTime::UNIX_EPOCH);
+        assert!(clock.now() > SystemTime::UNIX_EPOCH);
+    }
+
+    #[actix_rt::test]
+    async fn extract_from_app_data() {
+        let (req, mut pl) = TestRequest::default().to_http_parts();
+        let clock = Clock::from_request(&req, &mut pl).await.unwrap();
+        assert!(!clock.is_frozen());
+
+        let frozen = Clock::frozen(SystemTime::UNIX_EPOCH);
+        let (req, mut pl) = TestRequest::default()
+            .app_data(frozen.clone())
+            .to_http_parts();
+        let clock = Clock::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(clock.now(), SystemTime::UNIX_EPOCH);
+
+        frozen.advance(Duration::from_secs(1));
+        assert_eq!(clock.now(), SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+    }
+}
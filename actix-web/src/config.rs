@@ -1,4 +1,4 @@
-use std::{net::SocketAddr, rc::Rc};
+use std::{cell::RefCell, net::SocketAddr, rc::Rc};
 
 use actix_service::{boxed, IntoServiceFactory, ServiceFactory, ServiceFactoryExt as _};
 
@@ -7,6 +7,8 @@ use crate::{
     dev::{Extensions, ResourceDef},
     error::Error,
     guard::Guard,
+    http::Method,
+    openapi::{Operation, Operations},
     resource::Resource,
     rmap::ResourceMap,
     route::Route,
@@ -30,6 +32,8 @@ pub struct AppService {
         Option<Guards>,
         Option<Rc<ResourceMap>>,
     )>,
+    operations: Operations,
+    path_prefix: String,
 }
 
 impl AppService {
@@ -40,6 +44,8 @@ impl AppService {
             default,
             root: true,
             services: Vec::new(),
+            operations: Rc::new(RefCell::new(Vec::new())),
+            path_prefix: String::new(),
         }
     }
 
@@ -71,9 +77,27 @@ impl AppService {
             default: Rc::clone(&self.default),
             services: Vec::new(),
             root: false,
+            operations: Rc::clone(&self.operations),
+            path_prefix: self.path_prefix.clone(),
         }
     }
 
+    /// Appends a scope's path to the prefix of operations registered through this config.
+    pub(crate) fn push_path_prefix(&mut self, prefix: &str) {
+        self.path_prefix.push_str(prefix.trim_end_matches('/'));
+    }
+
+    /// Records API operation metadata for the given path, relative to the current scope.
+    pub(crate) fn register_operation(&mut self, path: &str, method: Method, operation: Operation) {
+        let path = format!("{}{}", self.path_prefix, path);
+        self.operations.borrow_mut().push((path, method, operation));
+    }
+
+    /// Returns API operation metadata registered across the whole application.
+    pub(crate) fn operations(&self) -> Operations {
+        Rc::clone(&self.operations)
+    }
+
     /// Returns reference to configuration.
     pub fn config(&self) -> &AppConfig {
         &self.config
@@ -172,6 +196,62 @@ impl Default for AppConfig {
     }
 }
 
+/// A self-contained part of an app's configuration, such as a feature area or the routes provided
+/// by a third-party crate.
+///
+/// Modules contribute services, app data, external resources, and a default service through the
+/// [`ServiceConfig`] they are given. They are registered using [`App::module()`],
+/// [`Scope::module()`], or [`ServiceConfig::module()`], so a main binary only needs to know about
+/// the modules it uses, not every resource they define. Middleware that only applies to a module's
+/// services can be added by registering them inside a [`Scope`] wrapped with that middleware.
+///
+/// Unlike [`App::configure()`], modules are values that can carry their own settings and can be
+/// stored in a collection; boxed modules and `Vec`s of modules are modules too.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App, HttpResponse};
+///
+/// struct Health {
+///     path: &'static str,
+/// }
+///
+/// impl web::AppModule for Health {
+///     fn register(&self, cfg: &mut web::ServiceConfig) {
+///         cfg.route(self.path, web::get().to(HttpResponse::Ok));
+///     }
+/// }
+///
+/// let modules: Vec<Box<dyn web::AppModule>> = vec![Box::new(Health { path: "/health" })];
+///
+/// App::new()
+///     .module(Health { path: "/healthz" })
+///     .module(modules);
+/// ```
+///
+/// [`App::module()`]: crate::App::module()
+/// [`App::configure()`]: crate::App::configure()
+/// [`Scope`]: crate::Scope
+/// [`Scope::module()`]: crate::Scope::module()
+pub trait AppModule {
+    /// Registers this module's configuration.
+    fn register(&self, cfg: &mut ServiceConfig);
+}
+
+impl<M: AppModule + ?Sized> AppModule for Box<M> {
+    fn register(&self, cfg: &mut ServiceConfig) {
+        (**self).register(cfg);
+    }
+}
+
+impl<M: AppModule> AppModule for Vec<M> {
+    fn register(&self, cfg: &mut ServiceConfig) {
+        for module in self {
+            module.register(cfg);
+        }
+    }
+}
+
 /// Enables parts of app configuration to be declared separately from the app itself. Helpful for
 /// modularizing large applications.
 ///
@@ -256,6 +336,14 @@ impl ServiceConfig {
         self
     }
 
+    /// Registers a [module](AppModule) as part of the configuration.
+    ///
+    /// Counterpart to [`App::module()`](crate::App::module) that allows for easy nesting.
+    pub fn module<M: AppModule>(&mut self, module: M) -> &mut Self {
+        module.register(self);
+        self
+    }
+
     /// Configure route for a specific path.
     ///
     /// Counterpart to [`App::route()`](crate::App::route).
@@ -386,6 +474,51 @@ mod tests {
         assert_eq!(body, Bytes::from_static(b"scoped four oh four"));
     }
 
+    #[actix_rt::test]
+    async fn registers_modules() {
+        struct Greeting(&'static str);
+
+        impl AppModule for Greeting {
+            fn register(&self, cfg: &mut ServiceConfig) {
+                let greeting = self.0;
+                cfg.app_data(42u32)
+                    .route(greeting, web::get().to(move || async move { greeting }));
+            }
+        }
+
+        let modules: Vec<Box<dyn AppModule>> =
+            vec![Box::new(Greeting("/hello")), Box::new(Greeting("/hi"))];
+
+        let srv = init_service(
+            App::new()
+                .module(modules)
+                .service(web::scope("/scoped").module(Greeting("/hey")))
+                .configure(|cfg| {
+                    cfg.module(Greeting("/yo"));
+                })
+                .route(
+                    "/data",
+                    web::get().to(|req: HttpRequest| async move {
+                        req.app_data::<u32>().unwrap().to_string()
+                    }),
+                ),
+        )
+        .await;
+
+        for (path, body) in [
+            ("/hello", "/hello"),
+            ("/hi", "/hi"),
+            ("/scoped/hey", "/hey"),
+            ("/yo", "/yo"),
+            ("/data", "42"),
+        ] {
+            let req = TestRequest::with_uri(path).to_request();
+            let resp = call_service(&srv, req).await;
+            assert_eq!(resp.status(), StatusCode::OK, "{path}");
+            assert_body_eq!(resp, body.as_bytes());
+        }
+    }
+
     #[actix_rt::test]
     async fn test_service() {
         let srv = init_service(App::new().configure(|cfg| {
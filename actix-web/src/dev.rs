@@ -9,7 +9,10 @@
 
 #[cfg(feature = "__compress")]
 pub use actix_http::encoding::Decoder as Decompress;
-pub use actix_http::{Extensions, Payload, RequestHead, Response, ResponseHead};
+pub use actix_http::{
+    Extensions, FinishStatus, HeaderLimits, MinDataRate, OnFinish, Payload, RequestHead, Response,
+    ResponseHead,
+};
 use actix_router::Patterns;
 pub use actix_router::{Path, ResourceDef, ResourcePath, Url};
 pub use actix_server::{Server, ServerHandle};
@@ -19,12 +22,16 @@ pub use actix_service::{
 
 #[doc(hidden)]
 pub use crate::handler::Handler;
+#[cfg(feature = "xml")]
+pub use crate::types::XmlBody;
 pub use crate::{
     config::{AppConfig, AppService},
-    info::{ConnectionInfo, PeerAddr},
+    info::{ConnectionInfo, PeerAddr, TrustedProxies},
+    request_context::ContextScope,
     rmap::ResourceMap,
     service::{HttpServiceFactory, ServiceRequest, ServiceResponse, WebService},
     types::{JsonBody, Readlines, UrlEncoded},
+    worker_dispatch::WorkerDispatch,
 };
 
 pub(crate) fn ensure_leading_slash(mut patterns: Patterns) -> Patterns {
@@ -27,6 +27,20 @@ impl Error {
         <dyn ResponseError>::downcast_ref(self.cause.as_ref())
     }
 
+    /// Similar to `as_error` but takes ownership of the underlying error.
+    ///
+    /// Returns the error unchanged if it does not wrap a `T`.
+    pub fn downcast<T: ResponseError + 'static>(self) -> Result<T, Self> {
+        if self.as_error::<T>().is_none() {
+            return Err(self);
+        }
+
+        let cause = Box::into_raw(self.cause) as *mut T;
+
+        // SAFETY: the check above guarantees that the boxed error is a `T`
+        Ok(*unsafe { Box::from_raw(cause) })
+    }
+
     /// Shortcut for creating an `HttpResponse`.
     pub fn error_response(&self) -> HttpResponse {
         self.cause.error_response()
@@ -47,7 +61,7 @@ impl fmt::Debug for Error {
 
 impl StdError for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        None
+        self.cause.as_std_error()
     }
 }
 
@@ -60,6 +74,17 @@ impl<T: ResponseError + 'static> From<T> for Error {
     }
 }
 
+/// Converts an `anyhow` error into an error that results in a `500 Internal Server Error`.
+///
+/// The error is converted through [`actix_http::Error`], keeping the full chain of causes in its
+/// `Display` and `Debug` output.
+#[cfg(feature = "anyhow")]
+impl From<anyhow::Error> for Error {
+    fn from(err: anyhow::Error) -> Error {
+        actix_http::Error::from(err).into()
+    }
+}
+
 impl From<Box<dyn ResponseError>> for Error {
     fn from(value: Box<dyn ResponseError>) -> Self {
         Error { cause: value }
@@ -71,3 +96,48 @@ impl From<Error> for Response<BoxBody> {
         err.error_response().into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+    use crate::error::{JsonPayloadError, PayloadError};
+
+    #[test]
+    fn source_chain() {
+        let err = Error::from(JsonPayloadError::Payload(PayloadError::Overflow));
+
+        let source = err.source().unwrap();
+        assert!(source.is::<JsonPayloadError>());
+        assert!(source.source().unwrap().is::<PayloadError>());
+
+        let err = crate::error::ErrorBadRequest("bad");
+        assert!(err.source().is_none());
+    }
+
+    #[test]
+    fn downcast() {
+        let err = Error::from(io::Error::new(io::ErrorKind::NotFound, "gone"));
+
+        let err = err.downcast::<JsonPayloadError>().unwrap_err();
+        let err = err.downcast::<io::Error>().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[cfg(feature = "anyhow")]
+    #[test]
+    fn from_anyhow() {
+        let err = anyhow::anyhow!("connection refused").context("failed to load user");
+        let err = Error::from(err);
+
+        assert_eq!(
+            err.to_string(),
+            "error processing HTTP: failed to load user: connection refused"
+        );
+        assert_eq!(
+            err.error_response().status(),
+            crate::http::StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
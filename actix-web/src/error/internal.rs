@@ -1,13 +1,10 @@
-use std::{cell::RefCell, fmt, io::Write as _};
+use std::{cell::RefCell, fmt};
 
-use actix_http::{
-    body::BoxBody,
-    header::{self, TryIntoHeaderValue as _},
-    StatusCode,
-};
-use bytes::{BufMut as _, BytesMut};
+use actix_http::{body::BoxBody, StatusCode};
 
-use crate::{Error, HttpRequest, HttpResponse, Responder, ResponseError};
+use crate::{
+    error::compose_error_response, Error, HttpRequest, HttpResponse, Responder, ResponseError,
+};
 
 /// Wraps errors to alter the generated response status code.
 ///
@@ -15,6 +12,14 @@ use crate::{Error, HttpRequest, HttpResponse, Responder, ResponseError};
 /// response with the 400 Bad Request status code instead of the usual status code generated by
 /// an `io::Error`.
 ///
+/// Any error type implementing `Debug` and `Display` can be wrapped, including error reports that
+/// do not implement [`ResponseError`] themselves, such as `anyhow::Error` or `eyre::Report`. The
+/// wrapper's `Debug` and `Display` output is that of the wrapped error. For such reports, only the
+/// `Debug` output, which the [`Logger`] middleware uses for errors, includes the full chain of
+/// causes; the `Display` output, which is used in the response body, is the outermost message.
+///
+/// [`Logger`]: crate::middleware::Logger
+///
 /// # Examples
 /// ```
 /// # use std::io;
@@ -83,16 +88,7 @@ where
 
     fn error_response(&self) -> HttpResponse {
         match self.status {
-            InternalErrorType::Status(status) => {
-                let mut res = HttpResponse::new(status);
-                let mut buf = BytesMut::new().writer();
-                let _ = write!(buf, "{}", self);
-
-                let mime = mime::TEXT_PLAIN_UTF_8.try_into_value().unwrap();
-                res.headers_mut().insert(header::CONTENT_TYPE, mime);
-
-                res.set_body(BoxBody::new(buf.into_inner()))
-            }
+            InternalErrorType::Status(_) => compose_error_response(self),
 
             InternalErrorType::Response(ref resp) => {
                 if let Some(resp) = resp.borrow_mut().take() {
@@ -74,6 +74,16 @@ macro_rules! downcast_dyn {
     };
 }
 
+// Implement `ResponseError::as_std_error` for types that implement `std::error::Error`
+macro_rules! as_std_error {
+    () => {
+        fn as_std_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(self)
+        }
+    };
+}
+
+pub(crate) use as_std_error;
 pub(crate) use downcast_dyn;
 pub(crate) use downcast_get_type_id;
 
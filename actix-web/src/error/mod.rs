@@ -1,5 +1,7 @@
 //! Error and Result module
 
+use std::time::Duration;
+
 // This is meant to be a glob import of the whole error module except for `Error`. Rustdoc can't yet
 // correctly resolve the conflicting `Error` type defined in this module, so these re-exports are
 // expanded manually.
@@ -17,10 +19,20 @@ use crate::http::StatusCode;
 mod error;
 mod internal;
 mod macros;
+mod renderer;
 mod response_error;
 
-pub(crate) use self::macros::{downcast_dyn, downcast_get_type_id};
-pub use self::{error::Error, internal::*, response_error::ResponseError};
+pub use self::{
+    error::Error,
+    internal::*,
+    renderer::{ErrorRenderer, ProblemDetails},
+    response_error::{ErrorBodyFormat, ResponseError},
+};
+pub(crate) use self::{
+    macros::{as_std_error, downcast_dyn, downcast_get_type_id},
+    renderer::render_errors,
+    response_error::compose_error_response,
+};
 
 /// A convenience [`Result`](std::result::Result) for Actix Web operations.
 ///
@@ -33,7 +45,9 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 #[non_exhaustive]
 pub struct BlockingError;
 
-impl ResponseError for crate::error::BlockingError {}
+impl ResponseError for crate::error::BlockingError {
+    as_std_error!();
+}
 
 /// Errors which can occur when attempting to generate resource uri.
 #[derive(Debug, PartialEq, Eq, Display, Error, From)]
@@ -52,7 +66,9 @@ pub enum UrlGenerationError {
     ParseError(UrlParseError),
 }
 
-impl ResponseError for UrlGenerationError {}
+impl ResponseError for UrlGenerationError {
+    as_std_error!();
+}
 
 /// A set of errors that can occur during parsing urlencoded payloads
 #[derive(Debug, Display, Error, From)]
@@ -63,6 +79,9 @@ pub enum UrlencodedError {
     Chunked,
 
     /// Payload size is larger than allowed. (default limit: 256kB).
+    ///
+    /// For payloads without a `Content-Length`, `size` is the number of bytes received when the
+    /// limit was exceeded rather than the size of the whole payload.
     #[display(
         "URL encoded payload is larger ({} bytes) than allowed (limit: {} bytes).",
         size,
@@ -96,6 +115,8 @@ pub enum UrlencodedError {
 }
 
 impl ResponseError for UrlencodedError {
+    as_std_error!();
+
     fn status_code(&self) -> StatusCode {
         match self {
             Self::Overflow { .. } => StatusCode::PAYLOAD_TOO_LARGE,
@@ -147,6 +168,8 @@ impl From<PayloadError> for JsonPayloadError {
 }
 
 impl ResponseError for JsonPayloadError {
+    as_std_error!();
+
     fn status_code(&self) -> StatusCode {
         match self {
             Self::OverflowKnownLength {
@@ -161,6 +184,79 @@ impl ResponseError for JsonPayloadError {
     }
 }
 
+/// Error serializing or deserializing XML.
+#[cfg(feature = "xml")]
+#[derive(Debug, Display, Error)]
+#[display("{}", msg)]
+pub struct XmlError {
+    #[error(not(source))]
+    msg: String,
+}
+
+#[cfg(feature = "xml")]
+impl XmlError {
+    pub(crate) fn new(err: impl std::fmt::Display) -> Self {
+        Self {
+            msg: err.to_string(),
+        }
+    }
+}
+
+/// A set of errors that can occur during parsing XML payloads.
+#[cfg(feature = "xml")]
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum XmlPayloadError {
+    /// Payload size is larger than allowed. (default limit: 256kB).
+    ///
+    /// For payloads without a `Content-Length`, `size` is the number of bytes received when the
+    /// limit was exceeded rather than the size of the whole payload.
+    #[display(
+        "XML payload ({} bytes) is larger than allowed (limit: {} bytes).",
+        size,
+        limit
+    )]
+    Overflow { size: usize, limit: usize },
+
+    /// Content type error.
+    #[display("Content type error")]
+    ContentType,
+
+    /// Deserialize error.
+    #[display("XML deserialize error: {}", _0)]
+    Deserialize(XmlError),
+
+    /// Serialize error.
+    #[display("XML serialize error: {}", _0)]
+    Serialize(XmlError),
+
+    /// Payload error.
+    #[display("Error that occur during reading payload: {}", _0)]
+    Payload(PayloadError),
+}
+
+#[cfg(feature = "xml")]
+impl From<PayloadError> for XmlPayloadError {
+    fn from(err: PayloadError) -> Self {
+        Self::Payload(err)
+    }
+}
+
+#[cfg(feature = "xml")]
+impl ResponseError for XmlPayloadError {
+    as_std_error!();
+
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Overflow { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::Serialize(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Payload(err) => err.status_code(),
+            Self::Deserialize(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
 /// A set of errors that can occur during parsing request paths
 #[derive(Debug, Display, Error)]
 #[non_exhaustive]
@@ -172,6 +268,8 @@ pub enum PathError {
 
 /// Return `BadRequest` for `PathError`
 impl ResponseError for PathError {
+    as_std_error!();
+
     fn status_code(&self) -> StatusCode {
         StatusCode::BAD_REQUEST
     }
@@ -187,6 +285,8 @@ pub enum QueryPayloadError {
 }
 
 impl ResponseError for QueryPayloadError {
+    as_std_error!();
+
     fn status_code(&self) -> StatusCode {
         StatusCode::BAD_REQUEST
     }
@@ -214,6 +314,8 @@ pub enum ReadlinesError {
 }
 
 impl ResponseError for ReadlinesError {
+    as_std_error!();
+
     fn status_code(&self) -> StatusCode {
         match *self {
             ReadlinesError::LimitOverflow => StatusCode::PAYLOAD_TOO_LARGE,
@@ -222,6 +324,49 @@ impl ResponseError for ReadlinesError {
     }
 }
 
+/// Error returned when a stage of handling a request did not complete in time.
+///
+/// Each variant carries the timeout that was exceeded. The status code depends on which party was
+/// too slow: `408 Request Timeout` when the client did not send the payload in time and
+/// `504 Gateway Timeout` when the server or an upstream service did not respond in time.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum TimeoutError {
+    /// Handler did not produce a response in time.
+    #[display("Handler did not respond within {:?}", _0)]
+    Handler(#[error(not(source))] Duration),
+
+    /// Request payload was not read in time.
+    #[display("Request payload was not read within {:?}", _0)]
+    PayloadRead(#[error(not(source))] Duration),
+
+    /// Upstream service called by a client did not respond in time.
+    #[display("Client did not receive a response within {:?}", _0)]
+    ClientResponse(#[error(not(source))] Duration),
+}
+
+impl TimeoutError {
+    /// Returns the timeout that was exceeded.
+    pub fn timeout(&self) -> Duration {
+        match *self {
+            Self::Handler(timeout) | Self::PayloadRead(timeout) | Self::ClientResponse(timeout) => {
+                timeout
+            }
+        }
+    }
+}
+
+impl ResponseError for TimeoutError {
+    as_std_error!();
+
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::PayloadRead(_) => StatusCode::REQUEST_TIMEOUT,
+            Self::Handler(_) | Self::ClientResponse(_) => StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,4 +411,21 @@ mod tests {
         let resp = ReadlinesError::EncodingError.error_response();
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
+
+    #[test]
+    fn test_timeout_error() {
+        let timeout = Duration::from_secs(5);
+
+        let err = TimeoutError::Handler(timeout);
+        assert_eq!(err.status_code(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(err.to_string(), "Handler did not respond within 5s");
+        assert_eq!(err.timeout(), timeout);
+
+        let err = TimeoutError::PayloadRead(timeout);
+        assert_eq!(err.status_code(), StatusCode::REQUEST_TIMEOUT);
+        assert_eq!(err.to_string(), "Request payload was not read within 5s");
+
+        let resp = TimeoutError::ClientResponse(timeout).error_response();
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+    }
 }
@@ -0,0 +1,156 @@
+use std::rc::Rc;
+
+use futures_core::future::LocalBoxFuture;
+
+use crate::{
+    body::BoxBody,
+    http::header::{HeaderValue, CONTENT_TYPE},
+    service::ServiceResponse,
+    Error, HttpRequest, HttpResponse,
+};
+
+/// Converts errors into the responses sent to clients.
+///
+/// A renderer is set for a whole app with [`App::error_renderer()`] or for the services of a scope
+/// with [`Scope::error_renderer()`], so that different parts of an app can present errors
+/// differently. It replaces the responses that the handlers, resources, nested scopes and default
+/// service of that app or scope generate from errors, e.g. when a handler returns an `Err` or an
+/// extractor fails; these are the responses for which [`HttpResponse::error()`] is set. When
+/// scopes are nested, the innermost renderer is used.
+///
+/// Errors returned by services as `Err` before a response exists, typically by middleware, are not
+/// rendered and get the [default error response](crate::ResponseError::error_response()).
+///
+/// Rendered responses keep the original error, which middleware can still inspect through
+/// [`HttpResponse::error()`].
+///
+/// Closures taking the error and the request are renderers too.
+///
+/// # Examples
+/// ```
+/// use actix_web::{error::ProblemDetails, web, App, HttpResponse};
+///
+/// let app = App::new()
+///     .service(
+///         web::scope("/api")
+///             .error_renderer(ProblemDetails)
+///             .route("/", web::get().to(HttpResponse::Ok)),
+///     )
+///     .error_renderer(|err: &actix_web::Error, _req: &actix_web::HttpRequest| {
+///         HttpResponse::build(err.as_response_error().status_code())
+///             .body(format!("<h1>{err}</h1>"))
+///     });
+/// ```
+///
+/// [`App::error_renderer()`]: crate::App::error_renderer()
+/// [`Scope::error_renderer()`]: crate::Scope::error_renderer()
+pub trait ErrorRenderer: 'static {
+    /// Renders the response for `err`, which occurred while handling `req`.
+    fn render(&self, err: &Error, req: &HttpRequest) -> HttpResponse;
+}
+
+impl<F> ErrorRenderer for F
+where
+    F: Fn(&Error, &HttpRequest) -> HttpResponse + 'static,
+{
+    fn render(&self, err: &Error, req: &HttpRequest) -> HttpResponse {
+        (self)(err, req)
+    }
+}
+
+/// Renders errors as `application/problem+json` problem details, as specified by [RFC 7807].
+///
+/// The status code and headers are those of the default error response. The body is of the form:
+///
+/// ```json
+/// {
+///   "type": "about:blank",
+///   "title": "Bad Request",
+///   "status": 400,
+///   "detail": "<error message>",
+///   "instance": "<request path>"
+/// }
+/// ```
+///
+/// [RFC 7807]: https://datatracker.ietf.org/doc/html/rfc7807
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProblemDetails;
+
+impl ErrorRenderer for ProblemDetails {
+    fn render(&self, err: &Error, req: &HttpRequest) -> HttpResponse {
+        let res = err.error_response();
+        let status = res.status();
+
+        let body = serde_json::json!({
+            "type": "about:blank",
+            "title": status.canonical_reason().unwrap_or_default(),
+            "status": status.as_u16(),
+            "detail": err.to_string(),
+            "instance": req.path(),
+        });
+
+        let mut res = res.set_body(BoxBody::new(body.to_string()));
+        res.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/problem+json"),
+        );
+        res
+    }
+}
+
+/// Marks responses that have already been rendered by an [`ErrorRenderer`].
+struct Rendered;
+
+/// Re-renders the response of `fut` with `renderer` if it was generated from an error.
+pub(crate) fn render_errors(
+    renderer: Rc<dyn ErrorRenderer>,
+    fut: LocalBoxFuture<'static, Result<ServiceResponse, Error>>,
+) -> LocalBoxFuture<'static, Result<ServiceResponse, Error>> {
+    Box::pin(async move {
+        let res = fut.await?;
+
+        if res.response().error().is_none() || res.response().extensions().contains::<Rendered>() {
+            return Ok(res);
+        }
+
+        let (req, mut res) = res.into_parts();
+        let err = res.take_error().unwrap();
+
+        let mut res = renderer.render(&err, &req).with_error(err);
+        res.extensions_mut().insert(Rendered);
+
+        Ok(ServiceResponse::new(req, res))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::ErrorNotFound, test::TestRequest};
+
+    #[actix_rt::test]
+    async fn problem_details() {
+        let req = TestRequest::with_uri("/missing").to_http_request();
+        let res = ProblemDetails.render(&ErrorNotFound("no such item"), &req);
+
+        assert_eq!(res.status(), 404);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            "application/problem+json"
+        );
+
+        let body = actix_http::body::to_bytes(res.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "type": "about:blank",
+                "title": "Not Found",
+                "status": 404,
+                "detail": "no such item",
+                "instance": "/missing",
+            })
+        );
+    }
+}
@@ -5,6 +5,7 @@ use std::{
     error::Error as StdError,
     fmt,
     io::{self, Write as _},
+    sync::atomic::{AtomicU8, Ordering},
 };
 
 use actix_http::Response;
@@ -12,16 +13,20 @@ use bytes::BytesMut;
 
 use crate::{
     body::BoxBody,
-    error::{downcast_dyn, downcast_get_type_id},
+    error::{as_std_error, downcast_dyn, downcast_get_type_id},
     helpers,
     http::{
-        header::{self, TryIntoHeaderValue},
+        header::{self, HeaderMap, TryIntoHeaderValue},
         StatusCode,
     },
     HttpResponse,
 };
 
 /// Errors that can generate responses.
+///
+/// The default [`error_response`](Self::error_response) is composed of the
+/// [`status_code`](Self::status_code), [`headers`](Self::headers), and [`body`](Self::body)
+/// hooks, so most errors only need to implement the parts they want to customize.
 // TODO: flesh out documentation
 pub trait ResponseError: fmt::Debug + fmt::Display {
     /// Returns appropriate status code for error.
@@ -32,28 +37,146 @@ pub trait ResponseError: fmt::Debug + fmt::Display {
         StatusCode::INTERNAL_SERVER_ERROR
     }
 
+    /// Returns headers to add to the error response.
+    ///
+    /// These are inserted after the default `Content-Type` header, which they can replace when
+    /// [`body`](Self::body) is customized. No headers are added by default.
+    fn headers(&self) -> HeaderMap {
+        HeaderMap::new()
+    }
+
+    /// Returns body of the error response.
+    ///
+    /// By default, the body is `Self`'s `Display` impl, either as plain text or wrapped in a JSON
+    /// object, depending on the global [`ErrorBodyFormat`].
+    fn body(&self) -> BoxBody {
+        ErrorBodyFormat::global().render(self.status_code(), format_args!("{}", self))
+    }
+
     /// Creates full response for error.
     ///
-    /// By default, the generated response uses a 500 Internal Server Error status code, a
-    /// `Content-Type` of `text/plain`, and the body is set to `Self`'s `Display` impl.
+    /// By default, the generated response uses the status code from
+    /// [`status_code`](Self::status_code) (500 Internal Server Error unless overridden), a
+    /// `Content-Type` matching the global [`ErrorBodyFormat`], the headers from
+    /// [`headers`](Self::headers), and the body from [`body`](Self::body).
     fn error_response(&self) -> HttpResponse<BoxBody> {
-        let mut res = HttpResponse::new(self.status_code());
+        compose_error_response(self)
+    }
 
-        let mut buf = BytesMut::new();
-        let _ = write!(helpers::MutWriter(&mut buf), "{}", self);
+    /// Returns this error as a standard library error, if it is one.
+    ///
+    /// [`Error`](crate::Error) reports the returned error as its
+    /// [`source`](StdError::source), which lets error reporters walk into the wrapped error and
+    /// its own sources. Returns `None` by default; errors that implement [`std::error::Error`]
+    /// should return `Some(self)`.
+    fn as_std_error(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
 
-        let mime = mime::TEXT_PLAIN_UTF_8.try_into_value().unwrap();
-        res.headers_mut().insert(header::CONTENT_TYPE, mime);
+    downcast_get_type_id!();
+}
+
+/// Format of the error bodies generated by the default [`ResponseError::body()`].
+///
+/// The format applies to the whole process and can be changed at any time, typically once at
+/// startup.
+///
+/// # Examples
+/// ```
+/// use actix_web::error::ErrorBodyFormat;
+///
+/// // render error bodies as `{"error": "...", "status": 400}`
+/// ErrorBodyFormat::Json.set_global();
+/// assert_eq!(ErrorBodyFormat::global(), ErrorBodyFormat::Json);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum ErrorBodyFormat {
+    /// A `text/plain` body holding the error message.
+    #[default]
+    PlainText,
+
+    /// An `application/json` body of the form `{"error": "<message>", "status": <code>}`.
+    Json,
+}
 
-        res.set_body(BoxBody::new(buf))
+static ERROR_BODY_FORMAT: AtomicU8 = AtomicU8::new(0);
+
+impl ErrorBodyFormat {
+    /// Sets the format of error bodies for the whole process.
+    pub fn set_global(self) {
+        let format = match self {
+            ErrorBodyFormat::PlainText => 0,
+            ErrorBodyFormat::Json => 1,
+        };
+
+        ERROR_BODY_FORMAT.store(format, Ordering::Relaxed);
     }
 
-    downcast_get_type_id!();
+    /// Returns the current format of error bodies.
+    pub fn global() -> Self {
+        match ERROR_BODY_FORMAT.load(Ordering::Relaxed) {
+            1 => ErrorBodyFormat::Json,
+            _ => ErrorBodyFormat::PlainText,
+        }
+    }
+
+    /// Returns the `Content-Type` of bodies in this format.
+    fn mime(self) -> mime::Mime {
+        match self {
+            ErrorBodyFormat::PlainText => mime::TEXT_PLAIN_UTF_8,
+            ErrorBodyFormat::Json => mime::APPLICATION_JSON,
+        }
+    }
+
+    /// Renders an error message in this format.
+    fn render(self, status: StatusCode, msg: fmt::Arguments<'_>) -> BoxBody {
+        match self {
+            ErrorBodyFormat::PlainText => {
+                let mut buf = BytesMut::new();
+                let _ = helpers::MutWriter(&mut buf).write_fmt(msg);
+                BoxBody::new(buf)
+            }
+
+            ErrorBodyFormat::Json => {
+                let body =
+                    serde_json::json!({ "error": msg.to_string(), "status": status.as_u16() });
+                BoxBody::new(body.to_string())
+            }
+        }
+    }
+}
+
+/// Composes the default error response of `err` from its hooks.
+pub(crate) fn compose_error_response<E>(err: &E) -> HttpResponse<BoxBody>
+where
+    E: ResponseError + ?Sized,
+{
+    let mut res = HttpResponse::with_body(err.status_code(), err.body());
+
+    let mime = ErrorBodyFormat::global().mime().try_into_value().unwrap();
+    res.headers_mut().insert(header::CONTENT_TYPE, mime);
+
+    let headers = err.headers();
+
+    for name in headers.keys() {
+        res.headers_mut().remove(name);
+    }
+
+    for (name, value) in headers.iter() {
+        res.headers_mut().append(name.clone(), value.clone());
+    }
+
+    res
 }
 
 downcast_dyn!(ResponseError);
 
-impl ResponseError for Box<dyn StdError + 'static> {}
+impl ResponseError for Box<dyn StdError + 'static> {
+    fn as_std_error(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(self.as_ref())
+    }
+}
 
 impl ResponseError for Infallible {
     fn status_code(&self) -> StatusCode {
@@ -65,25 +188,37 @@ impl ResponseError for Infallible {
 }
 
 #[cfg(feature = "openssl")]
-impl ResponseError for actix_tls::accept::openssl::reexports::Error {}
+impl ResponseError for actix_tls::accept::openssl::reexports::Error {
+    as_std_error!();
+}
 
 impl ResponseError for serde::de::value::Error {
+    as_std_error!();
+
     fn status_code(&self) -> StatusCode {
         StatusCode::BAD_REQUEST
     }
 }
 
-impl ResponseError for serde_json::Error {}
+impl ResponseError for serde_json::Error {
+    as_std_error!();
+}
 
-impl ResponseError for serde_urlencoded::ser::Error {}
+impl ResponseError for serde_urlencoded::ser::Error {
+    as_std_error!();
+}
 
 impl ResponseError for std::str::Utf8Error {
+    as_std_error!();
+
     fn status_code(&self) -> StatusCode {
         StatusCode::BAD_REQUEST
     }
 }
 
 impl ResponseError for std::io::Error {
+    as_std_error!();
+
     fn status_code(&self) -> StatusCode {
         match self.kind() {
             io::ErrorKind::NotFound => StatusCode::NOT_FOUND,
@@ -93,9 +228,13 @@ impl ResponseError for std::io::Error {
     }
 }
 
-impl ResponseError for actix_http::error::HttpError {}
+impl ResponseError for actix_http::error::HttpError {
+    as_std_error!();
+}
 
 impl ResponseError for actix_http::Error {
+    as_std_error!();
+
     fn status_code(&self) -> StatusCode {
         StatusCode::INTERNAL_SERVER_ERROR
     }
@@ -106,40 +245,65 @@ impl ResponseError for actix_http::Error {
 }
 
 impl ResponseError for actix_http::header::InvalidHeaderValue {
+    as_std_error!();
+
     fn status_code(&self) -> StatusCode {
         StatusCode::BAD_REQUEST
     }
 }
 
 impl ResponseError for actix_http::error::ParseError {
+    as_std_error!();
+
     fn status_code(&self) -> StatusCode {
         StatusCode::BAD_REQUEST
     }
 }
 
 impl ResponseError for actix_http::error::PayloadError {
+    as_std_error!();
+
     fn status_code(&self) -> StatusCode {
         match *self {
             actix_http::error::PayloadError::Overflow => StatusCode::PAYLOAD_TOO_LARGE,
+            actix_http::error::PayloadError::Timeout => StatusCode::REQUEST_TIMEOUT,
             _ => StatusCode::BAD_REQUEST,
         }
     }
 }
 
-impl ResponseError for actix_http::ws::ProtocolError {}
+impl ResponseError for actix_http::ws::ProtocolError {
+    as_std_error!();
+}
 
 impl ResponseError for actix_http::error::ContentTypeError {
+    as_std_error!();
+
     fn status_code(&self) -> StatusCode {
         StatusCode::BAD_REQUEST
     }
 }
 
 impl ResponseError for actix_http::ws::HandshakeError {
+    as_std_error!();
+
+    fn status_code(&self) -> StatusCode {
+        actix_http::ws::HandshakeError::status_code(self)
+    }
+
     fn error_response(&self) -> HttpResponse<BoxBody> {
         Response::from(self).map_into_boxed_body().into()
     }
 }
 
+impl ResponseError for actix_http::ws::OriginError {
+    as_std_error!();
+
+    fn status_code(&self) -> StatusCode {
+        actix_http::ws::OriginError::status_code(self)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -157,4 +321,64 @@ mod tests {
         let not_err = resp_err.downcast_ref::<ContentTypeError>();
         assert!(not_err.is_none());
     }
+
+    #[actix_rt::test]
+    async fn response_hooks() {
+        use actix_http::header::{HeaderValue, CONTENT_TYPE, RETRY_AFTER};
+
+        use crate::body::to_bytes;
+
+        #[derive(Debug, derive_more::Display)]
+        #[display("busy")]
+        struct Busy;
+
+        impl ResponseError for Busy {
+            fn status_code(&self) -> StatusCode {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+
+            fn headers(&self) -> HeaderMap {
+                let mut headers = HeaderMap::new();
+                headers.insert(RETRY_AFTER, HeaderValue::from_static("5"));
+                headers
+            }
+        }
+
+        let res = Busy.error_response();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers().get(RETRY_AFTER).unwrap(), "5");
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(to_bytes(res.into_body()).await.unwrap(), "busy");
+
+        #[derive(Debug, derive_more::Display)]
+        #[display("not found")]
+        struct Missing;
+
+        impl ResponseError for Missing {
+            fn status_code(&self) -> StatusCode {
+                StatusCode::NOT_FOUND
+            }
+
+            fn headers(&self) -> HeaderMap {
+                let mut headers = HeaderMap::new();
+                headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/html"));
+                headers
+            }
+
+            fn body(&self) -> BoxBody {
+                BoxBody::new("<h1>not found</h1>")
+            }
+        }
+
+        let res = Missing.error_response();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "text/html");
+        assert_eq!(
+            to_bytes(res.into_body()).await.unwrap(),
+            "<h1>not found</h1>"
+        );
+    }
 }
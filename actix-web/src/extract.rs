@@ -8,7 +8,7 @@ use std::{
     task::{Context, Poll},
 };
 
-use actix_http::{Method, Uri};
+use actix_http::{header::HeaderMap, Method, Uri, Version};
 use actix_utils::future::{ok, Ready};
 use futures_core::ready;
 use pin_project_lite::pin_project;
@@ -307,6 +307,52 @@ impl FromRequest for Method {
     }
 }
 
+/// Extract the request's HTTP version.
+///
+/// # Examples
+/// ```
+/// use actix_web::{http::Version, web, App, Responder};
+///
+/// async fn handler(version: Version) -> impl Responder {
+///     format!("Request HTTP version: {:?}", version)
+/// }
+///
+/// let app = App::new().default_service(web::to(handler));
+/// ```
+impl FromRequest for Version {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ok(req.version())
+    }
+}
+
+/// Extract the request's headers.
+///
+/// Header names and values are reference-counted, so the returned map shares its contents with
+/// the request head rather than copying them. Use the [`Header`](crate::web::Header) extractor to
+/// extract a single typed header.
+///
+/// # Examples
+/// ```
+/// use actix_web::{http::header::HeaderMap, web, App, Responder};
+///
+/// async fn handler(headers: HeaderMap) -> impl Responder {
+///     format!("Request has {} headers", headers.len())
+/// }
+///
+/// let app = App::new().default_service(web::to(handler));
+/// ```
+impl FromRequest for HeaderMap {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ok(req.headers().clone())
+    }
+}
+
 #[doc(hidden)]
 #[allow(non_snake_case)]
 mod tuple_from_req {
@@ -531,6 +577,27 @@ mod tests {
         assert_eq!(method, Method::GET);
     }
 
+    #[actix_rt::test]
+    async fn test_version() {
+        let req = TestRequest::default()
+            .version(Version::HTTP_2)
+            .to_http_request();
+        let version = Version::extract(&req).await.unwrap();
+        assert_eq!(version, Version::HTTP_2);
+    }
+
+    #[actix_rt::test]
+    async fn test_header_map() {
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "text/plain"))
+            .append_header(("x-test", "1"))
+            .append_header(("x-test", "2"))
+            .to_http_request();
+        let headers = HeaderMap::extract(&req).await.unwrap();
+        assert_eq!(headers.get(header::CONTENT_TYPE).unwrap(), "text/plain");
+        assert_eq!(headers.get_all("x-test").count(), 2);
+    }
+
     #[actix_rt::test]
     async fn test_concurrent() {
         let (req, mut pl) = TestRequest::default()
@@ -540,10 +607,13 @@ mod tests {
             .insert_header((header::CONTENT_LENGTH, "11"))
             .set_payload(Bytes::from_static(b"hello=world"))
             .to_http_parts();
-        let (method, uri, form) = <(Method, Uri, Form<Info>)>::from_request(&req, &mut pl)
-            .await
-            .unwrap();
+        let (method, uri, version, headers, form) =
+            <(Method, Uri, Version, HeaderMap, Form<Info>)>::from_request(&req, &mut pl)
+                .await
+                .unwrap();
         assert_eq!(method, Method::GET);
+        assert_eq!(version, Version::HTTP_11);
+        assert_eq!(headers.get(header::CONTENT_LENGTH).unwrap(), "11");
         assert_eq!(uri.path(), "/foo/bar");
         assert_eq!(
             form,
@@ -552,4 +622,96 @@ mod tests {
             })
         );
     }
+
+    #[actix_rt::test]
+    async fn test_tuple_first_error() {
+        struct Never;
+
+        impl FromRequest for Never {
+            type Error = Infallible;
+            type Future = std::future::Pending<Result<Self, Self::Error>>;
+
+            fn from_request(_: &HttpRequest, _: &mut Payload) -> Self::Future {
+                std::future::pending()
+            }
+        }
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .insert_header((header::CONTENT_LENGTH, "9"))
+            .set_payload(Bytes::from_static(b"bye=world"))
+            .to_http_parts();
+
+        // error is returned without waiting for the other extractors to complete
+        let res = <(Never, Form<Info>)>::from_request(&req, &mut pl).await;
+        assert!(res.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_tuple_arity_12() {
+        let req = TestRequest::default().uri("/foo/bar").to_http_request();
+
+        let (m, _, _, _, _, _, _, _, _, _, _, u) = <(
+            Method,
+            Method,
+            Method,
+            Method,
+            Method,
+            Method,
+            Method,
+            Method,
+            Method,
+            Method,
+            Method,
+            Uri,
+        )>::extract(&req)
+        .await
+        .unwrap();
+
+        assert_eq!(m, Method::GET);
+        assert_eq!(u.path(), "/foo/bar");
+    }
+
+    #[actix_rt::test]
+    async fn test_wrapper_extractors_in_handler() {
+        use crate::{http::StatusCode, test, web, App, HttpResponse};
+
+        #[derive(Deserialize)]
+        struct Paging {
+            page: u32,
+        }
+
+        async fn opt(paging: Option<web::Query<Paging>>) -> String {
+            paging.map_or(1, |paging| paging.page).to_string()
+        }
+
+        async fn res(paging: Result<web::Query<Paging>, Error>) -> HttpResponse {
+            match paging {
+                Ok(paging) => HttpResponse::Ok().body(paging.page.to_string()),
+                Err(err) => HttpResponse::UnprocessableEntity().body(err.to_string()),
+            }
+        }
+
+        let app = test::init_service(
+            App::new()
+                .route("/opt", web::get().to(opt))
+                .route("/res", web::get().to(res)),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/opt?page=abc").to_request();
+        assert_eq!(test::call_and_read_body(&app, req).await, "1");
+
+        let req = TestRequest::with_uri("/opt?page=3").to_request();
+        assert_eq!(test::call_and_read_body(&app, req).await, "3");
+
+        // the handler chooses the response instead of the default 400
+        let req = TestRequest::with_uri("/res?page=abc").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = test::read_body(res).await;
+        assert!(std::str::from_utf8(&body)
+            .unwrap()
+            .contains("Query deserialize error"));
+    }
 }
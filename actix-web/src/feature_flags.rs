@@ -0,0 +1,415 @@
+//! Feature flags.
+//!
+//! Apps register [`FeatureFlags`] as app data to decide which features are enabled. The flags are
+//! backed by a [`FlagProvider`]: a fixed set of [`StaticFlags`], flags read from the environment
+//! with [`EnvFlags`], [`RemoteFlags`] that are refreshed from a remote service in the background,
+//! or a custom provider that evaluates flags per request (e.g., for gradual rollouts).
+//!
+//! Routes behind a flag are guarded with [`guard::Feature`], which makes them respond with
+//! `404 Not Found` while the flag is disabled. Handlers branch on flags using the [`Flags`]
+//! extractor. Each flag is evaluated at most once per request, so guards and handlers always agree.
+//!
+//! # Examples
+//! ```
+//! use actix_web::{
+//!     feature_flags::{FeatureFlags, Flags, StaticFlags},
+//!     get, guard, web, App, HttpResponse,
+//! };
+//!
+//! #[get("/")]
+//! async fn index(flags: Flags) -> &'static str {
+//!     if flags.is_enabled("new-homepage") {
+//!         "new homepage"
+//!     } else {
+//!         "old homepage"
+//!     }
+//! }
+//!
+//! let app = App::new()
+//!     .app_data(FeatureFlags::new(
+//!         StaticFlags::new()
+//!             .set("new-homepage", true)
+//!             .set("new-checkout", false),
+//!     ))
+//!     .service(index)
+//!     .service(
+//!         web::resource("/checkout")
+//!             .guard(guard::Feature("new-checkout"))
+//!             .to(HttpResponse::Ok),
+//!     );
+//! ```
+//!
+//! [`guard::Feature`]: crate::guard::Feature
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    env, fmt,
+    sync::{Arc, RwLock},
+};
+
+use actix_utils::future::{ok, Ready};
+
+use crate::{
+    dev::{Extensions, Payload, RequestHead},
+    FromRequest, HttpMessage as _, HttpRequest,
+};
+
+/// Decides whether feature flags are enabled.
+///
+/// Implemented for closures with the same signature as [`is_enabled()`](Self::is_enabled()).
+pub trait FlagProvider: Send + Sync + 'static {
+    /// Returns true if `flag` is enabled for the request with head `req`.
+    ///
+    /// Unknown flags should be reported as disabled.
+    fn is_enabled(&self, flag: &str, req: &RequestHead) -> bool;
+}
+
+impl<F> FlagProvider for F
+where
+    F: Fn(&str, &RequestHead) -> bool + Send + Sync + 'static,
+{
+    fn is_enabled(&self, flag: &str, req: &RequestHead) -> bool {
+        (self)(flag, req)
+    }
+}
+
+/// Fixed set of feature flags.
+#[derive(Debug, Clone, Default)]
+pub struct StaticFlags {
+    flags: HashMap<String, bool>,
+}
+
+impl StaticFlags {
+    /// Constructs an empty set of flags.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether `flag` is enabled.
+    pub fn set(mut self, flag: impl Into<String>, enabled: bool) -> Self {
+        self.flags.insert(flag.into(), enabled);
+        self
+    }
+}
+
+impl FlagProvider for StaticFlags {
+    fn is_enabled(&self, flag: &str, _: &RequestHead) -> bool {
+        self.flags.get(flag).copied().unwrap_or(false)
+    }
+}
+
+/// Feature flags read from environment variables.
+///
+/// The variable for a flag is named after the flag, upper-cased, with dashes replaced by
+/// underscores, and prefixed with the given prefix; e.g., the `new-checkout` flag is read from
+/// `FEATURE_NEW_CHECKOUT` with the prefix `FEATURE_`. Values of `1`, `true`, `on`, and `yes` (in
+/// any case) enable a flag. Variables are read once, when the provider is constructed.
+#[derive(Debug, Clone)]
+pub struct EnvFlags {
+    prefix: String,
+    vars: HashMap<String, bool>,
+}
+
+impl EnvFlags {
+    /// Constructs a provider from the environment variables starting with `prefix`.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
+
+        let vars = env::vars()
+            .filter(|(name, _)| name.starts_with(&prefix))
+            .map(|(name, val)| {
+                let enabled = ["1", "true", "on", "yes"]
+                    .iter()
+                    .any(|truthy| val.trim().eq_ignore_ascii_case(truthy));
+                (name, enabled)
+            })
+            .collect();
+
+        Self { prefix, vars }
+    }
+
+    fn var_name(&self, flag: &str) -> String {
+        let flag = flag.to_ascii_uppercase().replace('-', "_");
+        format!("{}{flag}", self.prefix)
+    }
+}
+
+impl FlagProvider for EnvFlags {
+    fn is_enabled(&self, flag: &str, _: &RequestHead) -> bool {
+        self.vars
+            .get(&self.var_name(flag))
+            .copied()
+            .unwrap_or(false)
+    }
+}
+
+/// Feature flags that can be replaced while the server is running.
+///
+/// Clones share the same flags, so a clone can be kept by a background task that periodically
+/// fetches the flags from a remote service and [replaces](Self::replace) them.
+///
+/// # Examples
+/// ```
+/// use std::{collections::HashMap, time::Duration};
+///
+/// use actix_web::feature_flags::{FeatureFlags, RemoteFlags};
+///
+/// # async fn fetch_flags() -> HashMap<String, bool> { HashMap::new() }
+/// # async fn run() {
+/// let remote = RemoteFlags::new();
+/// let flags = FeatureFlags::new(remote.clone());
+///
+/// actix_web::rt::spawn(async move {
+///     loop {
+///         remote.replace(fetch_flags().await);
+///         actix_web::rt::time::sleep(Duration::from_secs(30)).await;
+///     }
+/// });
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RemoteFlags {
+    flags: Arc<RwLock<HashMap<String, bool>>>,
+}
+
+impl RemoteFlags {
+    /// Constructs a provider with all flags disabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces all flags.
+    pub fn replace(&self, flags: HashMap<String, bool>) {
+        *self.flags.write().unwrap() = flags;
+    }
+
+    /// Sets whether `flag` is enabled.
+    pub fn set(&self, flag: impl Into<String>, enabled: bool) {
+        self.flags.write().unwrap().insert(flag.into(), enabled);
+    }
+}
+
+impl FlagProvider for RemoteFlags {
+    fn is_enabled(&self, flag: &str, _: &RequestHead) -> bool {
+        self.flags
+            .read()
+            .unwrap()
+            .get(flag)
+            .copied()
+            .unwrap_or(false)
+    }
+}
+
+/// Feature flags of an app.
+///
+/// Register as app data, without wrapping in [`Data`](crate::web::Data). If no flags are
+/// registered, all flags are disabled.
+///
+/// See the [module docs](self) for an example.
+#[derive(Clone)]
+pub struct FeatureFlags {
+    provider: Arc<dyn FlagProvider>,
+}
+
+/// Flags evaluated for a request.
+#[derive(Default)]
+struct Evaluated(HashMap<String, bool>);
+
+impl FeatureFlags {
+    /// Constructs feature flags backed by `provider`.
+    pub fn new(provider: impl FlagProvider) -> Self {
+        Self {
+            provider: Arc::new(provider),
+        }
+    }
+
+    /// Returns true if `flag` is enabled, evaluating it only on first use for the request.
+    pub(crate) fn is_enabled_for(
+        flags: Option<&Self>,
+        flag: &str,
+        head: &RequestHead,
+        extensions: &mut Extensions,
+    ) -> bool {
+        if let Some(&enabled) = extensions
+            .get::<Evaluated>()
+            .and_then(|evaluated| evaluated.0.get(flag))
+        {
+            return enabled;
+        }
+
+        let enabled = flags.is_some_and(|flags| flags.provider.is_enabled(flag, head));
+
+        if !extensions.contains::<Evaluated>() {
+            extensions.insert(Evaluated::default());
+        }
+
+        extensions
+            .get_mut::<Evaluated>()
+            .unwrap()
+            .0
+            .insert(flag.to_owned(), enabled);
+
+        enabled
+    }
+}
+
+impl fmt::Debug for FeatureFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FeatureFlags").finish_non_exhaustive()
+    }
+}
+
+/// Extractor for evaluating feature flags for the current request.
+///
+/// See the [module docs](self) for an example.
+#[derive(Clone)]
+pub struct Flags {
+    req: HttpRequest,
+}
+
+impl Flags {
+    /// Returns true if `flag` is enabled for the request.
+    pub fn is_enabled(&self, flag: &str) -> bool {
+        FeatureFlags::is_enabled_for(
+            self.req.app_data::<FeatureFlags>(),
+            flag,
+            self.req.head(),
+            &mut self.req.extensions_mut(),
+        )
+    }
+}
+
+impl fmt::Debug for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Flags").finish_non_exhaustive()
+    }
+}
+
+impl FromRequest for Flags {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ok(Flags { req: req.clone() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{
+        guard,
+        http::StatusCode,
+        test::{self, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    #[test]
+    fn env_flags() {
+        env::set_var("ACTIX_TEST_FLAG_NEW_CHECKOUT", "True");
+        env::set_var("ACTIX_TEST_FLAG_BETA", "0");
+
+        let flags = EnvFlags::new("ACTIX_TEST_FLAG_");
+        let head = RequestHead::default();
+        assert!(flags.is_enabled("new-checkout", &head));
+        assert!(!flags.is_enabled("beta", &head));
+        assert!(!flags.is_enabled("unknown", &head));
+    }
+
+    #[test]
+    fn remote_flags() {
+        let remote = RemoteFlags::new();
+        let head = RequestHead::default();
+        assert!(!remote.is_enabled("beta", &head));
+
+        remote.clone().set("beta", true);
+        assert!(remote.is_enabled("beta", &head));
+
+        remote.replace(HashMap::new());
+        assert!(!remote.is_enabled("beta", &head));
+    }
+
+    async fn checkout(flags: Flags) -> HttpResponse {
+        let body = if flags.is_enabled("express") {
+            "express checkout"
+        } else {
+            "checkout"
+        };
+
+        HttpResponse::Ok().body(body)
+    }
+
+    #[actix_rt::test]
+    async fn guard_and_extractor() {
+        let evaluations = Arc::new(AtomicUsize::new(0));
+
+        let flags = FeatureFlags::new({
+            let evaluations = Arc::clone(&evaluations);
+            move |flag: &str, req: &RequestHead| {
+                evaluations.fetch_add(1, Ordering::SeqCst);
+                match flag {
+                    "checkout" => true,
+                    "express" => req.headers().contains_key("x-beta"),
+                    _ => false,
+                }
+            }
+        });
+
+        let app = test::init_service(
+            App::new()
+                .app_data(flags)
+                .service(
+                    web::resource("/checkout")
+                        .guard(guard::Feature("checkout"))
+                        .guard(guard::Feature("checkout"))
+                        .to(checkout),
+                )
+                .service(
+                    web::resource("/labs")
+                        .guard(guard::Feature("labs"))
+                        .to(HttpResponse::Ok),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/checkout").to_request();
+        assert_eq!(test::call_and_read_body(&app, req).await, "checkout");
+        assert_eq!(evaluations.load(Ordering::SeqCst), 2);
+
+        let req = TestRequest::with_uri("/checkout")
+            .insert_header(("x-beta", "1"))
+            .to_request();
+        assert_eq!(
+            test::call_and_read_body(&app, req).await,
+            "express checkout"
+        );
+
+        let req = TestRequest::with_uri("/labs").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_rt::test]
+    async fn flags_not_registered() {
+        let app = test::init_service(
+            App::new().service(
+                web::resource("/checkout")
+                    .guard(guard::Feature("checkout"))
+                    .to(HttpResponse::Ok),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/checkout").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let (req, _) = TestRequest::default().to_http_parts();
+        let flags = Flags { req };
+        assert!(!flags.is_enabled("checkout"));
+    }
+}
@@ -44,7 +44,7 @@ impl Acceptable {
 
 impl Guard for Acceptable {
     fn check(&self, ctx: &GuardContext<'_>) -> bool {
-        let accept = match ctx.header::<Accept>() {
+        let accept = match ctx.cached_header::<Accept>() {
             Some(hdr) => hdr,
             None => return false,
         };
@@ -0,0 +1,72 @@
+use super::{Guard, GuardContext};
+use crate::feature_flags::FeatureFlags;
+
+/// Creates a guard that matches requests for which a feature flag is enabled.
+///
+/// Flags are evaluated by the [`FeatureFlags`] registered as app data. Routes behind a disabled
+/// flag do not match, so requests for them respond with `404 Not Found` unless another route
+/// matches. If no `FeatureFlags` are registered, all flags are disabled.
+///
+/// # Examples
+/// ```
+/// use actix_web::{
+///     feature_flags::{FeatureFlags, StaticFlags},
+///     guard, web, App, HttpResponse,
+/// };
+///
+/// App::new()
+///     .app_data(FeatureFlags::new(StaticFlags::new().set("new-checkout", true)))
+///     .service(
+///         web::resource("/checkout")
+///             .guard(guard::Feature("new-checkout"))
+///             .to(HttpResponse::Ok),
+///     );
+/// ```
+#[allow(non_snake_case)]
+pub fn Feature(flag: impl Into<String>) -> FeatureGuard {
+    FeatureGuard { flag: flag.into() }
+}
+
+/// Guard for feature flags.
+///
+/// See [`Feature`] for details.
+#[derive(Debug, Clone)]
+pub struct FeatureGuard {
+    flag: String,
+}
+
+impl Guard for FeatureGuard {
+    fn check(&self, ctx: &GuardContext<'_>) -> bool {
+        FeatureFlags::is_enabled_for(
+            ctx.app_data::<FeatureFlags>(),
+            &self.flag,
+            ctx.head(),
+            &mut ctx.req_data_mut(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{feature_flags::StaticFlags, test::TestRequest};
+
+    #[test]
+    fn feature_flag() {
+        let req = TestRequest::default()
+            .app_data(FeatureFlags::new(
+                StaticFlags::new().set("beta", true).set("labs", false),
+            ))
+            .to_srv_request();
+
+        assert!(Feature("beta").check(&req.guard_ctx()));
+        assert!(!Feature("labs").check(&req.guard_ctx()));
+        assert!(!Feature("unknown").check(&req.guard_ctx()));
+    }
+
+    #[test]
+    fn flags_not_registered() {
+        let req = TestRequest::default().to_srv_request();
+        assert!(!Feature("beta").check(&req.guard_ctx()));
+    }
+}
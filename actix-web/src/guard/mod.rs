@@ -59,10 +59,12 @@ use actix_http::{header, Extensions, Method as HttpMethod, RequestHead};
 use crate::{http::header::Header, service::ServiceRequest, HttpMessage as _};
 
 mod acceptable;
+mod feature;
 mod host;
 
 pub use self::{
     acceptable::Acceptable,
+    feature::{Feature, FeatureGuard},
     host::{Host, HostGuard},
 };
 
@@ -111,6 +113,15 @@ impl<'a> GuardContext<'a> {
         H::parse(self.req).ok()
     }
 
+    /// Extracts a typed header from the request, reusing the result of earlier lookups.
+    ///
+    /// Prefer this over [`header()`](Self::header()) for headers that are checked by multiple
+    /// guards. See [`HttpRequest::cached_header()`](crate::HttpRequest::cached_header()).
+    #[inline]
+    pub fn cached_header<H: Header + Clone + 'static>(&self) -> Option<H> {
+        self.req.cached_header()
+    }
+
     /// Counterpart to [HttpRequest::app_data](crate::HttpRequest::app_data).
     #[inline]
     pub fn app_data<T: 'static>(&self) -> Option<&T> {
@@ -0,0 +1,442 @@
+//! The `Link` header and associated types.
+//!
+//! # References
+//! - "Web Linking": <https://datatracker.ietf.org/doc/html/rfc8288>
+//! - IANA link relations: <https://www.iana.org/assignments/link-relations/link-relations.xhtml>
+
+use std::{
+    fmt::{self, Write as _},
+    ops::{Deref, DerefMut},
+};
+
+use super::{Header, HeaderValue, InvalidHeaderValue, TryIntoHeaderValue, Writer};
+use crate::{
+    error::{ParseError, UrlGenerationError},
+    http::header,
+    HttpMessage, HttpRequest,
+};
+
+/// `Link` header, defined in [RFC 8288 §3](https://datatracker.ietf.org/doc/html/rfc8288#section-3).
+///
+/// The `Link` header field serializes one or more links, each consisting of a target URI and
+/// parameters describing its relation to the current resource.
+///
+/// When parsing, links from all `Link` header lines in a message are collected in order.
+///
+/// # ABNF
+/// ```plain
+/// Link       = #link-value
+/// link-value = "<" URI-Reference ">" *( OWS ";" OWS link-param )
+/// link-param = token BWS [ "=" BWS ( token / quoted-string ) ]
+/// ```
+///
+/// # Example Values
+/// * `<https://example.com/items?page=2>; rel="next"`
+/// * `</style.css>; rel=preload; as=style, </app.js>; rel=preload; as=script`
+///
+/// # Examples
+/// ```
+/// use actix_web::{http::header::{Link, LinkValue}, HttpResponse};
+///
+/// let mut builder = HttpResponse::Ok();
+/// builder.insert_header(Link(vec![
+///     LinkValue::new("/items?page=2").with_rel("next"),
+///     LinkValue::new("/style.css")
+///         .with_rel("preload")
+///         .with_param("as", "style"),
+/// ]));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Link(pub Vec<LinkValue>);
+
+impl Link {
+    /// Returns the first link with the given relation type, if any.
+    ///
+    /// See [`LinkValue::has_rel`] for how relation types are matched.
+    pub fn find(&self, rel: &str) -> Option<&LinkValue> {
+        self.0.iter().find(|link| link.has_rel(rel))
+    }
+
+    /// Parses a single header value, appending its links to `links`.
+    fn parse_value(mut s: &str, links: &mut Vec<LinkValue>) -> Result<(), ParseError> {
+        loop {
+            s = s.trim_start_matches(|c: char| c == ',' || is_ows(c));
+
+            if s.is_empty() {
+                return Ok(());
+            }
+
+            let (link, rest) = LinkValue::parse(s)?;
+            links.push(link);
+            s = rest;
+        }
+    }
+}
+
+impl Deref for Link {
+    type Target = Vec<LinkValue>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Link {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, link) in self.0.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+
+            fmt::Display::fmt(link, f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TryIntoHeaderValue for Link {
+    type Error = InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        let mut writer = Writer::new();
+        let _ = write!(&mut writer, "{}", self);
+        HeaderValue::from_maybe_shared(writer.take())
+    }
+}
+
+impl Header for Link {
+    fn name() -> header::HeaderName {
+        header::LINK
+    }
+
+    fn parse<M: HttpMessage>(msg: &M) -> Result<Self, ParseError> {
+        let mut links = Vec::new();
+        let mut found = false;
+
+        for value in msg.headers().get_all(Self::name()) {
+            found = true;
+            let value = value.to_str().map_err(|_| ParseError::Header)?;
+            Link::parse_value(value, &mut links)?;
+        }
+
+        if found {
+            Ok(Link(links))
+        } else {
+            Err(ParseError::Header)
+        }
+    }
+}
+
+/// A single link in a [`Link`] header: a target URI reference and its parameters.
+///
+/// Parameter names are matched case-insensitively and kept in order; a parameter without a value
+/// has an empty string value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkValue {
+    target: String,
+    params: Vec<(String, String)>,
+}
+
+impl LinkValue {
+    /// Constructs a link to `target`, with no parameters.
+    pub fn new(target: impl Into<String>) -> Self {
+        Self {
+            target: target.into(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Constructs a link to the named resource, generated using [`HttpRequest::url_for`].
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{http::header::{Link, LinkValue}, web, App, HttpRequest, HttpResponse};
+    ///
+    /// async fn create_user(req: HttpRequest) -> actix_web::Result<HttpResponse> {
+    ///     let link = LinkValue::url_for(&req, "user", ["42"])?.with_rel("self");
+    ///     Ok(HttpResponse::Created().insert_header(Link(vec![link])).finish())
+    /// }
+    ///
+    /// let app = App::new()
+    ///     .service(web::resource("/users/{id}").name("user"))
+    ///     .route("/users", web::post().to(create_user));
+    /// ```
+    pub fn url_for<U, I>(
+        req: &HttpRequest,
+        name: &str,
+        elements: U,
+    ) -> Result<Self, UrlGenerationError>
+    where
+        U: IntoIterator<Item = I>,
+        I: AsRef<str>,
+    {
+        req.url_for(name, elements).map(Self::from)
+    }
+
+    /// Adds a `rel` parameter with the given relation type.
+    pub fn with_rel(self, rel: impl Into<String>) -> Self {
+        self.with_param("rel", rel)
+    }
+
+    /// Adds a parameter.
+    ///
+    /// Values are written as quoted strings, except for extended parameters (names ending in `*`)
+    /// whose values must already be encoded according to [RFC 8187].
+    ///
+    /// [RFC 8187]: https://datatracker.ietf.org/doc/html/rfc8187
+    pub fn with_param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.params.push((name.into(), value.into()));
+        self
+    }
+
+    /// Returns the target URI reference.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns the value of the `rel` parameter, if present.
+    pub fn rel(&self) -> Option<&str> {
+        self.param("rel")
+    }
+
+    /// Returns true if the `rel` parameter contains the given relation type.
+    ///
+    /// The `rel` parameter may contain several space-separated relation types, which are compared
+    /// case-insensitively.
+    pub fn has_rel(&self, rel: &str) -> bool {
+        self.rel().is_some_and(|rels| {
+            rels.split_ascii_whitespace()
+                .any(|item| item.eq_ignore_ascii_case(rel))
+        })
+    }
+
+    /// Returns the value of the first parameter with the given name, if present.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns an iterator over all parameters, in order.
+    pub fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.params
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Parses one link value from the start of `s`, returning it and the unparsed remainder.
+    fn parse(s: &str) -> Result<(Self, &str), ParseError> {
+        let s = s.strip_prefix('<').ok_or(ParseError::Header)?;
+        let end = s.find('>').ok_or(ParseError::Header)?;
+        let mut link = LinkValue::new(&s[..end]);
+        let mut s = &s[end + 1..];
+
+        loop {
+            s = s.trim_start_matches(is_ows);
+
+            s = match s.strip_prefix(';') {
+                Some(rest) => rest.trim_start_matches(is_ows),
+                None if s.is_empty() || s.starts_with(',') => return Ok((link, s)),
+                None => return Err(ParseError::Header),
+            };
+
+            let name_len = s.find(|c| !is_tchar(c)).unwrap_or(s.len());
+            if name_len == 0 {
+                return Err(ParseError::Header);
+            }
+            let (name, rest) = s.split_at(name_len);
+            s = rest.trim_start_matches(is_ows);
+
+            let value = match s.strip_prefix('=') {
+                Some(rest) => {
+                    let (value, rest) = parse_token_or_quoted(rest.trim_start_matches(is_ows))?;
+                    s = rest;
+                    value
+                }
+                None => String::new(),
+            };
+
+            link.params.push((name.to_owned(), value));
+        }
+    }
+}
+
+impl From<url::Url> for LinkValue {
+    fn from(url: url::Url) -> Self {
+        Self::new(String::from(url))
+    }
+}
+
+impl fmt::Display for LinkValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}>", self.target)?;
+
+        for (name, value) in &self.params {
+            if name.ends_with('*') {
+                write!(f, "; {name}={value}")?;
+            } else {
+                write!(f, "; {name}=\"")?;
+
+                for c in value.chars() {
+                    if c == '"' || c == '\\' {
+                        f.write_char('\\')?;
+                    }
+                    f.write_char(c)?;
+                }
+
+                f.write_char('"')?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_ows(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+/// Token characters, as defined in [RFC 9110 §5.6.2](https://datatracker.ietf.org/doc/html/rfc9110#section-5.6.2).
+fn is_tchar(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+/// Parses a token or quoted-string from the start of `s`, returning it and the remainder.
+fn parse_token_or_quoted(s: &str) -> Result<(String, &str), ParseError> {
+    let Some(quoted) = s.strip_prefix('"') else {
+        let len = s.find(|c| !is_tchar(c)).unwrap_or(s.len());
+        if len == 0 {
+            return Err(ParseError::Header);
+        }
+        return Ok((s[..len].to_owned(), &s[len..]));
+    };
+
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &quoted[idx + 1..])),
+            '\\' => value.push(chars.next().ok_or(ParseError::Header)?.1),
+            c => value.push(c),
+        }
+    }
+
+    // unterminated quoted-string
+    Err(ParseError::Header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dev::ResourceDef, rmap::ResourceMap, test::TestRequest};
+
+    fn parse(values: &[&'static str]) -> Result<Link, ParseError> {
+        let mut req = TestRequest::default();
+        for value in values {
+            req = req.append_header((header::LINK, *value));
+        }
+        Link::parse(&req.to_http_request())
+    }
+
+    #[test]
+    fn parse_single() {
+        let link = parse(&["<https://example.com/items?page=2>; rel=\"next\""]).unwrap();
+        assert_eq!(link.len(), 1);
+        assert_eq!(link[0].target(), "https://example.com/items?page=2");
+        assert_eq!(link[0].rel(), Some("next"));
+    }
+
+    #[test]
+    fn parse_multiple() {
+        let link = parse(&[
+            "</style.css>; rel=preload; as=style, </app.js> ;rel = \"preload modulepreload\"",
+            "</a,b>; title=\"one, \\\"two\\\"\"; crossorigin",
+        ])
+        .unwrap();
+        assert_eq!(link.len(), 3);
+
+        assert_eq!(link[0].target(), "/style.css");
+        assert_eq!(
+            link[0].params().collect::<Vec<_>>(),
+            [("rel", "preload"), ("as", "style")]
+        );
+
+        assert!(link[1].has_rel("ModulePreload"));
+        assert!(!link[1].has_rel("module"));
+        assert_eq!(link.find("preload"), Some(&link[0]));
+        assert_eq!(link.find("next"), None);
+
+        assert_eq!(link[2].target(), "/a,b");
+        assert_eq!(link[2].param("TITLE"), Some("one, \"two\""));
+        assert_eq!(link[2].param("crossorigin"), Some(""));
+        assert_eq!(link[2].rel(), None);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["https://example.com"]).is_err());
+        assert!(parse(&["<https://example.com"]).is_err());
+        assert!(parse(&["<https://example.com> rel=next"]).is_err());
+        assert!(parse(&["<https://example.com>; =next"]).is_err());
+        assert!(parse(&["<https://example.com>; rel=\"next"]).is_err());
+        assert!(parse(&["<https://example.com>; rel="]).is_err());
+
+        // empty list elements are allowed
+        assert_eq!(parse(&[""]).unwrap(), Link(vec![]));
+        assert_eq!(parse(&[" , </a>,"]).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn format() {
+        let link = Link(vec![
+            LinkValue::new("/items?page=2").with_rel("next"),
+            LinkValue::new("/doc")
+                .with_param("title", "a \"quoted\" title")
+                .with_param("title*", "UTF-8'de'n%c3%a4chstes%20Kapitel"),
+        ]);
+
+        let value = link.clone().try_into_value().unwrap();
+        assert_eq!(
+            value,
+            "</items?page=2>; rel=\"next\", \
+             </doc>; title=\"a \\\"quoted\\\" title\"; title*=UTF-8'de'n%c3%a4chstes%20Kapitel"
+        );
+
+        // round trip
+        let req = TestRequest::default()
+            .insert_header(link.clone())
+            .to_http_request();
+        assert_eq!(Link::parse(&req).unwrap(), link);
+    }
+
+    #[test]
+    fn from_route() {
+        let mut rdef = ResourceDef::new("/users/{id}");
+        rdef.set_name("user");
+
+        let mut rmap = ResourceMap::new(ResourceDef::prefix(""));
+        rmap.add(&mut rdef, None);
+
+        let req = TestRequest::default().rmap(rmap).to_http_request();
+
+        let link = LinkValue::url_for(&req, "user", ["42"])
+            .unwrap()
+            .with_rel("self");
+        assert_eq!(
+            link.to_string(),
+            "<http://localhost:8080/users/42>; rel=\"self\""
+        );
+
+        assert!(LinkValue::url_for(&req, "missing", [""; 0]).is_err());
+    }
+}
@@ -38,6 +38,7 @@ mod if_none_match;
 mod if_range;
 mod if_unmodified_since;
 mod last_modified;
+mod link;
 mod macros;
 mod preference;
 mod range;
@@ -68,6 +69,7 @@ pub use self::{
     if_range::IfRange,
     if_unmodified_since::IfUnmodifiedSince,
     last_modified::LastModified,
+    link::{Link, LinkValue},
     preference::Preference,
     range::{ByteRangeSpec, Range},
 };
@@ -0,0 +1,677 @@
+//! User identity and authentication.
+//!
+//! The [`IdentityService`] middleware picks up the token presented by the client, either from a
+//! `Bearer` credential in the `Authorization` header or from the identity cookie. Handlers receive
+//! the resulting user through the [`RequestIdentity`] extractor, which asks the app's
+//! [`IdentityBackend`] to validate the token the first time it is extracted for a request. Backends
+//! can therefore verify self-contained tokens (e.g., JWTs) or look up opaque session tokens in a
+//! database.
+//!
+//! Handlers call [`RequestIdentity::login()`] and [`RequestIdentity::logout()`] to change the
+//! identity. Once the handler has returned, the middleware has the backend revoke the token
+//! presented with the request, if any, and then either issue a token for the new identity and store
+//! it in the identity cookie, or remove the cookie.
+//!
+//! # Examples
+//! ```
+//! use actix_web::{
+//!     get,
+//!     identity::{IdentityBackend, IdentityService, RequestIdentity},
+//!     post, App, Error, HttpRequest, HttpResponse,
+//! };
+//! use futures_util::future::{ok, LocalBoxFuture};
+//!
+//! /// Uses the user ID as token. Real backends must issue tokens that cannot be forged.
+//! struct Insecure;
+//!
+//! impl IdentityBackend for Insecure {
+//!     fn validate(
+//!         &self,
+//!         token: String,
+//!         _req: &HttpRequest,
+//!     ) -> LocalBoxFuture<'static, Result<Option<String>, Error>> {
+//!         Box::pin(ok(Some(token)))
+//!     }
+//!
+//!     fn issue(&self, id: String) -> LocalBoxFuture<'static, Result<String, Error>> {
+//!         Box::pin(ok(id))
+//!     }
+//! }
+//!
+//! #[get("/")]
+//! async fn index(identity: RequestIdentity) -> String {
+//!     match identity.id() {
+//!         Some(id) => format!("Welcome back, {id}!"),
+//!         None => "Welcome, stranger!".to_owned(),
+//!     }
+//! }
+//!
+//! #[post("/login")]
+//! async fn login(identity: RequestIdentity) -> HttpResponse {
+//!     identity.login("alice");
+//!     HttpResponse::NoContent().finish()
+//! }
+//!
+//! let app = App::new()
+//!     .wrap(IdentityService::new(Insecure))
+//!     .service(index)
+//!     .service(login);
+//! ```
+
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use actix_utils::future::{ok, ready, Either, Ready};
+use cookie::{time::Duration, Cookie, SameSite};
+use derive_more::derive::{Display, Error};
+use futures_core::future::LocalBoxFuture;
+
+use crate::{
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    error::as_std_error,
+    http::{header, StatusCode},
+    Error, FromRequest, HttpMessage as _, HttpRequest, ResponseError,
+};
+
+/// Validates and issues the tokens that identify users.
+///
+/// Futures returned by the backend must be `'static`; clone anything needed from the request, such
+/// as a database pool registered as app data, before returning.
+///
+/// See the [module docs](self) for an example.
+pub trait IdentityBackend: 'static {
+    /// Returns the ID of the user that `token` belongs to.
+    ///
+    /// Returning `Ok(None)` treats the request as anonymous, which is appropriate for tokens that
+    /// are invalid or expired. Errors are returned to the client instead.
+    fn validate(
+        &self,
+        token: String,
+        req: &HttpRequest,
+    ) -> LocalBoxFuture<'static, Result<Option<String>, Error>>;
+
+    /// Returns a new token for the user with the given ID.
+    ///
+    /// Called after a handler that used [`RequestIdentity::login()`] has returned. The token is
+    /// stored in the identity cookie.
+    fn issue(&self, id: String) -> LocalBoxFuture<'static, Result<String, Error>>;
+
+    /// Invalidates `token`.
+    ///
+    /// Called with the token presented with the request after a handler that used
+    /// [`RequestIdentity::login()`] or [`RequestIdentity::logout()`] has returned. Does nothing by
+    /// default, which is appropriate for self-contained tokens that expire on their own.
+    fn revoke(&self, token: String) -> LocalBoxFuture<'static, Result<(), Error>> {
+        let _ = token;
+        Box::pin(ok(()))
+    }
+}
+
+/// Errors that can occur when extracting a [`RequestIdentity`].
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum IdentityError {
+    /// The [`IdentityService`] middleware is not registered for the resource.
+    #[display("Identity middleware is missing")]
+    MissingMiddleware,
+}
+
+impl ResponseError for IdentityError {
+    as_std_error!();
+
+    fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+/// Middleware for resolving the identity of the user making a request.
+///
+/// The identity cookie is named `id`, is scoped to the path `/`, and is sent with the `Secure`,
+/// `HttpOnly`, and `SameSite=Lax` attributes by default.
+///
+/// See the [module docs](self) for an example.
+#[derive(Clone)]
+pub struct IdentityService {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    backend: Box<dyn IdentityBackend>,
+    cookie_name: String,
+    cookie_path: String,
+    cookie_domain: Option<String>,
+    cookie_secure: bool,
+    cookie_same_site: SameSite,
+    cookie_max_age: Option<Duration>,
+    authorization_header: bool,
+}
+
+impl IdentityService {
+    /// Constructs new identity middleware that uses `backend` to validate and issue tokens.
+    pub fn new(backend: impl IdentityBackend) -> Self {
+        Self {
+            inner: Rc::new(Inner {
+                backend: Box::new(backend),
+                cookie_name: "id".to_owned(),
+                cookie_path: "/".to_owned(),
+                cookie_domain: None,
+                cookie_secure: true,
+                cookie_same_site: SameSite::Lax,
+                cookie_max_age: None,
+                authorization_header: true,
+            }),
+        }
+    }
+
+    /// Sets the name of the identity cookie.
+    pub fn cookie_name(mut self, name: impl Into<String>) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .cookie_name = name.into();
+        self
+    }
+
+    /// Sets the `Path` attribute of the identity cookie.
+    pub fn cookie_path(mut self, path: impl Into<String>) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .cookie_path = path.into();
+        self
+    }
+
+    /// Sets the `Domain` attribute of the identity cookie.
+    pub fn cookie_domain(mut self, domain: impl Into<String>) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .cookie_domain = Some(domain.into());
+        self
+    }
+
+    /// Sets whether the identity cookie is only sent over HTTPS.
+    ///
+    /// Defaults to true.
+    pub fn cookie_secure(mut self, secure: bool) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .cookie_secure = secure;
+        self
+    }
+
+    /// Sets the `SameSite` attribute of the identity cookie.
+    ///
+    /// Defaults to `Lax`.
+    pub fn cookie_same_site(mut self, same_site: SameSite) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .cookie_same_site = same_site;
+        self
+    }
+
+    /// Sets the `Max-Age` attribute of the identity cookie.
+    ///
+    /// By default, the identity cookie is a session cookie.
+    pub fn cookie_max_age(mut self, max_age: Duration) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .cookie_max_age = Some(max_age);
+        self
+    }
+
+    /// Sets whether `Bearer` tokens in the `Authorization` header are accepted.
+    ///
+    /// When accepted, they take precedence over the identity cookie. Defaults to true.
+    pub fn authorization_header(mut self, enabled: bool) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .authorization_header = enabled;
+        self
+    }
+}
+
+impl fmt::Debug for IdentityService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdentityService")
+            .field("cookie_name", &self.inner.cookie_name)
+            .field("authorization_header", &self.inner.authorization_header)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Inner {
+    fn token(&self, req: &ServiceRequest) -> Option<String> {
+        let bearer = self
+            .authorization_header
+            .then(|| req.headers().get(header::AUTHORIZATION))
+            .flatten()
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| {
+                let (scheme, token) = value.split_once(' ')?;
+                scheme
+                    .eq_ignore_ascii_case("bearer")
+                    .then_some(token.trim())
+            })
+            .filter(|token| !token.is_empty());
+
+        match bearer {
+            Some(token) => Some(token.to_owned()),
+            None => req
+                .cookie(&self.cookie_name)
+                .map(|cookie| cookie.value().to_owned())
+                .filter(|token| !token.is_empty()),
+        }
+    }
+
+    fn cookie(&self, value: String) -> Cookie<'static> {
+        let mut cookie = Cookie::build(self.cookie_name.clone(), value)
+            .path(self.cookie_path.clone())
+            .secure(self.cookie_secure)
+            .http_only(true)
+            .same_site(self.cookie_same_site)
+            .finish();
+
+        if let Some(domain) = &self.cookie_domain {
+            cookie.set_domain(domain.clone());
+        }
+
+        if let Some(max_age) = self.cookie_max_age {
+            cookie.set_max_age(max_age);
+        }
+
+        cookie
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for IdentityService
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = IdentityMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(IdentityMiddleware {
+            service: Rc::new(service),
+            inner: Rc::clone(&self.inner),
+        }))
+    }
+}
+
+#[doc(hidden)]
+pub struct IdentityMiddleware<S> {
+    service: Rc<S>,
+    inner: Rc<Inner>,
+}
+
+impl<S, B> Service<ServiceRequest> for IdentityMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let state = Rc::new(State {
+            inner: Rc::clone(&self.inner),
+            token: self.inner.token(&req),
+            id: RefCell::new(None),
+            change: RefCell::new(None),
+        });
+        req.extensions_mut().insert(Rc::clone(&state));
+
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+
+            let change = state.change.borrow_mut().take();
+            let inner = &state.inner;
+
+            match change {
+                Some(Change::Login(id)) => {
+                    // never keep a token that was set before login, e.g., by an attacker
+                    if let Some(token) = state.token.clone() {
+                        inner.backend.revoke(token).await?;
+                    }
+
+                    let token = inner.backend.issue(id).await?;
+                    res.response_mut().add_cookie(&inner.cookie(token))?;
+                }
+
+                Some(Change::Logout) => {
+                    if let Some(token) = state.token.clone() {
+                        inner.backend.revoke(token).await?;
+                    }
+                    res.response_mut()
+                        .add_removal_cookie(&inner.cookie(String::new()))?;
+                }
+
+                None => {}
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+/// Per-request identity state, shared between the middleware and extractors.
+struct State {
+    inner: Rc<Inner>,
+
+    /// Token presented by the client.
+    token: Option<String>,
+
+    /// Validated user ID; `None` until first extracted.
+    id: RefCell<Option<Option<String>>>,
+
+    /// Change requested by the handler, applied once it has returned.
+    change: RefCell<Option<Change>>,
+}
+
+enum Change {
+    Login(String),
+    Logout,
+}
+
+/// Extractor for the identity of the user making a request.
+///
+/// Requests without a token, or with a token that the [`IdentityBackend`] does not accept, are
+/// anonymous. Fails with a `500 Internal Server Error` if the [`IdentityService`] middleware is not
+/// registered.
+///
+/// See the [module docs](self) for an example.
+#[derive(Clone)]
+pub struct RequestIdentity {
+    state: Rc<State>,
+}
+
+impl RequestIdentity {
+    /// Returns the ID of the user making the request, or `None` if the request is anonymous.
+    ///
+    /// Reflects calls to [`login()`](Self::login()) and [`logout()`](Self::logout()) made while
+    /// handling the request.
+    pub fn id(&self) -> Option<String> {
+        self.state.id.borrow().clone().flatten()
+    }
+
+    /// Logs in the user with the given ID.
+    ///
+    /// Once the handler has returned, the token presented with the request is revoked and a new
+    /// token for the user is issued and stored in the identity cookie.
+    pub fn login(&self, id: impl Into<String>) {
+        let id = id.into();
+        *self.state.id.borrow_mut() = Some(Some(id.clone()));
+        *self.state.change.borrow_mut() = Some(Change::Login(id));
+    }
+
+    /// Logs out the user.
+    ///
+    /// The token presented with the request is revoked and the identity cookie is removed once the
+    /// handler has returned.
+    pub fn logout(&self) {
+        *self.state.id.borrow_mut() = Some(None);
+        *self.state.change.borrow_mut() = Some(Change::Logout);
+    }
+}
+
+impl fmt::Debug for RequestIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RequestIdentity").field(&self.id()).finish()
+    }
+}
+
+impl FromRequest for RequestIdentity {
+    type Error = Error;
+    type Future = Either<Ready<Result<Self, Error>>, LocalBoxFuture<'static, Result<Self, Error>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let state = match req.extensions().get::<Rc<State>>() {
+            Some(state) => Rc::clone(state),
+            None => {
+                log::debug!(
+                    "Failed to extract `RequestIdentity`: `IdentityService` middleware is not registered"
+                );
+                return Either::left(ready(Err(IdentityError::MissingMiddleware.into())));
+            }
+        };
+
+        if state.id.borrow().is_some() {
+            return Either::left(ready(Ok(RequestIdentity { state })));
+        }
+
+        let Some(token) = state.token.clone() else {
+            *state.id.borrow_mut() = Some(None);
+            return Either::left(ready(Ok(RequestIdentity { state })));
+        };
+
+        let validate = state.inner.backend.validate(token, req);
+
+        Either::right(Box::pin(async move {
+            let id = validate.await?;
+
+            // a concurrent extraction or a login/logout may have settled the identity already
+            state.id.borrow_mut().get_or_insert(id);
+
+            Ok(RequestIdentity { state })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App,
+    };
+
+    /// Accepts tokens of the form `token-<id>`, except those listed as revoked.
+    #[derive(Clone, Default)]
+    struct TestBackend {
+        revoked: Rc<RefCell<HashSet<String>>>,
+    }
+
+    impl IdentityBackend for TestBackend {
+        fn validate(
+            &self,
+            token: String,
+            _req: &HttpRequest,
+        ) -> LocalBoxFuture<'static, Result<Option<String>, Error>> {
+            let id = if self.revoked.borrow().contains(&token) {
+                None
+            } else {
+                token.strip_prefix("token-").map(str::to_owned)
+            };
+            Box::pin(ok(id))
+        }
+
+        fn issue(&self, id: String) -> LocalBoxFuture<'static, Result<String, Error>> {
+            Box::pin(ok(format!("token-{id}")))
+        }
+
+        fn revoke(&self, token: String) -> LocalBoxFuture<'static, Result<(), Error>> {
+            self.revoked.borrow_mut().insert(token);
+            Box::pin(ok(()))
+        }
+    }
+
+    async fn whoami(identity: RequestIdentity) -> String {
+        identity.id().unwrap_or_else(|| "anonymous".to_owned())
+    }
+
+    #[actix_rt::test]
+    async fn resolves_identity() {
+        let app = init_service(
+            App::new()
+                .wrap(IdentityService::new(TestBackend::default()))
+                .default_service(web::to(whoami)),
+        )
+        .await;
+
+        let req = TestRequest::default().to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(read_body(res).await, "anonymous");
+
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Bearer token-alice"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(read_body(res).await, "alice");
+
+        let req = TestRequest::default()
+            .cookie(Cookie::new("id", "token-bob"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(read_body(res).await, "bob");
+
+        // header takes precedence over cookie
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "bearer token-alice"))
+            .cookie(Cookie::new("id", "token-bob"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(read_body(res).await, "alice");
+
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Bearer forged"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(read_body(res).await, "anonymous");
+
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Basic dXNlcjpwYXNz"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(read_body(res).await, "anonymous");
+    }
+
+    #[actix_rt::test]
+    async fn authorization_header_can_be_disabled() {
+        let app = init_service(
+            App::new()
+                .wrap(IdentityService::new(TestBackend::default()).authorization_header(false))
+                .default_service(web::to(whoami)),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Bearer token-alice"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(read_body(res).await, "anonymous");
+    }
+
+    #[actix_rt::test]
+    async fn login_and_logout() {
+        let backend = TestBackend::default();
+
+        let app = init_service(
+            App::new()
+                .wrap(
+                    IdentityService::new(backend.clone())
+                        .cookie_name("session")
+                        .cookie_secure(false),
+                )
+                .route(
+                    "/login",
+                    web::post().to(|identity: RequestIdentity| async move {
+                        identity.login("alice");
+                        identity.id().unwrap()
+                    }),
+                )
+                .route(
+                    "/logout",
+                    web::post().to(|identity: RequestIdentity| async move {
+                        identity.logout();
+                        format!("{:?}", identity.id())
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::post().uri("/login").to_request();
+        let res = call_service(&app, req).await;
+        let cookie = res
+            .response()
+            .cookies()
+            .find(|cookie| cookie.name() == "session")
+            .unwrap()
+            .into_owned();
+        assert_eq!(cookie.value(), "token-alice");
+        assert_eq!(cookie.http_only(), Some(true));
+        assert_eq!(cookie.path(), Some("/"));
+        assert_eq!(read_body(res).await, "alice");
+
+        let req = TestRequest::post()
+            .uri("/logout")
+            .cookie(cookie)
+            .to_request();
+        let res = call_service(&app, req).await;
+        let cookie = res
+            .response()
+            .cookies()
+            .find(|cookie| cookie.name() == "session")
+            .unwrap()
+            .into_owned();
+        assert_eq!(cookie.value(), "");
+        assert_eq!(cookie.max_age(), Some(Duration::ZERO));
+        assert_eq!(read_body(res).await, "None");
+        assert!(backend.revoked.borrow().contains("token-alice"));
+    }
+
+    #[actix_rt::test]
+    async fn login_revokes_presented_token() {
+        let backend = TestBackend::default();
+
+        let app = init_service(
+            App::new()
+                .wrap(IdentityService::new(backend.clone()))
+                .route(
+                    "/login",
+                    web::post().to(|identity: RequestIdentity| async move {
+                        identity.login("alice");
+                        "logged in"
+                    }),
+                )
+                .default_service(web::to(whoami)),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .uri("/login")
+            .cookie(Cookie::new("id", "token-mallory"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        let cookie = res
+            .response()
+            .cookies()
+            .find(|cookie| cookie.name() == "id")
+            .unwrap()
+            .into_owned();
+        assert_eq!(cookie.value(), "token-alice");
+        assert!(backend.revoked.borrow().contains("token-mallory"));
+
+        let req = TestRequest::default()
+            .cookie(Cookie::new("id", "token-mallory"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(read_body(res).await, "anonymous");
+    }
+
+    #[actix_rt::test]
+    async fn missing_middleware() {
+        let app = init_service(App::new().default_service(web::to(whoami))).await;
+
+        let req = TestRequest::default().to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -1,12 +1,16 @@
-use std::{convert::Infallible, net::SocketAddr};
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
 
 use actix_utils::future::{err, ok, Ready};
 use derive_more::derive::{Display, Error};
 
 use crate::{
     dev::{AppConfig, Payload, RequestHead},
+    error::as_std_error,
     http::{
-        header::{self, HeaderName},
+        header::{self, Forwarded, HeaderName},
         uri::{Authority, Scheme},
     },
     FromRequest, HttpRequest, ResponseError,
@@ -16,11 +20,6 @@ static X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
 static X_FORWARDED_HOST: HeaderName = HeaderName::from_static("x-forwarded-host");
 static X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
 
-/// Trim whitespace then any quote marks.
-fn unquote(val: &str) -> &str {
-    val.trim().trim_start_matches('"').trim_end_matches('"')
-}
-
 /// Remove port and IPv6 square brackets from a peer specification.
 fn bare_address(val: &str) -> &str {
     if val.starts_with('[') {
@@ -42,6 +41,127 @@ fn first_header_value<'a>(req: &'a RequestHead, name: &'_ HeaderName) -> Option<
     Some(val)
 }
 
+/// Extracts and trims all comma-separated values across all headers with given name.
+fn all_header_values<'a>(
+    req: &'a RequestHead,
+    name: &'_ HeaderName,
+) -> impl Iterator<Item = &'a str> {
+    req.headers
+        .get_all(name)
+        .filter_map(|hdr| hdr.to_str().ok())
+        .flat_map(|val| val.split(','))
+        .map(str::trim)
+}
+
+/// Set of proxy addresses trusted to provide forwarding headers.
+///
+/// When registered as app data, [`ConnectionInfo`] only considers the `Forwarded` and
+/// `X-Forwarded-*` headers if the peer address of the connection is within one of the trusted
+/// address ranges. The [real IP](ConnectionInfo::realip_remote_addr) is then resolved by walking
+/// the chain of forwarded addresses from the nearest proxy and taking the first address that is not
+/// a trusted proxy itself.
+///
+/// Without this app data, forwarding headers are always considered, regardless of which peer sent
+/// them.
+///
+/// # Examples
+/// ```
+/// use actix_web::{dev::TrustedProxies, App};
+///
+/// let app = App::new().app_data(
+///     TrustedProxies::new()
+///         .add_range("10.0.0.0/8")
+///         .add_range("fd00::/8")
+///         .add_range("127.0.0.1"),
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    ranges: Vec<IpRange>,
+}
+
+impl TrustedProxies {
+    /// Constructs an empty set of trusted proxies.
+    ///
+    /// Registering an empty set causes forwarding headers to be ignored for all requests.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trusts an address range in CIDR notation (e.g., `192.168.0.0/16`) or a single IP address.
+    ///
+    /// # Panics
+    /// Panics if `cidr` is not a valid IP address or CIDR range.
+    pub fn add_range(mut self, cidr: &str) -> Self {
+        match IpRange::parse(cidr) {
+            Some(range) => self.ranges.push(range),
+            None => panic!("Invalid trusted proxy address range: {cidr}"),
+        }
+
+        self
+    }
+
+    /// Returns true if `ip` is within one of the trusted address ranges.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.ranges.iter().any(|range| range.contains(ip))
+    }
+
+    /// Returns true if `addr` is a forwarded address within one of the trusted address ranges.
+    ///
+    /// Obfuscated identifiers and unknown addresses are never trusted.
+    fn contains_forwarded(&self, addr: &str) -> bool {
+        addr.parse()
+            .or_else(|_| bare_address(addr).parse())
+            .is_ok_and(|ip| self.contains(ip))
+    }
+}
+
+/// An IP address range in CIDR notation.
+#[derive(Debug, Clone, Copy)]
+struct IpRange {
+    addr: IpAddr,
+    prefix_len: u32,
+}
+
+impl IpRange {
+    fn parse(cidr: &str) -> Option<Self> {
+        let (addr, prefix_len) = match cidr.trim().split_once('/') {
+            Some((addr, prefix_len)) => (addr.parse::<IpAddr>().ok()?, Some(prefix_len)),
+            None => (cidr.trim().parse::<IpAddr>().ok()?, None),
+        };
+
+        let max_len = if addr.is_ipv4() { 32 } else { 128 };
+
+        let prefix_len = match prefix_len {
+            Some(len) => len.parse().ok().filter(|len| *len <= max_len)?,
+            None => max_len,
+        };
+
+        Some(Self { addr, prefix_len })
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => prefix_eq(
+                u32::from(net).into(),
+                u32::from(ip).into(),
+                self.prefix_len,
+                32,
+            ),
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                prefix_eq(u128::from(net), u128::from(ip), self.prefix_len, 128)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Compares the leading `prefix_len` bits of two `bits`-wide addresses.
+fn prefix_eq(a: u128, b: u128, prefix_len: u32, bits: u32) -> bool {
+    prefix_len == 0 || (a ^ b) >> (bits - prefix_len) == 0
+}
+
 /// HTTP connection information.
 ///
 /// `ConnectionInfo` implements `FromRequest` and can be extracted in handlers.
@@ -62,14 +182,17 @@ fn first_header_value<'a>(req: &'a RequestHead, name: &'_ HeaderName) -> Option<
 /// ```
 ///
 /// # Implementation Notes
-/// Parses `Forwarded` header information according to [RFC 7239][rfc7239] but does not try to
-/// interpret the values for each property. As such, the getter methods on `ConnectionInfo` return
-/// strings instead of IP addresses or other types to acknowledge that they may be
-/// [obfuscated][rfc7239-63] or [unknown][rfc7239-62].
+/// Parses `Forwarded` header information according to [RFC 7239][rfc7239] using the typed
+/// [`Forwarded`] header. The getter methods on `ConnectionInfo` return strings instead of IP
+/// addresses or other types to acknowledge that forwarded addresses may be
+/// [obfuscated][rfc7239-63] or [unknown][rfc7239-62]. A malformed `Forwarded` header is ignored.
 ///
 /// If the older, related headers are also present (eg. `X-Forwarded-For`), then `Forwarded`
 /// is preferred.
 ///
+/// Forwarding headers can be set by any client. Register [`TrustedProxies`] as app data to only
+/// accept them from known proxies.
+///
 /// [rfc7239]: https://datatracker.ietf.org/doc/html/rfc7239
 /// [rfc7239-62]: https://datatracker.ietf.org/doc/html/rfc7239#section-6.2
 /// [rfc7239-63]: https://datatracker.ietf.org/doc/html/rfc7239#section-6.3
@@ -82,28 +205,31 @@ pub struct ConnectionInfo {
 }
 
 impl ConnectionInfo {
-    pub(crate) fn new(req: &RequestHead, cfg: &AppConfig) -> ConnectionInfo {
+    pub(crate) fn new(
+        req: &RequestHead,
+        cfg: &AppConfig,
+        trusted_proxies: Option<&TrustedProxies>,
+    ) -> ConnectionInfo {
         let mut host = None;
         let mut scheme = None;
-        let mut realip_remote_addr = None;
-
-        for (name, val) in req
-            .headers
-            .get_all(&header::FORWARDED)
-            .filter_map(|hdr| hdr.to_str().ok())
-            // "for=1.2.3.4, for=5.6.7.8; scheme=https"
-            .flat_map(|val| val.split(';'))
-            // ["for=1.2.3.4, for=5.6.7.8", " scheme=https"]
-            .flat_map(|vals| vals.split(','))
-            // ["for=1.2.3.4", " for=5.6.7.8", " scheme=https"]
-            .flat_map(|pair| {
-                let mut items = pair.trim().splitn(2, '=');
-                Some((items.next()?, items.next()?))
-            })
-        {
-            // [(name , val      ), ...                                    ]
-            // [("for", "1.2.3.4"), ("for", "5.6.7.8"), ("scheme", "https")]
-
+        let mut forwarded_for = Vec::new();
+
+        // forwarding headers are only considered when sent directly by a trusted proxy
+        let use_forwarded = match trusted_proxies {
+            Some(trusted) => req
+                .peer_addr
+                .is_some_and(|addr| trusted.contains(addr.ip())),
+            None => true,
+        };
+
+        // malformed headers are ignored, falling back to the `X-Forwarded-*` headers
+        let forwarded = if use_forwarded {
+            Forwarded::from_raw(req.headers.get_all(&header::FORWARDED)).unwrap_or_default()
+        } else {
+            Forwarded::default()
+        };
+
+        for element in forwarded.iter() {
             // taking the first value for each property is correct because spec states that first
             // "for" value is client and rest are proxies; multiple values other properties have
             // no defined semantics
@@ -113,35 +239,50 @@ impl ConnectionInfo {
             // > made, followed by any subsequent proxy identifiers.
             // --- https://datatracker.ietf.org/doc/html/rfc7239#section-5.2
 
-            match name.trim().to_lowercase().as_str() {
-                "for" => realip_remote_addr.get_or_insert_with(|| bare_address(unquote(val))),
-                "proto" => scheme.get_or_insert_with(|| unquote(val)),
-                "host" => host.get_or_insert_with(|| unquote(val)),
-                "by" => {
-                    // TODO: implement https://datatracker.ietf.org/doc/html/rfc7239#section-5.1
-                    continue;
-                }
-                _ => continue,
-            };
+            if let Some(node) = element.for_node() {
+                forwarded_for.push(node.name().to_string());
+            }
+
+            scheme = scheme.or(element.proto());
+            host = host.or(element.host());
         }
 
+        let forwarded_header = |name| {
+            if use_forwarded {
+                first_header_value(req, name)
+            } else {
+                None
+            }
+        };
+
         let scheme = scheme
-            .or_else(|| first_header_value(req, &X_FORWARDED_PROTO))
+            .or_else(|| forwarded_header(&X_FORWARDED_PROTO))
             .or_else(|| req.uri.scheme().map(Scheme::as_str))
             .or_else(|| Some("https").filter(|_| cfg.secure()))
             .unwrap_or("http")
             .to_owned();
 
         let host = host
-            .or_else(|| first_header_value(req, &X_FORWARDED_HOST))
+            .or_else(|| forwarded_header(&X_FORWARDED_HOST))
             .or_else(|| req.headers.get(&header::HOST)?.to_str().ok())
             .or_else(|| req.uri.authority().map(Authority::as_str))
             .unwrap_or_else(|| cfg.host())
             .to_owned();
 
-        let realip_remote_addr = realip_remote_addr
-            .or_else(|| first_header_value(req, &X_FORWARDED_FOR))
-            .map(str::to_owned);
+        if forwarded_for.is_empty() && use_forwarded {
+            forwarded_for.extend(all_header_values(req, &X_FORWARDED_FOR).map(str::to_owned));
+        }
+
+        let realip_remote_addr = match trusted_proxies {
+            // skip over trusted proxies, starting from the one nearest to this server
+            Some(trusted) => forwarded_for
+                .iter()
+                .rev()
+                .find(|addr| !trusted.contains_forwarded(addr))
+                .or(forwarded_for.first()),
+            None => forwarded_for.first(),
+        }
+        .cloned();
 
         let peer_addr = req.peer_addr.map(|addr| addr.ip().to_string());
 
@@ -162,8 +303,9 @@ impl ConnectionInfo {
     ///
     /// # Security
     /// Do not use this function for security purposes unless you can be sure that the `Forwarded`
-    /// and `X-Forwarded-For` headers cannot be spoofed by the client. If you are running without a
-    /// proxy then [obtaining the peer address](Self::peer_addr) would be more appropriate.
+    /// and `X-Forwarded-For` headers cannot be spoofed by the client, e.g., by registering
+    /// [`TrustedProxies`] as app data. If you are running without a proxy then
+    /// [obtaining the peer address](Self::peer_addr) would be more appropriate.
     #[inline]
     pub fn realip_remote_addr(&self) -> Option<&str> {
         self.realip_remote_addr
@@ -250,7 +392,9 @@ impl PeerAddr {
 #[display("Missing peer address")]
 pub struct MissingPeerAddr;
 
-impl ResponseError for MissingPeerAddr {}
+impl ResponseError for MissingPeerAddr {
+    as_std_error!();
+}
 
 impl FromRequest for PeerAddr {
     type Error = MissingPeerAddr;
@@ -413,6 +557,29 @@ mod tests {
         assert_eq!(info.realip_remote_addr(), Some("192.0.2.60"));
     }
 
+    #[test]
+    fn forwarded_for_obfuscated() {
+        let req = TestRequest::default()
+            .insert_header((header::FORWARDED, "for=_hidden:_port, for=192.0.2.60"))
+            .to_http_request();
+        let info = req.connection_info();
+        assert_eq!(info.realip_remote_addr(), Some("_hidden"));
+    }
+
+    #[test]
+    fn forwarded_malformed() {
+        let req = TestRequest::default()
+            .insert_header((
+                header::FORWARDED,
+                "for=192.0.2.60; host=rust-lang.org; for=1.2.3.4",
+            ))
+            .insert_header((X_FORWARDED_FOR, "192.0.2.61"))
+            .to_http_request();
+        let info = req.connection_info();
+        assert_eq!(info.realip_remote_addr(), Some("192.0.2.61"));
+        assert_eq!(info.host(), "localhost:8080");
+    }
+
     #[test]
     fn scheme_from_uri() {
         let req = TestRequest::get()
@@ -441,6 +608,88 @@ mod tests {
         assert_eq!(info.host(), "actix.rs");
     }
 
+    #[test]
+    fn trusted_proxy_ranges() {
+        let trusted = TrustedProxies::new()
+            .add_range("10.0.0.0/8")
+            .add_range("192.168.1.1")
+            .add_range("fd00::/8");
+
+        assert!(trusted.contains("10.1.2.3".parse().unwrap()));
+        assert!(trusted.contains("192.168.1.1".parse().unwrap()));
+        assert!(trusted.contains("fd12::1".parse().unwrap()));
+        assert!(trusted.contains("::ffff:10.0.0.1".parse().unwrap()));
+        assert!(!trusted.contains("11.0.0.1".parse().unwrap()));
+        assert!(!trusted.contains("192.168.1.2".parse().unwrap()));
+        assert!(!trusted.contains("fe80::1".parse().unwrap()));
+
+        assert!(TrustedProxies::new()
+            .add_range("0.0.0.0/0")
+            .contains("1.2.3.4".parse().unwrap()));
+        assert!(!TrustedProxies::new().contains("1.2.3.4".parse().unwrap()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn trusted_proxy_invalid_range() {
+        TrustedProxies::new().add_range("10.0.0.0/33");
+    }
+
+    #[test]
+    fn untrusted_peer_ignores_forwarded_headers() {
+        let req = TestRequest::default()
+            .app_data(TrustedProxies::new().add_range("10.0.0.0/8"))
+            .peer_addr("203.0.113.7:4000".parse().unwrap())
+            .insert_header((header::HOST, "actix.rs"))
+            .insert_header((
+                header::FORWARDED,
+                "for=192.0.2.60; proto=https; host=evil.com",
+            ))
+            .insert_header((X_FORWARDED_FOR, "192.0.2.61"))
+            .insert_header((X_FORWARDED_HOST, "evil.com"))
+            .to_http_request();
+
+        let info = req.connection_info();
+        assert_eq!(info.scheme(), "http");
+        assert_eq!(info.host(), "actix.rs");
+        assert_eq!(info.realip_remote_addr(), Some("203.0.113.7"));
+    }
+
+    #[test]
+    fn trusted_peer_uses_forwarded_headers() {
+        let req = TestRequest::default()
+            .app_data(TrustedProxies::new().add_range("10.0.0.0/8"))
+            .peer_addr("10.0.0.2:4000".parse().unwrap())
+            .insert_header((X_FORWARDED_FOR, "198.51.100.1, 192.0.2.60, 10.0.0.1"))
+            .insert_header((X_FORWARDED_PROTO, "https"))
+            .to_http_request();
+
+        let info = req.connection_info();
+        assert_eq!(info.scheme(), "https");
+        // spoofed leftmost value is skipped in favor of the address the trusted proxy saw
+        assert_eq!(info.realip_remote_addr(), Some("192.0.2.60"));
+
+        let req = TestRequest::default()
+            .app_data(TrustedProxies::new().add_range("10.0.0.0/8"))
+            .peer_addr("10.0.0.2:4000".parse().unwrap())
+            .insert_header((
+                header::FORWARDED,
+                r#"for=192.0.2.60, for="[2001:db8::17]:4711", for=10.0.0.1"#,
+            ))
+            .to_http_request();
+        let info = req.connection_info();
+        assert_eq!(info.realip_remote_addr(), Some("2001:db8::17"));
+
+        let req = TestRequest::default()
+            .app_data(TrustedProxies::new().add_range("10.0.0.0/8"))
+            .peer_addr("10.0.0.2:4000".parse().unwrap())
+            .insert_header((X_FORWARDED_FOR, "10.0.0.9, 10.0.0.1"))
+            .to_http_request();
+        let info = req.connection_info();
+        // all forwarded addresses are trusted so take the first
+        assert_eq!(info.realip_remote_addr(), Some("10.0.0.9"));
+    }
+
     #[actix_rt::test]
     async fn conn_info_extract() {
         let req = TestRequest::default()
@@ -69,6 +69,11 @@
 //! - `rustls-0_22` - HTTPS support via `rustls` 0.22 crate, supports `HTTP/2`
 //! - `rustls-0_23` - HTTPS support via `rustls` 0.23 crate, supports `HTTP/2`
 //! - `secure-cookies` - secure cookies support
+//! - `webhook` - signed webhook verification
+//! - `nonce` - single-use nonces for replay protection
+//! - `cbor` - CBOR serialization support for the `Negotiate` responder
+//! - `xml` - XML extractor and responder
+//! - `anyhow` - conversion of `anyhow` errors into [`Error`]
 
 #![doc(html_logo_url = "https://actix.rs/img/logo.png")]
 #![doc(html_favicon_url = "https://actix.rs/favicon.ico")]
@@ -81,20 +86,30 @@ pub use cookie;
 
 mod app;
 mod app_service;
+mod cache;
+mod clock;
 mod config;
 mod data;
 pub mod dev;
 pub mod error;
 mod extract;
+pub mod feature_flags;
 pub mod guard;
 mod handler;
 mod helpers;
 pub mod http;
+#[cfg(feature = "cookies")]
+pub mod identity;
 mod info;
 pub mod middleware;
+#[cfg(feature = "nonce")]
+pub mod nonce;
+pub mod openapi;
 mod redirect;
 mod request;
+mod request_context;
 mod request_data;
+mod request_rng;
 mod resource;
 mod response;
 mod rmap;
@@ -103,10 +118,16 @@ pub mod rt;
 mod scope;
 mod server;
 mod service;
+pub mod tenant;
 pub mod test;
 mod thin_data;
+pub mod transaction;
 pub(crate) mod types;
+mod upload_progress;
 pub mod web;
+#[cfg(feature = "webhook")]
+pub mod webhook;
+mod worker_dispatch;
 
 #[doc(inline)]
 pub use crate::error::Result;
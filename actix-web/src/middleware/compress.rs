@@ -4,6 +4,7 @@ use std::{
     future::Future,
     marker::PhantomData,
     pin::Pin,
+    rc::Rc,
     task::{Context, Poll},
 };
 
@@ -16,13 +17,13 @@ use once_cell::sync::Lazy;
 use pin_project_lite::pin_project;
 
 use crate::{
-    body::{EitherBody, MessageBody},
+    body::{BodySize, EitherBody, MessageBody},
     http::{
-        header::{self, AcceptEncoding, ContentEncoding, Encoding, HeaderValue},
+        header::{self, AcceptEncoding, ContentEncoding, Encoding, HeaderMap, HeaderValue},
         StatusCode,
     },
     service::{ServiceRequest, ServiceResponse},
-    Error, HttpMessage, HttpResponse,
+    Error, HttpResponse,
 };
 
 /// Middleware for compressing response payloads.
@@ -44,7 +45,18 @@ use crate::{
 /// perform manual encoding negotiation, if required. When negotiating content encoding, it is also
 /// required by the spec to send a `Vary: Accept-Encoding` header.
 ///
-/// A (naïve) example serving an pre-compressed Gzip file is included below.
+/// A (naïve) example serving an pre-compressed Gzip file is included below. For static files,
+/// `actix-files` can negotiate pre-compressed `.br` and `.gz` variants itself.
+///
+/// # Selecting Responses
+/// By default, all responses are compressed except images, videos, and empty bodies. Responses
+/// can be further restricted to a [minimum size](Self::min_size()) and to a [list of content
+/// types](Self::content_types()).
+///
+/// # Per-Route Configuration
+/// A `Compress` value registered as app data on a resource or scope replaces the middleware's
+/// configuration for responses from that route. Use [`Compress::disabled()`] to opt routes out
+/// of compression, e.g. for event streams that must not be buffered by the encoder.
 ///
 /// # Examples
 /// To enable automatic payload compression just include `Compress` as a top-level middleware:
@@ -56,6 +68,17 @@ use crate::{
 ///     .default_service(web::to(|| async { HttpResponse::Ok().body("hello world") }));
 /// ```
 ///
+/// Only compress text and JSON responses of at least 1KiB:
+/// ```
+/// use actix_web::{middleware::Compress, App};
+///
+/// let app = App::new().wrap(
+///     Compress::default()
+///         .min_size(1024)
+///         .content_types([mime::TEXT_STAR, mime::APPLICATION_JSON]),
+/// );
+/// ```
+///
 /// Pre-compressed Gzip file being served from disk with correct headers added to bypass middleware:
 /// ```no_run
 /// use actix_web::{middleware, http::header, web, App, HttpResponse, Responder};
@@ -71,10 +94,86 @@ use crate::{
 ///     .default_service(web::to(index_handler));
 /// ```
 ///
+/// Opt a route out of compression:
+/// ```
+/// use actix_web::{middleware::Compress, web, App, HttpResponse};
+///
+/// let app = App::new().wrap(Compress::default()).service(
+///     web::resource("/events")
+///         .app_data(Compress::disabled())
+///         .to(|| async { HttpResponse::Ok().body("data: hello\n\n") }),
+/// );
+/// ```
+///
 /// [feature flags]: ../index.html#crate-features
 #[derive(Debug, Clone, Default)]
 #[non_exhaustive]
-pub struct Compress;
+pub struct Compress {
+    min_size: u64,
+    content_types: Option<Vec<Mime>>,
+    disabled: bool,
+}
+
+impl Compress {
+    /// Constructs a configuration that disables compression.
+    ///
+    /// Intended to be registered as resource or scope app data to opt routes out of compression.
+    pub fn disabled() -> Self {
+        Self {
+            disabled: true,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the minimum body size, in bytes, for a response to be compressed.
+    ///
+    /// Responses with a smaller known size are sent uncompressed. Streaming responses of unknown
+    /// size are always eligible for compression. Defaults to 0.
+    pub fn min_size(mut self, size: u64) -> Self {
+        self.min_size = size;
+        self
+    }
+
+    /// Sets the content types of responses that are compressed.
+    ///
+    /// Only responses with a `Content-Type` matching one of the given types are compressed.
+    /// Parameters (e.g., `charset`) are ignored when matching and a `*` subtype matches all
+    /// subtypes of its type (e.g., [`mime::TEXT_STAR`]). By default, all content types except
+    /// images and videos are compressed.
+    pub fn content_types(mut self, types: impl IntoIterator<Item = Mime>) -> Self {
+        self.content_types = Some(types.into_iter().collect());
+        self
+    }
+
+    /// Returns true if a response with the given headers and body size should be compressed.
+    fn should_compress(&self, headers: &HeaderMap, size: BodySize) -> bool {
+        if self.disabled {
+            return false;
+        }
+
+        if matches!(size, BodySize::Sized(size) if size < self.min_size) {
+            return false;
+        }
+
+        let mime = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|hdr| hdr.to_str().ok())
+            .and_then(|hdr| hdr.parse::<Mime>().ok());
+
+        match (&self.content_types, mime) {
+            (Some(types), Some(mime)) => types.iter().any(|ty| {
+                ty.type_() == mime.type_()
+                    && (ty.subtype() == mime::STAR || ty.subtype() == mime.subtype())
+            }),
+
+            // only listed content types are compressed
+            (Some(_), None) => false,
+
+            (None, Some(mime)) => !matches!(mime.type_().as_str(), "image" | "video"),
+            (None, None) => true,
+        }
+    }
+}
 
 impl<S, B> Transform<S, ServiceRequest> for Compress
 where
@@ -88,12 +187,16 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ok(CompressMiddleware { service })
+        ok(CompressMiddleware {
+            service,
+            config: Rc::new(self.clone()),
+        })
     }
 }
 
 pub struct CompressMiddleware<S> {
     service: S,
+    config: Rc<Compress>,
 }
 
 impl<S, B> Service<ServiceRequest> for CompressMiddleware<S>
@@ -111,7 +214,7 @@ where
     #[allow(clippy::borrow_interior_mutable_const)]
     fn call(&self, req: ServiceRequest) -> Self::Future {
         // negotiate content-encoding
-        let accept_encoding = req.get_header::<AcceptEncoding>();
+        let accept_encoding = req.cached_header::<AcceptEncoding>();
 
         let accept_encoding = match accept_encoding {
             // missing header; fallback to identity
@@ -119,6 +222,7 @@ where
                 return Either::left(CompressResponse {
                     encoding: Encoding::identity(),
                     fut: self.service.call(req),
+                    config: Rc::clone(&self.config),
                     _phantom: PhantomData,
                 })
             }
@@ -146,6 +250,7 @@ where
             Some(encoding) => Either::left(CompressResponse {
                 fut: self.service.call(req),
                 encoding,
+                config: Rc::clone(&self.config),
                 _phantom: PhantomData,
             }),
         }
@@ -160,6 +265,7 @@ pin_project! {
         #[pin]
         fut: S::Future,
         encoding: Encoding,
+        config: Rc<Compress>,
         _phantom: PhantomData<B>,
     }
 }
@@ -183,23 +289,12 @@ where
                     }
                 };
 
-                Poll::Ready(Ok(resp.map_body(move |head, body| {
-                    let content_type = head.headers.get(header::CONTENT_TYPE);
-
-                    fn default_compress_predicate(content_type: Option<&HeaderValue>) -> bool {
-                        match content_type {
-                            None => true,
-                            Some(hdr) => {
-                                match hdr.to_str().ok().and_then(|hdr| hdr.parse::<Mime>().ok()) {
-                                    Some(mime) if mime.type_().as_str() == "image" => false,
-                                    Some(mime) if mime.type_().as_str() == "video" => false,
-                                    _ => true,
-                                }
-                            }
-                        }
-                    }
+                // route-level configuration takes precedence over the middleware's
+                let route_config = resp.request().app_data::<Compress>().cloned();
+                let config = route_config.as_ref().unwrap_or(this.config.as_ref());
 
-                    let enc = if default_compress_predicate(content_type) {
+                Poll::Ready(Ok(resp.map_body(move |head, body| {
+                    let enc = if config.should_compress(&head.headers, body.size()) {
                         enc
                     } else {
                         ContentEncoding::Identity
@@ -430,6 +525,103 @@ mod tests {
         assert_eq!(test::read_body(res).await, TEXT_DATA.as_bytes());
     }
 
+    #[actix_rt::test]
+    async fn min_size() {
+        let app = test::init_service(
+            App::new()
+                .wrap(Compress::default().min_size(HTML_DATA.len() as u64))
+                .configure(configure_predicate_test)
+                .route(
+                    "/small",
+                    web::to(|| {
+                        HttpResponse::Ok()
+                            .content_type(ContentType::html())
+                            .body("hi")
+                    }),
+                ),
+        )
+        .await;
+
+        let req =
+            test::TestRequest::with_uri("/html").insert_header((header::ACCEPT_ENCODING, "gzip"));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_successful_gzip_res_with_content_type(&res, "text/html");
+
+        let req =
+            test::TestRequest::with_uri("/small").insert_header((header::ACCEPT_ENCODING, "gzip"));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_successful_identity_res_with_content_type(&res, "text/html");
+        assert_eq!(test::read_body(res).await, "hi");
+    }
+
+    #[actix_rt::test]
+    async fn content_types() {
+        let app = test::init_service(
+            App::new()
+                .wrap(Compress::default().content_types([mime::TEXT_STAR]))
+                .configure(configure_predicate_test)
+                .route(
+                    "/json",
+                    web::to(|| {
+                        HttpResponse::Ok()
+                            .content_type(ContentType::json())
+                            .body(TEXT_DATA)
+                    }),
+                ),
+        )
+        .await;
+
+        let req =
+            test::TestRequest::with_uri("/html").insert_header((header::ACCEPT_ENCODING, "gzip"));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_successful_gzip_res_with_content_type(&res, "text/html");
+
+        let req =
+            test::TestRequest::with_uri("/json").insert_header((header::ACCEPT_ENCODING, "gzip"));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_successful_identity_res_with_content_type(&res, "application/json");
+        assert_eq!(test::read_body(res).await, TEXT_DATA.as_bytes());
+    }
+
+    #[actix_rt::test]
+    async fn route_config() {
+        let app = test::init_service(
+            App::new()
+                .wrap(Compress::default())
+                .configure(configure_predicate_test)
+                .service(
+                    web::resource("/disabled")
+                        .app_data(Compress::disabled())
+                        .to(|| HttpResponse::Ok().body(TEXT_DATA)),
+                )
+                .service(
+                    web::scope("/scope")
+                        .app_data(Compress::default().min_size(HTML_DATA.len() as u64 + 1))
+                        .configure(configure_predicate_test),
+                ),
+        )
+        .await;
+
+        let req =
+            test::TestRequest::with_uri("/html").insert_header((header::ACCEPT_ENCODING, "gzip"));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_successful_gzip_res_with_content_type(&res, "text/html");
+
+        let req = test::TestRequest::with_uri("/disabled")
+            .insert_header((header::ACCEPT_ENCODING, "gzip"));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(!res.headers().contains_key(header::CONTENT_ENCODING));
+        assert!(!res.headers().contains_key(header::VARY));
+        assert_eq!(test::read_body(res).await, TEXT_DATA.as_bytes());
+
+        let req = test::TestRequest::with_uri("/scope/html")
+            .insert_header((header::ACCEPT_ENCODING, "gzip"));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_successful_identity_res_with_content_type(&res, "text/html");
+        assert_eq!(test::read_body(res).await, HTML_DATA.as_bytes());
+    }
+
     #[actix_rt::test]
     async fn prevents_compression_empty() {
         let app = test::init_service({
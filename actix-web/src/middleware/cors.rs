@@ -0,0 +1,638 @@
+//! For middleware documentation, see [`Cors`].
+
+use std::{fmt, rc::Rc, time::Duration};
+
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Ready};
+use futures_core::future::LocalBoxFuture;
+
+use crate::{
+    body::EitherBody,
+    dev::RequestHead,
+    http::{
+        header::{self, HeaderMap, HeaderName, HeaderValue},
+        Method,
+    },
+    service::{ServiceRequest, ServiceResponse},
+    Error, HttpResponse,
+};
+
+type OriginFn = dyn Fn(&HeaderValue, &RequestHead) -> bool;
+
+/// Middleware for Cross-Origin Resource Sharing (CORS).
+///
+/// Allows browsers to make requests to the wrapped services from pages on other origins, according
+/// to the configured policy. By default, no origins are allowed; they are added using:
+/// - [`allowed_origin()`](Self::allowed_origin()) for an exact origin;
+/// - [`allowed_origin_fn()`](Self::allowed_origin_fn()) for a predicate over the `Origin` header;
+/// - [`allow_any_origin()`](Self::allow_any_origin()) for all origins.
+///
+/// Preflight requests (`OPTIONS` requests with an `Access-Control-Request-Method` header) are
+/// answered by the middleware itself, before routing, so routes do not need `OPTIONS` handlers.
+/// Allowed preflights receive a `204 No Content` response listing the allowed methods and headers,
+/// which browsers may cache for [`max_age()`](Self::max_age()). Disallowed preflights receive a
+/// `403 Forbidden` response. Other cross-origin requests are always passed to the wrapped service;
+/// CORS headers are only added to responses for allowed origins, so browsers will not expose
+/// responses to other origins.
+///
+/// Responses include `Vary: Origin` whenever their CORS headers depend on the request's origin.
+///
+/// # Credentials
+/// When [`supports_credentials()`](Self::supports_credentials()) is enabled, requests from origins
+/// allowed by [`allowed_origin()`](Self::allowed_origin()) or
+/// [`allowed_origin_fn()`](Self::allowed_origin_fn()) have their origin echoed back along with
+/// `Access-Control-Allow-Credentials: true`. Origins that are only allowed by
+/// [`allow_any_origin()`](Self::allow_any_origin()) always receive `Access-Control-Allow-Origin: *`
+/// without credentials, so that enabling both never grants credentialed access to arbitrary sites.
+///
+/// # Examples
+/// ```
+/// use actix_web::{http::Method, middleware::Cors, web, App, HttpResponse};
+///
+/// let app = App::new()
+///     .wrap(
+///         Cors::new()
+///             .allowed_origin("https://www.rust-lang.org")
+///             .allowed_origin_fn(|origin, _req_head| {
+///                 origin.as_bytes().ends_with(b".rust-lang.org")
+///             })
+///             .allowed_methods([Method::GET, Method::POST])
+///             .supports_credentials()
+///             .max_age(std::time::Duration::from_secs(3600)),
+///     )
+///     .route("/", web::post().to(HttpResponse::Ok));
+/// ```
+#[derive(Clone)]
+pub struct Cors {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    any_origin: bool,
+    origins: Vec<HeaderValue>,
+    origin_fns: Vec<Box<OriginFn>>,
+    methods: Vec<Method>,
+    headers: Option<Vec<HeaderName>>,
+    expose_headers: Vec<HeaderName>,
+    credentials: bool,
+    max_age: Option<Duration>,
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Self {
+            inner: Rc::new(Inner {
+                any_origin: false,
+                origins: Vec::new(),
+                origin_fns: Vec::new(),
+                methods: vec![
+                    Method::GET,
+                    Method::HEAD,
+                    Method::POST,
+                    Method::PUT,
+                    Method::PATCH,
+                    Method::DELETE,
+                ],
+                headers: None,
+                expose_headers: Vec::new(),
+                credentials: false,
+                max_age: None,
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for Cors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cors")
+            .field("any_origin", &self.inner.any_origin)
+            .field("origins", &self.inner.origins)
+            .field("origin_fns", &self.inner.origin_fns.len())
+            .field("methods", &self.inner.methods)
+            .field("headers", &self.inner.headers)
+            .field("expose_headers", &self.inner.expose_headers)
+            .field("credentials", &self.inner.credentials)
+            .field("max_age", &self.inner.max_age)
+            .finish()
+    }
+}
+
+impl Cors {
+    /// Constructs new CORS middleware that allows no origins.
+    ///
+    /// Once origins are allowed, the `GET`, `HEAD`, `POST`, `PUT`, `PATCH`, and `DELETE` methods
+    /// and any request headers are allowed, without credentials.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Rc::get_mut(&mut self.inner).expect("Multiple copies exist")
+    }
+
+    /// Allows requests from the given origin, e.g. `https://example.com`.
+    ///
+    /// Origins are compared exactly; they consist of a scheme, host, and (non-default) port, and
+    /// have no trailing slash.
+    ///
+    /// # Panics
+    /// Panics if `origin` is not a valid header value.
+    pub fn allowed_origin(mut self, origin: &str) -> Self {
+        let origin = HeaderValue::from_str(origin).expect("Invalid origin");
+        self.inner_mut().origins.push(origin);
+        self
+    }
+
+    /// Allows requests from origins for which the given predicate returns true.
+    ///
+    /// The predicate is called with the `Origin` header and the request head.
+    pub fn allowed_origin_fn<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&HeaderValue, &RequestHead) -> bool + 'static,
+    {
+        self.inner_mut().origin_fns.push(Box::new(f));
+        self
+    }
+
+    /// Allows requests from any origin.
+    ///
+    /// Responses to origins that are only allowed this way never permit credentials. See the
+    /// [type docs](Self#credentials).
+    pub fn allow_any_origin(mut self) -> Self {
+        self.inner_mut().any_origin = true;
+        self
+    }
+
+    /// Sets the methods that cross-origin requests may use, replacing the defaults.
+    pub fn allowed_methods(mut self, methods: impl IntoIterator<Item = Method>) -> Self {
+        self.inner_mut().methods = methods.into_iter().collect();
+        self
+    }
+
+    /// Sets the request headers that cross-origin requests may send.
+    ///
+    /// By default, any headers requested by a preflight request are allowed.
+    pub fn allowed_headers(mut self, headers: impl IntoIterator<Item = HeaderName>) -> Self {
+        self.inner_mut().headers = Some(headers.into_iter().collect());
+        self
+    }
+
+    /// Sets the response headers that browsers may expose to cross-origin scripts, in addition to
+    /// the CORS-safelisted response headers.
+    pub fn expose_headers(mut self, headers: impl IntoIterator<Item = HeaderName>) -> Self {
+        self.inner_mut().expose_headers = headers.into_iter().collect();
+        self
+    }
+
+    /// Allows cross-origin requests to include credentials, such as cookies, from explicitly
+    /// allowed origins.
+    pub fn supports_credentials(mut self) -> Self {
+        self.inner_mut().credentials = true;
+        self
+    }
+
+    /// Sets how long browsers may cache the results of preflight requests.
+    ///
+    /// By default, no `Access-Control-Max-Age` header is sent and browsers use their own default
+    /// (usually 5 seconds).
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.inner_mut().max_age = Some(max_age);
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Cors
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = CorsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CorsMiddleware {
+            service,
+            inner: Rc::clone(&self.inner),
+        }))
+    }
+}
+
+#[doc(hidden)]
+pub struct CorsMiddleware<S> {
+    service: S,
+    inner: Rc<Inner>,
+}
+
+/// How a request's origin is allowed.
+enum Allowed {
+    /// Origin is explicitly allowed; it is echoed back and may be granted credentials.
+    Origin,
+
+    /// Origin is only allowed by [`Cors::allow_any_origin()`].
+    Any,
+}
+
+impl Inner {
+    fn allowed(&self, origin: &HeaderValue, head: &RequestHead) -> Option<Allowed> {
+        if self.origins.contains(origin) || self.origin_fns.iter().any(|f| f(origin, head)) {
+            Some(Allowed::Origin)
+        } else if self.any_origin {
+            Some(Allowed::Any)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if responses differ depending on the request's origin.
+    fn varies_by_origin(&self) -> bool {
+        !self.any_origin || !self.origins.is_empty() || !self.origin_fns.is_empty()
+    }
+
+    fn insert_allow_origin(&self, headers: &mut HeaderMap, origin: &HeaderValue, allowed: Allowed) {
+        match allowed {
+            Allowed::Origin => {
+                headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+
+                if self.credentials {
+                    headers.insert(
+                        header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                        HeaderValue::from_static("true"),
+                    );
+                }
+            }
+
+            Allowed::Any => {
+                headers.insert(
+                    header::ACCESS_CONTROL_ALLOW_ORIGIN,
+                    HeaderValue::from_static("*"),
+                );
+            }
+        }
+    }
+
+    /// Checks the method and headers requested by a preflight request.
+    fn preflight_allowed(&self, head: &RequestHead) -> bool {
+        let method_allowed = head
+            .headers()
+            .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+            .and_then(|method| Method::from_bytes(method.as_bytes()).ok())
+            .is_some_and(|method| self.methods.contains(&method));
+
+        let Some(allowed_headers) = &self.headers else {
+            return method_allowed;
+        };
+
+        method_allowed
+            && requested_headers(head).all(|name| {
+                HeaderName::from_bytes(name.as_bytes())
+                    .is_ok_and(|name| allowed_headers.contains(&name))
+            })
+    }
+
+    fn preflight_response(&self, head: &RequestHead, origin: &HeaderValue) -> HttpResponse {
+        let allowed = self
+            .allowed(origin, head)
+            .filter(|_| self.preflight_allowed(head));
+
+        let mut res = match allowed {
+            Some(_) => HttpResponse::NoContent().finish(),
+            None => HttpResponse::Forbidden().finish(),
+        };
+
+        let headers = res.headers_mut();
+
+        if let Some(allowed) = allowed {
+            self.insert_allow_origin(headers, origin, allowed);
+
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_METHODS,
+                join(self.methods.iter().map(Method::as_str)),
+            );
+
+            let allow_headers = match &self.headers {
+                Some(allowed_headers) => join(allowed_headers.iter().map(HeaderName::as_str)),
+                None => join(requested_headers(head)),
+            };
+
+            if !allow_headers.is_empty() {
+                headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, allow_headers);
+            }
+
+            if let Some(max_age) = self.max_age {
+                headers.insert(
+                    header::ACCESS_CONTROL_MAX_AGE,
+                    HeaderValue::from(max_age.as_secs()),
+                );
+            }
+        }
+
+        if self.varies_by_origin() {
+            append_vary(headers, header::ORIGIN);
+        }
+        append_vary(headers, header::ACCESS_CONTROL_REQUEST_METHOD);
+        append_vary(headers, header::ACCESS_CONTROL_REQUEST_HEADERS);
+
+        res
+    }
+}
+
+/// Iterates over the header names listed in a preflight's `Access-Control-Request-Headers`.
+fn requested_headers(head: &RequestHead) -> impl Iterator<Item = &str> {
+    head.headers()
+        .get_all(header::ACCESS_CONTROL_REQUEST_HEADERS)
+        .filter_map(|val| val.to_str().ok())
+        .flat_map(|val| val.split(','))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+}
+
+/// Joins `items` into a comma-separated header value.
+fn join<'a>(items: impl Iterator<Item = &'a str>) -> HeaderValue {
+    let joined = items.collect::<Vec<_>>().join(", ");
+
+    // items are methods, header names, or parts of valid header values
+    HeaderValue::from_str(&joined).unwrap()
+}
+
+/// Adds `name` to the `Vary` header, unless it is already listed.
+fn append_vary(headers: &mut HeaderMap, name: HeaderName) {
+    let listed = headers
+        .get_all(header::VARY)
+        .filter_map(|val| val.to_str().ok())
+        .flat_map(|val| val.split(','))
+        .map(str::trim)
+        .any(|val| val == "*" || val.eq_ignore_ascii_case(name.as_str()));
+
+    if !listed {
+        headers.append(header::VARY, HeaderValue::from(name));
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for CorsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let Some(origin) = req.headers().get(header::ORIGIN).cloned() else {
+            // not a cross-origin request
+            let fut = self.service.call(req);
+            return Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) });
+        };
+
+        if req.method() == Method::OPTIONS
+            && req
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+        {
+            let res = self.inner.preflight_response(req.head(), &origin);
+            return Box::pin(ready(Ok(req.into_response(res).map_into_right_body())));
+        }
+
+        let allowed = self.inner.allowed(&origin, req.head());
+        let inner = Rc::clone(&self.inner);
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+            let headers = res.headers_mut();
+
+            if let Some(allowed) = allowed {
+                inner.insert_allow_origin(headers, &origin, allowed);
+
+                if !inner.expose_headers.is_empty() {
+                    headers.insert(
+                        header::ACCESS_CONTROL_EXPOSE_HEADERS,
+                        join(inner.expose_headers.iter().map(HeaderName::as_str)),
+                    );
+                }
+            }
+
+            if inner.varies_by_origin() {
+                append_vary(headers, header::ORIGIN);
+            }
+
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{call_service, init_service, TestRequest},
+        web, App,
+    };
+
+    fn header(res: &ServiceResponse<impl Sized>, name: HeaderName) -> Option<&str> {
+        res.headers().get(name).map(|val| val.to_str().unwrap())
+    }
+
+    #[actix_rt::test]
+    async fn allowed_origins() {
+        let app = init_service(
+            App::new()
+                .wrap(
+                    Cors::new()
+                        .allowed_origin("https://example.com")
+                        .allowed_origin_fn(|origin, _| origin.as_bytes().ends_with(b".example.org"))
+                        .expose_headers([HeaderName::from_static("x-total")]),
+                )
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        for origin in ["https://example.com", "https://api.example.org"] {
+            let req = TestRequest::get()
+                .insert_header((header::ORIGIN, origin))
+                .to_request();
+            let res = call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(
+                header(&res, header::ACCESS_CONTROL_ALLOW_ORIGIN),
+                Some(origin)
+            );
+            assert_eq!(
+                header(&res, header::ACCESS_CONTROL_EXPOSE_HEADERS),
+                Some("x-total")
+            );
+            assert_eq!(header(&res, header::ACCESS_CONTROL_ALLOW_CREDENTIALS), None);
+            assert_eq!(header(&res, header::VARY), Some("origin"));
+        }
+
+        // disallowed origins are served without CORS headers
+        let req = TestRequest::get()
+            .insert_header((header::ORIGIN, "https://evil.com"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(header(&res, header::ACCESS_CONTROL_ALLOW_ORIGIN), None);
+        assert_eq!(header(&res, header::VARY), Some("origin"));
+
+        // same-origin requests are untouched
+        let req = TestRequest::get().to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(header(&res, header::VARY), None);
+    }
+
+    #[actix_rt::test]
+    async fn wildcard_with_credentials() {
+        let app = init_service(
+            App::new()
+                .wrap(
+                    Cors::new()
+                        .allow_any_origin()
+                        .allowed_origin("https://example.com")
+                        .supports_credentials(),
+                )
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = TestRequest::get()
+            .insert_header((header::ORIGIN, "https://example.com"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(
+            header(&res, header::ACCESS_CONTROL_ALLOW_ORIGIN),
+            Some("https://example.com")
+        );
+        assert_eq!(
+            header(&res, header::ACCESS_CONTROL_ALLOW_CREDENTIALS),
+            Some("true")
+        );
+
+        let req = TestRequest::get()
+            .insert_header((header::ORIGIN, "https://other.com"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(header(&res, header::ACCESS_CONTROL_ALLOW_ORIGIN), Some("*"));
+        assert_eq!(header(&res, header::ACCESS_CONTROL_ALLOW_CREDENTIALS), None);
+        assert_eq!(header(&res, header::VARY), Some("origin"));
+
+        // responses do not vary when every origin receives a wildcard
+        let app = init_service(
+            App::new()
+                .wrap(Cors::new().allow_any_origin())
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = TestRequest::get()
+            .insert_header((header::ORIGIN, "https://other.com"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(header(&res, header::ACCESS_CONTROL_ALLOW_ORIGIN), Some("*"));
+        assert_eq!(header(&res, header::VARY), None);
+    }
+
+    #[actix_rt::test]
+    async fn preflight() {
+        let app = init_service(
+            App::new()
+                .wrap(
+                    Cors::new()
+                        .allowed_origin("https://example.com")
+                        .allowed_methods([Method::GET, Method::PUT])
+                        .allowed_headers([header::CONTENT_TYPE])
+                        .max_age(Duration::from_secs(600)),
+                )
+                .route("/", web::put().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .method(Method::OPTIONS)
+            .insert_header((header::ORIGIN, "https://example.com"))
+            .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, "PUT"))
+            .insert_header((header::ACCESS_CONTROL_REQUEST_HEADERS, "Content-Type"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            header(&res, header::ACCESS_CONTROL_ALLOW_ORIGIN),
+            Some("https://example.com")
+        );
+        assert_eq!(
+            header(&res, header::ACCESS_CONTROL_ALLOW_METHODS),
+            Some("GET, PUT")
+        );
+        assert_eq!(
+            header(&res, header::ACCESS_CONTROL_ALLOW_HEADERS),
+            Some("content-type")
+        );
+        assert_eq!(header(&res, header::ACCESS_CONTROL_MAX_AGE), Some("600"));
+
+        let vary = res
+            .headers()
+            .get_all(header::VARY)
+            .map(|val| val.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vary,
+            [
+                "origin",
+                "access-control-request-method",
+                "access-control-request-headers"
+            ]
+        );
+
+        for (origin, method, headers) in [
+            ("https://evil.com", "PUT", "content-type"),
+            ("https://example.com", "DELETE", "content-type"),
+            ("https://example.com", "PUT", "content-type, x-custom"),
+        ] {
+            let req = TestRequest::default()
+                .method(Method::OPTIONS)
+                .insert_header((header::ORIGIN, origin))
+                .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, method))
+                .insert_header((header::ACCESS_CONTROL_REQUEST_HEADERS, headers))
+                .to_request();
+            let res = call_service(&app, req).await;
+            assert_eq!(
+                res.status(),
+                StatusCode::FORBIDDEN,
+                "{origin} {method} {headers}"
+            );
+            assert_eq!(header(&res, header::ACCESS_CONTROL_ALLOW_ORIGIN), None);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn preflight_echoes_requested_headers() {
+        let app = init_service(
+            App::new()
+                .wrap(Cors::new().allow_any_origin())
+                .route("/", web::put().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .method(Method::OPTIONS)
+            .insert_header((header::ORIGIN, "https://example.com"))
+            .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, "PUT"))
+            .insert_header((header::ACCESS_CONTROL_REQUEST_HEADERS, "x-a, x-b"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        assert_eq!(header(&res, header::ACCESS_CONTROL_ALLOW_ORIGIN), Some("*"));
+        assert_eq!(
+            header(&res, header::ACCESS_CONTROL_ALLOW_HEADERS),
+            Some("x-a, x-b")
+        );
+        assert_eq!(header(&res, header::ACCESS_CONTROL_MAX_AGE), None);
+    }
+}
@@ -106,6 +106,34 @@ type DefaultHandler<B> = Option<Rc<ErrorHandler<B>>>;
 ///     .service(web::resource("/").route(web::get().to(HttpResponse::InternalServerError)));
 /// ```
 ///
+/// Replacing the body, whether streaming or not, with a JSON envelope built from the request:
+///
+/// ```
+/// use actix_web::{
+///     dev::ServiceResponse,
+///     http::StatusCode,
+///     middleware::{ErrorHandlerResponse, ErrorHandlers},
+///     web, App, HttpResponse, Result,
+/// };
+///
+/// fn json_error<B>(res: ServiceResponse<B>) -> Result<ErrorHandlerResponse<B>> {
+///     let (req, res) = res.into_parts();
+///
+///     // the original body is dropped without being read
+///     let res = HttpResponse::build(res.status()).json(serde_json::json!({
+///         "status": res.status().as_u16(),
+///         "path": req.path(),
+///     }));
+///
+///     let res = ServiceResponse::new(req, res).map_into_right_body();
+///     Ok(ErrorHandlerResponse::Response(res))
+/// }
+///
+/// let app = App::new()
+///     .wrap(ErrorHandlers::new().handler(StatusCode::INTERNAL_SERVER_ERROR, json_error))
+///     .service(web::resource("/").route(web::get().to(HttpResponse::InternalServerError)));
+/// ```
+///
 /// Registering default handler:
 ///
 /// ```
@@ -413,6 +441,7 @@ mod tests {
         body,
         http::header::{HeaderValue, CONTENT_TYPE},
         test::{self, TestRequest},
+        HttpResponse,
     };
 
     #[actix_rt::test]
@@ -491,6 +520,39 @@ mod tests {
         assert_eq!(test::read_body(res).await, "sorry, that's no bueno");
     }
 
+    #[actix_rt::test]
+    async fn replaces_streaming_body() {
+        #[allow(clippy::unnecessary_wraps)]
+        fn error_handler<B>(res: ServiceResponse<B>) -> Result<ErrorHandlerResponse<B>> {
+            let (req, res) = res.into_parts();
+            let res = res.set_body(format!("{} failed", req.path()));
+
+            let res = ServiceResponse::new(req, res)
+                .map_into_boxed_body()
+                .map_into_right_body();
+
+            Ok(ErrorHandlerResponse::Response(res))
+        }
+
+        let srv = |req: ServiceRequest| {
+            let body = futures_util::stream::once(async {
+                Ok::<_, Error>(Bytes::from_static(b"streamed"))
+            });
+
+            ok(req.into_response(HttpResponse::InternalServerError().streaming(body)))
+        };
+
+        let mw = ErrorHandlers::new()
+            .handler(StatusCode::INTERNAL_SERVER_ERROR, error_handler)
+            .new_transform(srv.into_service())
+            .await
+            .unwrap();
+
+        let req = TestRequest::with_uri("/upload").to_srv_request();
+        let res = test::call_service(&mw, req).await;
+        assert_eq!(test::read_body(res).await, "/upload failed");
+    }
+
     #[actix_rt::test]
     async fn error_thrown() {
         #[allow(clippy::unnecessary_wraps)]
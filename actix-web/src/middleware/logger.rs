@@ -26,8 +26,10 @@ use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
 use crate::{
     body::{BodySize, MessageBody},
-    http::header::HeaderName,
+    dev::ContextScope,
+    http::header::{HeaderMap, HeaderName},
     service::{ServiceRequest, ServiceResponse},
+    web::RequestContext,
     Error, Result,
 };
 
@@ -36,6 +38,10 @@ use crate::{
 /// This middleware uses the `log` crate to output information. Enable `log`'s output for the
 /// "actix_web" scope using [`env_logger`](https://docs.rs/env_logger) or similar crate.
 ///
+/// Wrapped services are run in the scope of the request's [`RequestContext`], so log records
+/// emitted by handlers, and by tasks they spawn using [`RequestContext::spawn()`], can be
+/// correlated with access logs using the `%L` request ID.
+///
 /// # Default Format
 /// The [`default`](Logger::default) Logger uses the following format:
 ///
@@ -70,6 +76,7 @@ use crate::{
 /// `%T` | Time taken to serve the request, in seconds to 6 decimal places
 /// `%D` | Time taken to serve the request, in milliseconds
 /// `%U` | Request URL
+/// `%L` | [Request ID](crate::web::RequestContext::request_id); also available as `%{request_id}`
 /// `%{r}a` | "Real IP" remote address **\***
 /// `%{FOO}i` | `request.headers["FOO"]`
 /// `%{FOO}o` | `response.headers["FOO"]`
@@ -308,7 +315,7 @@ where
 
         if excluded {
             LoggerResponse {
-                fut: self.service.call(req),
+                fut: ContextScope::new(None, self.service.call(req)),
                 format: None,
                 time: OffsetDateTime::now_utc(),
                 log_target: Cow::Borrowed(""),
@@ -317,13 +324,14 @@ where
         } else {
             let now = OffsetDateTime::now_utc();
             let mut format = self.inner.format.clone();
+            let ctx = RequestContext::get_or_insert(req.request());
 
             for unit in &mut format.0 {
                 unit.render_request(now, &req);
             }
 
             LoggerResponse {
-                fut: self.service.call(req),
+                fut: ContextScope::new(Some(ctx), self.service.call(req)),
                 format: Some(format),
                 time: now,
                 log_target: self.inner.log_target.clone(),
@@ -340,7 +348,7 @@ pin_project! {
         S: Service<ServiceRequest>,
     {
         #[pin]
-        fut: S::Future,
+        fut: ContextScope<S::Future>,
         time: OffsetDateTime,
         format: Option<Format>,
         log_target: Cow<'static, str>,
@@ -453,6 +461,11 @@ impl<B: MessageBody> MessageBody for StreamLog<B> {
             None => Poll::Ready(None),
         }
     }
+
+    #[inline]
+    fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        self.project().body.poll_trailers(cx)
+    }
 }
 
 /// A formatting style for the `Logger` consisting of multiple concatenated `FormatText` items.
@@ -472,7 +485,9 @@ impl Format {
     /// Returns `None` if the format string syntax is incorrect.
     pub fn new(s: &str) -> Format {
         log::trace!("Access log format: {}", s);
-        let fmt = Regex::new(r"%(\{([A-Za-z0-9\-_]+)\}([aioe]|x[io])|[%atPrUsbTD]?)").unwrap();
+        let fmt =
+            Regex::new(r"%(\{([A-Za-z0-9\-_]+)\}([aioe]|x[io])|\{request_id\}|[%atPrUsbTDL]?)")
+                .unwrap();
 
         let mut idx = 0;
         let mut results = Vec::new();
@@ -512,6 +527,7 @@ impl Format {
                     "U" => FormatText::UrlPath,
                     "T" => FormatText::Time,
                     "D" => FormatText::TimeMillis,
+                    "L" | "{request_id}" => FormatText::RequestId,
                     _ => FormatText::Str(m.as_str().to_owned()),
                 });
             }
@@ -541,6 +557,7 @@ enum FormatText {
     RemoteAddr,
     RealIpRemoteAddr,
     UrlPath,
+    RequestId,
     RequestHeader(HeaderName),
     ResponseHeader(HeaderName),
     EnvironHeader(String),
@@ -663,6 +680,12 @@ impl FormatText {
                 };
             }
             FormatText::UrlPath => *self = FormatText::Str(req.path().to_string()),
+            FormatText::RequestId => {
+                let id = RequestContext::get_or_insert(req.request())
+                    .request_id()
+                    .to_owned();
+                *self = FormatText::Str(id);
+            }
             FormatText::RequestTime => *self = FormatText::Str(now.format(&Rfc3339).unwrap()),
             FormatText::RequestHeader(ref name) => {
                 let s = if let Some(val) = req.headers().get(name) {
@@ -832,6 +855,28 @@ mod tests {
         assert!(s.contains("/test/route/yeah"));
     }
 
+    #[actix_rt::test]
+    async fn test_request_id() {
+        let mut format = Format::new("id=%L %{request_id}");
+        let req = TestRequest::default()
+            .insert_header(("x-request-id", "abc-123"))
+            .to_srv_request();
+
+        let now = OffsetDateTime::now_utc();
+        for unit in &mut format.0 {
+            unit.render_request(now, &req);
+        }
+
+        let render = |fmt: &mut fmt::Formatter<'_>| {
+            for unit in &format.0 {
+                unit.render(fmt, 1024, now)?;
+            }
+            Ok(())
+        };
+        let s = format!("{}", FormatDisplay(&render));
+        assert_eq!(s, "id=abc-123 abc-123");
+    }
+
     #[actix_rt::test]
     async fn test_default_format() {
         let mut format = Format::default();
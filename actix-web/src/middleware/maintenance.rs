@@ -0,0 +1,392 @@
+//! For middleware documentation, see [`Maintenance`].
+
+use std::{
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Ready};
+use bytes::Bytes;
+use futures_core::future::LocalBoxFuture;
+
+use crate::{
+    body::EitherBody,
+    http::header::{self, HeaderValue, TryIntoHeaderValue},
+    service::{ServiceRequest, ServiceResponse},
+    Error, HttpResponse,
+};
+
+/// Switch for turning maintenance mode on and off at runtime.
+///
+/// Clones share the same state, so a switch can be passed to the [`Maintenance`] middleware of
+/// every worker and toggled from anywhere else: an admin endpoint (e.g., registered as
+/// [`ThinData`](crate::web::ThinData) app data), a signal handler, or a task that follows a watch
+/// channel.
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceSwitch {
+    enabled: Arc<AtomicBool>,
+}
+
+impl MaintenanceSwitch {
+    /// Constructs a switch with maintenance mode turned off.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Turns maintenance mode on.
+    pub fn enable(&self) {
+        self.set(true);
+    }
+
+    /// Turns maintenance mode off.
+    pub fn disable(&self) {
+        self.set(false);
+    }
+
+    /// Sets whether maintenance mode is on.
+    pub fn set(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns true if maintenance mode is on.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+}
+
+/// Middleware for answering requests with `503 Service Unavailable` during maintenance.
+///
+/// While the [`MaintenanceSwitch`] is on, requests are answered with a `503 Service Unavailable`
+/// response that has a `Retry-After` header and a configurable body, except for requests to
+/// [allowed](Self::allow) paths, such as health checks and admin endpoints. While the switch is
+/// off, requests pass through untouched.
+///
+/// Register the middleware on the `App` with the last call to `wrap()`, so that it runs before all
+/// other middleware and rejected requests do no further work.
+///
+/// # Examples
+/// ```
+/// use actix_web::{
+///     middleware::{Maintenance, MaintenanceSwitch},
+///     post,
+///     web::{self, ThinData},
+///     App, HttpResponse, HttpServer,
+/// };
+///
+/// #[post("/admin/maintenance/{on}")]
+/// async fn toggle(
+///     ThinData(switch): ThinData<MaintenanceSwitch>,
+///     on: web::Path<bool>,
+/// ) -> HttpResponse {
+///     switch.set(on.into_inner());
+///     HttpResponse::NoContent().finish()
+/// }
+///
+/// # fn run() -> std::io::Result<()> {
+/// let switch = MaintenanceSwitch::new();
+///
+/// HttpServer::new(move || {
+///     App::new()
+///         .app_data(ThinData(switch.clone()))
+///         .service(toggle)
+///         .route("/health", web::get().to(HttpResponse::Ok))
+///         .wrap(
+///             Maintenance::new(switch.clone())
+///                 .allow("/health")
+///                 .allow("/admin")
+///                 .body("Down for maintenance, back soon."),
+///         )
+/// })
+/// # ; Ok(()) }
+/// ```
+///
+/// Following a watch channel instead is a matter of updating the switch from a task:
+/// ```no_run
+/// # use actix_web::middleware::MaintenanceSwitch;
+/// # async fn follow(mut rx: tokio::sync::watch::Receiver<bool>, switch: MaintenanceSwitch) {
+/// while rx.changed().await.is_ok() {
+///     switch.set(*rx.borrow());
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Maintenance {
+    inner: Rc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    switch: MaintenanceSwitch,
+    allowed: Vec<String>,
+    retry_after: Duration,
+    content_type: HeaderValue,
+    body: Bytes,
+}
+
+impl Maintenance {
+    /// Constructs maintenance middleware controlled by `switch`.
+    ///
+    /// By default, clients are asked to retry after 60 seconds and are sent a short plain text
+    /// message.
+    pub fn new(switch: MaintenanceSwitch) -> Self {
+        Self {
+            inner: Rc::new(Inner {
+                switch,
+                allowed: Vec::new(),
+                retry_after: Duration::from_secs(60),
+                content_type: HeaderValue::from_static("text/plain; charset=utf-8"),
+                body: Bytes::from_static(b"Service is down for maintenance."),
+            }),
+        }
+    }
+
+    /// Allows requests to `path` and paths below it during maintenance.
+    ///
+    /// For example, allowing `/admin` allows `/admin` and `/admin/users` but not `/administrator`.
+    /// Request paths are matched after percent-decoding and resolving `.` and `..` segments, so
+    /// `/admin/../api` is not allowed.
+    pub fn allow(mut self, path: impl Into<String>) -> Self {
+        let mut path = path.into();
+
+        while path.len() > 1 && path.ends_with('/') {
+            path.pop();
+        }
+
+        Rc::get_mut(&mut self.inner)
+            .expect("Maintenance is shared")
+            .allowed
+            .push(path);
+
+        self
+    }
+
+    /// Sets the delay sent in the `Retry-After` header, rounded down to whole seconds.
+    pub fn retry_after(mut self, retry_after: Duration) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Maintenance is shared")
+            .retry_after = retry_after;
+        self
+    }
+
+    /// Sets the response body sent during maintenance.
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Maintenance is shared")
+            .body = body.into();
+        self
+    }
+
+    /// Sets the `Content-Type` of the response body sent during maintenance.
+    ///
+    /// # Panics
+    /// Panics if `content_type` is not a valid header value.
+    pub fn content_type(mut self, content_type: impl TryIntoHeaderValue) -> Self {
+        let content_type = match content_type.try_into_value() {
+            Ok(content_type) => content_type,
+            Err(err) => panic!("Invalid header: {}", err.into()),
+        };
+
+        Rc::get_mut(&mut self.inner)
+            .expect("Maintenance is shared")
+            .content_type = content_type;
+        self
+    }
+}
+
+impl Inner {
+    /// Returns true if the percent-decoded request `path` is allowed during maintenance.
+    fn is_allowed(&self, path: &str) -> bool {
+        let path = normalize(path);
+
+        self.allowed.iter().any(|allowed| {
+            allowed == "/"
+                || path
+                    .strip_prefix(allowed.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+}
+
+/// Resolves `.` and `..` segments and removes empty segments of a request path.
+fn normalize(path: &str) -> String {
+    let mut segments = Vec::new();
+
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    format!("/{}", segments.join("/"))
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Maintenance
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = MaintenanceMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MaintenanceMiddleware {
+            service,
+            inner: Rc::clone(&self.inner),
+        }))
+    }
+}
+
+#[doc(hidden)]
+pub struct MaintenanceMiddleware<S> {
+    service: S,
+    inner: Rc<Inner>,
+}
+
+impl<S, B> Service<ServiceRequest> for MaintenanceMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if !self.inner.switch.is_enabled() || self.inner.is_allowed(req.match_info().as_str()) {
+            let fut = self.service.call(req);
+            return Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) });
+        }
+
+        let res = HttpResponse::ServiceUnavailable()
+            .insert_header((
+                header::RETRY_AFTER,
+                HeaderValue::from(self.inner.retry_after.as_secs()),
+            ))
+            .insert_header((header::CONTENT_TYPE, self.inner.content_type.clone()))
+            .body(self.inner.body.clone());
+
+        let res = req.into_response(res).map_into_right_body();
+        Box::pin(async move { Ok(res) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{self, TestRequest},
+        web, App,
+    };
+
+    #[test]
+    fn allowed_paths() {
+        let maintenance = Maintenance::new(MaintenanceSwitch::new())
+            .allow("/health")
+            .allow("/admin/");
+
+        let inner = &maintenance.inner;
+        assert!(inner.is_allowed("/health"));
+        assert!(inner.is_allowed("/admin"));
+        assert!(inner.is_allowed("/admin/users"));
+        assert!(!inner.is_allowed("/healthz"));
+        assert!(!inner.is_allowed("/"));
+        assert!(!inner.is_allowed("/api/health"));
+
+        assert!(inner.is_allowed("//admin/./users"));
+        assert!(inner.is_allowed("/api/../health"));
+        assert!(!inner.is_allowed("/health/../api"));
+        assert!(!inner.is_allowed("/health/.."));
+        assert!(!inner.is_allowed("/admin/users/../../api"));
+    }
+
+    #[actix_rt::test]
+    async fn encoded_paths() {
+        let switch = MaintenanceSwitch::new();
+        switch.enable();
+
+        let app = test::init_service(
+            App::new()
+                .default_service(web::to(HttpResponse::Ok))
+                .wrap(Maintenance::new(switch).allow("/health")),
+        )
+        .await;
+
+        for uri in [
+            "/health/../admin",
+            "/health/%2e%2e/admin",
+            "/health/%2E%2E/admin",
+            "/%68ealth/../admin",
+            "/health%2F..%2Fadmin",
+        ] {
+            let req = TestRequest::with_uri(uri).to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE, "{uri}");
+        }
+
+        for uri in ["/health", "/%68ealth", "/health/%2e/live"] {
+            let req = TestRequest::with_uri(uri).to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::OK, "{uri}");
+        }
+    }
+
+    #[actix_rt::test]
+    async fn toggle_at_runtime() {
+        let switch = MaintenanceSwitch::new();
+
+        let app = test::init_service(
+            App::new()
+                .route("/", web::get().to(HttpResponse::Ok))
+                .route("/health", web::get().to(HttpResponse::Ok))
+                .wrap(
+                    Maintenance::new(switch.clone())
+                        .allow("/health")
+                        .retry_after(Duration::from_secs(120))
+                        .content_type("text/html")
+                        .body("<h1>Back soon</h1>"),
+                ),
+        )
+        .await;
+
+        let res = test::call_service(&app, TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        switch.enable();
+
+        let res = test::call_service(&app, TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers().get(header::RETRY_AFTER).unwrap(), "120");
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/html"
+        );
+        assert_eq!(test::read_body(res).await, "<h1>Back soon</h1>");
+
+        let req = TestRequest::with_uri("/health").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        switch.disable();
+        assert!(!switch.is_enabled());
+
+        let res = test::call_service(&app, TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
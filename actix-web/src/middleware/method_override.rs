@@ -0,0 +1,330 @@
+//! For middleware documentation, see [`MethodOverride`].
+
+use std::rc::Rc;
+
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Ready};
+use bytes::{Bytes, BytesMut};
+use futures_core::future::LocalBoxFuture;
+use futures_util::StreamExt as _;
+
+use crate::{
+    dev,
+    http::{
+        header::{self, HeaderName},
+        Method,
+    },
+    service::{ServiceRequest, ServiceResponse},
+    Error, HttpMessage as _,
+};
+
+/// Name of the header used to override a request's method.
+const X_HTTP_METHOD_OVERRIDE: HeaderName = HeaderName::from_static("x-http-method-override");
+
+/// Name of the form field used to override a request's method.
+const METHOD_FIELD: &str = "_method";
+
+/// Middleware for overriding the method of `POST` requests.
+///
+/// HTML forms can only be submitted using `GET` or `POST`. This middleware allows such apps to
+/// express other methods, such as `PUT` and `DELETE`, so that routes are matched against the
+/// intended method. Only `POST` requests are ever rewritten and only to one of the allowed methods
+/// (by default, `PUT`, `PATCH`, and `DELETE`). Unrecognized or disallowed overrides are ignored.
+///
+/// The overriding method is taken from, in order of precedence:
+/// - the `X-HTTP-Method-Override` header;
+/// - the `_method` field of a URL-encoded form body, if the body is no larger than the
+///   [form limit](Self::form_limit()). The body remains available to handlers.
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::MethodOverride, web, App, HttpResponse};
+///
+/// let app = App::new()
+///     .wrap(MethodOverride::new())
+///     .route("/posts/{id}", web::delete().to(HttpResponse::NoContent));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MethodOverride {
+    inner: Rc<Inner>,
+}
+
+#[derive(Debug, Clone)]
+struct Inner {
+    allowed: Vec<Method>,
+    form_limit: usize,
+}
+
+impl Default for MethodOverride {
+    fn default() -> Self {
+        Self {
+            inner: Rc::new(Inner {
+                allowed: vec![Method::PUT, Method::PATCH, Method::DELETE],
+                form_limit: 16_384,
+            }),
+        }
+    }
+}
+
+impl MethodOverride {
+    /// Constructs new method override middleware allowing `PUT`, `PATCH`, and `DELETE` overrides.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the methods that `POST` requests may be rewritten to, replacing the defaults.
+    pub fn allowed_methods(mut self, methods: impl IntoIterator<Item = Method>) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .allowed = methods.into_iter().collect();
+        self
+    }
+
+    /// Sets the maximum size of URL-encoded form bodies inspected for a `_method` field.
+    ///
+    /// Setting a limit of zero disables reading the method from form bodies. The default limit is
+    /// 16kB.
+    pub fn form_limit(mut self, limit: usize) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .form_limit = limit;
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for MethodOverride
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = MethodOverrideMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MethodOverrideMiddleware {
+            service: Rc::new(service),
+            inner: Rc::clone(&self.inner),
+        }))
+    }
+}
+
+#[doc(hidden)]
+pub struct MethodOverrideMiddleware<S> {
+    service: Rc<S>,
+    inner: Rc<Inner>,
+}
+
+impl Inner {
+    /// Parses `val` as a method, returning it only if it is an allowed override.
+    fn allowed(&self, val: &str) -> Option<Method> {
+        let method = Method::from_bytes(val.trim().to_ascii_uppercase().as_bytes()).ok()?;
+        self.allowed.contains(&method).then_some(method)
+    }
+
+    /// Returns true if the request body should be inspected for a `_method` field.
+    fn should_read_form(&self, req: &ServiceRequest) -> bool {
+        if self.form_limit == 0 || req.content_type() != mime::APPLICATION_WWW_FORM_URLENCODED {
+            return false;
+        }
+
+        req.headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok())
+            .and_then(|len| len.parse::<usize>().ok())
+            .map_or(true, |len| len > 0 && len <= self.form_limit)
+    }
+
+    /// Finds the allowed method override in a URL-encoded form body, if any.
+    fn form_method(&self, body: &[u8]) -> Option<Method> {
+        serde_urlencoded::from_bytes::<Vec<(String, String)>>(body)
+            .ok()?
+            .into_iter()
+            .find(|(key, _)| key == METHOD_FIELD)
+            .and_then(|(_, val)| self.allowed(&val))
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for MethodOverrideMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        if req.method() != Method::POST {
+            return Box::pin(self.service.call(req));
+        }
+
+        let header_method = req
+            .headers()
+            .get(X_HTTP_METHOD_OVERRIDE)
+            .and_then(|val| val.to_str().ok())
+            .and_then(|val| self.inner.allowed(val));
+
+        if let Some(method) = header_method {
+            req.head_mut().method = method;
+            return Box::pin(self.service.call(req));
+        }
+
+        if !self.inner.should_read_form(&req) {
+            return Box::pin(self.service.call(req));
+        }
+
+        let service = Rc::clone(&self.service);
+        let inner = Rc::clone(&self.inner);
+
+        Box::pin(async move {
+            let mut payload = req.take_payload();
+            let mut body = BytesMut::new();
+
+            while let Some(chunk) = payload.next().await {
+                body.extend_from_slice(&chunk?);
+
+                if body.len() > inner.form_limit {
+                    break;
+                }
+            }
+
+            let body = body.freeze();
+
+            if body.len() <= inner.form_limit {
+                if let Some(method) = inner.form_method(&body) {
+                    req.head_mut().method = method;
+                }
+
+                req.set_payload(payload_from_bytes(body));
+            } else {
+                // body is over the limit; hand it to the handler untouched
+                let stream = futures_util::stream::once(async { Ok(body) }).chain(payload);
+                let stream: actix_http::BoxedPayloadStream = Box::pin(stream);
+                req.set_payload(dev::Payload::from(stream));
+            }
+
+            service.call(req).await
+        })
+    }
+}
+
+fn payload_from_bytes(bytes: Bytes) -> dev::Payload {
+    let (_, mut h1_payload) = actix_http::h1::Payload::create(true);
+    h1_payload.unread_data(bytes);
+    dev::Payload::from(h1_payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{self, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    async fn app() -> impl Service<actix_http::Request, Response = ServiceResponse, Error = Error> {
+        test::init_service(
+            App::new()
+                .wrap(MethodOverride::new())
+                .route("/", web::post().to(|body: String| async move { body }))
+                .route("/", web::put().to(|| async { "put" }))
+                .route(
+                    "/",
+                    web::delete().to(|form: web::Form<Vec<(String, String)>>| async move {
+                        format!("delete {}", form.len())
+                    }),
+                )
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await
+    }
+
+    #[actix_rt::test]
+    async fn header_override() {
+        let app = app().await;
+
+        let req = TestRequest::post()
+            .insert_header((X_HTTP_METHOD_OVERRIDE, "PUT"))
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "put");
+
+        // disallowed methods are ignored
+        let req = TestRequest::post()
+            .insert_header((X_HTTP_METHOD_OVERRIDE, "GET"))
+            .set_payload("post")
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "post");
+
+        // only POST requests are overridden
+        let req = TestRequest::get()
+            .insert_header((X_HTTP_METHOD_OVERRIDE, "PUT"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(test::read_body(res).await.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn form_override() {
+        let app = app().await;
+
+        let req = TestRequest::post()
+            .set_form([("_method", "delete"), ("title", "test")])
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "delete 2", "form body should remain readable");
+
+        let req = TestRequest::post()
+            .set_form([("_method", "OPTIONS")])
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "_method=OPTIONS");
+
+        // bodies over the limit are not inspected
+        let app = test::init_service(
+            App::new()
+                .wrap(MethodOverride::new().form_limit(8))
+                .route("/", web::post().to(|body: String| async move { body }))
+                .route("/", web::delete().to(|| async { "delete" })),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .set_form([("_method", "DELETE")])
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "_method=DELETE");
+    }
+
+    #[actix_rt::test]
+    async fn allowed_methods() {
+        let app = test::init_service(
+            App::new()
+                .wrap(MethodOverride::new().allowed_methods([Method::DELETE]))
+                .route("/", web::post().to(|| async { "post" }))
+                .route("/", web::put().to(|| async { "put" }))
+                .route("/", web::delete().to(|| async { "delete" })),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .insert_header((X_HTTP_METHOD_OVERRIDE, "PUT"))
+            .to_request();
+        assert_eq!(test::call_and_read_body(&app, req).await, "post");
+
+        let req = TestRequest::post()
+            .insert_header((X_HTTP_METHOD_OVERRIDE, "DELETE"))
+            .to_request();
+        assert_eq!(test::call_and_read_body(&app, req).await, "delete");
+    }
+}
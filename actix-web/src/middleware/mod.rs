@@ -246,24 +246,40 @@ mod compat;
 #[cfg(feature = "__compress")]
 mod compress;
 mod condition;
+mod cors;
 mod default_headers;
 mod err_handlers;
 mod from_fn;
 mod identity;
 mod logger;
+mod maintenance;
+mod method_override;
 mod normalize;
+mod normalize_request;
+mod rate_limit;
+mod security_headers;
+mod upload_tracker;
 
 #[cfg(feature = "__compress")]
 pub use self::compress::Compress;
 pub use self::{
     compat::Compat,
     condition::Condition,
+    cors::Cors,
     default_headers::DefaultHeaders,
     err_handlers::{ErrorHandlerResponse, ErrorHandlers},
     from_fn::{from_fn, Next},
     identity::Identity,
     logger::Logger,
-    normalize::{NormalizePath, TrailingSlash},
+    maintenance::{Maintenance, MaintenanceSwitch},
+    method_override::MethodOverride,
+    normalize::{NormalizePath, NormalizePathRedirect, TrailingSlash},
+    normalize_request::{DuplicateQueryKeys, NormalizeRequest},
+    rate_limit::{
+        MemoryRateLimitStore, RateLimitDecision, RateLimitQuota, RateLimitStore, RateLimiter,
+    },
+    security_headers::{ContentSecurityPolicy, FrameOptions, SecurityHeaders},
+    upload_tracker::UploadTracker,
 };
 
 #[cfg(test)]
@@ -283,10 +299,15 @@ mod tests {
                 Ok(ErrorHandlerResponse::Response(res.map_into_left_body()))
             }))
             .wrap(Logger::default())
+            .wrap(MethodOverride::new())
+            .wrap(Cors::new().allow_any_origin())
+            .wrap(NormalizeRequest::new())
             .wrap(NormalizePath::new(TrailingSlash::Trim));
 
         let _ = App::new()
             .wrap(NormalizePath::new(TrailingSlash::Trim))
+            .wrap(NormalizeRequest::new())
+            .wrap(MethodOverride::new())
             .wrap(Logger::default())
             .wrap(ErrorHandlers::new().handler(StatusCode::FORBIDDEN, |res| {
                 Ok(ErrorHandlerResponse::Response(res.map_into_left_body()))
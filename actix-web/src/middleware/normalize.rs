@@ -4,14 +4,17 @@ use actix_http::uri::{PathAndQuery, Uri};
 use actix_service::{Service, Transform};
 use actix_utils::future::{ready, Ready};
 use bytes::Bytes;
+use futures_core::future::LocalBoxFuture;
 #[cfg(feature = "unicode")]
 use regex::Regex;
 #[cfg(not(feature = "unicode"))]
 use regex_lite::Regex;
 
 use crate::{
+    body::EitherBody,
+    http::{header, StatusCode},
     service::{ServiceRequest, ServiceResponse},
-    Error,
+    Error, HttpResponse,
 };
 
 /// Determines the behavior of the [`NormalizePath`] middleware.
@@ -44,6 +47,10 @@ pub enum TrailingSlash {
 /// - Appends a trailing slash if one is not present, removes one if present, or keeps trailing
 ///   slashes as-is, depending on which [`TrailingSlash`] variant is supplied
 ///   to [`new`](NormalizePath::new()).
+/// - Optionally, converts the path to lowercase; see [`lowercase`](NormalizePath::lowercase()).
+///
+/// By default, the path is rewritten in place before routing. Alternatively, clients can be
+/// [redirected](NormalizePath::redirect()) to the normalized path instead.
 ///
 /// # Default Behavior
 /// The default constructor chooses to strip trailing slashes from the end of paths with them
@@ -84,7 +91,10 @@ pub enum TrailingSlash {
 /// # })
 /// ```
 #[derive(Debug, Clone, Copy)]
-pub struct NormalizePath(TrailingSlash);
+pub struct NormalizePath {
+    trailing_slash: TrailingSlash,
+    lowercase: bool,
+}
 
 impl Default for NormalizePath {
     fn default() -> Self {
@@ -93,14 +103,17 @@ impl Default for NormalizePath {
             in v4 from `Always` to `Trim`. Update your call to `NormalizePath::new(...)`."
         );
 
-        Self(TrailingSlash::Trim)
+        Self::new(TrailingSlash::Trim)
     }
 }
 
 impl NormalizePath {
     /// Create new `NormalizePath` middleware with the specified trailing slash style.
     pub fn new(trailing_slash_style: TrailingSlash) -> Self {
-        Self(trailing_slash_style)
+        Self {
+            trailing_slash: trailing_slash_style,
+            lowercase: false,
+        }
     }
 
     /// Constructs a new `NormalizePath` middleware with [trim](TrailingSlash::Trim) semantics.
@@ -109,6 +122,61 @@ impl NormalizePath {
     pub fn trim() -> Self {
         Self::new(TrailingSlash::Trim)
     }
+
+    /// Sets whether paths are converted to lowercase.
+    ///
+    /// Only the path is affected; the query string is left as-is. Routes need to be defined in
+    /// lowercase for them to be accessible.
+    pub fn lowercase(mut self, lowercase: bool) -> Self {
+        self.lowercase = lowercase;
+        self
+    }
+
+    /// Returns middleware that redirects clients to the normalized path using `status` instead of
+    /// rewriting the path.
+    ///
+    /// Use [`StatusCode::PERMANENT_REDIRECT`] (308) or [`StatusCode::TEMPORARY_REDIRECT`] (307) so
+    /// that clients retry with the same method and body. Requests whose paths are already
+    /// normalized are passed through.
+    ///
+    /// # Panics
+    /// Panics if `status` is not a redirection status code.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{
+    ///     http::StatusCode,
+    ///     middleware::{NormalizePath, TrailingSlash},
+    ///     web, App, HttpResponse,
+    /// };
+    ///
+    /// let app = App::new()
+    ///     .wrap(
+    ///         NormalizePath::new(TrailingSlash::Trim)
+    ///             .lowercase(true)
+    ///             .redirect(StatusCode::PERMANENT_REDIRECT),
+    ///     )
+    ///     .route("/docs/intro", web::get().to(HttpResponse::Ok));
+    /// ```
+    pub fn redirect(self, status: StatusCode) -> NormalizePathRedirect {
+        assert!(
+            status.is_redirection(),
+            "NormalizePath redirect status must be a redirection, got {status}"
+        );
+
+        NormalizePathRedirect {
+            normalize: self,
+            status,
+        }
+    }
+
+    fn normalizer(&self) -> Normalizer {
+        Normalizer {
+            merge_slash: Regex::new("//+").unwrap(),
+            trailing_slash_behavior: self.trailing_slash,
+            lowercase: self.lowercase,
+        }
+    }
 }
 
 impl<S, B> Transform<S, ServiceRequest> for NormalizePath
@@ -125,16 +193,76 @@ where
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(NormalizePathNormalization {
             service,
-            merge_slash: Regex::new("//+").unwrap(),
-            trailing_slash_behavior: self.0,
+            normalizer: self.normalizer(),
         }))
     }
 }
 
-pub struct NormalizePathNormalization<S> {
-    service: S,
+struct Normalizer {
     merge_slash: Regex,
     trailing_slash_behavior: TrailingSlash,
+    lowercase: bool,
+}
+
+impl Normalizer {
+    /// Returns the normalized path and query of `uri`, if normalization changes its path.
+    fn normalize(&self, uri: &Uri) -> Option<Bytes> {
+        let original_path = uri.path();
+
+        // An empty path here means that the URI has no valid path. We skip normalization in this
+        // case, because adding a path can make the URI invalid
+        if original_path.is_empty() {
+            return None;
+        }
+
+        // Either adds a string to the end (duplicates will be removed anyways) or trims all
+        // slashes from the end
+        let path = match self.trailing_slash_behavior {
+            TrailingSlash::Always => format!("{}/", original_path),
+            TrailingSlash::MergeOnly => original_path.to_string(),
+            TrailingSlash::Trim => original_path.trim_end_matches('/').to_string(),
+        };
+
+        // normalize multiple /'s to one /
+        let path = self.merge_slash.replace_all(&path, "/");
+
+        // Ensure root paths are still resolvable. If resulting path is blank after previous
+        // step it means the path was one or more slashes. Reduce to single slash.
+        let path = if path.is_empty() { "/" } else { path.as_ref() };
+
+        let lowercased;
+        let path = if self.lowercase {
+            lowercased = path.to_lowercase();
+            lowercased.as_str()
+        } else {
+            path
+        };
+
+        // Check whether the path has been changed
+        //
+        // This check was previously implemented as string length comparison
+        //
+        // That approach fails when a trailing slash is added,
+        // and a duplicate slash is removed,
+        // since the length of the strings remains the same
+        //
+        // For example, the path "/v1//s" will be normalized to "/v1/s/"
+        // Both of the paths have the same length,
+        // so the change can not be deduced from the length comparison
+        if path == original_path {
+            return None;
+        }
+
+        Some(match uri.query() {
+            Some(q) => Bytes::from(format!("{}?{}", path, q)),
+            None => Bytes::copy_from_slice(path.as_bytes()),
+        })
+    }
+}
+
+pub struct NormalizePathNormalization<S> {
+    service: S,
+    normalizer: Normalizer,
 }
 
 impl<S, B> Service<ServiceRequest> for NormalizePathNormalization<S>
@@ -151,56 +279,83 @@ where
     fn call(&self, mut req: ServiceRequest) -> Self::Future {
         let head = req.head_mut();
 
-        let original_path = head.uri.path();
+        if let Some(path) = self.normalizer.normalize(&head.uri) {
+            let mut parts = head.uri.clone().into_parts();
+            parts.path_and_query = Some(PathAndQuery::from_maybe_shared(path).unwrap());
 
-        // An empty path here means that the URI has no valid path. We skip normalization in this
-        // case, because adding a path can make the URI invalid
-        if !original_path.is_empty() {
-            // Either adds a string to the end (duplicates will be removed anyways) or trims all
-            // slashes from the end
-            let path = match self.trailing_slash_behavior {
-                TrailingSlash::Always => format!("{}/", original_path),
-                TrailingSlash::MergeOnly => original_path.to_string(),
-                TrailingSlash::Trim => original_path.trim_end_matches('/').to_string(),
-            };
-
-            // normalize multiple /'s to one /
-            let path = self.merge_slash.replace_all(&path, "/");
-
-            // Ensure root paths are still resolvable. If resulting path is blank after previous
-            // step it means the path was one or more slashes. Reduce to single slash.
-            let path = if path.is_empty() { "/" } else { path.as_ref() };
-
-            // Check whether the path has been changed
-            //
-            // This check was previously implemented as string length comparison
-            //
-            // That approach fails when a trailing slash is added,
-            // and a duplicate slash is removed,
-            // since the length of the strings remains the same
-            //
-            // For example, the path "/v1//s" will be normalized to "/v1/s/"
-            // Both of the paths have the same length,
-            // so the change can not be deduced from the length comparison
-            if path != original_path {
-                let mut parts = head.uri.clone().into_parts();
-                let query = parts.path_and_query.as_ref().and_then(|pq| pq.query());
-
-                let path = match query {
-                    Some(q) => Bytes::from(format!("{}?{}", path, q)),
-                    None => Bytes::copy_from_slice(path.as_bytes()),
-                };
-                parts.path_and_query = Some(PathAndQuery::from_maybe_shared(path).unwrap());
-
-                let uri = Uri::from_parts(parts).unwrap();
-                req.match_info_mut().get_mut().update(&uri);
-                req.head_mut().uri = uri;
-            }
+            let uri = Uri::from_parts(parts).unwrap();
+            req.match_info_mut().get_mut().update(&uri);
+            req.head_mut().uri = uri;
         }
+
         self.service.call(req)
     }
 }
 
+/// Middleware for redirecting clients to the normalized request path.
+///
+/// Constructed by [`NormalizePath::redirect()`].
+#[derive(Debug, Clone, Copy)]
+pub struct NormalizePathRedirect {
+    normalize: NormalizePath,
+    status: StatusCode,
+}
+
+impl<S, B> Transform<S, ServiceRequest> for NormalizePathRedirect
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = NormalizePathRedirectMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(NormalizePathRedirectMiddleware {
+            service,
+            normalizer: self.normalize.normalizer(),
+            status: self.status,
+        }))
+    }
+}
+
+#[doc(hidden)]
+pub struct NormalizePathRedirectMiddleware<S> {
+    service: S,
+    normalizer: Normalizer,
+    status: StatusCode,
+}
+
+impl<S, B> Service<ServiceRequest> for NormalizePathRedirectMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if let Some(path) = self.normalizer.normalize(req.uri()) {
+            let res = HttpResponse::build(self.status)
+                .insert_header((header::LOCATION, path.as_ref()))
+                .finish();
+
+            let res = req.into_response(res).map_into_right_body();
+            return Box::pin(async move { Ok(res) });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use actix_http::StatusCode;
@@ -253,7 +408,7 @@ mod tests {
     async fn trim_trailing_slashes() {
         let app = init_service(
             App::new()
-                .wrap(NormalizePath(TrailingSlash::Trim))
+                .wrap(NormalizePath::new(TrailingSlash::Trim))
                 .service(web::resource("/").to(HttpResponse::Ok))
                 .service(web::resource("/v1/something").to(HttpResponse::Ok))
                 .service(
@@ -289,11 +444,13 @@ mod tests {
     #[actix_rt::test]
     async fn trim_root_trailing_slashes_with_query() {
         let app = init_service(
-            App::new().wrap(NormalizePath(TrailingSlash::Trim)).service(
-                web::resource("/")
-                    .guard(fn_guard(|ctx| ctx.head().uri.query() == Some("query=test")))
-                    .to(HttpResponse::Ok),
-            ),
+            App::new()
+                .wrap(NormalizePath::new(TrailingSlash::Trim))
+                .service(
+                    web::resource("/")
+                        .guard(fn_guard(|ctx| ctx.head().uri.query() == Some("query=test")))
+                        .to(HttpResponse::Ok),
+                ),
         )
         .await;
 
@@ -310,7 +467,7 @@ mod tests {
     async fn ensure_trailing_slash() {
         let app = init_service(
             App::new()
-                .wrap(NormalizePath(TrailingSlash::Always))
+                .wrap(NormalizePath::new(TrailingSlash::Always))
                 .service(web::resource("/").to(HttpResponse::Ok))
                 .service(web::resource("/v1/something/").to(HttpResponse::Ok))
                 .service(
@@ -347,7 +504,7 @@ mod tests {
     async fn ensure_root_trailing_slash_with_query() {
         let app = init_service(
             App::new()
-                .wrap(NormalizePath(TrailingSlash::Always))
+                .wrap(NormalizePath::new(TrailingSlash::Always))
                 .service(
                     web::resource("/")
                         .guard(fn_guard(|ctx| ctx.head().uri.query() == Some("query=test")))
@@ -369,7 +526,7 @@ mod tests {
     async fn keep_trailing_slash_unchanged() {
         let app = init_service(
             App::new()
-                .wrap(NormalizePath(TrailingSlash::MergeOnly))
+                .wrap(NormalizePath::new(TrailingSlash::MergeOnly))
                 .service(web::resource("/").to(HttpResponse::Ok))
                 .service(web::resource("/v1/something").to(HttpResponse::Ok))
                 .service(web::resource("/v1/").to(HttpResponse::Ok))
@@ -406,6 +563,65 @@ mod tests {
         }
     }
 
+    #[actix_rt::test]
+    async fn lowercase() {
+        let app = init_service(
+            App::new()
+                .wrap(NormalizePath::new(TrailingSlash::Trim).lowercase(true))
+                .service(
+                    web::resource("/v1/something")
+                        .guard(fn_guard(|ctx| ctx.head().uri.query() == Some("Query=Test")))
+                        .to(HttpResponse::Ok),
+                ),
+        )
+        .await;
+
+        let tests = vec![
+            ("/v1/something?Query=Test", true),
+            ("/V1/Something?Query=Test", true),
+            ("//V1//SOMETHING/?Query=Test", true),
+            ("/v1/something?query=test", false),
+        ];
+
+        for (uri, success) in tests {
+            let req = TestRequest::with_uri(uri).to_request();
+            let res = call_service(&app, req).await;
+            assert_eq!(res.status().is_success(), success, "Failed uri: {}", uri);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn redirect() {
+        let app = init_service(
+            App::new()
+                .wrap(
+                    NormalizePath::new(TrailingSlash::Always)
+                        .lowercase(true)
+                        .redirect(StatusCode::PERMANENT_REDIRECT),
+                )
+                .service(web::resource("/v1/something/").to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/v1/something/").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("//V1/something?query=test").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            res.headers().get(header::LOCATION).unwrap(),
+            "/v1/something/?query=test"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn redirect_non_redirection_status() {
+        NormalizePath::trim().redirect(StatusCode::OK);
+    }
+
     #[actix_rt::test]
     async fn no_path() {
         let app = init_service(
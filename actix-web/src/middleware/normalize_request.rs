@@ -0,0 +1,442 @@
+//! For middleware documentation, see [`NormalizeRequest`].
+
+use std::{collections::HashSet, rc::Rc};
+
+use actix_http::uri::{PathAndQuery, Uri};
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Ready};
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    http::header::{self, HeaderMap, HeaderName, HeaderValue},
+    service::{ServiceRequest, ServiceResponse},
+    Error,
+};
+
+/// Determines how the [`NormalizeRequest`] middleware treats repeated query string keys.
+///
+/// Keys are compared after percent-decoding, so `a=1&%61=2` contains the key `a` twice.
+///
+/// The default is `DuplicateQueryKeys::Keep`.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateQueryKeys {
+    /// Leave the query string as it is.
+    #[default]
+    Keep,
+
+    /// Keep only the first occurrence of each key.
+    First,
+
+    /// Keep only the last occurrence of each key.
+    Last,
+}
+
+/// Middleware for normalizing request headers and query strings before they reach guards and
+/// extractors.
+///
+/// Clients differ in how they send equivalent requests; some repeat a header instead of sending a
+/// comma-separated list, pad header values with whitespace, or repeat query parameters. Depending
+/// on whether a guard or extractor looks at the first value, the last value, or all of them, such
+/// requests can behave unexpectedly. This middleware rewrites requests according to a policy:
+///
+/// - [`trim_header_values()`](Self::trim_header_values()) strips leading and trailing spaces and
+///   tabs from header values (enabled by default);
+/// - [`collapse_headers()`](Self::collapse_headers()) combines repeated headers into a single
+///   comma-separated value, or a `; `-separated value for `Cookie` (enabled by default);
+/// - [`query_keys()`](Self::query_keys()) removes repeated query string keys, keeping either the
+///   first or the last occurrence (disabled by default).
+///
+/// Header names need no normalization; they are always lowercased when parsed.
+///
+/// Collapsing headers is only meaningful for headers defined as comma-separated lists. A singleton
+/// header that a client sends more than once reaches handlers as a combined value, which will
+/// typically fail to parse. Repeated `Set-Cookie` headers are never combined.
+///
+/// # Examples
+/// ```
+/// use actix_web::{
+///     middleware::{DuplicateQueryKeys, NormalizeRequest},
+///     web, App, HttpResponse,
+/// };
+///
+/// let app = App::new()
+///     .wrap(NormalizeRequest::new().query_keys(DuplicateQueryKeys::Last))
+///     .route("/", web::get().to(HttpResponse::Ok));
+/// ```
+#[derive(Debug, Clone)]
+pub struct NormalizeRequest {
+    inner: Rc<Inner>,
+}
+
+#[derive(Debug, Clone)]
+struct Inner {
+    trim_header_values: bool,
+    collapse_headers: bool,
+    query_keys: DuplicateQueryKeys,
+}
+
+impl Default for NormalizeRequest {
+    fn default() -> Self {
+        Self {
+            inner: Rc::new(Inner {
+                trim_header_values: true,
+                collapse_headers: true,
+                query_keys: DuplicateQueryKeys::Keep,
+            }),
+        }
+    }
+}
+
+impl NormalizeRequest {
+    /// Constructs new request normalization middleware that trims and collapses headers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether leading and trailing whitespace is stripped from header values.
+    pub fn trim_header_values(mut self, trim: bool) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .trim_header_values = trim;
+        self
+    }
+
+    /// Sets whether repeated headers are combined into a single value.
+    pub fn collapse_headers(mut self, collapse: bool) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .collapse_headers = collapse;
+        self
+    }
+
+    /// Sets how repeated query string keys are treated.
+    pub fn query_keys(mut self, policy: DuplicateQueryKeys) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .query_keys = policy;
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for NormalizeRequest
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = NormalizeRequestMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(NormalizeRequestMiddleware {
+            service,
+            inner: Rc::clone(&self.inner),
+        }))
+    }
+}
+
+#[doc(hidden)]
+pub struct NormalizeRequestMiddleware<S> {
+    service: S,
+    inner: Rc<Inner>,
+}
+
+/// Returns `val` without leading and trailing spaces and tabs.
+fn trim_ows(val: &[u8]) -> &[u8] {
+    let is_ows = |byte: &u8| *byte == b' ' || *byte == b'\t';
+
+    let start = val.iter().position(|b| !is_ows(b)).unwrap_or(val.len());
+    let end = val
+        .iter()
+        .rposition(|b| !is_ows(b))
+        .map_or(start, |end| end + 1);
+
+    &val[start..end]
+}
+
+/// Returns the separator used when combining repeated `name` headers, or `None` if they must not
+/// be combined.
+fn separator(name: &HeaderName) -> Option<&'static [u8]> {
+    match *name {
+        header::COOKIE => Some(b"; "),
+        header::SET_COOKIE => None,
+        _ => Some(b", "),
+    }
+}
+
+impl Inner {
+    /// Trims and collapses header values according to the policy.
+    fn normalize_headers(&self, headers: &mut HeaderMap) {
+        let needs_trim = self.trim_header_values
+            && headers
+                .iter()
+                .any(|(_, val)| trim_ows(val.as_bytes()).len() != val.len());
+
+        let needs_collapse = self.collapse_headers && headers.len() != headers.len_keys();
+
+        if !needs_trim && !needs_collapse {
+            return;
+        }
+
+        let original = std::mem::take(headers);
+
+        for name in original.keys() {
+            let vals = original.get_all(name).map(|val| {
+                if !self.trim_header_values {
+                    return val.clone();
+                }
+
+                let trimmed = trim_ows(val.as_bytes());
+
+                if trimmed.len() == val.len() {
+                    return val.clone();
+                }
+
+                // a trimmed valid header value is still valid
+                let mut trimmed = HeaderValue::from_bytes(trimmed).unwrap();
+                trimmed.set_sensitive(val.is_sensitive());
+                trimmed
+            });
+
+            match separator(name).filter(|_| self.collapse_headers) {
+                Some(sep) if original.get_all(name).len() > 1 => {
+                    let mut combined = BytesMut::new();
+                    let mut sensitive = false;
+
+                    for (idx, val) in vals.enumerate() {
+                        if idx > 0 {
+                            combined.extend_from_slice(sep);
+                        }
+
+                        combined.extend_from_slice(val.as_bytes());
+                        sensitive |= val.is_sensitive();
+                    }
+
+                    // joining valid header values with a valid separator yields a valid value
+                    let mut combined = HeaderValue::from_maybe_shared(combined.freeze()).unwrap();
+                    combined.set_sensitive(sensitive);
+                    headers.insert(name.clone(), combined);
+                }
+
+                _ => {
+                    for val in vals {
+                        headers.append(name.clone(), val);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Removes repeated keys from `query` according to the policy, returning `None` if nothing is
+    /// removed.
+    fn normalize_query(&self, query: &str) -> Option<String> {
+        if self.query_keys == DuplicateQueryKeys::Keep {
+            return None;
+        }
+
+        let pairs = query
+            .split('&')
+            .map(|pair| {
+                let key = url::form_urlencoded::parse(pair.as_bytes())
+                    .next()
+                    .map(|(key, _)| key.into_owned());
+
+                (pair, key)
+            })
+            .collect::<Vec<_>>();
+
+        let mut seen = HashSet::new();
+        let mut keep = vec![true; pairs.len()];
+
+        let order: Box<dyn Iterator<Item = usize>> = match self.query_keys {
+            DuplicateQueryKeys::Last => Box::new((0..pairs.len()).rev()),
+            _ => Box::new(0..pairs.len()),
+        };
+
+        for idx in order {
+            if let Some(key) = &pairs[idx].1 {
+                keep[idx] = seen.insert(key);
+            }
+        }
+
+        if keep.iter().all(|keep| *keep) {
+            return None;
+        }
+
+        let query = pairs
+            .iter()
+            .zip(keep)
+            .filter(|(_, keep)| *keep)
+            .map(|((pair, _), _)| *pair)
+            .collect::<Vec<_>>()
+            .join("&");
+
+        Some(query)
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for NormalizeRequestMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = S::Future;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let head = req.head_mut();
+
+        self.inner.normalize_headers(&mut head.headers);
+
+        let query = head.uri.query().and_then(|q| self.inner.normalize_query(q));
+
+        if let Some(query) = query {
+            let mut parts = head.uri.clone().into_parts();
+            let path = head.uri.path();
+
+            let path_and_query = if query.is_empty() {
+                Bytes::copy_from_slice(path.as_bytes())
+            } else {
+                Bytes::from(format!("{}?{}", path, query))
+            };
+            parts.path_and_query = Some(PathAndQuery::from_maybe_shared(path_and_query).unwrap());
+
+            let uri = Uri::from_parts(parts).unwrap();
+            req.match_info_mut().get_mut().update(&uri);
+            req.head_mut().uri = uri;
+        }
+
+        self.service.call(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        http::header::{ACCEPT, COOKIE},
+        test::{call_and_read_body, init_service, TestRequest},
+        web, App, HttpRequest,
+    };
+
+    async fn echo(req: HttpRequest) -> String {
+        let mut headers = req
+            .headers()
+            .iter()
+            .map(|(name, val)| format!("{}={}", name, val.to_str().unwrap()))
+            .collect::<Vec<_>>();
+        headers.sort();
+
+        format!("{}|{}", headers.join(","), req.query_string())
+    }
+
+    #[actix_rt::test]
+    async fn trims_and_collapses_headers() {
+        let app = init_service(
+            App::new()
+                .wrap(NormalizeRequest::new())
+                .default_service(web::to(echo)),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .append_header((ACCEPT, " text/html\t"))
+            .append_header((ACCEPT, "application/json "))
+            .append_header((COOKIE, "a=1"))
+            .append_header((COOKIE, "b=2"))
+            .append_header(("x-single", "  value  "))
+            .to_request();
+        let body = call_and_read_body(&app, req).await;
+        assert_eq!(
+            body,
+            "accept=text/html, application/json,cookie=a=1; b=2,x-single=value|"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn disabled_header_policies() {
+        let app = init_service(
+            App::new()
+                .wrap(
+                    NormalizeRequest::new()
+                        .trim_header_values(false)
+                        .collapse_headers(false),
+                )
+                .default_service(web::to(echo)),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .append_header((ACCEPT, " a"))
+            .append_header((ACCEPT, "b"))
+            .to_request();
+        let body = call_and_read_body(&app, req).await;
+        assert_eq!(body, "accept= a,accept=b|");
+
+        let app = init_service(
+            App::new()
+                .wrap(NormalizeRequest::new().collapse_headers(false))
+                .default_service(web::to(echo)),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .append_header((ACCEPT, " a"))
+            .append_header((ACCEPT, "b"))
+            .to_request();
+        let body = call_and_read_body(&app, req).await;
+        assert_eq!(body, "accept=a,accept=b|");
+    }
+
+    #[actix_rt::test]
+    async fn query_keys() {
+        for (policy, uri, expected) in [
+            (DuplicateQueryKeys::Keep, "/?a=1&b=2&a=3", "|a=1&b=2&a=3"),
+            (DuplicateQueryKeys::First, "/?a=1&b=2&a=3", "|a=1&b=2"),
+            (DuplicateQueryKeys::Last, "/?a=1&b=2&a=3", "|b=2&a=3"),
+            (
+                DuplicateQueryKeys::First,
+                "/?a=1&%61=2&a+b=3&a%20b",
+                "|a=1&a+b=3",
+            ),
+            (DuplicateQueryKeys::Last, "/?a=1&b=2", "|a=1&b=2"),
+            (DuplicateQueryKeys::Last, "/", "|"),
+        ] {
+            let app = init_service(
+                App::new()
+                    .wrap(NormalizeRequest::new().query_keys(policy))
+                    .default_service(web::to(echo)),
+            )
+            .await;
+
+            let req = TestRequest::with_uri(uri).to_request();
+            let body = call_and_read_body(&app, req).await;
+            assert_eq!(body, expected, "{policy:?} {uri}");
+        }
+    }
+
+    #[actix_rt::test]
+    async fn query_keys_with_path_params() {
+        let app = init_service(
+            App::new()
+                .wrap(NormalizeRequest::new().query_keys(DuplicateQueryKeys::Last))
+                .route(
+                    "/{id}",
+                    web::get().to(|path: web::Path<String>, query: web::Query<Vec<(String, String)>>| async move {
+                        format!("{} {:?}", path.into_inner(), query.into_inner())
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/abc?page=1&page=2").to_request();
+        let body = call_and_read_body(&app, req).await;
+        assert_eq!(body, "abc [(\"page\", \"2\")]");
+    }
+}
@@ -0,0 +1,597 @@
+//! For middleware documentation, see [`RateLimiter`].
+
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    fmt,
+    hash::BuildHasher as _,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use actix_service::{Service, Transform};
+use actix_utils::future::{ok, ready, Ready};
+use futures_core::future::LocalBoxFuture;
+
+use crate::{
+    body::EitherBody,
+    dev::TrustedProxies,
+    http::header::{self, HeaderValue},
+    service::{ServiceRequest, ServiceResponse},
+    web::Clock,
+    Error, HttpResponse,
+};
+
+/// Number of acquisitions on a shard of [`MemoryRateLimitStore`] between removals of idle keys.
+const PRUNE_INTERVAL: u32 = 1024;
+
+/// Number of requests a client may make within a period of time.
+///
+/// Quotas are enforced using a token bucket: each key starts with `burst` tokens, each request
+/// takes one, and tokens are replenished evenly over `period` up to the `burst` limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitQuota {
+    burst: u32,
+    period: Duration,
+}
+
+impl RateLimitQuota {
+    /// Constructs a quota of `burst` requests that are fully replenished after `period`.
+    ///
+    /// # Panics
+    /// Panics if `burst` or `period` is zero.
+    pub fn new(burst: u32, period: Duration) -> Self {
+        assert!(burst > 0, "rate limit burst must not be zero");
+        assert!(!period.is_zero(), "rate limit period must not be zero");
+
+        Self { burst, period }
+    }
+
+    /// Constructs a quota of `n` requests per second.
+    ///
+    /// # Panics
+    /// Panics if `n` is zero.
+    pub fn per_second(n: u32) -> Self {
+        Self::new(n, Duration::from_secs(1))
+    }
+
+    /// Constructs a quota of `n` requests per minute.
+    ///
+    /// # Panics
+    /// Panics if `n` is zero.
+    pub fn per_minute(n: u32) -> Self {
+        Self::new(n, Duration::from_secs(60))
+    }
+
+    /// Returns the number of requests that may be made at once.
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    /// Returns the time after which an exhausted quota is fully replenished.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Returns the number of tokens replenished per second.
+    fn rate(&self) -> f64 {
+        f64::from(self.burst) / self.period.as_secs_f64()
+    }
+}
+
+/// Outcome of taking a token from a [`RateLimitStore`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    /// The request may proceed.
+    Allowed {
+        /// Number of requests that may still be made without waiting.
+        remaining: u32,
+    },
+
+    /// The request exceeds the quota.
+    Limited {
+        /// Time until the next request will be allowed.
+        retry_after: Duration,
+    },
+}
+
+/// Storage for the rate limiting state of each key.
+///
+/// Implement this trait to share rate limits between several servers, e.g., using Redis. Futures
+/// returned by the store must be `'static`; clone anything needed from `key` before returning.
+pub trait RateLimitStore: Send + Sync + 'static {
+    /// Takes a token for `key` at time `now` according to `quota`.
+    ///
+    /// Errors are returned to the client without calling the wrapped service.
+    fn acquire(
+        &self,
+        key: &str,
+        quota: &RateLimitQuota,
+        now: SystemTime,
+    ) -> LocalBoxFuture<'static, Result<RateLimitDecision, Error>>;
+}
+
+/// In-memory, sharded [`RateLimitStore`].
+///
+/// Keys are spread across a number of independently locked shards to reduce contention between
+/// workers. Keys whose quota has been fully replenished are removed periodically.
+#[derive(Clone)]
+pub struct MemoryRateLimitStore {
+    shards: Arc<[Mutex<Shard>]>,
+    hasher: RandomState,
+}
+
+#[derive(Default)]
+struct Shard {
+    buckets: HashMap<String, Bucket>,
+    acquisitions: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: SystemTime,
+}
+
+impl Bucket {
+    /// Returns the number of tokens in the bucket at time `now`.
+    fn tokens_at(&self, quota: &RateLimitQuota, now: SystemTime) -> f64 {
+        let elapsed = now.duration_since(self.updated).unwrap_or_default();
+        (self.tokens + elapsed.as_secs_f64() * quota.rate()).min(f64::from(quota.burst))
+    }
+}
+
+impl MemoryRateLimitStore {
+    /// Constructs new store with 16 shards.
+    pub fn new() -> Self {
+        Self::with_shards(16)
+    }
+
+    /// Constructs new store with the given number of shards.
+    ///
+    /// # Panics
+    /// Panics if `shards` is zero.
+    pub fn with_shards(shards: usize) -> Self {
+        assert!(shards > 0, "number of shards must not be zero");
+
+        Self {
+            shards: (0..shards).map(|_| Mutex::default()).collect(),
+            hasher: RandomState::new(),
+        }
+    }
+
+    fn take(&self, key: &str, quota: &RateLimitQuota, now: SystemTime) -> RateLimitDecision {
+        let idx = self.hasher.hash_one(key) as usize % self.shards.len();
+        let mut shard = self.shards[idx].lock().unwrap();
+
+        shard.acquisitions += 1;
+        if shard.acquisitions >= PRUNE_INTERVAL {
+            shard.acquisitions = 0;
+            let burst = f64::from(quota.burst);
+            shard
+                .buckets
+                .retain(|_, bucket| bucket.tokens_at(quota, now) < burst);
+        }
+
+        let tokens = match shard.buckets.get(key) {
+            Some(bucket) => bucket.tokens_at(quota, now),
+            None => f64::from(quota.burst),
+        };
+
+        if tokens >= 1.0 {
+            let tokens = tokens - 1.0;
+
+            shard.buckets.insert(
+                key.to_owned(),
+                Bucket {
+                    tokens,
+                    updated: now,
+                },
+            );
+
+            RateLimitDecision::Allowed {
+                remaining: tokens as u32,
+            }
+        } else {
+            RateLimitDecision::Limited {
+                retry_after: Duration::from_secs_f64((1.0 - tokens) / quota.rate()),
+            }
+        }
+    }
+}
+
+impl Default for MemoryRateLimitStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for MemoryRateLimitStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryRateLimitStore")
+            .field("shards", &self.shards.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl RateLimitStore for MemoryRateLimitStore {
+    fn acquire(
+        &self,
+        key: &str,
+        quota: &RateLimitQuota,
+        now: SystemTime,
+    ) -> LocalBoxFuture<'static, Result<RateLimitDecision, Error>> {
+        Box::pin(ok(self.take(key, quota, now)))
+    }
+}
+
+type KeyFn = dyn Fn(&ServiceRequest) -> Option<String> + Send + Sync;
+
+/// Middleware for limiting the rate of requests made by each client.
+///
+/// Requests are keyed by the IP address of the peer unless a different [key extractor](Self::key)
+/// is set. When [`TrustedProxies`] are registered as app data, requests are keyed by the client
+/// address reported by [`ConnectionInfo::realip_remote_addr()`] instead, so that clients behind a
+/// reverse proxy are limited separately. Forwarding headers are not considered otherwise, since
+/// any client could evade the limit by sending them. Requests without a key are not limited.
+///
+/// Each key may make requests according to the [quota](RateLimitQuota). Requests beyond it are
+/// answered with `429 Too Many Requests` and a `Retry-After` header without calling the wrapped
+/// service. Time is read from the [`Clock`] registered as app data, if any.
+///
+/// Rate limiting state is kept in a [`MemoryRateLimitStore`] unless a different
+/// [store](Self::store) is set. Since the app factory runs once per worker, construct the limiter
+/// outside of it and clone it in so that all workers share the same state. Wrap scopes or
+/// resources with separate limiters to give them separate quotas.
+///
+/// # Examples
+/// ```
+/// use actix_web::{
+///     dev::TrustedProxies,
+///     middleware::{RateLimitQuota, RateLimiter},
+///     web, App, HttpResponse, HttpServer,
+/// };
+///
+/// # fn run() -> std::io::Result<actix_web::dev::Server> {
+/// let limiter = RateLimiter::new(RateLimitQuota::per_second(10));
+/// let login_limiter = RateLimiter::new(RateLimitQuota::per_minute(5));
+///
+/// let srv = HttpServer::new(move || {
+///     App::new()
+///         // limit clients by the address forwarded by the reverse proxy
+///         .app_data(TrustedProxies::new().add_range("10.0.0.0/8"))
+///         .wrap(limiter.clone())
+///         .service(
+///             web::resource("/login")
+///                 .wrap(login_limiter.clone())
+///                 .route(web::post().to(HttpResponse::Ok)),
+///         )
+/// })
+/// .bind(("127.0.0.1", 8080))?
+/// .run();
+/// # Ok(srv)
+/// # }
+/// ```
+///
+/// [`TrustedProxies`]: crate::dev::TrustedProxies
+/// [`ConnectionInfo::realip_remote_addr()`]: crate::dev::ConnectionInfo::realip_remote_addr
+#[derive(Clone)]
+pub struct RateLimiter {
+    quota: RateLimitQuota,
+    store: Arc<dyn RateLimitStore>,
+    key: Option<Arc<KeyFn>>,
+}
+
+impl RateLimiter {
+    /// Constructs new rate limiting middleware enforcing `quota` per client IP address.
+    ///
+    /// See the [type documentation](Self) for how the client IP address is determined.
+    pub fn new(quota: RateLimitQuota) -> Self {
+        Self {
+            quota,
+            store: Arc::new(MemoryRateLimitStore::new()),
+            key: None,
+        }
+    }
+
+    /// Sets the store in which rate limiting state is kept.
+    pub fn store(mut self, store: impl RateLimitStore) -> Self {
+        self.store = Arc::new(store);
+        self
+    }
+
+    /// Sets the function used to derive the rate limiting key of a request.
+    ///
+    /// Requests for which `key` returns `None` are not limited.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::middleware::{RateLimitQuota, RateLimiter};
+    ///
+    /// // limit per API key instead of per IP address
+    /// let limiter = RateLimiter::new(RateLimitQuota::per_minute(100)).key(|req| {
+    ///     req.headers()
+    ///         .get("x-api-key")
+    ///         .and_then(|key| key.to_str().ok())
+    ///         .map(str::to_owned)
+    /// });
+    /// ```
+    pub fn key<F>(mut self, key: F) -> Self
+    where
+        F: Fn(&ServiceRequest) -> Option<String> + Send + Sync + 'static,
+    {
+        self.key = Some(Arc::new(key));
+        self
+    }
+
+    fn key_for(&self, req: &ServiceRequest) -> Option<String> {
+        match &self.key {
+            Some(key) => key(req),
+            None if req.app_data::<TrustedProxies>().is_some() => req
+                .connection_info()
+                .realip_remote_addr()
+                .map(str::to_owned),
+            None => req.peer_addr().map(|addr| addr.ip().to_string()),
+        }
+    }
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("quota", &self.quota)
+            .field("key", &self.key.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimiter
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RateLimiterMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimiterMiddleware {
+            service: Rc::new(service),
+            limiter: self.clone(),
+        }))
+    }
+}
+
+#[doc(hidden)]
+pub struct RateLimiterMiddleware<S> {
+    service: Rc<S>,
+    limiter: RateLimiter,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimiterMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let Some(key) = self.limiter.key_for(&req) else {
+            let fut = self.service.call(req);
+            return Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) });
+        };
+
+        let now = req.app_data::<Clock>().cloned().unwrap_or_default().now();
+        let decision = self.limiter.store.acquire(&key, &self.limiter.quota, now);
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            match decision.await? {
+                RateLimitDecision::Allowed { .. } => service
+                    .call(req)
+                    .await
+                    .map(ServiceResponse::map_into_left_body),
+
+                RateLimitDecision::Limited { retry_after } => {
+                    log::debug!("rate limit exceeded for {key}");
+
+                    // round up so that retrying after the given number of seconds succeeds
+                    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+
+                    let res = HttpResponse::TooManyRequests()
+                        .insert_header((header::RETRY_AFTER, HeaderValue::from(secs.max(1))))
+                        .finish();
+
+                    Ok(req.into_response(res).map_into_right_body())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{self, TestRequest},
+        web, App,
+    };
+
+    #[test]
+    fn memory_store_token_bucket() {
+        let store = MemoryRateLimitStore::with_shards(1);
+        let quota = RateLimitQuota::new(2, Duration::from_secs(10));
+        let start = SystemTime::UNIX_EPOCH;
+
+        assert_eq!(
+            store.take("a", &quota, start),
+            RateLimitDecision::Allowed { remaining: 1 }
+        );
+        assert_eq!(
+            store.take("a", &quota, start),
+            RateLimitDecision::Allowed { remaining: 0 }
+        );
+        assert_eq!(
+            store.take("a", &quota, start),
+            RateLimitDecision::Limited {
+                retry_after: Duration::from_secs(5)
+            }
+        );
+
+        // other keys are unaffected
+        assert_eq!(
+            store.take("b", &quota, start),
+            RateLimitDecision::Allowed { remaining: 1 }
+        );
+
+        // one token is replenished every 5 seconds
+        let later = start + Duration::from_secs(6);
+        assert_eq!(
+            store.take("a", &quota, later),
+            RateLimitDecision::Allowed { remaining: 0 }
+        );
+        assert!(matches!(
+            store.take("a", &quota, later),
+            RateLimitDecision::Limited { .. }
+        ));
+    }
+
+    #[test]
+    fn memory_store_prunes_full_buckets() {
+        let store = MemoryRateLimitStore::with_shards(1);
+        let quota = RateLimitQuota::per_second(1);
+        let start = SystemTime::UNIX_EPOCH;
+
+        for i in 0..PRUNE_INTERVAL - 1 {
+            store.take(&i.to_string(), &quota, start);
+        }
+        assert_eq!(store.shards[0].lock().unwrap().buckets.len(), 1023);
+
+        store.take("last", &quota, start + Duration::from_secs(1));
+        assert_eq!(store.shards[0].lock().unwrap().buckets.len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn limits_requests() {
+        let clock = Clock::frozen(SystemTime::UNIX_EPOCH);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(clock.clone())
+                .wrap(RateLimiter::new(RateLimitQuota::new(
+                    2,
+                    Duration::from_secs(3),
+                )))
+                .default_service(web::to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = || TestRequest::default().peer_addr("10.0.0.1:1234".parse().unwrap());
+
+        for _ in 0..2 {
+            let res = test::call_service(&app, req().to_request()).await;
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+
+        let res = test::call_service(&app, req().to_request()).await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers().get(header::RETRY_AFTER).unwrap(), "2");
+
+        // other clients are unaffected
+        let res = test::call_service(
+            &app,
+            TestRequest::default()
+                .peer_addr("10.0.0.2:1234".parse().unwrap())
+                .to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        clock.advance(Duration::from_secs(2));
+        let res = test::call_service(&app, req().to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn forwarded_addresses() {
+        let app = test::init_service(
+            App::new()
+                .wrap(RateLimiter::new(RateLimitQuota::per_minute(1)))
+                .default_service(web::to(HttpResponse::Ok)),
+        )
+        .await;
+
+        // forwarding headers are ignored without trusted proxies
+        for (forwarded_for, status) in [
+            ("192.0.2.1", StatusCode::OK),
+            ("192.0.2.2", StatusCode::TOO_MANY_REQUESTS),
+        ] {
+            let req = TestRequest::default()
+                .peer_addr("10.0.0.1:1234".parse().unwrap())
+                .insert_header(("x-forwarded-for", forwarded_for))
+                .to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), status);
+        }
+
+        let app = test::init_service(
+            App::new()
+                .app_data(TrustedProxies::new().add_range("10.0.0.0/8"))
+                .wrap(RateLimiter::new(RateLimitQuota::per_minute(1)))
+                .default_service(web::to(HttpResponse::Ok)),
+        )
+        .await;
+
+        // clients behind trusted proxies are limited separately
+        for (forwarded_for, status) in [
+            ("192.0.2.1", StatusCode::OK),
+            ("192.0.2.2", StatusCode::OK),
+            ("192.0.2.1", StatusCode::TOO_MANY_REQUESTS),
+        ] {
+            let req = TestRequest::default()
+                .peer_addr("10.0.0.1:1234".parse().unwrap())
+                .insert_header(("x-forwarded-for", forwarded_for))
+                .to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), status);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn custom_key() {
+        let limiter = RateLimiter::new(RateLimitQuota::per_minute(1)).key(|req| {
+            req.headers()
+                .get("x-api-key")
+                .and_then(|key| key.to_str().ok())
+                .map(str::to_owned)
+        });
+
+        let app = test::init_service(
+            App::new()
+                .wrap(limiter)
+                .default_service(web::to(HttpResponse::Ok)),
+        )
+        .await;
+
+        for status in [StatusCode::OK, StatusCode::TOO_MANY_REQUESTS] {
+            let req = TestRequest::default()
+                .insert_header(("x-api-key", "abc"))
+                .to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), status);
+        }
+
+        // requests without a key are not limited
+        for _ in 0..2 {
+            let res = test::call_service(&app, TestRequest::default().to_request()).await;
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+    }
+}
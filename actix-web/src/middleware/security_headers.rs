@@ -0,0 +1,362 @@
+//! For middleware documentation, see [`SecurityHeaders`].
+
+use std::{fmt, time::Duration};
+
+use actix_utils::future::Ready;
+
+use super::{default_headers::DefaultHeadersMiddleware, DefaultHeaders};
+use crate::{
+    dev::{Service, Transform},
+    http::header::{
+        HeaderMap, HeaderName, TryIntoHeaderValue, CONTENT_SECURITY_POLICY,
+        CONTENT_SECURITY_POLICY_REPORT_ONLY, REFERRER_POLICY, STRICT_TRANSPORT_SECURITY,
+        X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+    },
+    service::{ServiceRequest, ServiceResponse},
+    Error,
+};
+
+/// Value of the `X-Frame-Options` header set by [`SecurityHeaders`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameOptions {
+    /// Pages may not be displayed in frames.
+    Deny,
+
+    /// Pages may only be displayed in frames on the same origin.
+    SameOrigin,
+}
+
+/// Builder for `Content-Security-Policy` header values.
+///
+/// # Examples
+/// ```
+/// use actix_web::middleware::ContentSecurityPolicy;
+///
+/// let csp = ContentSecurityPolicy::new()
+///     .directive("default-src", &["'self'"])
+///     .directive("img-src", &["'self'", "https://images.example.com"])
+///     .directive("upgrade-insecure-requests", &[]);
+///
+/// assert_eq!(
+///     csp.to_string(),
+///     "default-src 'self'; img-src 'self' https://images.example.com; upgrade-insecure-requests",
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentSecurityPolicy {
+    directives: Vec<(String, Vec<String>)>,
+    report_only: bool,
+}
+
+impl ContentSecurityPolicy {
+    /// Constructs an empty policy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a directive, replacing any sources previously set for it.
+    ///
+    /// Directives that take no sources, like `upgrade-insecure-requests`, are set by passing an
+    /// empty slice.
+    pub fn directive(mut self, name: &str, sources: &[&str]) -> Self {
+        let sources = sources.iter().map(|&src| src.to_owned()).collect();
+
+        match self.directives.iter_mut().find(|(n, _)| n == name) {
+            Some((_, srcs)) => *srcs = sources,
+            None => self.directives.push((name.to_owned(), sources)),
+        }
+
+        self
+    }
+
+    /// Sets whether the policy is only reported and not enforced.
+    ///
+    /// Report-only policies are sent in the `Content-Security-Policy-Report-Only` header.
+    pub fn report_only(mut self, report_only: bool) -> Self {
+        self.report_only = report_only;
+        self
+    }
+
+    fn header_name(&self) -> HeaderName {
+        if self.report_only {
+            CONTENT_SECURITY_POLICY_REPORT_ONLY
+        } else {
+            CONTENT_SECURITY_POLICY
+        }
+    }
+}
+
+impl fmt::Display for ContentSecurityPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, (name, sources)) in self.directives.iter().enumerate() {
+            if idx > 0 {
+                f.write_str("; ")?;
+            }
+
+            f.write_str(name)?;
+
+            for src in sources {
+                write!(f, " {src}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Middleware for setting hardened security headers.
+///
+/// The headers are added to every response unless a header with the same name is already set,
+/// e.g., by a handler, just like [`DefaultHeaders`] does. By default, the following are set:
+///
+/// Header | Value
+/// -------|------
+/// `Strict-Transport-Security` | `max-age=31536000; includeSubDomains`
+/// `X-Content-Type-Options` | `nosniff`
+/// `X-Frame-Options` | `DENY`
+/// `Referrer-Policy` | `strict-origin-when-cross-origin`
+/// `Content-Security-Policy` | `default-src 'self'; base-uri 'self'; frame-ancestors 'none'; object-src 'none'`
+///
+/// Browsers ignore `Strict-Transport-Security` on plain HTTP responses, so it is safe to send
+/// during development. Wrap an app or scope to configure the headers for it; a
+/// `SecurityHeaders` can also be converted into [`DefaultHeaders`] to add further headers.
+///
+/// # Examples
+/// ```
+/// use actix_web::{
+///     middleware::{ContentSecurityPolicy, FrameOptions, SecurityHeaders},
+///     web, App, HttpResponse,
+/// };
+///
+/// let app = App::new()
+///     .wrap(
+///         SecurityHeaders::new()
+///             .frame_options(FrameOptions::SameOrigin)
+///             .content_security_policy(
+///                 ContentSecurityPolicy::new()
+///                     .directive("default-src", &["'self'"])
+///                     .directive("script-src", &["'self'", "https://cdn.example.com"]),
+///             ),
+///     )
+///     .route("/", web::get().to(HttpResponse::Ok));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    headers: HeaderMap,
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self {
+            headers: HeaderMap::new(),
+        }
+        .hsts(Duration::from_secs(31_536_000), true, false)
+        .content_type_options(true)
+        .frame_options(FrameOptions::Deny)
+        .referrer_policy("strict-origin-when-cross-origin")
+        .content_security_policy(
+            ContentSecurityPolicy::new()
+                .directive("default-src", &["'self'"])
+                .directive("base-uri", &["'self'"])
+                .directive("frame-ancestors", &["'none'"])
+                .directive("object-src", &["'none'"]),
+        )
+    }
+}
+
+impl SecurityHeaders {
+    /// Constructs security headers middleware with hardened defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `Strict-Transport-Security` header.
+    pub fn hsts(mut self, max_age: Duration, include_subdomains: bool, preload: bool) -> Self {
+        let mut value = format!("max-age={}", max_age.as_secs());
+
+        if include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+
+        if preload {
+            value.push_str("; preload");
+        }
+
+        self.set(STRICT_TRANSPORT_SECURITY, value);
+        self
+    }
+
+    /// Sets whether the `X-Content-Type-Options: nosniff` header is sent.
+    pub fn content_type_options(mut self, nosniff: bool) -> Self {
+        if nosniff {
+            self.set(X_CONTENT_TYPE_OPTIONS, "nosniff");
+        } else {
+            self.headers.remove(X_CONTENT_TYPE_OPTIONS);
+        }
+
+        self
+    }
+
+    /// Sets the `X-Frame-Options` header.
+    pub fn frame_options(mut self, options: FrameOptions) -> Self {
+        let value = match options {
+            FrameOptions::Deny => "DENY",
+            FrameOptions::SameOrigin => "SAMEORIGIN",
+        };
+
+        self.set(X_FRAME_OPTIONS, value);
+        self
+    }
+
+    /// Sets the `Referrer-Policy` header.
+    ///
+    /// # Panics
+    /// Panics if `policy` is not a valid header value.
+    pub fn referrer_policy(mut self, policy: impl TryIntoHeaderValue) -> Self {
+        self.set(REFERRER_POLICY, policy);
+        self
+    }
+
+    /// Sets the content security policy, replacing the default policy.
+    ///
+    /// # Panics
+    /// Panics if the policy is not a valid header value.
+    pub fn content_security_policy(mut self, csp: ContentSecurityPolicy) -> Self {
+        self.headers.remove(CONTENT_SECURITY_POLICY);
+        self.headers.remove(CONTENT_SECURITY_POLICY_REPORT_ONLY);
+        self.set(csp.header_name(), csp.to_string());
+        self
+    }
+
+    /// Removes a header from the set, e.g., to not send a content security policy at all.
+    pub fn without(mut self, name: HeaderName) -> Self {
+        self.headers.remove(name);
+        self
+    }
+
+    fn set(&mut self, name: HeaderName, value: impl TryIntoHeaderValue) {
+        match value.try_into_value() {
+            Ok(value) => {
+                self.headers.insert(name, value);
+            }
+            Err(err) => panic!("Invalid header: {}", err.into()),
+        }
+    }
+}
+
+impl From<SecurityHeaders> for DefaultHeaders {
+    fn from(security: SecurityHeaders) -> Self {
+        security
+            .headers
+            .into_iter()
+            .fold(DefaultHeaders::new(), |headers, (name, value)| {
+                headers.add((name, value))
+            })
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for SecurityHeaders
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = DefaultHeadersMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        DefaultHeaders::from(self.clone()).new_transform(service)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test::{self, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    async fn embeddable() -> HttpResponse {
+        HttpResponse::Ok()
+            .insert_header((X_FRAME_OPTIONS, "SAMEORIGIN"))
+            .finish()
+    }
+
+    #[actix_rt::test]
+    async fn hardened_defaults() {
+        let app = test::init_service(
+            App::new()
+                .wrap(SecurityHeaders::new())
+                .route("/", web::get().to(HttpResponse::Ok))
+                .route("/embeddable", web::get().to(embeddable)),
+        )
+        .await;
+
+        let res = test::call_service(&app, TestRequest::default().to_request()).await;
+        let headers = res.headers();
+        assert_eq!(
+            headers.get(STRICT_TRANSPORT_SECURITY).unwrap(),
+            "max-age=31536000; includeSubDomains"
+        );
+        assert_eq!(headers.get(X_CONTENT_TYPE_OPTIONS).unwrap(), "nosniff");
+        assert_eq!(headers.get(X_FRAME_OPTIONS).unwrap(), "DENY");
+        assert_eq!(
+            headers.get(REFERRER_POLICY).unwrap(),
+            "strict-origin-when-cross-origin"
+        );
+        assert_eq!(
+            headers.get(CONTENT_SECURITY_POLICY).unwrap(),
+            "default-src 'self'; base-uri 'self'; frame-ancestors 'none'; object-src 'none'"
+        );
+
+        // handler-set headers are not overridden
+        let req = TestRequest::with_uri("/embeddable").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.headers().get(X_FRAME_OPTIONS).unwrap(), "SAMEORIGIN");
+    }
+
+    #[actix_rt::test]
+    async fn customized() {
+        let security = SecurityHeaders::new()
+            .hsts(Duration::from_secs(60), false, true)
+            .content_type_options(false)
+            .without(X_FRAME_OPTIONS)
+            .content_security_policy(
+                ContentSecurityPolicy::new()
+                    .directive("default-src", &["'none'"])
+                    .directive("default-src", &["'self'"])
+                    .report_only(true),
+            );
+
+        let app = test::init_service(
+            App::new()
+                .wrap(DefaultHeaders::from(security).add(("x-version", "1")))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let res = test::call_service(&app, TestRequest::default().to_request()).await;
+        let headers = res.headers();
+        assert_eq!(
+            headers.get(STRICT_TRANSPORT_SECURITY).unwrap(),
+            "max-age=60; preload"
+        );
+        assert!(!headers.contains_key(X_CONTENT_TYPE_OPTIONS));
+        assert!(!headers.contains_key(X_FRAME_OPTIONS));
+        assert!(!headers.contains_key(CONTENT_SECURITY_POLICY));
+        assert_eq!(
+            headers.get(CONTENT_SECURITY_POLICY_REPORT_ONLY).unwrap(),
+            "default-src 'self'"
+        );
+        assert_eq!(headers.get("x-version").unwrap(), "1");
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_referrer_policy() {
+        SecurityHeaders::new().referrer_policy("\n");
+    }
+}
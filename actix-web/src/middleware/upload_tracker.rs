@@ -0,0 +1,206 @@
+//! For middleware documentation, see [`UploadTracker`].
+
+use std::{fmt, rc::Rc};
+
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Ready};
+
+use crate::{
+    dev,
+    http::header,
+    request_context::RequestContext,
+    service::{ServiceRequest, ServiceResponse},
+    upload_progress::{ProgressPayload, UploadProgress},
+    Error, HttpMessage as _,
+};
+
+type Callback = Rc<dyn Fn(&UploadProgress)>;
+
+/// Middleware for tracking the progress of request body uploads.
+///
+/// For each request, an [`UploadProgress`] handle is inserted into the request's extensions and
+/// the request payload is wrapped so that every chunk read from it, by any extractor, updates the
+/// handle. The total size is taken from the `Content-Length` header, if present. Handlers can
+/// extract the handle to observe the upload themselves.
+///
+/// An optional callback, set with [`on_progress()`](Self::on_progress), is registered as a
+/// listener on every request's handle. It is called each time a chunk of the body is received,
+/// once the body is complete, and, for multipart bodies read with `actix-multipart`, as each part
+/// progresses. The callback is created per worker, like the rest of the app, so it need not be
+/// `Send`; use the [request ID](UploadProgress::request_id) to correlate events with requests.
+///
+/// Since the payload is wrapped, [`web::Payload::set_read_watermarks()`] and
+/// [`web::Payload::trailers()`] have no effect on tracked requests.
+///
+/// [`web::Payload::set_read_watermarks()`]: crate::web::Payload::set_read_watermarks
+/// [`web::Payload::trailers()`]: crate::web::Payload::trailers
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::UploadTracker, web, App, HttpResponse};
+///
+/// let app = App::new()
+///     .wrap(UploadTracker::new().on_progress(|progress| {
+///         if let Some(total) = progress.total() {
+///             log::info!(
+///                 "[{}] upload {}% done",
+///                 progress.request_id(),
+///                 progress.received() * 100 / total.max(1),
+///             );
+///         }
+///     }))
+///     .route("/upload", web::post().to(|body: web::Bytes| async move {
+///         HttpResponse::Ok().body(format!("received {} bytes", body.len()))
+///     }));
+/// ```
+#[derive(Clone, Default)]
+pub struct UploadTracker {
+    callback: Option<Callback>,
+}
+
+impl UploadTracker {
+    /// Constructs new upload tracking middleware without a callback.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a callback that is called whenever any request's upload makes progress.
+    pub fn on_progress(mut self, callback: impl Fn(&UploadProgress) + 'static) -> Self {
+        self.callback = Some(Rc::new(callback));
+        self
+    }
+}
+
+impl fmt::Debug for UploadTracker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UploadTracker")
+            .field("callback", &self.callback.is_some())
+            .finish()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for UploadTracker
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = UploadTrackerMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(UploadTrackerMiddleware {
+            service,
+            callback: self.callback.clone(),
+        }))
+    }
+}
+
+#[doc(hidden)]
+pub struct UploadTrackerMiddleware<S> {
+    service: S,
+    callback: Option<Callback>,
+}
+
+impl<S, B> Service<ServiceRequest> for UploadTrackerMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = S::Future;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let total = req
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok())
+            .and_then(|len| len.parse::<u64>().ok());
+
+        let ctx = RequestContext::get_or_insert(req.request());
+        let progress = UploadProgress::new(ctx.request_id().to_owned(), total);
+
+        if let Some(callback) = &self.callback {
+            let callback = Rc::clone(callback);
+            progress.on_progress(move |progress| callback(progress));
+        }
+
+        let payload = ProgressPayload::new(req.take_payload(), progress.clone());
+        let payload: actix_http::BoxedPayloadStream = Box::pin(payload);
+        req.set_payload(dev::Payload::from(payload));
+
+        req.extensions_mut().insert(progress);
+
+        self.service.call(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::{
+        test::{self, TestRequest},
+        web, App,
+    };
+
+    #[actix_rt::test]
+    async fn reports_progress() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+
+        let app = test::init_service(
+            App::new()
+                .wrap(UploadTracker::new().on_progress({
+                    let events = Rc::clone(&events);
+                    move |progress| {
+                        events.borrow_mut().push((
+                            progress.received(),
+                            progress.total(),
+                            progress.is_complete(),
+                        ))
+                    }
+                }))
+                .route(
+                    "/",
+                    web::post().to(|progress: web::UploadProgress, body: String| async move {
+                        format!(
+                            "{} {} {}",
+                            body,
+                            progress.received(),
+                            progress.is_complete()
+                        )
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .uri("/")
+            .insert_header((header::CONTENT_LENGTH, "5"))
+            .set_payload("hello")
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "hello 5 true");
+
+        let events = events.borrow();
+        assert_eq!(events.last(), Some(&(5, Some(5), true)));
+        assert!(events.iter().all(|&(received, ..)| received <= 5));
+    }
+
+    #[actix_rt::test]
+    async fn missing_middleware() {
+        let app = test::init_service(App::new().route(
+            "/",
+            web::post().to(|_: web::UploadProgress| async { "unreachable" }),
+        ))
+        .await;
+
+        let req = TestRequest::post().uri("/").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), crate::http::StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
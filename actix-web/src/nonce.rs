@@ -0,0 +1,525 @@
+//! Single-use nonces for replay protection.
+//!
+//! APIs that must not act twice on the same request, even when it is captured and re-sent by an
+//! attacker, can require each state-changing request to carry a nonce previously issued by the
+//! server. The [`NonceIssuer`] extractor issues nonces and the [`Nonce`] extractor validates the
+//! nonce in the `Replay-Nonce` request header, consuming it so that any later request carrying the
+//! same nonce is rejected.
+//!
+//! Issued nonces are kept in a [`NonceStore`] until they are used or expire. [`MemoryNonceStore`]
+//! keeps them in memory, which is enough for a single server process; deployments that balance
+//! requests across processes need a shared store. The current time is read from the
+//! [`Clock`](crate::web::Clock) app data, if registered, so that expiry can be tested
+//! deterministically.
+//!
+//! # Examples
+//! ```
+//! use actix_web::{
+//!     nonce::{MemoryNonceStore, Nonce, NonceConfig, NonceIssuer},
+//!     post, web, App, Error, HttpResponse,
+//! };
+//!
+//! #[post("/new-nonce")]
+//! async fn new_nonce(issuer: NonceIssuer) -> Result<HttpResponse, Error> {
+//!     let nonce = issuer.issue().await?;
+//!     Ok(HttpResponse::Ok().insert_header(("replay-nonce", nonce)).finish())
+//! }
+//!
+//! #[post("/transfers")]
+//! async fn transfer(_nonce: Nonce) -> HttpResponse {
+//!     // ... a request with this nonce can not be processed again
+//!     HttpResponse::Created().finish()
+//! }
+//!
+//! let config = NonceConfig::new(MemoryNonceStore::default());
+//!
+//! let app = App::new()
+//!     .app_data(config)
+//!     .service(new_nonce)
+//!     .service(transfer);
+//! ```
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use derive_more::derive::{Display, Error};
+use futures_core::future::LocalBoxFuture;
+use rand::RngCore as _;
+
+use crate::{
+    dev::Payload,
+    error::as_std_error,
+    http::{header::HeaderName, StatusCode},
+    web, FromRequest, HttpRequest, ResponseError,
+};
+
+/// Errors that can occur when issuing or validating nonces.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum NonceError {
+    /// No [`NonceConfig`] was registered for the resource.
+    #[display("Nonce configuration is missing")]
+    MissingConfig,
+
+    /// The nonce header was missing or malformed.
+    #[display("Nonce is missing or malformed")]
+    MissingNonce,
+
+    /// The nonce was not issued, has expired or has already been used.
+    #[display("Nonce is invalid, expired or already used")]
+    InvalidNonce,
+
+    /// The nonce store failed.
+    #[display("Nonce store failed: {}", _0)]
+    Store(crate::Error),
+}
+
+impl ResponseError for NonceError {
+    as_std_error!();
+
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::MissingConfig => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::MissingNonce | Self::InvalidNonce => StatusCode::BAD_REQUEST,
+            Self::Store(err) => err.as_response_error().status_code(),
+        }
+    }
+}
+
+/// Storage of issued nonces.
+///
+/// Stores must be safe to share between server workers, which usually means that clones share the
+/// same underlying storage.
+pub trait NonceStore: Send + Sync + 'static {
+    /// Stores a `nonce` issued at `now` that expires after `ttl`.
+    fn insert(
+        &self,
+        nonce: &str,
+        now: SystemTime,
+        ttl: Duration,
+    ) -> LocalBoxFuture<'_, Result<(), crate::Error>>;
+
+    /// Removes `nonce` from the store.
+    ///
+    /// Returns true if the nonce was stored and had not expired at `now`. Implementations must
+    /// ensure that a nonce is only ever taken once, even by concurrent requests.
+    fn take(&self, nonce: &str, now: SystemTime) -> LocalBoxFuture<'_, Result<bool, crate::Error>>;
+}
+
+/// In-memory [`NonceStore`].
+///
+/// Clones share the same nonces. Expired nonces are purged whenever a nonce is issued. The store
+/// holds up to 100,000 nonces by default; when it is full, issuing a nonce evicts the ones closest
+/// to expiry.
+#[derive(Debug, Clone)]
+pub struct MemoryNonceStore {
+    nonces: Arc<Mutex<Nonces>>,
+    max_nonces: usize,
+}
+
+#[derive(Debug, Default)]
+struct Nonces {
+    expires_at: HashMap<String, SystemTime>,
+    by_expiry: BTreeSet<(SystemTime, String)>,
+}
+
+impl MemoryNonceStore {
+    /// Sets the maximum number of unused nonces held by the store.
+    pub fn max_nonces(mut self, max_nonces: usize) -> Self {
+        self.max_nonces = max_nonces;
+        self
+    }
+}
+
+impl Default for MemoryNonceStore {
+    fn default() -> Self {
+        Self {
+            nonces: Arc::default(),
+            max_nonces: 100_000,
+        }
+    }
+}
+
+impl Nonces {
+    /// Removes the nonce closest to expiry. Returns false if there are no nonces.
+    fn evict_first(&mut self) -> bool {
+        match self.by_expiry.pop_first() {
+            Some((_, nonce)) => {
+                self.expires_at.remove(&nonce);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl NonceStore for MemoryNonceStore {
+    fn insert(
+        &self,
+        nonce: &str,
+        now: SystemTime,
+        ttl: Duration,
+    ) -> LocalBoxFuture<'_, Result<(), crate::Error>> {
+        let mut nonces = self.nonces.lock().unwrap();
+
+        while nonces
+            .by_expiry
+            .first()
+            .is_some_and(|(expires_at, _)| *expires_at <= now)
+        {
+            nonces.evict_first();
+        }
+
+        if let Some(expires_at) = nonces.expires_at.remove(nonce) {
+            nonces.by_expiry.remove(&(expires_at, nonce.to_owned()));
+        }
+
+        while nonces.expires_at.len() >= self.max_nonces && nonces.evict_first() {}
+
+        let expires_at = crate::clock::expires_at(now, ttl);
+        nonces.expires_at.insert(nonce.to_owned(), expires_at);
+        nonces.by_expiry.insert((expires_at, nonce.to_owned()));
+
+        Box::pin(async { Ok(()) })
+    }
+
+    fn take(&self, nonce: &str, now: SystemTime) -> LocalBoxFuture<'_, Result<bool, crate::Error>> {
+        let mut nonces = self.nonces.lock().unwrap();
+
+        let valid = match nonces.expires_at.remove(nonce) {
+            Some(expires_at) => {
+                nonces.by_expiry.remove(&(expires_at, nonce.to_owned()));
+                expires_at > now
+            }
+            None => false,
+        };
+
+        Box::pin(async move { Ok(valid) })
+    }
+}
+
+/// Nonce issuance and validation configuration.
+///
+/// Register as app data on the resources that issue or require nonces; extraction of [`Nonce`] and
+/// [`NonceIssuer`] fails with a 500 response if no configuration is found.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{
+///     http::header::HeaderName,
+///     nonce::{MemoryNonceStore, NonceConfig},
+/// };
+///
+/// let config = NonceConfig::new(MemoryNonceStore::default())
+///     .header(HeaderName::from_static("x-nonce"))
+///     .ttl(Duration::from_secs(60));
+/// ```
+#[derive(Clone)]
+pub struct NonceConfig {
+    store: Arc<dyn NonceStore>,
+    header: HeaderName,
+    ttl: Duration,
+}
+
+impl NonceConfig {
+    /// Constructs a configuration that keeps issued nonces in `store`.
+    pub fn new(store: impl NonceStore) -> Self {
+        Self {
+            store: Arc::new(store),
+            header: HeaderName::from_static("replay-nonce"),
+            ttl: Duration::from_secs(300),
+        }
+    }
+
+    /// Sets the name of the request header containing the nonce. Defaults to `Replay-Nonce`.
+    pub fn header(mut self, name: HeaderName) -> Self {
+        self.header = name;
+        self
+    }
+
+    /// Sets how long issued nonces remain valid. Defaults to 5 minutes.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Extract nonce config from app data. Check both `T` and `Data<T>`, in that order.
+    fn from_req(req: &HttpRequest) -> Result<&Self, NonceError> {
+        req.app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+            .ok_or_else(|| {
+                log::error!(
+                    "Failed to handle nonce for {:?}: no `NonceConfig` registered",
+                    req.match_name().unwrap_or(req.path())
+                );
+                NonceError::MissingConfig
+            })
+    }
+}
+
+impl fmt::Debug for NonceConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NonceConfig")
+            .field("header", &self.header)
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+fn now(req: &HttpRequest) -> SystemTime {
+    req.app_data::<web::Clock>()
+        .cloned()
+        .unwrap_or_default()
+        .now()
+}
+
+/// Extractor that issues new nonces.
+///
+/// Issued nonces are 128-bit random values, hex-encoded. They are usually sent to clients in a
+/// response header named like the [configured](NonceConfig::header) request header.
+///
+/// See the [module documentation](self) for details and examples.
+#[derive(Debug, Clone)]
+pub struct NonceIssuer {
+    config: NonceConfig,
+    now: SystemTime,
+}
+
+impl NonceIssuer {
+    /// Issues and stores a new nonce.
+    pub async fn issue(&self) -> Result<String, NonceError> {
+        let mut bytes = [0; 16];
+        rand::thread_rng().fill_bytes(&mut bytes);
+
+        let nonce = bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+
+        self.config
+            .store
+            .insert(&nonce, self.now, self.config.ttl)
+            .await
+            .map_err(NonceError::Store)?;
+
+        Ok(nonce)
+    }
+}
+
+impl FromRequest for NonceIssuer {
+    type Error = NonceError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let res = NonceConfig::from_req(req).map(|config| NonceIssuer {
+            config: config.clone(),
+            now: now(req),
+        });
+
+        Box::pin(async { res })
+    }
+}
+
+/// Extractor for a valid nonce, which is consumed by the extraction.
+///
+/// Extraction fails with a 400 response if the request carries no nonce or one that was not issued,
+/// has expired or has already been used.
+///
+/// See the [module documentation](self) for details and examples.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nonce(String);
+
+impl Nonce {
+    /// Returns the consumed nonce.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Unwraps into the consumed nonce.
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl FromRequest for Nonce {
+    type Error = NonceError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let config = match NonceConfig::from_req(req) {
+            Ok(config) => config,
+            Err(err) => return Box::pin(async { Err(err) }),
+        };
+
+        let nonce = req
+            .headers()
+            .get(&config.header)
+            .and_then(|hdr| hdr.to_str().ok())
+            .map(str::trim)
+            .filter(|nonce| !nonce.is_empty())
+            .map(str::to_owned);
+
+        let store = Arc::clone(&config.store);
+        let now = now(req);
+
+        Box::pin(async move {
+            let nonce = nonce.ok_or(NonceError::MissingNonce)?;
+
+            if store.take(&nonce, now).await.map_err(NonceError::Store)? {
+                Ok(Nonce(nonce))
+            } else {
+                Err(NonceError::InvalidNonce)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestRequest;
+
+    async fn issue(config: &NonceConfig, clock: &web::Clock) -> String {
+        let (req, mut pl) = TestRequest::default()
+            .app_data(config.clone())
+            .app_data(clock.clone())
+            .to_http_parts();
+
+        let issuer = NonceIssuer::from_request(&req, &mut pl).await.unwrap();
+        issuer.issue().await.unwrap()
+    }
+
+    async fn extract(
+        config: &NonceConfig,
+        clock: &web::Clock,
+        nonce: &str,
+    ) -> Result<Nonce, NonceError> {
+        let (req, mut pl) = TestRequest::default()
+            .app_data(config.clone())
+            .app_data(clock.clone())
+            .insert_header(("replay-nonce", nonce))
+            .to_http_parts();
+
+        Nonce::from_request(&req, &mut pl).await
+    }
+
+    #[actix_rt::test]
+    async fn single_use() {
+        let config = NonceConfig::new(MemoryNonceStore::default());
+        let clock = web::Clock::frozen(SystemTime::now());
+
+        let nonce = issue(&config, &clock).await;
+        assert_eq!(nonce.len(), 32);
+        assert_ne!(nonce, issue(&config, &clock).await);
+
+        assert_eq!(
+            extract(&config, &clock, &nonce).await.unwrap().as_str(),
+            nonce
+        );
+        assert!(matches!(
+            extract(&config, &clock, &nonce).await,
+            Err(NonceError::InvalidNonce)
+        ));
+
+        assert!(matches!(
+            extract(&config, &clock, "unknown").await,
+            Err(NonceError::InvalidNonce)
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn expiry() {
+        let config = NonceConfig::new(MemoryNonceStore::default()).ttl(Duration::from_secs(60));
+        let clock = web::Clock::frozen(SystemTime::now());
+
+        let nonce = issue(&config, &clock).await;
+        let nonce2 = issue(&config, &clock).await;
+
+        clock.advance(Duration::from_secs(59));
+        assert!(extract(&config, &clock, &nonce).await.is_ok());
+
+        clock.advance(Duration::from_secs(1));
+        assert!(matches!(
+            extract(&config, &clock, &nonce2).await,
+            Err(NonceError::InvalidNonce)
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn max_nonces() {
+        let store = MemoryNonceStore::default().max_nonces(2);
+        let config = NonceConfig::new(store.clone()).ttl(Duration::from_secs(60));
+        let clock = web::Clock::frozen(SystemTime::now());
+
+        let nonce = issue(&config, &clock).await;
+        clock.advance(Duration::from_secs(1));
+        let nonce2 = issue(&config, &clock).await;
+        clock.advance(Duration::from_secs(1));
+        let nonce3 = issue(&config, &clock).await;
+
+        assert_eq!(store.nonces.lock().unwrap().expires_at.len(), 2);
+
+        // the nonce closest to expiry was evicted
+        assert!(matches!(
+            extract(&config, &clock, &nonce).await,
+            Err(NonceError::InvalidNonce)
+        ));
+        assert!(extract(&config, &clock, &nonce2).await.is_ok());
+        assert!(extract(&config, &clock, &nonce3).await.is_ok());
+
+        let nonces = store.nonces.lock().unwrap();
+        assert!(nonces.expires_at.is_empty());
+        assert!(nonces.by_expiry.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn expired_nonces_are_purged() {
+        let store = MemoryNonceStore::default();
+        let config = NonceConfig::new(store.clone()).ttl(Duration::from_secs(60));
+        let clock = web::Clock::frozen(SystemTime::now());
+
+        issue(&config, &clock).await;
+        issue(&config, &clock).await;
+
+        clock.advance(Duration::from_secs(60));
+        let nonce = issue(&config, &clock).await;
+
+        let nonces = store.nonces.lock().unwrap();
+        assert_eq!(nonces.expires_at.keys().collect::<Vec<_>>(), [&nonce]);
+        assert_eq!(nonces.by_expiry.len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn unbounded_ttl() {
+        let config = NonceConfig::new(MemoryNonceStore::default()).ttl(Duration::MAX);
+        let clock = web::Clock::frozen(SystemTime::now());
+
+        let nonce = issue(&config, &clock).await;
+
+        clock.advance(Duration::from_secs(365 * 24 * 60 * 60));
+        assert!(extract(&config, &clock, &nonce).await.is_ok());
+    }
+
+    #[actix_rt::test]
+    async fn missing_nonce_and_config() {
+        let config = NonceConfig::new(MemoryNonceStore::default());
+
+        let (req, mut pl) = TestRequest::default()
+            .app_data(config.clone())
+            .to_http_parts();
+        let err = Nonce::from_request(&req, &mut pl).await.unwrap_err();
+        assert!(matches!(err, NonceError::MissingNonce));
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+
+        let (req, mut pl) = TestRequest::default().to_http_parts();
+        let err = Nonce::from_request(&req, &mut pl).await.unwrap_err();
+        assert!(matches!(err, NonceError::MissingConfig));
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let err = NonceIssuer::from_request(&req, &mut pl).await.unwrap_err();
+        assert!(matches!(err, NonceError::MissingConfig));
+    }
+}
@@ -0,0 +1,559 @@
+//! Generated OpenAPI documents for registered resources.
+//!
+//! Operation metadata (summary, tags, request and response schema names) is attached to resources
+//! using [`Resource::operation()`](crate::Resource::operation). The [`OpenApi`] service collects
+//! the metadata of every resource in the application and serves it as an [OpenAPI 3.0] document.
+//!
+//! Schemas are referred to by name. Generating the schemas themselves is delegated to a
+//! [`SchemaGenerator`], which makes it possible to integrate crates like `schemars` without this
+//! crate depending on them.
+//!
+//! # Examples
+//! ```
+//! use actix_web::{
+//!     http::{Method, StatusCode},
+//!     openapi::{OpenApi, Operation},
+//!     web, App, HttpResponse,
+//! };
+//! use serde_json::json;
+//!
+//! let app = App::new()
+//!     .service(
+//!         web::scope("/api").service(
+//!             web::resource("/users/{id}")
+//!                 .get(|| async { HttpResponse::Ok().finish() })
+//!                 .operation(
+//!                     Method::GET,
+//!                     Operation::new()
+//!                         .summary("Fetch user")
+//!                         .tag("users")
+//!                         .response_with_schema(StatusCode::OK, "The user", "User"),
+//!                 ),
+//!         ),
+//!     )
+//!     .service(
+//!         OpenApi::new("/openapi.json")
+//!             .title("Users API")
+//!             .version("1.2.0")
+//!             // e.g., `serde_json::to_value(schemars::schema_for!(User)).ok()`
+//!             .schema_generator(|name: &str| match name {
+//!                 "User" => Some(json!({ "type": "object" })),
+//!                 _ => None,
+//!             }),
+//!     );
+//! ```
+//!
+//! [OpenAPI 3.0]: https://spec.openapis.org/oas/v3.0.3
+
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::BTreeSet,
+    future,
+    rc::Rc,
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    dev::{AppService, HttpServiceFactory},
+    http::{header::ContentType, Method, StatusCode},
+    web, HttpResponse,
+};
+
+/// Operation metadata registered by resources, along with the full path and method.
+pub(crate) type Operations = Rc<RefCell<Vec<(String, Method, Operation)>>>;
+
+/// Version of the OpenAPI specification that generated documents conform to.
+const OPENAPI_VERSION: &str = "3.0.3";
+
+/// Generates JSON Schemas for named types referenced by operations.
+///
+/// Implemented for closures with the signature `Fn(&str) -> Option<serde_json::Value>`.
+pub trait SchemaGenerator {
+    /// Returns JSON Schema for the type with the given name.
+    ///
+    /// Schemas for which `None` is returned are referenced but not included in the document.
+    fn generate(&self, name: &str) -> Option<Value>;
+}
+
+impl<F> SchemaGenerator for F
+where
+    F: Fn(&str) -> Option<Value>,
+{
+    fn generate(&self, name: &str) -> Option<Value> {
+        (self)(name)
+    }
+}
+
+/// Metadata describing an API operation, i.e., a method on a resource.
+///
+/// See [`Resource::operation()`](crate::Resource::operation).
+#[derive(Debug, Clone, Default)]
+pub struct Operation {
+    summary: Option<String>,
+    description: Option<String>,
+    operation_id: Option<String>,
+    tags: Vec<String>,
+    request_body: Option<String>,
+    responses: Vec<OperationResponse>,
+}
+
+#[derive(Debug, Clone)]
+struct OperationResponse {
+    status: StatusCode,
+    description: String,
+    schema: Option<String>,
+}
+
+impl Operation {
+    /// Constructs new operation without any metadata.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets short summary of what the operation does.
+    pub fn summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// Sets detailed description of the operation.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets unique identifier of the operation.
+    pub fn operation_id(mut self, id: impl Into<String>) -> Self {
+        self.operation_id = Some(id.into());
+        self
+    }
+
+    /// Adds a tag used to group operations.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Sets name of the schema of the JSON request body.
+    pub fn request_body(mut self, schema: impl Into<String>) -> Self {
+        self.request_body = Some(schema.into());
+        self
+    }
+
+    /// Adds a possible response without a body schema.
+    pub fn response(mut self, status: StatusCode, description: impl Into<String>) -> Self {
+        self.responses.push(OperationResponse {
+            status,
+            description: description.into(),
+            schema: None,
+        });
+        self
+    }
+
+    /// Adds a possible response with a JSON body described by the named schema.
+    pub fn response_with_schema(
+        mut self,
+        status: StatusCode,
+        description: impl Into<String>,
+        schema: impl Into<String>,
+    ) -> Self {
+        self.responses.push(OperationResponse {
+            status,
+            description: description.into(),
+            schema: Some(schema.into()),
+        });
+        self
+    }
+
+    fn to_json(&self, path: &str, schemas: &mut BTreeSet<String>) -> Value {
+        let mut op = Map::new();
+
+        if let Some(ref summary) = self.summary {
+            op.insert("summary".to_owned(), json!(summary));
+        }
+
+        if let Some(ref description) = self.description {
+            op.insert("description".to_owned(), json!(description));
+        }
+
+        if let Some(ref id) = self.operation_id {
+            op.insert("operationId".to_owned(), json!(id));
+        }
+
+        if !self.tags.is_empty() {
+            op.insert("tags".to_owned(), json!(self.tags));
+        }
+
+        let params = path_params(path)
+            .map(|name| {
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" },
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if !params.is_empty() {
+            op.insert("parameters".to_owned(), Value::Array(params));
+        }
+
+        if let Some(ref schema) = self.request_body {
+            schemas.insert(schema.clone());
+            op.insert(
+                "requestBody".to_owned(),
+                json!({ "required": true, "content": json_content(schema) }),
+            );
+        }
+
+        let mut responses = Map::new();
+
+        for res in &self.responses {
+            let mut obj = Map::new();
+            obj.insert("description".to_owned(), json!(res.description));
+
+            if let Some(ref schema) = res.schema {
+                schemas.insert(schema.clone());
+                obj.insert("content".to_owned(), json_content(schema));
+            }
+
+            responses.insert(res.status.as_str().to_owned(), Value::Object(obj));
+        }
+
+        // at least one response is required by the specification
+        if responses.is_empty() {
+            responses.insert("default".to_owned(), json!({ "description": "" }));
+        }
+
+        op.insert("responses".to_owned(), Value::Object(responses));
+
+        Value::Object(op)
+    }
+}
+
+/// Service that serves the OpenAPI document of the application it is registered on.
+///
+/// The document is generated when first requested and includes the operations of all resources
+/// in the application, regardless of the scope this service is registered in.
+///
+/// See [module documentation](self) for an example.
+pub struct OpenApi {
+    path: String,
+    title: String,
+    version: String,
+    description: Option<String>,
+    generator: Option<Rc<dyn SchemaGenerator>>,
+}
+
+impl OpenApi {
+    /// Constructs new OpenAPI document service, served from `path`.
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            title: "API".to_owned(),
+            version: "1.0.0".to_owned(),
+            description: None,
+            generator: None,
+        }
+    }
+
+    /// Sets title of the API. Defaults to `API`.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Sets version of the API. Defaults to `1.0.0`.
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Sets description of the API.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets generator used for the schemas referenced by operations.
+    ///
+    /// Without a generator, schemas are referenced but not included in the document.
+    pub fn schema_generator(mut self, generator: impl SchemaGenerator + 'static) -> Self {
+        self.generator = Some(Rc::new(generator));
+        self
+    }
+
+    fn document(&self, operations: &[(String, Method, Operation)]) -> Value {
+        let mut info = Map::new();
+        info.insert("title".to_owned(), json!(self.title));
+        info.insert("version".to_owned(), json!(self.version));
+
+        if let Some(ref description) = self.description {
+            info.insert("description".to_owned(), json!(description));
+        }
+
+        let mut schemas = BTreeSet::new();
+        let mut paths = Map::new();
+
+        for (path, method, operation) in operations {
+            let path_item = paths
+                .entry(openapi_path(path))
+                .or_insert_with(|| Value::Object(Map::new()));
+
+            path_item.as_object_mut().unwrap().insert(
+                method.as_str().to_ascii_lowercase(),
+                operation.to_json(path, &mut schemas),
+            );
+        }
+
+        let mut doc = json!({
+            "openapi": OPENAPI_VERSION,
+            "info": info,
+            "paths": paths,
+        });
+
+        if let Some(ref generator) = self.generator {
+            let components = schemas
+                .iter()
+                .filter_map(|name| Some((name.clone(), generator.generate(name)?)))
+                .collect::<Map<_, _>>();
+
+            if !components.is_empty() {
+                doc["components"] = json!({ "schemas": components });
+            }
+        }
+
+        doc
+    }
+}
+
+impl HttpServiceFactory for OpenApi {
+    fn register(self, config: &mut AppService) {
+        let operations = config.operations();
+        let path = self.path.clone();
+        let api = Rc::new(self);
+        let rendered = Rc::new(OnceCell::new());
+
+        let resource = web::resource(path).get(move || {
+            let body = rendered
+                .get_or_init(|| api.document(&operations.borrow()).to_string())
+                .clone();
+
+            future::ready(
+                HttpResponse::Ok()
+                    .insert_header(ContentType::json())
+                    .body(body),
+            )
+        });
+
+        HttpServiceFactory::register(resource, config);
+    }
+}
+
+/// Returns a JSON media type object referencing the named schema.
+fn json_content(schema: &str) -> Value {
+    json!({
+        "application/json": {
+            "schema": { "$ref": format!("#/components/schemas/{schema}") },
+        },
+    })
+}
+
+/// Converts a resource path pattern to an OpenAPI path template.
+///
+/// Custom regular expressions and tail markers are removed from dynamic segments, e.g.,
+/// `/users/{id:\d+}/{tail}*` becomes `/users/{id}/{tail}`.
+fn openapi_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    let mut rest = path;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+
+        let Some(len) = segment_len(&rest[start..]) else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+
+        let segment = &rest[start + 1..start + len - 1];
+        let name = segment.split(':').next().unwrap_or(segment);
+        out.push('{');
+        out.push_str(name);
+        out.push('}');
+
+        rest = rest[start + len..]
+            .strip_prefix('*')
+            .unwrap_or(&rest[start + len..]);
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Returns names of the dynamic segments in a resource path pattern.
+fn path_params(path: &str) -> impl Iterator<Item = String> {
+    let path = openapi_path(path);
+
+    path.split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}').map(|(name, _)| name.to_owned()))
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+/// Returns length of the dynamic segment at the start of `path`, accounting for nested braces in
+/// custom regular expressions.
+fn segment_len(path: &str) -> Option<usize> {
+    let mut depth = 0;
+
+    for (idx, ch) in path.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return Some(idx + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test::{call_service, init_service, read_body_json, TestRequest},
+        App,
+    };
+
+    async fn ok() -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[test]
+    fn path_templates() {
+        assert_eq!(openapi_path("/users"), "/users");
+        assert_eq!(openapi_path("/users/{id}"), "/users/{id}");
+        assert_eq!(
+            openapi_path(r"/users/{id:\d{1,4}}/files/{tail}*"),
+            "/users/{id}/files/{tail}"
+        );
+        assert_eq!(
+            path_params(r"/{org}/users/{id:\d+}").collect::<Vec<_>>(),
+            ["org", "id"]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn serves_document() {
+        let app = init_service(
+            App::new()
+                .service(
+                    web::scope("/api/").service(
+                        web::resource(r"/users/{id:\d+}")
+                            .get(ok)
+                            .delete(ok)
+                            .operation(
+                                Method::GET,
+                                Operation::new()
+                                    .summary("Fetch user")
+                                    .operation_id("getUser")
+                                    .tag("users")
+                                    .response_with_schema(StatusCode::OK, "The user", "User")
+                                    .response(StatusCode::NOT_FOUND, "No such user"),
+                            )
+                            .operation(Method::DELETE, Operation::new()),
+                    ),
+                )
+                .service(
+                    web::resource("/users")
+                        .post(ok)
+                        .operation(Method::POST, Operation::new().request_body("NewUser")),
+                )
+                .service(
+                    OpenApi::new("/openapi.json")
+                        .title("Users")
+                        .version("2.0.0")
+                        .schema_generator(|name: &str| {
+                            (name == "User").then(|| json!({ "type": "object" }))
+                        }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/openapi.json").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get("content-type").unwrap(),
+            "application/json"
+        );
+
+        let doc: Value = read_body_json(res).await;
+        assert_eq!(
+            doc,
+            json!({
+                "openapi": "3.0.3",
+                "info": { "title": "Users", "version": "2.0.0" },
+                "paths": {
+                    "/api/users/{id}": {
+                        "get": {
+                            "summary": "Fetch user",
+                            "operationId": "getUser",
+                            "tags": ["users"],
+                            "parameters": [{
+                                "name": "id",
+                                "in": "path",
+                                "required": true,
+                                "schema": { "type": "string" },
+                            }],
+                            "responses": {
+                                "200": {
+                                    "description": "The user",
+                                    "content": {
+                                        "application/json": {
+                                            "schema": { "$ref": "#/components/schemas/User" },
+                                        },
+                                    },
+                                },
+                                "404": { "description": "No such user" },
+                            },
+                        },
+                        "delete": {
+                            "parameters": [{
+                                "name": "id",
+                                "in": "path",
+                                "required": true,
+                                "schema": { "type": "string" },
+                            }],
+                            "responses": { "default": { "description": "" } },
+                        },
+                    },
+                    "/users": {
+                        "post": {
+                            "requestBody": {
+                                "required": true,
+                                "content": {
+                                    "application/json": {
+                                        "schema": { "$ref": "#/components/schemas/NewUser" },
+                                    },
+                                },
+                            },
+                            "responses": { "default": { "description": "" } },
+                        },
+                    },
+                },
+                "components": {
+                    "schemas": { "User": { "type": "object" } },
+                },
+            })
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use std::{
     cell::{Ref, RefCell, RefMut},
-    fmt, net,
+    fmt,
+    future::Future,
+    net,
     rc::Rc,
     str,
 };
@@ -15,10 +17,13 @@ use smallvec::SmallVec;
 use crate::{
     app_service::AppInitServiceState,
     config::AppConfig,
-    dev::{Extensions, Payload},
+    dev::{Extensions, FinishStatus, OnFinish, Payload},
     error::UrlGenerationError,
-    http::{header::HeaderMap, Method, Uri, Version},
-    info::ConnectionInfo,
+    http::{
+        header::{Header, HeaderMap},
+        Method, Uri, Version,
+    },
+    info::{ConnectionInfo, TrustedProxies},
     rmap::ResourceMap,
     Error, FromRequest, HttpMessage,
 };
@@ -26,6 +31,10 @@ use crate::{
 #[cfg(feature = "cookies")]
 struct Cookies(Vec<Cookie<'static>>);
 
+/// Typed headers parsed by [`HttpRequest::cached_header()`], keyed by header type.
+#[derive(Default)]
+pub(crate) struct HeaderCache(Extensions);
+
 /// An incoming request.
 #[derive(Clone)]
 pub struct HttpRequest {
@@ -282,7 +291,11 @@ impl HttpRequest {
     #[inline]
     pub fn connection_info(&self) -> Ref<'_, ConnectionInfo> {
         if !self.extensions().contains::<ConnectionInfo>() {
-            let info = ConnectionInfo::new(self.head(), self.app_config());
+            let info = ConnectionInfo::new(
+                self.head(),
+                self.app_config(),
+                self.app_data::<TrustedProxies>(),
+            );
             self.extensions_mut().insert(info);
         }
 
@@ -333,6 +346,94 @@ impl HttpRequest {
         None
     }
 
+    /// Extracts a typed header, parsing it only on the first lookup for this request.
+    ///
+    /// Parse results, including failures, are cached in the request extensions so that guards,
+    /// middleware, and handlers looking up the same header share a single parse. The cache is
+    /// cleared when headers are modified through [`ServiceRequest::head_mut()`].
+    ///
+    /// Returns `None` if the header is missing or parsing `H` fails.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::{http::header::{Accept, QualityItem}, test::TestRequest};
+    /// let req = TestRequest::default()
+    ///     .insert_header(Accept(vec![QualityItem::max(mime::TEXT_HTML)]))
+    ///     .to_http_request();
+    ///
+    /// let accept = req.cached_header::<Accept>().unwrap();
+    /// assert_eq!(accept.preference(), mime::TEXT_HTML);
+    /// ```
+    ///
+    /// [`ServiceRequest::head_mut()`]: crate::dev::ServiceRequest::head_mut()
+    pub fn cached_header<H>(&self) -> Option<H>
+    where
+        H: Header + Clone + 'static,
+    {
+        if let Some(cached) = self
+            .extensions()
+            .get::<HeaderCache>()
+            .and_then(|cache| cache.0.get::<Option<H>>())
+        {
+            return cached.clone();
+        }
+
+        let parsed = self.get_header::<H>();
+
+        let mut extensions = self.extensions_mut();
+        if !extensions.contains::<HeaderCache>() {
+            extensions.insert(HeaderCache::default());
+        }
+        extensions
+            .get_mut::<HeaderCache>()
+            .unwrap()
+            .0
+            .insert(parsed.clone());
+
+        parsed
+    }
+
+    /// Registers an async callback to run once the response to this request has been completely
+    /// written, or the connection was dropped before that.
+    ///
+    /// The callback receives a [`FinishStatus`] telling the two outcomes apart, which makes it the
+    /// place to release resources acquired while handling the request, like database transactions
+    /// or temporary files. Callbacks run in reverse order of registration. See [`OnFinish`] for
+    /// details.
+    ///
+    /// Returns false, without registering the callback, if the request is not being served by an
+    /// HTTP dispatcher (e.g., it was built with [`TestRequest`](crate::test::TestRequest)).
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{dev::FinishStatus, HttpRequest, HttpResponse};
+    ///
+    /// async fn handler(req: HttpRequest) -> HttpResponse {
+    ///     let tx = "transaction";
+    ///
+    ///     req.on_finish(move |status: FinishStatus| async move {
+    ///         if !status.is_completed() {
+    ///             // ... roll back `tx`
+    ///         }
+    ///     });
+    ///
+    ///     HttpResponse::Ok().finish()
+    /// }
+    /// ```
+    pub fn on_finish<F, Fut>(&self, f: F) -> bool
+    where
+        F: FnOnce(FinishStatus) -> Fut + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        match self.extensions().get::<OnFinish>() {
+            Some(on_finish) => {
+                on_finish.register(f);
+                true
+            }
+            None => false,
+        }
+    }
+
     #[inline]
     fn app_state(&self) -> &AppInitServiceState {
         &self.inner.app_state
@@ -552,7 +653,7 @@ mod tests {
 
     use super::*;
     use crate::{
-        dev::{ResourceDef, Service},
+        dev::{ResourceDef, Service, ServiceRequest},
         http::{header, StatusCode},
         test::{self, call_service, init_service, read_body, TestRequest},
         web, App, HttpResponse,
@@ -567,6 +668,47 @@ mod tests {
         assert!(dbg.contains("HttpRequest"));
     }
 
+    #[test]
+    fn on_finish_without_dispatcher() {
+        let req = TestRequest::default().to_http_request();
+        assert!(!req.on_finish(|_| async {}));
+    }
+
+    #[test]
+    fn cached_header() {
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT_LANGUAGE, "en"))
+            .insert_header((header::CONTENT_TYPE, "not a mime"))
+            .to_srv_request();
+
+        let lang = req.cached_header::<header::AcceptLanguage>().unwrap();
+        assert_eq!(lang.preference().to_string(), "en");
+        assert!(req.cached_header::<header::ContentType>().is_none());
+        assert!(req.cached_header::<header::Accept>().is_none());
+
+        // later lookups are served from the cache
+        let (mut http_req, payload) = req.into_parts();
+        http_req.head_mut().headers.insert(
+            header::ACCEPT_LANGUAGE,
+            header::HeaderValue::from_static("de"),
+        );
+        let lang = http_req.cached_header::<header::AcceptLanguage>().unwrap();
+        assert_eq!(lang.preference().to_string(), "en");
+
+        // modifying headers through the service request clears the cache
+        let mut req = ServiceRequest::from_parts(http_req, payload);
+        req.headers_mut().insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("text/plain"),
+        );
+        let lang = req.cached_header::<header::AcceptLanguage>().unwrap();
+        assert_eq!(lang.preference().to_string(), "de");
+        assert_eq!(
+            req.cached_header::<header::ContentType>(),
+            Some(header::ContentType(mime::TEXT_PLAIN))
+        );
+    }
+
     #[test]
     #[cfg(feature = "cookies")]
     fn test_no_request_cookies() {
@@ -0,0 +1,359 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    convert::Infallible,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, RwLock},
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use actix_utils::future::{ok, Ready};
+use pin_project_lite::pin_project;
+
+use crate::{
+    dev::Payload,
+    http::header::{AcceptLanguage, HeaderName, Preference},
+    rt::task::JoinHandle,
+    web::RequestRng,
+    FromRequest, HttpMessage as _, HttpRequest,
+};
+
+/// Request header from which the request ID is taken, if present.
+const REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Maximum length of request IDs accepted from the `X-Request-Id` header.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+thread_local! {
+    static CURRENT: RefCell<Option<RequestContext>> = const { RefCell::new(None) };
+}
+
+/// Request context extractor that can be carried into tasks spawned from handlers.
+///
+/// The context holds the request ID along with an optional deadline, locale, and authentication
+/// claims. It is cheap to clone, can be sent across threads, and all clones share the same state,
+/// so values set by middleware are visible to tasks that captured the context earlier.
+///
+/// The context is stored in the request's extensions when it is first extracted. Its request ID is
+/// taken from the `X-Request-Id` header if that holds a short, visible ASCII value and is randomly
+/// generated otherwise. The locale is initialized from the most preferred language in the
+/// `Accept-Language` header.
+///
+/// Futures run through [`scope()`](Self::scope) or [`spawn()`](Self::spawn) can access the context
+/// using [`RequestContext::current()`]. The [`Logger`](crate::middleware::Logger) middleware runs
+/// the services it wraps in the context's scope and can include the request ID in access logs, so
+/// that log records from handlers and their background continuations correlate to the request.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web, HttpResponse};
+///
+/// #[get("/")]
+/// async fn index(ctx: web::RequestContext) -> HttpResponse {
+///     ctx.spawn(async {
+///         // ... background work
+///
+///         let ctx = web::RequestContext::current().unwrap();
+///         log::info!("[{}] background work done", ctx.request_id());
+///     });
+///
+///     HttpResponse::Accepted().finish()
+/// }
+/// ```
+#[derive(Clone)]
+pub struct RequestContext {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    request_id: String,
+    state: RwLock<State>,
+}
+
+#[derive(Default)]
+struct State {
+    deadline: Option<Instant>,
+    locale: Option<String>,
+    claims: Option<Arc<dyn Any + Send + Sync>>,
+}
+
+impl RequestContext {
+    /// Constructs new context with the given request ID.
+    pub fn new(request_id: impl Into<String>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                request_id: request_id.into(),
+                state: RwLock::default(),
+            }),
+        }
+    }
+
+    /// Returns the context of the request being handled by the current task, if any.
+    pub fn current() -> Option<Self> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// Returns ID of the request.
+    pub fn request_id(&self) -> &str {
+        &self.inner.request_id
+    }
+
+    /// Returns the instant by which handling of the request should be complete, if set.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.inner.state.read().unwrap().deadline
+    }
+
+    /// Sets the instant by which handling of the request should be complete.
+    pub fn set_deadline(&self, deadline: Instant) {
+        self.inner.state.write().unwrap().deadline = Some(deadline);
+    }
+
+    /// Returns the locale of the request, if known.
+    pub fn locale(&self) -> Option<String> {
+        self.inner.state.read().unwrap().locale.clone()
+    }
+
+    /// Sets the locale of the request.
+    pub fn set_locale(&self, locale: impl Into<String>) {
+        self.inner.state.write().unwrap().locale = Some(locale.into());
+    }
+
+    /// Returns the authentication claims of the request if they have been set and are of type `T`.
+    pub fn claims<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        let claims = self.inner.state.read().unwrap().claims.clone()?;
+        claims.downcast().ok()
+    }
+
+    /// Sets the authentication claims of the request, replacing claims of any type set before.
+    pub fn set_claims<T: Send + Sync + 'static>(&self, claims: T) {
+        self.inner.state.write().unwrap().claims = Some(Arc::new(claims));
+    }
+
+    /// Wraps `fut` so that this context is the [current](Self::current) context while it is polled.
+    pub fn scope<F: Future>(&self, fut: F) -> ContextScope<F> {
+        ContextScope {
+            ctx: Some(self.clone()),
+            fut,
+        }
+    }
+
+    /// Spawns `fut` onto the current Actix runtime in the scope of this context.
+    ///
+    /// # Panics
+    /// Panics if called outside of an Actix runtime.
+    pub fn spawn<F>(&self, fut: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+    {
+        actix_rt::spawn(self.scope(fut))
+    }
+
+    /// Returns the context stored in the request's extensions, inserting a new one if necessary.
+    pub(crate) fn get_or_insert(req: &HttpRequest) -> Self {
+        if let Some(ctx) = req.extensions().get::<Self>() {
+            return ctx.clone();
+        }
+
+        let request_id = match req.headers().get(&REQUEST_ID) {
+            Some(val) if is_valid_request_id(val.as_bytes()) => val.to_str().unwrap().to_owned(),
+            _ => {
+                let rng = req.extensions().get::<RequestRng>().cloned();
+                let rng = rng.unwrap_or_default();
+                format!("{:016x}", rng.next_u64())
+            }
+        };
+
+        let ctx = Self::new(request_id);
+
+        if let Some(langs) = req.cached_header::<AcceptLanguage>() {
+            if let Preference::Specific(lang) = langs.preference() {
+                ctx.set_locale(lang.to_string());
+            }
+        }
+
+        req.extensions_mut().insert(ctx.clone());
+        ctx
+    }
+}
+
+impl fmt::Debug for RequestContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.inner.state.read().unwrap();
+
+        f.debug_struct("RequestContext")
+            .field("request_id", &self.inner.request_id)
+            .field("deadline", &state.deadline)
+            .field("locale", &state.locale)
+            .field("claims", &state.claims.is_some())
+            .finish()
+    }
+}
+
+impl FromRequest for RequestContext {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ok(Self::get_or_insert(req))
+    }
+}
+
+pin_project! {
+    /// Future returned by [`RequestContext::scope()`].
+    pub struct ContextScope<F> {
+        ctx: Option<RequestContext>,
+        #[pin]
+        fut: F,
+    }
+}
+
+impl<F> ContextScope<F> {
+    /// Wraps `fut` so that `ctx`, if any, is the current context while it is polled.
+    pub(crate) fn new(ctx: Option<RequestContext>, fut: F) -> Self {
+        Self { ctx, fut }
+    }
+}
+
+impl<F: Future> Future for ContextScope<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        match this.ctx {
+            Some(ctx) => {
+                let _guard = Enter::new(ctx.clone());
+                this.fut.poll(cx)
+            }
+            None => this.fut.poll(cx),
+        }
+    }
+}
+
+impl<F> fmt::Debug for ContextScope<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContextScope")
+            .field("ctx", &self.ctx)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Sets the current context, restoring the previous one when dropped.
+struct Enter {
+    prev: Option<RequestContext>,
+}
+
+impl Enter {
+    fn new(ctx: RequestContext) -> Self {
+        let prev = CURRENT.with(|current| current.borrow_mut().replace(ctx));
+        Self { prev }
+    }
+}
+
+impl Drop for Enter {
+    fn drop(&mut self) {
+        let prev = self.prev.take();
+        CURRENT.with(|current| *current.borrow_mut() = prev);
+    }
+}
+
+/// Returns true if `id` is short enough and consists only of visible ASCII characters.
+fn is_valid_request_id(id: &[u8]) -> bool {
+    !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.iter().all(u8::is_ascii_graphic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        http::header,
+        middleware::Logger,
+        test::{call_and_read_body, init_service, TestRequest},
+        web, App,
+    };
+
+    #[actix_rt::test]
+    async fn extraction_from_headers() {
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((REQUEST_ID, "abc-123"))
+            .insert_header((header::ACCEPT_LANGUAGE, "fr;q=0.5, de"))
+            .to_http_parts();
+
+        let ctx = RequestContext::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(ctx.request_id(), "abc-123");
+        assert_eq!(ctx.locale().as_deref(), Some("de"));
+        assert!(ctx.deadline().is_none());
+
+        ctx.set_claims(42_u32);
+        let ctx2 = RequestContext::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(ctx2.claims::<u32>().as_deref(), Some(&42));
+        assert!(ctx2.claims::<String>().is_none());
+    }
+
+    #[actix_rt::test]
+    async fn generated_request_id() {
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((REQUEST_ID, "not valid"))
+            .rng_seed(3)
+            .to_http_parts();
+
+        let ctx = RequestContext::from_request(&req, &mut pl).await.unwrap();
+        let expected = format!("{:016x}", RequestRng::from_seed(3).next_u64());
+        assert_eq!(ctx.request_id(), expected);
+        assert!(ctx.locale().is_none());
+    }
+
+    #[actix_rt::test]
+    async fn scope_sets_current() {
+        let ctx = RequestContext::new("outer");
+        assert!(RequestContext::current().is_none());
+
+        let id = ctx
+            .scope(async {
+                let inner = RequestContext::new("inner");
+                let id = inner
+                    .scope(async { RequestContext::current().unwrap().request_id().to_owned() })
+                    .await;
+
+                assert_eq!(RequestContext::current().unwrap().request_id(), "outer");
+                id
+            })
+            .await;
+
+        assert_eq!(id, "inner");
+        assert!(RequestContext::current().is_none());
+
+        let id = ctx
+            .spawn(async { RequestContext::current().unwrap().request_id().to_owned() })
+            .await
+            .unwrap();
+        assert_eq!(id, "outer");
+    }
+
+    #[actix_rt::test]
+    async fn logger_scopes_handlers() {
+        async fn handler() -> String {
+            let ctx = RequestContext::current().unwrap();
+            ctx.spawn(async { RequestContext::current().unwrap().request_id().to_owned() })
+                .await
+                .unwrap()
+        }
+
+        let app = init_service(
+            App::new()
+                .wrap(Logger::new("%L"))
+                .default_service(web::to(handler)),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .insert_header((REQUEST_ID, "req-1"))
+            .to_request();
+        let body = call_and_read_body(&app, req).await;
+        assert_eq!(body, "req-1");
+    }
+}
@@ -73,10 +73,12 @@ impl<T: Clone + 'static> FromRequest for ReqData<T> {
             ok(ReqData(st.clone()))
         } else {
             log::debug!(
-                "Failed to construct App-level ReqData extractor. \
-                 Request path: {:?} (type: {})",
-                req.path(),
+                "Failed to extract `ReqData<{}>` for `{}` handler. For the ReqData extractor to \
+                work correctly, a middleware or guard must insert the data using \
+                `extensions_mut().insert()` before the handler is called. Ensure that types \
+                align in both the set and retrieve calls.",
                 type_name::<T>(),
+                req.match_name().unwrap_or(req.path())
             );
             err(ErrorInternalServerError(
                 "Missing expected request extension data",
@@ -139,6 +141,17 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::OK);
     }
 
+    #[actix_rt::test]
+    async fn req_data_missing() {
+        let srv =
+            init_service(App::new().default_service(web::to(|_: ReqData<u32>| HttpResponse::Ok())))
+                .await;
+
+        let req = TestRequest::get().uri("/test").to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
     #[actix_rt::test]
     async fn req_data_internal_mutability() {
         let srv = init_service(
@@ -0,0 +1,194 @@
+use std::{
+    cell::Cell,
+    collections::hash_map::RandomState,
+    convert::Infallible,
+    fmt,
+    hash::{BuildHasher as _, Hasher as _},
+    rc::Rc,
+};
+
+use actix_utils::future::{ok, Ready};
+
+use crate::{dev::Payload, FromRequest, HttpMessage as _, HttpRequest};
+
+/// Request-scoped random number generator extractor.
+///
+/// The generator is stored in the request's extensions the first time it is extracted so that all
+/// extractions during a request share the same state. Unless a generator has already been attached
+/// to the request, it is seeded from a random source.
+///
+/// In tests, a seed can be set using [`TestRequest::rng_seed`] which makes the values produced by
+/// this extractor deterministic. Middleware can achieve the same by inserting a seeded generator
+/// into the request extensions, using [`RequestRng::from_seed`].
+///
+/// The underlying algorithm (xoshiro256\*\*) is fast and of good statistical quality but is **not**
+/// cryptographically secure. Do not use it to generate secrets such as session keys.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web};
+///
+/// #[get("/")]
+/// async fn index(rng: web::RequestRng) -> String {
+///     format!("request id: {:016x}", rng.next_u64())
+/// }
+/// ```
+///
+/// [`TestRequest::rng_seed`]: crate::test::TestRequest::rng_seed
+#[derive(Clone)]
+pub struct RequestRng {
+    state: Rc<Cell<[u64; 4]>>,
+}
+
+impl RequestRng {
+    /// Constructs a new generator seeded from a random source.
+    pub fn new() -> Self {
+        Self::from_seed(RandomState::new().build_hasher().finish())
+    }
+
+    /// Constructs a new generator from the given seed.
+    ///
+    /// Generators created with the same seed will produce the same sequence of values.
+    pub fn from_seed(seed: u64) -> Self {
+        let mut sm = seed;
+        let state = [
+            splitmix64(&mut sm),
+            splitmix64(&mut sm),
+            splitmix64(&mut sm),
+            splitmix64(&mut sm),
+        ];
+
+        Self {
+            state: Rc::new(Cell::new(state)),
+        }
+    }
+
+    /// Returns the next random `u64`.
+    pub fn next_u64(&self) -> u64 {
+        let mut s = self.state.get();
+
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+
+        self.state.set(s);
+        result
+    }
+
+    /// Returns the next random `u32`.
+    pub fn next_u32(&self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Fills `dest` with random bytes.
+    pub fn fill_bytes(&self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+impl Default for RequestRng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for RequestRng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestRng").finish_non_exhaustive()
+    }
+}
+
+impl FromRequest for RequestRng {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        if let Some(rng) = req.extensions().get::<Self>() {
+            return ok(rng.clone());
+        }
+
+        let rng = Self::new();
+        req.extensions_mut().insert(rng.clone());
+        ok(rng)
+    }
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test::{call_and_read_body, init_service, TestRequest},
+        web, App,
+    };
+
+    #[test]
+    fn seeded_sequences_match() {
+        let a = RequestRng::from_seed(42);
+        let b = RequestRng::from_seed(42);
+        let c = RequestRng::from_seed(43);
+
+        let a = (0..8).map(|_| a.next_u64()).collect::<Vec<_>>();
+        let b = (0..8).map(|_| b.next_u64()).collect::<Vec<_>>();
+        let c = (0..8).map(|_| c.next_u64()).collect::<Vec<_>>();
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn fill_bytes_partial_chunk() {
+        let rng = RequestRng::from_seed(1);
+        let expected = rng.next_u64().to_le_bytes();
+
+        let rng = RequestRng::from_seed(1);
+        let mut buf = [0; 5];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(buf, expected[..5]);
+    }
+
+    #[actix_rt::test]
+    async fn extraction_shares_state() {
+        let (req, mut pl) = TestRequest::default().rng_seed(7).to_http_parts();
+
+        let rng1 = RequestRng::from_request(&req, &mut pl).await.unwrap();
+        let rng2 = RequestRng::from_request(&req, &mut pl).await.unwrap();
+
+        let expected = RequestRng::from_seed(7);
+        assert_eq!(rng1.next_u64(), expected.next_u64());
+        assert_eq!(rng2.next_u64(), expected.next_u64());
+    }
+
+    #[actix_rt::test]
+    async fn seeded_in_service() {
+        let app = init_service(
+            App::new().default_service(web::to(|rng: RequestRng| async move {
+                rng.next_u64().to_string()
+            })),
+        )
+        .await;
+
+        let req = TestRequest::default().rng_seed(99).to_request();
+        let body = call_and_read_body(&app, req).await;
+
+        let expected = RequestRng::from_seed(99).next_u64().to_string();
+        assert_eq!(body, expected.as_bytes());
+    }
+}
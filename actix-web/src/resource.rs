@@ -15,7 +15,8 @@ use crate::{
     dev::{ensure_leading_slash, AppService, ResourceDef},
     guard::{self, Guard},
     handler::Handler,
-    http::header,
+    http::{header, Method},
+    openapi::Operation,
     route::{Route, RouteService},
     service::{
         BoxedHttpService, BoxedHttpServiceFactory, HttpServiceFactory, ServiceRequest,
@@ -55,6 +56,7 @@ pub struct Resource<T = ResourceEndpoint> {
     routes: Vec<Route>,
     app_data: Option<Extensions>,
     guards: Vec<Box<dyn Guard>>,
+    operations: Vec<(Method, Operation)>,
     default: BoxedHttpServiceFactory,
     factory_ref: Rc<RefCell<Option<ResourceFactory>>>,
 }
@@ -71,6 +73,7 @@ impl Resource {
             endpoint: ResourceEndpoint::new(Rc::clone(&factory_ref)),
             factory_ref,
             guards: Vec::new(),
+            operations: Vec::new(),
             app_data: None,
             default: boxed::factory(fn_service(|req: ServiceRequest| async {
                 use crate::HttpMessage as _;
@@ -211,6 +214,35 @@ where
         self
     }
 
+    /// Attaches API operation metadata for requests with the given method to this resource.
+    ///
+    /// Metadata is only used for generating API documentation; it does not affect routing. See
+    /// [`OpenApi`](crate::openapi::OpenApi) for serving the generated document.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{
+    ///     http::{Method, StatusCode},
+    ///     openapi::Operation,
+    ///     web, App, HttpResponse,
+    /// };
+    ///
+    /// App::new().service(
+    ///     web::resource("/users/{id}")
+    ///         .get(|| HttpResponse::Ok())
+    ///         .operation(
+    ///             Method::GET,
+    ///             Operation::new()
+    ///                 .summary("Fetch user")
+    ///                 .response_with_schema(StatusCode::OK, "The user", "User"),
+    ///         ),
+    /// );
+    /// ```
+    pub fn operation(mut self, method: Method, operation: Operation) -> Self {
+        self.operations.push((method, operation));
+        self
+    }
+
     /// Add resource data after wrapping in `Data<T>`.
     ///
     /// Deprecated in favor of [`app_data`](Self::app_data).
@@ -283,6 +315,7 @@ where
             rdef: self.rdef,
             name: self.name,
             guards: self.guards,
+            operations: self.operations,
             routes: self.routes,
             default: self.default,
             app_data: self.app_data,
@@ -321,6 +354,7 @@ where
             rdef: self.rdef,
             name: self.name,
             guards: self.guards,
+            operations: self.operations,
             routes: self.routes,
             default: self.default,
             app_data: self.app_data,
@@ -433,6 +467,12 @@ where
             rdef.set_name(name);
         }
 
+        for (method, operation) in self.operations {
+            for path in rdef.pattern_iter() {
+                config.register_operation(path, method.clone(), operation.clone());
+            }
+        }
+
         *self.factory_ref.borrow_mut() = Some(ResourceFactory {
             routes: self.routes,
             default: self.default,
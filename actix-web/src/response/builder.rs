@@ -16,7 +16,7 @@ use crate::{
     error::{Error, JsonPayloadError},
     http::{
         header::{self, HeaderName, TryIntoHeaderPair, TryIntoHeaderValue},
-        ConnectionType, StatusCode,
+        ConnectionType, KeepAlive, StatusCode,
     },
     BoxError, HttpRequest, HttpResponse, Responder,
 };
@@ -161,6 +161,19 @@ impl HttpResponseBuilder {
         self
     }
 
+    /// Overrides the server's keep-alive policy for the connection this response is sent on.
+    ///
+    /// For example, `KeepAlive::Disabled` closes the connection once the response has been sent
+    /// and `KeepAlive::Timeout` changes how long the connection may stay idle afterwards. See
+    /// [`ResponseHead::set_keep_alive_policy`] for details.
+    #[inline]
+    pub fn keep_alive_policy(&mut self, keep_alive: impl Into<KeepAlive>) -> &mut Self {
+        if let Some(parts) = self.inner() {
+            parts.set_keep_alive_policy(keep_alive.into());
+        }
+        self
+    }
+
     /// Set connection type to Upgrade
     #[inline]
     pub fn upgrade<V>(&mut self, value: V) -> &mut Self
@@ -199,6 +212,22 @@ impl HttpResponseBuilder {
         self
     }
 
+    /// Delimit an HTTP/1.x streaming response body by closing the connection, without using
+    /// chunked transfer encoding.
+    ///
+    /// This is useful for clients that are known to mishandle chunked responses. Any previously
+    /// set `Content-Length` header is removed and the connection is closed once the body has been
+    /// written. Has no effect on HTTP/2 responses, which are framed by the protocol itself.
+    #[inline]
+    pub fn close_delimited(&mut self) -> &mut Self {
+        if let Some(parts) = self.inner() {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts.no_chunking(true);
+            parts.set_connection_type(ConnectionType::Close);
+        }
+        self
+    }
+
     /// Set response content type.
     #[inline]
     pub fn content_type<V>(&mut self, value: V) -> &mut Self
@@ -287,11 +316,24 @@ impl HttpResponseBuilder {
     /// Unlike [`message_body`](Self::message_body), errors are converted into error
     /// responses immediately.
     ///
+    /// A warning is logged if the body is not empty but the response status does not allow a body
+    /// to be sent, e.g. `204 No Content`; see [`ResponseHead::allows_body()`].
+    ///
     /// `HttpResponseBuilder` can not be used after this call.
     pub fn body<B>(&mut self, body: B) -> HttpResponse<BoxBody>
     where
         B: MessageBody + 'static,
     {
+        if let Some(res) = self.res.as_ref() {
+            if !res.head().allows_body() && !body.size().is_eof() {
+                log::warn!(
+                    "body of {} response will not be sent; responses with this status must not \
+                    have a body",
+                    res.status()
+                );
+            }
+        }
+
         match self.message_body(body) {
             Ok(res) => res.map_into_boxed_body(),
             Err(err) => HttpResponse::from_error(err),
@@ -440,6 +482,17 @@ mod tests {
         assert!(!resp.keep_alive())
     }
 
+    #[test]
+    fn test_close_delimited() {
+        let resp = HttpResponseBuilder::new(StatusCode::OK)
+            .no_chunking(10)
+            .close_delimited()
+            .finish();
+        assert!(!resp.keep_alive());
+        assert!(!resp.head().chunked());
+        assert!(!resp.headers().contains_key(header::CONTENT_LENGTH));
+    }
+
     #[test]
     fn test_content_type() {
         let resp = HttpResponseBuilder::new(StatusCode::OK)
@@ -79,6 +79,17 @@ impl<B> HttpResponse<B> {
         self.error.as_ref()
     }
 
+    /// Sets the source `error` for this response.
+    pub(crate) fn with_error(mut self, error: Error) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    /// Takes the source `error` out of this response.
+    pub(crate) fn take_error(&mut self) -> Option<Error> {
+        self.error.take()
+    }
+
     /// Get the response status code
     #[inline]
     pub fn status(&self) -> StatusCode {
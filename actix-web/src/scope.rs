@@ -10,9 +10,10 @@ use futures_core::future::LocalBoxFuture;
 use futures_util::future::join_all;
 
 use crate::{
-    config::ServiceConfig,
+    config::{AppModule, ServiceConfig},
     data::Data,
     dev::AppService,
+    error::{render_errors, ErrorRenderer},
     guard::Guard,
     rmap::ResourceMap,
     service::{
@@ -61,6 +62,7 @@ pub struct Scope<T = ScopeEndpoint> {
     services: Vec<Box<dyn AppServiceFactory>>,
     guards: Vec<Box<dyn Guard>>,
     default: Option<Rc<BoxedHttpServiceFactory>>,
+    error_renderer: Option<Rc<dyn ErrorRenderer>>,
     external: Vec<ResourceDef>,
     factory_ref: Rc<RefCell<Option<ScopeFactory>>>,
 }
@@ -77,6 +79,7 @@ impl Scope {
             guards: Vec::new(),
             services: Vec::new(),
             default: None,
+            error_renderer: None,
             external: Vec::new(),
             factory_ref,
         }
@@ -205,6 +208,14 @@ where
         self
     }
 
+    /// Registers a [module](AppModule) as part of the scope.
+    ///
+    /// Counterpart to [`App::module()`](crate::App::module) for modules whose services should be
+    /// mounted under this scope's path.
+    pub fn module<M: AppModule>(self, module: M) -> Self {
+        self.configure(|cfg| module.register(cfg))
+    }
+
     /// Register HTTP service.
     ///
     /// This is similar to `App's` service registration.
@@ -286,6 +297,28 @@ where
         self
     }
 
+    /// Sets the renderer of the error responses of this scope's services.
+    ///
+    /// This overrides the renderer of the parent scope or [`App`](crate::App) for the services of
+    /// this scope. See [`ErrorRenderer`] for which responses are rendered.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{error::ProblemDetails, web, App};
+    ///
+    /// let app = App::new().service(
+    ///     web::scope("/api")
+    ///         .error_renderer(ProblemDetails)
+    ///         .route("/items/{id}", web::get().to(|id: web::Path<u32>| async move {
+    ///             format!("item {id}")
+    ///         })),
+    /// );
+    /// ```
+    pub fn error_renderer<R: ErrorRenderer>(mut self, renderer: R) -> Self {
+        self.error_renderer = Some(Rc::new(renderer));
+        self
+    }
+
     /// Registers a scope-wide middleware.
     ///
     /// `mw` is a middleware component (type), that can modify the request and response across all
@@ -323,6 +356,7 @@ where
             guards: self.guards,
             services: self.services,
             default: self.default,
+            error_renderer: self.error_renderer,
             external: self.external,
             factory_ref: self.factory_ref,
         }
@@ -361,6 +395,7 @@ where
             guards: self.guards,
             services: self.services,
             default: self.default,
+            error_renderer: self.error_renderer,
             external: self.external,
             factory_ref: self.factory_ref,
         }
@@ -384,6 +419,7 @@ where
 
         // register nested services
         let mut cfg = config.clone_config();
+        cfg.push_path_prefix(&self.rdef);
         self.services
             .into_iter()
             .for_each(|mut srv| srv.register(&mut cfg));
@@ -398,6 +434,7 @@ where
         // complete scope pipeline creation
         *self.factory_ref.borrow_mut() = Some(ScopeFactory {
             default,
+            error_renderer: self.error_renderer,
             services: cfg
                 .into_services()
                 .1
@@ -451,6 +488,7 @@ pub struct ScopeFactory {
         )],
     >,
     default: Rc<BoxedHttpServiceFactory>,
+    error_renderer: Option<Rc<dyn ErrorRenderer>>,
 }
 
 impl ServiceFactory<ServiceRequest> for ScopeFactory {
@@ -477,6 +515,8 @@ impl ServiceFactory<ServiceRequest> for ScopeFactory {
             }
         }));
 
+        let error_renderer = self.error_renderer.clone();
+
         Box::pin(async move {
             let default = default_fut.await?;
 
@@ -492,7 +532,11 @@ impl ServiceFactory<ServiceRequest> for ScopeFactory {
                 })
                 .finish();
 
-            Ok(ScopeService { router, default })
+            Ok(ScopeService {
+                router,
+                default,
+                error_renderer,
+            })
         })
     }
 }
@@ -500,6 +544,7 @@ impl ServiceFactory<ServiceRequest> for ScopeFactory {
 pub struct ScopeService {
     router: Router<BoxedHttpService, Vec<Box<dyn Guard>>>,
     default: BoxedHttpService,
+    error_renderer: Option<Rc<dyn ErrorRenderer>>,
 }
 
 impl Service<ServiceRequest> for ScopeService {
@@ -515,10 +560,15 @@ impl Service<ServiceRequest> for ScopeService {
             guards.iter().all(|guard| guard.check(&guard_ctx))
         });
 
-        if let Some((srv, _info)) = res {
+        let fut = if let Some((srv, _info)) = res {
             srv.call(req)
         } else {
             self.default.call(req)
+        };
+
+        match self.error_renderer {
+            Some(ref renderer) => render_errors(Rc::clone(renderer), fut),
+            None => fut,
         }
     }
 }
@@ -900,6 +950,56 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::NOT_FOUND);
     }
 
+    #[actix_rt::test]
+    async fn test_error_renderer() {
+        let srv = init_service(
+            App::new()
+                .error_renderer(|err: &Error, _: &HttpRequest| {
+                    HttpResponse::BadRequest().body(format!("app: {err}"))
+                })
+                .service(
+                    web::scope("/app")
+                        .error_renderer(|err: &Error, req: &HttpRequest| {
+                            HttpResponse::BadRequest().body(format!("{}: {err}", req.path()))
+                        })
+                        .route(
+                            "/err",
+                            web::get().to(|| async {
+                                Err::<HttpResponse, _>(crate::error::ErrorNotFound("gone"))
+                            }),
+                        )
+                        .route("/ok", web::get().to(|| async { "ok" })),
+                )
+                .route(
+                    "/err",
+                    web::get().to(|| async {
+                        Err::<HttpResponse, _>(crate::error::ErrorNotFound("gone"))
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/app/err").to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(res.response().error().unwrap().to_string(), "gone");
+        assert_body_eq!(res, b"/app/err: gone");
+
+        let req = TestRequest::with_uri("/app/ok").to_request();
+        let res = call_service(&srv, req).await;
+        assert_body_eq!(res, b"ok");
+
+        let req = TestRequest::with_uri("/err").to_request();
+        let res = call_service(&srv, req).await;
+        assert_body_eq!(res, b"app: gone");
+
+        // responses not generated from errors are left alone
+        let req = TestRequest::with_uri("/missing").to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert!(read_body(res).await.is_empty());
+    }
+
     #[actix_rt::test]
     async fn test_default_resource_propagation() {
         let srv = init_service(
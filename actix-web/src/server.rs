@@ -1,36 +1,122 @@
 use std::{
     any::Any,
-    cmp, fmt, io,
+    cmp,
+    collections::HashMap,
+    fmt,
+    future::Future,
+    io,
     marker::PhantomData,
-    net,
+    mem, net,
+    rc::Rc,
     sync::{Arc, Mutex},
     time::Duration,
 };
 
+use actix_codec::Framed;
 #[cfg(feature = "__tls")]
 use actix_http::TlsAcceptorConfig;
-use actix_http::{body::MessageBody, Extensions, HttpService, KeepAlive, Request, Response};
-use actix_server::{Server, ServerBuilder};
+use actix_http::{
+    body::{BoxBody, MessageBody},
+    h1, Extensions, HeaderLimits, HttpService, HttpServiceBuilder, KeepAlive, Request, Response,
+    ShutdownSignal,
+};
+use actix_rt::net::TcpStream;
+use actix_server::{GracefulShutdownSignal, Server, ServerBuilder};
 use actix_service::{
+    boxed::{self, BoxServiceFactory},
     map_config, IntoServiceFactory, Service, ServiceFactory, ServiceFactoryExt as _,
 };
 #[cfg(feature = "openssl")]
 use actix_tls::accept::openssl::reexports::{AlpnError, SslAcceptor, SslAcceptorBuilder};
+use futures_core::future::LocalBoxFuture;
 
-use crate::{config::AppConfig, Error};
+use crate::{
+    config::AppConfig,
+    worker_dispatch::{WorkerBalancer, WorkerDispatch, WorkerLoads},
+    Error, HttpResponse,
+};
 
 struct Socket {
     scheme: &'static str,
     addr: net::SocketAddr,
 }
 
+/// Type-erased app factory used by named listeners.
+type BoxAppFactory =
+    BoxServiceFactory<AppConfig, Request, Response<BoxBody>, Response<BoxBody>, ()>;
+
 struct Config {
     host: Option<String>,
     keep_alive: KeepAlive,
     client_request_timeout: Duration,
     client_disconnect_timeout: Duration,
-    #[allow(dead_code)] // only dead when no TLS features are enabled
+    header_limits: HeaderLimits,
+    listener_apps: HashMap<String, Box<dyn Fn() -> BoxAppFactory + Send>>,
     tls_handshake_timeout: Option<Duration>,
+    warm_up: Arc<Mutex<WarmUp>>,
+    graceful_shutdown: GracefulShutdownSignal,
+    worker_dispatch: WorkerDispatch,
+    catch_panics: Option<CatchPanics>,
+}
+
+/// Callback creating the response sent when a panic of the app is caught.
+type PanicResponse = Arc<dyn Fn(&(dyn Any + Send)) -> HttpResponse + Send + Sync>;
+
+struct CatchPanics {
+    keep_alive: bool,
+    response: Option<PanicResponse>,
+}
+
+impl Config {
+    /// Returns a connection shutdown signal that is triggered when the server starts to stop
+    /// gracefully.
+    ///
+    /// Must be called on a worker thread, where the task that waits for the server to stop runs.
+    fn shutdown_signal(&self) -> ShutdownSignal {
+        let signal = ShutdownSignal::new();
+        let graceful_shutdown = self.graceful_shutdown.clone();
+
+        actix_rt::spawn({
+            let signal = signal.clone();
+
+            async move {
+                graceful_shutdown.notified().await;
+                signal.shutdown();
+            }
+        });
+
+        signal
+    }
+
+    /// Applies the panic catching settings to an HTTP service builder.
+    fn catch_panics<T, S, X, U>(
+        &self,
+        svc: HttpServiceBuilder<T, S, X, U>,
+    ) -> HttpServiceBuilder<T, S, X, U>
+    where
+        S: ServiceFactory<Request, Config = ()>,
+        S::Error: Into<Response<BoxBody>> + 'static,
+        S::InitError: fmt::Debug,
+        <S::Service as Service<Request>>::Future: 'static,
+        X: ServiceFactory<Request, Config = ()>,
+        X::Response: Into<h1::ExpectResponse>,
+        X::Error: Into<Response<BoxBody>>,
+        X::InitError: fmt::Debug,
+        U: ServiceFactory<(Request, Framed<T, h1::Codec>), Config = (), Response = ()>,
+        U::Error: fmt::Display,
+        U::InitError: fmt::Debug,
+    {
+        let Some(catch_panics) = &self.catch_panics else {
+            return svc;
+        };
+
+        let svc = svc.catch_panics(catch_panics.keep_alive);
+
+        match catch_panics.response.clone() {
+            Some(response) => svc.on_panic(move |payload| response(payload).into()),
+            None => svc,
+        }
+    }
 }
 
 /// An HTTP Server.
@@ -84,6 +170,8 @@ where
     _phantom: PhantomData<(S, B)>,
 }
 
+type WarmUpHook = Box<dyn FnOnce() -> LocalBoxFuture<'static, ()> + Send>;
+
 impl<F, I, S, B> HttpServer<F, I, S, B>
 where
     F: Fn() -> I + Send + Clone + 'static,
@@ -106,6 +194,8 @@ where
     /// [`bind()`](Self::bind()) docs for more on how worker count and bind address resolution
     /// causes multiple server factory instantiations.
     pub fn new(factory: F) -> Self {
+        let builder = ServerBuilder::default();
+
         HttpServer {
             factory,
             config: Arc::new(Mutex::new(Config {
@@ -113,11 +203,17 @@ where
                 keep_alive: KeepAlive::default(),
                 client_request_timeout: Duration::from_secs(5),
                 client_disconnect_timeout: Duration::from_secs(1),
+                header_limits: HeaderLimits::default(),
+                listener_apps: HashMap::new(),
                 tls_handshake_timeout: None,
+                warm_up: Arc::default(),
+                graceful_shutdown: builder.graceful_shutdown_signal(),
+                worker_dispatch: WorkerDispatch::default(),
+                catch_panics: None,
             })),
             backlog: 1024,
             sockets: Vec::new(),
-            builder: ServerBuilder::default(),
+            builder,
             on_connect_fn: None,
             _phantom: PhantomData,
         }
@@ -142,6 +238,85 @@ where
         self
     }
 
+    /// Sets how accepted connections are distributed between workers.
+    ///
+    /// By default, workers take turns serving connections. With [`WorkerDispatch::LeastLoaded`],
+    /// connections are moved to the worker with the fewest requests in flight instead, which
+    /// helps when some requests are much more expensive than others. Connections moved to another
+    /// worker keep counting towards the [`max_connections()`](Self::max_connections()) limit of the
+    /// worker that accepted them, which also waits for them on graceful shutdown.
+    ///
+    /// Only applies to TCP listeners; connections to Unix domain sockets are always served by the
+    /// worker they were accepted for.
+    ///
+    /// [`WorkerDispatch::LeastLoaded`]: crate::dev::WorkerDispatch::LeastLoaded
+    pub fn worker_dispatch(self, dispatch: WorkerDispatch) -> Self {
+        self.config.lock().unwrap().worker_dispatch = dispatch;
+        self
+    }
+
+    /// Catches panics of the app and responds with `500 Internal Server Error` instead.
+    ///
+    /// By default, a panic in a handler or middleware tears down the connection (for HTTP/1) or the
+    /// stream (for HTTP/2) it occurred on. When panics are caught, the panic message is logged and
+    /// an error response is sent in place of the response. Use [`on_panic()`](Self::on_panic()) to
+    /// customize this response.
+    ///
+    /// After the error response is sent, the connection is kept alive if `keep_alive` is true and
+    /// otherwise closed. Closing is the safer choice, since a panic may leave state shared with
+    /// other requests inconsistent.
+    ///
+    /// Panics while streaming a response body are not caught.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use actix_web::{App, HttpResponse, HttpServer};
+    ///
+    /// # fn inner() -> std::io::Result<()> {
+    /// HttpServer::new(|| App::new())
+    ///     .catch_panics(false)
+    ///     .on_panic(|_| HttpResponse::InternalServerError().body("something went wrong"))
+    ///     .bind(("127.0.0.1", 8080))?
+    ///     .run();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn catch_panics(self, keep_alive: bool) -> Self {
+        self.config
+            .lock()
+            .unwrap()
+            .catch_panics
+            .get_or_insert(CatchPanics {
+                keep_alive,
+                response: None,
+            })
+            .keep_alive = keep_alive;
+
+        self
+    }
+
+    /// Sets callback that creates the response sent when a panic of the app is caught.
+    ///
+    /// The callback receives the panic payload. This enables [catching
+    /// panics](Self::catch_panics()), closing the connection afterwards unless configured
+    /// otherwise.
+    pub fn on_panic<P>(self, f: P) -> Self
+    where
+        P: Fn(&(dyn Any + Send)) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.config
+            .lock()
+            .unwrap()
+            .catch_panics
+            .get_or_insert(CatchPanics {
+                keep_alive: false,
+                response: None,
+            })
+            .response = Some(Arc::new(f));
+
+        self
+    }
+
     /// Sets server keep-alive preference.
     ///
     /// By default keep-alive is set to 5 seconds.
@@ -231,6 +406,31 @@ where
         self
     }
 
+    /// Sets limits that request headers are checked against before requests reach the app.
+    ///
+    /// Requests exceeding the limits on cookie count or size, or on individual header value size,
+    /// are rejected with a `431 Request Header Fields Too Large` response. Requests containing a
+    /// forbidden header are rejected with a `400 Bad Request` response.
+    ///
+    /// By default, no limits are enforced.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use actix_web::{dev::HeaderLimits, App, HttpServer};
+    ///
+    /// # fn inner() -> std::io::Result<()> {
+    /// HttpServer::new(|| App::new())
+    ///     .header_limits(HeaderLimits::new().max_cookie_count(50).max_header_value_size(8192))
+    ///     .bind(("127.0.0.1", 8080))?
+    ///     .run();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn header_limits(self, limits: HeaderLimits) -> Self {
+        self.config.lock().unwrap().header_limits = limits;
+        self
+    }
+
     /// Sets TLS handshake timeout.
     ///
     /// Defines a timeout for TLS handshake. If the TLS handshake does not complete within this
@@ -298,6 +498,73 @@ where
         self
     }
 
+    /// Sets the app factory used by listeners bound with the given `name`.
+    ///
+    /// Listeners bound using the named variants of the bind and listen methods, such as
+    /// [`bind_named()`](Self::bind_named()), [`listen_named()`](Self::listen_named()), or
+    /// [`bind_uds_named()`](Self::bind_uds_named()), serve the app created by `factory` instead of
+    /// the default one passed to [`new()`](Self::new()). This allows, for example, serving an admin app with a different
+    /// middleware stack on a localhost-only listener. Named listeners without a registered app use
+    /// the default app factory.
+    ///
+    /// Apps can be registered before or after their listeners are bound.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use actix_web::{middleware::Logger, web, App, HttpServer};
+    ///
+    /// # fn inner() -> std::io::Result<()> {
+    /// HttpServer::new(|| {
+    ///     App::new()
+    ///         .wrap(Logger::default())
+    ///         .route("/", web::get().to(|| async { "public" }))
+    /// })
+    /// .listener_app("admin", || {
+    ///     App::new().route("/metrics", web::get().to(|| async { "admin" }))
+    /// })
+    /// .bind(("0.0.0.0", 8080))?
+    /// .bind_named("admin", ("127.0.0.1", 9090))?
+    /// .run();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn listener_app<F2, I2, S2, B2>(self, name: impl Into<String>, factory: F2) -> Self
+    where
+        F2: Fn() -> I2 + Send + 'static,
+        I2: IntoServiceFactory<S2, Request>,
+
+        S2: ServiceFactory<Request, Config = AppConfig> + 'static,
+        S2::Error: Into<Error> + 'static,
+        S2::InitError: fmt::Debug,
+        S2::Response: Into<Response<B2>> + 'static,
+        <S2::Service as Service<Request>>::Future: 'static,
+        S2::Service: 'static,
+
+        B2: MessageBody + 'static,
+    {
+        self.config
+            .lock()
+            .unwrap()
+            .listener_apps
+            .insert(name.into(), Box::new(move || box_app_factory(factory())));
+
+        self
+    }
+
+    /// Returns the factory of the app serving connections to the listener named `name`, falling
+    /// back to the default app if none was registered using [`listener_app()`](Self::listener_app()).
+    fn named_app(
+        &self,
+        name: String,
+    ) -> impl Fn(&Config) -> BoxAppFactory + Send + Clone + 'static {
+        let factory = self.factory.clone();
+
+        move |cfg: &Config| match cfg.listener_apps.get(&name) {
+            Some(listener_factory) => listener_factory(),
+            None => box_app_factory(factory()),
+        }
+    }
+
     /// Flags the `System` to exit after server shutdown.
     ///
     /// Does nothing when running under `#[tokio::main]` runtime.
@@ -315,7 +582,9 @@ where
     /// Sets timeout for graceful worker shutdown of workers.
     ///
     /// After receiving a stop signal, workers have this much time to finish serving requests.
-    /// Workers still alive after the timeout are force dropped.
+    /// Meanwhile, connections are drained: in-flight requests are answered with a
+    /// `Connection: close` header, idle connections are closed, and HTTP/2 connections send
+    /// `GOAWAY`. Workers still alive after the timeout are force dropped.
     ///
     /// By default shutdown timeout sets to 30 seconds.
     pub fn shutdown_timeout(mut self, sec: u64) -> Self {
@@ -323,6 +592,59 @@ where
         self
     }
 
+    /// Delays accepting connections for a period of time when the server starts.
+    ///
+    /// Warm-up happens when the server is started, while the first worker creates its services and
+    /// before bound sockets are polled for new connections; connections arriving in the meantime
+    /// wait in the listen backlog. Once this delay has elapsed, all [warm-up hooks](Self::warm_up)
+    /// are run. This gives new instances a chance to prime caches or fill connection pools before
+    /// taking full traffic.
+    ///
+    /// Like async [app data factories](crate::App::data_factory), warm-up runs on the worker's
+    /// runtime and the remaining workers are started once it is complete.
+    ///
+    /// By default, there is no delay.
+    pub fn warm_up_delay(self, dur: Duration) -> Self {
+        let warm_up = Arc::clone(&self.config.lock().unwrap().warm_up);
+        warm_up.lock().unwrap().delay = dur;
+        self
+    }
+
+    /// Registers an async hook that must complete before the server starts accepting connections.
+    ///
+    /// Hooks are run one after another, in the order they are registered, once the
+    /// [warm-up delay](Self::warm_up_delay) has elapsed. They run once per server, on the runtime
+    /// of the first worker to start, so they should be used to prepare state shared through `Send`
+    /// types (e.g., an `Arc`'d cache). If a hook panics, the server fails to start.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use actix_web::{web, App, HttpResponse, HttpServer};
+    ///
+    /// # async fn prime_cache() {}
+    /// #[actix_web::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     HttpServer::new(|| App::new().route("/", web::get().to(HttpResponse::Ok)))
+    ///         .warm_up(|| async { prime_cache().await })
+    ///         .bind(("127.0.0.1", 8080))?
+    ///         .run()
+    ///         .await
+    /// }
+    /// ```
+    pub fn warm_up<H, Fut>(self, hook: H) -> Self
+    where
+        H: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        let warm_up = Arc::clone(&self.config.lock().unwrap().warm_up);
+
+        warm_up.lock().unwrap().hooks.push(Box::new(move || {
+            Box::pin(hook()) as LocalBoxFuture<'static, ()>
+        }));
+
+        self
+    }
+
     /// Returns addresses of bound sockets.
     pub fn addrs(&self) -> Vec<net::SocketAddr> {
         self.sockets.iter().map(|s| s.addr).collect()
@@ -383,14 +705,21 @@ where
     ///     .bind("[::1]:9000")?
     /// # ; Ok(()) }
     /// ```
-    pub fn bind<A: net::ToSocketAddrs>(mut self, addrs: A) -> io::Result<Self> {
-        let sockets = bind_addrs(addrs, self.backlog)?;
-
-        for lst in sockets {
-            self = self.listen(lst)?;
-        }
+    pub fn bind<A: net::ToSocketAddrs>(self, addrs: A) -> io::Result<Self> {
+        self.bind_transport(None, addrs, Tcp)
+    }
 
-        Ok(self)
+    /// Resolves socket address(es) and binds server to created named listener(s).
+    ///
+    /// Connections to these listeners are served by the app registered for `name` using
+    /// [`listener_app()`](Self::listener_app()). See [`bind()`](Self::bind()) for more details on
+    /// `addrs` argument.
+    pub fn bind_named<A: net::ToSocketAddrs>(
+        self,
+        name: impl Into<String>,
+        addrs: A,
+    ) -> io::Result<Self> {
+        self.bind_transport(Some(name.into()), addrs, Tcp)
     }
 
     /// Resolves socket address(es) and binds server to created listener(s) for plaintext HTTP/1.x
@@ -398,14 +727,23 @@ where
     ///
     /// See [`bind()`](Self::bind()) for more details on `addrs` argument.
     #[cfg(feature = "http2")]
-    pub fn bind_auto_h2c<A: net::ToSocketAddrs>(mut self, addrs: A) -> io::Result<Self> {
-        let sockets = bind_addrs(addrs, self.backlog)?;
-
-        for lst in sockets {
-            self = self.listen_auto_h2c(lst)?;
-        }
+    pub fn bind_auto_h2c<A: net::ToSocketAddrs>(self, addrs: A) -> io::Result<Self> {
+        self.bind_transport(None, addrs, AutoH2c)
+    }
 
-        Ok(self)
+    /// Resolves socket address(es) and binds server to created named listener(s) for plaintext
+    /// HTTP/1.x or HTTP/2 connections.
+    ///
+    /// Connections to these listeners are served by the app registered for `name` using
+    /// [`listener_app()`](Self::listener_app()). See [`bind()`](Self::bind()) for more details on
+    /// `addrs` argument.
+    #[cfg(feature = "http2")]
+    pub fn bind_named_auto_h2c<A: net::ToSocketAddrs>(
+        self,
+        name: impl Into<String>,
+        addrs: A,
+    ) -> io::Result<Self> {
+        self.bind_transport(Some(name.into()), addrs, AutoH2c)
     }
 
     /// Resolves socket address(es) and binds server to created listener(s) for TLS connections
@@ -416,15 +754,11 @@ where
     /// ALPN protocols "h2" and "http/1.1" are added to any configured ones.
     #[cfg(feature = "rustls-0_20")]
     pub fn bind_rustls<A: net::ToSocketAddrs>(
-        mut self,
+        self,
         addrs: A,
         config: actix_tls::accept::rustls_0_20::reexports::ServerConfig,
     ) -> io::Result<Self> {
-        let sockets = bind_addrs(addrs, self.backlog)?;
-        for lst in sockets {
-            self = self.listen_rustls_0_20_inner(lst, config.clone())?;
-        }
-        Ok(self)
+        self.bind_transport(None, addrs, config)
     }
 
     /// Resolves socket address(es) and binds server to created listener(s) for TLS connections
@@ -435,15 +769,29 @@ where
     /// ALPN protocols "h2" and "http/1.1" are added to any configured ones.
     #[cfg(feature = "rustls-0_21")]
     pub fn bind_rustls_021<A: net::ToSocketAddrs>(
-        mut self,
+        self,
         addrs: A,
         config: actix_tls::accept::rustls_0_21::reexports::ServerConfig,
     ) -> io::Result<Self> {
-        let sockets = bind_addrs(addrs, self.backlog)?;
-        for lst in sockets {
-            self = self.listen_rustls_0_21_inner(lst, config.clone())?;
-        }
-        Ok(self)
+        self.bind_transport(None, addrs, config)
+    }
+
+    /// Resolves socket address(es) and binds server to created named listener(s) for TLS
+    /// connections using Rustls v0.21.
+    ///
+    /// Connections to these listeners are served by the app registered for `name` using
+    /// [`listener_app()`](Self::listener_app()). See [`bind()`](Self::bind()) for more details on
+    /// `addrs` argument.
+    ///
+    /// ALPN protocols "h2" and "http/1.1" are added to any configured ones.
+    #[cfg(feature = "rustls-0_21")]
+    pub fn bind_named_rustls_0_21<A: net::ToSocketAddrs>(
+        self,
+        name: impl Into<String>,
+        addrs: A,
+        config: actix_tls::accept::rustls_0_21::reexports::ServerConfig,
+    ) -> io::Result<Self> {
+        self.bind_transport(Some(name.into()), addrs, config)
     }
 
     /// Resolves socket address(es) and binds server to created listener(s) for TLS connections
@@ -454,15 +802,29 @@ where
     /// ALPN protocols "h2" and "http/1.1" are added to any configured ones.
     #[cfg(feature = "rustls-0_22")]
     pub fn bind_rustls_0_22<A: net::ToSocketAddrs>(
-        mut self,
+        self,
         addrs: A,
         config: actix_tls::accept::rustls_0_22::reexports::ServerConfig,
     ) -> io::Result<Self> {
-        let sockets = bind_addrs(addrs, self.backlog)?;
-        for lst in sockets {
-            self = self.listen_rustls_0_22_inner(lst, config.clone())?;
-        }
-        Ok(self)
+        self.bind_transport(None, addrs, config)
+    }
+
+    /// Resolves socket address(es) and binds server to created named listener(s) for TLS
+    /// connections using Rustls v0.22.
+    ///
+    /// Connections to these listeners are served by the app registered for `name` using
+    /// [`listener_app()`](Self::listener_app()). See [`bind()`](Self::bind()) for more details on
+    /// `addrs` argument.
+    ///
+    /// ALPN protocols "h2" and "http/1.1" are added to any configured ones.
+    #[cfg(feature = "rustls-0_22")]
+    pub fn bind_named_rustls_0_22<A: net::ToSocketAddrs>(
+        self,
+        name: impl Into<String>,
+        addrs: A,
+        config: actix_tls::accept::rustls_0_22::reexports::ServerConfig,
+    ) -> io::Result<Self> {
+        self.bind_transport(Some(name.into()), addrs, config)
     }
 
     /// Resolves socket address(es) and binds server to created listener(s) for TLS connections
@@ -473,15 +835,29 @@ where
     /// ALPN protocols "h2" and "http/1.1" are added to any configured ones.
     #[cfg(feature = "rustls-0_23")]
     pub fn bind_rustls_0_23<A: net::ToSocketAddrs>(
-        mut self,
+        self,
         addrs: A,
         config: actix_tls::accept::rustls_0_23::reexports::ServerConfig,
     ) -> io::Result<Self> {
-        let sockets = bind_addrs(addrs, self.backlog)?;
-        for lst in sockets {
-            self = self.listen_rustls_0_23_inner(lst, config.clone())?;
-        }
-        Ok(self)
+        self.bind_transport(None, addrs, config)
+    }
+
+    /// Resolves socket address(es) and binds server to created named listener(s) for TLS
+    /// connections using Rustls v0.23.
+    ///
+    /// Connections to these listeners are served by the app registered for `name` using
+    /// [`listener_app()`](Self::listener_app()). See [`bind()`](Self::bind()) for more details on
+    /// `addrs` argument.
+    ///
+    /// ALPN protocols "h2" and "http/1.1" are added to any configured ones.
+    #[cfg(feature = "rustls-0_23")]
+    pub fn bind_named_rustls_0_23<A: net::ToSocketAddrs>(
+        self,
+        name: impl Into<String>,
+        addrs: A,
+        config: actix_tls::accept::rustls_0_23::reexports::ServerConfig,
+    ) -> io::Result<Self> {
+        self.bind_transport(Some(name.into()), addrs, config)
     }
 
     /// Resolves socket address(es) and binds server to created listener(s) for TLS connections
@@ -491,15 +867,50 @@ where
     ///
     /// ALPN protocols "h2" and "http/1.1" are added to any configured ones.
     #[cfg(feature = "openssl")]
-    pub fn bind_openssl<A>(mut self, addrs: A, builder: SslAcceptorBuilder) -> io::Result<Self>
+    pub fn bind_openssl<A>(self, addrs: A, builder: SslAcceptorBuilder) -> io::Result<Self>
     where
         A: net::ToSocketAddrs,
     {
-        let sockets = bind_addrs(addrs, self.backlog)?;
         let acceptor = openssl_acceptor(builder)?;
+        self.bind_transport(None, addrs, acceptor)
+    }
+
+    /// Resolves socket address(es) and binds server to created named listener(s) for TLS
+    /// connections using OpenSSL.
+    ///
+    /// Connections to these listeners are served by the app registered for `name` using
+    /// [`listener_app()`](Self::listener_app()). See [`bind()`](Self::bind()) for more details on
+    /// `addrs` argument.
+    ///
+    /// ALPN protocols "h2" and "http/1.1" are added to any configured ones.
+    #[cfg(feature = "openssl")]
+    pub fn bind_named_openssl<A>(
+        self,
+        name: impl Into<String>,
+        addrs: A,
+        builder: SslAcceptorBuilder,
+    ) -> io::Result<Self>
+    where
+        A: net::ToSocketAddrs,
+    {
+        let acceptor = openssl_acceptor(builder)?;
+        self.bind_transport(Some(name.into()), addrs, acceptor)
+    }
+
+    fn bind_transport<A, P>(
+        mut self,
+        name: Option<String>,
+        addrs: A,
+        transport: P,
+    ) -> io::Result<Self>
+    where
+        A: net::ToSocketAddrs,
+        P: Transport,
+    {
+        let sockets = bind_addrs(addrs, self.backlog)?;
 
         for lst in sockets {
-            self = self.listen_openssl_inner(lst, acceptor.clone())?;
+            self = self.listen_transport(name.clone(), lst, transport.clone())?;
         }
 
         Ok(self)
@@ -509,90 +920,38 @@ where
     ///
     /// No changes are made to `lst`'s configuration. Ensure it is configured properly before
     /// passing ownership to `listen()`.
-    pub fn listen(mut self, lst: net::TcpListener) -> io::Result<Self> {
-        let cfg = Arc::clone(&self.config);
-        let factory = self.factory.clone();
-        let addr = lst.local_addr().unwrap();
-
-        self.sockets.push(Socket {
-            addr,
-            scheme: "http",
-        });
-
-        let on_connect_fn = self.on_connect_fn.clone();
-
-        self.builder =
-            self.builder
-                .listen(format!("actix-web-service-{}", addr), lst, move || {
-                    let cfg = cfg.lock().unwrap();
-                    let host = cfg.host.clone().unwrap_or_else(|| format!("{}", addr));
-
-                    let mut svc = HttpService::build()
-                        .keep_alive(cfg.keep_alive)
-                        .client_request_timeout(cfg.client_request_timeout)
-                        .client_disconnect_timeout(cfg.client_disconnect_timeout)
-                        .local_addr(addr);
-
-                    if let Some(handler) = on_connect_fn.clone() {
-                        svc =
-                            svc.on_connect_ext(move |io: &_, ext: _| (handler)(io as &dyn Any, ext))
-                    };
-
-                    let fac = factory()
-                        .into_factory()
-                        .map_err(|err| err.into().error_response());
-
-                    svc.finish(map_config(fac, move |_| {
-                        AppConfig::new(false, host.clone(), addr)
-                    }))
-                    .tcp()
-                })?;
+    pub fn listen(self, lst: net::TcpListener) -> io::Result<Self> {
+        self.listen_transport(None, lst, Tcp)
+    }
 
-        Ok(self)
+    /// Binds to existing listener as a named listener.
+    ///
+    /// Connections to this listener are served by the app registered for `name` using
+    /// [`listener_app()`](Self::listener_app()). See [`listen()`](Self::listen()) for more details
+    /// on the `lst` argument.
+    pub fn listen_named(self, name: impl Into<String>, lst: net::TcpListener) -> io::Result<Self> {
+        self.listen_transport(Some(name.into()), lst, Tcp)
     }
 
     /// Binds to existing listener for accepting incoming plaintext HTTP/1.x or HTTP/2 connections.
     #[cfg(feature = "http2")]
-    pub fn listen_auto_h2c(mut self, lst: net::TcpListener) -> io::Result<Self> {
-        let cfg = Arc::clone(&self.config);
-        let factory = self.factory.clone();
-        let addr = lst.local_addr().unwrap();
-
-        self.sockets.push(Socket {
-            addr,
-            scheme: "http",
-        });
-
-        let on_connect_fn = self.on_connect_fn.clone();
-
-        self.builder =
-            self.builder
-                .listen(format!("actix-web-service-{}", addr), lst, move || {
-                    let cfg = cfg.lock().unwrap();
-                    let host = cfg.host.clone().unwrap_or_else(|| format!("{}", addr));
-
-                    let mut svc = HttpService::build()
-                        .keep_alive(cfg.keep_alive)
-                        .client_request_timeout(cfg.client_request_timeout)
-                        .client_disconnect_timeout(cfg.client_disconnect_timeout)
-                        .local_addr(addr);
-
-                    if let Some(handler) = on_connect_fn.clone() {
-                        svc =
-                            svc.on_connect_ext(move |io: &_, ext: _| (handler)(io as &dyn Any, ext))
-                    };
-
-                    let fac = factory()
-                        .into_factory()
-                        .map_err(|err| err.into().error_response());
-
-                    svc.finish(map_config(fac, move |_| {
-                        AppConfig::new(false, host.clone(), addr)
-                    }))
-                    .tcp_auto_h2c()
-                })?;
+    pub fn listen_auto_h2c(self, lst: net::TcpListener) -> io::Result<Self> {
+        self.listen_transport(None, lst, AutoH2c)
+    }
 
-        Ok(self)
+    /// Binds to existing listener as a named listener for accepting incoming plaintext HTTP/1.x or
+    /// HTTP/2 connections.
+    ///
+    /// Connections to this listener are served by the app registered for `name` using
+    /// [`listener_app()`](Self::listener_app()). See [`listen()`](Self::listen()) for more details
+    /// on the `lst` argument.
+    #[cfg(feature = "http2")]
+    pub fn listen_named_auto_h2c(
+        self,
+        name: impl Into<String>,
+        lst: net::TcpListener,
+    ) -> io::Result<Self> {
+        self.listen_transport(Some(name.into()), lst, AutoH2c)
     }
 
     /// Binds to existing listener for accepting incoming TLS connection requests using Rustls
@@ -607,7 +966,7 @@ where
         lst: net::TcpListener,
         config: actix_tls::accept::rustls_0_20::reexports::ServerConfig,
     ) -> io::Result<Self> {
-        self.listen_rustls_0_20_inner(lst, config)
+        self.listen_transport(None, lst, config)
     }
 
     /// Binds to existing listener for accepting incoming TLS connection requests using Rustls
@@ -622,109 +981,25 @@ where
         lst: net::TcpListener,
         config: actix_tls::accept::rustls_0_21::reexports::ServerConfig,
     ) -> io::Result<Self> {
-        self.listen_rustls_0_21_inner(lst, config)
-    }
-
-    #[cfg(feature = "rustls-0_20")]
-    fn listen_rustls_0_20_inner(
-        mut self,
-        lst: net::TcpListener,
-        config: actix_tls::accept::rustls_0_20::reexports::ServerConfig,
-    ) -> io::Result<Self> {
-        let factory = self.factory.clone();
-        let cfg = Arc::clone(&self.config);
-        let addr = lst.local_addr().unwrap();
-        self.sockets.push(Socket {
-            addr,
-            scheme: "https",
-        });
-
-        let on_connect_fn = self.on_connect_fn.clone();
-
-        self.builder =
-            self.builder
-                .listen(format!("actix-web-service-{}", addr), lst, move || {
-                    let c = cfg.lock().unwrap();
-                    let host = c.host.clone().unwrap_or_else(|| format!("{}", addr));
-
-                    let svc = HttpService::build()
-                        .keep_alive(c.keep_alive)
-                        .client_request_timeout(c.client_request_timeout)
-                        .client_disconnect_timeout(c.client_disconnect_timeout);
-
-                    let svc = if let Some(handler) = on_connect_fn.clone() {
-                        svc.on_connect_ext(move |io: &_, ext: _| (handler)(io as &dyn Any, ext))
-                    } else {
-                        svc
-                    };
-
-                    let fac = factory()
-                        .into_factory()
-                        .map_err(|err| err.into().error_response());
-
-                    let acceptor_config = match c.tls_handshake_timeout {
-                        Some(dur) => TlsAcceptorConfig::default().handshake_timeout(dur),
-                        None => TlsAcceptorConfig::default(),
-                    };
-
-                    svc.finish(map_config(fac, move |_| {
-                        AppConfig::new(true, host.clone(), addr)
-                    }))
-                    .rustls_with_config(config.clone(), acceptor_config)
-                })?;
-
-        Ok(self)
+        self.listen_transport(None, lst, config)
     }
 
+    /// Binds to existing listener as a named listener for accepting incoming TLS connection
+    /// requests using Rustls v0.21.
+    ///
+    /// Connections to this listener are served by the app registered for `name` using
+    /// [`listener_app()`](Self::listener_app()). See [`listen()`](Self::listen()) for more details
+    /// on the `lst` argument.
+    ///
+    /// ALPN protocols "h2" and "http/1.1" are added to any configured ones.
     #[cfg(feature = "rustls-0_21")]
-    fn listen_rustls_0_21_inner(
-        mut self,
+    pub fn listen_named_rustls_0_21(
+        self,
+        name: impl Into<String>,
         lst: net::TcpListener,
         config: actix_tls::accept::rustls_0_21::reexports::ServerConfig,
     ) -> io::Result<Self> {
-        let factory = self.factory.clone();
-        let cfg = Arc::clone(&self.config);
-        let addr = lst.local_addr().unwrap();
-        self.sockets.push(Socket {
-            addr,
-            scheme: "https",
-        });
-
-        let on_connect_fn = self.on_connect_fn.clone();
-
-        self.builder =
-            self.builder
-                .listen(format!("actix-web-service-{}", addr), lst, move || {
-                    let c = cfg.lock().unwrap();
-                    let host = c.host.clone().unwrap_or_else(|| format!("{}", addr));
-
-                    let svc = HttpService::build()
-                        .keep_alive(c.keep_alive)
-                        .client_request_timeout(c.client_request_timeout)
-                        .client_disconnect_timeout(c.client_disconnect_timeout);
-
-                    let svc = if let Some(handler) = on_connect_fn.clone() {
-                        svc.on_connect_ext(move |io: &_, ext: _| (handler)(io as &dyn Any, ext))
-                    } else {
-                        svc
-                    };
-
-                    let fac = factory()
-                        .into_factory()
-                        .map_err(|err| err.into().error_response());
-
-                    let acceptor_config = match c.tls_handshake_timeout {
-                        Some(dur) => TlsAcceptorConfig::default().handshake_timeout(dur),
-                        None => TlsAcceptorConfig::default(),
-                    };
-
-                    svc.finish(map_config(fac, move |_| {
-                        AppConfig::new(true, host.clone(), addr)
-                    }))
-                    .rustls_021_with_config(config.clone(), acceptor_config)
-                })?;
-
-        Ok(self)
+        self.listen_transport(Some(name.into()), lst, config)
     }
 
     /// Binds to existing listener for accepting incoming TLS connection requests using Rustls
@@ -739,58 +1014,25 @@ where
         lst: net::TcpListener,
         config: actix_tls::accept::rustls_0_22::reexports::ServerConfig,
     ) -> io::Result<Self> {
-        self.listen_rustls_0_22_inner(lst, config)
+        self.listen_transport(None, lst, config)
     }
 
+    /// Binds to existing listener as a named listener for accepting incoming TLS connection
+    /// requests using Rustls v0.22.
+    ///
+    /// Connections to this listener are served by the app registered for `name` using
+    /// [`listener_app()`](Self::listener_app()). See [`listen()`](Self::listen()) for more details
+    /// on the `lst` argument.
+    ///
+    /// ALPN protocols "h2" and "http/1.1" are added to any configured ones.
     #[cfg(feature = "rustls-0_22")]
-    fn listen_rustls_0_22_inner(
-        mut self,
+    pub fn listen_named_rustls_0_22(
+        self,
+        name: impl Into<String>,
         lst: net::TcpListener,
         config: actix_tls::accept::rustls_0_22::reexports::ServerConfig,
     ) -> io::Result<Self> {
-        let factory = self.factory.clone();
-        let cfg = Arc::clone(&self.config);
-        let addr = lst.local_addr().unwrap();
-        self.sockets.push(Socket {
-            addr,
-            scheme: "https",
-        });
-
-        let on_connect_fn = self.on_connect_fn.clone();
-
-        self.builder =
-            self.builder
-                .listen(format!("actix-web-service-{}", addr), lst, move || {
-                    let c = cfg.lock().unwrap();
-                    let host = c.host.clone().unwrap_or_else(|| format!("{}", addr));
-
-                    let svc = HttpService::build()
-                        .keep_alive(c.keep_alive)
-                        .client_request_timeout(c.client_request_timeout)
-                        .client_disconnect_timeout(c.client_disconnect_timeout);
-
-                    let svc = if let Some(handler) = on_connect_fn.clone() {
-                        svc.on_connect_ext(move |io: &_, ext: _| (handler)(io as &dyn Any, ext))
-                    } else {
-                        svc
-                    };
-
-                    let fac = factory()
-                        .into_factory()
-                        .map_err(|err| err.into().error_response());
-
-                    let acceptor_config = match c.tls_handshake_timeout {
-                        Some(dur) => TlsAcceptorConfig::default().handshake_timeout(dur),
-                        None => TlsAcceptorConfig::default(),
-                    };
-
-                    svc.finish(map_config(fac, move |_| {
-                        AppConfig::new(true, host.clone(), addr)
-                    }))
-                    .rustls_0_22_with_config(config.clone(), acceptor_config)
-                })?;
-
-        Ok(self)
+        self.listen_transport(Some(name.into()), lst, config)
     }
 
     /// Binds to existing listener for accepting incoming TLS connection requests using Rustls
@@ -805,58 +1047,25 @@ where
         lst: net::TcpListener,
         config: actix_tls::accept::rustls_0_23::reexports::ServerConfig,
     ) -> io::Result<Self> {
-        self.listen_rustls_0_23_inner(lst, config)
+        self.listen_transport(None, lst, config)
     }
 
+    /// Binds to existing listener as a named listener for accepting incoming TLS connection
+    /// requests using Rustls v0.23.
+    ///
+    /// Connections to this listener are served by the app registered for `name` using
+    /// [`listener_app()`](Self::listener_app()). See [`listen()`](Self::listen()) for more details
+    /// on the `lst` argument.
+    ///
+    /// ALPN protocols "h2" and "http/1.1" are added to any configured ones.
     #[cfg(feature = "rustls-0_23")]
-    fn listen_rustls_0_23_inner(
-        mut self,
+    pub fn listen_named_rustls_0_23(
+        self,
+        name: impl Into<String>,
         lst: net::TcpListener,
         config: actix_tls::accept::rustls_0_23::reexports::ServerConfig,
     ) -> io::Result<Self> {
-        let factory = self.factory.clone();
-        let cfg = Arc::clone(&self.config);
-        let addr = lst.local_addr().unwrap();
-        self.sockets.push(Socket {
-            addr,
-            scheme: "https",
-        });
-
-        let on_connect_fn = self.on_connect_fn.clone();
-
-        self.builder =
-            self.builder
-                .listen(format!("actix-web-service-{}", addr), lst, move || {
-                    let c = cfg.lock().unwrap();
-                    let host = c.host.clone().unwrap_or_else(|| format!("{}", addr));
-
-                    let svc = HttpService::build()
-                        .keep_alive(c.keep_alive)
-                        .client_request_timeout(c.client_request_timeout)
-                        .client_disconnect_timeout(c.client_disconnect_timeout);
-
-                    let svc = if let Some(handler) = on_connect_fn.clone() {
-                        svc.on_connect_ext(move |io: &_, ext: _| (handler)(io as &dyn Any, ext))
-                    } else {
-                        svc
-                    };
-
-                    let fac = factory()
-                        .into_factory()
-                        .map_err(|err| err.into().error_response());
-
-                    let acceptor_config = match c.tls_handshake_timeout {
-                        Some(dur) => TlsAcceptorConfig::default().handshake_timeout(dur),
-                        None => TlsAcceptorConfig::default(),
-                    };
-
-                    svc.finish(map_config(fac, move |_| {
-                        AppConfig::new(true, host.clone(), addr)
-                    }))
-                    .rustls_0_23_with_config(config.clone(), acceptor_config)
-                })?;
-
-        Ok(self)
+        self.listen_transport(Some(name.into()), lst, config)
     }
 
     /// Binds to existing listener for accepting incoming TLS connection requests using OpenSSL.
@@ -870,75 +1079,167 @@ where
         lst: net::TcpListener,
         builder: SslAcceptorBuilder,
     ) -> io::Result<Self> {
-        self.listen_openssl_inner(lst, openssl_acceptor(builder)?)
+        self.listen_transport(None, lst, openssl_acceptor(builder)?)
     }
 
+    /// Binds to existing listener as a named listener for accepting incoming TLS connection
+    /// requests using OpenSSL.
+    ///
+    /// Connections to this listener are served by the app registered for `name` using
+    /// [`listener_app()`](Self::listener_app()). See [`listen()`](Self::listen()) for more details
+    /// on the `lst` argument.
+    ///
+    /// ALPN protocols "h2" and "http/1.1" are added to any configured ones.
     #[cfg(feature = "openssl")]
-    fn listen_openssl_inner(
-        mut self,
+    pub fn listen_named_openssl(
+        self,
+        name: impl Into<String>,
         lst: net::TcpListener,
-        acceptor: SslAcceptor,
+        builder: SslAcceptorBuilder,
     ) -> io::Result<Self> {
-        let factory = self.factory.clone();
+        self.listen_transport(Some(name.into()), lst, openssl_acceptor(builder)?)
+    }
+
+    /// Binds to existing listener, serving the app registered for `name` if given or else the
+    /// default app.
+    fn listen_transport<P: Transport>(
+        self,
+        name: Option<String>,
+        lst: net::TcpListener,
+        transport: P,
+    ) -> io::Result<Self> {
+        match name {
+            Some(name) => {
+                let app = self.named_app(name.clone());
+                self.listen_app(Some(&name), lst, transport, app)
+            }
+
+            None => {
+                let factory = self.factory.clone();
+                self.listen_app(None, lst, transport, move |_: &Config| {
+                    factory()
+                        .into_factory()
+                        .map_err(|err| err.into().error_response())
+                })
+            }
+        }
+    }
+
+    fn listen_app<P, A, T, B2>(
+        mut self,
+        name: Option<&str>,
+        lst: net::TcpListener,
+        transport: P,
+        app: A,
+    ) -> io::Result<Self>
+    where
+        P: Transport,
+        A: Fn(&Config) -> T + Send + Clone + 'static,
+        T: ServiceFactory<Request, Config = AppConfig> + 'static,
+        T::Future: 'static,
+        T::Error: Into<Response<BoxBody>> + 'static,
+        T::InitError: fmt::Debug,
+        T::Response: Into<Response<B2>> + 'static,
+        T::Service: 'static,
+        <T::Service as Service<Request>>::Future: 'static,
+        B2: MessageBody + 'static,
+    {
         let cfg = Arc::clone(&self.config);
         let addr = lst.local_addr().unwrap();
+
         self.sockets.push(Socket {
             addr,
-            scheme: "https",
+            scheme: if P::SECURE { "https" } else { "http" },
         });
 
         let on_connect_fn = self.on_connect_fn.clone();
 
-        self.builder =
-            self.builder
-                .listen(format!("actix-web-service-{}", addr), lst, move || {
-                    let c = cfg.lock().unwrap();
-                    let host = c.host.clone().unwrap_or_else(|| format!("{}", addr));
+        let loads = Arc::new(WorkerLoads::default());
+
+        self.builder = self
+            .builder
+            .listen(service_name(name, addr), lst, move || {
+                let cfg = cfg.lock().unwrap();
+                let host = cfg.host.clone().unwrap_or_else(|| format!("{}", addr));
+                let balancer = WorkerBalancer::new(&loads, cfg.worker_dispatch);
 
-                    let svc = HttpService::build()
-                        .keep_alive(c.keep_alive)
-                        .client_request_timeout(c.client_request_timeout)
-                        .client_disconnect_timeout(c.client_disconnect_timeout)
-                        .local_addr(addr);
+                let mut svc = HttpService::build()
+                    .keep_alive(cfg.keep_alive)
+                    .client_request_timeout(cfg.client_request_timeout)
+                    .client_disconnect_timeout(cfg.client_disconnect_timeout)
+                    .header_limits(cfg.header_limits.clone())
+                    .shutdown_signal(cfg.shutdown_signal())
+                    .in_flight_counter(balancer.in_flight())
+                    .local_addr(addr);
 
-                    let svc = if let Some(handler) = on_connect_fn.clone() {
-                        svc.on_connect_ext(move |io: &_, ext: _| (handler)(io as &dyn Any, ext))
-                    } else {
-                        svc
-                    };
+                if let Some(handler) = on_connect_fn.clone() {
+                    svc = svc.on_connect_ext(move |io: &_, ext: _| (handler)(io as &dyn Any, ext))
+                };
 
-                    let fac = factory()
-                        .into_factory()
-                        .map_err(|err| err.into().error_response());
-
-                    // false positive lint (?)
-                    #[allow(clippy::significant_drop_in_scrutinee)]
-                    let acceptor_config = match c.tls_handshake_timeout {
-                        Some(dur) => TlsAcceptorConfig::default().handshake_timeout(dur),
-                        None => TlsAcceptorConfig::default(),
-                    };
-
-                    svc.finish(map_config(fac, move |_| {
-                        AppConfig::new(true, host.clone(), addr)
-                    }))
-                    .openssl_with_config(acceptor.clone(), acceptor_config)
-                })?;
+                let fac = WarmUpFactory::new(&cfg.warm_up, app(&cfg));
+
+                let svc = cfg.catch_panics(svc).finish(map_config(fac, move |_| {
+                    AppConfig::new(P::SECURE, host.clone(), addr)
+                }));
+
+                balancer.wrap(transport.clone().finish(svc, cfg.tls_handshake_timeout))
+            })?;
 
         Ok(self)
     }
 
     /// Opens Unix Domain Socket (UDS) from `uds` path and binds server to created listener.
     #[cfg(unix)]
-    pub fn bind_uds<A>(mut self, uds_path: A) -> io::Result<Self>
+    pub fn bind_uds<A>(self, uds_path: A) -> io::Result<Self>
+    where
+        A: AsRef<std::path::Path>,
+    {
+        let factory = self.factory.clone();
+        self.bind_uds_app(None, uds_path, move |_: &Config| {
+            factory()
+                .into_factory()
+                .map_err(|err| err.into().error_response())
+        })
+    }
+
+    /// Opens Unix Domain Socket (UDS) from `uds` path and binds server to created named listener.
+    ///
+    /// Connections to this listener are served by the app registered for `name` using
+    /// [`listener_app()`](Self::listener_app()).
+    #[cfg(unix)]
+    pub fn bind_uds_named<A>(self, name: impl Into<String>, uds_path: A) -> io::Result<Self>
     where
         A: AsRef<std::path::Path>,
+    {
+        let name = name.into();
+        let app = self.named_app(name.clone());
+        self.bind_uds_app(Some(&name), uds_path, app)
+    }
+
+    #[cfg(unix)]
+    fn bind_uds_app<P, A, T, B2>(
+        mut self,
+        name: Option<&str>,
+        uds_path: P,
+        app: A,
+    ) -> io::Result<Self>
+    where
+        P: AsRef<std::path::Path>,
+        A: Fn(&Config) -> T + Send + Clone + 'static,
+        T: ServiceFactory<Request, Config = AppConfig> + 'static,
+        T::Future: 'static,
+        T::Error: Into<Response<BoxBody>> + 'static,
+        T::InitError: fmt::Debug,
+        T::Response: Into<Response<B2>> + 'static,
+        T::Service: 'static,
+        <T::Service as Service<Request>>::Future: 'static,
+        B2: MessageBody + 'static,
     {
         use actix_http::Protocol;
         use actix_rt::net::UnixStream;
-        use actix_service::{fn_service, ServiceFactoryExt as _};
+        use actix_service::fn_service;
 
         let cfg = Arc::clone(&self.config);
-        let factory = self.factory.clone();
         let socket_addr =
             net::SocketAddr::new(net::IpAddr::V4(net::Ipv4Addr::new(127, 0, 0, 1)), 8080);
 
@@ -947,30 +1248,30 @@ where
             addr: socket_addr,
         });
 
-        self.builder = self.builder.bind_uds(
-            format!("actix-web-service-{:?}", uds_path.as_ref()),
-            uds_path,
-            move || {
-                let c = cfg.lock().unwrap();
-                let config = AppConfig::new(
-                    false,
-                    c.host.clone().unwrap_or_else(|| format!("{}", socket_addr)),
-                    socket_addr,
-                );
-
-                let fac = factory()
-                    .into_factory()
-                    .map_err(|err| err.into().error_response());
-
-                fn_service(|io: UnixStream| async { Ok((io, Protocol::Http1, None)) }).and_then(
-                    HttpService::build()
-                        .keep_alive(c.keep_alive)
-                        .client_request_timeout(c.client_request_timeout)
-                        .client_disconnect_timeout(c.client_disconnect_timeout)
+        self.builder =
+            self.builder
+                .bind_uds(service_name(name, uds_path.as_ref()), uds_path, move || {
+                    let c = cfg.lock().unwrap();
+                    let config = AppConfig::new(
+                        false,
+                        c.host.clone().unwrap_or_else(|| format!("{}", socket_addr)),
+                        socket_addr,
+                    );
+
+                    let fac = WarmUpFactory::new(&c.warm_up, app(&c));
+
+                    fn_service(|io: UnixStream| async { Ok((io, Protocol::Http1, None)) }).and_then(
+                        c.catch_panics(
+                            HttpService::build()
+                                .keep_alive(c.keep_alive)
+                                .client_request_timeout(c.client_request_timeout)
+                                .client_disconnect_timeout(c.client_disconnect_timeout)
+                                .header_limits(c.header_limits.clone())
+                                .shutdown_signal(c.shutdown_signal()),
+                        )
                         .finish(map_config(fac, move |_| config.clone())),
-                )
-            },
-        )?;
+                    )
+                })?;
 
         Ok(self)
     }
@@ -1007,7 +1308,9 @@ where
                 let mut svc = HttpService::build()
                     .keep_alive(c.keep_alive)
                     .client_request_timeout(c.client_request_timeout)
-                    .client_disconnect_timeout(c.client_disconnect_timeout);
+                    .client_disconnect_timeout(c.client_disconnect_timeout)
+                    .header_limits(c.header_limits.clone())
+                    .shutdown_signal(c.shutdown_signal());
 
                 if let Some(handler) = on_connect_fn.clone() {
                     svc = svc.on_connect_ext(move |io: &_, ext: _| (handler)(io as &dyn Any, ext));
@@ -1016,8 +1319,10 @@ where
                 let fac = factory()
                     .into_factory()
                     .map_err(|err| err.into().error_response());
+                let fac = WarmUpFactory::new(&c.warm_up, fac);
 
-                svc.finish(map_config(fac, move |_| config.clone()))
+                c.catch_panics(svc)
+                    .finish(map_config(fac, move |_| config.clone()))
             })
         })?;
         Ok(self)
@@ -1052,6 +1357,256 @@ where
     }
 }
 
+/// Warm-up delay and hooks of a server.
+#[derive(Default)]
+struct WarmUp {
+    delay: Duration,
+    hooks: Vec<WarmUpHook>,
+    done: bool,
+}
+
+impl WarmUp {
+    /// Returns the future that waits for the delay and runs the hooks, unless it was already taken.
+    fn take(&mut self) -> Option<LocalBoxFuture<'static, ()>> {
+        if self.done || (self.delay.is_zero() && self.hooks.is_empty()) {
+            return None;
+        }
+
+        self.done = true;
+
+        let delay = self.delay;
+        let hooks = mem::take(&mut self.hooks);
+
+        Some(Box::pin(async move {
+            actix_rt::time::sleep(delay).await;
+
+            for hook in hooks {
+                hook().await;
+            }
+
+            log::debug!("warm-up complete");
+        }))
+    }
+}
+
+/// App factory that runs the server's warm-up before the first app is created.
+///
+/// Workers are started one after another and the accept loop only starts once all of them have
+/// created their services, so no connections are accepted until warm-up is complete.
+struct WarmUpFactory<T> {
+    warm_up: Arc<Mutex<WarmUp>>,
+    factory: Rc<T>,
+}
+
+impl<T> WarmUpFactory<T> {
+    fn new(warm_up: &Arc<Mutex<WarmUp>>, factory: T) -> Self {
+        Self {
+            warm_up: Arc::clone(warm_up),
+            factory: Rc::new(factory),
+        }
+    }
+}
+
+impl<T> ServiceFactory<Request> for WarmUpFactory<T>
+where
+    T: ServiceFactory<Request> + 'static,
+    T::Config: 'static,
+{
+    type Response = T::Response;
+    type Error = T::Error;
+    type Config = T::Config;
+    type Service = T::Service;
+    type InitError = T::InitError;
+    type Future = LocalBoxFuture<'static, Result<Self::Service, Self::InitError>>;
+
+    fn new_service(&self, cfg: Self::Config) -> Self::Future {
+        let warm_up = self.warm_up.lock().unwrap().take();
+        let factory = Rc::clone(&self.factory);
+
+        Box::pin(async move {
+            if let Some(warm_up) = warm_up {
+                warm_up.await;
+            }
+
+            factory.new_service(cfg).await
+        })
+    }
+}
+
+/// Erases the type of an app factory so apps with different middleware stacks can be served by
+/// named listeners.
+fn box_app_factory<I, S, B>(app: I) -> BoxAppFactory
+where
+    I: IntoServiceFactory<S, Request>,
+    S: ServiceFactory<Request, Config = AppConfig> + 'static,
+    S::Error: Into<Error> + 'static,
+    S::InitError: fmt::Debug,
+    S::Response: Into<Response<B>> + 'static,
+    <S::Service as Service<Request>>::Future: 'static,
+    S::Service: 'static,
+    B: MessageBody + 'static,
+{
+    boxed::factory(
+        app.into_factory()
+            .map(|res| res.into().map_into_boxed_body())
+            .map_err(|err| err.into().error_response().into())
+            .map_init_err(|err| log::error!("Can not construct app: {:?}", err)),
+    )
+}
+
+/// Protocol stack that connections accepted by a TCP listener are served with.
+trait Transport: Clone + Send + 'static {
+    /// IO stream type that the HTTP service handles.
+    type Io: 'static;
+
+    /// Whether connections are encrypted.
+    const SECURE: bool;
+
+    /// Creates the connection service factory of a worker from its HTTP service.
+    fn finish<S, B>(
+        self,
+        svc: HttpService<Self::Io, S, B>,
+        handshake_timeout: Option<Duration>,
+    ) -> impl ServiceFactory<TcpStream, Config = (), Response = (), InitError = ()> + 'static
+    where
+        S: ServiceFactory<Request, Config = ()> + 'static,
+        S::Future: 'static,
+        S::Error: Into<Response<BoxBody>> + 'static,
+        S::InitError: fmt::Debug,
+        S::Response: Into<Response<B>> + 'static,
+        <S::Service as Service<Request>>::Future: 'static,
+        B: MessageBody + 'static;
+}
+
+/// Plaintext HTTP/1.x connections.
+#[derive(Clone)]
+struct Tcp;
+
+impl Transport for Tcp {
+    type Io = TcpStream;
+
+    const SECURE: bool = false;
+
+    fn finish<S, B>(
+        self,
+        svc: HttpService<TcpStream, S, B>,
+        _: Option<Duration>,
+    ) -> impl ServiceFactory<TcpStream, Config = (), Response = (), InitError = ()> + 'static
+    where
+        S: ServiceFactory<Request, Config = ()> + 'static,
+        S::Future: 'static,
+        S::Error: Into<Response<BoxBody>> + 'static,
+        S::InitError: fmt::Debug,
+        S::Response: Into<Response<B>> + 'static,
+        <S::Service as Service<Request>>::Future: 'static,
+        B: MessageBody + 'static,
+    {
+        svc.tcp()
+    }
+}
+
+/// Plaintext HTTP/1.x or HTTP/2 connections.
+#[cfg(feature = "http2")]
+#[derive(Clone)]
+struct AutoH2c;
+
+#[cfg(feature = "http2")]
+impl Transport for AutoH2c {
+    type Io = TcpStream;
+
+    const SECURE: bool = false;
+
+    fn finish<S, B>(
+        self,
+        svc: HttpService<TcpStream, S, B>,
+        _: Option<Duration>,
+    ) -> impl ServiceFactory<TcpStream, Config = (), Response = (), InitError = ()> + 'static
+    where
+        S: ServiceFactory<Request, Config = ()> + 'static,
+        S::Future: 'static,
+        S::Error: Into<Response<BoxBody>> + 'static,
+        S::InitError: fmt::Debug,
+        S::Response: Into<Response<B>> + 'static,
+        <S::Service as Service<Request>>::Future: 'static,
+        B: MessageBody + 'static,
+    {
+        svc.tcp_auto_h2c()
+    }
+}
+
+/// Implements [`Transport`] for a TLS acceptor configuration.
+macro_rules! tls_transport {
+    ($feature:literal, $config:ty, $stream:ty, $finish:ident) => {
+        #[cfg(feature = $feature)]
+        impl Transport for $config {
+            type Io = $stream;
+
+            const SECURE: bool = true;
+
+            fn finish<S, B>(
+                self,
+                svc: HttpService<$stream, S, B>,
+                handshake_timeout: Option<Duration>,
+            ) -> impl ServiceFactory<TcpStream, Config = (), Response = (), InitError = ()> + 'static
+            where
+                S: ServiceFactory<Request, Config = ()> + 'static,
+                S::Future: 'static,
+                S::Error: Into<Response<BoxBody>> + 'static,
+                S::InitError: fmt::Debug,
+                S::Response: Into<Response<B>> + 'static,
+                <S::Service as Service<Request>>::Future: 'static,
+                B: MessageBody + 'static,
+            {
+                let acceptor_config = match handshake_timeout {
+                    Some(dur) => TlsAcceptorConfig::default().handshake_timeout(dur),
+                    None => TlsAcceptorConfig::default(),
+                };
+
+                svc.$finish(self, acceptor_config)
+            }
+        }
+    };
+}
+
+tls_transport!(
+    "rustls-0_20",
+    actix_tls::accept::rustls_0_20::reexports::ServerConfig,
+    actix_tls::accept::rustls_0_20::TlsStream<TcpStream>,
+    rustls_with_config
+);
+tls_transport!(
+    "rustls-0_21",
+    actix_tls::accept::rustls_0_21::reexports::ServerConfig,
+    actix_tls::accept::rustls_0_21::TlsStream<TcpStream>,
+    rustls_021_with_config
+);
+tls_transport!(
+    "rustls-0_22",
+    actix_tls::accept::rustls_0_22::reexports::ServerConfig,
+    actix_tls::accept::rustls_0_22::TlsStream<TcpStream>,
+    rustls_0_22_with_config
+);
+tls_transport!(
+    "rustls-0_23",
+    actix_tls::accept::rustls_0_23::reexports::ServerConfig,
+    actix_tls::accept::rustls_0_23::TlsStream<TcpStream>,
+    rustls_0_23_with_config
+);
+tls_transport!(
+    "openssl",
+    SslAcceptor,
+    actix_tls::accept::openssl::TlsStream<TcpStream>,
+    openssl_with_config
+);
+
+/// Returns the name under which a listener is registered with the server builder.
+fn service_name(name: Option<&str>, addr: impl fmt::Debug) -> String {
+    match name {
+        Some(name) => format!("actix-web-service-{name}-{addr:?}"),
+        None => format!("actix-web-service-{addr:?}"),
+    }
+}
+
 /// Bind TCP listeners to socket addresses resolved from `addrs` with options.
 fn bind_addrs(addrs: impl net::ToSocketAddrs, backlog: u32) -> io::Result<Vec<net::TcpListener>> {
     let mut err = None;
@@ -1,14 +1,16 @@
 use std::{
     cell::{Ref, RefMut},
-    fmt, net,
+    fmt,
+    future::Future,
+    net,
     rc::Rc,
 };
 
 use actix_http::{
     body::{BoxBody, EitherBody, MessageBody},
-    header::HeaderMap,
-    BoxedPayloadStream, Extensions, HttpMessage, Method, Payload, RequestHead, Response,
-    ResponseHead, StatusCode, Uri, Version,
+    header::{Header, HeaderMap},
+    BoxedPayloadStream, Extensions, FinishStatus, HttpMessage, Method, Payload, RequestHead,
+    Response, ResponseHead, StatusCode, Uri, Version,
 };
 use actix_router::{IntoPatterns, Path, Patterns, Resource, ResourceDef, Url};
 use actix_service::{
@@ -23,6 +25,7 @@ use crate::{
     dev::ensure_leading_slash,
     guard::{Guard, GuardContext},
     info::ConnectionInfo,
+    request::HeaderCache,
     rmap::ResourceMap,
     Error, FromRequest, HttpRequest, HttpResponse,
 };
@@ -172,8 +175,11 @@ impl ServiceRequest {
     }
 
     /// Returns a mutable reference to the request head.
+    ///
+    /// Clears typed headers cached by [`cached_header()`](Self::cached_header()).
     #[inline]
     pub fn head_mut(&mut self) -> &mut RequestHead {
+        self.req.extensions_mut().remove::<HeaderCache>();
         self.req.head_mut()
     }
 
@@ -284,6 +290,25 @@ impl ServiceRequest {
         None
     }
 
+    /// Counterpart to [`HttpRequest::cached_header`].
+    #[inline]
+    pub fn cached_header<H>(&self) -> Option<H>
+    where
+        H: Header + Clone + 'static,
+    {
+        self.req.cached_header()
+    }
+
+    /// Counterpart to [`HttpRequest::on_finish`].
+    #[inline]
+    pub fn on_finish<F, Fut>(&self, f: F) -> bool
+    where
+        F: FnOnce(FinishStatus) -> Fut + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        self.req.on_finish(f)
+    }
+
     /// Counterpart to [`HttpRequest::conn_data`].
     #[inline]
     pub fn conn_data<T: 'static>(&self) -> Option<&T> {
@@ -0,0 +1,479 @@
+//! Multi-tenant request handling.
+//!
+//! Apps that serve several tenants (e.g., customers or organizations) from the same routes register
+//! a [`TenantConfig`] as app data. Its [`TenantResolver`] determines which tenant each request is
+//! addressed to; [`BySubdomain`], [`ByHeader`], and [`ByPathPrefix`] cover the common schemes.
+//!
+//! Handlers receive the resolved tenant through the [`Tenant`] extractor, and state registered for
+//! that tenant through the [`TenantData`] extractor. State is only ever looked up for the tenant the
+//! request was resolved to, so handlers cannot reach state registered for other tenants. Requests
+//! for tenants that are not registered are rejected with `404 Not Found`.
+//!
+//! # Examples
+//! ```
+//! use actix_web::{
+//!     get,
+//!     tenant::{BySubdomain, Tenant, TenantConfig, TenantData},
+//!     App,
+//! };
+//!
+//! struct Settings {
+//!     theme: &'static str,
+//! }
+//!
+//! #[get("/")]
+//! async fn index(tenant: Tenant, settings: TenantData<Settings>) -> String {
+//!     format!("{} uses the {} theme", tenant.id(), settings.theme)
+//! }
+//!
+//! let app = App::new()
+//!     .app_data(
+//!         TenantConfig::new(BySubdomain::new("example.com"))
+//!             .data("acme", Settings { theme: "dark" })
+//!             .data("globex", Settings { theme: "light" }),
+//!     )
+//!     .service(index);
+//! ```
+
+use std::{collections::HashMap, fmt, ops::Deref, rc::Rc};
+
+use actix_utils::future::{ready, Ready};
+use derive_more::derive::{Display, Error};
+
+use crate::{
+    dev::{Extensions, Payload},
+    error::as_std_error,
+    http::{header::HeaderName, StatusCode},
+    web::Data,
+    FromRequest, HttpMessage as _, HttpRequest, ResponseError,
+};
+
+/// Determines which tenant a request is addressed to.
+///
+/// Implemented for closures with the same signature as [`resolve()`](Self::resolve()).
+pub trait TenantResolver: 'static {
+    /// Returns the ID of the tenant that `req` is addressed to, or `None` if it cannot be
+    /// determined.
+    fn resolve(&self, req: &HttpRequest) -> Option<String>;
+}
+
+impl<F> TenantResolver for F
+where
+    F: Fn(&HttpRequest) -> Option<String> + 'static,
+{
+    fn resolve(&self, req: &HttpRequest) -> Option<String> {
+        (self)(req)
+    }
+}
+
+/// Resolves tenants from the subdomain of a base domain, e.g. `acme` from `acme.example.com`.
+///
+/// The host is read from [`ConnectionInfo::host()`](crate::dev::ConnectionInfo::host()) and
+/// compared case-insensitively. Only a single label in front of the base domain is accepted.
+#[derive(Debug, Clone)]
+pub struct BySubdomain {
+    base: String,
+}
+
+impl BySubdomain {
+    /// Constructs a resolver for subdomains of `base`, e.g. `example.com`.
+    pub fn new(base: impl Into<String>) -> Self {
+        let base = base.into().trim_start_matches('.').to_ascii_lowercase();
+        Self { base }
+    }
+}
+
+impl TenantResolver for BySubdomain {
+    fn resolve(&self, req: &HttpRequest) -> Option<String> {
+        let info = req.connection_info();
+
+        // strip the port, if any
+        let host = match info.host().rsplit_once(':') {
+            Some((host, port)) if port.bytes().all(|b| b.is_ascii_digit()) => host,
+            _ => info.host(),
+        };
+
+        let host = host.to_ascii_lowercase();
+        let subdomain = host.strip_suffix(&self.base)?.strip_suffix('.')?;
+
+        (!subdomain.is_empty() && !subdomain.contains('.')).then(|| subdomain.to_owned())
+    }
+}
+
+/// Resolves tenants from the value of a request header, e.g. `X-Tenant-Id`.
+#[derive(Debug, Clone)]
+pub struct ByHeader {
+    name: HeaderName,
+}
+
+impl ByHeader {
+    /// Constructs a resolver that reads the tenant ID from the header `name`.
+    pub fn new(name: HeaderName) -> Self {
+        Self { name }
+    }
+}
+
+impl TenantResolver for ByHeader {
+    fn resolve(&self, req: &HttpRequest) -> Option<String> {
+        let id = req.headers().get(&self.name)?.to_str().ok()?.trim();
+        (!id.is_empty()).then(|| id.to_owned())
+    }
+}
+
+/// Resolves tenants from the first segment of the request path, e.g. `acme` from `/acme/orders`.
+///
+/// The segment is used as it appears in the path, without percent-decoding. It is not removed from
+/// the path; routes should account for it, e.g. by registering them in a `web::scope("/{tenant}")`.
+#[derive(Debug, Clone, Default)]
+pub struct ByPathPrefix;
+
+impl TenantResolver for ByPathPrefix {
+    fn resolve(&self, req: &HttpRequest) -> Option<String> {
+        let id = req.path().trim_start_matches('/').split('/').next()?;
+        (!id.is_empty()).then(|| id.to_owned())
+    }
+}
+
+/// Errors that can occur when extracting a [`Tenant`] or [`TenantData`].
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum TenantError {
+    /// No [`TenantConfig`] was registered for the resource.
+    #[display("Tenant configuration is missing")]
+    MissingConfig,
+
+    /// The tenant could not be determined from the request.
+    #[display("Tenant could not be determined from the request")]
+    Unresolved,
+
+    /// The request was addressed to a tenant that is not registered.
+    #[display("Unknown tenant: {_0}")]
+    Unknown(#[error(not(source))] String),
+
+    /// No data of the requested type was registered for the tenant.
+    #[display("Tenant data is missing")]
+    MissingData,
+}
+
+impl ResponseError for TenantError {
+    as_std_error!();
+
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::MissingConfig | Self::MissingData => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Unresolved => StatusCode::BAD_REQUEST,
+            Self::Unknown(_) => StatusCode::NOT_FOUND,
+        }
+    }
+}
+
+/// Tenant configuration, registered as app data.
+///
+/// See the [module docs](self) for an example.
+#[derive(Clone)]
+pub struct TenantConfig {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    resolver: Box<dyn TenantResolver>,
+    tenants: HashMap<String, Extensions>,
+    allow_unregistered: bool,
+}
+
+impl TenantConfig {
+    /// Constructs a config that resolves tenants using `resolver`.
+    pub fn new(resolver: impl TenantResolver) -> Self {
+        Self {
+            inner: Rc::new(Inner {
+                resolver: Box::new(resolver),
+                tenants: HashMap::new(),
+                allow_unregistered: false,
+            }),
+        }
+    }
+
+    /// Registers a tenant without any data.
+    pub fn tenant(mut self, id: impl Into<String>) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .tenants
+            .entry(id.into())
+            .or_default();
+        self
+    }
+
+    /// Registers `data` for a tenant, registering the tenant if necessary.
+    ///
+    /// Handlers of requests for this tenant can extract it as [`TenantData<T>`]. Registering data of
+    /// the same type twice for a tenant replaces the previous value.
+    pub fn data<T: 'static>(mut self, id: impl Into<String>, data: T) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .tenants
+            .entry(id.into())
+            .or_default()
+            .insert(Data::new(data));
+        self
+    }
+
+    /// Accepts requests for tenants that are not registered, instead of rejecting them with
+    /// `404 Not Found`. Such tenants have no data.
+    pub fn allow_unregistered(mut self) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .allow_unregistered = true;
+        self
+    }
+}
+
+impl fmt::Debug for TenantConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TenantConfig")
+            .field("tenants", &self.inner.tenants.keys())
+            .field("allow_unregistered", &self.inner.allow_unregistered)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Extractor for the tenant a request is addressed to.
+///
+/// The tenant is resolved using the registered [`TenantConfig`] once per request.
+///
+/// See the [module docs](self) for an example.
+#[derive(Clone)]
+pub struct Tenant {
+    id: Rc<str>,
+    config: Rc<Inner>,
+}
+
+impl Tenant {
+    /// Returns the tenant's ID.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the data of type `T` registered for this tenant, if any.
+    pub fn data<T: 'static>(&self) -> Option<Data<T>> {
+        self.config
+            .tenants
+            .get(&*self.id)?
+            .get::<Data<T>>()
+            .cloned()
+    }
+
+    fn resolve(req: &HttpRequest) -> Result<Self, TenantError> {
+        if let Some(tenant) = req.extensions().get::<Self>() {
+            return Ok(tenant.clone());
+        }
+
+        let Some(config) = req.app_data::<TenantConfig>() else {
+            log::error!(
+                "Failed to resolve tenant for {:?}: no `TenantConfig` registered",
+                req.match_name().unwrap_or(req.path())
+            );
+            return Err(TenantError::MissingConfig);
+        };
+
+        let config = Rc::clone(&config.inner);
+        let id = config
+            .resolver
+            .resolve(req)
+            .ok_or(TenantError::Unresolved)?;
+
+        if !config.allow_unregistered && !config.tenants.contains_key(&id) {
+            return Err(TenantError::Unknown(id));
+        }
+
+        let tenant = Self {
+            id: id.into(),
+            config,
+        };
+
+        req.extensions_mut().insert(tenant.clone());
+        Ok(tenant)
+    }
+}
+
+impl fmt::Debug for Tenant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Tenant").field(&self.id).finish()
+    }
+}
+
+impl FromRequest for Tenant {
+    type Error = TenantError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Self::resolve(req))
+    }
+}
+
+/// Extractor for data registered for the request's tenant using [`TenantConfig::data()`].
+///
+/// Fails with a `500 Internal Server Error` if no data of type `T` was registered for the tenant.
+///
+/// See the [module docs](self) for an example.
+#[derive(Debug, Clone)]
+pub struct TenantData<T: ?Sized>(Data<T>);
+
+impl<T: ?Sized> TenantData<T> {
+    /// Returns the inner `Data`.
+    pub fn into_inner(self) -> Data<T> {
+        self.0
+    }
+}
+
+impl<T: ?Sized> Deref for TenantData<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.0.get_ref()
+    }
+}
+
+impl<T: 'static> FromRequest for TenantData<T> {
+    type Error = TenantError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Tenant::resolve(req).and_then(|tenant| {
+            tenant.data::<T>().map(TenantData).ok_or_else(|| {
+                log::debug!(
+                    "Failed to extract `TenantData<{}>` for tenant {:?}",
+                    std::any::type_name::<T>(),
+                    tenant.id()
+                );
+                TenantError::MissingData
+            })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        http::header,
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App,
+    };
+
+    fn resolve(resolver: &impl TenantResolver, req: TestRequest) -> Option<String> {
+        resolver.resolve(&req.to_http_request())
+    }
+
+    #[test]
+    fn resolvers() {
+        let subdomain = BySubdomain::new("example.com");
+        let host = |host| TestRequest::default().insert_header((header::HOST, host));
+        assert_eq!(
+            resolve(&subdomain, host("acme.example.com")).as_deref(),
+            Some("acme")
+        );
+        assert_eq!(
+            resolve(&subdomain, host("Acme.Example.com:8080")).as_deref(),
+            Some("acme")
+        );
+        assert_eq!(resolve(&subdomain, host("example.com")), None);
+        assert_eq!(resolve(&subdomain, host("a.b.example.com")), None);
+        assert_eq!(resolve(&subdomain, host("acmeexample.com")), None);
+
+        let by_header = ByHeader::new(HeaderName::from_static("x-tenant-id"));
+        let req = TestRequest::default().insert_header(("x-tenant-id", " acme "));
+        assert_eq!(resolve(&by_header, req).as_deref(), Some("acme"));
+        assert_eq!(resolve(&by_header, TestRequest::default()), None);
+
+        let req = TestRequest::with_uri("/acme/orders");
+        assert_eq!(resolve(&ByPathPrefix, req).as_deref(), Some("acme"));
+        assert_eq!(resolve(&ByPathPrefix, TestRequest::with_uri("/")), None);
+
+        let closure = |req: &HttpRequest| req.query_string().strip_prefix("t=").map(str::to_owned);
+        let req = TestRequest::with_uri("/?t=acme");
+        assert_eq!(resolve(&closure, req).as_deref(), Some("acme"));
+    }
+
+    #[actix_rt::test]
+    async fn tenant_isolation() {
+        struct Secret {
+            value: &'static str,
+        }
+
+        let app = init_service(
+            App::new()
+                .app_data(
+                    TenantConfig::new(ByHeader::new(HeaderName::from_static("x-tenant-id")))
+                        .data(
+                            "acme",
+                            Secret {
+                                value: "acme secret",
+                            },
+                        )
+                        .tenant("globex"),
+                )
+                .default_service(web::to(
+                    |tenant: Tenant, secret: Option<TenantData<Secret>>| async move {
+                        let secret = secret.map_or("none", |secret| secret.value);
+                        format!("{}: {}", tenant.id(), secret)
+                    },
+                )),
+        )
+        .await;
+
+        for (tenant, status, body) in [
+            (Some("acme"), StatusCode::OK, "acme: acme secret"),
+            (Some("globex"), StatusCode::OK, "globex: none"),
+            (
+                Some("initech"),
+                StatusCode::NOT_FOUND,
+                "Unknown tenant: initech",
+            ),
+            (
+                None,
+                StatusCode::BAD_REQUEST,
+                "Tenant could not be determined from the request",
+            ),
+        ] {
+            let mut req = TestRequest::default();
+            if let Some(tenant) = tenant {
+                req = req.insert_header(("x-tenant-id", tenant));
+            }
+
+            let res = call_service(&app, req.to_request()).await;
+            assert_eq!(res.status(), status, "{tenant:?}");
+            assert_eq!(read_body(res).await, body, "{tenant:?}");
+        }
+    }
+
+    #[actix_rt::test]
+    async fn unregistered_tenants() {
+        let app = init_service(
+            App::new()
+                .app_data(TenantConfig::new(ByPathPrefix).allow_unregistered())
+                .route(
+                    "/{tenant}",
+                    web::get().to(|tenant: Tenant| async move {
+                        assert!(tenant.data::<u32>().is_none());
+                        tenant.id().to_owned()
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/initech").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(read_body(res).await, "initech");
+    }
+
+    #[actix_rt::test]
+    async fn missing_config() {
+        let req = TestRequest::default().to_http_request();
+        let err = Tenant::extract(&req).await.unwrap_err();
+        assert!(matches!(err, TenantError::MissingConfig));
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
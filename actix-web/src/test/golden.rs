@@ -0,0 +1,373 @@
+use std::{
+    env,
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use regex_lite::Regex;
+
+use crate::{
+    body::{self, MessageBody},
+    http::header::{self, HeaderName},
+    service::ServiceResponse,
+};
+
+/// Environment variable that, when set, causes snapshots to be (re)written instead of compared.
+const UPDATE_ENV_VAR: &str = "ACTIX_UPDATE_GOLDEN";
+
+/// Replacement text for redacted values.
+const REDACTED: &str = "[redacted]";
+
+/// Compares a response against a stored golden snapshot using the default [`Golden`] settings.
+///
+/// See [`Golden::assert`] for details.
+///
+/// # Examples
+/// ```no_run
+/// use actix_web::{test, web, App};
+///
+/// #[actix_web::test]
+/// async fn test_index() {
+///     let app = test::init_service(App::new().route("/", web::get().to(|| async { "Hello!" }))).await;
+///
+///     let req = test::TestRequest::default().to_request();
+///     let res = test::call_service(&app, req).await;
+///
+///     // compares against `tests/snapshots/index.snap`
+///     test::golden("index", res).await;
+/// }
+/// ```
+pub async fn golden<B>(name: &str, res: ServiceResponse<B>)
+where
+    B: MessageBody,
+{
+    Golden::new().assert(name, res).await
+}
+
+/// Golden (a.k.a. snapshot or approval) testing for responses.
+///
+/// Responses are serialized to a canonical text format containing the status line, headers sorted
+/// by name, and the body. The serialized response is then compared against a snapshot file stored
+/// in the snapshot directory. Values that legitimately change between test runs (e.g., dates or
+/// generated IDs) can be redacted so that snapshots are stable.
+///
+/// Snapshots are written, instead of compared, when the `ACTIX_UPDATE_GOLDEN` environment variable
+/// is set. Review changes to snapshot files like any other code change.
+///
+/// By default:
+/// - snapshots are stored in the `tests/snapshots` directory of the crate being tested;
+/// - all headers are included;
+/// - the `Date` header is redacted.
+///
+/// # Examples
+/// ```no_run
+/// use actix_web::{http::header, test, web, App, HttpResponse};
+///
+/// #[actix_web::test]
+/// async fn test_user() {
+///     let app = test::init_service(App::new().route(
+///         "/user",
+///         web::get().to(|| async {
+///             HttpResponse::Ok()
+///                 .insert_header(("x-request-id", "7f9c24e5"))
+///                 .body(r#"{"name":"alice","created":"2024-03-01T12:00:00Z"}"#)
+///         }),
+///     ))
+///     .await;
+///
+///     let req = test::TestRequest::with_uri("/user").to_request();
+///     let res = test::call_service(&app, req).await;
+///
+///     test::Golden::new()
+///         .headers([header::CONTENT_TYPE, header::HeaderName::from_static("x-request-id")])
+///         .redact_header(header::HeaderName::from_static("x-request-id"))
+///         .redact_body(r"\d{4}-\d{2}-\d{2}T[\d:]+Z")
+///         .assert("user", res)
+///         .await;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Golden {
+    dir: PathBuf,
+    headers: Option<Vec<HeaderName>>,
+    redacted_headers: Vec<HeaderName>,
+    body_redactions: Vec<Regex>,
+    update: bool,
+}
+
+impl Golden {
+    /// Constructs new golden test settings with defaults.
+    pub fn new() -> Self {
+        let manifest_dir = env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default();
+
+        Self {
+            dir: Path::new(&manifest_dir).join("tests").join("snapshots"),
+            headers: None,
+            redacted_headers: vec![header::DATE],
+            body_redactions: Vec::new(),
+            update: env::var_os(UPDATE_ENV_VAR).is_some(),
+        }
+    }
+
+    /// Sets directory that snapshots are stored in.
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = dir.into();
+        self
+    }
+
+    /// Only includes the given headers in snapshots.
+    ///
+    /// By default, all headers are included.
+    pub fn headers(mut self, names: impl IntoIterator<Item = HeaderName>) -> Self {
+        self.headers = Some(names.into_iter().collect());
+        self
+    }
+
+    /// Replaces values of the given header with `[redacted]` in snapshots.
+    pub fn redact_header(mut self, name: HeaderName) -> Self {
+        self.redacted_headers.push(name);
+        self
+    }
+
+    /// Replaces all matches of a regular expression in text bodies with `[redacted]`.
+    ///
+    /// # Panics
+    /// Panics if `pattern` is not a valid regular expression.
+    pub fn redact_body(mut self, pattern: &str) -> Self {
+        let re = Regex::new(pattern).expect("invalid body redaction pattern");
+        self.body_redactions.push(re);
+        self
+    }
+
+    /// Serializes response to the canonical snapshot format, applying redaction rules.
+    ///
+    /// Bodies that are not valid UTF-8 are represented by their length.
+    ///
+    /// # Panics
+    /// Panics if reading the response body fails.
+    pub async fn snapshot<B>(&self, res: ServiceResponse<B>) -> String
+    where
+        B: MessageBody,
+    {
+        let mut snapshot = String::new();
+
+        let status = res.status();
+        let _ = writeln!(snapshot, "{status}");
+
+        let mut headers = res
+            .headers()
+            .iter()
+            .filter(|(name, _)| match self.headers {
+                Some(ref names) => names.contains(name),
+                None => true,
+            })
+            .collect::<Vec<_>>();
+
+        // stable sort keeps multiple values of the same header in order
+        headers.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+        for (name, value) in headers {
+            if self.redacted_headers.contains(name) {
+                let _ = writeln!(snapshot, "{name}: {REDACTED}");
+            } else {
+                let value = String::from_utf8_lossy(value.as_bytes());
+                let _ = writeln!(snapshot, "{name}: {value}");
+            }
+        }
+
+        snapshot.push('\n');
+
+        let body = body::to_bytes(res.into_body())
+            .await
+            .map_err(Into::<Box<dyn std::error::Error>>::into)
+            .expect("error reading golden test response body");
+
+        match std::str::from_utf8(&body) {
+            Ok(body) => {
+                let mut body = body.replace("\r\n", "\n");
+
+                for re in &self.body_redactions {
+                    body = re.replace_all(&body, REDACTED).into_owned();
+                }
+
+                snapshot.push_str(&body);
+            }
+
+            Err(_) => {
+                let _ = write!(snapshot, "[binary body: {} bytes]", body.len());
+            }
+        }
+
+        if !snapshot.ends_with('\n') {
+            snapshot.push('\n');
+        }
+
+        snapshot
+    }
+
+    /// Compares response against the snapshot named `name`.
+    ///
+    /// The snapshot is stored in `{dir}/{name}.snap`. If the `ACTIX_UPDATE_GOLDEN` environment
+    /// variable is set, the snapshot file is (re)written instead.
+    ///
+    /// # Panics
+    /// Panics if the response does not match the snapshot, if the snapshot does not exist, or if
+    /// the snapshot can not be read or written.
+    pub async fn assert<B>(&self, name: &str, res: ServiceResponse<B>)
+    where
+        B: MessageBody,
+    {
+        let actual = self.snapshot(res).await;
+        let path = self.dir.join(format!("{name}.snap"));
+
+        if self.update {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).expect("failed to create golden snapshot directory");
+            }
+
+            fs::write(&path, actual).expect("failed to write golden snapshot");
+            return;
+        }
+
+        let expected = match fs::read_to_string(&path) {
+            Ok(expected) => expected.replace("\r\n", "\n"),
+
+            Err(err) if err.kind() == io::ErrorKind::NotFound => panic!(
+                "golden snapshot {} does not exist; set {UPDATE_ENV_VAR}=1 to create it\n\n{actual}",
+                path.display(),
+            ),
+
+            Err(err) => panic!("failed to read golden snapshot {}: {err}", path.display()),
+        };
+
+        if expected != actual {
+            panic!(
+                "response does not match golden snapshot {}; set {UPDATE_ENV_VAR}=1 to update it\n\
+                 (- snapshot, + actual)\n\n{}",
+                path.display(),
+                diff_lines(&expected, &actual),
+            );
+        }
+    }
+}
+
+impl Default for Golden {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Renders a line-by-line comparison of two snapshots.
+fn diff_lines(expected: &str, actual: &str) -> String {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let actual = actual.lines().collect::<Vec<_>>();
+
+    let mut diff = String::new();
+
+    for idx in 0..expected.len().max(actual.len()) {
+        match (expected.get(idx), actual.get(idx)) {
+            (Some(exp), Some(act)) if exp == act => {
+                let _ = writeln!(diff, "  {exp}");
+            }
+            (exp, act) => {
+                if let Some(exp) = exp {
+                    let _ = writeln!(diff, "- {exp}");
+                }
+                if let Some(act) = act {
+                    let _ = writeln!(diff, "+ {act}");
+                }
+            }
+        }
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test::{call_service, init_service, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    async fn handler() -> HttpResponse {
+        HttpResponse::Ok()
+            .content_type("text/plain")
+            .insert_header(("x-request-id", "abc123"))
+            .insert_header(("x-b", "2"))
+            .append_header(("x-a", "1"))
+            .body("id=42 at 2024-03-01\r\nok")
+    }
+
+    async fn response() -> ServiceResponse {
+        let app = init_service(App::new().route("/", web::to(handler))).await;
+
+        call_service(&app, TestRequest::default().to_request()).await
+    }
+
+    #[actix_rt::test]
+    async fn canonical_snapshot() {
+        let golden = Golden::new()
+            .redact_header(HeaderName::from_static("x-request-id"))
+            .redact_body(r"\d{4}-\d{2}-\d{2}");
+
+        assert_eq!(
+            golden.snapshot(response().await).await,
+            "200 OK\n\
+             content-type: text/plain\n\
+             x-a: 1\n\
+             x-b: 2\n\
+             x-request-id: [redacted]\n\
+             \n\
+             id=42 at [redacted]\n\
+             ok\n"
+        );
+
+        let golden = Golden::new().headers([HeaderName::from_static("x-a")]);
+        assert_eq!(
+            golden.snapshot(response().await).await,
+            "200 OK\nx-a: 1\n\nid=42 at 2024-03-01\nok\n"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn write_then_compare() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut golden = Golden::new().dir(dir.path());
+        golden.update = true;
+        golden.assert("nested/index", response().await).await;
+        assert!(dir.path().join("nested/index.snap").exists());
+
+        golden.update = false;
+        golden.assert("nested/index", response().await).await;
+    }
+
+    #[actix_rt::test]
+    #[should_panic(expected = "response does not match golden snapshot")]
+    async fn mismatch_panics() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("index.snap"), "200 OK\n\nother\n").unwrap();
+
+        let mut golden = Golden::new().dir(dir.path()).headers([]);
+        golden.update = false;
+        golden.assert("index", response().await).await;
+    }
+
+    #[actix_rt::test]
+    #[should_panic(expected = "does not exist")]
+    async fn missing_snapshot_panics() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut golden = Golden::new().dir(dir.path());
+        golden.update = false;
+        golden.assert("missing", response().await).await;
+    }
+
+    #[test]
+    fn diff_marks_changed_lines() {
+        assert_eq!(diff_lines("a\nb\n", "a\nc\nd\n"), "  a\n- b\n+ c\n+ d\n");
+    }
+}
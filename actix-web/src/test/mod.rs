@@ -20,11 +20,16 @@
 //! - [`try_read_body`]
 //! - [`read_body_json`]
 //! - [`try_read_body_json`]
+//!
+//! # Golden Testing
+//! - [`golden`]
+//! - [`Golden`]
 
 // TODO: more docs on generally how testing works with these parts
 
 pub use actix_http::test::TestBuffer;
 
+mod golden;
 mod test_request;
 mod test_services;
 mod test_utils;
@@ -36,6 +41,7 @@ pub(crate) use self::test_utils::try_init_service;
 #[allow(deprecated)]
 pub use self::test_utils::{read_response, read_response_json};
 pub use self::{
+    golden::{golden, Golden},
     test_request::TestRequest,
     test_utils::{
         call_and_read_body, call_and_read_body_json, call_service, init_service, read_body,
@@ -1,4 +1,4 @@
-use std::{borrow::Cow, net::SocketAddr, rc::Rc};
+use std::{borrow::Cow, cell::RefCell, net::SocketAddr, rc::Rc};
 
 use actix_http::{test::TestRequest as HttpTestRequest, Request};
 use serde::Serialize;
@@ -14,11 +14,12 @@ use crate::{
         header::{ContentType, TryIntoHeaderPair},
         Method, Uri, Version,
     },
+    request_rng::RequestRng,
     rmap::ResourceMap,
     service::{ServiceRequest, ServiceResponse},
     test,
     web::Bytes,
-    HttpRequest, HttpResponse,
+    HttpMessage as _, HttpRequest, HttpResponse,
 };
 
 /// Test `Request` builder.
@@ -65,6 +66,7 @@ pub struct TestRequest {
     path: Path<Url>,
     peer_addr: Option<SocketAddr>,
     app_data: Extensions,
+    rng_seed: Option<u64>,
     #[cfg(feature = "cookies")]
     cookies: CookieJar,
 }
@@ -78,6 +80,7 @@ impl Default for TestRequest {
             path: Path::new(Url::new(Uri::default())),
             peer_addr: None,
             app_data: Extensions::new(),
+            rng_seed: None,
             #[cfg(feature = "cookies")]
             cookies: CookieJar::new(),
         }
@@ -222,6 +225,15 @@ impl TestRequest {
         self
     }
 
+    /// Seeds the request's [`RequestRng`](crate::web::RequestRng).
+    ///
+    /// Handlers extracting a `RequestRng` from this request will observe a deterministic sequence
+    /// of values.
+    pub fn rng_seed(mut self, seed: u64) -> Self {
+        self.rng_seed = Some(seed);
+        self
+    }
+
     /// Sets resource map.
     #[cfg(test)]
     pub(crate) fn rmap(mut self, rmap: ResourceMap) -> Self {
@@ -237,6 +249,10 @@ impl TestRequest {
         #[allow(unused_mut)]
         let mut req = self.req.finish();
 
+        if let Some(seed) = self.rng_seed {
+            req.extensions_mut().insert(RequestRng::from_seed(seed));
+        }
+
         #[cfg(feature = "cookies")]
         {
             use actix_http::header::{HeaderValue, COOKIE};
@@ -267,7 +283,9 @@ impl TestRequest {
 
     /// Finalizes request creation and returns `ServiceRequest` instance.
     pub fn to_srv_request(mut self) -> ServiceRequest {
-        let (mut head, payload) = self.finish().into_parts();
+        let mut req = self.finish();
+        let extensions = Rc::new(RefCell::new(req.take_req_data()));
+        let (mut head, payload) = req.into_parts();
         head.peer_addr = self.peer_addr;
         self.path.get_mut().update(&head.uri);
 
@@ -280,7 +298,7 @@ impl TestRequest {
                 app_state,
                 Rc::new(self.app_data),
                 None,
-                Default::default(),
+                extensions,
             ),
             payload,
         )
@@ -293,7 +311,9 @@ impl TestRequest {
 
     /// Finalizes request creation and returns `HttpRequest` instance.
     pub fn to_http_request(mut self) -> HttpRequest {
-        let (mut head, _) = self.finish().into_parts();
+        let mut req = self.finish();
+        let extensions = Rc::new(RefCell::new(req.take_req_data()));
+        let (mut head, _) = req.into_parts();
         head.peer_addr = self.peer_addr;
         self.path.get_mut().update(&head.uri);
 
@@ -305,13 +325,15 @@ impl TestRequest {
             app_state,
             Rc::new(self.app_data),
             None,
-            Default::default(),
+            extensions,
         )
     }
 
     /// Finalizes request creation and returns `HttpRequest` and `Payload` pair.
     pub fn to_http_parts(mut self) -> (HttpRequest, Payload) {
-        let (mut head, payload) = self.finish().into_parts();
+        let mut req = self.finish();
+        let extensions = Rc::new(RefCell::new(req.take_req_data()));
+        let (mut head, payload) = req.into_parts();
         head.peer_addr = self.peer_addr;
         self.path.get_mut().update(&head.uri);
 
@@ -323,7 +345,7 @@ impl TestRequest {
             app_state,
             Rc::new(self.app_data),
             None,
-            Default::default(),
+            extensions,
         );
 
         (req, payload)
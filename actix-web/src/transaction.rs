@@ -0,0 +1,402 @@
+//! Transaction-per-request handling.
+//!
+//! The [`UnitOfWork`] middleware asks the app's [`TransactionManager`] to begin a transaction
+//! before each request is handled. Handlers access the transaction through the [`Transaction`]
+//! extractor. Once the handler has returned, the transaction is committed if the response is
+//! successful (`2xx`) or a redirection (`3xx`) and rolled back otherwise, including when the
+//! service returns an error.
+//!
+//! The manager trait makes no assumptions about the database library in use, so adapters for
+//! libraries such as Diesel or SQLx can be implemented outside of Actix Web.
+//!
+//! # Examples
+//! ```
+//! use actix_web::{
+//!     post,
+//!     transaction::{Transaction, TransactionManager, UnitOfWork},
+//!     App, Error, HttpRequest, HttpResponse,
+//! };
+//! use futures_util::future::{ok, LocalBoxFuture};
+//!
+//! /// Collects statements and "applies" them on commit.
+//! struct Journal;
+//!
+//! impl TransactionManager for Journal {
+//!     type Transaction = Vec<String>;
+//!
+//!     fn begin(
+//!         &self,
+//!         _req: &HttpRequest,
+//!     ) -> LocalBoxFuture<'static, Result<Vec<String>, Error>> {
+//!         Box::pin(ok(Vec::new()))
+//!     }
+//!
+//!     fn commit(&self, tx: Vec<String>) -> LocalBoxFuture<'static, Result<(), Error>> {
+//!         println!("applying {tx:?}");
+//!         Box::pin(ok(()))
+//!     }
+//!
+//!     fn rollback(&self, _tx: Vec<String>) -> LocalBoxFuture<'static, Result<(), Error>> {
+//!         Box::pin(ok(()))
+//!     }
+//! }
+//!
+//! #[post("/orders")]
+//! async fn create_order(tx: Transaction<Vec<String>>) -> HttpResponse {
+//!     tx.get_mut().push("INSERT INTO orders ...".to_owned());
+//!     HttpResponse::Created().finish()
+//! }
+//!
+//! let app = App::new()
+//!     .wrap(UnitOfWork::new(Journal))
+//!     .service(create_order);
+//! ```
+
+use std::{
+    cell::{RefCell, RefMut},
+    fmt,
+    rc::Rc,
+};
+
+use actix_utils::future::{ready, Ready};
+use derive_more::derive::{Display, Error};
+use futures_core::future::LocalBoxFuture;
+
+use crate::{
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    error::as_std_error,
+    http::StatusCode,
+    Error, FromRequest, HttpMessage as _, HttpRequest, ResponseError,
+};
+
+/// Begins, commits, and rolls back the transactions used by [`UnitOfWork`].
+///
+/// Futures returned by the manager must be `'static`; clone anything needed from the request, such
+/// as a connection pool registered as app data, before returning.
+///
+/// See the [module docs](self) for an example.
+pub trait TransactionManager: 'static {
+    /// Transaction handle given to handlers, e.g., a database transaction object.
+    type Transaction: 'static;
+
+    /// Begins a new transaction for `req`.
+    ///
+    /// Errors are returned to the client without calling the handler.
+    fn begin(&self, req: &HttpRequest)
+        -> LocalBoxFuture<'static, Result<Self::Transaction, Error>>;
+
+    /// Commits `tx`.
+    ///
+    /// Errors are returned to the client in place of the handler's response.
+    fn commit(&self, tx: Self::Transaction) -> LocalBoxFuture<'static, Result<(), Error>>;
+
+    /// Rolls back `tx`.
+    ///
+    /// Errors are logged, and the handler's response or error is returned to the client as usual.
+    fn rollback(&self, tx: Self::Transaction) -> LocalBoxFuture<'static, Result<(), Error>>;
+}
+
+/// Errors that can occur when using a [`Transaction`].
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum TransactionError {
+    /// The [`UnitOfWork`] middleware is not registered for the resource.
+    #[display("Unit of work middleware is missing")]
+    MissingMiddleware,
+
+    /// The transaction was still borrowed by a [`Transaction`] when the handler returned.
+    #[display("Transaction is still in use")]
+    InUse,
+}
+
+impl ResponseError for TransactionError {
+    as_std_error!();
+
+    fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+/// Middleware that wraps each request in a transaction.
+///
+/// See the [module docs](self) for an example.
+pub struct UnitOfWork<M> {
+    manager: Rc<M>,
+}
+
+impl<M: TransactionManager> UnitOfWork<M> {
+    /// Constructs new unit of work middleware that uses `manager` to handle transactions.
+    pub fn new(manager: M) -> Self {
+        Self {
+            manager: Rc::new(manager),
+        }
+    }
+}
+
+impl<M> Clone for UnitOfWork<M> {
+    fn clone(&self) -> Self {
+        Self {
+            manager: Rc::clone(&self.manager),
+        }
+    }
+}
+
+impl<M> fmt::Debug for UnitOfWork<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnitOfWork").finish_non_exhaustive()
+    }
+}
+
+impl<S, B, M> Transform<S, ServiceRequest> for UnitOfWork<M>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+    M: TransactionManager,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = UnitOfWorkMiddleware<S, M>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(UnitOfWorkMiddleware {
+            service: Rc::new(service),
+            manager: Rc::clone(&self.manager),
+        }))
+    }
+}
+
+#[doc(hidden)]
+pub struct UnitOfWorkMiddleware<S, M> {
+    service: Rc<S>,
+    manager: Rc<M>,
+}
+
+impl<S, B, M> Service<ServiceRequest> for UnitOfWorkMiddleware<S, M>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+    M: TransactionManager,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let manager = Rc::clone(&self.manager);
+
+        Box::pin(async move {
+            let tx = manager.begin(req.request()).await?;
+
+            let slot = Slot(Rc::new(RefCell::new(Some(tx))));
+            req.extensions_mut().insert(slot.clone());
+
+            let res = service.call(req).await;
+
+            let tx = match slot.0.try_borrow_mut() {
+                Ok(mut tx) => tx.take(),
+                Err(_) => {
+                    log::error!("Transaction is still borrowed after the handler returned");
+                    return Err(TransactionError::InUse.into());
+                }
+            };
+
+            let Some(tx) = tx else {
+                return res;
+            };
+
+            let commit = match &res {
+                Ok(res) => res.status().is_success() || res.status().is_redirection(),
+                Err(_) => false,
+            };
+
+            if commit {
+                manager.commit(tx).await?;
+            } else if let Err(err) = manager.rollback(tx).await {
+                log::error!("Failed to roll back transaction: {err}");
+            }
+
+            res
+        })
+    }
+}
+
+/// Per-request transaction storage, shared between the middleware and extractors.
+struct Slot<T>(Rc<RefCell<Option<T>>>);
+
+impl<T> Clone for Slot<T> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
+
+/// Extractor for the transaction of the current request.
+///
+/// `T` is the [`Transaction`](TransactionManager::Transaction) type of the manager used by the
+/// [`UnitOfWork`] middleware. All clones, and all `Transaction` extractors for the same request,
+/// share the same transaction. Fails with a `500 Internal Server Error` if the middleware is not
+/// registered.
+///
+/// See the [module docs](self) for an example.
+pub struct Transaction<T> {
+    slot: Slot<T>,
+}
+
+impl<T> Transaction<T> {
+    /// Mutably borrows the transaction.
+    ///
+    /// The borrow may be held across `.await` points, e.g., while executing queries, but must be
+    /// released before the handler returns.
+    ///
+    /// # Panics
+    /// Panics if the transaction is already borrowed or if it is used after the handler returned.
+    pub fn get_mut(&self) -> RefMut<'_, T> {
+        RefMut::map(self.slot.0.borrow_mut(), |tx| {
+            tx.as_mut()
+                .expect("Transaction used after the handler returned")
+        })
+    }
+}
+
+impl<T> Clone for Transaction<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slot: self.slot.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Transaction<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transaction").finish_non_exhaustive()
+    }
+}
+
+impl<T: 'static> FromRequest for Transaction<T> {
+    type Error = TransactionError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        match req.extensions().get::<Slot<T>>() {
+            Some(slot) => ready(Ok(Transaction { slot: slot.clone() })),
+            None => {
+                log::debug!(
+                    "Failed to extract `Transaction<{}>`: `UnitOfWork` middleware is not \
+                    registered",
+                    std::any::type_name::<T>()
+                );
+                ready(Err(TransactionError::MissingMiddleware))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_utils::future::ok;
+
+    use super::*;
+    use crate::{
+        error::ErrorBadRequest,
+        test::{call_service, init_service, try_call_service, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    /// Records the outcome of each transaction.
+    #[derive(Clone, Default)]
+    struct TestManager {
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl TransactionManager for TestManager {
+        type Transaction = Vec<String>;
+
+        fn begin(
+            &self,
+            req: &HttpRequest,
+        ) -> LocalBoxFuture<'static, Result<Self::Transaction, Error>> {
+            if req.path() == "/unavailable" {
+                return Box::pin(ready(Err(ErrorBadRequest("unavailable"))));
+            }
+
+            Box::pin(ok(Vec::new()))
+        }
+
+        fn commit(&self, tx: Self::Transaction) -> LocalBoxFuture<'static, Result<(), Error>> {
+            self.log.borrow_mut().push(format!("commit {tx:?}"));
+            Box::pin(ok(()))
+        }
+
+        fn rollback(&self, tx: Self::Transaction) -> LocalBoxFuture<'static, Result<(), Error>> {
+            self.log.borrow_mut().push(format!("rollback {tx:?}"));
+            Box::pin(ok(()))
+        }
+    }
+
+    /// Records the request path in the transaction and responds according to it.
+    async fn handler(
+        req: HttpRequest,
+        tx: Transaction<Vec<String>>,
+    ) -> Result<HttpResponse, Error> {
+        tx.get_mut().push(req.path().to_owned());
+
+        Ok(match req.path() {
+            "/created" => HttpResponse::Created().finish(),
+            "/redirect" => HttpResponse::SeeOther().finish(),
+            "/failed" => HttpResponse::InternalServerError().finish(),
+            _ => return Err(ErrorBadRequest("invalid")),
+        })
+    }
+
+    #[actix_rt::test]
+    async fn commits_and_rolls_back() {
+        let manager = TestManager::default();
+        let app = init_service(
+            App::new()
+                .wrap(UnitOfWork::new(manager.clone()))
+                .default_service(web::to(handler)),
+        )
+        .await;
+
+        for (path, status) in [
+            ("/created", StatusCode::CREATED),
+            ("/redirect", StatusCode::SEE_OTHER),
+            ("/failed", StatusCode::INTERNAL_SERVER_ERROR),
+            ("/error", StatusCode::BAD_REQUEST),
+        ] {
+            let req = TestRequest::with_uri(path).to_request();
+            let res = call_service(&app, req).await;
+            assert_eq!(res.status(), status, "{path}");
+        }
+
+        let req = TestRequest::with_uri("/unavailable").to_request();
+        let err = try_call_service(&app, req).await.unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::BAD_REQUEST
+        );
+
+        assert_eq!(
+            *manager.log.borrow(),
+            [
+                r#"commit ["/created"]"#,
+                r#"commit ["/redirect"]"#,
+                r#"rollback ["/failed"]"#,
+                r#"rollback ["/error"]"#,
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn missing_middleware() {
+        let app = init_service(App::new().default_service(web::to(handler))).await;
+
+        let req = TestRequest::default().to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -1,7 +1,6 @@
 //! For URL encoded form helper documentation, see [`Form`].
 
 use std::{
-    borrow::Cow,
     fmt,
     future::Future,
     ops,
@@ -11,16 +10,23 @@ use std::{
 };
 
 use actix_http::Payload;
-use bytes::BytesMut;
 use encoding_rs::{Encoding, UTF_8};
 use futures_core::{future::LocalBoxFuture, ready};
 use futures_util::{FutureExt as _, StreamExt as _};
+use mime::Mime;
 use serde::{de::DeserializeOwned, Serialize};
 
 #[cfg(feature = "__compress")]
 use crate::dev::Decompress;
 use crate::{
-    body::EitherBody, error::UrlencodedError, extract::FromRequest, http::header::CONTENT_LENGTH,
+    body::EitherBody,
+    error::UrlencodedError,
+    extract::FromRequest,
+    http::{
+        header::{CONTENT_LENGTH, CONTENT_TYPE, TRANSFER_ENCODING},
+        Method,
+    },
+    types::urlencoded::{self, UrlEncodedTokenizer},
     web, Error, HttpMessage, HttpRequest, HttpResponse, Responder,
 };
 
@@ -34,6 +40,10 @@ use crate::{
 ///
 /// Use [`FormConfig`] to configure extraction options.
 ///
+/// For `GET` and `HEAD` requests that do not declare a body (i.e., have no `Content-Type`,
+/// `Content-Length`, or `Transfer-Encoding` headers), the form is instead deserialized from the
+/// request's query string.
+///
 /// ## Examples
 /// ```
 /// use actix_web::{post, web};
@@ -130,16 +140,40 @@ where
 
     #[inline]
     fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
-        let FormConfig { limit, err_handler } = FormConfig::from_req(req).clone();
+        let cfg = FormConfig::from_req(req);
+
+        let fut = if is_bodiless_query_request(req) {
+            UrlEncoded::from_query(req).limit(cfg.limit)
+        } else {
+            let content_types = cfg.content_types.as_deref().unwrap_or_default();
+            UrlEncoded::new_with_content_types(req, payload, content_types).limit(cfg.limit)
+        };
 
         FormExtractFut {
-            fut: UrlEncoded::new(req, payload).limit(limit),
+            fut,
             req: req.clone(),
-            err_handler,
+            err_handler: cfg.err_handler.clone(),
         }
     }
 }
 
+/// Returns true if request is a `GET` or `HEAD` request that does not declare a payload.
+fn is_bodiless_query_request(req: &HttpRequest) -> bool {
+    if req.method() != Method::GET && req.method() != Method::HEAD {
+        return false;
+    }
+
+    let headers = req.headers();
+    if headers.contains_key(CONTENT_TYPE) || headers.contains_key(TRANSFER_ENCODING) {
+        return false;
+    }
+
+    match headers.get(&CONTENT_LENGTH) {
+        Some(len) => len.to_str().is_ok_and(|len| len.trim() == "0"),
+        None => true,
+    }
+}
+
 type FormErrHandler = Option<Rc<dyn Fn(UrlencodedError, &HttpRequest) -> Error>>;
 
 pub struct FormExtractFut<T> {
@@ -223,6 +257,7 @@ impl<T: Serialize> Responder for Form<T> {
 #[derive(Clone)]
 pub struct FormConfig {
     limit: usize,
+    content_types: Option<Vec<Mime>>,
     err_handler: FormErrHandler,
 }
 
@@ -233,6 +268,23 @@ impl FormConfig {
         self
     }
 
+    /// Add an additional accepted content type.
+    ///
+    /// `application/x-www-form-urlencoded` is always accepted. Content types are compared without
+    /// their parameters so, for example, adding `application/vnd.example+form` will also accept
+    /// `application/vnd.example+form; charset=utf-8`.
+    ///
+    /// ```
+    /// use actix_web::web;
+    ///
+    /// let cfg = web::FormConfig::default()
+    ///     .content_type("application/vnd.example+form".parse().unwrap());
+    /// ```
+    pub fn content_type(mut self, mime: Mime) -> Self {
+        self.content_types.get_or_insert_with(Vec::new).push(mime);
+        self
+    }
+
     /// Set custom error handler
     pub fn error_handler<F>(mut self, f: F) -> Self
     where
@@ -255,6 +307,7 @@ impl FormConfig {
 /// Allow shared refs used as default.
 const DEFAULT_CONFIG: FormConfig = FormConfig {
     limit: 16_384, // 2^14 bytes (~16kB)
+    content_types: None,
     err_handler: None,
 };
 
@@ -269,7 +322,8 @@ impl Default for FormConfig {
 /// Form can be deserialized from any type `T` that implements [`serde::Deserialize`].
 ///
 /// Returns error if:
-/// - content type is not `application/x-www-form-urlencoded`
+/// - content type is not `application/x-www-form-urlencoded` (or one of the additional types passed
+///   to [`new_with_content_types`](UrlEncoded::new_with_content_types()))
 /// - content length is greater than [limit](UrlEncoded::limit())
 pub struct UrlEncoded<T> {
     #[cfg(feature = "__compress")]
@@ -288,8 +342,25 @@ pub struct UrlEncoded<T> {
 impl<T> UrlEncoded<T> {
     /// Create a new future to decode a URL encoded request payload.
     pub fn new(req: &HttpRequest, payload: &mut Payload) -> Self {
+        Self::new_with_content_types(req, payload, &[])
+    }
+
+    /// Create a new future to decode a URL encoded request payload, additionally accepting any of
+    /// the given content types.
+    ///
+    /// Content types are compared by essence (i.e., ignoring parameters such as `charset`).
+    pub fn new_with_content_types(
+        req: &HttpRequest,
+        payload: &mut Payload,
+        content_types: &[Mime],
+    ) -> Self {
         // check content type
-        if req.content_type().to_lowercase() != "application/x-www-form-urlencoded" {
+        let ctype = req.content_type().to_lowercase();
+        if ctype != "application/x-www-form-urlencoded"
+            && !content_types
+                .iter()
+                .any(|mime| mime.essence_str().eq_ignore_ascii_case(&ctype))
+        {
             return Self::err(UrlencodedError::ContentType);
         }
         let encoding = match req.encoding() {
@@ -313,7 +384,7 @@ impl<T> UrlEncoded<T> {
         let payload = {
             cfg_if::cfg_if! {
                 if #[cfg(feature = "__compress")] {
-                    Decompress::from_headers(payload.take(), req.headers())
+                    Decompress::from_headers(payload.take(), req.headers()).limit(32_768)
                 } else {
                     payload.take()
                 }
@@ -343,11 +414,39 @@ impl<T> UrlEncoded<T> {
 
     /// Set maximum accepted payload size. The default limit is 256kB.
     pub fn limit(mut self, limit: usize) -> Self {
+        #[cfg(feature = "__compress")]
+        {
+            self.stream = self.stream.take().map(|stream| stream.limit(limit));
+        }
+
         self.limit = limit;
         self
     }
 }
 
+impl<T> UrlEncoded<T>
+where
+    T: DeserializeOwned + 'static,
+{
+    /// Create a future that decodes from the request's query string instead of its payload.
+    fn from_query(req: &HttpRequest) -> Self {
+        let query = req.query_string().to_owned();
+        let length = query.len();
+
+        let fut =
+            async move { serde_urlencoded::from_str::<T>(&query).map_err(UrlencodedError::Parse) };
+
+        UrlEncoded {
+            stream: None,
+            limit: 32_768,
+            fut: Some(fut.boxed_local()),
+            err: None,
+            length: Some(length),
+            encoding: UTF_8,
+        }
+    }
+}
+
 impl<T> Future for UrlEncoded<T>
 where
     T: DeserializeOwned + 'static,
@@ -355,10 +454,6 @@ where
     type Output = Result<T, UrlencodedError>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        if let Some(ref mut fut) = self.fut {
-            return Pin::new(fut).poll(cx);
-        }
-
         if let Some(err) = self.err.take() {
             return Poll::Ready(Err(err));
         }
@@ -371,37 +466,32 @@ where
             }
         }
 
+        if let Some(ref mut fut) = self.fut {
+            return Pin::new(fut).poll(cx);
+        }
+
         // future
         let encoding = self.encoding;
         let mut stream = self.stream.take().unwrap();
 
         self.fut = Some(
             async move {
-                let mut body = BytesMut::with_capacity(8192);
+                let mut tokenizer = UrlEncodedTokenizer::new(encoding);
+                let mut size = 0;
 
                 while let Some(item) = stream.next().await {
                     let chunk = item?;
 
-                    if (body.len() + chunk.len()) > limit {
-                        return Err(UrlencodedError::Overflow {
-                            size: body.len() + chunk.len(),
-                            limit,
-                        });
-                    } else {
-                        body.extend_from_slice(&chunk);
+                    size += chunk.len();
+                    if size > limit {
+                        return Err(UrlencodedError::Overflow { size, limit });
                     }
-                }
-
-                if encoding == UTF_8 {
-                    serde_urlencoded::from_bytes::<T>(&body).map_err(UrlencodedError::Parse)
-                } else {
-                    let body = encoding
-                        .decode_without_bom_handling_and_without_replacement(&body)
-                        .map(Cow::into_owned)
-                        .ok_or(UrlencodedError::Encoding)?;
 
-                    serde_urlencoded::from_str::<T>(&body).map_err(UrlencodedError::Parse)
+                    tokenizer.feed(&chunk);
                 }
+
+                tokenizer.finish()?;
+                urlencoded::from_tokenizer(tokenizer)
             }
             .boxed_local(),
         );
@@ -422,6 +512,7 @@ mod tests {
             StatusCode,
         },
         test::{assert_body_eq, TestRequest},
+        ResponseError as _,
     };
 
     #[derive(Deserialize, Serialize, Debug, PartialEq)]
@@ -486,6 +577,50 @@ mod tests {
         assert!(eq(info.err().unwrap(), UrlencodedError::ContentType));
     }
 
+    #[actix_rt::test]
+    async fn test_urlencoded_overflow_size() {
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .insert_header((CONTENT_LENGTH, 23))
+            .set_payload(Bytes::from_static(b"hello=world&counter=123"))
+            .to_http_parts();
+        let err = UrlEncoded::<Info>::new(&req, &mut pl)
+            .limit(10)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            UrlencodedError::Overflow {
+                size: 23,
+                limit: 10
+            }
+        ));
+
+        // without a content length, the size is known once the limit has been passed
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload(Bytes::from_static(b"hello=world&counter=123"))
+            .to_http_parts();
+        let err = UrlEncoded::<Info>::new(&req, &mut pl)
+            .limit(10)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            UrlencodedError::Overflow {
+                size: 23,
+                limit: 10
+            }
+        ));
+
+        let res = err.error_response();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_body_eq!(
+            res,
+            b"URL encoded payload is larger (23 bytes) than allowed (limit: 10 bytes)."
+        );
+    }
+
     #[actix_rt::test]
     async fn test_urlencoded() {
         let (req, mut pl) = TestRequest::default()
@@ -522,6 +657,75 @@ mod tests {
         );
     }
 
+    #[actix_rt::test]
+    async fn test_get_query_fallback() {
+        let (req, mut pl) = TestRequest::get()
+            .uri("/?hello=world&counter=123")
+            .to_http_parts();
+
+        let Form(s) = Form::<Info>::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(
+            s,
+            Info {
+                hello: "world".into(),
+                counter: 123
+            }
+        );
+
+        let (req, mut pl) = TestRequest::default()
+            .method(Method::HEAD)
+            .uri("/?hello=world")
+            .to_http_parts();
+        assert!(Form::<Info>::from_request(&req, &mut pl).await.is_err());
+
+        // requests with a body still require a form content type
+        let (req, mut pl) = TestRequest::post()
+            .uri("/?hello=world&counter=123")
+            .to_http_parts();
+        assert!(Form::<Info>::from_request(&req, &mut pl).await.is_err());
+
+        // query strings are subject to the configured limit
+        let (req, mut pl) = TestRequest::get()
+            .uri("/?hello=world&counter=123")
+            .app_data(FormConfig::default().limit(10))
+            .to_http_parts();
+        let err = Form::<Info>::from_request(&req, &mut pl).await.unwrap_err();
+        assert!(err.to_string().starts_with("URL encoded payload is larger"));
+    }
+
+    #[actix_rt::test]
+    async fn test_additional_content_types() {
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((CONTENT_TYPE, "application/vnd.example+form; charset=utf-8"))
+            .insert_header((CONTENT_LENGTH, 23))
+            .set_payload(Bytes::from_static(b"hello=world&counter=123"))
+            .app_data(
+                FormConfig::default().content_type("application/vnd.example+form".parse().unwrap()),
+            )
+            .to_http_parts();
+
+        let Form(s) = Form::<Info>::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(s.counter, 123);
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((CONTENT_TYPE, "application/vnd.other+form"))
+            .insert_header((CONTENT_LENGTH, 23))
+            .set_payload(Bytes::from_static(b"hello=world&counter=123"))
+            .app_data(
+                FormConfig::default().content_type("application/vnd.example+form".parse().unwrap()),
+            )
+            .to_http_parts();
+
+        let err = UrlEncoded::<Info>::new_with_content_types(
+            &req,
+            &mut pl,
+            &["application/vnd.example+form".parse().unwrap()],
+        )
+        .await
+        .unwrap_err();
+        assert!(eq(err, UrlencodedError::ContentType));
+    }
+
     #[actix_rt::test]
     async fn test_responder() {
         let req = TestRequest::default().to_http_request();
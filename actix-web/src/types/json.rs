@@ -356,7 +356,7 @@ impl<T: DeserializeOwned> JsonBody<T> {
         let payload = {
             cfg_if::cfg_if! {
                 if #[cfg(feature = "__compress")] {
-                    Decompress::from_headers(payload.take(), req.headers())
+                    Decompress::from_headers(payload.take(), req.headers()).limit(DEFAULT_LIMIT)
                 } else {
                     payload.take()
                 }
@@ -390,6 +390,9 @@ impl<T: DeserializeOwned> JsonBody<T> {
                     }
                 }
 
+                #[cfg(feature = "__compress")]
+                let payload = payload.limit(limit);
+
                 JsonBody::Body {
                     limit,
                     length,
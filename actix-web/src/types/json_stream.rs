@@ -0,0 +1,255 @@
+//! For streaming JSON array responder documentation, see [`JsonStream`].
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::{BufMut as _, Bytes, BytesMut};
+use futures_core::stream::Stream;
+use pin_project_lite::pin_project;
+use serde::Serialize;
+
+use crate::{
+    body::{BodySize, MessageBody},
+    error::JsonPayloadError,
+    http::{
+        header::{HeaderValue, CONTENT_TYPE},
+        StatusCode,
+    },
+    HttpRequest, HttpResponse, Responder,
+};
+
+pin_project! {
+    /// Streaming JSON array responder.
+    ///
+    /// Serializes the items of a stream into the response body as a single JSON array, e.g.
+    /// `[{"id":1},{"id":2}]`, so that large result sets can be sent without collecting them into a
+    /// `Vec` first. An empty stream produces `[]`.
+    ///
+    /// Items are only pulled from the stream when the connection is ready for more body data, so a
+    /// slow client slows down the stream instead of causing items to pile up in memory. Serialized
+    /// items are buffered and sent in chunks once [`flush_bytes()`] bytes or [`flush_items()`]
+    /// items are buffered, or whenever the stream has no item ready.
+    ///
+    /// The response is sent with a `200 OK` status and an `application/json` content type. Since
+    /// the head is sent before the stream has ended, an item that fails to serialize cannot be
+    /// turned into an error response; the body ends with the error and the connection is closed.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{get, web, Responder};
+    /// use futures_util::stream;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct User {
+    ///     id: u32,
+    /// }
+    ///
+    /// #[get("/users")]
+    /// async fn users() -> impl Responder {
+    ///     let users = stream::iter((0..10_000).map(|id| User { id }));
+    ///
+    ///     // responds with `[{"id":0},{"id":1},...]`
+    ///     web::JsonStream::new(users).flush_items(100)
+    /// }
+    /// ```
+    ///
+    /// [`flush_bytes()`]: Self::flush_bytes()
+    /// [`flush_items()`]: Self::flush_items()
+    pub struct JsonStream<S> {
+        #[pin]
+        stream: S,
+        buf: BytesMut,
+        items: usize,
+        buffered_items: usize,
+        flush_bytes: usize,
+        flush_items: usize,
+        done: bool,
+    }
+}
+
+impl<S> JsonStream<S>
+where
+    S: Stream,
+    S::Item: Serialize,
+{
+    /// Constructs a responder that serializes the items of `stream` as a JSON array.
+    pub fn new(stream: S) -> Self {
+        let mut buf = BytesMut::with_capacity(DEFAULT_FLUSH_BYTES);
+        buf.put_u8(b'[');
+
+        Self {
+            stream,
+            buf,
+            items: 0,
+            buffered_items: 0,
+            flush_bytes: DEFAULT_FLUSH_BYTES,
+            flush_items: usize::MAX,
+            done: false,
+        }
+    }
+
+    /// Sets the number of buffered bytes after which a chunk is sent. By default this is 8kB.
+    pub fn flush_bytes(mut self, bytes: usize) -> Self {
+        self.flush_bytes = bytes;
+        self
+    }
+
+    /// Sets the number of buffered items after which a chunk is sent.
+    ///
+    /// By default, chunks are only limited by [`flush_bytes()`](Self::flush_bytes()).
+    pub fn flush_items(mut self, items: usize) -> Self {
+        self.flush_items = items;
+        self
+    }
+}
+
+/// Number of buffered bytes after which a chunk is sent, by default.
+const DEFAULT_FLUSH_BYTES: usize = 8_192;
+
+impl<S> MessageBody for JsonStream<S>
+where
+    S: Stream,
+    S::Item: Serialize,
+{
+    type Error = JsonPayloadError;
+
+    #[inline]
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let mut this = self.project();
+
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        loop {
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    if *this.items > 0 {
+                        this.buf.put_u8(b',');
+                    }
+
+                    if let Err(err) = serde_json::to_writer(this.buf.writer(), &item) {
+                        *this.done = true;
+                        return Poll::Ready(Some(Err(JsonPayloadError::Serialize(err))));
+                    }
+
+                    *this.items += 1;
+                    *this.buffered_items += 1;
+
+                    if this.buf.len() >= *this.flush_bytes
+                        || *this.buffered_items >= *this.flush_items
+                    {
+                        *this.buffered_items = 0;
+                        return Poll::Ready(Some(Ok(this.buf.split().freeze())));
+                    }
+                }
+
+                Poll::Ready(None) => {
+                    *this.done = true;
+                    this.buf.put_u8(b']');
+                    return Poll::Ready(Some(Ok(this.buf.split().freeze())));
+                }
+
+                Poll::Pending if this.buf.is_empty() => return Poll::Pending,
+
+                // send what is buffered while waiting for the next item
+                Poll::Pending => {
+                    *this.buffered_items = 0;
+                    return Poll::Ready(Some(Ok(this.buf.split().freeze())));
+                }
+            }
+        }
+    }
+}
+
+impl<S> Responder for JsonStream<S>
+where
+    S: Stream + 'static,
+    S::Item: Serialize,
+{
+    type Body = Self;
+
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse<Self::Body> {
+        let mut res = HttpResponse::with_body(StatusCode::OK, self);
+        res.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static(mime::APPLICATION_JSON.as_ref()),
+        );
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream::{self, StreamExt as _};
+    use serde::ser::Error as _;
+
+    use super::*;
+    use crate::{body, test::TestRequest};
+
+    #[actix_rt::test]
+    async fn json_array() {
+        let req = TestRequest::default().to_http_request();
+
+        let res = JsonStream::new(stream::iter([1, 2, 3])).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/json");
+
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "[1,2,3]");
+
+        let empty = JsonStream::new(stream::empty::<u32>());
+        assert_eq!(body::to_bytes(empty).await.unwrap(), "[]");
+    }
+
+    async fn chunks(body: impl MessageBody<Error = JsonPayloadError>) -> Vec<Bytes> {
+        let mut body = std::pin::pin!(body);
+        let mut chunks = Vec::new();
+
+        while let Some(chunk) = std::future::poll_fn(|cx| body.as_mut().poll_next(cx)).await {
+            chunks.push(chunk.unwrap());
+        }
+
+        chunks
+    }
+
+    #[actix_rt::test]
+    async fn flush_thresholds() {
+        let body = JsonStream::new(stream::iter(["a", "b", "c"])).flush_items(2);
+        assert_eq!(chunks(body).await, ["[\"a\",\"b\"", ",\"c\"]"]);
+
+        let body = JsonStream::new(stream::iter([1, 2, 3])).flush_bytes(4);
+        assert_eq!(chunks(body).await, ["[1,2", ",3]"]);
+
+        // buffered items are sent while the stream has no item ready
+        let items = stream::iter([1, 2]).chain(stream::pending());
+        let mut body = std::pin::pin!(JsonStream::new(items));
+        let chunk = std::future::poll_fn(|cx| body.as_mut().poll_next(cx)).await;
+        assert_eq!(chunk.unwrap().unwrap(), "[1,2");
+    }
+
+    #[actix_rt::test]
+    async fn serialize_error() {
+        struct Unserializable;
+
+        impl Serialize for Unserializable {
+            fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+                Err(S::Error::custom("unserializable"))
+            }
+        }
+
+        let body = JsonStream::new(stream::iter([Unserializable]));
+        let err = body::to_bytes(body).await.unwrap_err();
+        assert!(matches!(err, JsonPayloadError::Serialize(_)));
+    }
+}
@@ -5,17 +5,32 @@ mod form;
 mod header;
 mod html;
 mod json;
+mod json_stream;
+mod ndjson;
+mod negotiate;
+mod pagination;
 mod path;
 mod payload;
 mod query;
 mod readlines;
+mod urlencoded;
+#[cfg(feature = "xml")]
+mod xml;
 
+#[cfg(feature = "webhook")]
+pub(crate) use self::payload::HttpMessageBody;
+#[cfg(feature = "xml")]
+pub use self::xml::{Xml, XmlBody, XmlConfig};
 pub use self::{
     either::Either,
     form::{Form, FormConfig, UrlEncoded},
     header::Header,
     html::Html,
     json::{Json, JsonBody, JsonConfig},
+    json_stream::JsonStream,
+    ndjson::{NdJson, NdJsonConfig},
+    negotiate::{Negotiate, NegotiateConfig, NegotiateValue},
+    pagination::{Pagination, PaginationConfig},
     path::{Path, PathConfig},
     payload::{Payload, PayloadConfig},
     query::{Query, QueryConfig},
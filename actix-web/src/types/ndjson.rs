@@ -0,0 +1,220 @@
+//! For newline-delimited JSON extractor documentation, see [`NdJson`].
+
+use std::{
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use actix_utils::future::{ok, Ready};
+use futures_core::{ready, stream::Stream};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    dev, error::JsonPayloadError, types::readlines::Readlines, web, Error, FromRequest,
+    HttpMessage as _, HttpRequest,
+};
+
+/// Newline-delimited JSON extractor.
+///
+/// Streams the request payload as a sequence of `T` values, one per line, as described by the
+/// [NDJSON] and [JSON Lines] formats. Blank lines are skipped.
+///
+/// Values are deserialized as lines arrive so that large uploads do not need to be buffered. Use
+/// [`NdJsonConfig`] to change the maximum length of each line.
+///
+/// Errors encountered while reading or deserializing a line are yielded from the stream; the
+/// extractor itself does not fail. A line that cannot be deserialized is skipped, but an error
+/// reading the payload, such as a line exceeding the limit, ends the stream.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web, Error};
+/// use futures_util::StreamExt as _;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Event {
+///     kind: String,
+/// }
+///
+/// #[post("/events")]
+/// async fn ingest(mut events: web::NdJson<Event>) -> Result<String, Error> {
+///     let mut count = 0;
+///
+///     while let Some(event) = events.next().await {
+///         let event = event?;
+///         log::info!("received {} event", event.kind);
+///         count += 1;
+///     }
+///
+///     Ok(format!("received {count} events"))
+/// }
+/// ```
+///
+/// [NDJSON]: https://github.com/ndjson/ndjson-spec
+/// [JSON Lines]: https://jsonlines.org
+pub struct NdJson<T> {
+    lines: Readlines<dev::ServiceRequest>,
+    done: bool,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> Stream for NdJson<T> {
+    type Item = Result<T, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        loop {
+            let line = match ready!(Pin::new(&mut this.lines).poll_next(cx)) {
+                Some(Ok(line)) => line,
+                Some(Err(err)) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(err.into())));
+                }
+                None => {
+                    this.done = true;
+                    return Poll::Ready(None);
+                }
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let res = serde_json::from_str(&line).map_err(JsonPayloadError::Deserialize);
+            return Poll::Ready(Some(res.map_err(Into::into)));
+        }
+    }
+}
+
+/// See [here](#examples) for example of usage as an extractor.
+impl<T: DeserializeOwned> FromRequest for NdJson<T> {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+        let limit = NdJsonConfig::from_req(req).limit;
+
+        let lines = match req.encoding() {
+            Ok(encoding) => Readlines::from_payload(payload.take(), encoding),
+            Err(err) => Readlines::err(err.into()),
+        };
+
+        ok(NdJson {
+            lines: lines.limit(limit),
+            done: false,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/// Configuration for the [`NdJson`] extractor.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App};
+///
+/// // limit each line to 4kB
+/// let app = App::new().app_data(web::NdJsonConfig::default().limit(4096));
+/// ```
+#[derive(Debug, Clone)]
+pub struct NdJsonConfig {
+    limit: usize,
+}
+
+impl NdJsonConfig {
+    /// Set maximum accepted line size in bytes. By default this limit is 256kB.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Extract NDJSON config from app data. Check both `T` and `Data<T>`, in that order, and fall
+    /// back to the default configuration.
+    fn from_req(req: &HttpRequest) -> &Self {
+        req.app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+            .unwrap_or(&DEFAULT_CONFIG)
+    }
+}
+
+/// Allow shared refs used as default.
+const DEFAULT_CONFIG: NdJsonConfig = NdJsonConfig {
+    limit: 262_144, // 2^18 bytes (~256kB)
+};
+
+impl Default for NdJsonConfig {
+    fn default() -> Self {
+        DEFAULT_CONFIG
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures_util::StreamExt as _;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{http::StatusCode, test::TestRequest};
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Event {
+        id: u32,
+    }
+
+    #[actix_rt::test]
+    async fn stream_values() {
+        let (req, mut pl) = TestRequest::default()
+            .set_payload(Bytes::from_static(
+                b"{\"id\":1}\n\n{\"id\":2}\r\n{\"id\":3}",
+            ))
+            .to_http_parts();
+
+        let events = NdJson::<Event>::from_request(&req, &mut pl)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(events, [Event { id: 1 }, Event { id: 2 }, Event { id: 3 }]);
+    }
+
+    #[actix_rt::test]
+    async fn deserialize_error() {
+        let (req, mut pl) = TestRequest::default()
+            .set_payload(Bytes::from_static(b"{\"id\":1}\n{\"id\":\"two\"}\n"))
+            .to_http_parts();
+
+        let mut events = NdJson::<Event>::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(events.next().await.unwrap().unwrap(), Event { id: 1 });
+
+        let err = events.next().await.unwrap().unwrap_err();
+        assert_eq!(err.error_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_rt::test]
+    async fn line_limit() {
+        let (req, mut pl) = TestRequest::default()
+            .app_data(NdJsonConfig::default().limit(10))
+            .set_payload(Bytes::from_static(b"{\"id\":1}\n{\"id\":1234567}\n"))
+            .to_http_parts();
+
+        let mut events = NdJson::<Event>::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(events.next().await.unwrap().unwrap(), Event { id: 1 });
+
+        let err = events.next().await.unwrap().unwrap_err();
+        assert_eq!(err.error_response().status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        assert!(events.next().await.is_none());
+        assert!(events.next().await.is_none());
+    }
+}
@@ -0,0 +1,430 @@
+//! For content negotiation responder documentation, see [`Negotiate`].
+
+use std::{fmt, sync::Arc};
+
+use bytes::Bytes;
+use mime::Mime;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    body::EitherBody,
+    error::{ErrorNotAcceptable, JsonPayloadError},
+    http::header::{self, Accept, Header as _, HeaderValue},
+    web, Error, HttpRequest, HttpResponse, Responder,
+};
+
+/// Content negotiation responder.
+///
+/// Serializes the wrapped value in the media type most preferred by the request's `Accept` header,
+/// out of those registered in the [`NegotiateConfig`]. If no `Accept` header is sent, the first
+/// registered media type is used. If the value cannot be serialized in the preferred media type,
+/// the next acceptable one is tried. If none of the registered media types are acceptable, a
+/// `406 Not Acceptable` response is returned.
+///
+/// By default, the following media types are supported, in this order:
+/// - `application/json`
+/// - `application/cbor` (requires the `cbor` crate feature)
+/// - `text/plain`, for values that serialize to a string, number, or boolean
+///
+/// Other media types, e.g., XML, can be added using [`NegotiateConfig::serializer()`].
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Info {
+///     name: String,
+/// }
+///
+/// #[get("/info")]
+/// async fn info() -> web::Negotiate<Info> {
+///     web::Negotiate(Info { name: "actix".to_owned() })
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Negotiate<T>(pub T);
+
+impl<T> Negotiate<T> {
+    /// Unwraps into inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Serialize> Responder for Negotiate<T> {
+    type Body = EitherBody<Bytes>;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let config = NegotiateConfig::from_req(req);
+
+        let mut first_err = None;
+
+        for format in config.acceptable(req) {
+            match (format.serialize)(&NegotiateValue(&self.0)) {
+                Ok(body) => {
+                    return HttpResponse::Ok()
+                        .content_type(format.media_type.clone())
+                        .insert_header((header::VARY, HeaderValue::from_static("accept")))
+                        .message_body(body)
+                        .map(HttpResponse::map_into_left_body)
+                        .unwrap_or_else(|err| HttpResponse::from_error(err).map_into_right_body());
+                }
+
+                Err(err) => {
+                    first_err.get_or_insert(err);
+                }
+            }
+        }
+
+        let err = first_err.unwrap_or_else(|| {
+            ErrorNotAcceptable(format!("supported media types: {}", config.media_types()))
+        });
+
+        HttpResponse::from_error(err).map_into_right_body()
+    }
+}
+
+/// Value of a [`Negotiate`] responder, as passed to the serializers of a [`NegotiateConfig`].
+///
+/// Implements [`Serialize`] by forwarding to the wrapped value.
+pub struct NegotiateValue<'a>(&'a dyn erased_serde::Serialize);
+
+impl Serialize for NegotiateValue<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        erased_serde::serialize(self.0, serializer)
+    }
+}
+
+impl fmt::Debug for NegotiateValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NegotiateValue").finish_non_exhaustive()
+    }
+}
+
+type SerializeFn = dyn Fn(&NegotiateValue<'_>) -> Result<Bytes, Error> + Send + Sync;
+
+#[derive(Clone)]
+struct Format {
+    media_type: Mime,
+    serialize: Arc<SerializeFn>,
+}
+
+/// Media types supported by the [`Negotiate`] responder.
+///
+/// # Examples
+/// ```
+/// use actix_web::{error, web, App};
+///
+/// let config = web::NegotiateConfig::default().serializer(
+///     "application/x-yaml".parse().unwrap(),
+///     |value| match serde_json::to_value(value) {
+///         Ok(serde_json::Value::Object(fields)) => Ok(fields
+///             .iter()
+///             .map(|(key, val)| format!("{key}: {val}\n"))
+///             .collect::<String>()
+///             .into()),
+///         _ => Err(error::ErrorNotAcceptable("value is not a struct")),
+///     },
+/// );
+///
+/// let app = App::new().app_data(config);
+/// ```
+#[derive(Clone)]
+pub struct NegotiateConfig {
+    formats: Vec<Format>,
+}
+
+impl NegotiateConfig {
+    /// Registers a serializer for `media_type`, replacing any existing serializer for it.
+    ///
+    /// The serializer is passed the responder's [value](NegotiateValue), which implements
+    /// [`Serialize`], and should
+    /// return an error if the value cannot be represented in the media type, in which case the
+    /// next acceptable media type is tried. Newly registered media types are preferred least when
+    /// the client has no preference.
+    pub fn serializer<F>(mut self, media_type: Mime, serialize: F) -> Self
+    where
+        F: Fn(&NegotiateValue<'_>) -> Result<Bytes, Error> + Send + Sync + 'static,
+    {
+        let format = Format {
+            media_type,
+            serialize: Arc::new(serialize),
+        };
+
+        match self
+            .formats
+            .iter_mut()
+            .find(|fmt| fmt.media_type.essence_str() == format.media_type.essence_str())
+        {
+            Some(existing) => *existing = format,
+            None => self.formats.push(format),
+        }
+
+        self
+    }
+
+    /// Returns the registered formats acceptable to the request's `Accept` header, most preferred
+    /// first.
+    fn acceptable(&self, req: &HttpRequest) -> Vec<&Format> {
+        let accept = match Accept::parse(req) {
+            Ok(accept) if !accept.is_empty() => accept,
+            _ => return self.formats.iter().collect(),
+        };
+
+        // ranges explicitly marked as unacceptable
+        let refused = accept
+            .iter()
+            .filter(|item| item.quality == header::Quality::ZERO)
+            .map(|item| &item.item)
+            .collect::<Vec<_>>();
+
+        let mut acceptable = Vec::<&Format>::new();
+
+        for range in accept
+            .ranked()
+            .iter()
+            .filter(|range| !refused.contains(range))
+        {
+            for fmt in &self.formats {
+                if media_range_matches(range, &fmt.media_type)
+                    && !is_refused(&accept, &fmt.media_type)
+                    && !acceptable
+                        .iter()
+                        .any(|other| Arc::ptr_eq(&other.serialize, &fmt.serialize))
+                {
+                    acceptable.push(fmt);
+                }
+            }
+        }
+
+        acceptable
+    }
+
+    fn media_types(&self) -> String {
+        self.formats
+            .iter()
+            .map(|fmt| fmt.media_type.essence_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Extract negotiation config from app data. Check both `T` and `Data<T>`, in that order, and
+    /// fall back to the default configuration.
+    fn from_req(req: &HttpRequest) -> &Self {
+        req.app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+            .unwrap_or(&DEFAULT_CONFIG)
+    }
+}
+
+static DEFAULT_CONFIG: Lazy<NegotiateConfig> = Lazy::new(NegotiateConfig::default);
+
+impl Default for NegotiateConfig {
+    fn default() -> Self {
+        let config = NegotiateConfig {
+            formats: Vec::new(),
+        };
+
+        let config = config.serializer(mime::APPLICATION_JSON, |value| {
+            serde_json::to_vec(value)
+                .map(Bytes::from)
+                .map_err(|err| JsonPayloadError::Serialize(err).into())
+        });
+
+        #[cfg(feature = "cbor")]
+        let config = config.serializer("application/cbor".parse().unwrap(), |value| {
+            let mut buf = Vec::new();
+            ciborium::into_writer(value, &mut buf)
+                .map_err(crate::error::ErrorInternalServerError)?;
+            Ok(buf.into())
+        });
+
+        config.serializer(
+            mime::TEXT_PLAIN_UTF_8,
+            |value| match serde_json::to_value(value).map_err(JsonPayloadError::Serialize)? {
+                Value::String(text) => Ok(text.into()),
+                value @ (Value::Number(_) | Value::Bool(_)) => Ok(value.to_string().into()),
+                _ => Err(ErrorNotAcceptable(
+                    "value cannot be represented as plain text",
+                )),
+            },
+        )
+    }
+}
+
+impl fmt::Debug for NegotiateConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NegotiateConfig")
+            .field("media_types", &self.media_types())
+            .finish()
+    }
+}
+
+/// Returns true if the most specific range of the `Accept` header that matches `media_type` marks it
+/// as unacceptable.
+fn is_refused(accept: &Accept, media_type: &Mime) -> bool {
+    accept
+        .iter()
+        .filter(|item| media_range_matches(&item.item, media_type))
+        .max_by_key(|item| {
+            u8::from(item.item.type_() != mime::STAR) + u8::from(item.item.subtype() != mime::STAR)
+        })
+        .is_some_and(|item| item.quality == header::Quality::ZERO)
+}
+
+/// Returns true if `media_type` is within the `Accept` header's media `range`.
+fn media_range_matches(range: &Mime, media_type: &Mime) -> bool {
+    (range.type_() == mime::STAR || range.type_() == media_type.type_())
+        && (range.subtype() == mime::STAR || range.subtype() == media_type.subtype())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{self, TestRequest},
+    };
+
+    #[derive(Serialize)]
+    struct Info {
+        name: &'static str,
+    }
+
+    async fn respond<T: Serialize>(value: T, accept: Option<&str>) -> (StatusCode, String, Bytes) {
+        let mut req = TestRequest::default();
+        if let Some(accept) = accept {
+            req = req.insert_header((header::ACCEPT, accept));
+        }
+        let req = req.to_http_request();
+
+        let res = Negotiate(value).respond_to(&req);
+        let status = res.status();
+        let content_type = res
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|ct| ct.to_str().unwrap().to_owned())
+            .unwrap_or_default();
+        let body = test::read_body(TestRequest::default().to_srv_response(res)).await;
+
+        (status, content_type, body)
+    }
+
+    #[actix_rt::test]
+    async fn defaults_to_json() {
+        let (status, ct, body) = respond(Info { name: "actix" }, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ct, "application/json");
+        assert_eq!(body, r#"{"name":"actix"}"#);
+
+        let (_, ct, _) = respond(Info { name: "actix" }, Some("*/*")).await;
+        assert_eq!(ct, "application/json");
+    }
+
+    #[actix_rt::test]
+    async fn prefers_quality() {
+        let accept = Some("application/json;q=0.5, text/*");
+
+        let (status, ct, body) = respond(42, accept).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ct, "text/plain; charset=utf-8");
+        assert_eq!(body, "42");
+
+        // not representable as plain text, so falls back to the next acceptable type
+        let (status, ct, body) = respond(Info { name: "actix" }, accept).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ct, "application/json");
+        assert_eq!(body, r#"{"name":"actix"}"#);
+
+        let (status, _, _) = respond(Info { name: "actix" }, Some("text/plain")).await;
+        assert_eq!(status, StatusCode::NOT_ACCEPTABLE);
+    }
+
+    #[actix_rt::test]
+    async fn serializes_value_directly() {
+        let value = std::collections::BTreeMap::from([(1u8, 1.0f64)]);
+
+        #[cfg(feature = "cbor")]
+        {
+            let (status, _, body) = respond(&value, Some("application/cbor")).await;
+            assert_eq!(status, StatusCode::OK);
+
+            let map: std::collections::BTreeMap<u8, f64> =
+                ciborium::from_reader(body.as_ref()).unwrap();
+            assert_eq!(map, value);
+        }
+
+        let (status, _, body) = respond(&value, Some("application/json")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, r#"{"1":1.0}"#);
+    }
+
+    #[actix_rt::test]
+    async fn not_acceptable() {
+        let (status, _, body) = respond("hi", Some("image/png")).await;
+        assert_eq!(status, StatusCode::NOT_ACCEPTABLE);
+        assert!(String::from_utf8_lossy(&body).contains("application/json"));
+
+        let (status, _, _) = respond("hi", Some("application/json;q=0, */*;q=0")).await;
+        assert_eq!(status, StatusCode::NOT_ACCEPTABLE);
+
+        let (status, ct, _) = respond("hi", Some("application/json;q=0, */*")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_ne!(ct, "application/json");
+
+        // refused ranges also apply to the media types they contain
+        let (status, ct, _) = respond("hi", Some("text/*;q=0, */*")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ct, "application/json");
+
+        let (status, _, _) = respond("hi", Some("application/*;q=0, text/*;q=0, */*")).await;
+        assert_eq!(status, StatusCode::NOT_ACCEPTABLE);
+
+        // more specific ranges take precedence
+        let (status, ct, _) = respond("hi", Some("text/*;q=0, text/plain;q=0.5")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ct, "text/plain; charset=utf-8");
+    }
+
+    #[actix_rt::test]
+    async fn custom_serializer() {
+        let config =
+            NegotiateConfig::default().serializer("application/xml".parse().unwrap(), |value| {
+                let value = serde_json::to_value(value).unwrap();
+                Ok(format!("<name>{}</name>", value["name"].as_str().unwrap()).into())
+            });
+
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, "application/xml, application/json;q=0.9"))
+            .app_data(config)
+            .to_http_request();
+
+        let res = Negotiate(Info { name: "actix" }).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/xml"
+        );
+        assert_eq!(res.headers().get(header::VARY).unwrap(), "accept");
+
+        let body = test::read_body(TestRequest::default().to_srv_response(res)).await;
+        assert_eq!(body, "<name>actix</name>");
+    }
+
+    #[cfg(feature = "cbor")]
+    #[actix_rt::test]
+    async fn cbor() {
+        let (status, ct, body) = respond(Info { name: "actix" }, Some("application/cbor")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ct, "application/cbor");
+
+        let value: Value = ciborium::from_reader(body.as_ref()).unwrap();
+        assert_eq!(value["name"], "actix");
+    }
+}
@@ -0,0 +1,449 @@
+//! For pagination extractor documentation, see [`Pagination`].
+
+use actix_utils::future::{ready, Ready};
+use serde::de::Error as _;
+
+use crate::{
+    dev::Payload,
+    error::QueryPayloadError,
+    http::header::{Link, LinkValue},
+    web, Error, FromRequest, HttpRequest,
+};
+
+/// Extract pagination parameters from the request's query string.
+///
+/// Pages can be requested either by number, using the `page` (1-based) and `per_page` parameters,
+/// or by position, using the `offset` and `limit` parameters. Mixing the two styles in one request
+/// is an error. Missing parameters fall back to the first page with the default page size.
+///
+/// Use [`PaginationConfig`] to configure the default and maximum page sizes; requested page sizes
+/// above the maximum are capped rather than rejected.
+///
+/// [`link_header`](Self::link_header) can be used to build an [RFC 8288] `Link` header pointing to
+/// the first, previous, next and last pages of a collection.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web, HttpResponse};
+///
+/// #[get("/items")]
+/// async fn items(page: web::Pagination) -> HttpResponse {
+///     let total = 95;
+///     let items = (page.offset()..total).take(page.limit() as usize).collect::<Vec<_>>();
+///
+///     let mut res = HttpResponse::Ok();
+///
+///     if let Some(link) = page.link_header(total) {
+///         res.insert_header(link);
+///     }
+///
+///     res.json(items)
+/// }
+/// ```
+///
+/// [RFC 8288]: https://datatracker.ietf.org/doc/html/rfc8288
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pagination {
+    offset: u64,
+    limit: u64,
+    style: Style,
+    url: url::Url,
+}
+
+/// Query parameter style that pagination was requested with, reused when building links.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Page,
+    Offset,
+}
+
+#[derive(Default)]
+struct Params {
+    page: Option<u64>,
+    per_page: Option<u64>,
+    offset: Option<u64>,
+    limit: Option<u64>,
+}
+
+impl Params {
+    fn parse(query_str: &str) -> Result<Self, serde_urlencoded::de::Error> {
+        let pairs = serde_urlencoded::from_str::<Vec<(String, String)>>(query_str)?;
+        let mut params = Params::default();
+
+        for (key, val) in pairs {
+            let field = match key.as_str() {
+                "page" => &mut params.page,
+                "per_page" => &mut params.per_page,
+                "offset" => &mut params.offset,
+                "limit" => &mut params.limit,
+                _ => continue,
+            };
+
+            // repeated parameters are ambiguous, e.g., when added by different layers of a client
+            if field.is_some() {
+                return Err(serde_urlencoded::de::Error::custom(format!(
+                    "duplicate `{key}` parameter"
+                )));
+            }
+
+            let val = val.parse().map_err(|_| {
+                serde_urlencoded::de::Error::custom(format!("invalid value for `{key}`"))
+            })?;
+
+            *field = Some(val);
+        }
+
+        Ok(params)
+    }
+}
+
+impl Pagination {
+    fn from_query(query_str: &str, cfg: &PaginationConfig) -> Result<(u64, u64, Style), Error> {
+        let params = Params::parse(query_str).map_err(QueryPayloadError::Deserialize)?;
+
+        let invalid = |msg: &str| -> Error {
+            QueryPayloadError::Deserialize(serde_urlencoded::de::Error::custom(msg)).into()
+        };
+
+        let by_page = params.page.is_some() || params.per_page.is_some();
+        let by_offset = params.offset.is_some() || params.limit.is_some();
+
+        if by_page && by_offset {
+            return Err(invalid(
+                "`page` and `per_page` cannot be combined with `offset` and `limit`",
+            ));
+        }
+
+        let (style, limit) = if by_offset {
+            (Style::Offset, params.limit)
+        } else {
+            (Style::Page, params.per_page)
+        };
+
+        let limit = match limit {
+            Some(0) => return Err(invalid("page size must be greater than 0")),
+            Some(limit) => limit.min(cfg.max_per_page),
+            None => cfg.default_per_page,
+        };
+
+        let offset = match style {
+            Style::Offset => params.offset.unwrap_or(0),
+            Style::Page => match params.page {
+                Some(0) => return Err(invalid("`page` must be greater than 0")),
+                Some(page) => (page - 1)
+                    .checked_mul(limit)
+                    .ok_or_else(|| invalid("`page` is too large"))?,
+                None => 0,
+            },
+        };
+
+        Ok((offset, limit, style))
+    }
+
+    /// Returns the number of items to skip.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the maximum number of items to return.
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Returns the 1-based page number.
+    ///
+    /// When requested using an `offset` that is not a multiple of the page size, this is the page
+    /// containing the first item.
+    pub fn page(&self) -> u64 {
+        self.offset / self.limit + 1
+    }
+
+    /// Returns the page size. Alias of [`limit`](Self::limit).
+    pub fn per_page(&self) -> u64 {
+        self.limit
+    }
+
+    /// Returns the number of pages needed to show `total` items; at least 1.
+    pub fn page_count(&self, total: u64) -> u64 {
+        total.div_ceil(self.limit).max(1)
+    }
+
+    /// Builds a `Link` header for a collection of `total` items.
+    ///
+    /// Links use the same query parameter style as the request and keep any other query
+    /// parameters. The `prev` and `next` links are omitted on the first and last pages,
+    /// respectively, so `None` is returned when there are no other pages to link to.
+    pub fn link_header(&self, total: u64) -> Option<Link> {
+        let last_offset = (self.page_count(total) - 1) * self.limit;
+
+        if self.offset == 0 && last_offset == 0 {
+            return None;
+        }
+
+        let mut links = vec![self.link(0, "first")];
+
+        if self.offset > 0 {
+            let prev = self.offset.saturating_sub(self.limit).min(last_offset);
+            links.push(self.link(prev, "prev"));
+        }
+
+        let next = self.offset.saturating_add(self.limit);
+        if next < total {
+            links.push(self.link(next, "next"));
+        }
+
+        links.push(self.link(last_offset, "last"));
+
+        Some(Link(links))
+    }
+
+    fn link(&self, offset: u64, rel: &str) -> LinkValue {
+        const PARAMS: [&str; 4] = ["page", "per_page", "offset", "limit"];
+
+        let mut url = self.url.clone();
+
+        let other_pairs = self
+            .url
+            .query_pairs()
+            .filter(|(key, _)| !PARAMS.contains(&key.as_ref()))
+            .collect::<Vec<_>>();
+
+        {
+            let mut pairs = url.query_pairs_mut();
+            pairs.clear().extend_pairs(other_pairs);
+
+            match self.style {
+                Style::Page => pairs
+                    .append_pair("page", &(offset / self.limit + 1).to_string())
+                    .append_pair("per_page", &self.limit.to_string()),
+                Style::Offset => pairs
+                    .append_pair("offset", &offset.to_string())
+                    .append_pair("limit", &self.limit.to_string()),
+            };
+        }
+
+        LinkValue::from(url).with_rel(rel)
+    }
+}
+
+/// See [here](#examples) for example of usage as an extractor.
+impl FromRequest for Pagination {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let cfg = PaginationConfig::from_req(req);
+
+        let res = Pagination::from_query(req.query_string(), cfg).map(|(offset, limit, style)| {
+            Pagination {
+                offset,
+                limit,
+                style,
+                url: req.full_url(),
+            }
+        });
+
+        if res.is_err() {
+            log::debug!(
+                "Failed during Pagination extractor deserialization. \
+                 Request path: {:?}",
+                req.path()
+            );
+        }
+
+        ready(res)
+    }
+}
+
+/// Configuration for the [`Pagination`] extractor.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App};
+///
+/// let app = App::new().app_data(
+///     web::PaginationConfig::default()
+///         .default_per_page(25)
+///         .max_per_page(50),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct PaginationConfig {
+    default_per_page: u64,
+    max_per_page: u64,
+}
+
+impl PaginationConfig {
+    /// Sets the page size used when none is requested. Defaults to 20.
+    ///
+    /// # Panics
+    /// Panics if `per_page` is 0.
+    pub fn default_per_page(mut self, per_page: u64) -> Self {
+        assert!(per_page > 0, "default page size must be greater than 0");
+        self.default_per_page = per_page;
+        self
+    }
+
+    /// Sets the maximum page size; larger requested sizes are capped to this value. Defaults
+    /// to 100.
+    ///
+    /// # Panics
+    /// Panics if `per_page` is 0.
+    pub fn max_per_page(mut self, per_page: u64) -> Self {
+        assert!(per_page > 0, "maximum page size must be greater than 0");
+        self.max_per_page = per_page;
+        self
+    }
+
+    /// Extract pagination config from app data. Check both `T` and `Data<T>`, in that order, and
+    /// fall back to the default configuration.
+    fn from_req(req: &HttpRequest) -> &Self {
+        req.app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+            .unwrap_or(&DEFAULT_CONFIG)
+    }
+}
+
+/// Allow shared refs used as default.
+const DEFAULT_CONFIG: PaginationConfig = PaginationConfig {
+    default_per_page: 20,
+    max_per_page: 100,
+};
+
+impl Default for PaginationConfig {
+    fn default() -> Self {
+        DEFAULT_CONFIG
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestRequest;
+
+    async fn extract(uri: &str) -> Result<Pagination, Error> {
+        let (req, mut pl) = TestRequest::with_uri(uri).to_http_parts();
+        Pagination::from_request(&req, &mut pl).await
+    }
+
+    #[actix_rt::test]
+    async fn defaults() {
+        let page = extract("/items").await.unwrap();
+        assert_eq!(page.page(), 1);
+        assert_eq!(page.offset(), 0);
+        assert_eq!(page.limit(), 20);
+    }
+
+    #[actix_rt::test]
+    async fn page_style() {
+        let page = extract("/items?page=3&per_page=10").await.unwrap();
+        assert_eq!(page.page(), 3);
+        assert_eq!(page.per_page(), 10);
+        assert_eq!(page.offset(), 20);
+
+        // capped to maximum
+        let page = extract("/items?per_page=1000").await.unwrap();
+        assert_eq!(page.per_page(), 100);
+    }
+
+    #[actix_rt::test]
+    async fn offset_style() {
+        let page = extract("/items?offset=25&limit=10").await.unwrap();
+        assert_eq!(page.offset(), 25);
+        assert_eq!(page.limit(), 10);
+        assert_eq!(page.page(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn invalid_params() {
+        assert!(extract("/items?page=0").await.is_err());
+        assert!(extract("/items?per_page=0").await.is_err());
+        assert!(extract("/items?limit=0").await.is_err());
+        assert!(extract("/items?page=abc").await.is_err());
+        assert!(extract("/items?page=2&offset=10").await.is_err());
+        assert!(extract(&format!("/items?page={}", u64::MAX)).await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn duplicate_params() {
+        let err = extract("/items?page=1&page=2").await.unwrap_err();
+        assert!(err.to_string().contains("duplicate `page` parameter"));
+        assert!(extract("/items?limit=10&offset=0&limit=20").await.is_err());
+
+        // other parameters may repeat
+        let page = extract("/items?tag=a&tag=b&page=2").await.unwrap();
+        assert_eq!(page.page(), 2);
+    }
+
+    #[actix_rt::test]
+    async fn custom_config() {
+        let (req, mut pl) = TestRequest::with_uri("/items?per_page=80")
+            .app_data(
+                PaginationConfig::default()
+                    .default_per_page(5)
+                    .max_per_page(50),
+            )
+            .to_http_parts();
+        let page = Pagination::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(page.per_page(), 50);
+
+        let (req, mut pl) = TestRequest::with_uri("/items")
+            .app_data(web::Data::new(
+                PaginationConfig::default().default_per_page(5),
+            ))
+            .to_http_parts();
+        let page = Pagination::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(page.per_page(), 5);
+    }
+
+    #[actix_rt::test]
+    async fn page_links() {
+        let page = extract("/items?sort=name&page=2&per_page=10")
+            .await
+            .unwrap();
+        let link = page.link_header(35).unwrap();
+        assert_eq!(
+            link.to_string(),
+            "<http://localhost:8080/items?sort=name&page=1&per_page=10>; rel=\"first\", \
+             <http://localhost:8080/items?sort=name&page=1&per_page=10>; rel=\"prev\", \
+             <http://localhost:8080/items?sort=name&page=3&per_page=10>; rel=\"next\", \
+             <http://localhost:8080/items?sort=name&page=4&per_page=10>; rel=\"last\""
+        );
+
+        // exactly full last page has no next link
+        let page = extract("/items?page=4&per_page=10").await.unwrap();
+        let link = page.link_header(40).unwrap();
+        assert!(link.find("next").is_none());
+        assert_eq!(
+            link.find("last").unwrap().target(),
+            "http://localhost:8080/items?page=4&per_page=10"
+        );
+
+        // single page
+        let page = extract("/items").await.unwrap();
+        assert!(page.link_header(20).is_none());
+        assert!(page.link_header(0).is_none());
+    }
+
+    #[actix_rt::test]
+    async fn offset_links() {
+        let page = extract("/items?offset=5&limit=10").await.unwrap();
+        let link = page.link_header(30).unwrap();
+        assert_eq!(
+            link.to_string(),
+            "<http://localhost:8080/items?offset=0&limit=10>; rel=\"first\", \
+             <http://localhost:8080/items?offset=0&limit=10>; rel=\"prev\", \
+             <http://localhost:8080/items?offset=15&limit=10>; rel=\"next\", \
+             <http://localhost:8080/items?offset=20&limit=10>; rel=\"last\""
+        );
+
+        // beyond the end of the collection
+        let page = extract("/items?offset=100&limit=10").await.unwrap();
+        let link = page.link_header(30).unwrap();
+        assert_eq!(
+            link.find("prev").unwrap().target(),
+            "http://localhost:8080/items?offset=20&limit=10"
+        );
+    }
+}
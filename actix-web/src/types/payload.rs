@@ -6,6 +6,7 @@ use std::{
     pin::Pin,
     str,
     task::{Context, Poll},
+    time::Duration,
 };
 
 use actix_http::error::PayloadError;
@@ -52,6 +53,71 @@ impl Payload {
         self.0
     }
 
+    /// Sets the buffered sizes at which reading the request body from the connection is paused
+    /// and resumed.
+    ///
+    /// Reading pauses once `high` bytes are buffered but not yet consumed by the handler, and
+    /// resumes once the buffer has been drained below `low` bytes. This allows tuning back-pressure
+    /// for handlers that consume large bodies slowly. Both default to 32KiB.
+    ///
+    /// Only applies to HTTP/1.x requests.
+    ///
+    /// # Panics
+    /// Panics if `low` is greater than `high`.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{web, Responder};
+    ///
+    /// async fn upload(mut body: web::Payload) -> impl Responder {
+    ///     body.set_read_watermarks(64 * 1024, 256 * 1024);
+    ///     // ...
+    ///     # ""
+    /// }
+    /// ```
+    pub fn set_read_watermarks(&mut self, low: usize, high: usize) {
+        self.0.set_read_watermarks(low, high);
+    }
+
+    /// Requires the client to send the request body at a minimum data rate.
+    ///
+    /// Once fewer than `min_rate` bytes per second, averaged over `window`, are received while
+    /// waiting for the body, the payload fails with [`PayloadError::Timeout`], which responds with
+    /// `408 Request Timeout` when returned from a handler. See [`dev::MinDataRate`] for details.
+    ///
+    /// The returned payload no longer supports [`set_read_watermarks`](Self::set_read_watermarks)
+    /// or [`trailers`](Self::trailers), so configure watermarks first.
+    ///
+    /// # Panics
+    /// Panics if `window` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use actix_web::{web, Responder};
+    ///
+    /// async fn upload(body: web::Payload) -> actix_web::Result<impl Responder> {
+    ///     let body = body.min_data_rate(1024, Duration::from_secs(10));
+    ///     let bytes = body.to_bytes().await?;
+    ///     Ok(format!("received {} bytes", bytes.len()))
+    /// }
+    /// ```
+    pub fn min_data_rate(self, min_rate: u64, window: Duration) -> Self {
+        let payload = dev::MinDataRate::new(self.0, min_rate, window);
+        Payload(dev::Payload::Stream {
+            payload: Box::pin(payload),
+        })
+    }
+
+    /// Returns the trailer fields sent after the request body, if any.
+    ///
+    /// Trailers are only available for HTTP/1.x chunked requests and only once the payload stream
+    /// has been read to its end.
+    pub fn trailers(&self) -> Option<header::HeaderMap> {
+        self.0.trailers()
+    }
+
     /// Buffers payload from request up to `limit` bytes.
     ///
     /// This method is preferred over [`Payload::to_bytes()`] since it will not lead to unexpected
@@ -270,10 +336,20 @@ fn bytes_to_string(body: Bytes, encoding: &'static Encoding) -> Result<String, E
 ///
 /// To use this, add an instance of it to your [`app`](crate::App), [`scope`](crate::Scope)
 /// or [`resource`](crate::Resource) through the associated `.app_data()` method.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App};
+///
+/// let cfg = web::PayloadConfig::new(4096)
+///     .mimetypes([mime::TEXT_PLAIN, mime::TEXT_PLAIN_UTF_8]);
+///
+/// App::new().app_data(cfg);
+/// ```
 #[derive(Clone)]
 pub struct PayloadConfig {
     limit: usize,
-    mimetype: Option<Mime>,
+    mimetypes: Option<Vec<Mime>>,
 }
 
 impl PayloadConfig {
@@ -292,17 +368,28 @@ impl PayloadConfig {
     }
 
     /// Set required mime type of the request. By default mime type is not enforced.
+    ///
+    /// Replaces any mime types previously set using this method or [`mimetypes`](Self::mimetypes).
     pub fn mimetype(mut self, mt: Mime) -> Self {
-        self.mimetype = Some(mt);
+        self.mimetypes = Some(vec![mt]);
+        self
+    }
+
+    /// Set allowed mime types of the request. By default mime type is not enforced.
+    ///
+    /// Requests are accepted if their mime type is equal to any of the given types. Replaces any
+    /// mime types previously set using this method or [`mimetype`](Self::mimetype).
+    pub fn mimetypes(mut self, mts: impl IntoIterator<Item = Mime>) -> Self {
+        self.mimetypes = Some(mts.into_iter().collect());
         self
     }
 
     fn check_mimetype(&self, req: &HttpRequest) -> Result<(), Error> {
         // check content-type
-        if let Some(ref mt) = self.mimetype {
+        if let Some(ref mts) = self.mimetypes {
             match req.mime_type() {
                 Ok(Some(ref req_mt)) => {
-                    if mt != req_mt {
+                    if !mts.contains(req_mt) {
                         return Err(ErrorBadRequest("Unexpected Content-Type"));
                     }
                 }
@@ -332,7 +419,7 @@ const DEFAULT_CONFIG_LIMIT: usize = 262_144; // 2^18 bytes (~256kB)
 /// Allow shared refs used as defaults.
 const DEFAULT_CONFIG: PayloadConfig = PayloadConfig {
     limit: DEFAULT_CONFIG_LIMIT,
-    mimetype: None,
+    mimetypes: None,
 };
 
 impl Default for PayloadConfig {
@@ -382,6 +469,7 @@ impl HttpMessageBody {
             cfg_if::cfg_if! {
                 if #[cfg(feature = "__compress")] {
                     dev::Decompress::from_headers(payload.take(), req.headers())
+                        .limit(DEFAULT_CONFIG_LIMIT)
                 } else {
                     payload.take()
                 }
@@ -406,6 +494,12 @@ impl HttpMessageBody {
                 None
             };
         }
+
+        #[cfg(feature = "__compress")]
+        {
+            self.stream = self.stream.limit(limit);
+        }
+
         self.limit = limit;
         self
     }
@@ -507,6 +601,30 @@ mod tests {
         assert!(cfg.check_mimetype(&req).is_ok());
     }
 
+    #[actix_rt::test]
+    async fn test_payload_config_mimetype_allowlist() {
+        let cfg = PayloadConfig::default().mimetypes([mime::APPLICATION_JSON, mime::TEXT_PLAIN]);
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "text/plain"))
+            .to_http_request();
+        assert!(cfg.check_mimetype(&req).is_ok());
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .to_http_request();
+        assert!(cfg.check_mimetype(&req).is_ok());
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "text/html"))
+            .to_http_request();
+        assert!(cfg.check_mimetype(&req).is_err());
+
+        // single mime type replaces allowlist
+        let cfg = cfg.mimetype(mime::TEXT_HTML);
+        assert!(cfg.check_mimetype(&req).is_ok());
+    }
+
     // allow deprecated App::data
     #[allow(deprecated)]
     #[actix_rt::test]
@@ -644,4 +762,66 @@ mod tests {
             _ => unreachable!("error"),
         }
     }
+
+    #[actix_rt::test]
+    async fn test_min_data_rate() {
+        use futures_util::stream;
+
+        let trickle = stream::unfold((), |_| async {
+            actix_rt::time::sleep(std::time::Duration::from_millis(20)).await;
+            Some((Ok(Bytes::from_static(b"a")), ()))
+        });
+        let pl = Payload(dev::Payload::Stream {
+            payload: Box::pin(trickle),
+        });
+
+        let err = pl
+            .min_data_rate(1024, Duration::from_millis(100))
+            .to_bytes()
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::REQUEST_TIMEOUT
+        );
+    }
+
+    #[cfg(feature = "compress-gzip")]
+    #[actix_rt::test]
+    async fn test_message_body_gzip() {
+        use std::io::Write as _;
+
+        use actix_http::ContentEncoding;
+        use flate2::{write::GzEncoder, Compression};
+        use futures_util::{future::ready, stream, StreamExt as _};
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(&[b'a'; 64 * 1024]).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::CONTENT_ENCODING, "gzip"))
+            .set_payload(gzipped.clone())
+            .to_http_parts();
+        let res = HttpMessageBody::new(&req, &mut pl).await;
+        assert_eq!(res.unwrap().len(), 64 * 1024);
+
+        // decompressing stops as soon as the limit is exceeded
+        let payload = stream::once(ready(Ok(Bytes::from(gzipped))));
+        let mut decoder = dev::Decompress::new(payload, ContentEncoding::Gzip).limit(1024);
+        match decoder.next().await.unwrap().unwrap_err() {
+            PayloadError::Overflow => {}
+            _ => unreachable!("error"),
+        }
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::CONTENT_ENCODING, "gzip"))
+            .set_payload(Bytes::from_static(b"\x1f\x8b\x08\x00not gzip data"))
+            .to_http_parts();
+        let res = HttpMessageBody::new(&req, &mut pl).await;
+        match res.err().unwrap() {
+            PayloadError::EncodingCorrupted => {}
+            _ => unreachable!("error"),
+        }
+    }
 }
@@ -39,8 +39,13 @@ where
             Err(err) => return Self::err(err.into()),
         };
 
+        Self::from_payload(req.take_payload(), encoding)
+    }
+
+    /// Create a new stream to read lines from a payload that has already been taken.
+    pub(crate) fn from_payload(stream: Payload<T::Stream>, encoding: &'static Encoding) -> Self {
         Readlines {
-            stream: req.take_payload(),
+            stream,
             buf: BytesMut::with_capacity(262_144),
             limit: 262_144,
             checked_buff: true,
@@ -49,13 +54,13 @@ where
         }
     }
 
-    /// Set maximum accepted payload size. The default limit is 256kB.
+    /// Set maximum accepted line size. The default limit is 256kB.
     pub fn limit(mut self, limit: usize) -> Self {
         self.limit = limit;
         self
     }
 
-    fn err(err: ReadlinesError) -> Self {
+    pub(crate) fn err(err: ReadlinesError) -> Self {
         Readlines {
             stream: Payload::None,
             buf: BytesMut::new(),
@@ -65,6 +70,19 @@ where
             err: Some(err),
         }
     }
+
+    fn decode(&self, line: &[u8]) -> Result<String, ReadlinesError> {
+        if self.encoding == UTF_8 {
+            str::from_utf8(line)
+                .map(str::to_owned)
+                .map_err(|_| ReadlinesError::EncodingError)
+        } else {
+            self.encoding
+                .decode_without_bom_handling_and_without_replacement(line)
+                .map(Cow::into_owned)
+                .ok_or(ReadlinesError::EncodingError)
+        }
+    }
 }
 
 impl<T> Stream for Readlines<T>
@@ -81,96 +99,45 @@ where
             return Poll::Ready(Some(Err(err)));
         }
 
-        // check if there is a newline in the buffer
-        if !this.checked_buff {
-            let mut found: Option<usize> = None;
-            for (ind, b) in this.buf.iter().enumerate() {
-                if *b == b'\n' {
-                    found = Some(ind);
-                    break;
-                }
-            }
-            if let Some(ind) = found {
-                // check if line is longer than limit
-                if ind + 1 > this.limit {
-                    return Poll::Ready(Some(Err(ReadlinesError::LimitOverflow)));
-                }
-                let line = if this.encoding == UTF_8 {
-                    str::from_utf8(&this.buf.split_to(ind + 1))
-                        .map_err(|_| ReadlinesError::EncodingError)?
-                        .to_owned()
-                } else {
-                    this.encoding
-                        .decode_without_bom_handling_and_without_replacement(
-                            &this.buf.split_to(ind + 1),
-                        )
-                        .map(Cow::into_owned)
-                        .ok_or(ReadlinesError::EncodingError)?
-                };
-                return Poll::Ready(Some(Ok(line)));
-            }
-            this.checked_buff = true;
-        }
-
-        // poll req for more bytes
-        match ready!(Pin::new(&mut this.stream).poll_next(cx)) {
-            Some(Ok(mut bytes)) => {
-                // check if there is a newline in bytes
-                let mut found: Option<usize> = None;
-                for (ind, b) in bytes.iter().enumerate() {
-                    if *b == b'\n' {
-                        found = Some(ind);
-                        break;
-                    }
-                }
-                if let Some(ind) = found {
+        loop {
+            // check if there is a newline in the buffer
+            if !this.checked_buff {
+                if let Some(ind) = this.buf.iter().position(|b| *b == b'\n') {
                     // check if line is longer than limit
                     if ind + 1 > this.limit {
                         return Poll::Ready(Some(Err(ReadlinesError::LimitOverflow)));
                     }
-                    let line = if this.encoding == UTF_8 {
-                        str::from_utf8(&bytes.split_to(ind + 1))
-                            .map_err(|_| ReadlinesError::EncodingError)?
-                            .to_owned()
-                    } else {
-                        this.encoding
-                            .decode_without_bom_handling_and_without_replacement(
-                                &bytes.split_to(ind + 1),
-                            )
-                            .map(Cow::into_owned)
-                            .ok_or(ReadlinesError::EncodingError)?
-                    };
-                    // extend buffer with rest of the bytes;
-                    this.buf.extend_from_slice(&bytes);
-                    this.checked_buff = false;
-                    return Poll::Ready(Some(Ok(line)));
+
+                    let line = this.buf.split_to(ind + 1);
+                    return Poll::Ready(Some(this.decode(&line)));
                 }
-                this.buf.extend_from_slice(&bytes);
-                Poll::Pending
+
+                this.checked_buff = true;
+            }
+
+            // a partial line that is already longer than limit will never become valid
+            if this.buf.len() > this.limit {
+                return Poll::Ready(Some(Err(ReadlinesError::LimitOverflow)));
             }
 
-            None => {
-                if this.buf.is_empty() {
-                    return Poll::Ready(None);
+            // poll req for more bytes
+            match ready!(Pin::new(&mut this.stream).poll_next(cx)) {
+                Some(Ok(bytes)) => {
+                    this.buf.extend_from_slice(&bytes);
+                    this.checked_buff = false;
                 }
-                if this.buf.len() > this.limit {
-                    return Poll::Ready(Some(Err(ReadlinesError::LimitOverflow)));
+
+                None => {
+                    if this.buf.is_empty() {
+                        return Poll::Ready(None);
+                    }
+
+                    let line = this.buf.split();
+                    return Poll::Ready(Some(this.decode(&line)));
                 }
-                let line = if this.encoding == UTF_8 {
-                    str::from_utf8(&this.buf)
-                        .map_err(|_| ReadlinesError::EncodingError)?
-                        .to_owned()
-                } else {
-                    this.encoding
-                        .decode_without_bom_handling_and_without_replacement(&this.buf)
-                        .map(Cow::into_owned)
-                        .ok_or(ReadlinesError::EncodingError)?
-                };
-                this.buf.clear();
-                Poll::Ready(Some(Ok(line)))
-            }
 
-            Some(Err(err)) => Poll::Ready(Some(Err(ReadlinesError::from(err)))),
+                Some(Err(err)) => return Poll::Ready(Some(Err(ReadlinesError::from(err)))),
+            }
         }
     }
 }
@@ -207,5 +174,39 @@ mod tests {
             stream.next().await.unwrap().unwrap(),
             "Contrary to popular belief, Lorem Ipsum is not simply random text."
         );
+
+        assert!(stream.next().await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn lines_split_across_chunks() {
+        let (mut sender, payload) = actix_http::h1::Payload::create(false);
+        sender.feed_data(Bytes::from_static(b"first li"));
+        sender.feed_data(Bytes::from_static(b"ne\nsecond"));
+        sender.feed_data(Bytes::from_static(b" line\nthird"));
+        sender.feed_eof();
+
+        let mut req = TestRequest::default().to_srv_request();
+        req.set_payload(payload.into());
+
+        let lines = Readlines::new(&mut req)
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(lines, ["first line\n", "second line\n", "third"]);
+    }
+
+    #[actix_rt::test]
+    async fn line_limit() {
+        let mut req = TestRequest::default()
+            .set_payload(Bytes::from_static(b"short\nthis line is too long"))
+            .to_request();
+
+        let mut stream = Readlines::new(&mut req).limit(8);
+        assert_eq!(stream.next().await.unwrap().unwrap(), "short\n");
+        assert!(matches!(
+            stream.next().await.unwrap(),
+            Err(ReadlinesError::LimitOverflow)
+        ));
     }
 }
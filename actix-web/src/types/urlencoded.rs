@@ -0,0 +1,434 @@
+//! Incremental URL encoded payload parser used by [`UrlEncoded`](super::UrlEncoded).
+
+use std::borrow::Cow;
+
+use bytes::BytesMut;
+use encoding_rs::{Encoding, UTF_8};
+use serde::de::{
+    self,
+    value::{Error, SeqDeserializer, StringDeserializer},
+    DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor,
+};
+use url::form_urlencoded;
+
+use crate::error::UrlencodedError;
+
+/// Tokenizes a URL encoded payload into decoded key/value pairs as chunks arrive.
+///
+/// Each pair is yielded by [`next_pair()`](Self::next_pair) as soon as its terminating `&` has been
+/// fed; only the trailing, not-yet-terminated part of the payload is kept in its raw form.
+///
+/// Encodings that are not ASCII-compatible cannot be safely split on `&` before decoding; for these
+/// the whole payload is buffered and its pairs are yielded once it is [finished](Self::finish).
+pub(crate) struct UrlEncodedTokenizer {
+    encoding: &'static Encoding,
+    tail: BytesMut,
+}
+
+impl UrlEncodedTokenizer {
+    pub(crate) fn new(encoding: &'static Encoding) -> Self {
+        Self {
+            encoding,
+            tail: BytesMut::new(),
+        }
+    }
+
+    /// Feeds a chunk of payload into the tokenizer.
+    pub(crate) fn feed(&mut self, chunk: &[u8]) {
+        self.tail.extend_from_slice(chunk);
+    }
+
+    /// Marks the end of the payload, terminating the final pair.
+    pub(crate) fn finish(&mut self) -> Result<(), UrlencodedError> {
+        if !self.encoding.is_ascii_compatible() {
+            let decoded = self
+                .encoding
+                .decode_without_bom_handling_and_without_replacement(&self.tail)
+                .ok_or(UrlencodedError::Encoding)?;
+
+            self.tail = BytesMut::from(decoded.as_bytes());
+            self.encoding = UTF_8;
+        }
+
+        self.tail.extend_from_slice(b"&");
+        Ok(())
+    }
+
+    /// Returns the next complete pair, or `None` if no terminated pair is buffered.
+    pub(crate) fn next_pair(&mut self) -> Result<Option<(String, String)>, UrlencodedError> {
+        if !self.encoding.is_ascii_compatible() {
+            return Ok(None);
+        }
+
+        while let Some(idx) = self.tail.iter().position(|&b| b == b'&') {
+            let raw = self.tail.split_to(idx + 1);
+
+            if let Some(pair) = self.decode_pair(&raw[..idx])? {
+                return Ok(Some(pair));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn decode_pair(&self, raw: &[u8]) -> Result<Option<(String, String)>, UrlencodedError> {
+        if raw.is_empty() {
+            return Ok(None);
+        }
+
+        let raw = if self.encoding == UTF_8 {
+            Cow::Borrowed(raw)
+        } else {
+            let decoded = self
+                .encoding
+                .decode_without_bom_handling_and_without_replacement(raw)
+                .ok_or(UrlencodedError::Encoding)?;
+
+            Cow::Owned(decoded.into_owned().into_bytes())
+        };
+
+        Ok(form_urlencoded::parse(&raw)
+            .next()
+            .map(|(key, val)| (key.into_owned(), val.into_owned())))
+    }
+}
+
+/// Deserializes a `T` from the pairs of a [finished](UrlEncodedTokenizer::finish) tokenizer.
+///
+/// Pairs are decoded one at a time as the deserializer asks for them.
+pub(crate) fn from_tokenizer<T: DeserializeOwned>(
+    tokenizer: UrlEncodedTokenizer,
+) -> Result<T, UrlencodedError> {
+    let mut de = PairsDeserializer {
+        tokenizer,
+        value: None,
+        err: None,
+    };
+
+    let res = T::deserialize(&mut de);
+
+    match de.err {
+        Some(err) => Err(err),
+        None => res.map_err(UrlencodedError::Parse),
+    }
+}
+
+/// Deserializer pulling decoded key/value pairs from a tokenizer, mirroring the behavior of
+/// `serde_urlencoded`.
+struct PairsDeserializer {
+    tokenizer: UrlEncodedTokenizer,
+
+    /// Value of the pair whose key was just deserialized.
+    value: Option<String>,
+
+    /// Decoding error of the tokenizer, reported instead of the resulting deserialization error.
+    err: Option<UrlencodedError>,
+}
+
+impl PairsDeserializer {
+    fn next_pair(&mut self) -> Result<Option<(String, String)>, Error> {
+        self.tokenizer.next_pair().map_err(|err| {
+            let msg = err.to_string();
+            self.err = Some(err);
+            de::Error::custom(msg)
+        })
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut PairsDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut remaining = 0;
+        while self.next_pair()?.is_some() {
+            remaining += 1;
+        }
+
+        if remaining > 0 {
+            return Err(de::Error::invalid_length(remaining, &"no key/value pairs"));
+        }
+
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string option bytes byte_buf
+        unit_struct newtype_struct tuple_struct struct identifier tuple enum ignored_any
+    }
+}
+
+impl<'de> de::MapAccess<'de> for PairsDeserializer {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.next_pair()? {
+            Some((key, val)) => {
+                self.value = Some(val);
+                seed.deserialize(Part(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let val = self
+            .value
+            .take()
+            .expect("MapAccess::next_value called before next_key");
+
+        seed.deserialize(Part(val))
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for PairsDeserializer {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.next_pair()? {
+            Some((key, val)) => seed.deserialize(PairDeserializer(key, val)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Deserializer of a single key/value pair as a two-element sequence.
+struct PairDeserializer(String, String);
+
+impl<'de> de::Deserializer<'de> for PairDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut seq = SeqDeserializer::new([Part(self.0), Part(self.1)].into_iter());
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(value)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string option bytes byte_buf unit
+        unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// A single decoded key or value.
+struct Part(String);
+
+impl<'de> IntoDeserializer<'de, Error> for Part {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! forward_parsed_value {
+    ($($ty:ident => $method:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.0.parse::<$ty>() {
+                    Ok(val) => val.into_deserializer().$method(visitor),
+                    Err(err) => Err(de::Error::custom(err)),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Part {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let de: StringDeserializer<Error> = self.0.into_deserializer();
+        de.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string unit bytes byte_buf unit_struct tuple_struct struct identifier tuple
+        ignored_any seq map
+    }
+
+    forward_parsed_value! {
+        bool => deserialize_bool,
+        u8 => deserialize_u8,
+        u16 => deserialize_u16,
+        u32 => deserialize_u32,
+        u64 => deserialize_u64,
+        i8 => deserialize_i8,
+        i16 => deserialize_i16,
+        i32 => deserialize_i32,
+        i64 => deserialize_i64,
+        f32 => deserialize_f32,
+        f64 => deserialize_f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use encoding_rs::{UTF_16LE, WINDOWS_1252};
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Kind {
+        Small,
+        Large,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Info {
+        hello: String,
+        counter: i64,
+        flag: Option<bool>,
+        kind: Kind,
+    }
+
+    fn parse_chunked<T: DeserializeOwned>(
+        encoding: &'static Encoding,
+        chunks: &[&[u8]],
+    ) -> Result<T, UrlencodedError> {
+        let mut tokenizer = UrlEncodedTokenizer::new(encoding);
+
+        for chunk in chunks {
+            tokenizer.feed(chunk);
+        }
+
+        tokenizer.finish()?;
+        from_tokenizer(tokenizer)
+    }
+
+    #[test]
+    fn pairs_yielded_when_terminated() {
+        let mut tokenizer = UrlEncodedTokenizer::new(UTF_8);
+
+        tokenizer.feed(b"a=1&b=");
+        assert_eq!(
+            tokenizer.next_pair().unwrap(),
+            Some(("a".to_owned(), "1".to_owned()))
+        );
+        assert_eq!(tokenizer.next_pair().unwrap(), None);
+        assert_eq!(&tokenizer.tail[..], b"b=");
+
+        tokenizer.feed(b"2&&c");
+        assert_eq!(
+            tokenizer.next_pair().unwrap(),
+            Some(("b".to_owned(), "2".to_owned()))
+        );
+        assert_eq!(tokenizer.next_pair().unwrap(), None);
+        assert_eq!(&tokenizer.tail[..], b"c");
+
+        tokenizer.finish().unwrap();
+        assert_eq!(
+            tokenizer.next_pair().unwrap(),
+            Some(("c".to_owned(), String::new()))
+        );
+        assert_eq!(tokenizer.next_pair().unwrap(), None);
+    }
+
+    #[test]
+    fn pairs_split_across_chunks() {
+        let info = parse_chunked::<Info>(
+            UTF_8,
+            &[
+                b"hel",
+                b"lo=wo%2",
+                b"0rld&coun",
+                b"ter=1",
+                b"23&",
+                b"&kind=Lar",
+                b"ge",
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            info,
+            Info {
+                hello: "wo rld".to_owned(),
+                counter: 123,
+                flag: None,
+                kind: Kind::Large,
+            }
+        );
+    }
+
+    #[test]
+    fn matches_serde_urlencoded() {
+        let body = b"a=1&b=two+words&c=%F0%9F%A6%80&a=3";
+
+        let streamed = parse_chunked::<Vec<(String, String)>>(UTF_8, &[body]).unwrap();
+        let buffered = serde_urlencoded::from_bytes::<Vec<(String, String)>>(body).unwrap();
+        assert_eq!(streamed, buffered);
+
+        let map = parse_chunked::<HashMap<String, String>>(UTF_8, &[body]).unwrap();
+        assert_eq!(map["b"], "two words");
+        assert_eq!(map["c"], "🦀");
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = parse_chunked::<Info>(UTF_8, &[b"hello=x&counter=abc&kind=Small"]).unwrap_err();
+        assert!(matches!(err, UrlencodedError::Parse(_)));
+
+        let err = parse_chunked::<Info>(UTF_8, &[b"hello=x&counter=1&kind=Medium"]).unwrap_err();
+        assert!(matches!(err, UrlencodedError::Parse(_)));
+    }
+
+    #[test]
+    fn other_encodings() {
+        let map =
+            parse_chunked::<HashMap<String, String>>(WINDOWS_1252, &[b"a=\xe9&", b"b=2"]).unwrap();
+        assert_eq!(map["a"], "é");
+        assert_eq!(map["b"], "2");
+
+        let body = "a=1&b=2"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+        let (first, second) = body.split_at(5);
+        let map = parse_chunked::<HashMap<String, String>>(UTF_16LE, &[first, second]).unwrap();
+        assert_eq!(map["a"], "1");
+        assert_eq!(map["b"], "2");
+    }
+}
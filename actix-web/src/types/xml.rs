@@ -0,0 +1,532 @@
+//! For XML helper documentation, see [`Xml`].
+
+use std::{
+    fmt,
+    future::Future,
+    ops,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use actix_http::Payload;
+use bytes::BytesMut;
+use futures_core::future::LocalBoxFuture;
+use futures_util::{FutureExt as _, StreamExt as _};
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(feature = "__compress")]
+use crate::dev::Decompress;
+use crate::{
+    body::EitherBody,
+    error::{Error, XmlError, XmlPayloadError},
+    extract::FromRequest,
+    http::header::{ContentLength, Header as _},
+    request::HttpRequest,
+    web, HttpMessage, HttpResponse, Responder,
+};
+
+/// XML extractor and responder.
+///
+/// `Xml` has two uses: XML responses, and extracting typed data from XML request payloads.
+///
+/// Values are mapped to XML by [`quick-xml`], the same way for both uses:
+/// - struct fields and map entries are child elements;
+/// - fields renamed to start with `@` (e.g., `#[serde(rename = "@id")]`) are attributes;
+/// - a field renamed to `$text` is the element's text content;
+/// - sequences are repeated elements with the name of the field.
+///
+/// See the [`quick-xml` documentation][mapping] for the complete mapping, including enums.
+///
+/// This type is only available with the `xml` crate feature enabled.
+///
+/// # Extractor
+/// To extract typed data from a request body, the inner type `T` must implement the
+/// [`serde::Deserialize`] trait. The name of the root element is not checked.
+///
+/// Use [`XmlConfig`] to configure extraction options.
+///
+/// ```
+/// use actix_web::{post, web};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Order {
+///     #[serde(rename = "@id")]
+///     id: u64,
+///     #[serde(rename = "item")]
+///     items: Vec<String>,
+/// }
+///
+/// /// deserialize `<order id="1"><item>tea</item><item>cake</item></order>`
+/// #[post("/orders")]
+/// async fn create(order: web::Xml<Order>) -> String {
+///     format!("order {} has {} items", order.id, order.items.len())
+/// }
+/// ```
+///
+/// # Responder
+/// A handler may return a value of type `Xml<T>` where `T` is a struct that implements
+/// [`serde::Serialize`]. The root element is named after the struct.
+///
+/// ```
+/// use actix_web::{get, web};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// #[serde(rename = "status")]
+/// struct Status {
+///     healthy: bool,
+/// }
+///
+/// /// responds with `<status><healthy>true</healthy></status>`
+/// #[get("/status")]
+/// async fn status() -> web::Xml<Status> {
+///     web::Xml(Status { healthy: true })
+/// }
+/// ```
+///
+/// [`quick-xml`]: https://docs.rs/quick-xml
+/// [mapping]: https://docs.rs/quick-xml/0.37/quick_xml/de/index.html#mapping-xml-to-rust-types
+#[derive(Debug)]
+pub struct Xml<T>(pub T);
+
+impl<T> Xml<T> {
+    /// Unwrap into inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> ops::Deref for Xml<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> ops::DerefMut for Xml<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Xml<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// Creates response with OK status code, correct content type header, and serialized XML payload.
+impl<T: Serialize> Responder for Xml<T> {
+    type Body = EitherBody<String>;
+
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse<Self::Body> {
+        match quick_xml::se::to_string(&self.0) {
+            Ok(body) => match HttpResponse::Ok()
+                .content_type("application/xml; charset=utf-8")
+                .message_body(body)
+            {
+                Ok(res) => res.map_into_left_body(),
+                Err(err) => HttpResponse::from_error(err).map_into_right_body(),
+            },
+
+            Err(err) => HttpResponse::from_error(XmlPayloadError::Serialize(XmlError::new(err)))
+                .map_into_right_body(),
+        }
+    }
+}
+
+/// See [here](#extractor) for example of usage as an extractor.
+impl<T: DeserializeOwned + 'static> FromRequest for Xml<T> {
+    type Error = Error;
+    type Future = XmlExtractFut<T>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = XmlConfig::from_req(req);
+
+        XmlExtractFut {
+            req: Some(req.clone()),
+            fut: XmlBody::new(req, payload).limit(config.limit),
+            err_handler: config.err_handler.clone(),
+        }
+    }
+}
+
+type XmlErrorHandler = Option<Arc<dyn Fn(XmlPayloadError, &HttpRequest) -> Error + Send + Sync>>;
+
+pub struct XmlExtractFut<T> {
+    req: Option<HttpRequest>,
+    fut: XmlBody<T>,
+    err_handler: XmlErrorHandler,
+}
+
+impl<T: DeserializeOwned + 'static> Future for XmlExtractFut<T> {
+    type Output = Result<Xml<T>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let res = match futures_core::ready!(Pin::new(&mut this.fut).poll(cx)) {
+            Err(err) => {
+                let req = this.req.take().unwrap();
+                log::debug!(
+                    "Failed to deserialize Xml from payload. Request path: {}",
+                    req.path()
+                );
+
+                match this.err_handler.as_ref() {
+                    Some(err_handler) => Err((*err_handler)(err, &req)),
+                    None => Err(err.into()),
+                }
+            }
+            Ok(data) => Ok(Xml(data)),
+        };
+
+        Poll::Ready(res)
+    }
+}
+
+/// `Xml` extractor configuration.
+///
+/// # Examples
+/// ```
+/// use actix_web::{error, web, App, HttpResponse};
+///
+/// let xml_cfg = web::XmlConfig::default()
+///     // limit request payload size
+///     .limit(4096)
+///     // use custom error handler
+///     .error_handler(|err, req| {
+///         error::InternalError::from_response(err, HttpResponse::Conflict().into()).into()
+///     });
+///
+/// App::new().app_data(xml_cfg);
+/// ```
+#[derive(Clone)]
+pub struct XmlConfig {
+    limit: usize,
+    err_handler: XmlErrorHandler,
+}
+
+impl XmlConfig {
+    /// Set maximum accepted payload size. By default this limit is 256kB.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Set custom error handler.
+    pub fn error_handler<F>(mut self, f: F) -> Self
+    where
+        F: Fn(XmlPayloadError, &HttpRequest) -> Error + Send + Sync + 'static,
+    {
+        self.err_handler = Some(Arc::new(f));
+        self
+    }
+
+    /// Extract payload config from app data. Check both `T` and `Data<T>`, in that order, and fall
+    /// back to the default payload config.
+    fn from_req(req: &HttpRequest) -> &Self {
+        req.app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+            .unwrap_or(&DEFAULT_CONFIG)
+    }
+}
+
+const DEFAULT_LIMIT: usize = 262_144; // 2^18 bytes (~256kB)
+
+/// Allow shared refs used as default.
+const DEFAULT_CONFIG: XmlConfig = XmlConfig {
+    limit: DEFAULT_LIMIT,
+    err_handler: None,
+};
+
+impl Default for XmlConfig {
+    fn default() -> Self {
+        DEFAULT_CONFIG
+    }
+}
+
+/// Future that resolves to some `T` when parsed from an XML payload.
+///
+/// Returns error if:
+/// - `Content-Type` is not `application/xml`, `text/xml`, or a `+xml` type.
+/// - The payload is larger than the [limit](XmlBody::limit()), according to `Content-Length` or
+///   as it is received.
+/// - The payload, when consumed, is not valid XML or does not match `T`.
+pub struct XmlBody<T> {
+    #[cfg(feature = "__compress")]
+    stream: Option<Decompress<Payload>>,
+    #[cfg(not(feature = "__compress"))]
+    stream: Option<Payload>,
+
+    limit: usize,
+    length: Option<usize>,
+    err: Option<XmlPayloadError>,
+    fut: Option<LocalBoxFuture<'static, Result<T, XmlPayloadError>>>,
+}
+
+impl<T> XmlBody<T> {
+    /// Create a new future to decode an XML request payload.
+    pub fn new(req: &HttpRequest, payload: &mut Payload) -> Self {
+        let is_xml = match req.mime_type() {
+            Ok(Some(mime)) => mime.subtype() == mime::XML || mime.suffix() == Some(mime::XML),
+            _ => false,
+        };
+
+        if !is_xml {
+            return Self::err(XmlPayloadError::ContentType);
+        }
+
+        let length = ContentLength::parse(req).ok().map(|len| len.0);
+
+        let payload = {
+            cfg_if::cfg_if! {
+                if #[cfg(feature = "__compress")] {
+                    Decompress::from_headers(payload.take(), req.headers()).limit(DEFAULT_LIMIT)
+                } else {
+                    payload.take()
+                }
+            }
+        };
+
+        XmlBody {
+            stream: Some(payload),
+            limit: DEFAULT_LIMIT,
+            length,
+            err: None,
+            fut: None,
+        }
+    }
+
+    fn err(err: XmlPayloadError) -> Self {
+        XmlBody {
+            stream: None,
+            limit: DEFAULT_LIMIT,
+            length: None,
+            err: Some(err),
+            fut: None,
+        }
+    }
+
+    /// Set maximum accepted payload size. The default limit is 256kB.
+    pub fn limit(mut self, limit: usize) -> Self {
+        #[cfg(feature = "__compress")]
+        {
+            self.stream = self.stream.take().map(|stream| stream.limit(limit));
+        }
+
+        self.limit = limit;
+        self
+    }
+}
+
+impl<T: DeserializeOwned + 'static> Future for XmlBody<T> {
+    type Output = Result<T, XmlPayloadError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(ref mut fut) = self.fut {
+            return Pin::new(fut).poll(cx);
+        }
+
+        if let Some(err) = self.err.take() {
+            return Poll::Ready(Err(err));
+        }
+
+        // payload size
+        let limit = self.limit;
+        if let Some(len) = self.length.take() {
+            if len > limit {
+                return Poll::Ready(Err(XmlPayloadError::Overflow { size: len, limit }));
+            }
+        }
+
+        let mut stream = self.stream.take().unwrap();
+
+        self.fut = Some(
+            async move {
+                let mut body = BytesMut::with_capacity(8192);
+
+                while let Some(item) = stream.next().await {
+                    let chunk = item?;
+
+                    let size = body.len() + chunk.len();
+                    if size > limit {
+                        return Err(XmlPayloadError::Overflow { size, limit });
+                    }
+
+                    body.extend_from_slice(&chunk);
+                }
+
+                quick_xml::de::from_reader(body.as_ref())
+                    .map_err(|err| XmlPayloadError::Deserialize(XmlError::new(err)))
+            }
+            .boxed_local(),
+        );
+
+        self.poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{
+        http::{
+            header::{CONTENT_LENGTH, CONTENT_TYPE},
+            StatusCode,
+        },
+        test::{assert_body_eq, TestRequest},
+        ResponseError as _,
+    };
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename = "order")]
+    struct Order {
+        #[serde(rename = "@id")]
+        id: u64,
+        customer: Customer,
+        #[serde(rename = "item", default)]
+        items: Vec<Item>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        note: Option<String>,
+        status: Status,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Customer {
+        name: String,
+        vip: bool,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Item {
+        #[serde(rename = "@qty")]
+        qty: u32,
+        #[serde(rename = "$text")]
+        name: String,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Status {
+        Pending,
+        Shipped,
+    }
+
+    fn order() -> Order {
+        Order {
+            id: 7,
+            customer: Customer {
+                name: "Ferris & Co".to_owned(),
+                vip: true,
+            },
+            items: vec![
+                Item {
+                    qty: 2,
+                    name: "<tea>".to_owned(),
+                },
+                Item {
+                    qty: 1,
+                    name: "cake".to_owned(),
+                },
+            ],
+            note: None,
+            status: Status::Shipped,
+        }
+    }
+
+    const ORDER_XML: &str = "<order id=\"7\"><customer><name>Ferris &amp; Co</name><vip>true</vip>\
+        </customer><item qty=\"2\">&lt;tea&gt;</item><item qty=\"1\">cake</item><status>shipped\
+        </status></order>";
+
+    #[actix_rt::test]
+    async fn responder() {
+        let req = TestRequest::default().to_http_request();
+        let res = Xml(order()).respond_to(&req);
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            "application/xml; charset=utf-8"
+        );
+        assert_body_eq!(res, ORDER_XML.as_bytes());
+
+        // no root element name
+        let res = Xml(42).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[actix_rt::test]
+    async fn extractor() {
+        for content_type in [
+            "application/xml",
+            "text/xml; charset=utf-8",
+            "application/atom+xml",
+        ] {
+            let (req, mut pl) = TestRequest::default()
+                .insert_header((CONTENT_TYPE, content_type))
+                .set_payload(Bytes::from_static(ORDER_XML.as_bytes()))
+                .to_http_parts();
+
+            let Xml(order) = Xml::<Order>::from_request(&req, &mut pl).await.unwrap();
+            assert_eq!(order, self::order());
+        }
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/json"))
+            .set_payload(Bytes::from_static(ORDER_XML.as_bytes()))
+            .to_http_parts();
+        let err = XmlBody::<Order>::new(&req, &mut pl).await.unwrap_err();
+        assert!(matches!(err, XmlPayloadError::ContentType));
+        assert_eq!(err.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/xml"))
+            .set_payload(Bytes::from_static(b"<order>"))
+            .to_http_parts();
+        let err = Xml::<Order>::from_request(&req, &mut pl).await.unwrap_err();
+        assert_eq!(err.error_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_rt::test]
+    async fn limit() {
+        // known length
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/xml"))
+            .insert_header((CONTENT_LENGTH, 10_000))
+            .set_payload(Bytes::from_static(ORDER_XML.as_bytes()))
+            .to_http_parts();
+        let err = XmlBody::<Order>::new(&req, &mut pl)
+            .limit(100)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            XmlPayloadError::Overflow {
+                size: 10_000,
+                limit: 100
+            }
+        ));
+
+        // streamed payload, with custom error handler
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/xml"))
+            .app_data(XmlConfig::default().limit(100).error_handler(|err, _| {
+                assert!(
+                    matches!(err, XmlPayloadError::Overflow { size, limit: 100 } if size > 100)
+                );
+                crate::error::ErrorConflict("too big")
+            }))
+            .set_payload(Bytes::from_static(ORDER_XML.as_bytes()))
+            .to_http_parts();
+        let err = Xml::<Order>::from_request(&req, &mut pl).await.unwrap_err();
+        assert_eq!(err.error_response().status(), StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,341 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use actix_http::error::PayloadError;
+use actix_utils::future::{err, ok, Ready};
+use bytes::Bytes;
+use futures_core::{ready, Stream};
+use pin_project_lite::pin_project;
+
+use crate::{
+    dev::Payload, error::ErrorInternalServerError, Error, FromRequest, HttpMessage as _,
+    HttpRequest,
+};
+
+/// Progress of a request body upload.
+///
+/// A handle is placed in the request's extensions by the
+/// [`UploadTracker`](crate::middleware::UploadTracker) middleware and is updated as the request
+/// body is read, no matter which extractor reads it. Handlers can extract the handle to register
+/// further listeners, e.g., to forward progress to a WebSocket or store it for a progress endpoint
+/// keyed by the [request ID](Self::request_id).
+///
+/// Multipart parsers report the progress of the part being read using
+/// [`start_part()`](Self::start_part), [`record_part()`](Self::record_part), and
+/// [`finish_part()`](Self::finish_part). `actix-multipart` does so automatically.
+///
+/// All clones share the same state. Extraction fails with a `500 Internal Server Error` if the
+/// `UploadTracker` middleware is not registered.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web, HttpResponse};
+///
+/// #[post("/upload")]
+/// async fn upload(
+///     progress: web::UploadProgress,
+///     body: web::Payload,
+/// ) -> actix_web::Result<HttpResponse> {
+///     progress.on_progress(|progress| {
+///         log::debug!("[{}] received {} bytes", progress.request_id(), progress.received());
+///     });
+///
+///     let body = body.to_bytes().await?;
+///     Ok(HttpResponse::Ok().body(format!("received {} bytes", body.len())))
+/// }
+/// ```
+#[derive(Clone)]
+pub struct UploadProgress {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    request_id: String,
+    total: Option<u64>,
+    state: RefCell<State>,
+    listeners: RefCell<Vec<Listener>>,
+}
+
+type Listener = Rc<dyn Fn(&UploadProgress)>;
+
+#[derive(Default)]
+struct State {
+    received: u64,
+    complete: bool,
+    parts: usize,
+    part: Option<PartProgress>,
+}
+
+/// Progress of a single part of a multipart upload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartProgress {
+    index: usize,
+    name: Option<String>,
+    filename: Option<String>,
+    received: u64,
+    complete: bool,
+}
+
+impl PartProgress {
+    /// Returns the zero-based position of the part in the request body.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the form field name of the part, if known.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the file name of the part, if known.
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// Returns the number of bytes of part content received so far.
+    pub fn received(&self) -> u64 {
+        self.received
+    }
+
+    /// Returns true if the part has been read in full.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+}
+
+impl UploadProgress {
+    pub(crate) fn new(request_id: String, total: Option<u64>) -> Self {
+        Self {
+            inner: Rc::new(Inner {
+                request_id,
+                total,
+                state: RefCell::default(),
+                listeners: RefCell::default(),
+            }),
+        }
+    }
+
+    /// Returns ID of the request, as reported by [`RequestContext`](crate::web::RequestContext).
+    pub fn request_id(&self) -> &str {
+        &self.inner.request_id
+    }
+
+    /// Returns the number of body bytes received so far.
+    pub fn received(&self) -> u64 {
+        self.inner.state.borrow().received
+    }
+
+    /// Returns the size of the body as stated by the `Content-Length` header, if known.
+    pub fn total(&self) -> Option<u64> {
+        self.inner.total
+    }
+
+    /// Returns true if the body has been read in full.
+    pub fn is_complete(&self) -> bool {
+        self.inner.state.borrow().complete
+    }
+
+    /// Returns the progress of the multipart part currently or most recently read, if any.
+    pub fn part(&self) -> Option<PartProgress> {
+        self.inner.state.borrow().part.clone()
+    }
+
+    /// Registers a listener that is called whenever progress is made.
+    ///
+    /// Listeners are passed the handle itself; capturing a clone of it in the listener creates a
+    /// reference cycle that leaks the request's progress state.
+    pub fn on_progress(&self, listener: impl Fn(&UploadProgress) + 'static) {
+        self.inner.listeners.borrow_mut().push(Rc::new(listener));
+    }
+
+    /// Records the start of a new multipart part, which replaces the previous part.
+    pub fn start_part(&self, name: Option<&str>, filename: Option<&str>) {
+        {
+            let mut state = self.inner.state.borrow_mut();
+            let index = state.parts;
+            state.parts += 1;
+            state.part = Some(PartProgress {
+                index,
+                name: name.map(str::to_owned),
+                filename: filename.map(str::to_owned),
+                received: 0,
+                complete: false,
+            });
+        }
+
+        self.notify();
+    }
+
+    /// Records `len` bytes of content received for the current multipart part.
+    pub fn record_part(&self, len: usize) {
+        if let Some(part) = &mut self.inner.state.borrow_mut().part {
+            part.received += len as u64;
+        }
+
+        self.notify();
+    }
+
+    /// Records that the current multipart part has been read in full.
+    pub fn finish_part(&self) {
+        if let Some(part) = &mut self.inner.state.borrow_mut().part {
+            part.complete = true;
+        }
+
+        self.notify();
+    }
+
+    pub(crate) fn record(&self, len: usize) {
+        self.inner.state.borrow_mut().received += len as u64;
+        self.notify();
+    }
+
+    pub(crate) fn complete(&self) {
+        self.inner.state.borrow_mut().complete = true;
+        self.notify();
+    }
+
+    fn notify(&self) {
+        // listeners may register further listeners
+        let listeners = self.inner.listeners.borrow().clone();
+
+        for listener in listeners {
+            listener(self);
+        }
+    }
+}
+
+impl fmt::Debug for UploadProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.inner.state.borrow();
+
+        f.debug_struct("UploadProgress")
+            .field("request_id", &self.inner.request_id)
+            .field("received", &state.received)
+            .field("total", &self.inner.total)
+            .field("complete", &state.complete)
+            .field("part", &state.part)
+            .finish()
+    }
+}
+
+impl FromRequest for UploadProgress {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        match req.extensions().get::<Self>() {
+            Some(progress) => ok(progress.clone()),
+            None => {
+                log::debug!(
+                    "Failed to extract `UploadProgress`: `UploadTracker` middleware is not \
+                    registered"
+                );
+                err(ErrorInternalServerError(
+                    "Missing expected request extension data",
+                ))
+            }
+        }
+    }
+}
+
+pin_project! {
+    /// Payload stream that records the bytes it yields to an [`UploadProgress`].
+    pub(crate) struct ProgressPayload<S> {
+        #[pin]
+        stream: S,
+        progress: UploadProgress,
+    }
+}
+
+impl<S> ProgressPayload<S> {
+    pub(crate) fn new(stream: S, progress: UploadProgress) -> Self {
+        Self { stream, progress }
+    }
+}
+
+impl<S> Stream for ProgressPayload<S>
+where
+    S: Stream<Item = Result<Bytes, PayloadError>>,
+{
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        let item = ready!(this.stream.poll_next(cx));
+
+        match &item {
+            Some(Ok(chunk)) => this.progress.record(chunk.len()),
+            Some(Err(_)) => {}
+            None if !this.progress.is_complete() => this.progress.complete(),
+            None => {}
+        }
+
+        Poll::Ready(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use futures_util::{stream, StreamExt as _};
+
+    use super::*;
+
+    #[actix_rt::test]
+    async fn records_payload_progress() {
+        let progress = UploadProgress::new("abc".to_owned(), Some(4));
+
+        let events = Rc::new(Cell::new(0));
+        progress.on_progress({
+            let events = Rc::clone(&events);
+            move |_| events.set(events.get() + 1)
+        });
+
+        let chunks = stream::iter([Ok(Bytes::from_static(b"abc")), Ok(Bytes::from_static(b"d"))]);
+        let mut payload = Box::pin(ProgressPayload::new(chunks, progress.clone()));
+
+        payload.next().await.unwrap().unwrap();
+        assert_eq!(progress.received(), 3);
+        assert!(!progress.is_complete());
+
+        payload.next().await.unwrap().unwrap();
+        assert!(payload.next().await.is_none());
+        assert!(payload.next().await.is_none());
+        assert_eq!(progress.received(), 4);
+        assert_eq!(progress.total(), Some(4));
+        assert!(progress.is_complete());
+        assert_eq!(events.get(), 3);
+    }
+
+    #[test]
+    fn records_part_progress() {
+        let progress = UploadProgress::new("abc".to_owned(), None);
+        assert!(progress.part().is_none());
+
+        progress.start_part(Some("avatar"), Some("me.png"));
+        progress.record_part(10);
+        progress.record_part(5);
+
+        let part = progress.part().unwrap();
+        assert_eq!(part.index(), 0);
+        assert_eq!(part.name(), Some("avatar"));
+        assert_eq!(part.filename(), Some("me.png"));
+        assert_eq!(part.received(), 15);
+        assert!(!part.is_complete());
+
+        progress.finish_part();
+        assert!(progress.part().unwrap().is_complete());
+
+        progress.start_part(Some("caption"), None);
+        let part = progress.part().unwrap();
+        assert_eq!(part.index(), 1);
+        assert_eq!(part.received(), 0);
+    }
+}
@@ -4,16 +4,26 @@
 //! - [`Data`]: Application data item
 //! - [`ThinData`]: Cheap-to-clone application data item
 //! - [`ReqData`]: Request-local data item
+//! - [`RequestRng`]: Request-scoped random number generator
+//! - [`RequestContext`]: Request context for spawned tasks
+//! - [`Clock`]: Application clock
+//! - [`Cache`]: Shared in-memory cache with expiring entries
 //! - [`Path`]: URL path parameters / dynamic segments
 //! - [`Query`]: URL query parameters
+//! - [`Pagination`]: Pagination query parameters
 //! - [`Header`]: Typed header
 //! - [`Json`]: JSON payload
+//! - [`NdJson`]: Newline-delimited JSON payload stream
 //! - [`Form`]: URL-encoded payload
+//! - [`Xml`]: XML payload (requires the `xml` crate feature)
 //! - [`Bytes`]: Raw payload
 //!
 //! # Responders
 //! - [`Json`]: JSON response
+//! - [`JsonStream`]: JSON array response streamed from a `Stream`
+//! - [`Negotiate`]: Response serialized according to the `Accept` header
 //! - [`Form`]: URL-encoded response
+//! - [`Xml`]: XML response (requires the `xml` crate feature)
 //! - [`Bytes`]: Raw bytes response
 //! - [`Redirect`](Redirect::to): Convenient redirect responses
 
@@ -23,8 +33,17 @@ use actix_router::IntoPatterns;
 pub use bytes::{Buf, BufMut, Bytes, BytesMut};
 
 pub use crate::{
-    config::ServiceConfig, data::Data, redirect::Redirect, request_data::ReqData,
-    thin_data::ThinData, types::*,
+    cache::Cache,
+    clock::Clock,
+    config::{AppModule, ServiceConfig},
+    data::Data,
+    redirect::Redirect,
+    request_context::RequestContext,
+    request_data::ReqData,
+    request_rng::RequestRng,
+    thin_data::ThinData,
+    types::*,
+    upload_progress::{PartProgress, UploadProgress},
 };
 use crate::{
     error::BlockingError, http::Method, service::WebService, FromRequest, Handler, Resource,
@@ -0,0 +1,586 @@
+//! Verification of signed webhook requests.
+//!
+//! Webhook providers sign each delivery with a secret shared with the receiver so that endpoints
+//! can reject requests that did not come from the provider. The [`Webhook`] extractor buffers the
+//! raw request body, verifies its signature according to the registered [`WebhookConfig`] and only
+//! then hands the body to the handler.
+//!
+//! Signatures are HMAC-SHA256 digests compared in constant time. Schemes that sign a timestamp
+//! along with the body also reject deliveries outside of a tolerance window to limit replay
+//! attacks. The current time is read from the [`Clock`](crate::web::Clock) app data, if
+//! registered, so that tolerance checks can be tested deterministically.
+//!
+//! # Examples
+//! ```
+//! use actix_web::{
+//!     post, web,
+//!     webhook::{Webhook, WebhookConfig},
+//!     App, Error, HttpResponse,
+//! };
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct PushEvent {
+//!     after: String,
+//! }
+//!
+//! #[post("/hooks/github")]
+//! async fn github(hook: Webhook) -> Result<HttpResponse, Error> {
+//!     let event = hook.json::<PushEvent>()?;
+//!     log::info!("pushed {}", event.after);
+//!     Ok(HttpResponse::NoContent().finish())
+//! }
+//!
+//! let app = App::new().service(
+//!     web::scope("")
+//!         .app_data(WebhookConfig::github("It's a Secret to Everybody"))
+//!         .service(github),
+//! );
+//! ```
+
+use std::{
+    borrow::Cow,
+    fmt,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bytes::Bytes;
+use derive_more::derive::{Display, Error};
+use futures_core::future::LocalBoxFuture;
+use hmac::{Hmac, Mac as _};
+use serde::de::DeserializeOwned;
+use sha2::Sha256;
+
+use crate::{
+    dev::Payload,
+    error::{as_std_error, JsonPayloadError, PayloadError},
+    http::{header::HeaderName, StatusCode},
+    types::HttpMessageBody,
+    web, FromRequest, HttpRequest, ResponseError,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Errors that can occur when verifying a webhook request.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum WebhookError {
+    /// No [`WebhookConfig`] was registered for the resource.
+    #[display("Webhook configuration is missing")]
+    MissingConfig,
+
+    /// The signature header was missing or malformed.
+    #[display("Webhook signature is missing or malformed")]
+    MissingSignature,
+
+    /// The timestamp was missing or malformed.
+    #[display("Webhook timestamp is missing or malformed")]
+    MissingTimestamp,
+
+    /// The timestamp was outside of the configured tolerance.
+    #[display("Webhook timestamp is outside of the tolerance window")]
+    Expired,
+
+    /// The signature did not match the payload.
+    #[display("Webhook signature does not match payload")]
+    InvalidSignature,
+
+    /// Error reading the payload.
+    #[display("Error that occur during reading payload: {}", _0)]
+    Payload(PayloadError),
+}
+
+impl ResponseError for WebhookError {
+    as_std_error!();
+
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::MissingConfig => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Payload(err) => err.status_code(),
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+/// How signatures are carried in a request.
+#[derive(Debug, Clone)]
+enum Scheme {
+    /// Hex-encoded signature in a header, optionally with a prefix and a separate timestamp header.
+    Header {
+        prefix: Cow<'static, str>,
+        timestamp: Option<HeaderName>,
+    },
+
+    /// Stripe-style `t=<timestamp>,v1=<signature>` header.
+    Stripe,
+}
+
+/// Webhook signature verification configuration.
+///
+/// Register as app data on the resources that receive webhooks; [`Webhook`] extraction fails with
+/// a 500 response if no configuration is found.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{http::header::HeaderName, webhook::WebhookConfig};
+///
+/// // signature in `X-Signature: <hex>` over `<timestamp>.<body>`, timestamp in `X-Timestamp`
+/// let config = WebhookConfig::hmac_sha256("secret")
+///     .timestamp_header(HeaderName::from_static("x-timestamp"))
+///     .tolerance(Duration::from_secs(60))
+///     .limit(1024 * 1024);
+/// ```
+#[derive(Clone)]
+pub struct WebhookConfig {
+    secret: Arc<[u8]>,
+    header: HeaderName,
+    scheme: Scheme,
+    tolerance: Duration,
+    limit: usize,
+}
+
+impl WebhookConfig {
+    /// Verifies a hex-encoded HMAC-SHA256 signature of the body in the `X-Signature` header.
+    pub fn hmac_sha256(secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            secret: secret.into().into(),
+            header: HeaderName::from_static("x-signature"),
+            scheme: Scheme::Header {
+                prefix: Cow::Borrowed(""),
+                timestamp: None,
+            },
+            tolerance: Duration::from_secs(300),
+            limit: 262_144,
+        }
+    }
+
+    /// Verifies GitHub-style signatures in the `X-Hub-Signature-256: sha256=<hex>` header.
+    pub fn github(secret: impl Into<Vec<u8>>) -> Self {
+        Self::hmac_sha256(secret)
+            .signature_header(HeaderName::from_static("x-hub-signature-256"))
+            .signature_prefix("sha256=")
+    }
+
+    /// Verifies Stripe-style signatures in the `Stripe-Signature: t=<timestamp>,v1=<hex>` header.
+    ///
+    /// The signature covers `<timestamp>.<body>` and any of multiple `v1` signatures may match.
+    pub fn stripe(secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            scheme: Scheme::Stripe,
+            ..Self::hmac_sha256(secret)
+                .signature_header(HeaderName::from_static("stripe-signature"))
+        }
+    }
+
+    /// Sets the name of the header containing the signature.
+    pub fn signature_header(mut self, name: HeaderName) -> Self {
+        self.header = name;
+        self
+    }
+
+    /// Sets a prefix that precedes the hex-encoded signature, e.g., `sha256=`.
+    ///
+    /// Has no effect on Stripe-style signatures.
+    pub fn signature_prefix(mut self, prefix: impl Into<Cow<'static, str>>) -> Self {
+        if let Scheme::Header {
+            prefix: ref mut pfx,
+            ..
+        } = self.scheme
+        {
+            *pfx = prefix.into();
+        }
+        self
+    }
+
+    /// Sets the name of a header containing the delivery's UNIX timestamp, in seconds.
+    ///
+    /// When set, the signature is expected to cover `<timestamp>.<body>` and deliveries outside of
+    /// the [tolerance](Self::tolerance) window are rejected. Has no effect on Stripe-style
+    /// signatures, which always include a timestamp.
+    pub fn timestamp_header(mut self, name: HeaderName) -> Self {
+        if let Scheme::Header {
+            ref mut timestamp, ..
+        } = self.scheme
+        {
+            *timestamp = Some(name);
+        }
+        self
+    }
+
+    /// Sets how far a signed timestamp may be from the current time. Defaults to 5 minutes.
+    pub fn tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Sets the maximum accepted payload size in bytes. Defaults to 256kB.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Extract webhook config from app data. Check both `T` and `Data<T>`, in that order.
+    fn from_req(req: &HttpRequest) -> Option<&Self> {
+        req.app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+    }
+
+    /// Parses the signature (and timestamp) from request headers and checks the timestamp.
+    fn expected(&self, req: &HttpRequest, now: SystemTime) -> Result<Expected, WebhookError> {
+        let header = req
+            .headers()
+            .get(&self.header)
+            .and_then(|hdr| hdr.to_str().ok())
+            .ok_or(WebhookError::MissingSignature)?;
+
+        let expected = match &self.scheme {
+            Scheme::Header { prefix, timestamp } => {
+                let signature = header
+                    .trim()
+                    .strip_prefix(prefix.as_ref())
+                    .and_then(decode_hex)
+                    .ok_or(WebhookError::MissingSignature)?;
+
+                let timestamp = match timestamp {
+                    Some(name) => Some(
+                        req.headers()
+                            .get(name)
+                            .and_then(|hdr| hdr.to_str().ok())
+                            .and_then(|ts| ts.trim().parse().ok())
+                            .ok_or(WebhookError::MissingTimestamp)?,
+                    ),
+                    None => None,
+                };
+
+                Expected {
+                    timestamp,
+                    signatures: vec![signature],
+                }
+            }
+
+            Scheme::Stripe => {
+                let mut timestamp = None;
+                let mut signatures = Vec::new();
+
+                for (key, val) in header.split(',').filter_map(|pair| pair.split_once('=')) {
+                    match key.trim() {
+                        "t" => timestamp = val.trim().parse().ok(),
+                        "v1" => signatures.extend(decode_hex(val.trim())),
+                        _ => {}
+                    }
+                }
+
+                if signatures.is_empty() {
+                    return Err(WebhookError::MissingSignature);
+                }
+
+                Expected {
+                    timestamp: Some(timestamp.ok_or(WebhookError::MissingTimestamp)?),
+                    signatures,
+                }
+            }
+        };
+
+        if let Some(timestamp) = expected.timestamp {
+            let signed_at = UNIX_EPOCH
+                .checked_add(Duration::from_secs(timestamp))
+                .ok_or(WebhookError::Expired)?;
+
+            let skew = now
+                .duration_since(signed_at)
+                .unwrap_or_else(|err| err.duration());
+
+            if skew > self.tolerance {
+                return Err(WebhookError::Expired);
+            }
+        }
+
+        Ok(expected)
+    }
+}
+
+impl fmt::Debug for WebhookConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebhookConfig")
+            .field("header", &self.header)
+            .field("scheme", &self.scheme)
+            .field("tolerance", &self.tolerance)
+            .field("limit", &self.limit)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Signature material parsed from request headers.
+struct Expected {
+    timestamp: Option<u64>,
+    signatures: Vec<Vec<u8>>,
+}
+
+impl Expected {
+    fn verify(&self, secret: &[u8], body: &[u8]) -> Result<(), WebhookError> {
+        let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any size");
+
+        if let Some(timestamp) = self.timestamp {
+            mac.update(timestamp.to_string().as_bytes());
+            mac.update(b".");
+        }
+
+        mac.update(body);
+
+        // `verify_slice` compares in constant time
+        if self
+            .signatures
+            .iter()
+            .any(|signature| mac.clone().verify_slice(signature).is_ok())
+        {
+            Ok(())
+        } else {
+            Err(WebhookError::InvalidSignature)
+        }
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(hex.get(idx..idx + 2)?, 16).ok())
+        .collect()
+}
+
+/// Extractor for a webhook request body with a verified signature.
+///
+/// See the [module documentation](self) for details and examples.
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    body: Bytes,
+    timestamp: Option<SystemTime>,
+}
+
+impl Webhook {
+    /// Returns the verified request body.
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// Unwraps into the verified request body.
+    pub fn into_body(self) -> Bytes {
+        self.body
+    }
+
+    /// Returns the signed timestamp of the delivery, if the signature scheme includes one.
+    pub fn timestamp(&self) -> Option<SystemTime> {
+        self.timestamp
+    }
+
+    /// Deserializes the verified request body as JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, JsonPayloadError> {
+        serde_json::from_slice(&self.body).map_err(JsonPayloadError::Deserialize)
+    }
+}
+
+impl FromRequest for Webhook {
+    type Error = WebhookError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let Some(cfg) = WebhookConfig::from_req(req) else {
+            log::error!(
+                "Failed to verify webhook for {:?}: no `WebhookConfig` registered",
+                req.match_name().unwrap_or(req.path())
+            );
+            return Box::pin(async { Err(WebhookError::MissingConfig) });
+        };
+
+        let now = req
+            .app_data::<web::Clock>()
+            .cloned()
+            .unwrap_or_default()
+            .now();
+
+        // check headers before reading the body so that bad requests are rejected early
+        let expected = match cfg.expected(req, now) {
+            Ok(expected) => expected,
+            Err(err) => return Box::pin(async { Err(err) }),
+        };
+
+        let secret = Arc::clone(&cfg.secret);
+        let body = HttpMessageBody::new(req, payload).limit(cfg.limit);
+
+        Box::pin(async move {
+            let body = body.await.map_err(WebhookError::Payload)?;
+            expected.verify(&secret, &body)?;
+
+            Ok(Webhook {
+                body,
+                timestamp: expected
+                    .timestamp
+                    .and_then(|ts| UNIX_EPOCH.checked_add(Duration::from_secs(ts))),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestRequest;
+
+    const SECRET: &str = "It's a Secret to Everybody";
+    const BODY: &[u8] = b"Hello, World!";
+
+    fn sign(data: &[u8]) -> String {
+        HmacSha256::new_from_slice(SECRET.as_bytes())
+            .unwrap()
+            .chain_update(data)
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    async fn extract(req: TestRequest) -> Result<Webhook, WebhookError> {
+        let (req, mut pl) = req.set_payload(BODY).to_http_parts();
+        Webhook::from_request(&req, &mut pl).await
+    }
+
+    #[test]
+    fn github_docs_example() {
+        // from https://docs.github.com/en/webhooks/using-webhooks/validating-webhook-deliveries
+        assert_eq!(
+            sign(BODY),
+            "757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn github_signature() {
+        let req = TestRequest::post()
+            .app_data(WebhookConfig::github(SECRET))
+            .insert_header(("x-hub-signature-256", format!("sha256={}", sign(BODY))));
+        let hook = extract(req).await.unwrap();
+        assert_eq!(hook.body(), BODY);
+        assert!(hook.timestamp().is_none());
+
+        let req = TestRequest::post()
+            .app_data(WebhookConfig::github(SECRET))
+            .insert_header(("x-hub-signature-256", format!("sha256={}", sign(b"other"))));
+        assert!(matches!(
+            extract(req).await.unwrap_err(),
+            WebhookError::InvalidSignature
+        ));
+
+        let req = TestRequest::post()
+            .app_data(WebhookConfig::github(SECRET))
+            .insert_header(("x-hub-signature-256", sign(BODY)));
+        assert!(matches!(
+            extract(req).await.unwrap_err(),
+            WebhookError::MissingSignature
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn stripe_signature() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let signed = sign(b"1700000000.Hello, World!");
+        let header = format!("t=1700000000,v1={},v1={signed},v0=abc", sign(b"old"));
+
+        let req = TestRequest::post()
+            .app_data(WebhookConfig::stripe(SECRET))
+            .app_data(web::Clock::frozen(now + Duration::from_secs(30)))
+            .insert_header(("stripe-signature", header.clone()));
+        let hook = extract(req).await.unwrap();
+        assert_eq!(hook.timestamp(), Some(now));
+
+        let req = TestRequest::post()
+            .app_data(WebhookConfig::stripe(SECRET))
+            .app_data(web::Clock::frozen(now + Duration::from_secs(600)))
+            .insert_header(("stripe-signature", header));
+        assert!(matches!(
+            extract(req).await.unwrap_err(),
+            WebhookError::Expired
+        ));
+
+        let req = TestRequest::post()
+            .app_data(WebhookConfig::stripe(SECRET))
+            .insert_header(("stripe-signature", format!("v1={signed}")));
+        assert!(matches!(
+            extract(req).await.unwrap_err(),
+            WebhookError::MissingTimestamp
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn timestamp_header() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let cfg = WebhookConfig::hmac_sha256(SECRET)
+            .timestamp_header(HeaderName::from_static("x-timestamp"))
+            .tolerance(Duration::from_secs(10));
+
+        let req = TestRequest::post()
+            .app_data(cfg.clone())
+            .app_data(web::Clock::frozen(now))
+            .insert_header(("x-timestamp", "1700000005"))
+            .insert_header(("x-signature", sign(b"1700000005.Hello, World!")));
+        extract(req).await.unwrap();
+
+        // signed too far in the future
+        let req = TestRequest::post()
+            .app_data(cfg.clone())
+            .app_data(web::Clock::frozen(now))
+            .insert_header(("x-timestamp", "1700000050"))
+            .insert_header(("x-signature", sign(b"1700000050.Hello, World!")));
+        assert!(matches!(
+            extract(req).await.unwrap_err(),
+            WebhookError::Expired
+        ));
+
+        // timestamp not representable as a system time
+        let req = TestRequest::post()
+            .app_data(cfg.clone())
+            .app_data(web::Clock::frozen(now))
+            .insert_header(("x-timestamp", u64::MAX.to_string()))
+            .insert_header((
+                "x-signature",
+                sign(format!("{}.Hello, World!", u64::MAX).as_bytes()),
+            ));
+        assert!(matches!(
+            extract(req).await.unwrap_err(),
+            WebhookError::Expired
+        ));
+
+        // timestamp not covered by signature
+        let req = TestRequest::post()
+            .app_data(cfg)
+            .app_data(web::Clock::frozen(now))
+            .insert_header(("x-timestamp", "1700000005"))
+            .insert_header(("x-signature", sign(BODY)));
+        assert!(matches!(
+            extract(req).await.unwrap_err(),
+            WebhookError::InvalidSignature
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn missing_config_and_limit() {
+        let err = extract(TestRequest::post()).await.unwrap_err();
+        assert_eq!(
+            err.error_response().status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+
+        let req = TestRequest::post()
+            .app_data(web::Data::new(WebhookConfig::hmac_sha256(SECRET).limit(4)))
+            .insert_header(("x-signature", sign(BODY)));
+        let err = extract(req).await.unwrap_err();
+        assert!(matches!(err, WebhookError::Payload(PayloadError::Overflow)));
+        assert_eq!(err.error_response().status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,428 @@
+//! Distribution of accepted connections between server workers.
+
+use std::{
+    cell::RefCell,
+    future::{poll_fn, Future as _},
+    net,
+    pin::Pin,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+};
+
+use actix_rt::net::TcpStream;
+use actix_service::{Service, ServiceFactory};
+use actix_utils::future::ready;
+use futures_core::future::LocalBoxFuture;
+use tokio::sync::{mpsc, oneshot};
+
+/// Strategy for distributing accepted connections between server workers.
+///
+/// See [`HttpServer::worker_dispatch()`](crate::HttpServer::worker_dispatch()).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WorkerDispatch {
+    /// Connections are served by the worker they were accepted for; workers take turns.
+    #[default]
+    RoundRobin,
+
+    /// Connections are moved to the worker with the fewest requests in flight.
+    ///
+    /// Workers still take turns receiving connections from the accept loop, but a worker hands a
+    /// new connection over to the least-loaded worker serving the same listener if that worker has
+    /// fewer requests in flight than itself. This improves tail latency when the cost of requests
+    /// varies a lot, at the price of moving connections between threads.
+    LeastLoaded,
+}
+
+/// Registry of the workers serving a listener.
+///
+/// Only locked when a worker starts; workers are told about each other through their peer channels
+/// so that choosing a worker for a new connection does not need any locking.
+#[derive(Default)]
+pub(crate) struct WorkerLoads {
+    workers: Mutex<Vec<Registration>>,
+}
+
+struct Registration {
+    slot: WorkerSlot,
+    peers_tx: mpsc::UnboundedSender<WorkerSlot>,
+}
+
+/// In-flight request counter and hand-off channel of a worker.
+#[derive(Clone)]
+struct WorkerSlot {
+    in_flight: Arc<AtomicUsize>,
+    tx: mpsc::UnboundedSender<HandOff>,
+}
+
+/// Connection handed over to another worker.
+struct HandOff {
+    stream: net::TcpStream,
+
+    /// Resolved when the connection has been served, or with the connection if the worker could
+    /// not serve it.
+    done: oneshot::Sender<Result<(), net::TcpStream>>,
+}
+
+impl WorkerLoads {
+    /// Registers a worker and returns the other workers serving the listener.
+    fn register(&self, slot: WorkerSlot) -> Peers {
+        let (peers_tx, peers_rx) = mpsc::unbounded_channel();
+
+        let mut workers = self.workers.lock().unwrap();
+
+        // stopped or restarted workers
+        workers.retain(|worker| !worker.slot.tx.is_closed());
+
+        let mut peers = Vec::with_capacity(workers.len());
+
+        for worker in workers.iter() {
+            let _ = worker.peers_tx.send(slot.clone());
+            peers.push(worker.slot.clone());
+        }
+
+        workers.push(Registration { slot, peers_tx });
+
+        Peers {
+            workers: peers,
+            rx: peers_rx,
+        }
+    }
+}
+
+/// Other workers serving the same listener, as known to a worker.
+struct Peers {
+    workers: Vec<WorkerSlot>,
+
+    /// Workers started after this one.
+    rx: mpsc::UnboundedReceiver<WorkerSlot>,
+}
+
+impl Peers {
+    /// Returns the hand-off channel of the least-loaded worker, if it has fewer than `load`
+    /// requests in flight.
+    fn least_loaded(&mut self, load: usize) -> Option<mpsc::UnboundedSender<HandOff>> {
+        while let Ok(worker) = self.rx.try_recv() {
+            self.workers.push(worker);
+        }
+
+        self.workers.retain(|worker| !worker.tx.is_closed());
+
+        self.workers
+            .iter()
+            .map(|worker| (worker.in_flight.load(Ordering::Relaxed), worker))
+            .min_by_key(|(load, _)| *load)
+            .filter(|(least, _)| *least < load)
+            .map(|(_, worker)| worker.tx.clone())
+    }
+}
+
+/// Load tracking of one worker serving a listener.
+///
+/// Created on the worker thread for each HTTP service factory of a TCP listener.
+pub(crate) struct WorkerBalancer {
+    loads: Option<Arc<WorkerLoads>>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl WorkerBalancer {
+    pub(crate) fn new(loads: &Arc<WorkerLoads>, dispatch: WorkerDispatch) -> Self {
+        Self {
+            loads: (dispatch == WorkerDispatch::LeastLoaded).then(|| Arc::clone(loads)),
+            in_flight: Arc::default(),
+        }
+    }
+
+    /// Returns the counter of requests in flight on this worker, to be set on its HTTP service.
+    pub(crate) fn in_flight(&self) -> Arc<AtomicUsize> {
+        Arc::clone(&self.in_flight)
+    }
+
+    /// Wraps the connection service factory of the worker.
+    pub(crate) fn wrap<T>(self, factory: T) -> BalancedFactory<T> {
+        BalancedFactory {
+            factory,
+            balancer: Rc::new(self),
+        }
+    }
+}
+
+/// Connection service factory that registers the worker for receiving connections handed over
+/// by other workers.
+pub(crate) struct BalancedFactory<T> {
+    factory: T,
+    balancer: Rc<WorkerBalancer>,
+}
+
+impl<T> ServiceFactory<TcpStream> for BalancedFactory<T>
+where
+    T: ServiceFactory<TcpStream, Config = (), Response = ()>,
+    T::Error: 'static,
+    T::Future: 'static,
+    T::Service: 'static,
+    <T::Service as Service<TcpStream>>::Future: 'static,
+{
+    type Response = ();
+    type Error = T::Error;
+    type Config = ();
+    type Service = BalancedService<T::Service>;
+    type InitError = T::InitError;
+    type Future = LocalBoxFuture<'static, Result<Self::Service, Self::InitError>>;
+
+    fn new_service(&self, _: ()) -> Self::Future {
+        let fut = self.factory.new_service(());
+        let balancer = Rc::clone(&self.balancer);
+
+        Box::pin(async move {
+            let service = Rc::new(fut.await?);
+
+            let dispatch = balancer.loads.as_ref().map(|loads| {
+                let (tx, rx) = mpsc::unbounded_channel();
+                let (stop_tx, stop_rx) = oneshot::channel();
+
+                let peers = loads.register(WorkerSlot {
+                    in_flight: balancer.in_flight(),
+                    tx,
+                });
+
+                actix_rt::spawn(receive_connections(Rc::clone(&service), rx, stop_rx));
+
+                Dispatch {
+                    peers: RefCell::new(peers),
+                    _stop: stop_tx,
+                }
+            });
+
+            Ok(BalancedService {
+                service,
+                balancer,
+                dispatch,
+            })
+        })
+    }
+}
+
+/// Serves connections handed over by other workers until the worker's service is dropped.
+async fn receive_connections<S>(
+    service: Rc<S>,
+    mut rx: mpsc::UnboundedReceiver<HandOff>,
+    mut stop_rx: oneshot::Receiver<()>,
+) where
+    S: Service<TcpStream, Response = ()> + 'static,
+    S::Future: 'static,
+{
+    loop {
+        let hand_off = poll_fn(|cx| {
+            if Pin::new(&mut stop_rx).poll(cx).is_ready() {
+                return Poll::Ready(None);
+            }
+
+            rx.poll_recv(cx)
+        })
+        .await;
+
+        let Some(HandOff { stream, done }) = hand_off else {
+            break;
+        };
+
+        // service failed and is about to be restarted
+        if poll_fn(|cx| service.poll_ready(cx)).await.is_err() {
+            let _ = done.send(Err(stream));
+            break;
+        }
+
+        let stream = match TcpStream::from_std(stream) {
+            Ok(stream) => stream,
+            Err(err) => {
+                log::error!("can not serve connection handed over by another worker: {err}");
+                let _ = done.send(Ok(()));
+                continue;
+            }
+        };
+
+        let fut = service.call(stream);
+        actix_rt::spawn(async move {
+            let _ = fut.await;
+            let _ = done.send(Ok(()));
+        });
+    }
+
+    // hand queued connections back to the workers that accepted them
+    rx.close();
+
+    while let Ok(HandOff { stream, done }) = rx.try_recv() {
+        let _ = done.send(Err(stream));
+    }
+}
+
+/// Connection service that hands new connections over to the least-loaded worker.
+///
+/// Handed-over connections are still counted by the server as connections of this worker until
+/// they have been served, so that connection limits and graceful shutdown apply to them.
+pub(crate) struct BalancedService<S> {
+    service: Rc<S>,
+    balancer: Rc<WorkerBalancer>,
+    dispatch: Option<Dispatch>,
+}
+
+struct Dispatch {
+    peers: RefCell<Peers>,
+
+    /// Stops receiving connections from other workers when dropped.
+    _stop: oneshot::Sender<()>,
+}
+
+impl<S> BalancedService<S> {
+    /// Returns the hand-off channel of the least-loaded worker, if it is less loaded than this one.
+    fn least_loaded(&self) -> Option<mpsc::UnboundedSender<HandOff>> {
+        let dispatch = self.dispatch.as_ref()?;
+        let load = self.balancer.in_flight.load(Ordering::Relaxed);
+
+        dispatch.peers.borrow_mut().least_loaded(load)
+    }
+}
+
+impl<S> Service<TcpStream> for BalancedService<S>
+where
+    S: Service<TcpStream, Response = ()> + 'static,
+    S::Error: 'static,
+    S::Future: 'static,
+{
+    type Response = ();
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<(), S::Error>>;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, stream: TcpStream) -> Self::Future {
+        let Some(tx) = self.least_loaded() else {
+            return Box::pin(self.service.call(stream));
+        };
+
+        let stream = match stream.into_std() {
+            Ok(stream) => stream,
+            Err(err) => {
+                log::error!("can not hand connection over to another worker: {err}");
+                return Box::pin(ready(Ok(())));
+            }
+        };
+
+        let service = Rc::clone(&self.service);
+        let (done, done_rx) = oneshot::channel();
+
+        // worker stopped in the meantime
+        if let Err(mpsc::error::SendError(hand_off)) = tx.send(HandOff { stream, done }) {
+            return Box::pin(serve_std(service, hand_off.stream));
+        }
+
+        // resolve only once the connection has been served, so that the server keeps counting it
+        Box::pin(async move {
+            match done_rx.await {
+                Ok(Err(stream)) => serve_std(service, stream).await,
+
+                // served by the other worker, or dropped when its thread stopped
+                Ok(Ok(())) | Err(_) => Ok(()),
+            }
+        })
+    }
+}
+
+/// Serves a connection handed back by another worker.
+async fn serve_std<S>(service: Rc<S>, stream: net::TcpStream) -> Result<(), S::Error>
+where
+    S: Service<TcpStream, Response = ()>,
+{
+    match TcpStream::from_std(stream) {
+        Ok(stream) => service.call(stream).await,
+        Err(err) => {
+            log::error!("can not serve connection: {err}");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Receiver = mpsc::UnboundedReceiver<HandOff>;
+
+    fn register(loads: &WorkerLoads, in_flight: usize) -> (Arc<AtomicUsize>, Peers, Receiver) {
+        let in_flight = Arc::new(AtomicUsize::new(in_flight));
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let peers = loads.register(WorkerSlot {
+            in_flight: Arc::clone(&in_flight),
+            tx,
+        });
+
+        (in_flight, peers, rx)
+    }
+
+    fn load(in_flight: &AtomicUsize) -> usize {
+        in_flight.load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn least_loaded() {
+        let loads = WorkerLoads::default();
+
+        let (busy, mut busy_peers, _busy_rx) = register(&loads, 3);
+        let (idle, mut idle_peers, mut idle_rx) = register(&loads, 0);
+        let (other, mut other_peers, _other_rx) = register(&loads, 1);
+
+        let idle_tx = loads.workers.lock().unwrap()[1].slot.tx.clone();
+
+        // workers started later are learned about through the peer channel
+        let tx = busy_peers.least_loaded(load(&busy)).unwrap();
+        assert!(tx.same_channel(&idle_tx));
+        assert_eq!(busy_peers.workers.len(), 2);
+
+        let tx = other_peers.least_loaded(load(&other)).unwrap();
+        assert!(tx.same_channel(&idle_tx));
+
+        // least-loaded worker keeps its connections
+        assert!(idle_peers.least_loaded(load(&idle)).is_none());
+
+        // ties are not worth a hand-off
+        idle.store(1, Ordering::Relaxed);
+        assert!(other_peers.least_loaded(load(&other)).is_none());
+
+        // stopped workers are skipped
+        idle.store(0, Ordering::Relaxed);
+        idle_rx.close();
+        let tx = busy_peers.least_loaded(load(&busy)).unwrap();
+        assert!(!tx.same_channel(&idle_tx));
+        assert_eq!(busy_peers.workers.len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn hand_back_on_stop() {
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let service = Rc::new(actix_service::fn_service(|_: TcpStream| async {
+            Ok::<_, ()>(())
+        }));
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (stop_tx, stop_rx) = oneshot::channel();
+
+        // queued before the worker stops receiving connections
+        let (done, done_rx) = oneshot::channel();
+        let stream = net::TcpStream::connect(addr).unwrap();
+        tx.send(HandOff { stream, done }).unwrap();
+
+        drop(stop_tx);
+        receive_connections(service, rx, stop_rx).await;
+
+        assert!(matches!(done_rx.await, Ok(Err(_))));
+        assert!(tx.is_closed());
+    }
+}
@@ -200,6 +200,55 @@ async fn negotiate_encoding_zstd() {
     feature = "compress-gzip",
     feature = "compress-zstd",
 ))]
+#[actix_rt::test]
+async fn close_delimited_gzip() {
+    use std::{
+        io::{Read as _, Write as _},
+        net::TcpStream,
+        time::Duration,
+    };
+
+    use actix_web::body::BodyStream;
+
+    let srv = actix_test::start(|| {
+        App::new().wrap(Compress::default()).route(
+            "/",
+            web::to(|| async {
+                let body = BodyStream::new(futures_util::stream::iter(
+                    LOREM
+                        .chunks(1024)
+                        .map(|chunk| Ok::<_, actix_web::Error>(Bytes::from_static(chunk))),
+                ));
+
+                HttpResponse::Ok().close_delimited().body(body)
+            }),
+        )
+    });
+
+    let mut stream = TcpStream::connect(srv.addr()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\naccept-encoding: gzip\r\n\r\n")
+        .unwrap();
+
+    let mut data = Vec::new();
+    stream.read_to_end(&mut data).unwrap();
+
+    let split = data.windows(4).position(|win| win == b"\r\n\r\n").unwrap();
+    let head = String::from_utf8(data[..split].to_vec()).unwrap();
+    assert!(head.starts_with("HTTP/1.1 200 OK\r\n"), "{head}");
+    assert!(head.contains("content-encoding: gzip\r\n"), "{head}");
+    assert!(head.contains("connection: close\r\n"), "{head}");
+    assert!(!head.contains("transfer-encoding"), "{head}");
+    assert!(!head.contains("content-length"), "{head}");
+
+    assert_eq!(utils::gzip::decode(&data[split + 4..]), LOREM);
+
+    srv.stop().await;
+}
+
 #[actix_rt::test]
 async fn client_encoding_prefers_brotli() {
     let srv = test_server!();
@@ -0,0 +1,44 @@
+use actix_web::{
+    body::to_bytes,
+    error::{self, ErrorBodyFormat},
+    http::{header, StatusCode},
+    test, web, App,
+};
+
+// the error body format is process-wide, so it is only changed by this test binary
+#[actix_rt::test]
+async fn json_error_bodies() {
+    ErrorBodyFormat::Json.set_global();
+
+    let res = error::ErrorBadRequest("invalid \"name\"").error_response();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/json"
+    );
+    assert_eq!(
+        to_bytes(res.into_body()).await.unwrap(),
+        r#"{"error":"invalid \"name\"","status":400}"#
+    );
+
+    let app = test::init_service(App::new().route(
+        "/{id}",
+        web::get().to(|id: web::Path<u32>| async move { id.to_string() }),
+    ))
+    .await;
+
+    let req = test::TestRequest::get().uri("/abc").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/json"
+    );
+
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["status"], 404);
+    assert!(body["error"].as_str().unwrap().contains("abc"));
+
+    ErrorBodyFormat::PlainText.set_global();
+    assert_eq!(ErrorBodyFormat::global(), ErrorBodyFormat::PlainText);
+}
@@ -56,6 +56,381 @@ async fn test_start() {
     srv.stop(false).await;
 }
 
+#[cfg(unix)]
+#[actix_rt::test]
+async fn test_warm_up() {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    let addr = actix_test::unused_addr();
+    let (tx, rx) = mpsc::channel();
+
+    let warm = Arc::new(AtomicBool::new(false));
+    let warm2 = Arc::clone(&warm);
+
+    thread::spawn(move || {
+        actix_rt::System::new()
+            .block_on(async {
+                let srv = HttpServer::new(move || {
+                    let warm = Arc::clone(&warm2);
+                    App::new().route(
+                        "/",
+                        web::to(move || {
+                            let warm = warm.load(Ordering::SeqCst);
+                            async move {
+                                if warm {
+                                    "warm"
+                                } else {
+                                    "cold"
+                                }
+                            }
+                        }),
+                    )
+                })
+                .workers(1)
+                .disable_signals()
+                .warm_up_delay(Duration::from_millis(100))
+                .warm_up({
+                    let warm = Arc::clone(&warm);
+                    || async move {
+                        actix_rt::time::sleep(Duration::from_millis(100)).await;
+                        warm.store(true, Ordering::SeqCst);
+                    }
+                })
+                .bind(addr)
+                .unwrap()
+                .run();
+
+                tx.send(srv.handle()).unwrap();
+
+                srv.await
+            })
+            .unwrap();
+    });
+
+    let srv = rx.recv().unwrap();
+
+    // warm-up completes before connections are accepted
+    let client = awc::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .finish();
+    let mut res = client.get(format!("http://{}", addr)).send().await.unwrap();
+    assert!(res.status().is_success());
+    assert_eq!(res.body().await.unwrap(), "warm");
+
+    srv.stop(false).await;
+}
+
+#[cfg(unix)]
+#[actix_rt::test]
+async fn test_graceful_stop_drains_connections() {
+    use std::{
+        io::{Read as _, Write as _},
+        net::TcpStream,
+        time::Instant,
+    };
+
+    let addr = actix_test::unused_addr();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        actix_rt::System::new()
+            .block_on(async {
+                let srv = HttpServer::new(|| {
+                    App::new().route("/", web::to(HttpResponse::Ok)).route(
+                        "/slow",
+                        web::to(|| async {
+                            actix_rt::time::sleep(Duration::from_millis(500)).await;
+                            HttpResponse::Ok().body("slow")
+                        }),
+                    )
+                })
+                .workers(1)
+                .disable_signals()
+                .keep_alive(Duration::from_secs(30))
+                .shutdown_timeout(30)
+                .bind(addr)
+                .unwrap()
+                .run();
+
+                tx.send(srv.handle()).unwrap();
+
+                srv.await
+            })
+            .unwrap();
+    });
+
+    let srv = rx.recv().unwrap();
+
+    let connect = || {
+        let stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream
+    };
+
+    // idle keep-alive connection
+    let mut idle = connect();
+    idle.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+    let mut buf = [0; 1024];
+    let n = idle.read(&mut buf).unwrap();
+    assert!(buf[..n].starts_with(b"HTTP/1.1 200 OK"));
+
+    // connection with a request in flight
+    let mut busy = connect();
+    busy.write_all(b"GET /slow HTTP/1.1\r\n\r\n").unwrap();
+    thread::sleep(Duration::from_millis(100));
+
+    let start = Instant::now();
+    let stop = srv.stop(true);
+
+    // idle connection is closed without waiting for the keep-alive or shutdown timeouts
+    assert_eq!(idle.read(&mut buf).unwrap(), 0);
+
+    // in-flight request is answered and its connection closed afterwards
+    let mut res = Vec::new();
+    busy.read_to_end(&mut res).unwrap();
+    let res = String::from_utf8(res).unwrap().to_lowercase();
+    assert!(res.starts_with("http/1.1 200 ok"));
+    assert!(res.contains("connection: close"));
+    assert!(res.ends_with("slow"));
+
+    stop.await;
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[cfg(unix)]
+#[actix_rt::test]
+async fn test_catch_panics() {
+    use std::{
+        io::{Read as _, Write as _},
+        net::TcpStream,
+    };
+
+    let addr = actix_test::unused_addr();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        actix_rt::System::new()
+            .block_on(async {
+                let srv = HttpServer::new(|| {
+                    App::new().route("/", web::to(HttpResponse::Ok)).route(
+                        "/panic",
+                        web::to(|| async {
+                            if true {
+                                panic!("handler failed");
+                            }
+
+                            HttpResponse::Ok().finish()
+                        }),
+                    )
+                })
+                .workers(1)
+                .disable_signals()
+                .catch_panics(true)
+                .on_panic(|payload| {
+                    let msg = payload.downcast_ref::<&str>().copied().unwrap_or_default();
+                    HttpResponse::InternalServerError().body(format!("caught: {msg}"))
+                })
+                .bind(addr)
+                .unwrap()
+                .run();
+
+                tx.send(srv.handle()).unwrap();
+
+                srv.await
+            })
+            .unwrap();
+    });
+
+    let srv = rx.recv().unwrap();
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+
+    let mut buf = [0; 1024];
+
+    stream.write_all(b"GET /panic HTTP/1.1\r\n\r\n").unwrap();
+    let n = stream.read(&mut buf).unwrap();
+    let res = String::from_utf8_lossy(&buf[..n]);
+    assert!(res.starts_with("HTTP/1.1 500 Internal Server Error"));
+    assert!(res.ends_with("caught: handler failed"));
+
+    // connection is kept alive
+    stream.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+    let n = stream.read(&mut buf).unwrap();
+    assert!(buf[..n].starts_with(b"HTTP/1.1 200 OK"));
+
+    srv.stop(false).await;
+}
+
+#[cfg(unix)]
+#[actix_rt::test]
+async fn test_least_loaded_worker_dispatch() {
+    use std::{
+        collections::HashSet,
+        io::{Read as _, Write as _},
+        net::TcpStream,
+    };
+
+    use actix_web::dev::WorkerDispatch;
+
+    async fn thread_name() -> String {
+        format!("{:?}", thread::current().id())
+    }
+
+    let addr = actix_test::unused_addr();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        actix_rt::System::new()
+            .block_on(async {
+                let srv = HttpServer::new(|| {
+                    App::new().route("/", web::to(thread_name)).route(
+                        "/slow",
+                        web::to(|| async {
+                            actix_rt::time::sleep(Duration::from_millis(500)).await;
+                            thread_name().await
+                        }),
+                    )
+                })
+                .workers(2)
+                .worker_dispatch(WorkerDispatch::LeastLoaded)
+                .disable_signals()
+                .bind(addr)
+                .unwrap()
+                .run();
+
+                tx.send(srv.handle()).unwrap();
+
+                srv.await
+            })
+            .unwrap();
+    });
+
+    let srv = rx.recv().unwrap();
+
+    let get = |path: &str| {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nconnection: close\r\n\r\n").unwrap();
+        stream
+    };
+
+    let read_body = |mut stream: TcpStream| {
+        let mut res = String::new();
+        stream.read_to_string(&mut res).unwrap();
+        res.split("\r\n\r\n").nth(1).unwrap().to_owned()
+    };
+
+    // keep one worker busy
+    let slow = get("/slow");
+    thread::sleep(Duration::from_millis(100));
+
+    // connections alternate between workers, but are all moved to the idle one
+    let threads = (0..4).map(|_| read_body(get("/"))).collect::<HashSet<_>>();
+    assert_eq!(threads.len(), 1);
+
+    let busy_thread = read_body(slow);
+    assert!(!threads.contains(&busy_thread));
+
+    // connections moved to another worker are drained on graceful shutdown
+    let slow = (0..3)
+        .map(|_| {
+            let stream = get("/slow");
+            thread::sleep(Duration::from_millis(50));
+            stream
+        })
+        .collect::<Vec<_>>();
+
+    let stop = srv.stop(true);
+
+    for stream in slow {
+        assert!(!read_body(stream).is_empty());
+    }
+
+    stop.await;
+}
+
+#[cfg(unix)]
+#[actix_rt::test]
+async fn test_named_listener_apps() {
+    use actix_web::middleware::DefaultHeaders;
+
+    let public_addr = actix_test::unused_addr();
+    let admin_addr = actix_test::unused_addr();
+    let other_addr = actix_test::unused_addr();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        actix_rt::System::new()
+            .block_on(async {
+                let srv = HttpServer::new(|| {
+                    App::new()
+                        .wrap(DefaultHeaders::new().add(("x-stack", "public")))
+                        .route("/", web::to(|| async { "public" }))
+                })
+                .workers(1)
+                .disable_signals()
+                .bind(public_addr)
+                .unwrap()
+                .bind_named("admin", admin_addr)
+                .unwrap()
+                .bind_named("other", other_addr)
+                .unwrap()
+                // registered after binding
+                .listener_app("admin", || {
+                    App::new().route("/", web::to(|| async { "admin" }))
+                })
+                .run();
+
+                tx.send(srv.handle()).unwrap();
+
+                srv.await
+            })
+            .unwrap();
+    });
+
+    let srv = rx.recv().unwrap();
+
+    let client = awc::Client::builder()
+        .connector(awc::Connector::new().timeout(Duration::from_millis(100)))
+        .finish();
+
+    let mut res = client
+        .get(format!("http://{}", public_addr))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.headers().get("x-stack").unwrap(), "public");
+    assert_eq!(res.body().await.unwrap(), "public");
+
+    let mut res = client
+        .get(format!("http://{}", admin_addr))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.headers().get("x-stack").is_none());
+    assert_eq!(res.body().await.unwrap(), "admin");
+
+    // named listeners without a registered app use the default app
+    let mut res = client
+        .get(format!("http://{}", other_addr))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.body().await.unwrap(), "public");
+
+    srv.stop(false).await;
+}
+
 #[cfg(feature = "openssl")]
 fn ssl_acceptor() -> openssl::ssl::SslAcceptorBuilder {
     use openssl::{
@@ -135,3 +510,66 @@ async fn test_start_ssl() {
 
     srv.stop(false).await;
 }
+
+#[actix_rt::test]
+#[cfg(feature = "openssl")]
+async fn test_named_listener_ssl() {
+    use actix_web::HttpRequest;
+    use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+
+    let lst = std::net::TcpListener::bind(actix_test::unused_addr()).unwrap();
+    let addr = lst.local_addr().unwrap();
+    let bind_addr = actix_test::unused_addr();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        actix_rt::System::new()
+            .block_on(async {
+                let srv = HttpServer::new(|| App::new().route("/", web::to(|| async { "public" })))
+                    .workers(1)
+                    .disable_signals()
+                    .listen_named_openssl("admin", lst, ssl_acceptor())
+                    .unwrap()
+                    .bind_named_openssl("admin", bind_addr, ssl_acceptor())
+                    .unwrap()
+                    .listener_app("admin", || {
+                        App::new().route(
+                            "/",
+                            web::to(|req: HttpRequest| async move {
+                                assert!(req.app_config().secure());
+                                "admin"
+                            }),
+                        )
+                    })
+                    .run();
+
+                tx.send(srv.handle()).unwrap();
+
+                srv.await
+            })
+            .unwrap()
+    });
+    let srv = rx.recv().unwrap();
+
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_verify(SslVerifyMode::NONE);
+
+    let client = awc::Client::builder()
+        .connector(
+            awc::Connector::new()
+                .openssl(builder.build())
+                .timeout(Duration::from_millis(100)),
+        )
+        .finish();
+
+    for addr in [addr, bind_addr] {
+        let mut res = client
+            .get(format!("https://{}", addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.body().await.unwrap(), "admin");
+    }
+
+    srv.stop(false).await;
+}
@@ -554,6 +554,52 @@ async fn test_reading_gzip_encoding_large_random() {
     srv.stop().await;
 }
 
+#[actix_rt::test]
+async fn test_gzip_encoding_over_limit() {
+    let srv = actix_test::start_with(actix_test::config().h1(), || {
+        App::new()
+            .app_data(web::PayloadConfig::new(1024))
+            .service(web::resource("/").route(web::to(move |body: Bytes| async {
+                HttpResponse::Ok().body(body)
+            })))
+    });
+
+    // compresses to far less than the limit but decompresses to far more
+    let data = vec![0; 1024 * 1024];
+
+    let res = srv
+        .post("/")
+        .append_header((header::CONTENT_ENCODING, "gzip"))
+        .send_body(utils::gzip::encode(data))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_gzip_encoding_corrupted() {
+    let srv = actix_test::start_with(actix_test::config().h1(), || {
+        App::new().service(web::resource("/").route(web::to(move |body: Bytes| async {
+            HttpResponse::Ok().body(body)
+        })))
+    });
+
+    let mut body = utils::gzip::encode(STR);
+    body[12..24].fill(0xff);
+
+    let res = srv
+        .post("/")
+        .append_header((header::CONTENT_ENCODING, "gzip"))
+        .send_body(body)
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    srv.stop().await;
+}
+
 #[actix_rt::test]
 async fn test_reading_deflate_encoding() {
     let srv = actix_test::start_with(actix_test::config().h1(), || {
@@ -940,3 +986,33 @@ async fn test_accept_encoding_no_match() {
 
     srv.stop().await;
 }
+
+#[actix_rt::test]
+async fn test_on_finish() {
+    use std::sync::mpsc;
+
+    use actix_web::{dev::FinishStatus, HttpRequest};
+
+    let (tx, rx) = mpsc::channel::<FinishStatus>();
+
+    let srv = actix_test::start(move || {
+        let tx = tx.clone();
+
+        App::new().service(web::resource("/").to(move |req: HttpRequest| {
+            let tx = tx.clone();
+
+            async move {
+                assert!(req.on_finish(move |status| async move { tx.send(status).unwrap() }));
+                HttpResponse::Ok().body(STR)
+            }
+        }))
+    });
+
+    let mut res = srv.get("/").send().await.unwrap();
+    assert_eq!(res.body().await.unwrap(), STR);
+
+    let status = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(status, FinishStatus::Completed);
+
+    srv.stop().await;
+}
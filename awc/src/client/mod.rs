@@ -106,6 +106,8 @@ impl Client {
     ///
     /// It is useful for proxy requests. This implementation
     /// copies all headers and the method.
+    ///
+    /// Use [`ClientRequest::forwarded`] to append this hop to the `Forwarded` header.
     pub fn request_from<U>(&self, url: U, head: &RequestHead) -> ClientRequest
     where
         Uri: TryFrom<U>,
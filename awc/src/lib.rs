@@ -125,6 +125,8 @@ mod request;
 mod responses;
 mod sender;
 pub mod test;
+#[cfg(feature = "webhook")]
+pub mod webhook;
 pub mod ws;
 
 pub mod http {
@@ -3,7 +3,7 @@ use std::{fmt, net, rc::Rc, time::Duration};
 use actix_http::{
     body::MessageBody,
     error::HttpError,
-    header::{self, HeaderMap, HeaderValue, TryIntoHeaderPair},
+    header::{self, ForwardedElement, HeaderMap, HeaderValue, TryIntoHeaderPair},
     ConnectionType, Method, RequestHead, Uri, Version,
 };
 use base64::prelude::*;
@@ -246,6 +246,44 @@ impl ClientRequest {
         self.insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
     }
 
+    /// Appends an element describing this hop to the `Forwarded` header, keeping any elements
+    /// already present.
+    ///
+    /// This is intended for proxies that build requests using
+    /// [`Client::request_from`](crate::Client::request_from), which copies the `Forwarded` header
+    /// of the incoming request.
+    ///
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    ///
+    /// use awc::{http::header::ForwardedElement, Client};
+    ///
+    /// let client_addr: SocketAddr = "192.0.2.43:52000".parse().unwrap();
+    ///
+    /// Client::new()
+    ///     .get("http://backend.internal")
+    ///     .forwarded(
+    ///         ForwardedElement::new()
+    ///             .with_for(client_addr.ip())
+    ///             .with_host("example.com")
+    ///             .with_proto("https"),
+    ///     );
+    /// ```
+    pub fn forwarded(self, element: ForwardedElement) -> Self {
+        let mut value = String::new();
+
+        for existing in self.head.headers.get_all(header::FORWARDED) {
+            if let Ok(existing) = existing.to_str() {
+                value.push_str(existing);
+                value.push_str(", ");
+            }
+        }
+
+        value.push_str(&element.to_string());
+
+        self.insert_header((header::FORWARDED, value))
+    }
+
     /// Set a cookie
     ///
     /// ```no_run
@@ -529,6 +567,25 @@ mod tests {
         assert!(repr.contains("x-test"));
     }
 
+    #[actix_rt::test]
+    async fn test_forwarded() {
+        let req = Client::new()
+            .get("/")
+            .append_header((header::FORWARDED, "for=192.0.2.43"))
+            .append_header((header::FORWARDED, "for=198.51.100.17;proto=http"))
+            .forwarded(
+                ForwardedElement::new()
+                    .with_for("[2001:db8::1]:4711".parse::<net::SocketAddr>().unwrap())
+                    .with_proto("https"),
+            );
+
+        assert_eq!(
+            req.headers().get(header::FORWARDED).unwrap(),
+            "for=192.0.2.43, for=198.51.100.17;proto=http, for=\"[2001:db8::1]:4711\";proto=https"
+        );
+        assert_eq!(req.headers().get_all(header::FORWARDED).count(), 1);
+    }
+
     #[actix_rt::test]
     async fn test_basics() {
         let req = Client::new()
@@ -0,0 +1,525 @@
+//! Signed webhook delivery.
+//!
+//! [`WebhookSender`] delivers payloads signed with an HMAC-SHA256 signature, retrying failed
+//! deliveries with exponential backoff. Deliveries to endpoints that keep failing are short
+//! circuited for a while so that a broken receiver does not tie up the sender.
+//!
+//! Signatures are compatible with the `actix_web::webhook` receiver's `hmac_sha256` scheme:
+//! the hex-encoded signature is sent in the `X-Signature` header and, if a timestamp header is
+//! configured, covers `<timestamp>.<body>`.
+//!
+//! # Examples
+//! ```no_run
+//! # #[actix_rt::main]
+//! # async fn main() -> Result<(), awc::webhook::DeliveryError> {
+//! use std::time::Duration;
+//!
+//! use awc::{
+//!     http::header::HeaderName,
+//!     webhook::{RetryPolicy, WebhookSender},
+//!     Client,
+//! };
+//!
+//! let sender = WebhookSender::new(Client::default(), "secret")
+//!     .timestamp_header(HeaderName::from_static("x-timestamp"))
+//!     .retry_policy(RetryPolicy::default().max_attempts(5));
+//!
+//! let status = sender
+//!     .send_json("https://example.com/hooks", &serde_json::json!({ "event": "ping" }))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use actix_http::{
+    header::{HeaderName, HeaderValue, CONTENT_TYPE},
+    StatusCode,
+};
+use bytes::Bytes;
+use derive_more::derive::{Display, Error};
+use hmac::{Hmac, Mac as _};
+use serde::Serialize;
+use sha2::Sha256;
+
+use crate::{error::SendRequestError, Client};
+
+/// Errors that can occur when delivering a webhook.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum DeliveryError {
+    /// Deliveries to the endpoint are suspended after repeated failures.
+    #[display("Circuit breaker is open for endpoint")]
+    CircuitOpen,
+
+    /// The endpoint responded with a non-success status code.
+    #[display("Endpoint responded with status {}", _0)]
+    Status(#[error(not(source))] StatusCode),
+
+    /// The request could not be sent.
+    #[display("{}", _0)]
+    Send(SendRequestError),
+
+    /// The payload could not be serialized.
+    #[display("Failed to serialize payload: {}", _0)]
+    Serialize(serde_json::Error),
+}
+
+impl DeliveryError {
+    /// Returns true if a later attempt may succeed.
+    fn is_retryable(&self) -> bool {
+        match self {
+            DeliveryError::Status(status) => {
+                status.is_server_error()
+                    || *status == StatusCode::REQUEST_TIMEOUT
+                    || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            DeliveryError::Send(_) => true,
+            DeliveryError::CircuitOpen | DeliveryError::Serialize(_) => false,
+        }
+    }
+}
+
+/// Receives the outcomes of deliveries made by a [`WebhookSender`].
+///
+/// All methods have empty default implementations.
+pub trait DeliveryObserver {
+    /// Called when attempt number `attempt` failed and the delivery will be retried after `delay`.
+    fn retrying(&self, url: &str, attempt: u32, error: &DeliveryError, delay: Duration) {
+        let _ = (url, attempt, error, delay);
+    }
+
+    /// Called when the endpoint accepted the delivery.
+    fn delivered(&self, url: &str, attempts: u32, status: StatusCode) {
+        let _ = (url, attempts, status);
+    }
+
+    /// Called when the delivery failed for good.
+    fn failed(&self, url: &str, attempts: u32, error: &DeliveryError) {
+        let _ = (url, attempts, error);
+    }
+}
+
+/// Retry behavior of a [`WebhookSender`].
+///
+/// Attempts that fail with a connection error, a 5xx status, `408 Request Timeout`, or
+/// `429 Too Many Requests` are retried; other failures are final.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Sets the maximum number of attempts per delivery, including the first. Defaults to 3.
+    ///
+    /// # Panics
+    /// Panics if `max_attempts` is zero.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        assert!(max_attempts > 0, "at least one attempt is required");
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets the delay before the first retry, doubled for each further retry. Defaults to 1 second.
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Sets the maximum delay between retries. Defaults to 1 minute.
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Returns the delay after failed attempt number `attempt`.
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Circuit breaker state of an endpoint.
+#[derive(Debug, Clone, Copy)]
+enum Circuit {
+    /// Deliveries are allowed.
+    Closed { failures: u32 },
+
+    /// Deliveries are rejected until the deadline passes.
+    Open { until: Instant },
+
+    /// A single trial delivery is in flight.
+    HalfOpen,
+}
+
+/// Webhook delivery client.
+///
+/// See the [module documentation](self) for details and examples.
+pub struct WebhookSender {
+    client: Client,
+    secret: Arc<[u8]>,
+    signature_header: HeaderName,
+    signature_prefix: String,
+    timestamp_header: Option<HeaderName>,
+    retry: RetryPolicy,
+    failure_threshold: u32,
+    reset_timeout: Duration,
+    circuits: RefCell<HashMap<String, Circuit>>,
+    observer: Option<Rc<dyn DeliveryObserver>>,
+}
+
+impl WebhookSender {
+    /// Constructs a sender that signs payloads with `secret`.
+    pub fn new(client: Client, secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            client,
+            secret: secret.into().into(),
+            signature_header: HeaderName::from_static("x-signature"),
+            signature_prefix: String::new(),
+            timestamp_header: None,
+            retry: RetryPolicy::default(),
+            failure_threshold: 5,
+            reset_timeout: Duration::from_secs(30),
+            circuits: RefCell::new(HashMap::new()),
+            observer: None,
+        }
+    }
+
+    /// Sets the name of the header carrying the signature. Defaults to `X-Signature`.
+    pub fn signature_header(mut self, name: HeaderName) -> Self {
+        self.signature_header = name;
+        self
+    }
+
+    /// Sets a prefix to send before the hex-encoded signature, e.g., `sha256=`.
+    pub fn signature_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.signature_prefix = prefix.into();
+        self
+    }
+
+    /// Sends the UNIX timestamp of each attempt in the named header and includes it in the
+    /// signature.
+    pub fn timestamp_header(mut self, name: HeaderName) -> Self {
+        self.timestamp_header = Some(name);
+        self
+    }
+
+    /// Sets the retry policy.
+    pub fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Configures per-endpoint circuit breaking.
+    ///
+    /// After `failure_threshold` consecutive failed attempts to an endpoint, deliveries to it fail
+    /// immediately with [`DeliveryError::CircuitOpen`] until `reset_timeout` has passed. Then a
+    /// single trial delivery is let through, which either closes the circuit or opens it again.
+    ///
+    /// Defaults to 5 failures and 30 seconds.
+    pub fn circuit_breaker(mut self, failure_threshold: u32, reset_timeout: Duration) -> Self {
+        self.failure_threshold = failure_threshold;
+        self.reset_timeout = reset_timeout;
+        self
+    }
+
+    /// Sets the observer notified of delivery outcomes.
+    pub fn observer(mut self, observer: impl DeliveryObserver + 'static) -> Self {
+        self.observer = Some(Rc::new(observer));
+        self
+    }
+
+    /// Serializes `payload` as JSON and delivers it to `url`.
+    pub async fn send_json<T: Serialize + ?Sized>(
+        &self,
+        url: &str,
+        payload: &T,
+    ) -> Result<StatusCode, DeliveryError> {
+        let body = match serde_json::to_vec(payload) {
+            Ok(body) => body,
+            Err(err) => return Err(self.fail(url, 0, DeliveryError::Serialize(err))),
+        };
+
+        self.send_body(url, HeaderValue::from_static("application/json"), body)
+            .await
+    }
+
+    /// Delivers `body` with the given content type to `url`.
+    ///
+    /// Resolves to the status code of the response that accepted the delivery.
+    pub async fn send_body(
+        &self,
+        url: &str,
+        content_type: HeaderValue,
+        body: impl Into<Bytes>,
+    ) -> Result<StatusCode, DeliveryError> {
+        let body = body.into();
+        let mut attempt = 0;
+
+        loop {
+            let Some(admission) = self.admit(url) else {
+                return Err(self.fail(url, attempt, DeliveryError::CircuitOpen));
+            };
+
+            attempt += 1;
+
+            let res = self.attempt(url, &content_type, &body).await;
+            admission.record(res.is_ok());
+
+            let err = match res {
+                Ok(status) => {
+                    if let Some(observer) = &self.observer {
+                        observer.delivered(url, attempt, status);
+                    }
+
+                    return Ok(status);
+                }
+                Err(err) => err,
+            };
+
+            if attempt >= self.retry.max_attempts || !err.is_retryable() {
+                return Err(self.fail(url, attempt, err));
+            }
+
+            let delay = self.retry.backoff(attempt);
+
+            if let Some(observer) = &self.observer {
+                observer.retrying(url, attempt, &err, delay);
+            }
+
+            actix_rt::time::sleep(delay).await;
+        }
+    }
+
+    /// Makes a single, freshly signed delivery attempt.
+    async fn attempt(
+        &self,
+        url: &str,
+        content_type: &HeaderValue,
+        body: &Bytes,
+    ) -> Result<StatusCode, DeliveryError> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any size");
+
+        let mut req = self
+            .client
+            .post(url)
+            .insert_header((CONTENT_TYPE, content_type.clone()));
+
+        if let Some(name) = &self.timestamp_header {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+                .to_string();
+
+            mac.update(timestamp.as_bytes());
+            mac.update(b".");
+            req = req.insert_header((name.clone(), timestamp));
+        }
+
+        mac.update(body);
+
+        let mut signature = self.signature_prefix.clone();
+        for byte in mac.finalize().into_bytes() {
+            signature.push_str(&format!("{byte:02x}"));
+        }
+
+        let res = req
+            .insert_header((self.signature_header.clone(), signature))
+            .send_body(body.clone())
+            .await
+            .map_err(DeliveryError::Send)?;
+
+        if res.status().is_success() {
+            Ok(res.status())
+        } else {
+            Err(DeliveryError::Status(res.status()))
+        }
+    }
+
+    /// Returns a guard for a delivery attempt if the endpoint's circuit lets it through.
+    fn admit<'a>(&'a self, url: &'a str) -> Option<Admission<'a>> {
+        let mut circuits = self.circuits.borrow_mut();
+
+        let trial = match circuits.get_mut(url) {
+            None | Some(Circuit::Closed { .. }) => false,
+            Some(circuit) => match *circuit {
+                Circuit::Open { until } if Instant::now() >= until => {
+                    *circuit = Circuit::HalfOpen;
+                    true
+                }
+                _ => return None,
+            },
+        };
+
+        Some(Admission {
+            sender: self,
+            url,
+            trial,
+        })
+    }
+
+    /// Updates the endpoint's circuit with the result of an attempt.
+    fn record(&self, url: &str, success: bool) {
+        let mut circuits = self.circuits.borrow_mut();
+
+        if success {
+            circuits.remove(url);
+            return;
+        }
+
+        let circuit = circuits
+            .entry(url.to_owned())
+            .or_insert(Circuit::Closed { failures: 0 });
+
+        *circuit = match *circuit {
+            Circuit::Closed { failures } if failures + 1 < self.failure_threshold => {
+                Circuit::Closed {
+                    failures: failures + 1,
+                }
+            }
+            _ => Circuit::Open {
+                until: Instant::now() + self.reset_timeout,
+            },
+        };
+    }
+
+    /// Notifies the observer of a failed delivery.
+    fn fail(&self, url: &str, attempts: u32, err: DeliveryError) -> DeliveryError {
+        if let Some(observer) = &self.observer {
+            observer.failed(url, attempts, &err);
+        }
+
+        err
+    }
+}
+
+/// Delivery attempt let through by an endpoint's circuit.
+///
+/// If the guard of a trial attempt is dropped without recording a result, e.g., because the
+/// delivery future was cancelled, the circuit is opened again so that the next delivery becomes
+/// the trial instead of the circuit being stuck half-open.
+struct Admission<'a> {
+    sender: &'a WebhookSender,
+    url: &'a str,
+    trial: bool,
+}
+
+impl Admission<'_> {
+    /// Updates the endpoint's circuit with the result of the attempt.
+    fn record(mut self, success: bool) {
+        self.trial = false;
+        self.sender.record(self.url, success);
+    }
+}
+
+impl Drop for Admission<'_> {
+    fn drop(&mut self) {
+        if !self.trial {
+            return;
+        }
+
+        if let Some(circuit @ Circuit::HalfOpen) =
+            self.sender.circuits.borrow_mut().get_mut(self.url)
+        {
+            *circuit = Circuit::Open {
+                until: Instant::now(),
+            };
+        }
+    }
+}
+
+impl fmt::Debug for WebhookSender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebhookSender")
+            .field("signature_header", &self.signature_header)
+            .field("signature_prefix", &self.signature_prefix)
+            .field("timestamp_header", &self.timestamp_header)
+            .field("retry", &self.retry)
+            .field("failure_threshold", &self.failure_threshold)
+            .field("reset_timeout", &self.reset_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff() {
+        let retry = RetryPolicy::default()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(350));
+
+        assert_eq!(retry.backoff(1), Duration::from_millis(100));
+        assert_eq!(retry.backoff(2), Duration::from_millis(200));
+        assert_eq!(retry.backoff(3), Duration::from_millis(350));
+        assert_eq!(retry.backoff(40), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn retryable() {
+        assert!(DeliveryError::Status(StatusCode::BAD_GATEWAY).is_retryable());
+        assert!(DeliveryError::Status(StatusCode::TOO_MANY_REQUESTS).is_retryable());
+        assert!(!DeliveryError::Status(StatusCode::UNAUTHORIZED).is_retryable());
+        assert!(!DeliveryError::CircuitOpen.is_retryable());
+    }
+
+    #[actix_rt::test]
+    async fn cancelled_trial_reopens_circuit() {
+        // accepts connections but never responds
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let sender = WebhookSender::new(Client::default(), "secret")
+            .retry_policy(RetryPolicy::default().max_attempts(1));
+
+        sender.circuits.borrow_mut().insert(
+            url.clone(),
+            Circuit::Open {
+                until: Instant::now(),
+            },
+        );
+
+        let trial = sender.send_body(&url, HeaderValue::from_static("text/plain"), "hi");
+        actix_rt::time::timeout(Duration::from_millis(100), trial)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            sender.circuits.borrow().get(&url),
+            Some(Circuit::Open { .. })
+        ));
+
+        // the next delivery is let through as the new trial
+        let admission = sender.admit(&url).unwrap();
+        assert!(admission.trial);
+        assert!(sender.admit(&url).is_none());
+
+        admission.record(true);
+        assert!(sender.circuits.borrow().get(&url).is_none());
+    }
+}
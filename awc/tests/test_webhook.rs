@@ -0,0 +1,189 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use actix_http::StatusCode;
+use actix_web::{
+    http::header::HeaderName,
+    web,
+    webhook::{Webhook, WebhookConfig},
+    App, HttpResponse,
+};
+use awc::{
+    webhook::{DeliveryError, DeliveryObserver, RetryPolicy, WebhookSender},
+    Client,
+};
+
+/// Server that responds with the given statuses in order, then with `200 OK`.
+fn flaky_server(statuses: &'static [u16]) -> (actix_test::TestServer, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&hits);
+
+    let srv = actix_test::start(move || {
+        let hits = Arc::clone(&counter);
+
+        App::new().default_service(web::to(move || {
+            let hit = hits.fetch_add(1, Ordering::SeqCst);
+            let status = statuses.get(hit).copied().unwrap_or(200);
+            async move { HttpResponse::new(StatusCode::from_u16(status).unwrap()) }
+        }))
+    });
+
+    (srv, hits)
+}
+
+fn fast_retries(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy::default()
+        .max_attempts(max_attempts)
+        .initial_backoff(Duration::from_millis(1))
+}
+
+#[derive(Default, Clone)]
+struct Outcomes(Rc<RefCell<Vec<String>>>);
+
+impl DeliveryObserver for Outcomes {
+    fn retrying(&self, _url: &str, attempt: u32, err: &DeliveryError, _delay: Duration) {
+        self.0
+            .borrow_mut()
+            .push(format!("retrying {attempt}: {err}"));
+    }
+
+    fn delivered(&self, _url: &str, attempts: u32, status: StatusCode) {
+        self.0
+            .borrow_mut()
+            .push(format!("delivered {attempts}: {}", status.as_u16()));
+    }
+
+    fn failed(&self, _url: &str, attempts: u32, err: &DeliveryError) {
+        self.0
+            .borrow_mut()
+            .push(format!("failed {attempts}: {err}"));
+    }
+}
+
+#[actix_rt::test]
+async fn verified_by_receiver() {
+    let srv = actix_test::start(|| {
+        App::new()
+            .app_data(
+                WebhookConfig::hmac_sha256("secret")
+                    .timestamp_header(HeaderName::from_static("x-timestamp")),
+            )
+            .route(
+                "/",
+                web::post().to(|hook: Webhook| async move {
+                    assert_eq!(hook.body(), r#"{"event":"ping"}"#);
+                    HttpResponse::NoContent().finish()
+                }),
+            )
+    });
+
+    let sender = WebhookSender::new(Client::default(), "secret")
+        .timestamp_header(HeaderName::from_static("x-timestamp"));
+    let status = sender
+        .send_json(&srv.url("/"), &serde_json::json!({ "event": "ping" }))
+        .await
+        .unwrap();
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let sender = WebhookSender::new(Client::default(), "wrong secret");
+    let err = sender
+        .send_json(&srv.url("/"), &serde_json::json!({ "event": "ping" }))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        DeliveryError::Status(StatusCode::UNAUTHORIZED)
+    ));
+}
+
+#[actix_rt::test]
+async fn retries_with_backoff() {
+    let (srv, hits) = flaky_server(&[503, 429]);
+    let outcomes = Outcomes::default();
+
+    let sender = WebhookSender::new(Client::default(), "secret")
+        .retry_policy(fast_retries(3))
+        .observer(outcomes.clone());
+
+    let status = sender
+        .send_body(&srv.url("/"), "text/plain".try_into().unwrap(), "hi")
+        .await
+        .unwrap();
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+    assert_eq!(
+        *outcomes.0.borrow(),
+        [
+            "retrying 1: Endpoint responded with status 503 Service Unavailable",
+            "retrying 2: Endpoint responded with status 429 Too Many Requests",
+            "delivered 3: 200",
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn client_errors_are_final() {
+    let (srv, hits) = flaky_server(&[400]);
+    let outcomes = Outcomes::default();
+
+    let sender = WebhookSender::new(Client::default(), "secret")
+        .retry_policy(fast_retries(3))
+        .observer(outcomes.clone());
+
+    let err = sender.send_json(&srv.url("/"), "hi").await.unwrap_err();
+    assert!(matches!(
+        err,
+        DeliveryError::Status(StatusCode::BAD_REQUEST)
+    ));
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        *outcomes.0.borrow(),
+        ["failed 1: Endpoint responded with status 400 Bad Request"]
+    );
+}
+
+#[actix_rt::test]
+async fn circuit_breaker() {
+    let (srv, hits) = flaky_server(&[500, 500, 500, 500, 500]);
+
+    let sender = WebhookSender::new(Client::default(), "secret")
+        .retry_policy(fast_retries(3))
+        .circuit_breaker(2, Duration::from_millis(50));
+
+    // circuit opens after the second failed attempt
+    let err = sender.send_json(&srv.url("/"), "hi").await.unwrap_err();
+    assert!(matches!(err, DeliveryError::CircuitOpen));
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    let err = sender.send_json(&srv.url("/"), "hi").await.unwrap_err();
+    assert!(matches!(err, DeliveryError::CircuitOpen));
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    // other endpoints have their own circuit
+    let err = sender
+        .send_json(&srv.url("/other"), "hi")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, DeliveryError::CircuitOpen));
+    assert_eq!(hits.load(Ordering::SeqCst), 4);
+
+    // trial delivery after the reset timeout fails and opens the circuit again
+    actix_rt::time::sleep(Duration::from_millis(60)).await;
+    let err = sender.send_json(&srv.url("/"), "hi").await.unwrap_err();
+    assert!(matches!(err, DeliveryError::CircuitOpen));
+    assert_eq!(hits.load(Ordering::SeqCst), 5);
+
+    // successful trial delivery closes the circuit
+    actix_rt::time::sleep(Duration::from_millis(60)).await;
+    let status = sender.send_json(&srv.url("/"), "hi").await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+    let status = sender.send_json(&srv.url("/"), "hi").await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+}